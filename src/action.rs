@@ -0,0 +1,72 @@
+// everything hydra can be asked to do by name: what the palette lists, hydra.macro steps run and
+// the keymap binds keys to. App::dispatch carries them out
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    GoStatus,
+    GoLog,
+    GoBranches,
+    GoStashes,
+    GoRemote,
+    StageAll,
+    Commit,
+    Stash,
+    NewBranch,
+    Gitflow,
+    SetUpstream,
+    Fetch,
+    ListRemoteBranches,
+    Push,
+    PushBranches,
+    Pull,
+    SyncFork,
+    AuthStatus,
+    CreateBundle,
+    FetchBundle,
+    ToggleGraph,
+    ToggleIgnoreEol,
+    TogglePerfHud,
+    ToggleOffline,
+    SearchChanges,
+    JumpToCommit,
+    JumpToHead,
+    CheckoutCommit,
+    MarkCommit,
+    RestoreFile,
+    OpenIssue,
+    CopyIssueLinks,
+    ScopeStatus,
+    DiffBase,
+    Packages,
+    EditAttributes,
+    StageLines,
+    StageModeOnly,
+    StageContentOnly,
+    ExportArchive,
+    BrowseFiles,
+    OpenOnWeb,
+    SwitchRepo,
+    Notifications,
+    ErrorLog,
+    LogViewer,
+    RecordMacro,
+    ReplayMacro,
+    // one of the hydra.macro ones, by position
+    Macro(usize),
+    // hydra.command / hydra.panelCommand, by position
+    Custom(usize),
+    CommandOutput,
+    Hooks,
+    Stats,
+    LargeObjects,
+    Maintenance,
+    Clean,
+    Health,
+    Help,
+    ReleaseNotes,
+    NewRelease,
+    RebaseOnto,
+    Stacks,
+    Refresh,
+    Quit,
+}
@@ -0,0 +1,74 @@
+use anyhow::{Context, Result};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+
+// --announce: what changed on screen, said in words, for screen readers that can't make sense
+// of a redrawn tui. `--announce title` puts the latest one in the terminal title (OSC 2), a path
+// gets one line per change appended, a fifo works too:
+//   mkfifo /tmp/hydra && (cat /tmp/hydra | espeak &) && git-hydra --announce /tmp/hydra
+// only what changed since the last time is said: the view, what's selected in it, HEAD, errors and
+// every notification
+
+enum Sink {
+    Title,
+    File(File),
+}
+
+pub struct Announcer {
+    sink: Sink,
+    // the last thing said about each fact, by name
+    said: Vec<(&'static str, String)>,
+}
+
+impl Announcer {
+    pub fn open(target: &str) -> Result<Self> {
+        let sink = match target {
+            "title" => Sink::Title,
+            path => Sink::File(OpenOptions::new().create(true).append(true).open(path).with_context(|| format!("can't open {}", path))?),
+        };
+        Ok(Self { sink, said: Vec::new() })
+    }
+
+    // facts are said when they differ from last time, messages always
+    pub fn update(&mut self, facts: Vec<(&'static str, String)>, messages: Vec<String>) {
+        let mut lines = Vec::new();
+        for (name, text) in facts {
+            match self.said.iter_mut().find(|(n, _)| *n == name) {
+                Some((_, said)) if *said == text => continue,
+                Some((_, said)) => *said = text.clone(),
+                None => self.said.push((name, text.clone())),
+            }
+            if !text.is_empty() {
+                lines.push(text);
+            }
+        }
+        lines.extend(messages);
+        if lines.is_empty() {
+            return;
+        }
+        let result = match &mut self.sink {
+            // control characters would end the escape sequence early
+            Sink::Title => {
+                let title: String = lines.join(". ").chars().filter(|c| !c.is_control()).collect();
+                let mut stdout = std::io::stdout();
+                write!(stdout, "\x1b]2;{}\x07", title).and_then(|_| stdout.flush())
+            }
+            Sink::File(file) => lines.iter().try_for_each(|line| writeln!(file, "{}", line)).and_then(|_| file.flush()),
+        };
+        if let Err(e) = result {
+            log::warn!("can't announce: {}", e);
+        }
+    }
+}
+
+// CommitDialog -> "commit dialog", KeepOurs -> "keep ours"
+pub fn mode_name(mode: &impl std::fmt::Debug) -> String {
+    let mut words = String::new();
+    for c in format!("{:?}", mode).chars() {
+        if c.is_uppercase() && !words.is_empty() {
+            words.push(' ');
+        }
+        words.extend(c.to_lowercase());
+    }
+    words
+}
@@ -1,932 +1,5715 @@
-use anyhow::Result;
-use crossterm::{
-    event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode},
-    execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
-};
-
-use ratatui::{
-    backend::{Backend, CrosstermBackend},
-    Terminal,
-};
-use std::io;
-use std::path::PathBuf;
-use std::time::Duration;
-
-use std::collections::HashMap;
-use crate::git::*;
-use crate::ui;
-
-#[derive(Debug, PartialEq)]
-pub enum AppMode {
-    Status,
-    Log,
-    Branches,
-    CommitDialog,
-    CreateBranchDialog,
-    StashDialog,
-    StashList,
-    RemoteOperations,
-    MergeConflict,
-    ResetDialog,
-}
-
-pub struct App {
-    pub should_quit: bool,
-    pub mode: AppMode,
-    pub repo: Repository,
-    pub status: Option<RepoStatus>,
-    pub commits: Vec<Commit>,
-    pub branches: Vec<String>,
-    pub selected_commit: usize,
-    pub selected_file: usize,
-
-    pub commit_message: String,
-    pub error_message: Option<String>,
-
-    pub diff_scroll: u16,
-
-    pub branch_name: String,
-    pub stash_message: String,
-    pub stashes: Vec<String>,
-    pub selected_stash: usize,
-
-    // remote stuff
-    pub remotes: Vec<String>,
-    pub selected_remote: usize,
-    pub current_branch: String,
-    pub is_pushing: bool,
-    pub is_pulling: bool,
-    pub push_progress: Option<String>,
-    pub pull_progress: Option<String>,
-
-    pub merge_conflict: Option<MergeConflict>,
-    pub selected_conflict_file: usize,
-    pub selected_conflict_hunk: usize,
-    pub conflict_resolutions: std::collections::HashMap<(usize, usize), MergeResolution>,
-
-    pub reset_mode: ResetMode,
-    pub selected_reset_mode: usize,
-    pub reset_target_commit: Option<String>,
-}
-
-impl App {
-    pub fn new(repo_path: PathBuf) -> Result<Self> {
-        let repo = Repository::open(repo_path)?;
-
-        Ok(Self {
-            should_quit: false,
-            mode: AppMode::Status,
-            repo,
-            status: None,
-            commits: Vec::new(),
-            branches: Vec::new(),
-            selected_commit: 0,
-            selected_file: 0,
-            
-            commit_message: String::new(),
-            error_message: None,
-
-            diff_scroll: 0,
-
-            branch_name: String::new(),
-            stash_message: String::new(),
-            stashes: Vec::new(),
-            selected_stash: 0,
-
-            remotes: Vec::new(),
-            selected_remote: 0,
-            current_branch: String::new(),
-            is_pushing: false,
-            is_pulling: false,
-            push_progress: None,
-            pull_progress: None,
-
-            merge_conflict: None,
-            selected_conflict_file: 0,
-            selected_conflict_hunk: 0,
-            conflict_resolutions: std::collections::HashMap::new(),
-
-            reset_mode: ResetMode::Mixed,
-            selected_reset_mode: 1,
-            reset_target_commit: None,
-        })
-    }
-
-    pub async fn run(&mut self) -> Result<()> {
-        // setup the terminal and init.
-        enable_raw_mode()?;
-        let mut stdout = io::stdout();
-        execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
-        let backend = CrosstermBackend::new(stdout);
-        let mut terminal = Terminal::new(backend)?;
-
-        self.refresh_data()?;
-
-        let result = self.run_app(&mut terminal).await;
-
-        disable_raw_mode()?;
-        execute!(
-            terminal.backend_mut(),
-            LeaveAlternateScreen,
-            DisableMouseCapture
-        )?;
-        terminal.show_cursor()?;
-
-        result
-    }
-
-    async fn run_app<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> Result<()> {
-        loop {
-            terminal.draw(|f| ui::draw(f, self))?;
-
-            if event::poll(Duration::from_millis(100))? {
-                if let Event::Key(key) = event::read()? {
-                    self.handle_key_event(key.code)?;
-                }
-            }
-
-            if self.should_quit {
-                break;
-            }
-        }
-        Ok(())
-    }
-
-    pub fn get_reset_modes() -> Vec<&'static str> {
-        vec!["Soft", "Mixed", "Hard"]
-    }
-    
-    pub fn get_current_reset_mode(&self) -> ResetMode {
-        match self.selected_reset_mode {
-            0 => ResetMode::Soft,
-            1 => ResetMode::Mixed,
-            2 => ResetMode::Hard,
-            _ => ResetMode::Mixed,
-        }
-    }
-
-    pub fn get_reset_description(&self) -> &'static str{
-        match self.selected_reset_mode {
-            0 => "Soft: Keep changes staged",
-            1 => "Mixed: Keep changes unstaged",
-            2 => "Hard: Discard all changes",
-            _ => "Mixed: Keep changes unstaged"
-        }
-    }
-
-    fn perform_reset(&mut self) -> Result <()> {
-        let reset_mode = self.get_current_reset_mode();
-
-        if let Some(target_commit) = &self.reset_target_commit {
-            self.repo.reset_to_commit(&target_commit, reset_mode)?;
-        } else {
-            self.repo.reset_head(reset_mode)?;
-        }
-
-        self.refresh_data()?;
-        Ok(())
-    }
-
-    pub fn get_selected_file_path(&self) -> Option<String> {
-        if let Some(status) = &self.status {
-            let mut all_files = Vec::new();
-            // staged / unstaged / untracked
-
-            for file in &status.staged {
-                all_files.push(file.path.clone());
-            }
-            for file in &status.unstaged {
-                all_files.push(file.path.clone());
-            }
-            for file in &status.untracked {
-                all_files.push(file.path.clone());
-            }
-
-            all_files.get(self.selected_file).cloned()
-        } else {
-            None
-        }
-    }
-
-    fn push_current_branch(&mut self) -> Result<()> {
-        if self.selected_remote < self.remotes.len() {
-            let remote_name = &self.remotes[self.selected_remote];
-            self.is_pushing = true;
-            self.push_progress = Some("pushing to remote...".to_string());
-
-            match self.repo.push_to_remote(remote_name, &self.current_branch) {
-                Ok(message) => {
-                    self.push_progress = Some(message);
-                    self.refresh_data()?;
-                }
-                Err(e) => {
-                    self.error_message = Some(format!("push failed: {}", e));
-                    self.push_progress = None;
-                }
-            }
-            self.is_pushing = false;
-        }
-        Ok(())
-    }
-
-    fn pull_current_branch(&mut self) -> Result<()> {
-        if self.selected_remote < self.remotes.len() {
-            let remote_name = &self.remotes[self.selected_remote];
-            self.is_pulling = true;
-            self.pull_progress = Some("pulling from remote...".to_string());
-
-            match self.repo.pull_from_remote(remote_name, &self.current_branch) {
-                Ok(message) => {
-                    self.pull_progress = Some(message);
-                    self.refresh_data()?;
-                    if self.mode == AppMode::MergeConflict {
-                        self.error_message = Some("merge conflict detected after pull, please resolve.".to_string());
-                    }
-                }
-                Err(e) => {
-                    self.pull_progress = None;
-                    let error_mesg = e.to_string();
-                    if error_mesg.contains("merge conflicts require resolution") {
-                        self.refresh_data()?;
-                        if self.mode == AppMode::MergeConflict {
-                            self.error_message = Some("merge conflict detected after pull, please resolve.".to_string());
-                        } else {
-                            self.error_message = Some(format!("pull failed: {}", e));
-                        }
-                    } else {
-                        self.error_message = Some(format!("pull failed: {}", e));
-                    }
-                }
-            }
-            self.is_pulling = false;
-        }
-        Ok(())
-    }
-
-    fn handle_key_event(&mut self, key: KeyCode) -> Result<()> {
-        // clear error message on any key press -> visual oops
-        self.error_message = None;
-    
-        self.push_progress = None;
-        self.pull_progress = None;
-
-        match self.mode {
-            AppMode::CommitDialog => {
-                match key {
-                    KeyCode::Esc => {
-                        self.mode = AppMode::Status;
-                        self.commit_message.clear();
-                    }
-                    KeyCode::Enter => {
-                        if !self.commit_message.trim().is_empty() {
-                            match self.repo.commit(&self.commit_message) {
-                                Ok(_) => {
-                                    self.mode = AppMode::Status;
-                                    self.commit_message.clear();
-                                    self.refresh_data()?;
-                                }
-                                Err(e) => {
-                                    self.error_message = Some(format!("commit failed: {}", e));
-                                }
-                            }
-                        }
-                    }
-                    KeyCode::Char(c) => {
-                        self.commit_message.push(c);
-                    }
-                    KeyCode::Backspace => {
-                        self.commit_message.pop();
-                    }
-                    _ => {}
-                }
-                return Ok(());
-            }
-            AppMode::CreateBranchDialog => {
-                match key {
-                    KeyCode::Esc => {
-                        self.mode = AppMode::Branches;
-                        self.branch_name.clear()
-                    }
-                    KeyCode::Enter => {
-                        if !self.branch_name.trim().is_empty() {
-                            match self.repo.create_branch(&self.branch_name) {
-                                Ok(_) => {
-                                    self.mode = AppMode::Branches;
-                                    self.branch_name.clear();
-                                    self.refresh_data()?;
-                                }
-                                Err(e) => {
-                                    self.error_message = Some(format!("failed to create branch: {}", e));
-                                }
-                            }
-                        }
-                    }
-                    KeyCode::Char(c) => {
-                        self.branch_name.push(c);
-                    }
-                    KeyCode::Backspace => {
-                        self.branch_name.pop();
-                    }
-
-                    _ => {}
-                }
-                return Ok(());
-            }
-            AppMode::StashDialog => {
-                match key {
-                    KeyCode::Esc => {
-                        self.mode = AppMode::Status;
-                        self.stash_message.clear();
-                    }
-                    KeyCode::Enter => {
-                        let msg = if self.stash_message.trim().is_empty(){
-                            None
-                        } else {
-                            Some(self.stash_message.as_str())
-                        };
-
-                        match self.repo.stash_save(msg){
-                            Ok(_) => {
-                                self.mode = AppMode::Status;
-                                self.stash_message.clear();
-                                self.refresh_data()?;
-                            }
-                            Err(e) => {
-                                self.error_message = Some(format!("failed to stash: {}", e));
-                            }
-                        }
-                    }
-                    KeyCode::Char(c) => {
-                        self.stash_message.push(c);
-                    }
-                    KeyCode::Backspace => {
-                        self.stash_message.pop();
-                    }
-                    _ => {}
-                }
-                return Ok(());
-            }
-            AppMode::StashList => {
-                match key {
-                    //idiot forgot to add key handling
-                    KeyCode::Char('q') => self.should_quit = true,
-                    KeyCode::Char('1') => {
-                        self.mode = AppMode::Status;
-                        self.refresh_data()?;
-                    }
-                    KeyCode::Char('2') => {
-                        self.mode = AppMode::Log;
-                        self.refresh_data()?;
-                    }
-                    KeyCode::Char('3') => {
-                        self.mode = AppMode::Branches;
-                        self.refresh_data()?;
-                    }
-                    KeyCode::Esc => {
-                        self.mode = AppMode::Status;
-                        return Ok(());
-                    }
-                    KeyCode::Up => {
-                        if self.selected_stash > 0 {
-                            self.selected_stash -= 1;
-                        }
-                        return Ok(());
-                    }
-                    KeyCode::Down => {
-                        if self.selected_stash + 1 < self.stashes.len() {
-                            self.selected_stash += 1;
-                        }
-                        return Ok(());
-                    }
-                    KeyCode::Enter => {
-                        match self.repo.stash_pop() {
-                            Ok(_) => {
-                                self.mode = AppMode::Status;
-                                self.refresh_data()?;
-                            }
-                            Err(e) => {
-                                self.error_message = Some(format!("failed to pop stash: {}", e));
-                            }
-                        }
-                        return Ok(());
-                    }
-                    KeyCode::Delete => {
-                        if self.selected_stash < self.stashes.len() {
-                            match self.repo.stash_drop(self.selected_stash) {
-                                Ok(_) => {
-                                    self.refresh_data()?;
-                                    if self.selected_stash >= self.stashes.len() && !self.stashes.is_empty() {
-                                        self.selected_stash = self.stashes.len() -1;
-                                    }
-                                }
-                                Err(e) => {
-                                    self.error_message = Some(format!("failed to drop stash: {}", e));
-                                }
-                            }
-                        }
-                        return Ok(());
-                    }
-                    _ => {}
-                }
-                return Ok(());
-            }
-            AppMode::RemoteOperations => {
-                match key {
-                    KeyCode::Up => {
-                        if self.selected_remote > 0{
-                            self.selected_remote -= 1;
-                        }
-                        return Ok(());
-                    }
-                    KeyCode::Down => {
-                        if self.selected_remote + 1 < self.remotes.len() {
-                            self.selected_remote += 1;
-                        }
-                        return Ok(())
-                    }
-                    _ => {}
-                }
-            }
-            AppMode::MergeConflict => {
-                match key {
-                    KeyCode::Up => {
-                        if self.selected_conflict_hunk > 0 {
-                            self.selected_conflict_hunk -= 1;
-                        } else if self.selected_conflict_file > 0 {
-                            self.selected_conflict_file -= 1;
-                            if let Some(file) = self.get_current_conflict_file() {
-                                self.selected_conflict_hunk = file.conflicts.len().saturating_sub(1);
-                            }
-                        }
-                        return Ok(());
-                    }
-                    KeyCode::Down => {
-                        if let Some(file) = self.get_current_conflict_file() {
-                            if self.selected_conflict_hunk + 1 < file.conflicts.len() {
-                                self.selected_conflict_hunk += 1;
-                            } else if let Some(merge_conflict) = &self.merge_conflict {
-                                if self.selected_conflict_file + 1 < merge_conflict.files.len() {
-                                    self.selected_conflict_file += 1;
-                                    self.selected_conflict_hunk = 0;
-                                }
-
-                            }
-                        }
-                        return Ok(());
-                    }
-                    KeyCode::Right => {
-                        if let Some(merge_conflict) = &self.merge_conflict {
-                            if self.selected_conflict_file + 1 < merge_conflict.files.len() {
-                                self.selected_conflict_file += 1;
-                                self.selected_conflict_hunk = 0;
-                            }
-                        }
-                        return Ok(());
-                    }
-
-                    // resolution choices
-                    KeyCode::Char('o') => {
-                        self.set_current_resolution(MergeResolution::KeepOurs);
-                        return Ok(());
-                    }
-                    KeyCode::Char('t') => {
-                        self.set_current_resolution(MergeResolution::KeepTheirs);
-                        return Ok(());
-                    }
-                    KeyCode::Char('b') => {
-                        self.set_current_resolution(MergeResolution::KeepBoth);
-                        return Ok(());
-                    }
-                    KeyCode::Char('e') => {
-                        // TODO: edit custom res.
-                        // self.set_current_resolution(MergeResolution::Edit);
-                        return Ok(());
-                    }
-
-                    
-                    KeyCode::Char('c') => {
-                        if self.can_complete_merge() {
-                            match self.complete_merge("Merge commit") {
-                                Ok(_) =>{
-                                    self.refresh_data()?;
-                                }
-                                Err(e) => {
-                                    self.error_message = Some(format!("failed to complete merge: {}", e));
-                                }
-                            }
-                        } else {
-                            self.error_message = Some("cannot complete merge, some conflicts are unresolved.".to_string());
-                        }
-                        return Ok(());
-                    }
-
-                    KeyCode::Char('a') => {
-                        match self.abort_merge() {
-                            Ok(_) => {
-                                self.refresh_data()?;
-                            }
-                            Err(e) => {
-                                self.error_message = Some(format!("failed to abort merge: {}", e));
-                            }
-                        }
-                    }
-
-                    KeyCode::F(5) => {
-                        self.refresh_data()?;
-                        return Ok(());
-                    }
-
-                    KeyCode::Esc => {
-                        if self.merge_conflict.is_some() {
-                            self.mode = AppMode::Status;
-                        } else {
-                            self.error_message = Some("cannot exit merge mode while conflicts exist. use 'a' to abort or 'c' to complete.".to_string());
-                        }
-                        return Ok(());
-                    }
-
-                    _ => {}
-                }
-            }
-            AppMode::ResetDialog => {
-                match key {
-                    KeyCode::Esc => {
-                        self.mode = AppMode::Status;
-                        self.reset_target_commit = None;
-                        self.selected_reset_mode = 1;
-                    }
-                    KeyCode::Up => {
-                        if self.selected_reset_mode > 0 {
-                            self.selected_reset_mode -= 1;
-                        }
-                    }
-                    KeyCode::Down => {
-                        if self.selected_reset_mode + 1 < Self::get_reset_modes().len() {
-                            self.selected_reset_mode += 1;
-                        }
-                    }
-                    KeyCode::Enter => {
-                        if self.get_current_reset_mode() == ResetMode::Hard {
-                            match self.perform_reset() {
-                                Ok(_) => {
-                                    self.mode = AppMode::Status;
-                                    self.reset_target_commit = None;
-                                    self.selected_reset_mode = 1;
-                                }
-                                Err(e) => {
-                                    self.error_message = Some(format!("Reset failed: {}", e));
-                                }
-                            }
-                        } else {
-                            match self.perform_reset() {
-                                Ok(_) => {
-                                    self.mode = AppMode::Status;
-                                    self.reset_target_commit = None;
-                                    self.selected_reset_mode = 1;
-                                }
-                                Err(e) => {
-                                    self.error_message = Some(format!("Reset failed: {}", e))
-                                }
-                            }
-                        }
-                    }
-                    _ => {}
-                }
-            }
-            _ => {}
-        }
-        match key {
-            KeyCode::Char('q') => self.should_quit = true,
-            KeyCode::Char('1') => {
-                self.mode = AppMode::Status;
-                self.refresh_data()?;
-            }
-            KeyCode::Char('2') => {
-                self.mode = AppMode::Log;
-                self.refresh_data()?;
-            }
-            KeyCode::Char('3') => {
-                self.mode = AppMode::Branches;
-                self.refresh_data()?;
-            }
-            KeyCode::Char('4') => {
-                self.mode = AppMode::StashList;
-                self.refresh_data()?;
-            }
-            KeyCode::Char('5') => {
-                self.mode = AppMode::RemoteOperations;
-                self.refresh_data()?;
-            }
-            KeyCode::Char('r') => {
-                if self.mode == AppMode::Log && !self.commits.is_empty() {
-                    let selected_commit = &self.commits[self.selected_commit];
-                    self.reset_target_commit = Some(selected_commit.id.clone());
-                    self.mode = AppMode::ResetDialog;
-                    self.selected_reset_mode = 1;
-                } else if self.mode == AppMode::Status {
-                    self.reset_target_commit = None;
-                    self.mode = AppMode::ResetDialog;
-                    self.selected_reset_mode = 1;
-                } else {
-                    self.error_message = Some("Reset only available from Status or Log view".to_string());
-                }
-            }
-            KeyCode::Char('p') => {
-                if self.mode == AppMode::RemoteOperations && !self.remotes.is_empty() {
-                    self.push_current_branch()?;
-                }
-            }
-            KeyCode::Char('u') => {
-                if self.mode == AppMode::RemoteOperations && !self.remotes.is_empty() {
-                    self.pull_current_branch()?;
-                }
-            }
-            KeyCode::Char('s') => {
-                self.mode = AppMode::StashDialog;
-                self.stash_message.clear();
-            }
-            KeyCode::Char('n') => {
-                if self.mode == AppMode::Branches {
-                    self.mode = AppMode::CreateBranchDialog;
-                    self.branch_name.clear();
-                }
-            }
-            KeyCode::Char('c') => {
-                // Open dialog if there are staged files
-                if let Some(status) = &self.status {
-                    if !status.staged.is_empty() {
-                        self.mode = AppMode::CommitDialog;
-                        self.commit_message.clear();
-                    } else {
-                        self.error_message = Some("no staged files to commit.".to_string())
-                    }
-                }
-            }
-            
-            KeyCode::Char('m') => {
-                if let Ok(Some(_)) = self.repo.detect_merge_conflicts() {
-                    self.mode = AppMode::MergeConflict;
-                    self.refresh_data()?;
-                } else {
-                    self.error_message = Some("no merge conflicts detected.".to_string());
-                }
-            }
-            KeyCode::Up => {
-                match self.mode {
-                    AppMode::Log => {
-                        if self.selected_commit > 0 {
-                            self.selected_commit -= 1;
-                        }
-                    }
-                    AppMode::Status => {
-                        if self.selected_file > 0 {
-                            self.selected_file -= 1;
-                            self.diff_scroll = 0;
-                        }
-                    }
-                    AppMode::Branches => {
-                        if self.selected_file > 0{
-                            self.selected_file -= 1;
-                        }
-                    }
-                    _ => {}
-                }
-            }
-            KeyCode::Down => {
-                match self.mode {
-                    AppMode::Log => {
-                        if self.selected_commit + 1 < self.commits.len() {
-                            self.selected_commit += 1;
-                        }
-                    }
-                    AppMode::Status => {
-                        if let Some(status) = &self.status {
-                            let total_files = status.staged.len() + status.unstaged.len() + status.untracked.len();
-                            if self.selected_file + 1 < total_files {
-                                self.selected_file += 1;
-                                self.diff_scroll = 0;
-
-                            }
-                        }
-                    }
-                    AppMode::Branches => {
-                        if self.selected_file + 1 < self.branches.len() {
-                            self.selected_file += 1;
-                        }
-                    }
-
-                    _ => {}
-                }
-            }
-            KeyCode::F(5) => {
-                self.refresh_data()?;
-            }
-            KeyCode::Enter => {
-                match self.mode {
-                    AppMode::Status => {
-                        if let Some(status) = &self.status {
-                            let total_files = status.staged.len() + status.unstaged.len() + status.untracked.len();
-                            if self.selected_file < total_files {
-                                if let Some(file_path) = self.get_selected_file_path() {
-                                    if self.selected_file < status.staged.len() {
-                                        if let Err(e) = self.repo.unstage_file(&file_path){
-                                            self.error_message = Some(format!("failed to unstage: {}", e));
-                                        } else {
-                                            self.refresh_data()?;
-                                        }
-                                    } else {
-                                        if let Err(e) = self.repo.stage_file(&file_path){
-                                            self.error_message = Some(format!("failed to stage: {}", e));
-
-                                        } else {
-                                            self.refresh_data()?;
-                                        }
-                                    }
-                                    self.refresh_data()?;
-                                } 
-                            }
-                        }
-                    }
-                    AppMode::Branches => {
-                        if self.selected_file < self.branches.len() {
-                            let branch = &self.branches[self.selected_file];
-                            if !branch.starts_with("origin/"){
-                                match self.repo.checkout_branch(branch){
-                                    Ok(_) => {
-                                        self.refresh_data()?;
-                                    }
-                                    Err(e) => {
-                                        self.error_message = Some(format!("failed to checkout: {}", e))
-                                    }
-                                }
-                            }
-                        } else {
-                            self.error_message = Some("cannot checkout remote branch directly.".to_string());
-                        }
-                    }
-                    _ => {}
-                }
-            }
-            
-            // this is staged by git-hydra!
-            KeyCode::Char(' ') => {
-                if let Some(status) = &self.status{
-                    let total_files = status.staged.len() + status.unstaged.len() + status.untracked.len();
-                    if self.selected_file < total_files {
-                        if let Some(file_path) = self.get_selected_file_path() {
-                            if self.selected_file < status.staged.len() {
-                                if let Err(e) = self.repo.unstage_file(&file_path){
-                                    self.error_message = Some(format!("failed to unstage: {}", e));
-                                } else {
-                                    self.refresh_data()?;
-                                }
-                            } else {
-                                if let Err(e) = self.repo.stage_file(&file_path){
-                                    self.error_message = Some(format!("failed to stage: {}", e));
-
-                                } else {
-                                    self.refresh_data()?;
-                                }
-                            }
-                            self.refresh_data()?;
-                        } 
-                    }
-                }
-            }
-
-            KeyCode::PageUp => {
-                if self.mode == AppMode::Status {
-                    self.diff_scroll = self.diff_scroll.saturating_sub(10);
-                }
-            }
-            KeyCode::PageDown => {
-                if self.mode == AppMode::Status {
-                    self.diff_scroll = self.diff_scroll.saturating_add(10);
-                }
-            }
-            KeyCode::Home => {
-                if self.mode == AppMode::Status {
-                    self.diff_scroll = 0;
-                }
-            }
-            KeyCode::End => {
-                if self.mode == AppMode::Status {
-                    self.diff_scroll += 1000;
-                }
-            }
-            _ => {}
-        }
-        Ok(())
-    }
-
-    fn refresh_data(&mut self) -> Result<()> {
-        match self.repo.detect_merge_conflicts(){
-            Ok(Some(conflict)) => {
-                self.merge_conflict = Some(conflict);
-                if self.mode != AppMode::MergeConflict {
-                    self.mode = AppMode::MergeConflict;
-                    self.selected_conflict_file = 0;
-                    self.selected_conflict_hunk = 0;
-                    self.conflict_resolutions.clear();
-                }
-                return Ok(());
-            }
-            Ok(None) => {
-                if self.mode == AppMode::MergeConflict {
-                    self.merge_conflict = None;
-                    self.conflict_resolutions.clear();
-                    self.mode = AppMode::Status;
-                }
-            }
-            Err(e) => {
-                self.error_message = Some(format!("failed to detect merge conflicts: {}", e));
-            }
-        }
-        match self.mode {
-            AppMode::Status => {
-                self.status = Some(self.repo.status()?);
-            }
-            AppMode::Log => {
-                self.commits = self.repo.get_commits(50)?;
-                if self.selected_commit >= self.commits.len() {
-                    self.selected_commit = self.commits.len().saturating_sub(1);
-                }
-            }
-            AppMode::Branches => {
-                self.branches = self.repo.get_branches()?;
-            }
-            AppMode::StashList => {
-                self.stashes = self.repo.stash_list()?;
-                if self.selected_stash >= self.stashes.len() {
-                    self.selected_stash = self.stashes.len().saturating_sub(1);
-                }
-
-            }
-            AppMode::RemoteOperations => {
-                self.remotes = self.repo.get_remotes()?;
-                self.current_branch = self.repo.get_current_branch()?;
-                if self.selected_remote >= self.remotes.len() {
-                    self.selected_remote = self.remotes.len().saturating_sub(1);
-                }
-            }
-            _ => {}
-        }
-        Ok(())
-    }
-
-    pub fn get_current_conflict_file(&self) -> Option<&ConflictedFile> {
-        self.merge_conflict.as_ref()
-            .and_then(|mc | mc.files.get(self.selected_conflict_file))
-    }
-
-    pub fn get_current_conflict_hunk(&self) -> Option<&ConflictHunk> {
-        self.get_current_conflict_file()
-            .and_then(|file| file.conflicts.get(self.selected_conflict_hunk))
-    }
-
-    pub fn get_current_resolution(&self) -> Option<&MergeResolution> {
-        self.conflict_resolutions.get(&(self.selected_conflict_file, self.selected_conflict_hunk))
-    }
-
-    pub fn set_current_resolution(&mut self, resolution: MergeResolution) {
-        self.conflict_resolutions.insert((self.selected_conflict_file, self.selected_conflict_hunk), resolution);
-    }
-
-    pub fn can_complete_merge(&self) -> bool {
-        if let Some(merge_conflict) = &self.merge_conflict {
-            for (file_idx, file) in merge_conflict.files.iter().enumerate() {
-                for hunk_idx in 0 .. file.conflicts.len() {
-                    if !self.conflict_resolutions.contains_key(&(file_idx, hunk_idx)) {
-                        return false; 
-                    }
-                }
-            }
-            true
-        } else {
-            false
-        }
-    }
-
-    pub fn complete_merge(&mut self, commit_message: &str) -> Result<()> {
-        if let Some(merge_conflict) = &self.merge_conflict {
-            self.repo.resolve_conflicts(&self.conflict_resolutions, merge_conflict)?;
-            self.repo.complete_merge(commit_message)?;
-
-            self.merge_conflict = None;
-            self.conflict_resolutions.clear();
-            self.mode = AppMode::Status;
-            self.refresh_data()?;
-        }
-        Ok(())
-    }
-
-    pub fn abort_merge(&mut self) -> Result<()> {
-        self.repo.abort_merge()?;
-        self.merge_conflict = None;
-        self.conflict_resolutions.clear();
-        self.mode = AppMode::Status;
-        self.refresh_data()?;
-        Ok(())
-    }
-}
+use anyhow::Result;
+use crossterm::{
+    event::{DisableMouseCapture, EnableMouseCapture, KeyCode, KeyEvent, KeyModifiers},
+    execute,
+    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+};
+
+use ratatui::{
+    backend::{Backend, CrosstermBackend},
+    Terminal,
+};
+use std::io;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use crate::auth::AuthStatus;
+use crate::auto_fetch::AutoFetch;
+use crate::avatars::Avatars;
+use crate::branch_names;
+use crate::clipboard;
+use crate::colors::{self, ColorLevel};
+use crate::commands::{self, CustomCommand, RunningCommand};
+use crate::git::*;
+use crate::graph::Graph;
+use crate::input_history::InputHistory;
+use crate::issues::{self, IssueRef, IssueRule};
+use crate::line_counts::LineCountCache;
+use crate::logging;
+use crate::maintenance;
+use crate::notifications::{Level, Notifications};
+use crate::events::{Message, Queue, Watcher};
+use crate::files::{FileBrowser, Preview};
+use crate::help;
+use crate::keymap::Keymap;
+use crate::macros::{self, Macro};
+use crate::replay::{Recorder, Replay};
+use crate::action::Action;
+use crate::announce::{self, Announcer};
+use crate::packages::{self, Package};
+use crate::palette::{self, PaletteCommand};
+use crate::perf::{Op, Perf};
+use crate::protection::Protection;
+use crate::release::{self, Plan};
+use crate::release_notes;
+use crate::secrets;
+use crate::session::{self, SessionState};
+use crate::stats::{self, Contribution};
+use crate::ui;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AppMode {
+    Status,
+    Log,
+    Branches,
+    CommitDialog,
+    CreateBranchDialog,
+    StashDialog,
+    StashList,
+    RemoteOperations,
+    MergeConflict,
+    MergeMessageDialog,
+    ResetDialog,
+    ArchiveDialog,
+    BundleDialog,
+    PickaxeDialog,
+    AttributesDialog,
+    LineStaging,
+    ScopePicker,
+    DiffBasePicker,
+    PackagePicker,
+    JumpDialog,
+    CommandPalette,
+    SwitchRepoDialog,
+    NotificationHistory,
+    ErrorLog,
+    LogViewer,
+    CommandOutput,
+    Hooks,
+    Stats,
+    LargeObjects,
+    Maintenance,
+    Clean,
+    Health,
+    Help,
+    ReleaseNotes,
+    NewRelease,
+    CommitView,
+    Files,
+    AutostashDialog,
+    RestoreDialog,
+    GitflowDialog,
+    UpstreamDialog,
+    SyncForkDialog,
+    RebaseOntoDialog,
+    StackView,
+    PushDialog,
+    PushPreview,
+    CredentialDialog,
+}
+
+// where a list view was left: the selected row, how far its diff / details pane is scrolled, and
+// the first row the list showed last frame (kept by the ui, so a list only scrolls as far as the
+// selection needs instead of jumping)
+#[derive(Debug, Default)]
+pub struct ViewState {
+    pub selected: usize,
+    pub scroll: usize,
+    pub offset: std::cell::Cell<usize>,
+}
+
+#[derive(Debug, Default)]
+pub struct Views {
+    // the status files (staged, unstaged, untracked, ...) and the diff of the selected one
+    pub status: ViewState,
+    // the commits and the details pane
+    pub log: ViewState,
+    pub branches: ViewState,
+    pub stashes: ViewState,
+    pub remotes: ViewState,
+}
+
+// the gitflow dialog's entries, (kind, starting)
+pub const FLOW_ACTIONS: &[(FlowKind, bool)] = &[
+    (FlowKind::Feature, true),
+    (FlowKind::Feature, false),
+    (FlowKind::Release, true),
+    (FlowKind::Release, false),
+    (FlowKind::Hotfix, true),
+    (FlowKind::Hotfix, false),
+];
+
+// what gets run between stashing local changes and putting them back
+#[derive(Debug, Clone, PartialEq)]
+pub enum StashedOperation {
+    Checkout(String),
+    Detach(String),
+    Pull,
+}
+
+impl StashedOperation {
+    pub fn describe(&self) -> String {
+        match self {
+            StashedOperation::Checkout(branch) => format!("checkout {}", branch),
+            StashedOperation::Detach(commit) => format!("checkout {}", &commit[..commit.len().min(8)]),
+            StashedOperation::Pull => "pull".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BundleAction {
+    Create,
+    Fetch,
+}
+
+// what's being typed at the bottom of the release notes
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NotesInput {
+    Range,
+    File,
+}
+
+const LOG_PAGE_SIZE: usize = 50;
+const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d %H:%M %z";
+const LARGE_OBJECT_LIMIT: usize = 100;
+const ERROR_LOG_LIMIT: usize = 50;
+// the screen is drawn after every key and event, and otherwise this often for what changes on
+// its own (toasts running out, relative dates, a command's output): quickly while something
+// runs or for a few seconds after a key, when line counts and the like come in, then slowly
+const ACTIVE_TICK: Duration = Duration::from_millis(100);
+const IDLE_TICK: Duration = Duration::from_secs(1);
+const IDLE_AFTER: Duration = Duration::from_secs(3);
+
+pub struct App {
+    pub should_quit: bool,
+    // `git-hydra commit`: status and the commit dialog only, quits once committed. holds the
+    // -m message the dialog starts with
+    pub quick_commit: Option<String>,
+    // "<id> <subject>" of what the quick commit made, printed on the way out
+    pub committed: Option<String>,
+    pub needs_full_redraw: bool,
+    pub mode: AppMode,
+    pub repo: Repository,
+    pub status: Option<RepoStatus>,
+    pub commits: Vec<Commit>,
+    pub branches: Vec<String>,
+    // the selection and scroll of each list view, kept apart so switching views and back lands
+    // where it was
+    pub views: Views,
+
+    pub commit_message: String,
+    commit_history: InputHistory,
+    branch_history: InputHistory,
+    stash_history: InputHistory,
+    pub error_message: Option<String>,
+    pub notifications: Notifications,
+    // set once the current error_message is in the history, so it's logged once
+    error_logged: bool,
+    pub error_scroll: u16,
+    // the error up is about a stale .git/index.lock that L can remove
+    pub index_locked: bool,
+    pub repo_snapshot: RepoSnapshot,
+    pub pending_push: Option<PushPreview>,
+    pub protection: Protection,
+    // from the hydra.secretScan scan of the pending push
+    pub secret_findings: Vec<secrets::Finding>,
+    pub secrets_overridden: bool,
+    // enter was pressed once on a push dialog that pushes to protected branches
+    pub push_confirmed: bool,
+    // clamped when drawn
+    pub push_preview_scroll: usize,
+    // the remote view's auth panel (a), checked when it's opened
+    pub auth_status: Option<AuthStatus>,
+    // the credential prompt, for a url no helper had a username/token for
+    pub credential_url: String,
+    pub credential_username: String,
+    pub credential_token: String,
+    pub credential_editing_token: bool,
+    // where it ends up once it works
+    pub credential_store_note: String,
+    // core.autocrlf / .gitattributes trouble, for the repository info pane
+    pub eol_warnings: Vec<String>,
+    // whether the file in the diff pane only has line ending changes, worked out once per
+    // file and refresh rather than every frame
+    pub eol_churn: std::cell::RefCell<Option<(String, bool)>>,
+    // the .gitattributes of the file in the diff pane, the same way
+    pub diff_attributes: std::cell::RefCell<Option<(String, Vec<String>)>>,
+    // +N -M per file in the status list, counted in the background
+    pub line_counts: LineCountCache,
+    // D in status: the diff pane compares against a ref, these are the files that differ from it
+    pub base_changes: Vec<StashFileStat>,
+    pub diff_base_input: String,
+    // (ref, branch / remote branch / tag) to pick from
+    pub diff_base_candidates: Vec<(String, &'static str)>,
+    pub selected_diff_base: usize,
+    // last ERROR_LOG_LIMIT errors of the session, newest first
+    pub error_log: std::collections::VecDeque<(chrono::DateTime<chrono::Local>, String)>,
+    pub selected_error: usize,
+    // lines up from the bottom of the log viewer
+    pub log_viewer_scroll: usize,
+
+
+    pub branch_name: String,
+    pub branch_templates: Vec<String>,
+    pub branch_template: Option<usize>,
+    // (placeholder, value) for the active template, branch_name is rendered from these
+    pub branch_fields: Vec<(String, String)>,
+    pub branch_field: usize,
+    pub stash_message: String,
+    // kept between stashes in a session, it's usually the same every time
+    pub stash_options: StashOptions,
+    // 0 is the message, 1.. the toggles
+    pub stash_focus: usize,
+    pub autostash_operation: Option<StashedOperation>,
+    pub detached_head: Option<String>,
+    // set while the repo has no commits, the branch the first one will create
+    pub unborn_branch: Option<String>,
+
+    pub restore_commit: String,
+    pub restore_files: Vec<String>,
+    // filters the list, or names a path the commit didn't touch
+    pub restore_input: String,
+    pub selected_restore_file: usize,
+    pub restore_target: RestoreTarget,
+    pub restore_preview: String,
+    pub restore_scroll: usize,
+
+    pub selected_flow_action: usize,
+    pub flow_name: String,
+    pub stashes: Vec<StashEntry>,
+    pub stash_files: Vec<StashFileStat>,
+    // what popping the selected stash runs into, None when it couldn't be worked out
+    pub stash_preview: Option<StashPreview>,
+
+    // remote stuff
+    pub remotes: Vec<String>,
+    pub current_branch: String,
+    // local branch -> what it tracks, for the branches list
+    pub branch_upstreams: std::collections::HashMap<String, Upstream>,
+    pub upstream_branch: String,
+    pub upstream_input: String,
+    pub selected_upstream: usize,
+    // the current branch against each remote, in the same order as `remotes`
+    pub remote_divergence: Vec<(String, Option<Upstream>)>,
+    pub sync_fork_options: SyncForkOptions,
+    // branches marked with space in the branches view, pushed together
+    pub marked_branches: std::collections::BTreeSet<String>,
+    pub push_refspecs: String,
+    pub push_remote: usize,
+    // ls-remote of one remote, shown under the branches while open
+    pub remote_listing: Option<(String, Vec<ListedBranch>)>,
+    pub listing_focused: bool,
+    pub selected_listed_branch: usize,
+    pub is_pushing: bool,
+    pub is_pulling: bool,
+
+    pub merge_conflict: Option<MergeConflict>,
+    pub selected_conflict_file: usize,
+    pub selected_conflict_hunk: usize,
+    pub conflict_resolutions: std::collections::HashMap<(usize, usize), MergeResolution>,
+    pub merge_message: String,
+    pub pending_stash_drop: Option<git2::Oid>,
+    pub show_conflict_preview: bool,
+    // log in graph order with lanes drawn, the layout is loaded on first use
+    pub show_graph: bool,
+    pub graph: Option<Graph>,
+    pub conflict_preview_scroll: u16,
+
+    pub selected_reset_mode: usize,
+    pub reset_target_commit: Option<String>,
+
+    pub archive_path: String,
+    pub archive_target: String,
+
+    pub bundle_action: BundleAction,
+    pub bundle_path: String,
+    pub bundle_refs: String,
+    pub bundle_editing_refs: bool,
+
+    pub pickaxe_query: String,
+    pub log_filter: Option<String>,
+    // how many commits the log loads, grows a page at a time
+    pub log_limit: usize,
+    pub jump_input: String,
+    // filled in lazily as commits get selected, verifying means running gpg/ssh
+    pub signatures: std::collections::HashMap<String, CommitSignature>,
+    pub ref_map: std::collections::HashMap<String, Vec<RefLabel>>,
+    pub issue_rules: Vec<IssueRule>,
+    // hydra.absoluteDates swaps "2 hours ago" in the lists for hydra.dateFormat (strftime)
+    pub absolute_dates: bool,
+    pub date_format: String,
+    pub avatars: Avatars,
+    // what the terminal can show, full color until run() looks (snapshots stay in full color)
+    pub colors: ColorLevel,
+    // set by the ui each frame: whose picture goes where, drawn after ratatui is done
+    pub avatar_slot: std::cell::Cell<Option<(String, ratatui::layout::Rect)>>,
+    pub auto_fetch: AutoFetch,
+    // keys, timers, the watcher and background work all come through here, see events.rs
+    pub queue: Queue,
+    watcher: Option<Watcher>,
+    // no fetch, push, pull, ls-remote or avatar downloads, for metered or missing connections
+    pub offline: bool,
+    // main view keys -> actions, the defaults and hydra.bind
+    pub keymap: Keymap,
+    pub perf: Perf,
+
+    pub attributes_lines: Vec<String>,
+    pub attributes_input: String,
+    pub selected_attribute: usize,
+
+    // line staging: the hunks of one file, a cursor over header + line rows, and the picked lines
+    pub line_diff: Option<FileDiff>,
+    pub line_diff_staged: bool,
+    pub line_cursor: usize,
+    pub selected_lines: std::collections::HashSet<(usize, usize)>,
+    // d: the lines about to be thrown out of the worktree, waiting for y
+    pub line_discard: Option<std::collections::HashSet<(usize, usize)>>,
+
+    pub palette_query: String,
+    pub selected_palette_item: usize,
+    // where the palette was opened from, actions run there
+    pub palette_return_mode: AppMode,
+    // hydra.macro, read again every time the palette opens
+    pub macros: Vec<Macro>,
+    // keys so far while recording (Q), and where the palette was opened in them
+    pub recording: Option<Vec<KeyCode>>,
+    recording_palette_mark: usize,
+    // --record / --replay, see replay.rs
+    pub recorder: Option<Recorder>,
+    pub replay: Option<Replay>,
+    // --announce, the newest notification it has already said and HEAD in words
+    pub announcer: Option<Announcer>,
+    announced_at: Option<chrono::DateTime<chrono::Local>>,
+    announced_head: Option<(git2::Oid, String)>,
+    recorded_macro: Vec<KeyCode>,
+    // hydra.command / hydra.panelCommand, loaded with the macros
+    pub custom_commands: Vec<CustomCommand>,
+    pub running_command: Option<RunningCommand>,
+    // lines up from the bottom of the output panel
+    pub command_output_scroll: usize,
+    pub hooks: Vec<Hook>,
+    pub selected_hook: usize,
+    // what the last test run printed
+    pub hook_output: Option<String>,
+    // new base, old base, tip
+    pub onto_fields: [String; 3],
+    pub onto_field: usize,
+    // branches and recent commits to pick from: (what goes in the field, what's shown next to it)
+    pub onto_candidates: Vec<(String, String)>,
+    pub selected_onto_candidate: usize,
+    pub stack: Vec<StackedBranch>,
+    pub selected_stacked: usize,
+    pub large_objects: Vec<LargeObject>,
+    pub object_totals: ObjectTotals,
+    pub health: HealthReport,
+    pub selected_health_issue: usize,
+    // the help browser (F1): page, scroll, and the search typed after /
+    pub help_page: usize,
+    pub help_scroll: u16,
+    pub help_query: String,
+    pub help_searching: bool,
+    help_match: usize,
+    // release notes (palette): the range, its markdown, and the range or file name being typed
+    pub notes_range: String,
+    pub notes: String,
+    pub notes_scroll: u16,
+    pub notes_input: Option<NotesInput>,
+    pub notes_buffer: String,
+    // new release (palette): the version it'd tag, and whether the tag gets pushed and where
+    pub release_plan: Option<Plan>,
+    pub release_push: bool,
+    pub release_remote: usize,
+    pub release_scroll: u16,
+    pub selected_large_object: usize,
+    pub selected_maintenance_task: usize,
+    // the clean tool (palette): what git clean would take, what's marked, and the dry run of the
+    // marked ones waiting for a yes
+    pub clean_entries: Vec<CleanEntry>,
+    pub selected_clean: usize,
+    pub clean_marked: std::collections::BTreeSet<String>,
+    pub clean_preview: Option<Vec<String>>,
+    // .git/objects when the menu was opened
+    pub maintenance_size: u64,
+    // .git/objects when a maintenance task started, for the size it reports when done
+    pub maintenance_before: Option<u64>,
+    pub contributions: Vec<Contribution>,
+    pub stats_weeks: usize,
+    // 0 is everyone, then the authors by number of commits
+    pub stats_author: usize,
+    // the full-screen view of the selected commit (enter)
+    pub commit_view: Option<CommitDiff>,
+    // the files view, the tree of a commit
+    pub files: Option<FileBrowser>,
+    pub commit_view_scroll: usize,
+    // how many lines come before the patch, written when drawn so n/p can land on a file
+    pub commit_view_head: std::cell::Cell<usize>,
+    // space in the log marks a commit, enter on another then shows everything between the two
+    pub log_mark: Option<String>,
+    // (older, newer) when the commit view holds such a range instead of one commit
+    pub commit_range: Option<(String, String)>,
+    pub switch_repo_path: String,
+    pub notification_scroll: usize,
+    popup_return_mode: AppMode,
+
+    pub scope_picker_dir: String,
+    pub scope_picker_entries: Vec<String>,
+    pub selected_scope_entry: usize,
+    // workspace packages (Z), the one status and log are limited to. 0 in the picker is all of them
+    pub packages: Vec<Package>,
+    pub active_package: Option<Package>,
+    pub selected_package: usize,
+}
+
+impl App {
+    pub fn new(repo: Repository) -> Result<Self> {
+        let avatars = Avatars::new(repo.config_bool("hydra.avatars"));
+        let auto_fetch = AutoFetch::from_config(&repo);
+        let offline = repo.config_bool("hydra.offline");
+        repo.set_offline(offline);
+        let (keymap, keymap_errors) = Keymap::from_config(&repo);
+
+        let mut app = Self {
+            should_quit: false,
+            quick_commit: None,
+            committed: None,
+            needs_full_redraw: false,
+            mode: AppMode::Status,
+            repo,
+            status: None,
+            commits: Vec::new(),
+            branches: Vec::new(),
+            
+            commit_message: String::new(),
+            commit_history: InputHistory::new(),
+            branch_history: InputHistory::new(),
+            stash_history: InputHistory::new(),
+            error_message: None,
+            notifications: Notifications::new(),
+            error_logged: false,
+            error_scroll: 0,
+            index_locked: false,
+            repo_snapshot: RepoSnapshot::default(),
+            pending_push: None,
+            protection: Protection::default(),
+            secret_findings: Vec::new(),
+            secrets_overridden: false,
+            push_confirmed: false,
+            push_preview_scroll: 0,
+            auth_status: None,
+            credential_url: String::new(),
+            credential_username: String::new(),
+            credential_token: String::new(),
+            credential_editing_token: false,
+            credential_store_note: String::new(),
+            eol_warnings: Vec::new(),
+            eol_churn: std::cell::RefCell::new(None),
+            diff_attributes: std::cell::RefCell::new(None),
+            line_counts: LineCountCache::default(),
+            base_changes: Vec::new(),
+            diff_base_input: String::new(),
+            diff_base_candidates: Vec::new(),
+            selected_diff_base: 0,
+            error_log: std::collections::VecDeque::new(),
+            selected_error: 0,
+            log_viewer_scroll: 0,
+
+
+            branch_name: String::new(),
+            branch_templates: Vec::new(),
+            branch_template: None,
+            branch_fields: Vec::new(),
+            branch_field: 0,
+            stash_message: String::new(),
+            stash_options: StashOptions::default(),
+            stash_focus: 0,
+            autostash_operation: None,
+            detached_head: None,
+            unborn_branch: None,
+
+            restore_commit: String::new(),
+            restore_files: Vec::new(),
+            restore_input: String::new(),
+            selected_restore_file: 0,
+            restore_target: RestoreTarget::Worktree,
+            restore_preview: String::new(),
+            restore_scroll: 0,
+
+            selected_flow_action: 0,
+            flow_name: String::new(),
+            stashes: Vec::new(),
+            stash_files: Vec::new(),
+            stash_preview: None,
+
+            remotes: Vec::new(),
+            current_branch: String::new(),
+            is_pushing: false,
+            is_pulling: false,
+
+            merge_conflict: None,
+            selected_conflict_file: 0,
+            selected_conflict_hunk: 0,
+            conflict_resolutions: std::collections::HashMap::new(),
+            merge_message: String::new(),
+            pending_stash_drop: None,
+            show_conflict_preview: false,
+            show_graph: false,
+            graph: None,
+            conflict_preview_scroll: 0,
+
+            selected_reset_mode: 1,
+            reset_target_commit: None,
+
+            archive_path: String::new(),
+            archive_target: String::new(),
+
+            bundle_action: BundleAction::Create,
+            bundle_path: String::new(),
+            bundle_refs: String::new(),
+            bundle_editing_refs: false,
+
+            pickaxe_query: String::new(),
+            log_filter: None,
+            log_limit: LOG_PAGE_SIZE,
+            jump_input: String::new(),
+            signatures: std::collections::HashMap::new(),
+            ref_map: std::collections::HashMap::new(),
+            branch_upstreams: std::collections::HashMap::new(),
+            upstream_branch: String::new(),
+            upstream_input: String::new(),
+            selected_upstream: 0,
+            remote_divergence: Vec::new(),
+            sync_fork_options: SyncForkOptions { rebase: false, push: true },
+            marked_branches: std::collections::BTreeSet::new(),
+            push_refspecs: String::new(),
+            push_remote: 0,
+            remote_listing: None,
+            listing_focused: false,
+            selected_listed_branch: 0,
+            issue_rules: Vec::new(),
+            absolute_dates: false,
+            date_format: DEFAULT_DATE_FORMAT.to_string(),
+            avatars,
+            colors: ColorLevel::TrueColor,
+            views: Views::default(),
+            avatar_slot: std::cell::Cell::new(None),
+            auto_fetch,
+            queue: Queue::default(),
+            watcher: None,
+            offline,
+            keymap,
+            perf: Perf::default(),
+
+            attributes_lines: Vec::new(),
+            attributes_input: String::new(),
+            selected_attribute: 0,
+
+            line_diff: None,
+            line_diff_staged: false,
+            line_cursor: 0,
+            selected_lines: std::collections::HashSet::new(),
+            line_discard: None,
+
+            palette_query: String::new(),
+            selected_palette_item: 0,
+            palette_return_mode: AppMode::Status,
+            macros: Vec::new(),
+            recording: None,
+            recording_palette_mark: 0,
+            recorder: None,
+            replay: None,
+            announcer: None,
+            announced_at: None,
+            announced_head: None,
+            recorded_macro: Vec::new(),
+            custom_commands: Vec::new(),
+            running_command: None,
+            command_output_scroll: 0,
+            hooks: Vec::new(),
+            selected_hook: 0,
+            hook_output: None,
+            onto_fields: Default::default(),
+            onto_field: 0,
+            onto_candidates: Vec::new(),
+            selected_onto_candidate: 0,
+            stack: Vec::new(),
+            selected_stacked: 0,
+            large_objects: Vec::new(),
+            object_totals: ObjectTotals::default(),
+            selected_large_object: 0,
+            health: HealthReport::default(),
+            selected_health_issue: 0,
+            help_page: 0,
+            help_scroll: 0,
+            help_query: String::new(),
+            help_searching: false,
+            help_match: 0,
+            notes_range: String::new(),
+            notes: String::new(),
+            notes_scroll: 0,
+            notes_input: None,
+            notes_buffer: String::new(),
+            release_plan: None,
+            release_push: false,
+            release_remote: 0,
+            release_scroll: 0,
+            selected_maintenance_task: 0,
+            clean_entries: Vec::new(),
+            selected_clean: 0,
+            clean_marked: Default::default(),
+            clean_preview: None,
+            maintenance_size: 0,
+            maintenance_before: None,
+            contributions: Vec::new(),
+            stats_weeks: stats::DEFAULT_WEEKS,
+            stats_author: 0,
+            commit_view: None,
+            files: None,
+            commit_view_scroll: 0,
+            commit_view_head: std::cell::Cell::new(0),
+            log_mark: None,
+            commit_range: None,
+            switch_repo_path: String::new(),
+            notification_scroll: 0,
+            popup_return_mode: AppMode::Status,
+
+            scope_picker_dir: String::new(),
+            scope_picker_entries: Vec::new(),
+            selected_scope_entry: 0,
+            packages: Vec::new(),
+            active_package: None,
+            selected_package: 0,
+        };
+        app.restore_session();
+        app.repo_snapshot = app.repo.snapshot(&RepoSnapshot::default());
+        if !keymap_errors.is_empty() {
+            app.error_message = Some(keymap_errors.join("\n"));
+        }
+        Ok(app)
+    }
+
+    pub async fn run(&mut self) -> Result<()> {
+        // setup the terminal and init.
+        enable_raw_mode()?;
+        let mut stdout = io::stdout();
+        execute!(stdout, EnterAlternateScreen, EnableMouseCapture)?;
+        let backend = CrosstermBackend::new(stdout);
+        let mut terminal = Terminal::new(backend)?;
+        self.colors = colors::detect(self.repo.config_string("hydra.colors").as_deref(), |name| std::env::var(name).ok());
+        log::info!("terminal colors: {:?}", self.colors);
+
+        self.refresh_data()?;
+
+        let result = self.run_app(&mut terminal).await;
+        let _ = self.avatars.clear(&mut io::stdout());
+
+        disable_raw_mode()?;
+        execute!(
+            terminal.backend_mut(),
+            LeaveAlternateScreen,
+            DisableMouseCapture
+        )?;
+        terminal.show_cursor()?;
+
+        // a quick commit leaves the session of the full app alone
+        if self.quick_commit.is_none() && let Err(e) = self.save_session() {
+            eprintln!("failed to save session state: {:#}", e);
+        }
+
+        result
+    }
+
+    pub fn start_quick_commit(&mut self, message: Option<&str>) {
+        self.quick_commit = Some(message.unwrap_or("").to_string());
+        self.mode = AppMode::Status;
+        self.views.status.selected = 0;
+        self.views.status.scroll = 0;
+    }
+
+    fn session_key(&self) -> PathBuf {
+        self.repo.workdir().unwrap_or(self.repo.git_dir()).to_path_buf()
+    }
+
+    fn restore_session(&mut self) {
+        let state = session::load(&self.session_key());
+
+        self.mode = match state.mode.as_deref() {
+            Some("Log") => AppMode::Log,
+            Some("Branches") => AppMode::Branches,
+            Some("StashList") => AppMode::StashList,
+            Some("RemoteOperations") => AppMode::RemoteOperations,
+            // a bare repo has no status to show, the log is the next best start
+            _ if self.repo.is_bare() => AppMode::Log,
+            _ => AppMode::Status,
+        };
+        self.views.status.selected = state.selected_file;
+        self.views.log.selected = state.selected_commit;
+        self.views.remotes.selected = state.selected_remote;
+        self.views.stashes.selected = state.selected_stash;
+        self.views.status.scroll = state.diff_scroll;
+        self.log_limit = state.log_limit.max(LOG_PAGE_SIZE);
+        self.log_filter = state.log_filter;
+        self.show_conflict_preview = state.show_conflict_preview;
+        self.show_graph = state.show_graph;
+        self.graph = None;
+        // the directory may be gone since last time, then we just show everything
+        if self.repo.set_status_scope(state.status_scope.as_deref()).is_err() {
+            let _ = self.repo.set_status_scope(None);
+        }
+    }
+
+    fn save_session(&self) -> Result<()> {
+        // dialogs and conflict mode don't survive a restart, the view under them does
+        let mode = match self.overlay_return_mode() {
+            AppMode::Log | AppMode::CommitView | AppMode::PickaxeDialog | AppMode::JumpDialog | AppMode::RestoreDialog => "Log",
+            AppMode::Branches | AppMode::CreateBranchDialog | AppMode::GitflowDialog | AppMode::UpstreamDialog | AppMode::PushDialog => "Branches",
+            AppMode::StashList => "StashList",
+            AppMode::RemoteOperations | AppMode::BundleDialog | AppMode::SyncForkDialog | AppMode::PushPreview => "RemoteOperations",
+            _ => "Status",
+        };
+
+        let state = SessionState {
+            mode: Some(mode.to_string()),
+            selected_file: self.views.status.selected,
+            selected_commit: self.views.log.selected,
+            selected_remote: self.views.remotes.selected,
+            selected_stash: self.views.stashes.selected,
+            diff_scroll: self.views.status.scroll,
+            log_limit: self.log_limit,
+            log_filter: self.log_filter.clone(),
+            status_scope: self.repo.status_scope().map(|s| s.to_string()),
+            show_conflict_preview: self.show_conflict_preview,
+            show_graph: self.show_graph,
+        };
+        session::save(&self.session_key(), &state)
+    }
+
+    async fn run_app<B: Backend>(&mut self, terminal: &mut Terminal<B>) -> Result<()> {
+        self.watcher = Some(Watcher::start(&self.repo, self.queue.sender()));
+        let mut dirty = true;
+        let mut last_draw = Instant::now();
+        let mut last_tick = Instant::now();
+        let mut last_input = Instant::now();
+        let mut input_time = Duration::ZERO;
+        loop {
+            if self.needs_full_redraw {
+                // an external program had the screen, throw away what ratatui thinks is there
+                terminal.clear()?;
+                self.avatars.invalidate();
+                self.needs_full_redraw = false;
+                dirty = true;
+            }
+            let tick = self.redraw_tick(last_input.elapsed());
+            if dirty || last_draw.elapsed() >= tick {
+                self.avatar_slot.set(None);
+                let started = Instant::now();
+                terminal.draw(|f| ui::draw(f, self))?;
+                self.avatars.render(&mut io::stdout(), self.avatar_slot.take())?;
+                self.perf.frame(started.elapsed(), input_time);
+                last_draw = Instant::now();
+                dirty = false;
+            }
+
+            let wait = tick.saturating_sub(last_draw.elapsed());
+            let wait = self.replay.as_ref().and_then(|r| r.wait()).map_or(wait, |w| w.min(wait));
+            // replayed keys go in like typed ones
+            let message = match self.replay.as_mut().and_then(|r| r.due()) {
+                Some(key) => Some(Message::Key(key)),
+                None => self.queue.next(wait)?,
+            };
+            if let Some(message) = message {
+                let key = matches!(message, Message::Key(_));
+                // a resize or focus change counts as the user being there too
+                if key || matches!(message, Message::Terminal) {
+                    last_input = Instant::now();
+                }
+                let started = Instant::now();
+                dirty |= self.update(message)?;
+                if key {
+                    input_time = started.elapsed();
+                }
+                if self.replay.as_ref().is_some_and(|r| r.is_done()) {
+                    self.replay = None;
+                    self.notify("replay finished");
+                }
+            }
+            if last_tick.elapsed() >= ACTIVE_TICK {
+                dirty |= self.update(Message::Tick)?;
+                last_tick = Instant::now();
+            }
+
+            if self.should_quit {
+                break;
+            }
+        }
+        Ok(())
+    }
+
+    // every change to the app goes through here, one message at a time, the run loop only draws.
+    // true when the screen should be drawn again straight away
+    pub fn update(&mut self, message: Message) -> Result<bool> {
+        let redraw = match message {
+            Message::Key(key) => {
+                self.handle_input(key);
+                true
+            }
+            Message::Terminal => true,
+            Message::Tick => self.tick()?,
+            Message::Fetched(errors) => {
+                self.auto_fetch.finish(errors);
+                // new remote-tracking refs, ahead / behind need counting again
+                self.refresh_data()?;
+                true
+            }
+            Message::RepoChanged => self.repo_changed()?,
+        };
+        match &self.error_message {
+            Some(error) if !self.error_logged => {
+                log::error!("{}", error);
+                self.notifications.push(Level::Error, error.clone());
+                self.error_log.push_front((chrono::Local::now(), error.clone()));
+                self.error_log.truncate(ERROR_LOG_LIMIT);
+                self.error_logged = true;
+                // libgit2's "the index is locked" and git's "Unable to create '.../index.lock'"
+                if (error.contains("index is locked") || error.contains("index.lock")) && self.repo.index_lock().exists() {
+                    self.explain_index_lock();
+                }
+            }
+            Some(_) => {}
+            None => self.error_logged = false,
+        }
+        self.announce();
+        Ok(redraw)
+    }
+
+    // what's polled rather than sent: notifications timing out, auto-fetch coming due, the output
+    // of a running command
+    fn tick(&mut self) -> Result<bool> {
+        self.notifications.tick();
+        self.auto_fetch.tick(&self.repo, self.offline, &self.queue);
+        if let Some(running) = &mut self.running_command && running.poll() {
+            let name = running.name.clone();
+            // maintenance tasks say what they did to the repo's size
+            let finished = match self.maintenance_before.take() {
+                Some(before) => {
+                    let after = self.repo.objects_disk_size();
+                    let change = match before.checked_sub(after) {
+                        Some(saved) => format!("{} smaller", human_size(saved)),
+                        None => format!("{} bigger", human_size(after - before)),
+                    };
+                    format!("{} finished, .git/objects went from {} to {} ({})", name, human_size(before), human_size(after), change)
+                }
+                None => format!("{} finished", name),
+            };
+            match running.status {
+                Some(status) if status.success() => self.notify(finished),
+                Some(status) => self.error_message = Some(format!("{} exited with {}", name, status)),
+                None => {}
+            }
+            // just refreshed, so whatever it did isn't going to surprise anyone
+            self.refresh_data()?;
+            self.repo_snapshot = self.repo.snapshot(&self.repo_snapshot);
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    // the watcher saw HEAD or the index move. what hydra did itself is in repo_snapshot already, a
+    // change from outside is refreshed straight away in the main views. a dialog is left alone,
+    // the next key there finds it (check_external_changes), and so is a running command's work
+    fn repo_changed(&mut self) -> Result<bool> {
+        if !self.is_main_view() || self.running_command.as_ref().is_some_and(|r| r.status.is_none()) {
+            return Ok(false);
+        }
+        let now = self.repo.snapshot(&self.repo_snapshot);
+        let change = self.repo_snapshot.change(&now, &self.repo);
+        self.repo_snapshot = now;
+        let Some(change) = change else {
+            return Ok(false);
+        };
+        log::info!("{} outside git-hydra", change);
+        self.refresh_data()?;
+        self.notify(format!("{} outside git-hydra, refreshed", change));
+        Ok(true)
+    }
+
+    // how long the run loop goes without drawing, quick while something on screen keeps changing
+    // without a key being pressed
+    pub fn redraw_tick(&self, since_input: Duration) -> Duration {
+        let busy = self.replay.is_some() || self.auto_fetch.running() || self.running_command.as_ref().is_some_and(|r| r.status.is_none());
+        if busy || since_input < IDLE_AFTER { ACTIVE_TICK } else { IDLE_TICK }
+    }
+
+    // one key press, everything the run loop does with it besides drawing
+    pub fn handle_input(&mut self, key: KeyEvent) {
+        if let Some(recorder) = &mut self.recorder {
+            recorder.record(key, self.mode == AppMode::CredentialDialog && self.credential_editing_token);
+        }
+        let ctrl_p = key.code == KeyCode::Char('p') && key.modifiers.contains(KeyModifiers::CONTROL);
+        let macro_key = self.is_main_view() && matches!(key.code, KeyCode::Char('Q') | KeyCode::Char('@'));
+        if let Some(keys) = &mut self.recording && !macro_key && key.code != KeyCode::F(12) {
+            // ctrl-p has no KeyCode of its own, : does the same
+            keys.push(if ctrl_p { KeyCode::Char(':') } else { key.code });
+        }
+        // the merge message is several lines, enter is a newline in it
+        let save_merge_message = self.mode == AppMode::MergeMessageDialog
+            && ((key.code == KeyCode::Char('s') && key.modifiers.contains(KeyModifiers::CONTROL))
+                || (key.code == KeyCode::Enter && key.modifiers.contains(KeyModifiers::ALT)));
+        // o is text in the push dialog, ctrl-o overrides the secret scan there
+        let override_secrets = self.mode == AppMode::PushDialog
+            && key.code == KeyCode::Char('o')
+            && key.modifiers.contains(KeyModifiers::CONTROL);
+        if ctrl_p && self.is_main_view() {
+            self.open_palette();
+        } else if override_secrets {
+            if !self.secret_findings.is_empty() {
+                self.secrets_overridden = !self.secrets_overridden;
+            }
+        } else if save_merge_message {
+            self.error_message = None;
+            if let Err(e) = self.finish_merge_message() {
+                self.error_message = Some(format!("{:#}", e));
+            }
+        } else if key.code == KeyCode::F(12) {
+            self.perf.visible = !self.perf.visible;
+        } else if let Err(e) = self.check_external_changes(key.code).and_then(|stale| if stale { Ok(()) } else { self.handle_key_event(key.code) }) {
+            // don't take the whole ui down over one failed action
+            self.error_message = Some(format!("{:#}", e));
+        }
+        if let Some(url) = self.repo.take_credential_request() {
+            self.open_credential_dialog(url);
+        }
+        // whatever hydra did itself doesn't count as a change from outside
+        self.repo_snapshot = self.repo.snapshot(&self.repo_snapshot);
+    }
+
+    // completes the merge (or cherry-pick, revert...) with what's in the merge message dialog
+    fn finish_merge_message(&mut self) -> Result<()> {
+        let message = git2::message_prettify(&self.merge_message, Some(b'#'))
+            .unwrap_or_else(|_| self.merge_message.clone());
+        if message.trim().is_empty() {
+            self.error_message = Some("merge message cannot be empty.".to_string());
+            return Ok(());
+        }
+        match self.complete_merge(&message) {
+            Ok(_) => {
+                self.merge_message.clear();
+                self.refresh_data()?;
+            }
+            Err(e) => {
+                self.mode = AppMode::MergeConflict;
+                self.error_message = Some(format!("failed to complete {}: {:#}", self.conflict_label(), e));
+            }
+        }
+        Ok(())
+    }
+
+    // for --announce: the state in words, and the notifications since last time
+    pub fn announce(&mut self) {
+        if self.announcer.is_none() {
+            return;
+        }
+        // the snapshot is up to date after every key, looking the commit up isn't
+        let head = self.repo_snapshot.head();
+        if head != self.announced_head.as_ref().map(|(id, _)| *id) {
+            self.announced_head = head.map(|id| {
+                let summary = self.repo.commits_by_id(&[id]).ok().and_then(|c| c.into_iter().next())
+                    .map(|c| c.message.lines().next().unwrap_or("").to_string())
+                    .unwrap_or_default();
+                (id, format!("HEAD at {} {}", self.repo.short_id(id), summary))
+            });
+        }
+        let facts = vec![
+            ("view", format!("{} view", announce::mode_name(&self.mode))),
+            ("selection", self.describe_selection()),
+            ("head", self.announced_head.as_ref().map(|(_, text)| text.clone()).unwrap_or_default()),
+            ("error", self.error_message.as_ref().map(|e| format!("error: {}", e)).unwrap_or_default()),
+        ];
+        let history = self.notifications.history();
+        let messages: Vec<String> = history
+            .iter()
+            .take_while(|n| self.announced_at.is_none_or(|at| n.at > at))
+            .filter(|n| n.level != Level::Error)
+            .map(|n| n.message.clone())
+            .collect();
+        if let Some(newest) = history.front() {
+            self.announced_at = Some(newest.at);
+        }
+        if let Some(announcer) = &mut self.announcer {
+            announcer.update(facts, messages.into_iter().rev().collect());
+        }
+    }
+
+    fn describe_selection(&self) -> String {
+        match self.mode {
+            AppMode::Status => {
+                let Some(status) = &self.status else {
+                    return String::new();
+                };
+                let (staged, unstaged) = (status.staged.len(), status.unstaged.len());
+                let section = match self.views.status.selected {
+                    i if i < staged => "staged",
+                    i if i < staged + unstaged => "unstaged",
+                    _ => "untracked",
+                };
+                match self.get_selected_file_path() {
+                    Some(path) => format!("{} file {}", section, path),
+                    None => "no changes".to_string(),
+                }
+            }
+            AppMode::Log => match self.commits.get(self.views.log.selected) {
+                Some(c) => format!("commit {} by {}: {}", &c.id[..c.id.len().min(8)], c.author, c.message.lines().next().unwrap_or("")),
+                None => "no commits".to_string(),
+            },
+            AppMode::Branches => self.branches.get(self.views.branches.selected).map(|b| format!("branch {}", b)).unwrap_or_default(),
+            AppMode::StashList => self.stashes.get(self.views.stashes.selected).map(|s| format!("stash {}: {}", s.index, s.message)).unwrap_or_default(),
+            AppMode::MergeConflict => match self.get_current_conflict_file() {
+                Some(file) => {
+                    let resolution = match self.get_current_resolution() {
+                        Some(r) => announce::mode_name(r),
+                        None => "unresolved".to_string(),
+                    };
+                    format!("{}, conflict {} of {}, {}", file.path, self.selected_conflict_hunk + 1, file.conflicts.len(), resolution)
+                }
+                None => String::new(),
+            },
+            _ => String::new(),
+        }
+    }
+
+    pub fn get_reset_modes() -> Vec<&'static str> {
+        vec!["Soft", "Mixed", "Hard"]
+    }
+    
+    pub fn get_current_reset_mode(&self) -> ResetMode {
+        match self.selected_reset_mode {
+            0 => ResetMode::Soft,
+            1 => ResetMode::Mixed,
+            2 => ResetMode::Hard,
+            _ => ResetMode::Mixed,
+        }
+    }
+
+    fn perform_reset(&mut self) -> Result <()> {
+        let reset_mode = self.get_current_reset_mode();
+
+        if let Some(target_commit) = &self.reset_target_commit {
+            self.repo.reset_to_commit(target_commit, reset_mode)?;
+        } else {
+            self.repo.reset_head(reset_mode)?;
+        }
+
+        self.refresh_data()?;
+        Ok(())
+    }
+
+    // cached in eol_churn, see there
+    pub fn eol_only_change(&self, path: &str) -> bool {
+        if let Some((cached, churn)) = self.eol_churn.borrow().as_ref()
+            && cached == path
+        {
+            return *churn;
+        }
+        let churn = self.repo.eol_only_change(path).unwrap_or(false);
+        self.eol_churn.replace(Some((path.to_string(), churn)));
+        churn
+    }
+
+    // cached in diff_attributes
+    pub fn file_attributes(&self, path: &str) -> Vec<String> {
+        if let Some((cached, attributes)) = self.diff_attributes.borrow().as_ref()
+            && cached == path
+        {
+            return attributes.clone();
+        }
+        let attributes = self.repo.file_attributes(path);
+        self.diff_attributes.replace(Some((path.to_string(), attributes.clone())));
+        attributes
+    }
+
+    pub fn get_selected_file_path(&self) -> Option<String> {
+        if let Some(status) = &self.status {
+            let mut all_files = Vec::new();
+            // staged / unstaged / untracked
+
+            for file in &status.staged {
+                all_files.push(file.path.clone());
+            }
+            for file in &status.unstaged {
+                all_files.push(file.path.clone());
+            }
+            for file in &status.untracked {
+                all_files.push(file.path.clone());
+            }
+
+            all_files.get(self.views.status.selected).cloned()
+        } else {
+            None
+        }
+    }
+
+    pub fn set_offline(&mut self, offline: bool) {
+        self.offline = offline;
+        self.avatars.set_offline(offline);
+        self.repo.set_offline(offline);
+    }
+
+    // true, with the message saying so, when offline mode keeps `what` from going out
+    fn offline_blocks(&mut self, what: &str) -> bool {
+        if self.offline {
+            self.error_message = Some(format!("offline: {} needs the network, \"toggle offline mode\" in the palette to go back online.", what));
+        }
+        self.offline
+    }
+
+    // what the push would send, confirmed before anything goes over the network
+    fn open_push_preview(&mut self) {
+        let Some(remote) = self.remotes.get(self.views.remotes.selected).cloned() else {
+            return;
+        };
+        if self.offline_blocks("pushing") {
+            return;
+        }
+        if self.detached_head.is_some() || self.current_branch.is_empty() {
+            self.error_message = Some("HEAD is detached, check out a branch to push it.".to_string());
+            return;
+        }
+        let branch = self.current_branch.clone();
+        self.preview_push(&remote, &branch);
+    }
+
+    // any push of one branch goes through here: refused when it's protected and
+    // hydra.protectedPush is block, otherwise shown (protected or not) and scanned first
+    fn preview_push(&mut self, remote: &str, branch: &str) {
+        self.load_protection();
+        if self.protection.block && self.protection.is_protected(branch) {
+            self.error_message = Some(format!("{} is a protected branch and hydra.protectedPush is block, so hydra won't push it.", branch));
+            return;
+        }
+        match self.repo.push_preview(remote, branch) {
+            Ok(preview) if preview.count == 0 && preview.remote_tip.is_some() => {
+                self.notify(format!("nothing to push, {}/{} has everything on {}", remote, branch, branch));
+            }
+            Ok(preview) => {
+                self.scan_outgoing(&preview.commits);
+                self.pending_push = Some(preview);
+                self.push_preview_scroll = 0;
+                self.mode = AppMode::PushPreview;
+            }
+            Err(e) => self.error_message = Some(format!("failed to work out what the push would send: {:#}", e)),
+        }
+    }
+
+    // with hydra.secretScan on, what a push sends is checked first. any findings block it
+    // until they're overridden
+    fn scan_outgoing(&mut self, commits: &[Commit]) {
+        self.secret_findings = Vec::new();
+        self.secrets_overridden = false;
+        if self.repo.config_bool("hydra.secretScan") {
+            let (rules, errors) = secrets::load(&self.repo.config_values("hydra.secretPattern"));
+            if !errors.is_empty() {
+                self.error_message = Some(errors.join("\n"));
+            }
+            match self.repo.scan_for_secrets(commits, &rules) {
+                Ok(findings) => self.secret_findings = findings,
+                Err(e) => self.error_message = Some(format!("secret scan failed: {:#}", e)),
+            }
+        }
+    }
+
+    fn load_protection(&mut self) {
+        let (protection, error) = Protection::load(self.repo.config_values("hydra.protectedBranch"), self.repo.config_string("hydra.protectedPush"));
+        self.protection = protection;
+        if let Some(error) = error {
+            self.error_message = Some(error);
+        }
+    }
+
+    // the protected branches `refspecs` would push to or delete on the remote
+    fn protected_targets(&self, refspecs: &[PushRefspec]) -> Vec<String> {
+        refspecs
+            .iter()
+            .filter_map(|refspec| refspec.dst.strip_prefix("refs/heads/"))
+            .filter(|branch| self.protection.is_protected(branch))
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn push_branch(&mut self, remote_name: &str, branch: &str) -> Result<()> {
+        self.is_pushing = true;
+        match self.perf.time(Op::Network, remote_name, || self.repo.push_to_remote(remote_name, branch)) {
+            Ok(message) => {
+                self.notify(message);
+                self.refresh_data()?;
+            }
+            Err(e) => {
+                self.error_message = Some(format!("push failed: {:#}", e));
+            }
+        }
+        self.is_pushing = false;
+        Ok(())
+    }
+
+    fn pull_current_branch(&mut self) -> Result<()> {
+        if self.views.remotes.selected < self.remotes.len() && !self.offline_blocks("pulling") {
+            // a fast-forward checks out over the working tree, don't let it eat local changes
+            if self.repo.has_local_changes()? {
+                self.offer_autostash(StashedOperation::Pull)?;
+                return Ok(());
+            }
+            self.run_pull()?;
+        }
+        Ok(())
+    }
+
+    // true when the pull went through without leaving conflicts behind
+    fn run_pull(&mut self) -> Result<bool> {
+        let remote_name = self.remotes[self.views.remotes.selected].clone();
+        self.is_pulling = true;
+
+        let mut clean = false;
+        match self.perf.time(Op::Network, &remote_name, || self.repo.pull_from_remote(&remote_name, &self.current_branch)) {
+            Ok(message) => {
+                self.notify(message);
+                self.refresh_data()?;
+                if self.mode == AppMode::MergeConflict {
+                    self.error_message = Some("merge conflict detected after pull, please resolve.".to_string());
+                } else {
+                    clean = true;
+                }
+            }
+            Err(e) => {
+                let error_mesg = e.to_string();
+                if error_mesg.contains("merge conflicts require resolution") {
+                    self.refresh_data()?;
+                    if self.mode == AppMode::MergeConflict {
+                        self.error_message = Some("merge conflict detected after pull, please resolve.".to_string());
+                    } else {
+                        self.error_message = Some(format!("pull failed: {:#}", e));
+                    }
+                } else {
+                    self.error_message = Some(format!("pull failed: {:#}", e));
+                }
+            }
+        }
+        self.is_pulling = false;
+        Ok(clean)
+    }
+
+    // true when the branch is checked out. a checkout blocked by local changes offers to
+    // autostash them unless we're already inside an autostash
+    fn checkout(&mut self, branch: &str, offer_autostash: bool) -> Result<bool> {
+        match self.repo.checkout_branch(branch) {
+            Ok(_) => {
+                self.refresh_data()?;
+                Ok(true)
+            }
+            Err(e) if offer_autostash && is_checkout_conflict(&e) => {
+                self.offer_autostash(StashedOperation::Checkout(branch.to_string()))?;
+                Ok(false)
+            }
+            Err(e) => {
+                self.error_message = Some(format!("failed to checkout: {:#}", e));
+                Ok(false)
+            }
+        }
+    }
+
+    fn checkout_detached(&mut self, commit: &str, offer_autostash: bool) -> Result<bool> {
+        match self.repo.checkout_detached(commit) {
+            Ok(oid) => {
+                self.notify(format!("HEAD detached at {}, n: create a branch here", self.repo.short_id(oid)));
+                self.refresh_data()?;
+                Ok(true)
+            }
+            Err(e) if offer_autostash && is_checkout_conflict(&e) => {
+                self.offer_autostash(StashedOperation::Detach(commit.to_string()))?;
+                Ok(false)
+            }
+            Err(e) => {
+                self.error_message = Some(format!("failed to checkout: {:#}", e));
+                Ok(false)
+            }
+        }
+    }
+
+    // the network operation already failed, what's typed here is used when it's tried again
+    fn open_credential_dialog(&mut self, url: String) {
+        self.credential_username = url::Url::parse(&url).map(|u| u.username().to_string()).unwrap_or_default();
+        self.credential_url = url;
+        self.credential_token.clear();
+        self.credential_editing_token = !self.credential_username.is_empty();
+        self.credential_store_note = match self.repo.credential_store() {
+            Some(store) if self.repo.credential_helper_installed(&store) => format!("saved with credential-{} once it works", store),
+            Some(store) => format!("credential-{} isn't installed, hydra won't save it", store),
+            None => "hydra.credentialStore = none, hydra won't save it".to_string(),
+        };
+        // the dialog says what went wrong
+        self.error_message = None;
+        self.popup_return_mode = self.mode;
+        self.mode = AppMode::CredentialDialog;
+    }
+
+    // hydra.autoStash (or git's rebase.autoStash) skips the question
+    fn offer_autostash(&mut self, operation: StashedOperation) -> Result<()> {
+        if self.repo.config_bool("hydra.autoStash") || self.repo.config_bool("rebase.autoStash") {
+            return self.run_autostashed(operation);
+        }
+        self.autostash_operation = Some(operation);
+        self.popup_return_mode = self.mode;
+        self.mode = AppMode::AutostashDialog;
+        Ok(())
+    }
+
+    // stash, run the operation, put the changes back. if the operation itself ends in a
+    // merge conflict the stash stays put, popping it into a conflicted tree only makes it worse
+    fn run_autostashed(&mut self, operation: StashedOperation) -> Result<()> {
+        let stash_id = match self.repo.stash_save(Some("git-hydra autostash"), StashOptions::default()) {
+            Ok(id) => id,
+            Err(e) => {
+                self.error_message = Some(format!("autostash failed: {:#}", e));
+                return Ok(());
+            }
+        };
+
+        let done = match &operation {
+            StashedOperation::Checkout(branch) => self.checkout(branch, false)?,
+            StashedOperation::Detach(commit) => self.checkout_detached(commit, false)?,
+            StashedOperation::Pull => self.run_pull()?,
+        };
+
+        if !done && self.mode == AppMode::MergeConflict {
+            self.error_message = Some(format!(
+                "{} left conflicts. your local changes are in the stash (git-hydra autostash), pop it once they're resolved.",
+                operation.describe()
+            ));
+            return Ok(());
+        }
+        self.reapply_autostash(stash_id)
+    }
+
+    fn reapply_autostash(&mut self, stash_id: git2::Oid) -> Result<()> {
+        let Some(index) = self.repo.stash_list()?.iter().find(|s| s.oid == stash_id).map(|s| s.index) else {
+            self.error_message = Some("the autostash is gone, nothing to re-apply.".to_string());
+            return Ok(());
+        };
+
+        match self.repo.stash_pop(index) {
+            Ok(StashPopResult::Popped) => {
+                self.notify("re-applied autostashed changes.");
+                self.refresh_data()?;
+            }
+            Ok(StashPopResult::Conflicted(stash_id)) => {
+                self.pending_stash_drop = Some(stash_id);
+                self.refresh_data()?;
+                self.error_message = Some("autostashed changes conflicted when re-applied, the stash is dropped once they're resolved.".to_string());
+            }
+            Err(e) => {
+                self.error_message = Some(format!("failed to re-apply autostash, your changes are still in stash@{{{}}}: {:#}", index, e));
+            }
+        }
+        Ok(())
+    }
+
+    // another tool committed, switched branches or staged since the last key, so the screen is out
+    // of date. it's refreshed either way, and a key that might write something is dropped (true)
+    // so it can't act on what the user thought HEAD was
+    fn check_external_changes(&mut self, key: KeyCode) -> Result<bool> {
+        let now = self.repo.snapshot(&self.repo_snapshot);
+        let Some(change) = self.repo_snapshot.change(&now, &self.repo) else {
+            self.repo_snapshot = now;
+            return Ok(false);
+        };
+        self.repo_snapshot = now;
+        log::info!("{} outside git-hydra", change);
+        self.refresh_data()?;
+        let may_write = match key {
+            KeyCode::Enter | KeyCode::Delete => true,
+            KeyCode::Char(_) => self.is_main_view(),
+            _ => false,
+        };
+        if may_write {
+            self.error_message = Some(format!(
+                "{} outside git-hydra, so what was on screen was out of date. it's been refreshed, have a look and press the key again.",
+                change
+            ));
+        } else {
+            self.notify(format!("{} outside git-hydra, refreshed", change));
+        }
+        Ok(may_write)
+    }
+
+    // says what the lock is and whether it looks safe to remove, under the error that ran into it
+    fn explain_index_lock(&mut self) {
+        let explanation = match git_running() {
+            Some(true) => {
+                "\n\n.git/index.lock is there because another git is working on the index, and a git process is running right now (maybe waiting on an editor or a prompt in another terminal). let it finish and try again."
+            }
+            Some(false) => {
+                self.index_locked = true;
+                "\n\n.git/index.lock was left behind by a git that crashed or was killed, no git process is running now. L: remove the stale lock, then try again."
+            }
+            None => {
+                self.index_locked = true;
+                "\n\n.git/index.lock is in the way. it's stale if no other git command is running on this repository, then L removes it."
+            }
+        };
+        if let Some(error) = &mut self.error_message {
+            error.push_str(explanation);
+        }
+    }
+
+    fn remove_index_lock(&mut self) {
+        self.index_locked = false;
+        if git_running() == Some(true) {
+            self.error_message = Some("a git process has started since, leaving .git/index.lock alone. try again once it's done.".to_string());
+            return;
+        }
+        match self.repo.remove_lock(&self.repo.index_lock()) {
+            Ok(()) => {
+                self.error_message = None;
+                self.notify("removed the stale .git/index.lock, try again");
+            }
+            Err(e) => self.error_message = Some(format!("{:#}", e)),
+        }
+    }
+
+    fn handle_key_event(&mut self, key: KeyCode) -> Result<()> {
+        // while the error popup is up esc only dismisses it and the arrows scroll it,
+        // any other key still clears it and does its thing
+        if self.error_message.is_some() {
+            match key {
+                KeyCode::Esc => {
+                    self.error_message = None;
+                    self.index_locked = false;
+                    return Ok(());
+                }
+                KeyCode::Char('L') if self.index_locked => {
+                    self.remove_index_lock();
+                    return Ok(());
+                }
+                KeyCode::Up | KeyCode::PageUp => {
+                    self.error_scroll = self.error_scroll.saturating_sub(if key == KeyCode::Up { 1 } else { 5 });
+                    return Ok(());
+                }
+                KeyCode::Down | KeyCode::PageDown => {
+                    self.error_scroll = self.error_scroll.saturating_add(if key == KeyCode::Down { 1 } else { 5 });
+                    return Ok(());
+                }
+                _ => {}
+            }
+        }
+        self.error_message = None;
+        self.error_scroll = 0;
+        self.index_locked = false;
+
+        if key == KeyCode::F(1) && self.is_main_view() {
+            self.open_help();
+            return Ok(());
+        }
+        // `git-hydra commit` only has status and the commit dialog, the view's own keys see to that
+        if self.quick_commit.is_none() && let Some(action) = self.keymap.action(self.mode, key) {
+            return self.dispatch(action);
+        }
+        self.handle_view_key(key)
+    }
+
+    // what the key does in the view it's pressed in. the keymap and the actions end up here for
+    // the keys they stand for, it never goes back to the keymap
+    fn handle_view_key(&mut self, key: KeyCode) -> Result<()> {
+        match self.mode {
+            AppMode::CommitDialog => {
+                match key {
+                    KeyCode::Esc => {
+                        self.mode = AppMode::Status;
+                        self.commit_message.clear();
+                    }
+                    KeyCode::Enter => {
+                        if !self.commit_message.trim().is_empty() {
+                            match self.repo.commit(&self.commit_message) {
+                                Ok(oid) => {
+                                    self.commit_history.remember(&self.commit_message);
+                                    if self.quick_commit.is_some() {
+                                        let subject = self.commit_message.lines().next().unwrap_or("").trim();
+                                        self.committed = Some(format!("{} {}", self.repo.short_id(oid), subject));
+                                        self.should_quit = true;
+                                    }
+                                    if let Some(branch) = self.unborn_branch.take() {
+                                        self.notify(format!("first commit, created {}", branch));
+                                    }
+                                    self.mode = AppMode::Status;
+                                    self.commit_message.clear();
+                                    self.refresh_data()?;
+                                }
+                                Err(e) => {
+                                    self.error_message = Some(format!("commit failed: {:#}", e));
+                                }
+                            }
+                        }
+                    }
+                    KeyCode::Up => {
+                        if let Some(message) = self.commit_history.older(&self.commit_message) {
+                            self.commit_message = message;
+                        }
+                    }
+                    KeyCode::Down => {
+                        if let Some(message) = self.commit_history.newer() {
+                            self.commit_message = message;
+                        }
+                    }
+                    KeyCode::Char(c) => {
+                        self.commit_message.push(c);
+                    }
+                    KeyCode::Backspace => {
+                        self.commit_message.pop();
+                    }
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::CreateBranchDialog => {
+                let templated = self.branch_template.is_some();
+                match key {
+                    KeyCode::Esc => {
+                        self.mode = AppMode::Branches;
+                        self.branch_name.clear()
+                    }
+                    KeyCode::Tab if !self.branch_templates.is_empty() => {
+                        self.branch_template = match self.branch_template {
+                            None => Some(0),
+                            Some(i) if i + 1 < self.branch_templates.len() => Some(i + 1),
+                            Some(_) => None,
+                        };
+                        self.branch_fields = self.branch_template
+                            .map(|i| branch_names::placeholders(&self.branch_templates[i]))
+                            .unwrap_or_default()
+                            .into_iter()
+                            .map(|p| (p, String::new()))
+                            .collect();
+                        self.branch_field = 0;
+                        self.render_branch_template();
+                    }
+                    // with a template enter walks through the placeholders first
+                    KeyCode::Enter if templated && self.branch_field + 1 < self.branch_fields.len() => {
+                        self.branch_field += 1;
+                    }
+                    KeyCode::Enter => {
+                        if let Some(problem) = self.repo.validate_branch_name(&self.branch_name) {
+                            self.error_message = Some(format!("cannot create branch: {}", problem));
+                            return Ok(());
+                        }
+                        let name = self.branch_name.trim().to_string();
+                        // on a detached HEAD "create branch here" also switches to it
+                        let result = self.repo.create_branch(&name).and_then(|_| match self.detached_head {
+                            Some(_) => self.repo.checkout_branch(&name),
+                            None => Ok(()),
+                        });
+                        // made on top of another feature branch: that's a stack, remember it for restacking
+                        if result.is_ok() && self.detached_head.is_none() && self.current_branch != self.repo.flow_config().main {
+                            if let Err(e) = self.repo.record_stack_parent(&name, &self.current_branch) {
+                                log::warn!("couldn't record {} as stacked on {}: {:#}", name, self.current_branch, e);
+                            }
+                        }
+                        match result {
+                            Ok(_) => {
+                                self.branch_history.remember(&name);
+                                self.mode = AppMode::Branches;
+                                self.branch_name.clear();
+                                self.refresh_data()?;
+                            }
+                            Err(e) => {
+                                self.error_message = Some(format!("failed to create branch: {:#}", e));
+                            }
+                        }
+                    }
+                    KeyCode::Up if templated => {
+                        self.branch_field = self.branch_field.saturating_sub(1);
+                    }
+                    KeyCode::Down if templated => {
+                        if self.branch_field + 1 < self.branch_fields.len() {
+                            self.branch_field += 1;
+                        }
+                    }
+                    KeyCode::Char(c) if templated => {
+                        if let Some((_, value)) = self.branch_fields.get_mut(self.branch_field) {
+                            value.push(c);
+                        }
+                        self.render_branch_template();
+                    }
+                    KeyCode::Backspace if templated => {
+                        if let Some((_, value)) = self.branch_fields.get_mut(self.branch_field) {
+                            value.pop();
+                        }
+                        self.render_branch_template();
+                    }
+                    KeyCode::Up => {
+                        if let Some(name) = self.branch_history.older(&self.branch_name) {
+                            self.branch_name = name;
+                        }
+                    }
+                    KeyCode::Down => {
+                        if let Some(name) = self.branch_history.newer() {
+                            self.branch_name = name;
+                        }
+                    }
+                    KeyCode::Char(c) => {
+                        self.branch_name.push(c);
+                    }
+                    KeyCode::Backspace => {
+                        self.branch_name.pop();
+                    }
+
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::StashDialog => {
+                match key {
+                    KeyCode::Esc => {
+                        self.mode = AppMode::Status;
+                        self.stash_message.clear();
+                    }
+                    KeyCode::Enter => {
+                        let msg = if self.stash_message.trim().is_empty(){
+                            None
+                        } else {
+                            Some(self.stash_message.as_str())
+                        };
+
+                        match self.repo.stash_save(msg, self.stash_options){
+                            Ok(_) => {
+                                self.stash_history.remember(&self.stash_message);
+                                self.mode = AppMode::Status;
+                                self.stash_message.clear();
+                                self.refresh_data()?;
+                            }
+                            Err(e) => {
+                                self.error_message = Some(format!("failed to stash: {:#}", e));
+                            }
+                        }
+                    }
+                    KeyCode::Tab => {
+                        self.stash_focus = (self.stash_focus + 1) % 4;
+                    }
+                    KeyCode::BackTab => {
+                        self.stash_focus = (self.stash_focus + 3) % 4;
+                    }
+                    KeyCode::Char(' ') if self.stash_focus > 0 => {
+                        let options = &mut self.stash_options;
+                        match self.stash_focus {
+                            1 => options.include_untracked = !options.include_untracked,
+                            2 => options.include_ignored = !options.include_ignored,
+                            _ => options.keep_index = !options.keep_index,
+                        }
+                    }
+                    KeyCode::Up if self.stash_focus == 0 => {
+                        if let Some(message) = self.stash_history.older(&self.stash_message) {
+                            self.stash_message = message;
+                        }
+                    }
+                    KeyCode::Down if self.stash_focus == 0 => {
+                        if let Some(message) = self.stash_history.newer() {
+                            self.stash_message = message;
+                        }
+                    }
+                    KeyCode::Char(c) if self.stash_focus == 0 => {
+                        self.stash_message.push(c);
+                    }
+                    KeyCode::Backspace if self.stash_focus == 0 => {
+                        self.stash_message.pop();
+                    }
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::GitflowDialog => {
+                match key {
+                    KeyCode::Esc => {
+                        self.mode = AppMode::Branches;
+                    }
+                    KeyCode::Up => {
+                        self.selected_flow_action = self.selected_flow_action.saturating_sub(1);
+                    }
+                    KeyCode::Down => {
+                        if self.selected_flow_action + 1 < FLOW_ACTIONS.len() {
+                            self.selected_flow_action += 1;
+                        }
+                    }
+                    KeyCode::Enter => {
+                        self.run_flow_action()?;
+                    }
+                    KeyCode::Char(c) if FLOW_ACTIONS[self.selected_flow_action].1 => {
+                        self.flow_name.push(c);
+                    }
+                    KeyCode::Backspace => {
+                        self.flow_name.pop();
+                    }
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::PushPreview => {
+                match key {
+                    KeyCode::Esc | KeyCode::Char('n') => {
+                        self.pending_push = None;
+                        self.mode = AppMode::RemoteOperations;
+                    }
+                    KeyCode::Enter | KeyCode::Char('y') if !self.secret_findings.is_empty() && !self.secrets_overridden => {
+                        self.error_message = Some(format!(
+                            "the scan found {} possible secret(s) in what would be pushed, see the list. take them out of the commits, or o pushes anyway.",
+                            self.secret_findings.len()
+                        ));
+                    }
+                    KeyCode::Enter | KeyCode::Char('y') => {
+                        self.mode = AppMode::RemoteOperations;
+                        if let Some(preview) = self.pending_push.take() {
+                            self.push_branch(&preview.remote, &preview.branch)?;
+                        }
+                    }
+                    KeyCode::Char('o') if !self.secret_findings.is_empty() => {
+                        self.secrets_overridden = !self.secrets_overridden;
+                    }
+                    KeyCode::Up => self.push_preview_scroll = self.push_preview_scroll.saturating_sub(1),
+                    KeyCode::Down => self.push_preview_scroll += 1,
+                    KeyCode::PageUp => self.push_preview_scroll = self.push_preview_scroll.saturating_sub(10),
+                    KeyCode::PageDown => self.push_preview_scroll += 10,
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::PushDialog => {
+                match key {
+                    KeyCode::Esc => {
+                        self.mode = AppMode::Branches;
+                    }
+                    KeyCode::Tab => {
+                        if !self.remotes.is_empty() {
+                            self.push_remote = (self.push_remote + 1) % self.remotes.len();
+                        }
+                        self.push_confirmed = false;
+                        self.secret_findings.clear();
+                        self.secrets_overridden = false;
+                    }
+                    KeyCode::Enter => {
+                        let Some(remote) = self.remotes.get(self.push_remote).cloned() else {
+                            self.error_message = Some("no remotes configured.".to_string());
+                            return Ok(());
+                        };
+                        let refspecs: Result<Vec<PushRefspec>> = self.push_refspecs
+                            .split_whitespace()
+                            .map(|spec| self.repo.parse_push_refspec(spec))
+                            .collect();
+                        match refspecs {
+                            Ok(refspecs) if refspecs.is_empty() => self.error_message = Some("nothing to push.".to_string()),
+                            Ok(refspecs) if !self.push_confirmed && !self.protected_targets(&refspecs).is_empty() => {
+                                let protected = self.protected_targets(&refspecs);
+                                if self.protection.block {
+                                    self.error_message = Some(format!(
+                                        "{} protected and hydra.protectedPush is block, so hydra won't push to {}.",
+                                        protected.join(", "),
+                                        if protected.len() == 1 { "it" } else { "them" }
+                                    ));
+                                } else {
+                                    // the dialog asks, the next enter pushes
+                                    self.push_confirmed = true;
+                                }
+                            }
+                            Ok(refspecs) => {
+                                if !self.secrets_overridden && self.repo.config_bool("hydra.secretScan") {
+                                    match self.repo.outgoing_commits(&remote, &refspecs) {
+                                        Ok(commits) => self.scan_outgoing(&commits),
+                                        Err(e) => {
+                                            self.error_message = Some(format!("failed to work out what the push would send: {:#}", e));
+                                            return Ok(());
+                                        }
+                                    }
+                                    if !self.secret_findings.is_empty() {
+                                        self.error_message = Some(format!(
+                                            "the scan found {} possible secret(s) in what would be pushed, see the list. take them out of the commits, or ctrl-o pushes anyway.",
+                                            self.secret_findings.len()
+                                        ));
+                                        return Ok(());
+                                    }
+                                }
+                                self.is_pushing = true;
+                                match self.perf.time(Op::Network, &remote, || self.repo.push_refspecs(&remote, &refspecs)) {
+                                    Ok(message) => {
+                                        self.notify(message);
+                                        self.marked_branches.clear();
+                                        self.mode = AppMode::Branches;
+                                        self.refresh_data()?;
+                                    }
+                                    Err(e) => self.error_message = Some(format!("push failed: {:#}", e)),
+                                }
+                                self.is_pushing = false;
+                            }
+                            Err(e) => self.error_message = Some(format!("{:#}", e)),
+                        }
+                    }
+                    KeyCode::Char(c) => {
+                        self.push_refspecs.push(c);
+                        self.push_confirmed = false;
+                        self.secret_findings.clear();
+                        self.secrets_overridden = false;
+                    }
+                    KeyCode::Backspace => {
+                        self.push_refspecs.pop();
+                        self.push_confirmed = false;
+                        self.secret_findings.clear();
+                        self.secrets_overridden = false;
+                    }
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::RebaseOntoDialog => {
+                match key {
+                    KeyCode::Esc => {
+                        self.mode = self.popup_return_mode;
+                    }
+                    KeyCode::Tab | KeyCode::BackTab => {
+                        self.onto_field = if key == KeyCode::Tab { (self.onto_field + 1) % 3 } else { (self.onto_field + 2) % 3 };
+                        self.selected_onto_candidate = 0;
+                    }
+                    // a highlighted pick goes in the field first, enter again once everything is filled in
+                    KeyCode::Enter => {
+                        let picked = self.filtered_onto_candidates().get(self.selected_onto_candidate).map(|(name, _)| name.clone());
+                        match picked {
+                            Some(name) if name != self.onto_fields[self.onto_field] => {
+                                self.onto_fields[self.onto_field] = name;
+                                if let Some(empty) = self.onto_fields.iter().position(|f| f.trim().is_empty()) {
+                                    self.onto_field = empty;
+                                }
+                                self.selected_onto_candidate = 0;
+                            }
+                            _ if self.onto_fields.iter().any(|f| f.trim().is_empty()) => {
+                                self.error_message = Some("pick the new base, the old base and the tip first.".to_string());
+                            }
+                            _ => {
+                                let [new_base, old_base, tip] = self.onto_fields.clone().map(|f| f.trim().to_string());
+                                self.mode = self.popup_return_mode;
+                                match self.repo.rebase_onto(&new_base, &old_base, &tip) {
+                                    Ok(message) => self.notify(message),
+                                    Err(_) if self.repo.detect_merge_conflicts().ok().flatten().is_some() => {
+                                        self.notify("rebase stopped on conflicts, resolve them and continue.");
+                                    }
+                                    Err(e) => self.error_message = Some(format!("rebase --onto failed: {:#}", e)),
+                                }
+                                self.refresh_data()?;
+                            }
+                        }
+                    }
+                    KeyCode::Up => {
+                        self.selected_onto_candidate = self.selected_onto_candidate.saturating_sub(1);
+                    }
+                    KeyCode::Down => {
+                        if self.selected_onto_candidate + 1 < self.filtered_onto_candidates().len() {
+                            self.selected_onto_candidate += 1;
+                        }
+                    }
+                    KeyCode::Char(c) => {
+                        self.onto_fields[self.onto_field].push(c);
+                        self.selected_onto_candidate = 0;
+                    }
+                    KeyCode::Backspace => {
+                        self.onto_fields[self.onto_field].pop();
+                        self.selected_onto_candidate = 0;
+                    }
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::SyncForkDialog => {
+                match key {
+                    KeyCode::Esc => {
+                        self.mode = AppMode::RemoteOperations;
+                    }
+                    KeyCode::Char('r') => self.sync_fork_options.rebase = !self.sync_fork_options.rebase,
+                    KeyCode::Char('p') => self.sync_fork_options.push = !self.sync_fork_options.push,
+                    KeyCode::Enter => {
+                        self.mode = AppMode::RemoteOperations;
+                        let synced = self.perf.time(Op::Network, "sync fork", || self.repo.sync_fork(self.sync_fork_options));
+                        self.refresh_data()?;
+                        match synced {
+                            Ok((branch, steps)) => {
+                                self.notify(format!("synced fork: {}.", steps.join(", ")));
+                                // the push to origin is like any other, protected branches and the
+                                // secret scan included
+                                if self.sync_fork_options.push {
+                                    if self.remotes.iter().any(|r| r == "origin") {
+                                        self.preview_push("origin", &branch);
+                                    } else {
+                                        self.error_message = Some(format!("no origin remote to push {} to.", branch));
+                                    }
+                                }
+                            }
+                            Err(e) => self.error_message = Some(format!("sync fork failed: {:#}", e)),
+                        }
+                    }
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::UpstreamDialog => {
+                match key {
+                    KeyCode::Esc => {
+                        self.mode = AppMode::Branches;
+                    }
+                    KeyCode::Enter | KeyCode::Delete => {
+                        let upstream = match key {
+                            KeyCode::Enter => match self.filtered_upstreams().get(self.selected_upstream) {
+                                Some(name) => Some(name.to_string()),
+                                None => {
+                                    self.error_message = Some(format!("no branch matches '{}'.", self.upstream_input));
+                                    return Ok(());
+                                }
+                            },
+                            _ => None,
+                        };
+                        let branch = self.upstream_branch.clone();
+                        match self.repo.set_upstream(&branch, upstream.as_deref()) {
+                            Ok(_) => {
+                                match &upstream {
+                                    Some(name) => self.notify(format!("{} now tracks {}.", branch, name)),
+                                    None => self.notify(format!("{} no longer tracks anything.", branch)),
+                                }
+                                self.mode = AppMode::Branches;
+                                self.refresh_data()?;
+                            }
+                            Err(e) => self.error_message = Some(format!("failed to set upstream: {:#}", e)),
+                        }
+                    }
+                    KeyCode::Up => {
+                        self.selected_upstream = self.selected_upstream.saturating_sub(1);
+                    }
+                    KeyCode::Down => {
+                        if self.selected_upstream + 1 < self.filtered_upstreams().len() {
+                            self.selected_upstream += 1;
+                        }
+                    }
+                    KeyCode::Char(c) => {
+                        self.upstream_input.push(c);
+                        self.selected_upstream = 0;
+                    }
+                    KeyCode::Backspace => {
+                        self.upstream_input.pop();
+                        self.selected_upstream = 0;
+                    }
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::RestoreDialog => {
+                match key {
+                    KeyCode::Esc => {
+                        self.mode = AppMode::Log;
+                    }
+                    KeyCode::Enter => {
+                        if let Some(path) = self.restore_path() {
+                            match self.repo.restore_file(&self.restore_commit, &path, self.restore_target) {
+                                Ok(_) => {
+                                    self.notify(format!("restored {} from {} into the {}.", path, self.repo.short_id(&self.restore_commit), self.restore_target.label()));
+                                    self.mode = AppMode::Log;
+                                    self.refresh_data()?;
+                                }
+                                Err(e) => self.error_message = Some(format!("restore failed: {:#}", e)),
+                            }
+                        }
+                    }
+                    KeyCode::Tab => {
+                        self.restore_target = match self.restore_target {
+                            RestoreTarget::Worktree => RestoreTarget::Index,
+                            RestoreTarget::Index => RestoreTarget::Both,
+                            RestoreTarget::Both => RestoreTarget::Worktree,
+                        };
+                        self.load_restore_preview();
+                    }
+                    KeyCode::Up => {
+                        if self.selected_restore_file > 0 {
+                            self.selected_restore_file -= 1;
+                            self.load_restore_preview();
+                        }
+                    }
+                    KeyCode::Down => {
+                        if self.selected_restore_file + 1 < self.filtered_restore_files().len() {
+                            self.selected_restore_file += 1;
+                            self.load_restore_preview();
+                        }
+                    }
+                    KeyCode::PageUp => {
+                        self.restore_scroll = self.restore_scroll.saturating_sub(10);
+                    }
+                    KeyCode::PageDown => {
+                        self.restore_scroll = self.restore_scroll.saturating_add(10);
+                    }
+                    KeyCode::Char(c) => {
+                        self.restore_input.push(c);
+                        self.selected_restore_file = 0;
+                        self.load_restore_preview();
+                    }
+                    KeyCode::Backspace => {
+                        self.restore_input.pop();
+                        self.selected_restore_file = 0;
+                        self.load_restore_preview();
+                    }
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::CredentialDialog => {
+                match key {
+                    KeyCode::Esc => {
+                        self.credential_token.clear();
+                        self.mode = self.popup_return_mode;
+                    }
+                    KeyCode::Tab => self.credential_editing_token = !self.credential_editing_token,
+                    KeyCode::Enter if !self.credential_editing_token => self.credential_editing_token = true,
+                    KeyCode::Enter if !self.credential_token.is_empty() => {
+                        self.repo.set_prompted_credential(&self.credential_url, self.credential_username.trim(), &self.credential_token);
+                        self.credential_token.clear();
+                        self.mode = self.popup_return_mode;
+                        self.notify(format!("credentials for {} set ({}), run it again.", self.credential_url, self.credential_store_note));
+                    }
+                    KeyCode::Char(c) if self.credential_editing_token => self.credential_token.push(c),
+                    KeyCode::Char(c) => self.credential_username.push(c),
+                    KeyCode::Backspace if self.credential_editing_token => {
+                        self.credential_token.pop();
+                    }
+                    KeyCode::Backspace => {
+                        self.credential_username.pop();
+                    }
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::AutostashDialog => {
+                match key {
+                    KeyCode::Enter | KeyCode::Char('y') | KeyCode::Char('a') => {
+                        if key == KeyCode::Char('a') {
+                            self.repo.set_config_bool("hydra.autoStash", true)?;
+                            self.notify("hydra.autoStash set, local changes will be stashed automatically.");
+                        }
+                        self.mode = self.popup_return_mode;
+                        if let Some(operation) = self.autostash_operation.take() {
+                            self.run_autostashed(operation)?;
+                        }
+                    }
+                    KeyCode::Esc | KeyCode::Char('n') => {
+                        self.autostash_operation = None;
+                        self.mode = self.popup_return_mode;
+                    }
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::StashList => {
+                match key {
+                    //idiot forgot to add key handling
+                    KeyCode::Char('q') => self.should_quit = true,
+                    KeyCode::Char('1') => {
+                        self.mode = AppMode::Status;
+                        self.refresh_data()?;
+                    }
+                    KeyCode::Char('2') => {
+                        self.mode = AppMode::Log;
+                        self.refresh_data()?;
+                    }
+                    KeyCode::Char('3') => {
+                        self.mode = AppMode::Branches;
+                        self.refresh_data()?;
+                    }
+                    KeyCode::Esc => {
+                        self.mode = AppMode::Status;
+                        return Ok(());
+                    }
+                    KeyCode::Up => {
+                        if self.views.stashes.selected > 0 {
+                            self.views.stashes.selected -= 1;
+                            self.load_selected_stash_files();
+                        }
+                        return Ok(());
+                    }
+                    KeyCode::Down => {
+                        if self.views.stashes.selected + 1 < self.stashes.len() {
+                            self.views.stashes.selected += 1;
+                            self.load_selected_stash_files();
+                        }
+                        return Ok(());
+                    }
+                    KeyCode::Enter => {
+                        match self.repo.stash_pop(self.views.stashes.selected) {
+                            Ok(StashPopResult::Popped) => {
+                                self.mode = AppMode::Status;
+                                self.refresh_data()?;
+                            }
+                            Ok(StashPopResult::Conflicted(stash_id)) => {
+                                self.pending_stash_drop = Some(stash_id);
+                                self.refresh_data()?;
+                                self.error_message = Some("stash applied with conflicts, it will be dropped once they're resolved.".to_string());
+                            }
+                            Err(e) => {
+                                self.error_message = Some(format!("failed to pop stash: {:#}", e));
+                            }
+                        }
+                        return Ok(());
+                    }
+                    KeyCode::Delete => {
+                        if self.views.stashes.selected < self.stashes.len() {
+                            match self.repo.stash_drop(self.views.stashes.selected) {
+                                Ok(_) => {
+                                    self.refresh_data()?;
+                                    if self.views.stashes.selected >= self.stashes.len() && !self.stashes.is_empty() {
+                                        self.views.stashes.selected = self.stashes.len() -1;
+                                        self.load_selected_stash_files();
+                                    }
+                                }
+                                Err(e) => {
+                                    self.error_message = Some(format!("failed to drop stash: {:#}", e));
+                                }
+                            }
+                        }
+                        return Ok(());
+                    }
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::RemoteOperations => {
+                match key {
+                    KeyCode::Up => {
+                        if self.views.remotes.selected > 0{
+                            self.views.remotes.selected -= 1;
+                        }
+                        return Ok(());
+                    }
+                    KeyCode::Down => {
+                        if self.views.remotes.selected + 1 < self.remotes.len() {
+                            self.views.remotes.selected += 1;
+                        }
+                        return Ok(())
+                    }
+                    _ => {}
+                }
+            }
+            AppMode::MergeConflict => {
+                if matches!(key, KeyCode::Up | KeyCode::Down | KeyCode::Right) {
+                    self.conflict_preview_scroll = 0;
+                }
+                match key {
+                    KeyCode::Char('p') => {
+                        self.show_conflict_preview = !self.show_conflict_preview;
+                        self.conflict_preview_scroll = 0;
+                        return Ok(());
+                    }
+                    KeyCode::PageUp => {
+                        self.conflict_preview_scroll = self.conflict_preview_scroll.saturating_sub(10);
+                        return Ok(());
+                    }
+                    KeyCode::PageDown => {
+                        self.conflict_preview_scroll = self.conflict_preview_scroll.saturating_add(10);
+                        return Ok(());
+                    }
+                    KeyCode::Up => {
+                        if self.selected_conflict_hunk > 0 {
+                            self.selected_conflict_hunk -= 1;
+                        } else if self.selected_conflict_file > 0 {
+                            self.selected_conflict_file -= 1;
+                            if let Some(file) = self.get_current_conflict_file() {
+                                self.selected_conflict_hunk = file.conflicts.len().saturating_sub(1);
+                            }
+                        }
+                        return Ok(());
+                    }
+                    KeyCode::Down => {
+                        if let Some(file) = self.get_current_conflict_file() {
+                            if self.selected_conflict_hunk + 1 < file.conflicts.len() {
+                                self.selected_conflict_hunk += 1;
+                            } else if let Some(merge_conflict) = &self.merge_conflict {
+                                if self.selected_conflict_file + 1 < merge_conflict.files.len() {
+                                    self.selected_conflict_file += 1;
+                                    self.selected_conflict_hunk = 0;
+                                }
+
+                            }
+                        }
+                        return Ok(());
+                    }
+                    KeyCode::Right => {
+                        if let Some(merge_conflict) = &self.merge_conflict {
+                            if self.selected_conflict_file + 1 < merge_conflict.files.len() {
+                                self.selected_conflict_file += 1;
+                                self.selected_conflict_hunk = 0;
+                            }
+                        }
+                        return Ok(());
+                    }
+
+                    // resolution choices
+                    KeyCode::Char('o') => {
+                        self.set_current_resolution(MergeResolution::KeepOurs);
+                        return Ok(());
+                    }
+                    KeyCode::Char('t') => {
+                        self.set_current_resolution(MergeResolution::KeepTheirs);
+                        return Ok(());
+                    }
+                    // the same side for every hunk, of this file or of all of them
+                    KeyCode::Char('O') | KeyCode::Char('T') | KeyCode::Char('<') | KeyCode::Char('>') => {
+                        let resolution = if matches!(key, KeyCode::Char('O') | KeyCode::Char('<')) { MergeResolution::KeepOurs } else { MergeResolution::KeepTheirs };
+                        self.resolve_all(resolution, matches!(key, KeyCode::Char('<') | KeyCode::Char('>')));
+                        return Ok(());
+                    }
+                    // the file exactly as ours / theirs has it, straight from the index stages
+                    KeyCode::Char('[') | KeyCode::Char(']') => {
+                        let resolution = if key == KeyCode::Char('[') { MergeResolution::OursFile } else { MergeResolution::TheirsFile };
+                        self.resolve_all(resolution, false);
+                        return Ok(());
+                    }
+                    KeyCode::Char('b') => {
+                        if self.get_current_conflict_file().is_some_and(|f| f.kind != ConflictKind::Text) {
+                            self.error_message = Some("cannot keep both sides of a non-text conflict.".to_string());
+                        } else {
+                            self.set_current_resolution(MergeResolution::KeepBoth);
+                        }
+                        return Ok(());
+                    }
+                    KeyCode::Char('d') => {
+                        if self.get_current_conflict_file().is_some_and(|f| f.kind != ConflictKind::Text) {
+                            self.set_current_resolution(MergeResolution::Delete);
+                        } else {
+                            self.error_message = Some("delete is only available for binary and delete/modify conflicts.".to_string());
+                        }
+                        return Ok(());
+                    }
+                    KeyCode::Char('M') => {
+                        self.run_mergetool()?;
+                        return Ok(());
+                    }
+                    KeyCode::Char('e') => {
+                        // TODO: edit custom res.
+                        // self.set_current_resolution(MergeResolution::Edit);
+                        return Ok(());
+                    }
+
+                    
+                    KeyCode::Char('c') => {
+                        let operation = self.merge_conflict.as_ref().map(|mc| mc.operation);
+                        let needs_message = !matches!(operation, Some(ConflictOperation::Rebase | ConflictOperation::Stash));
+                        if self.can_complete_merge() && !needs_message {
+                            // rebase keeps the replayed commit's message and a stash has no commit at all
+                            if let Err(e) = self.complete_merge("") {
+                                self.error_message = Some(format!("failed to complete {}: {:#}", self.conflict_label(), e));
+                            }
+                        } else if self.can_complete_merge() {
+                            // let the user review/edit what git prepared in MERGE_MSG first
+                            self.merge_message = self.repo.get_merge_message()
+                                .unwrap_or_else(|| "Merge commit".to_string());
+                            self.mode = AppMode::MergeMessageDialog;
+                        } else {
+                            self.error_message = Some("cannot complete merge, some conflicts are unresolved.".to_string());
+                        }
+                        return Ok(());
+                    }
+
+                    KeyCode::Char('a') => {
+                        match self.abort_merge() {
+                            Ok(_) => {
+                                self.refresh_data()?;
+                            }
+                            Err(e) => {
+                                self.error_message = Some(format!("failed to abort {}: {:#}", self.conflict_label(), e));
+                            }
+                        }
+                    }
+
+                    KeyCode::F(5) => {
+                        self.refresh_data()?;
+                        return Ok(());
+                    }
+
+                    KeyCode::Esc => {
+                        if self.merge_conflict.is_some() {
+                            self.mode = AppMode::Status;
+                        } else {
+                            self.error_message = Some("cannot exit merge mode while conflicts exist. use 'a' to abort or 'c' to complete.".to_string());
+                        }
+                        return Ok(());
+                    }
+
+                    _ => {}
+                }
+            }
+            AppMode::MergeMessageDialog => {
+                match key {
+                    KeyCode::Esc => {
+                        self.mode = AppMode::MergeConflict;
+                    }
+                    // ctrl-s / alt-enter complete it, in handle_input
+                    KeyCode::Enter => {
+                        self.merge_message.push('\n');
+                    }
+                    KeyCode::Char(c) => {
+                        self.merge_message.push(c);
+                    }
+                    KeyCode::Backspace => {
+                        self.merge_message.pop();
+                    }
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::ArchiveDialog => {
+                match key {
+                    KeyCode::Esc => {
+                        self.mode = if self.archive_target == "HEAD" { AppMode::Status } else { AppMode::Log };
+                        self.archive_path.clear();
+                    }
+                    KeyCode::Enter => {
+                        if !self.archive_path.trim().is_empty() {
+                            self.export_archive();
+                        }
+                    }
+                    KeyCode::Char(c) => {
+                        self.archive_path.push(c);
+                    }
+                    KeyCode::Backspace => {
+                        self.archive_path.pop();
+                    }
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::PickaxeDialog => {
+                match key {
+                    KeyCode::Esc => {
+                        self.mode = AppMode::Log;
+                    }
+                    KeyCode::Enter => {
+                        self.log_filter = if self.pickaxe_query.is_empty() {
+                            None
+                        } else {
+                            Some(self.pickaxe_query.clone())
+                        };
+                        self.views.log.selected = 0;
+                        self.mode = AppMode::Log;
+                        if let Err(e) = self.refresh_data() {
+                            self.error_message = Some(format!("search failed: {:#}", e));
+                        }
+                    }
+                    KeyCode::Char(c) => {
+                        self.pickaxe_query.push(c);
+                    }
+                    KeyCode::Backspace => {
+                        self.pickaxe_query.pop();
+                    }
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::Help if self.help_searching => {
+                match key {
+                    KeyCode::Esc => {
+                        self.help_searching = false;
+                        self.help_query.clear();
+                    }
+                    KeyCode::Enter => {
+                        self.help_searching = false;
+                        self.help_match = 0;
+                        self.show_help_match();
+                    }
+                    KeyCode::Char(c) => self.help_query.push(c),
+                    KeyCode::Backspace => {
+                        self.help_query.pop();
+                    }
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::Help => {
+                let pages = help::PAGES.len();
+                match key {
+                    KeyCode::Esc | KeyCode::Char('q') | KeyCode::F(1) => {
+                        self.mode = self.popup_return_mode;
+                    }
+                    KeyCode::Right | KeyCode::Tab => {
+                        self.help_page = (self.help_page + 1) % pages;
+                        self.help_scroll = 0;
+                    }
+                    KeyCode::Left | KeyCode::BackTab => {
+                        self.help_page = (self.help_page + pages - 1) % pages;
+                        self.help_scroll = 0;
+                    }
+                    KeyCode::Up => self.help_scroll = self.help_scroll.saturating_sub(1),
+                    KeyCode::Down => self.help_scroll = self.help_scroll.saturating_add(1),
+                    KeyCode::PageUp => self.help_scroll = self.help_scroll.saturating_sub(10),
+                    KeyCode::PageDown => self.help_scroll = self.help_scroll.saturating_add(10),
+                    KeyCode::Char('/') => {
+                        self.help_searching = true;
+                        self.help_query.clear();
+                    }
+                    KeyCode::Char('n') => {
+                        self.help_match += 1;
+                        self.show_help_match();
+                    }
+                    KeyCode::Char('N') => {
+                        self.help_match = self.help_match.wrapping_sub(1);
+                        self.show_help_match();
+                    }
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::ReleaseNotes if self.notes_input.is_some() => {
+                match key {
+                    KeyCode::Esc => self.notes_input = None,
+                    KeyCode::Enter => {
+                        let buffer = std::mem::take(&mut self.notes_buffer);
+                        match self.notes_input.take() {
+                            Some(NotesInput::Range) if !buffer.trim().is_empty() => {
+                                self.notes_range = buffer.trim().to_string();
+                                self.generate_notes();
+                            }
+                            Some(NotesInput::File) if !buffer.trim().is_empty() => {
+                                let path = PathBuf::from(buffer.trim());
+                                match std::fs::write(&path, &self.notes) {
+                                    Ok(()) => self.notify(format!("wrote the release notes for {} to {}", self.notes_range, path.display())),
+                                    Err(e) => self.error_message = Some(format!("failed to write {}: {}", path.display(), e)),
+                                }
+                            }
+                            _ => {}
+                        }
+                    }
+                    KeyCode::Char(c) => self.notes_buffer.push(c),
+                    KeyCode::Backspace => {
+                        self.notes_buffer.pop();
+                    }
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::ReleaseNotes => {
+                match key {
+                    KeyCode::Esc | KeyCode::Char('q') => self.mode = self.popup_return_mode,
+                    KeyCode::Char('r') => {
+                        self.notes_buffer = self.notes_range.clone();
+                        self.notes_input = Some(NotesInput::Range);
+                    }
+                    KeyCode::Char('w') => {
+                        self.notes_buffer = self.repo.config_string("hydra.notesFile").unwrap_or_else(|| "RELEASE_NOTES.md".to_string());
+                        self.notes_input = Some(NotesInput::File);
+                    }
+                    KeyCode::Char('y') => match clipboard::copy(&self.notes) {
+                        Ok(_) => self.notify(format!("copied the release notes for {} to clipboard.", self.notes_range)),
+                        Err(e) => self.error_message = Some(format!("failed to copy: {:#}", e)),
+                    },
+                    KeyCode::Up => self.notes_scroll = self.notes_scroll.saturating_sub(1),
+                    KeyCode::Down => self.notes_scroll = self.notes_scroll.saturating_add(1),
+                    KeyCode::PageUp => self.notes_scroll = self.notes_scroll.saturating_sub(10),
+                    KeyCode::PageDown => self.notes_scroll = self.notes_scroll.saturating_add(10),
+                    KeyCode::F(5) => self.generate_notes(),
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::NewRelease => {
+                match key {
+                    KeyCode::Esc => {
+                        self.release_plan = None;
+                        self.mode = self.popup_return_mode;
+                    }
+                    KeyCode::Left | KeyCode::Right => {
+                        if let Some(plan) = &mut self.release_plan {
+                            let at = release::BUMPS.iter().position(|b| *b == plan.bump).unwrap_or(0);
+                            let next = if key == KeyCode::Right { at + 1 } else { at + release::BUMPS.len() - 1 };
+                            plan.bump = release::BUMPS[next % release::BUMPS.len()];
+                        }
+                    }
+                    KeyCode::Char('p') => self.release_push = !self.release_push,
+                    KeyCode::Tab if !self.remotes.is_empty() => {
+                        self.release_remote = (self.release_remote + 1) % self.remotes.len();
+                    }
+                    KeyCode::Up => self.release_scroll = self.release_scroll.saturating_sub(1),
+                    KeyCode::Down => self.release_scroll = self.release_scroll.saturating_add(1),
+                    KeyCode::PageUp => self.release_scroll = self.release_scroll.saturating_sub(10),
+                    KeyCode::PageDown => self.release_scroll = self.release_scroll.saturating_add(10),
+                    KeyCode::Enter => self.create_release()?,
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::NotificationHistory => {
+                match key {
+                    KeyCode::Esc | KeyCode::Char('N') => {
+                        self.mode = self.popup_return_mode;
+                    }
+                    KeyCode::Up => {
+                        self.notification_scroll = self.notification_scroll.saturating_sub(1);
+                    }
+                    KeyCode::Down => {
+                        if self.notification_scroll + 1 < self.notifications.history().len() {
+                            self.notification_scroll += 1;
+                        }
+                    }
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::ErrorLog => {
+                match key {
+                    KeyCode::Esc | KeyCode::Char('E') => {
+                        self.mode = self.popup_return_mode;
+                    }
+                    KeyCode::Tab => {
+                        self.log_viewer_scroll = 0;
+                        self.mode = AppMode::LogViewer;
+                    }
+                    KeyCode::Up => {
+                        self.selected_error = self.selected_error.saturating_sub(1);
+                        self.error_scroll = 0;
+                    }
+                    KeyCode::Down => {
+                        if self.selected_error + 1 < self.error_log.len() {
+                            self.selected_error += 1;
+                            self.error_scroll = 0;
+                        }
+                    }
+                    KeyCode::PageUp => {
+                        self.error_scroll = self.error_scroll.saturating_sub(5);
+                    }
+                    KeyCode::PageDown => {
+                        self.error_scroll = self.error_scroll.saturating_add(5);
+                    }
+                    KeyCode::Char('y') => {
+                        if let Some((_, error)) = self.error_log.get(self.selected_error) {
+                            match clipboard::copy(error) {
+                                Ok(_) => self.notify("copied error"),
+                                Err(e) => self.error_message = Some(format!("copy failed: {:#}", e)),
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::Hooks => {
+                match key {
+                    KeyCode::Esc => {
+                        self.mode = self.popup_return_mode;
+                    }
+                    KeyCode::Up => {
+                        self.selected_hook = self.selected_hook.saturating_sub(1);
+                        self.hook_output = None;
+                    }
+                    KeyCode::Down => {
+                        if self.selected_hook + 1 < self.hooks.len() {
+                            self.selected_hook += 1;
+                            self.hook_output = None;
+                        }
+                    }
+                    KeyCode::Char(' ') => {
+                        if let Some(hook) = self.hooks.get(self.selected_hook).cloned() {
+                            let enable = !(hook.enabled && hook.executable);
+                            match self.repo.set_hook_enabled(&hook, enable) {
+                                Ok(_) => self.notify(format!("{} {}", if enable { "enabled" } else { "disabled" }, hook.name)),
+                                Err(e) => self.error_message = Some(format!("failed to update {}: {:#}", hook.name, e)),
+                            }
+                            self.load_hooks();
+                        }
+                    }
+                    KeyCode::Char('e') => {
+                        if let Some(hook) = self.hooks.get(self.selected_hook).cloned() {
+                            let (mut cmd, _) = editor_command();
+                            cmd.arg(&hook.path);
+                            self.run_external(cmd)?;
+                            self.load_hooks();
+                        }
+                    }
+                    KeyCode::Char('r') => {
+                        if let Some(hook) = self.hooks.get(self.selected_hook).cloned() {
+                            self.hook_output = Some(match self.repo.run_hook(&hook) {
+                                Ok((status, output)) if output.trim().is_empty() => format!("{} ({}), printed nothing", hook.name, status),
+                                Ok((status, output)) => format!("{} ({})\n\n{}", hook.name, status, output),
+                                Err(e) => format!("{:#}", e),
+                            });
+                        }
+                    }
+                    KeyCode::F(5) => self.load_hooks(),
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::LargeObjects => {
+                match key {
+                    KeyCode::Esc => {
+                        self.mode = self.popup_return_mode;
+                    }
+                    KeyCode::Up => {
+                        self.selected_large_object = self.selected_large_object.saturating_sub(1);
+                    }
+                    KeyCode::Down => {
+                        if self.selected_large_object + 1 < self.large_objects.len() {
+                            self.selected_large_object += 1;
+                        }
+                    }
+                    KeyCode::Enter => {
+                        match self.large_objects.get(self.selected_large_object).and_then(|o| o.commit.as_ref()) {
+                            Some(commit) => {
+                                let id = commit.id.clone();
+                                self.mode = AppMode::Log;
+                                self.refresh_data()?;
+                                self.jump_to(&id)?;
+                            }
+                            None => self.error_message = Some("no commit has this blob, it's unreachable or only in the index or a stash.".to_string()),
+                        }
+                    }
+                    KeyCode::F(5) => self.load_large_objects(),
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::Health => {
+                match key {
+                    KeyCode::Esc => {
+                        self.mode = self.popup_return_mode;
+                    }
+                    KeyCode::Up => {
+                        self.selected_health_issue = self.selected_health_issue.saturating_sub(1);
+                    }
+                    KeyCode::Down => {
+                        if self.selected_health_issue + 1 < self.health.issues.len() {
+                            self.selected_health_issue += 1;
+                        }
+                    }
+                    KeyCode::Enter => self.fix_health_issue()?,
+                    KeyCode::F(5) => self.load_health(),
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::Clean if self.clean_preview.is_some() => {
+                match key {
+                    KeyCode::Char('y') | KeyCode::Enter => self.run_clean()?,
+                    KeyCode::Char('n') | KeyCode::Esc => self.clean_preview = None,
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::Clean => {
+                match key {
+                    KeyCode::Esc => self.mode = self.popup_return_mode,
+                    KeyCode::Up => self.selected_clean = self.selected_clean.saturating_sub(1),
+                    KeyCode::Down if self.selected_clean + 1 < self.clean_entries.len() => self.selected_clean += 1,
+                    KeyCode::Char(' ') => {
+                        if let Some(entry) = self.clean_entries.get(self.selected_clean) {
+                            if !self.clean_marked.remove(&entry.path) {
+                                self.clean_marked.insert(entry.path.clone());
+                            }
+                        }
+                        if self.selected_clean + 1 < self.clean_entries.len() {
+                            self.selected_clean += 1;
+                        }
+                    }
+                    // everything, or nothing when everything already is
+                    KeyCode::Char('a') => {
+                        if self.clean_marked.len() == self.clean_entries.len() {
+                            self.clean_marked.clear();
+                        } else {
+                            self.clean_marked = self.clean_entries.iter().map(|e| e.path.clone()).collect();
+                        }
+                    }
+                    KeyCode::Char('i') => {
+                        self.clean_marked = self.clean_entries.iter().filter(|e| e.ignored).map(|e| e.path.clone()).collect();
+                    }
+                    KeyCode::Enter => self.preview_clean(),
+                    KeyCode::F(5) => self.load_clean_entries(),
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::Maintenance => {
+                match key {
+                    KeyCode::Esc => {
+                        self.mode = self.popup_return_mode;
+                    }
+                    KeyCode::Up => {
+                        self.selected_maintenance_task = self.selected_maintenance_task.saturating_sub(1);
+                    }
+                    KeyCode::Down => {
+                        if self.selected_maintenance_task + 1 < maintenance::TASKS.len() {
+                            self.selected_maintenance_task += 1;
+                        }
+                    }
+                    KeyCode::Enter => self.run_maintenance_task(),
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::Stats => {
+                match key {
+                    KeyCode::Esc => {
+                        self.mode = self.popup_return_mode;
+                    }
+                    KeyCode::Right => {
+                        self.stats_author = (self.stats_author + 1) % (stats::authors(&self.contributions).len() + 1);
+                    }
+                    KeyCode::Left => {
+                        let choices = stats::authors(&self.contributions).len() + 1;
+                        self.stats_author = (self.stats_author + choices - 1) % choices;
+                    }
+                    KeyCode::Char('+') if self.stats_weeks < stats::MAX_WEEKS => {
+                        self.stats_weeks = (self.stats_weeks + 4).min(stats::MAX_WEEKS);
+                        self.load_contributions();
+                    }
+                    KeyCode::Char('-') if self.stats_weeks > 4 => {
+                        self.stats_weeks -= 4;
+                        self.load_contributions();
+                    }
+                    KeyCode::F(5) => self.load_contributions(),
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::Files => {
+                self.handle_files_key(key);
+                return Ok(());
+            }
+            AppMode::CommitView => {
+                match key {
+                    KeyCode::Esc | KeyCode::Enter => {
+                        self.commit_view = None;
+                        self.commit_range = None;
+                        self.mode = AppMode::Log;
+                    }
+                    KeyCode::Char('n') => self.jump_to_file(true),
+                    KeyCode::Char('p') => self.jump_to_file(false),
+                    KeyCode::Char('y') => self.copy_commit_ids(false),
+                    KeyCode::Char('Y') => self.copy_commit_ids(true),
+                    KeyCode::Up => self.commit_view_scroll = self.commit_view_scroll.saturating_sub(1),
+                    KeyCode::Down => self.commit_view_scroll += 1,
+                    KeyCode::PageUp => self.commit_view_scroll = self.commit_view_scroll.saturating_sub(20),
+                    KeyCode::PageDown => self.commit_view_scroll += 20,
+                    KeyCode::Home => self.commit_view_scroll = 0,
+                    // clamped when drawn
+                    KeyCode::End => self.commit_view_scroll = usize::MAX,
+                    // older / newer without going back to the list
+                    KeyCode::Right if self.commit_range.is_none() && self.views.log.selected + 1 < self.commits.len() => {
+                        self.views.log.selected += 1;
+                        self.load_selected_signature();
+                        self.open_commit_view();
+                    }
+                    KeyCode::Left if self.commit_range.is_none() && self.views.log.selected > 0 => {
+                        self.views.log.selected -= 1;
+                        self.load_selected_signature();
+                        self.open_commit_view();
+                    }
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::StackView => {
+                match key {
+                    KeyCode::Esc => {
+                        self.mode = self.popup_return_mode;
+                    }
+                    KeyCode::Up => {
+                        self.selected_stacked = self.selected_stacked.saturating_sub(1);
+                    }
+                    KeyCode::Down => {
+                        if self.selected_stacked + 1 < self.stack.len() {
+                            self.selected_stacked += 1;
+                        }
+                    }
+                    KeyCode::Enter | KeyCode::Char('r') if self.repo.is_bare() => {
+                        self.error_message = Some("this is a bare repository, there is no working tree for that.".to_string());
+                    }
+                    KeyCode::Enter => {
+                        if let Some(branch) = self.stack.get(self.selected_stacked).map(|b| b.name.clone()) {
+                            self.mode = self.popup_return_mode;
+                            self.checkout(&branch, true)?;
+                        }
+                    }
+                    KeyCode::Char('r') => {
+                        match self.repo.restack() {
+                            Ok(moved) if moved.is_empty() => self.notify("every branch is already on top of its parent"),
+                            Ok(moved) => self.notify(format!("restacked {}", moved.join(", "))),
+                            Err(_) if self.repo.detect_merge_conflicts().ok().flatten().is_some() => {
+                                self.mode = self.popup_return_mode;
+                                self.notify("restack stopped on conflicts, resolve them, continue the rebase and restack again.");
+                            }
+                            Err(e) => self.error_message = Some(format!("restack failed: {:#}", e)),
+                        }
+                        self.refresh_data()?;
+                        self.load_stack();
+                    }
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::CommandOutput => {
+                match key {
+                    // it carries on in the background
+                    KeyCode::Esc => {
+                        self.mode = self.popup_return_mode;
+                    }
+                    KeyCode::Char('k') => {
+                        if let Some(running) = &mut self.running_command {
+                            running.kill();
+                        }
+                    }
+                    KeyCode::Up => self.command_output_scroll += 1,
+                    KeyCode::Down => self.command_output_scroll = self.command_output_scroll.saturating_sub(1),
+                    KeyCode::PageUp => self.command_output_scroll += 10,
+                    KeyCode::PageDown => self.command_output_scroll = self.command_output_scroll.saturating_sub(10),
+                    KeyCode::End => self.command_output_scroll = 0,
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::LogViewer => {
+                match key {
+                    KeyCode::Esc => {
+                        self.mode = self.popup_return_mode;
+                    }
+                    KeyCode::Tab => {
+                        self.mode = AppMode::ErrorLog;
+                    }
+                    KeyCode::Up => self.log_viewer_scroll += 1,
+                    KeyCode::Down => self.log_viewer_scroll = self.log_viewer_scroll.saturating_sub(1),
+                    KeyCode::PageUp => self.log_viewer_scroll += 10,
+                    KeyCode::PageDown => self.log_viewer_scroll = self.log_viewer_scroll.saturating_sub(10),
+                    KeyCode::End => self.log_viewer_scroll = 0,
+                    KeyCode::Char('y') => {
+                        match clipboard::copy(&logging::recent().join("\n")) {
+                            Ok(_) => self.notify("copied log"),
+                            Err(e) => self.error_message = Some(format!("copy failed: {:#}", e)),
+                        }
+                    }
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::CommandPalette => {
+                match key {
+                    KeyCode::Esc => {
+                        self.mode = self.palette_return_mode;
+                    }
+                    KeyCode::Up => {
+                        self.selected_palette_item = self.selected_palette_item.saturating_sub(1);
+                    }
+                    KeyCode::Down => {
+                        if self.selected_palette_item + 1 < palette::filter(&self.palette_query, &self.palette_extras()).len() {
+                            self.selected_palette_item += 1;
+                        }
+                    }
+                    KeyCode::Enter => {
+                        let action = palette::filter(&self.palette_query, &self.palette_extras())
+                            .get(self.selected_palette_item)
+                            .map(|cmd| cmd.action);
+                        self.mode = self.palette_return_mode;
+                        if let Some(action) = action {
+                            self.dispatch(action)?;
+                        }
+                    }
+                    KeyCode::Char(c) => {
+                        self.palette_query.push(c);
+                        self.selected_palette_item = 0;
+                    }
+                    KeyCode::Backspace => {
+                        self.palette_query.pop();
+                        self.selected_palette_item = 0;
+                    }
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::SwitchRepoDialog => {
+                match key {
+                    KeyCode::Esc => {
+                        self.mode = AppMode::Status;
+                    }
+                    KeyCode::Enter => {
+                        if !self.switch_repo_path.trim().is_empty() {
+                            let path = PathBuf::from(self.switch_repo_path.trim());
+                            self.switch_repo(path)?;
+                        }
+                    }
+                    KeyCode::Char(c) => {
+                        self.switch_repo_path.push(c);
+                    }
+                    KeyCode::Backspace => {
+                        self.switch_repo_path.pop();
+                    }
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::JumpDialog => {
+                match key {
+                    KeyCode::Esc => {
+                        self.mode = AppMode::Log;
+                    }
+                    KeyCode::Enter => {
+                        if !self.jump_input.trim().is_empty() {
+                            self.mode = AppMode::Log;
+                            let rev = self.jump_input.clone();
+                            self.jump_to(&rev)?;
+                        }
+                    }
+                    KeyCode::Char(c) => {
+                        self.jump_input.push(c);
+                    }
+                    KeyCode::Backspace => {
+                        self.jump_input.pop();
+                    }
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::PackagePicker => {
+                match key {
+                    KeyCode::Esc => self.mode = self.popup_return_mode,
+                    KeyCode::Up => self.selected_package = self.selected_package.saturating_sub(1),
+                    KeyCode::Down if self.selected_package < self.packages.len() => self.selected_package += 1,
+                    KeyCode::Enter => {
+                        let package = self.selected_package.checked_sub(1).and_then(|i| self.packages.get(i)).cloned();
+                        self.apply_package(package)?;
+                    }
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::DiffBasePicker => {
+                match key {
+                    KeyCode::Esc => {
+                        self.mode = AppMode::Status;
+                    }
+                    // the highlighted ref, or what's typed when nothing matches (a sha, HEAD~3)
+                    KeyCode::Enter => {
+                        let base = match self.filtered_diff_bases().get(self.selected_diff_base) {
+                            Some((name, _)) => name.clone(),
+                            None => self.diff_base_input.trim().to_string(),
+                        };
+                        self.apply_diff_base(Some(&base))?;
+                    }
+                    KeyCode::Delete => self.apply_diff_base(None)?,
+                    KeyCode::Up => {
+                        self.selected_diff_base = self.selected_diff_base.saturating_sub(1);
+                    }
+                    KeyCode::Down => {
+                        if self.selected_diff_base + 1 < self.filtered_diff_bases().len() {
+                            self.selected_diff_base += 1;
+                        }
+                    }
+                    KeyCode::Char(c) => {
+                        self.diff_base_input.push(c);
+                        self.selected_diff_base = 0;
+                    }
+                    KeyCode::Backspace => {
+                        self.diff_base_input.pop();
+                        self.selected_diff_base = 0;
+                    }
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::ScopePicker => {
+                match key {
+                    KeyCode::Esc => {
+                        self.mode = AppMode::Status;
+                    }
+                    KeyCode::Up => {
+                        self.selected_scope_entry = self.selected_scope_entry.saturating_sub(1);
+                    }
+                    KeyCode::Down => {
+                        if self.selected_scope_entry + 1 < self.scope_picker_entries.len() {
+                            self.selected_scope_entry += 1;
+                        }
+                    }
+                    KeyCode::Right => {
+                        if let Some(dir) = self.scope_picker_entries.get(self.selected_scope_entry).cloned() {
+                            self.load_scope_picker(&dir);
+                        }
+                    }
+                    KeyCode::Left | KeyCode::Backspace => {
+                        let parent = match self.scope_picker_dir.rsplit_once('/') {
+                            Some((parent, _)) => parent.to_string(),
+                            None => String::new(),
+                        };
+                        self.load_scope_picker(&parent);
+                    }
+                    KeyCode::Enter => {
+                        let scope = self.scope_picker_entries.get(self.selected_scope_entry).cloned();
+                        self.apply_scope(scope.as_deref())?;
+                    }
+                    // scope to the directory being browsed, at the root this clears the scope
+                    KeyCode::Char('.') => {
+                        let scope = self.scope_picker_dir.clone();
+                        self.apply_scope(Some(&scope))?;
+                    }
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::LineStaging if self.line_discard.is_some() => {
+                match key {
+                    KeyCode::Char('y') | KeyCode::Enter => self.discard_lines()?,
+                    KeyCode::Char('n') | KeyCode::Esc => self.line_discard = None,
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::LineStaging => {
+                match key {
+                    KeyCode::Esc => {
+                        self.line_diff = None;
+                        self.mode = AppMode::Status;
+                    }
+                    KeyCode::Up => {
+                        self.line_cursor = self.line_cursor.saturating_sub(1);
+                    }
+                    KeyCode::Down => {
+                        if self.line_cursor + 1 < self.line_rows().len() {
+                            self.line_cursor += 1;
+                        }
+                    }
+                    KeyCode::Char(' ') => {
+                        self.toggle_line_at_cursor();
+                    }
+                    KeyCode::Enter => {
+                        self.apply_selected_lines()?;
+                    }
+                    KeyCode::Char('d') if !self.line_diff_staged => self.confirm_discard(),
+                    KeyCode::Char('q') => self.should_quit = true,
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::AttributesDialog => {
+                match key {
+                    KeyCode::Esc => {
+                        self.mode = AppMode::Status;
+                    }
+                    KeyCode::Up => {
+                        self.selected_attribute = self.selected_attribute.saturating_sub(1);
+                    }
+                    KeyCode::Down => {
+                        if self.selected_attribute + 1 < self.attributes_lines.len() {
+                            self.selected_attribute += 1;
+                        }
+                    }
+                    KeyCode::Enter => {
+                        let line = self.attributes_input.trim().to_string();
+                        if !line.is_empty() {
+                            self.attributes_lines.push(line);
+                            self.save_attributes();
+                            self.attributes_input.clear();
+                            self.selected_attribute = self.attributes_lines.len() - 1;
+                        }
+                    }
+                    KeyCode::Delete => {
+                        if self.selected_attribute < self.attributes_lines.len() {
+                            self.attributes_lines.remove(self.selected_attribute);
+                            self.save_attributes();
+                            self.selected_attribute = self.selected_attribute.min(self.attributes_lines.len().saturating_sub(1));
+                        }
+                    }
+                    KeyCode::Char(c) => {
+                        self.attributes_input.push(c);
+                    }
+                    KeyCode::Backspace => {
+                        self.attributes_input.pop();
+                    }
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::BundleDialog => {
+                let input = if self.bundle_editing_refs { &mut self.bundle_refs } else { &mut self.bundle_path };
+                match key {
+                    KeyCode::Esc => {
+                        self.mode = AppMode::RemoteOperations;
+                    }
+                    KeyCode::Tab => {
+                        if self.bundle_action == BundleAction::Create {
+                            self.bundle_editing_refs = !self.bundle_editing_refs;
+                        }
+                    }
+                    KeyCode::Enter => {
+                        if !self.bundle_path.trim().is_empty() {
+                            self.run_bundle_action()?;
+                        }
+                    }
+                    KeyCode::Char(c) => {
+                        input.push(c);
+                    }
+                    KeyCode::Backspace => {
+                        input.pop();
+                    }
+                    _ => {}
+                }
+                return Ok(());
+            }
+            AppMode::ResetDialog => {
+                match key {
+                    KeyCode::Esc => {
+                        self.mode = AppMode::Status;
+                        self.reset_target_commit = None;
+                        self.selected_reset_mode = 1;
+                    }
+                    KeyCode::Up => {
+                        if self.selected_reset_mode > 0 {
+                            self.selected_reset_mode -= 1;
+                        }
+                    }
+                    KeyCode::Down => {
+                        if self.selected_reset_mode + 1 < Self::get_reset_modes().len() {
+                            self.selected_reset_mode += 1;
+                        }
+                    }
+                    KeyCode::Enter => {
+                        if self.get_current_reset_mode() == ResetMode::Hard {
+                            match self.perform_reset() {
+                                Ok(_) => {
+                                    self.mode = AppMode::Status;
+                                    self.reset_target_commit = None;
+                                    self.selected_reset_mode = 1;
+                                }
+                                Err(e) => {
+                                    self.error_message = Some(format!("Reset failed: {:#}", e));
+                                }
+                            }
+                        } else {
+                            match self.perform_reset() {
+                                Ok(_) => {
+                                    self.mode = AppMode::Status;
+                                    self.reset_target_commit = None;
+                                    self.selected_reset_mode = 1;
+                                }
+                                Err(e) => {
+                                    self.error_message = Some(format!("Reset failed: {:#}", e))
+                                }
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+        if self.mode == AppMode::Branches && self.remote_listing.is_some() && self.handle_listing_key(key)? {
+            return Ok(());
+        }
+        if self.repo.is_bare() && needs_worktree(self.mode, key) {
+            self.error_message = Some("this is a bare repository, there is no working tree for that.".to_string());
+            return Ok(());
+        }
+        match key {
+            KeyCode::Char('q') => self.should_quit = true,
+            KeyCode::Esc if self.quick_commit.is_some() && self.mode == AppMode::Status => self.should_quit = true,
+            KeyCode::Char('2'..='5') if self.quick_commit.is_some() => {
+                self.notify("git-hydra commit is just status and the commit dialog, plain git-hydra has the rest.");
+            }
+            KeyCode::Char('1') => {
+                self.mode = AppMode::Status;
+                self.refresh_data()?;
+            }
+            KeyCode::Char('2') => {
+                self.mode = AppMode::Log;
+                self.refresh_data()?;
+            }
+            KeyCode::Char('3') => {
+                self.mode = AppMode::Branches;
+                self.refresh_data()?;
+            }
+            KeyCode::Char('4') => {
+                self.mode = AppMode::StashList;
+                self.refresh_data()?;
+            }
+            KeyCode::Char('5') => {
+                self.mode = AppMode::RemoteOperations;
+                self.refresh_data()?;
+            }
+            KeyCode::Char('r') => {
+                if self.mode == AppMode::Log && !self.commits.is_empty() {
+                    let selected_commit = &self.commits[self.views.log.selected];
+                    self.reset_target_commit = Some(selected_commit.id.clone());
+                    self.mode = AppMode::ResetDialog;
+                    self.selected_reset_mode = 1;
+                } else if self.mode == AppMode::Status {
+                    self.reset_target_commit = None;
+                    self.mode = AppMode::ResetDialog;
+                    self.selected_reset_mode = 1;
+                } else {
+                    self.error_message = Some("Reset only available from Status or Log view".to_string());
+                }
+            }
+            KeyCode::Char('p') => {
+                if self.mode == AppMode::RemoteOperations && !self.remotes.is_empty() {
+                    self.open_push_preview();
+                }
+            }
+            KeyCode::Char('u') => {
+                if self.mode == AppMode::RemoteOperations && !self.remotes.is_empty() {
+                    self.pull_current_branch()?;
+                }
+            }
+            KeyCode::Char('s') => {
+                self.mode = AppMode::StashDialog;
+                self.stash_message.clear();
+                self.stash_focus = 0;
+                let suggestions = self.recent_stash_messages();
+                self.stash_history.begin(suggestions);
+            }
+            KeyCode::Char('R') if self.mode == AppMode::Log => {
+                if let Some(commit) = self.commits.get(self.views.log.selected) {
+                    let id = commit.id.clone();
+                    match self.repo.commit_files(&id) {
+                        Ok(files) => {
+                            self.restore_commit = id;
+                            self.restore_files = files;
+                            self.restore_input.clear();
+                            self.selected_restore_file = 0;
+                            self.restore_target = RestoreTarget::Worktree;
+                            self.mode = AppMode::RestoreDialog;
+                            self.load_restore_preview();
+                        }
+                        Err(e) => self.error_message = Some(format!("cannot list files of commit: {:#}", e)),
+                    }
+                }
+            }
+            KeyCode::Char('C') if self.mode == AppMode::Log => {
+                if let Some(commit) = self.commits.get(self.views.log.selected) {
+                    let id = commit.id.clone();
+                    self.checkout_detached(&id, true)?;
+                }
+            }
+            KeyCode::Char('f') if matches!(self.mode, AppMode::Status | AppMode::Log) => {
+                let rev = match self.mode {
+                    AppMode::Log => self.commits.get(self.views.log.selected).map(|c| c.id.clone()),
+                    _ => Some("HEAD".to_string()),
+                };
+                if let Some(rev) = rev {
+                    self.open_files(&rev);
+                }
+            }
+            KeyCode::Char('f') if self.mode == AppMode::Branches => {
+                self.mode = AppMode::GitflowDialog;
+            }
+            KeyCode::Char('F') if self.mode == AppMode::RemoteOperations => {
+                if !self.offline_blocks("syncing a fork") {
+                    self.mode = AppMode::SyncForkDialog;
+                }
+            }
+            KeyCode::Char('T') if self.mode == AppMode::Branches => {
+                self.popup_return_mode = self.mode;
+                self.selected_stacked = 0;
+                self.load_stack();
+                self.mode = AppMode::StackView;
+            }
+            KeyCode::Char('O') if matches!(self.mode, AppMode::Log | AppMode::Branches) => {
+                self.open_rebase_onto()?;
+            }
+            KeyCode::Char(' ') if self.mode == AppMode::Log => {
+                if let Some(commit) = self.commits.get(self.views.log.selected) {
+                    self.log_mark = match &self.log_mark {
+                        Some(mark) if *mark == commit.id => None,
+                        _ => Some(commit.id.clone()),
+                    };
+                }
+            }
+            KeyCode::Char(' ') if self.mode == AppMode::Branches => {
+                if let Some(branch) = self.branches.get(self.views.branches.selected).cloned() {
+                    if !self.repo.local_branch_exists(&branch) {
+                        self.error_message = Some("only local branches can be marked for pushing.".to_string());
+                    } else if !self.marked_branches.remove(&branch) {
+                        self.marked_branches.insert(branch);
+                    }
+                }
+            }
+            KeyCode::Char('P') if self.mode == AppMode::Branches => {
+                if self.offline_blocks("pushing") {
+                    return Ok(());
+                }
+                // the marked branches, or the selected one, as editable refspecs
+                let branches: Vec<String> = if self.marked_branches.is_empty() {
+                    self.branches.get(self.views.branches.selected).filter(|b| self.repo.local_branch_exists(b)).cloned().into_iter().collect()
+                } else {
+                    self.marked_branches.iter().cloned().collect()
+                };
+                self.remotes = self.repo.get_remotes()?;
+                self.push_remote = self.remotes.iter().position(|r| r == "origin").unwrap_or(0);
+                self.push_refspecs = branches.join(" ");
+                self.push_confirmed = false;
+                self.secret_findings.clear();
+                self.secrets_overridden = false;
+                self.mode = AppMode::PushDialog;
+            }
+            KeyCode::Char('L') if self.mode == AppMode::Branches => {
+                let remotes = self.repo.get_remotes()?;
+                // L again moves on to the next remote
+                let next = match &self.remote_listing {
+                    Some((current, _)) => remotes.iter().position(|r| r == current).map(|i| (i + 1) % remotes.len()),
+                    None => remotes.iter().position(|r| r == "origin").or(Some(0)),
+                };
+                match next.and_then(|i| remotes.get(i)).cloned() {
+                    Some(remote) => self.load_remote_listing(&remote),
+                    None => self.error_message = Some("no remotes configured.".to_string()),
+                }
+            }
+            KeyCode::Char('t') if self.mode == AppMode::Branches => {
+                match self.branches.get(self.views.branches.selected).cloned() {
+                    Some(branch) if self.repo.local_branch_exists(&branch) => {
+                        self.upstream_input = self.branch_upstreams.get(&branch).map(|u| u.name.clone()).unwrap_or_default();
+                        self.upstream_branch = branch;
+                        self.selected_upstream = 0;
+                        self.mode = AppMode::UpstreamDialog;
+                    }
+                    Some(_) => self.error_message = Some("only local branches have an upstream.".to_string()),
+                    None => {}
+                }
+            }
+            KeyCode::Char('n') => {
+                if self.mode == AppMode::Branches || (self.mode == AppMode::Log && self.detached_head.is_some()) {
+                    self.mode = AppMode::CreateBranchDialog;
+                    self.branch_name.clear();
+                    self.branch_templates = self.repo.config_values("hydra.branchTemplate");
+                    self.branch_template = None;
+                    self.branch_fields.clear();
+                    let local_branches = self.branches.iter()
+                        .filter(|b| !b.contains('/'))
+                        .cloned()
+                        .collect();
+                    self.branch_history.begin(local_branches);
+                }
+            }
+            KeyCode::Char('c') => {
+                // Open dialog if there are staged files
+                if let Some(status) = &self.status {
+                    if !status.staged.is_empty() {
+                        self.mode = AppMode::CommitDialog;
+                        self.commit_message = self.quick_commit.clone().unwrap_or_default();
+                        let suggestions = self.recent_commit_messages();
+                        self.commit_history.begin(suggestions);
+                    } else {
+                        self.error_message = Some("no staged files to commit.".to_string())
+                    }
+                }
+            }
+            
+            KeyCode::Char('y') => {
+                self.copy_selection(false);
+            }
+            KeyCode::Char('e') => {
+                if self.mode == AppMode::Status {
+                    self.open_in_editor()?;
+                }
+            }
+            KeyCode::Char('a') if self.mode == AppMode::RemoteOperations => {
+                self.auth_status = match self.auth_status {
+                    Some(_) => None,
+                    None => Some(AuthStatus::load(&self.repo)),
+                };
+            }
+            KeyCode::Char('b') | KeyCode::Char('B') if self.mode == AppMode::RemoteOperations => {
+                self.bundle_action = if key == KeyCode::Char('b') { BundleAction::Create } else { BundleAction::Fetch };
+                self.bundle_editing_refs = false;
+                if self.bundle_action == BundleAction::Create {
+                    self.bundle_path = format!("{}.bundle", self.current_branch.replace('/', "-"));
+                    self.bundle_refs = self.current_branch.clone();
+                } else {
+                    self.bundle_path.clear();
+                }
+                self.mode = AppMode::BundleDialog;
+            }
+            KeyCode::Char('w') if self.mode == AppMode::Status => {
+                let ignore = !self.repo.ignore_eol();
+                self.repo.set_ignore_eol(ignore);
+                self.line_counts.refresh(&self.repo);
+                self.views.status.scroll = 0;
+                self.notify(if ignore { "diff ignores line ending (CRLF/LF) changes" } else { "diff shows line ending changes again" });
+            }
+            KeyCode::Char('W') if self.mode == AppMode::Status => {
+                self.toggle_index_flag(IndexFlag::SkipWorktree)?;
+            }
+            KeyCode::Char('U') if self.mode == AppMode::Status => {
+                self.toggle_index_flag(IndexFlag::AssumeUnchanged)?;
+            }
+            KeyCode::Char('X') | KeyCode::Char('T') if self.mode == AppMode::Status => {
+                self.stage_mode_split(key == KeyCode::Char('X'))?;
+            }
+            KeyCode::Char('v') if self.mode == AppMode::Status => {
+                if let (Some(path), Some(status)) = (self.get_selected_file_path(), &self.status) {
+                    let staged = self.views.status.selected < status.staged.len();
+                    match self.repo.get_file_hunks(&path, staged) {
+                        Ok(file_diff) => {
+                            self.line_diff = Some(file_diff);
+                            self.line_diff_staged = staged;
+                            self.line_cursor = 0;
+                            self.selected_lines.clear();
+                            self.mode = AppMode::LineStaging;
+                        }
+                        Err(e) => {
+                            self.error_message = Some(format!("cannot stage lines: {:#}", e));
+                        }
+                    }
+                }
+            }
+            KeyCode::Char('Z') if matches!(self.mode, AppMode::Status | AppMode::Log) => self.open_package_picker(),
+            KeyCode::Char('D') if self.mode == AppMode::Status => self.open_diff_base_picker()?,
+            KeyCode::Char('F') if self.mode == AppMode::Status => {
+                let dir = self.repo.status_scope().unwrap_or("").trim_end_matches('/').to_string();
+                self.load_scope_picker(&dir);
+                if self.error_message.is_none() {
+                    self.mode = AppMode::ScopePicker;
+                }
+            }
+            KeyCode::Char('A') if self.mode == AppMode::Status => {
+                match self.repo.read_gitattributes() {
+                    Ok(lines) => {
+                        self.selected_attribute = lines.len().saturating_sub(1);
+                        self.attributes_lines = lines;
+                        self.attributes_input = self.get_selected_file_path()
+                            .map(|path| format!("{} -diff", path))
+                            .unwrap_or_default();
+                        self.mode = AppMode::AttributesDialog;
+                    }
+                    Err(e) => {
+                        self.error_message = Some(format!("cannot edit .gitattributes: {:#}", e));
+                    }
+                }
+            }
+            KeyCode::Char('N') => {
+                if self.is_main_view() {
+                    self.popup_return_mode = self.mode;
+                    self.notification_scroll = 0;
+                    self.mode = AppMode::NotificationHistory;
+                }
+            }
+            KeyCode::Char('!') => {
+                if self.is_main_view() {
+                    self.open_palette();
+                    self.palette_query = "run: ".to_string();
+                }
+            }
+            KeyCode::Char('%') => {
+                if self.is_main_view() {
+                    self.popup_return_mode = self.mode;
+                    self.stats_weeks = self.repo.config_string("hydra.statsWeeks")
+                        .and_then(|w| w.trim().parse().ok())
+                        .unwrap_or(stats::DEFAULT_WEEKS)
+                        .clamp(1, stats::MAX_WEEKS);
+                    self.stats_author = 0;
+                    self.load_contributions();
+                    self.mode = AppMode::Stats;
+                }
+            }
+            KeyCode::Char('K') => {
+                if self.is_main_view() {
+                    self.popup_return_mode = self.mode;
+                    self.selected_hook = 0;
+                    self.hook_output = None;
+                    self.load_hooks();
+                    self.mode = AppMode::Hooks;
+                }
+            }
+            KeyCode::Char('Q') => {
+                if self.is_main_view() {
+                    self.toggle_recording(false);
+                }
+            }
+            KeyCode::Char('@') => {
+                if self.is_main_view() {
+                    self.replay_macro()?;
+                }
+            }
+            KeyCode::Char('E') => {
+                if self.is_main_view() {
+                    self.popup_return_mode = self.mode;
+                    self.selected_error = 0;
+                    self.error_scroll = 0;
+                    self.mode = AppMode::ErrorLog;
+                }
+            }
+            KeyCode::Char(':') => {
+                if self.is_main_view() {
+                    self.open_palette();
+                }
+            }
+            KeyCode::Char('g') if self.mode == AppMode::Log => {
+                self.jump_input.clear();
+                self.mode = AppMode::JumpDialog;
+            }
+            KeyCode::Char('H') if self.mode == AppMode::Log => {
+                self.jump_to("HEAD")?;
+            }
+            KeyCode::Char('g') if self.mode == AppMode::Branches => {
+                if let Some(branch) = self.branches.get(self.views.branches.selected).cloned() {
+                    self.mode = AppMode::Log;
+                    self.jump_to(&branch)?;
+                }
+            }
+            KeyCode::Char('G') if self.mode == AppMode::Log => {
+                // stay on the same commit, it moves when the order changes
+                let selected = self.commits.get(self.views.log.selected).map(|c| c.id.clone());
+                self.show_graph = !self.show_graph;
+                self.refresh_data()?;
+                self.views.log.selected = selected
+                    .and_then(|id| self.commits.iter().position(|c| c.id == id))
+                    .unwrap_or(0);
+                self.load_selected_signature();
+            }
+            KeyCode::Char('S') if self.mode == AppMode::Log => {
+                self.pickaxe_query = self.log_filter.clone().unwrap_or_default();
+                self.mode = AppMode::PickaxeDialog;
+            }
+            KeyCode::Esc if self.mode == AppMode::Log && self.log_filter.is_some() => {
+                self.log_filter = None;
+                self.views.log.selected = 0;
+                self.refresh_data()?;
+            }
+            KeyCode::Esc => {
+                self.notifications.dismiss();
+            }
+            KeyCode::Char('x') => {
+                let target = match self.mode {
+                    AppMode::Log => self.commits.get(self.views.log.selected).map(|c| c.id.clone()),
+                    AppMode::Status => Some("HEAD".to_string()),
+                    _ => None,
+                };
+                if let Some(target) = target {
+                    self.archive_path = format!("{}.tar", self.archive_prefix(&target));
+                    self.archive_target = target;
+                    self.mode = AppMode::ArchiveDialog;
+                }
+            }
+            KeyCode::Char('d') => {
+                if self.mode == AppMode::Status {
+                    self.open_difftool()?;
+                }
+            }
+            KeyCode::Char('o') => {
+                let commit_id = match self.mode {
+                    AppMode::Log => self.commits.get(self.views.log.selected).map(|c| c.id.clone()),
+                    _ => None,
+                };
+                match self.repo.get_web_url(commit_id.as_deref()) {
+                    Ok(url) => {
+                        if let Err(e) = open_url(&url) {
+                            self.error_message = Some(format!("failed to open browser: {:#}", e));
+                        } else {
+                            self.notify(format!("opened {}", url));
+                        }
+                    }
+                    Err(e) => {
+                        self.error_message = Some(format!("cannot open on web: {:#}", e));
+                    }
+                }
+            }
+            KeyCode::Char('Y') => {
+                self.copy_selection(true);
+            }
+            KeyCode::Char('i') | KeyCode::Char('I') if self.mode == AppMode::Log => {
+                let refs = self.selected_issue_refs();
+                if refs.is_empty() {
+                    self.error_message = Some("no issue references in this commit.".to_string());
+                } else if key == KeyCode::Char('I') {
+                    let urls: Vec<&str> = refs.iter().map(|r| r.url.as_str()).collect();
+                    match clipboard::copy(&urls.join("\n")) {
+                        Ok(_) => self.notify(format!("copied {} issue link(s) to clipboard.", urls.len())),
+                        Err(e) => self.error_message = Some(format!("failed to copy: {:#}", e)),
+                    }
+                } else {
+                    // the first one, the rest are listed in the details pane to copy
+                    let url = &refs[0].url;
+                    if let Err(e) = open_url(url) {
+                        self.error_message = Some(format!("failed to open browser: {:#}", e));
+                    } else {
+                        self.notify(format!("opened {}", url));
+                    }
+                }
+            }
+            KeyCode::Char('m') => {
+                if let Ok(Some(_)) = self.repo.detect_merge_conflicts() {
+                    self.mode = AppMode::MergeConflict;
+                    self.refresh_data()?;
+                } else {
+                    self.error_message = Some("no merge conflicts detected.".to_string());
+                }
+            }
+            KeyCode::Up => {
+                match self.mode {
+                    AppMode::Log => {
+                        if self.views.log.selected > 0 {
+                            self.views.log.selected -= 1;
+                            self.views.log.scroll = 0;
+                            self.load_selected_signature();
+                        }
+                    }
+                    AppMode::Status => {
+                        if self.views.status.selected > 0 {
+                            self.views.status.selected -= 1;
+                            self.views.status.scroll = 0;
+                        }
+                    }
+                    AppMode::Branches => {
+                        if self.views.branches.selected > 0 {
+                            self.views.branches.selected -= 1;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            KeyCode::Down => {
+                match self.mode {
+                    AppMode::Log => {
+                        if self.views.log.selected + 1 >= self.commits.len() && self.commits.len() >= self.log_limit {
+                            // at the bottom of what's loaded, pull in the next page
+                            self.log_limit += LOG_PAGE_SIZE;
+                            self.refresh_data()?;
+                        }
+                        if self.views.log.selected + 1 < self.commits.len() {
+                            self.views.log.selected += 1;
+                            self.views.log.scroll = 0;
+                            self.load_selected_signature();
+                        }
+                    }
+                    AppMode::Status => {
+                        if let Some(status) = &self.status {
+                            let total_files = status.entry_count();
+                            if self.views.status.selected + 1 < total_files {
+                                self.views.status.selected += 1;
+                                self.views.status.scroll = 0;
+
+                            }
+                        }
+                    }
+                    AppMode::Branches => {
+                        if self.views.branches.selected + 1 < self.branches.len() {
+                            self.views.branches.selected += 1;
+                        }
+                    }
+
+                    _ => {}
+                }
+            }
+            KeyCode::F(5) => {
+                self.refresh_data()?;
+            }
+            KeyCode::Enter => {
+                match self.mode {
+                    AppMode::Status if self.selected_nested_repo().is_some() => {
+                        if let Some(nested) = self.selected_nested_repo() {
+                            let path = nested.path.clone();
+                            self.open_nested_repo(&path)?;
+                        }
+                    }
+                    AppMode::Status => {
+                        if let Some(status) = &self.status {
+                            let total_files = status.staged.len() + status.unstaged.len() + status.untracked.len();
+                            if self.views.status.selected < total_files {
+                                if let Some(file_path) = self.get_selected_file_path() {
+                                    if self.views.status.selected < status.staged.len() {
+                                        if let Err(e) = self.repo.unstage_file(&file_path){
+                                            self.error_message = Some(format!("failed to unstage: {:#}", e));
+                                        } else {
+                                            self.refresh_data()?;
+                                        }
+                                    } else {
+                                        if let Err(e) = self.repo.stage_file(&file_path){
+                                            self.error_message = Some(format!("failed to stage: {:#}", e));
+
+                                        } else {
+                                            self.refresh_data()?;
+                                        }
+                                    }
+                                    self.refresh_data()?;
+                                } 
+                            }
+                        }
+                    }
+                    AppMode::Log if self.log_mark.is_some() => self.open_range_view(),
+                    AppMode::Log if !self.commits.is_empty() => self.open_commit_view(),
+                    AppMode::Branches => {
+                        if self.views.branches.selected < self.branches.len() {
+                            let branch = &self.branches[self.views.branches.selected];
+                            if !branch.starts_with("origin/"){
+                                let branch = branch.clone();
+                                self.checkout(&branch, true)?;
+                            }
+                        } else {
+                            self.error_message = Some("cannot checkout remote branch directly.".to_string());
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            
+            // this is staged by git-hydra!
+            KeyCode::Char(' ') => {
+                if let Some(status) = &self.status{
+                    let total_files = status.staged.len() + status.unstaged.len() + status.untracked.len();
+                    if self.views.status.selected < total_files {
+                        if let Some(file_path) = self.get_selected_file_path() {
+                            if self.views.status.selected < status.staged.len() {
+                                if let Err(e) = self.repo.unstage_file(&file_path){
+                                    self.error_message = Some(format!("failed to unstage: {:#}", e));
+                                } else {
+                                    self.refresh_data()?;
+                                }
+                            } else {
+                                if let Err(e) = self.repo.stage_file(&file_path){
+                                    self.error_message = Some(format!("failed to stage: {:#}", e));
+
+                                } else {
+                                    self.refresh_data()?;
+                                }
+                            }
+                            self.refresh_data()?;
+                        } 
+                    }
+                }
+            }
+
+            KeyCode::PageUp => {
+                if self.mode == AppMode::Status {
+                    self.views.status.scroll = self.views.status.scroll.saturating_sub(10);
+                } else if self.mode == AppMode::Log {
+                    self.views.log.scroll = self.views.log.scroll.saturating_sub(5);
+                }
+            }
+            KeyCode::PageDown => {
+                if self.mode == AppMode::Status {
+                    self.views.status.scroll = self.views.status.scroll.saturating_add(10);
+                } else if self.mode == AppMode::Log {
+                    self.views.log.scroll = self.views.log.scroll.saturating_add(5);
+                }
+            }
+            KeyCode::Home => {
+                if self.mode == AppMode::Status {
+                    self.views.status.scroll = 0;
+                } else if self.mode == AppMode::Log {
+                    self.views.log.scroll = 0;
+                }
+            }
+            KeyCode::End => {
+                if self.mode == AppMode::Status {
+                    self.views.status.scroll += 1000;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    // first lines of recent commits, so "same as last time" messages are an Up away
+    fn recent_commit_messages(&self) -> Vec<String> {
+        self.repo.get_commits(20)
+            .map(|commits| commits.iter()
+                .map(|c| c.message.lines().next().unwrap_or("").to_string())
+                .collect())
+            .unwrap_or_default()
+    }
+
+    fn recent_stash_messages(&mut self) -> Vec<String> {
+        self.repo.stash_list()
+            .map(|stashes| stashes.into_iter().map(|s| s.message).collect())
+            .unwrap_or_default()
+    }
+
+    pub fn notify(&mut self, message: impl Into<String>) {
+        let message = message.into();
+        log::info!("{}", message);
+        self.notifications.push(Level::Success, message);
+    }
+
+    // the view under the palette / notification popup, or the current mode otherwise
+    pub fn overlay_return_mode(&self) -> AppMode {
+        match self.mode {
+            AppMode::CommandPalette => self.palette_return_mode,
+            AppMode::NotificationHistory | AppMode::ErrorLog | AppMode::LogViewer | AppMode::CommandOutput | AppMode::Hooks | AppMode::Stats | AppMode::LargeObjects | AppMode::Maintenance | AppMode::Health | AppMode::Help | AppMode::ReleaseNotes | AppMode::NewRelease | AppMode::PackagePicker | AppMode::Clean | AppMode::RebaseOntoDialog | AppMode::StackView | AppMode::AutostashDialog | AppMode::CredentialDialog | AppMode::Files => self.popup_return_mode,
+            mode => mode,
+        }
+    }
+
+    fn is_main_view(&self) -> bool {
+        matches!(
+            self.mode,
+            AppMode::Status | AppMode::Log | AppMode::Branches | AppMode::StashList | AppMode::RemoteOperations | AppMode::MergeConflict
+        )
+    }
+
+    // on the page for the view it's opened from
+    fn open_help(&mut self) {
+        let topic = match self.mode {
+            AppMode::Log => "log",
+            AppMode::Branches => "branches",
+            AppMode::StashList => "stashes",
+            AppMode::RemoteOperations => "remote",
+            AppMode::MergeConflict => "conflicts",
+            _ => "status",
+        };
+        self.popup_return_mode = self.mode;
+        self.help_page = help::find(topic).unwrap_or(0);
+        self.help_scroll = 0;
+        self.help_searching = false;
+        self.mode = AppMode::Help;
+    }
+
+    // the last tag..HEAD the first time, whatever range was typed after that
+    fn open_release_notes(&mut self) {
+        if self.notes_range.is_empty() {
+            self.notes_range = release_notes::default_range(&self.repo);
+        }
+        self.popup_return_mode = self.mode;
+        self.notes_input = None;
+        self.generate_notes();
+        self.mode = AppMode::ReleaseNotes;
+    }
+
+    fn generate_notes(&mut self) {
+        self.notes_scroll = 0;
+        match release_notes::generate(&self.repo, &self.notes_range) {
+            Ok((notes, errors)) => {
+                self.notes = notes;
+                if !errors.is_empty() {
+                    self.error_message = Some(errors.join("\n"));
+                }
+            }
+            Err(e) => {
+                self.notes.clear();
+                self.error_message = Some(format!("failed to list the commits in {}: {:#}", self.notes_range, e));
+            }
+        }
+    }
+
+    fn open_new_release(&mut self) {
+        match Plan::load(&self.repo) {
+            Ok((plan, _)) if plan.commits.is_empty() => {
+                let since = plan.latest.as_deref().unwrap_or("HEAD");
+                self.error_message = Some(format!("nothing new since {}, there's nothing to release.", since));
+            }
+            Ok((plan, errors)) => {
+                if !errors.is_empty() {
+                    self.error_message = Some(errors.join("\n"));
+                }
+                self.release_plan = Some(plan);
+                self.release_scroll = 0;
+                if let Ok(remotes) = self.repo.get_remotes() {
+                    self.remotes = remotes;
+                }
+                self.release_remote = self.remotes.iter().position(|r| r == "origin").unwrap_or(0);
+                self.popup_return_mode = self.mode;
+                self.mode = AppMode::NewRelease;
+            }
+            Err(e) => self.error_message = Some(format!("failed to work out the next version: {:#}", e)),
+        }
+    }
+
+    // the annotated tag, then the push of it if that's on. a failed push leaves the tag in place
+    fn create_release(&mut self) -> Result<()> {
+        if self.release_push && self.offline_blocks("pushing the tag") {
+            return Ok(());
+        }
+        let Some(plan) = self.release_plan.as_ref() else {
+            return Ok(());
+        };
+        let tag = plan.tag(plan.bump);
+        if self.release_push && self.remotes.is_empty() {
+            self.error_message = Some("no remotes configured to push the tag to, p to only tag it here.".to_string());
+            return Ok(());
+        }
+        if let Err(e) = self.repo.create_annotated_tag(&tag, &plan.notes(&self.repo)) {
+            self.error_message = Some(format!("failed to tag {}: {:#}", tag, e));
+            return Ok(());
+        }
+        let remote = self.remotes.get(self.release_remote).cloned().filter(|_| self.release_push);
+        match remote {
+            Some(remote) => {
+                let refname = format!("refs/tags/{}", tag);
+                let refspec = PushRefspec { force: false, src: Some(refname.clone()), dst: refname };
+                self.is_pushing = true;
+                match self.perf.time(Op::Network, &remote, || self.repo.push_refspecs(&remote, &[refspec])) {
+                    Ok(_) => self.notify(format!("tagged {} and pushed it to {}", tag, remote)),
+                    Err(e) => self.error_message = Some(format!("tagged {}, but the push failed: {:#}", tag, e)),
+                }
+                self.is_pushing = false;
+            }
+            None => self.notify(format!("tagged {}", tag)),
+        }
+        self.release_plan = None;
+        self.mode = self.popup_return_mode;
+        self.refresh_data()
+    }
+
+    // help_match wraps around the matches of every page
+    fn show_help_match(&mut self) {
+        let matches = help::search(&self.help_query);
+        if matches.is_empty() {
+            if !self.help_query.is_empty() {
+                self.error_message = Some(format!("'{}' isn't in the help.", self.help_query));
+            }
+            return;
+        }
+        self.help_match = if self.help_match == usize::MAX { matches.len() - 1 } else { self.help_match % matches.len() };
+        let (page, line) = matches[self.help_match];
+        self.help_page = page;
+        // a couple of lines of context above it
+        self.help_scroll = line.saturating_sub(2) as u16;
+    }
+
+    fn open_palette(&mut self) {
+        self.palette_return_mode = self.mode;
+        self.palette_query.clear();
+        self.selected_palette_item = 0;
+        self.mode = AppMode::CommandPalette;
+
+        let (macros, mut errors) = macros::load(&self.repo.config_values("hydra.macro"));
+        self.macros = macros;
+        let (custom_commands, command_errors) = commands::load(
+            &self.repo.config_values("hydra.command"),
+            &self.repo.config_values("hydra.panelCommand"),
+        );
+        self.custom_commands = custom_commands;
+        errors.extend(command_errors);
+        if !errors.is_empty() {
+            self.error_message = Some(errors.join("\n"));
+        }
+        // the : that opened it is already recorded
+        if let Some(keys) = &self.recording {
+            self.recording_palette_mark = keys.len().saturating_sub(1);
+        }
+    }
+
+    // the config macros and commands, for the palette to list after the built in ones
+    pub fn palette_extras(&self) -> Vec<PaletteCommand<'_>> {
+        let macros = self.macros
+            .iter()
+            .enumerate()
+            .map(|(i, m)| PaletteCommand { action: Action::Macro(i), name: &m.label, key: "" });
+        let custom = self.custom_commands
+            .iter()
+            .enumerate()
+            .map(|(i, c)| PaletteCommand { action: Action::Custom(i), name: &c.label, key: "" });
+        macros.chain(custom).collect()
+    }
+
+    // from the log: move what's after the selected commit. from branches: move the selected
+    // branch off whatever it tracks
+    fn open_rebase_onto(&mut self) -> Result<()> {
+        let current = self.repo.get_current_branch()?;
+        self.onto_fields = match self.mode {
+            AppMode::Log => {
+                let old = self.commits.get(self.views.log.selected).map(|c| self.repo.short_id(&c.id)).unwrap_or_default();
+                [String::new(), old, current]
+            }
+            _ => {
+                let tip = self.branches.get(self.views.branches.selected).cloned().unwrap_or(current);
+                let old = self.branch_upstreams.get(&tip).map(|u| u.name.clone()).unwrap_or_default();
+                [String::new(), old, tip]
+            }
+        };
+        self.onto_field = self.onto_fields.iter().position(|f| f.is_empty()).unwrap_or(0);
+        self.selected_onto_candidate = 0;
+
+        self.onto_candidates = self.repo.get_branches()?
+            .into_iter()
+            .filter(|b| !b.ends_with("/HEAD"))
+            .map(|b| (b, "branch".to_string()))
+            .collect();
+        for commit in self.repo.get_commits(200)? {
+            let summary = commit.message.lines().next().unwrap_or("").to_string();
+            self.onto_candidates.push((self.repo.short_id(&commit.id), summary));
+        }
+        self.popup_return_mode = self.mode;
+        self.mode = AppMode::RebaseOntoDialog;
+        Ok(())
+    }
+
+    pub fn filtered_onto_candidates(&self) -> Vec<&(String, String)> {
+        let needle = self.onto_fields[self.onto_field].trim().to_lowercase();
+        self.onto_candidates
+            .iter()
+            .filter(|(name, detail)| name.to_lowercase().contains(&needle) || detail.to_lowercase().contains(&needle))
+            .collect()
+    }
+
+    fn load_large_objects(&mut self) {
+        match self.repo.large_objects(LARGE_OBJECT_LIMIT) {
+            Ok((objects, totals)) => {
+                self.large_objects = objects;
+                self.object_totals = totals;
+            }
+            Err(e) => self.error_message = Some(format!("failed to scan the object database: {:#}", e)),
+        }
+        self.selected_large_object = self.selected_large_object.min(self.large_objects.len().saturating_sub(1));
+    }
+
+    fn load_health(&mut self) {
+        match self.repo.health_check() {
+            Ok(report) => self.health = report,
+            Err(e) => self.error_message = Some(format!("health check failed: {:#}", e)),
+        }
+        self.selected_health_issue = self.selected_health_issue.min(self.health.issues.len().saturating_sub(1));
+    }
+
+    // what enter does to the selected issue, the check runs again afterwards
+    fn fix_health_issue(&mut self) -> Result<()> {
+        let Some(issue) = self.health.issues.get(self.selected_health_issue).cloned() else {
+            return Ok(());
+        };
+        match issue {
+            HealthIssue::Lock { .. } if git_running() == Some(true) => {
+                self.error_message = Some("a git process is running, it may be holding that lock. try again once it's done.".to_string());
+                return Ok(());
+            }
+            HealthIssue::Lock { path, .. } => match self.repo.remove_lock(&path) {
+                Ok(()) => self.notify(format!("removed {}", path.display())),
+                Err(e) => self.error_message = Some(format!("{:#}", e)),
+            },
+            HealthIssue::Dangling(commit) => {
+                let short = self.repo.short_id(&commit.id);
+                let branch = format!("recovered-{}", short);
+                match self.repo.recover_commit(&commit.id, &branch) {
+                    Ok(()) => self.notify(format!("created {} at {}", branch, short)),
+                    Err(e) => self.error_message = Some(format!("failed to recover {}: {:#}", short, e)),
+                }
+            }
+            HealthIssue::BrokenRef { name, .. } if !name.is_empty() => match self.repo.delete_broken_ref(&name) {
+                Ok(()) => self.notify(format!("deleted {}", name)),
+                Err(e) => self.error_message = Some(format!("{:#}", e)),
+            },
+            _ => {
+                self.error_message = Some("hydra can't fix this one, `git fsck --full` says more about it.".to_string());
+                return Ok(());
+            }
+        }
+        self.load_health();
+        self.refresh_data()
+    }
+
+    fn load_contributions(&mut self) {
+        // whoever was picked stays picked when the range changes, if they're still in it
+        let picked = stats::authors(&self.contributions).get(self.stats_author.wrapping_sub(1)).map(|(name, _)| name.clone());
+        let since = stats::first_day(chrono::Local::now().date_naive(), self.stats_weeks);
+        match self.repo.contributions(since) {
+            Ok(contributions) => self.contributions = contributions,
+            Err(e) => self.error_message = Some(format!("failed to read history for stats: {:#}", e)),
+        }
+        self.stats_author = picked
+            .and_then(|name| stats::authors(&self.contributions).iter().position(|(n, _)| *n == name))
+            .map_or(0, |i| i + 1);
+    }
+
+    fn open_commit_view(&mut self) {
+        let Some(commit) = self.commits.get(self.views.log.selected) else {
+            return;
+        };
+        let id = commit.id.clone();
+        match self.perf.time(Op::Diff, &self.repo.short_id(&id), || self.repo.commit_diff(&id)) {
+            Ok(diff) => {
+                self.commit_view = Some(diff);
+                self.commit_view_scroll = 0;
+                self.commit_range = None;
+                self.mode = AppMode::CommitView;
+            }
+            Err(e) => self.error_message = Some(format!("failed to load commit {}: {:#}", self.repo.short_id(&id), e)),
+        }
+    }
+
+    fn open_files(&mut self, rev: &str) {
+        if !self.repo.has_commits() {
+            self.error_message = Some("no commits yet, there are no files to browse.".to_string());
+            return;
+        }
+        match FileBrowser::open(&self.repo, rev) {
+            Ok(files) => {
+                self.files = Some(files);
+                self.popup_return_mode = self.mode;
+                self.mode = AppMode::Files;
+            }
+            Err(e) => self.error_message = Some(format!("failed to read the files of {}: {:#}", rev, e)),
+        }
+    }
+
+    fn handle_files_key(&mut self, key: KeyCode) {
+        let Some(mut files) = self.files.take() else {
+            self.mode = self.popup_return_mode;
+            return;
+        };
+        let result = if files.export.is_some() {
+            self.export_file_key(&mut files, key);
+            Ok(())
+        } else if files.search.is_some() {
+            files.search_key(&self.repo, key)
+        } else {
+            match key {
+                KeyCode::Esc => {
+                    self.mode = self.popup_return_mode;
+                    return;
+                }
+                KeyCode::Up => {
+                    files.select(&self.repo, files.view.selected.saturating_sub(1));
+                    Ok(())
+                }
+                KeyCode::Down => {
+                    files.select(&self.repo, files.view.selected + 1);
+                    Ok(())
+                }
+                KeyCode::Enter | KeyCode::Right => files.open_selected(&self.repo).map(drop),
+                KeyCode::Left | KeyCode::Backspace => files.up(&self.repo),
+                KeyCode::PageUp => {
+                    files.view.scroll = files.view.scroll.saturating_sub(20);
+                    Ok(())
+                }
+                // to the last line here, to what fits when drawn
+                KeyCode::PageDown => {
+                    let len = match &files.preview {
+                        Preview::Text(lines) => lines.len(),
+                        _ => 0,
+                    };
+                    files.view.scroll = (files.view.scroll + 20).min(len.saturating_sub(1));
+                    Ok(())
+                }
+                KeyCode::Char('/') => files.start_search(&self.repo),
+                KeyCode::Char('x') => {
+                    match files.export_name(&self.repo) {
+                        Some(name) => files.export = Some(name),
+                        None => self.error_message = Some("only a file can be exported, not a directory.".to_string()),
+                    }
+                    Ok(())
+                }
+                KeyCode::Char('y') => {
+                    if let Some(path) = files.selected().map(|e| e.path.clone()) {
+                        match clipboard::copy(&path) {
+                            Ok(()) => self.notify(format!("copied {}", path)),
+                            Err(e) => self.error_message = Some(format!("copy failed: {:#}", e)),
+                        }
+                    }
+                    Ok(())
+                }
+                _ => Ok(()),
+            }
+        };
+        if let Err(e) = result {
+            self.error_message = Some(format!("{:#}", e));
+        }
+        self.files = Some(files);
+    }
+
+    // typing the file name for x
+    fn export_file_key(&mut self, files: &mut FileBrowser, key: KeyCode) {
+        let Some(name) = &mut files.export else {
+            return;
+        };
+        match key {
+            KeyCode::Esc => files.export = None,
+            KeyCode::Enter if !name.trim().is_empty() => {
+                let out = PathBuf::from(name.trim());
+                match files.export(&self.repo, &out) {
+                    Ok(bytes) => {
+                        let path = files.selected().map(|e| e.path.clone()).unwrap_or_default();
+                        self.notify(format!("wrote {} as of {} to {} ({} bytes)", path, files.label, out.display(), bytes));
+                        files.export = None;
+                    }
+                    Err(e) => self.error_message = Some(format!("export failed: {:#}", e)),
+                }
+            }
+            KeyCode::Char(c) => name.push(c),
+            KeyCode::Backspace => {
+                name.pop();
+            }
+            _ => {}
+        }
+    }
+
+    // the marked commit against the selected one, oldest of the two first like `git diff A..B`
+    fn open_range_view(&mut self) {
+        let (Some(mark), Some(selected)) = (self.log_mark.clone(), self.commits.get(self.views.log.selected)) else {
+            return;
+        };
+        if mark == selected.id {
+            self.open_commit_view();
+            return;
+        }
+        let selected = selected.id.clone();
+        // newest first in the list, a mark that's not in it anymore counts as the older one
+        let mark_below = self.commits.iter().position(|c| c.id == mark).is_none_or(|i| i > self.views.log.selected);
+        let (from, to) = if mark_below { (mark, selected) } else { (selected, mark) };
+        let label = format!("{}..{}", self.repo.short_id(&from), self.repo.short_id(&to));
+        match self.perf.time(Op::Diff, &label, || self.repo.range_diff(&from, &to)) {
+            Ok(diff) => {
+                self.commit_view = Some(diff);
+                self.commit_view_scroll = 0;
+                self.commit_range = Some((from, to));
+                self.mode = AppMode::CommitView;
+            }
+            Err(e) => self.error_message = Some(format!("failed to diff {}: {:#}", label, e)),
+        }
+    }
+
+    // n / p in the commit view: scroll to the next / previous file's patch
+    fn jump_to_file(&mut self, forward: bool) {
+        let Some(diff) = &self.commit_view else {
+            return;
+        };
+        let head = self.commit_view_head.get();
+        let scroll = self.commit_view_scroll;
+        let mut starts = diff.file_starts.iter().map(|start| head + start);
+        let target = match forward {
+            true => starts.find(|&at| at > scroll),
+            // before the first file is the header with the file list
+            false => starts.rev().find(|&at| at < scroll).or(Some(0)),
+        };
+        if let Some(at) = target {
+            self.commit_view_scroll = at;
+        }
+    }
+
+    fn load_stack(&mut self) {
+        match self.repo.branch_stack() {
+            Ok(stack) => self.stack = stack,
+            Err(e) => self.error_message = Some(format!("failed to work out the branch stack: {:#}", e)),
+        }
+        if self.selected_stacked >= self.stack.len() {
+            self.selected_stacked = self.stack.len().saturating_sub(1);
+        }
+    }
+
+    fn load_hooks(&mut self) {
+        match self.repo.list_hooks() {
+            Ok(hooks) => self.hooks = hooks,
+            Err(e) => self.error_message = Some(format!("failed to list hooks: {:#}", e)),
+        }
+        if self.selected_hook >= self.hooks.len() {
+            self.selected_hook = self.hooks.len().saturating_sub(1);
+        }
+    }
+
+    // in the background like a panel command, the menu's view is where esc on the output goes back to
+    fn run_maintenance_task(&mut self) {
+        let task = &maintenance::TASKS[self.selected_maintenance_task];
+        if self.running_command.as_ref().is_some_and(|r| r.status.is_none()) {
+            self.error_message = Some("a command is still running, k in its output panel stops it.".to_string());
+            return;
+        }
+        let line = format!("git {}", task.args.join(" "));
+        log::info!("running {}", line);
+        let mut cmd = self.repo.git_command();
+        cmd.args(task.args);
+        self.maintenance_before = Some(self.repo.objects_disk_size());
+        match RunningCommand::start(task.name, &line, cmd) {
+            Ok(running) => {
+                self.running_command = Some(running);
+                self.command_output_scroll = 0;
+                self.mode = AppMode::CommandOutput;
+            }
+            Err(e) => {
+                self.maintenance_before = None;
+                self.error_message = Some(format!("failed to run {}: {}", line, e));
+            }
+        }
+    }
+
+    fn run_custom_command(&mut self, index: usize) -> Result<()> {
+        let Some(command) = self.custom_commands.get(index).cloned() else {
+            return Ok(());
+        };
+        let vars = commands::Vars {
+            file: if self.mode == AppMode::Status { self.get_selected_file_path() } else { None },
+            branch: if self.detached_head.is_none() { self.repo.get_current_branch().ok() } else { None },
+            commit: if self.mode == AppMode::Log { self.commits.get(self.views.log.selected).map(|c| c.id.clone()) } else { None },
+            root: self.repo.workdir().map(|p| p.to_path_buf()),
+        };
+        let line = match commands::render(&command.template, &vars) {
+            Ok(line) => line,
+            Err(e) => {
+                self.error_message = Some(format!("can't run {}: {}", command.name, e));
+                return Ok(());
+            }
+        };
+        log::info!("running {}: {}", command.name, line);
+
+        if !command.panel {
+            self.hand_over_terminal(commands::shell(&line), true)?;
+            return self.refresh_data();
+        }
+        if self.running_command.as_ref().is_some_and(|r| r.status.is_none()) {
+            self.error_message = Some("a command is still running, k in its output panel stops it.".to_string());
+            return Ok(());
+        }
+        let mut cmd = commands::shell(&line);
+        if let Some(workdir) = self.repo.workdir() {
+            cmd.current_dir(workdir);
+        }
+        match RunningCommand::start(&command.name, &line, cmd) {
+            Ok(running) => {
+                self.running_command = Some(running);
+                self.command_output_scroll = 0;
+                self.popup_return_mode = self.mode;
+                self.mode = AppMode::CommandOutput;
+            }
+            Err(e) => self.error_message = Some(format!("failed to run {}: {}", command.name, e)),
+        }
+        Ok(())
+    }
+
+    fn toggle_recording(&mut self, from_palette: bool) {
+        match self.recording.take() {
+            Some(mut keys) => {
+                // stopped from the palette: opening it and picking the command aren't part of the macro
+                if from_palette {
+                    keys.truncate(self.recording_palette_mark);
+                }
+                self.notify(format!("recorded {} key(s), @ to replay", keys.len()));
+                self.recorded_macro = keys;
+            }
+            None => {
+                self.recording = Some(Vec::new());
+                self.notifications.push(Level::Info, "recording, Q to stop");
+            }
+        }
+    }
+
+    // the recorded keys go through the same handling as typing them, so does the view they were
+    // recorded in: the macro starts wherever it's replayed
+    fn replay_macro(&mut self) -> Result<()> {
+        if self.recording.is_some() {
+            self.error_message = Some("stop recording (Q) before replaying.".to_string());
+            return Ok(());
+        }
+        if self.recorded_macro.is_empty() {
+            self.error_message = Some("nothing recorded yet, Q starts recording.".to_string());
+            return Ok(());
+        }
+        let keys = self.recorded_macro.clone();
+        for (i, key) in keys.iter().enumerate() {
+            self.handle_key_event(*key)?;
+            if let Some(error) = &self.error_message {
+                self.error_message = Some(format!("macro stopped at key {} of {}: {}", i + 1, keys.len(), error));
+                return Ok(());
+            }
+        }
+        self.notify(format!("replayed {} key(s)", keys.len()));
+        Ok(())
+    }
+
+    fn run_macro(&mut self, index: usize) -> Result<()> {
+        let Some(m) = self.macros.get(index).cloned() else {
+            return Ok(());
+        };
+        for (name, action) in &m.steps {
+            if let Err(e) = self.dispatch(*action) {
+                self.error_message = Some(format!("macro '{}' stopped at '{}': {:#}", m.name, name, e));
+                return Ok(());
+            }
+            if let Some(error) = &self.error_message {
+                self.error_message = Some(format!("macro '{}' stopped at '{}': {}", m.name, name, error));
+                return Ok(());
+            }
+        }
+        self.notify(format!("ran macro {}", m.name));
+        Ok(())
+    }
+
+    // carries out an action, whether it came from a key, the palette or a macro
+    pub fn dispatch(&mut self, action: Action) -> Result<()> {
+        match action {
+            Action::GoStatus => self.go_to(AppMode::Status),
+            Action::GoLog => self.go_to(AppMode::Log),
+            Action::GoBranches => self.go_to(AppMode::Branches),
+            Action::GoStashes => self.go_to(AppMode::StashList),
+            Action::GoRemote => self.go_to(AppMode::RemoteOperations),
+            Action::StageAll => {
+                match self.repo.stage_all() {
+                    Ok(_) => self.notify("staged all changes".to_string()),
+                    Err(e) => self.error_message = Some(format!("failed to stage: {:#}", e)),
+                }
+                self.mode = AppMode::Status;
+                self.refresh_data()
+            }
+            Action::Commit => self.run_in_view(AppMode::Status, KeyCode::Char('c')),
+            Action::Stash => self.run_in_view(AppMode::Status, KeyCode::Char('s')),
+            Action::NewBranch => self.run_in_view(AppMode::Branches, KeyCode::Char('n')),
+            Action::Fetch => {
+                self.run_in_view(AppMode::RemoteOperations, KeyCode::F(5))?;
+                if self.offline_blocks("fetching") {
+                    return Ok(());
+                }
+                if let Some(remote) = self.remotes.get(self.views.remotes.selected).cloned() {
+                    match self.perf.time(Op::Network, &remote, || self.repo.fetch_remote(&remote)) {
+                        Ok(message) => self.notify(message),
+                        Err(e) => self.error_message = Some(format!("fetch failed: {:#}", e)),
+                    }
+                } else {
+                    self.error_message = Some("no remotes configured.".to_string());
+                }
+                Ok(())
+            }
+            Action::Push => self.run_in_view(AppMode::RemoteOperations, KeyCode::Char('p')),
+            Action::Pull => self.run_in_view(AppMode::RemoteOperations, KeyCode::Char('u')),
+            Action::CreateBundle => self.run_in_view(AppMode::RemoteOperations, KeyCode::Char('b')),
+            Action::FetchBundle => self.run_in_view(AppMode::RemoteOperations, KeyCode::Char('B')),
+            Action::ToggleGraph => self.run_in_view(AppMode::Log, KeyCode::Char('G')),
+            Action::AuthStatus => self.run_in_view(AppMode::RemoteOperations, KeyCode::Char('a')),
+            Action::ToggleIgnoreEol => self.run_in_view(AppMode::Status, KeyCode::Char('w')),
+            Action::TogglePerfHud => {
+                self.perf.visible = !self.perf.visible;
+                Ok(())
+            }
+            Action::ToggleOffline => {
+                self.set_offline(!self.offline);
+                self.notify(if self.offline { "offline: nothing goes over the network until it's toggled again" } else { "back online" }.to_string());
+                Ok(())
+            }
+            Action::SearchChanges => self.run_in_view(AppMode::Log, KeyCode::Char('S')),
+            Action::JumpToCommit => self.run_in_view(AppMode::Log, KeyCode::Char('g')),
+            Action::JumpToHead => self.run_in_view(AppMode::Log, KeyCode::Char('H')),
+            Action::Gitflow => self.run_in_view(AppMode::Branches, KeyCode::Char('f')),
+            Action::SetUpstream => self.run_in_view(AppMode::Branches, KeyCode::Char('t')),
+            Action::ListRemoteBranches => self.run_in_view(AppMode::Branches, KeyCode::Char('L')),
+            Action::PushBranches => self.run_in_view(AppMode::Branches, KeyCode::Char('P')),
+            Action::SyncFork => self.run_in_view(AppMode::RemoteOperations, KeyCode::Char('F')),
+            Action::RestoreFile => self.run_in_view(AppMode::Log, KeyCode::Char('R')),
+            Action::CheckoutCommit => self.run_in_view(AppMode::Log, KeyCode::Char('C')),
+            Action::MarkCommit => self.run_in_view(AppMode::Log, KeyCode::Char(' ')),
+            Action::OpenIssue => self.run_in_view(AppMode::Log, KeyCode::Char('i')),
+            Action::CopyIssueLinks => self.run_in_view(AppMode::Log, KeyCode::Char('I')),
+            Action::ScopeStatus => self.run_in_view(AppMode::Status, KeyCode::Char('F')),
+            Action::DiffBase => self.run_in_view(AppMode::Status, KeyCode::Char('D')),
+            Action::EditAttributes => self.run_in_view(AppMode::Status, KeyCode::Char('A')),
+            Action::StageLines => self.run_in_view(AppMode::Status, KeyCode::Char('v')),
+            Action::StageModeOnly => self.run_in_view(AppMode::Status, KeyCode::Char('X')),
+            Action::StageContentOnly => self.run_in_view(AppMode::Status, KeyCode::Char('T')),
+            Action::ExportArchive => {
+                let view = if self.mode == AppMode::Log { AppMode::Log } else { AppMode::Status };
+                self.run_in_view(view, KeyCode::Char('x'))
+            }
+            Action::BrowseFiles => {
+                let view = if self.mode == AppMode::Log { AppMode::Log } else { AppMode::Status };
+                self.run_in_view(view, KeyCode::Char('f'))
+            }
+            Action::OpenOnWeb => self.handle_view_key(KeyCode::Char('o')),
+            Action::SwitchRepo => {
+                self.switch_repo_path = self.repo.workdir()
+                    .map(|p| p.display().to_string())
+                    .unwrap_or_default();
+                self.mode = AppMode::SwitchRepoDialog;
+                Ok(())
+            }
+            Action::Notifications => self.handle_view_key(KeyCode::Char('N')),
+            Action::ErrorLog => self.handle_view_key(KeyCode::Char('E')),
+            Action::RecordMacro => {
+                self.toggle_recording(true);
+                Ok(())
+            }
+            Action::ReplayMacro => self.replay_macro(),
+            Action::Macro(index) => self.run_macro(index),
+            Action::Custom(index) => self.run_custom_command(index),
+            Action::Hooks => self.handle_view_key(KeyCode::Char('K')),
+            Action::Stats => self.handle_view_key(KeyCode::Char('%')),
+            Action::LargeObjects => {
+                self.popup_return_mode = self.mode;
+                self.selected_large_object = 0;
+                self.load_large_objects();
+                self.mode = AppMode::LargeObjects;
+                Ok(())
+            }
+            Action::Help => {
+                self.open_help();
+                Ok(())
+            }
+            Action::Packages => {
+                let view = if self.mode == AppMode::Log { AppMode::Log } else { AppMode::Status };
+                self.run_in_view(view, KeyCode::Char('Z'))
+            }
+            Action::ReleaseNotes => {
+                self.open_release_notes();
+                Ok(())
+            }
+            Action::NewRelease => {
+                self.open_new_release();
+                Ok(())
+            }
+            Action::Health => {
+                self.popup_return_mode = self.mode;
+                self.selected_health_issue = 0;
+                self.load_health();
+                self.mode = AppMode::Health;
+                Ok(())
+            }
+            Action::Clean => {
+                self.popup_return_mode = self.mode;
+                self.clean_preview = None;
+                self.clean_marked.clear();
+                self.selected_clean = 0;
+                self.load_clean_entries();
+                self.mode = AppMode::Clean;
+                Ok(())
+            }
+            Action::Maintenance => {
+                self.popup_return_mode = self.mode;
+                self.maintenance_size = self.repo.objects_disk_size();
+                self.mode = AppMode::Maintenance;
+                Ok(())
+            }
+            Action::RebaseOnto => self.run_in_view(AppMode::Log, KeyCode::Char('O')),
+            Action::Stacks => self.run_in_view(AppMode::Branches, KeyCode::Char('T')),
+            Action::CommandOutput => {
+                if self.running_command.is_none() {
+                    self.error_message = Some("no command has run yet.".to_string());
+                } else {
+                    self.popup_return_mode = self.mode;
+                    self.mode = AppMode::CommandOutput;
+                }
+                Ok(())
+            }
+            Action::LogViewer => {
+                self.handle_view_key(KeyCode::Char('E'))?;
+                self.handle_view_key(KeyCode::Tab)
+            }
+            Action::Refresh => self.handle_view_key(KeyCode::F(5)),
+            Action::Quit => {
+                self.should_quit = true;
+                Ok(())
+            }
+        }
+    }
+
+    fn go_to(&mut self, mode: AppMode) -> Result<()> {
+        self.mode = mode;
+        self.refresh_data()
+    }
+
+    // actions reuse the key handling of the view they belong to
+    fn run_in_view(&mut self, mode: AppMode, key: KeyCode) -> Result<()> {
+        if self.mode != mode {
+            self.mode = mode;
+            self.refresh_data()?;
+            // refresh_data puts us in conflict mode when there are conflicts, keys mean something else there
+            if self.mode != mode {
+                return Ok(());
+            }
+        }
+        self.handle_view_key(key)
+    }
+
+    fn switch_repo(&mut self, path: PathBuf) -> Result<()> {
+        let repo = match Repository::open(&path) {
+            Ok(repo) => repo,
+            Err(e) => {
+                self.error_message = Some(format!("cannot open {}: {:#}", path.display(), e));
+                return Ok(());
+            }
+        };
+
+        if let Err(e) = self.save_session() {
+            self.error_message = Some(format!("failed to save session state: {:#}", e));
+        }
+
+        let _ = self.avatars.clear(&mut io::stdout());
+        self.avatars = Avatars::new(repo.config_bool("hydra.avatars"));
+        self.avatars.set_offline(self.offline);
+        repo.set_offline(self.offline);
+        self.auto_fetch = AutoFetch::from_config(&repo);
+        // only when the run loop started one, the tests don't have it
+        if self.watcher.is_some() {
+            self.watcher = Some(Watcher::start(&repo, self.queue.sender()));
+        }
+        let (keymap, keymap_errors) = Keymap::from_config(&repo);
+        self.keymap = keymap;
+        if !keymap_errors.is_empty() {
+            self.error_message = Some(keymap_errors.join("\n"));
+        }
+        self.line_counts = LineCountCache::default();
+        self.repo = repo;
+        self.status = None;
+        self.commits.clear();
+        self.branches.clear();
+        self.stashes.clear();
+        self.stash_files.clear();
+        self.stash_preview = None;
+        self.remotes.clear();
+        self.views = Views::default();
+        self.merge_conflict = None;
+        self.conflict_resolutions.clear();
+        self.pending_stash_drop = None;
+        self.signatures.clear();
+        self.ref_map.clear();
+        self.issue_rules.clear();
+        self.remote_listing = None;
+        self.listing_focused = false;
+        self.log_filter = None;
+        self.log_limit = LOG_PAGE_SIZE;
+
+        self.restore_session();
+        self.notifications.push(Level::Info, format!("switched to {}", path.display()));
+        self.refresh_data()?;
+        Ok(())
+    }
+
+    // select a commit in the log by hash, branch, tag or any rev, loading more history if it's further down
+    fn jump_to(&mut self, rev: &str) -> Result<()> {
+        let commit_id = match self.repo.resolve_commit(rev) {
+            Ok(id) => id,
+            Err(e) => {
+                self.error_message = Some(format!("{:#}", e));
+                return Ok(());
+            }
+        };
+
+        // the filtered log may not contain it
+        self.log_filter = None;
+
+        let position = if self.show_graph {
+            self.graph_commits()?;
+            self.graph.as_ref().and_then(|g| g.position(git2::Oid::from_str(&commit_id).ok()?))
+        } else {
+            self.repo.commit_position(&commit_id)?
+        };
+        let position = match position {
+            Some(position) => position,
+            None => {
+                self.error_message = Some(format!("{} is not reachable from HEAD", self.repo.short_id(&commit_id)));
+                return Ok(());
+            }
+        };
+
+        if position >= self.log_limit {
+            self.log_limit = (position / LOG_PAGE_SIZE + 1) * LOG_PAGE_SIZE;
+        }
+        self.refresh_data()?;
+        self.views.log.selected = position.min(self.commits.len().saturating_sub(1));
+        self.load_selected_signature();
+        Ok(())
+    }
+
+    // the log in graph order, the cached layout brought up to HEAD first
+    fn graph_commits(&mut self) -> Result<Vec<Commit>> {
+        let path = session::repo_file(&self.session_key(), "graph");
+        let graph = self.graph.get_or_insert_with(|| path.as_deref().map(Graph::load).unwrap_or_default());
+        if self.repo.update_graph(graph)? {
+            if let Some(path) = &path {
+                if let Err(e) = graph.save(path) {
+                    self.error_message = Some(format!("failed to save the graph cache: {:#}", e));
+                }
+            }
+        }
+        self.repo.commits_by_id(&graph.top(self.log_limit))
+    }
+
+    fn load_scope_picker(&mut self, dir: &str) {
+        match self.repo.list_directories(dir) {
+            Ok(entries) => {
+                self.scope_picker_dir = dir.to_string();
+                self.scope_picker_entries = entries;
+                self.selected_scope_entry = 0;
+            }
+            Err(e) => {
+                self.error_message = Some(format!("cannot list {}: {:#}", if dir.is_empty() { "." } else { dir }, e));
+            }
+        }
+    }
+
+    // X / T on an unstaged file whose mode changed along with its content: stage one of the two
+    fn stage_mode_split(&mut self, mode_only: bool) -> Result<()> {
+        let Some(status) = &self.status else {
+            return Ok(());
+        };
+        let Some(file) = self.views.status.selected.checked_sub(status.staged.len()).and_then(|i| status.unstaged.get(i)).cloned() else {
+            self.error_message = Some("select an unstaged file whose mode changed.".to_string());
+            return Ok(());
+        };
+        let result = match mode_only {
+            true => self.repo.stage_mode_only(&file.path),
+            false => self.repo.stage_content_only(&file.path),
+        };
+        match result {
+            Ok(()) => {
+                let label = file.mode_label().unwrap_or_default();
+                match mode_only {
+                    true => self.notify(format!("staged the mode change of {} ({}), the content stays unstaged.", file.path, label)),
+                    false => self.notify(format!("staged the content of {}, the mode change ({}) stays unstaged.", file.path, label)),
+                }
+            }
+            Err(e) => self.error_message = Some(format!("cannot split the change: {:#}", e)),
+        }
+        self.refresh_data()
+    }
+
+    fn open_diff_base_picker(&mut self) -> Result<()> {
+        let mut candidates: Vec<(String, &'static str)> = self.repo.get_branches()?
+            .into_iter()
+            .filter(|b| !b.ends_with("/HEAD"))
+            .map(|b| {
+                let kind = if self.repo.local_branch_exists(&b) { "branch" } else { "remote branch" };
+                (b, kind)
+            })
+            .collect();
+        // the remote ones first, comparing against origin/main is what this is mostly for
+        candidates.sort_by_key(|(_, kind)| *kind == "branch");
+        candidates.extend(self.repo.tag_names()?.into_iter().map(|t| (t, "tag")));
+        self.diff_base_candidates = candidates;
+        self.diff_base_input = self.repo.diff_base().unwrap_or("").to_string();
+        self.selected_diff_base = 0;
+        self.mode = AppMode::DiffBasePicker;
+        Ok(())
+    }
+
+    pub fn filtered_diff_bases(&self) -> Vec<&(String, &'static str)> {
+        let needle = self.diff_base_input.trim().to_lowercase();
+        self.diff_base_candidates.iter().filter(|(name, _)| name.to_lowercase().contains(&needle)).collect()
+    }
+
+    fn apply_diff_base(&mut self, base: Option<&str>) -> Result<()> {
+        if let Err(e) = self.repo.set_diff_base(base) {
+            self.error_message = Some(format!("cannot diff against that: {:#}", e));
+            return Ok(());
+        }
+        match self.repo.diff_base() {
+            Some(base) => self.notify(format!("diffing against {}, D again to change it.", base)),
+            None => self.notify("diffing against the index / HEAD again."),
+        }
+        self.views.status.scroll = 0;
+        self.mode = AppMode::Status;
+        self.refresh_data()
+    }
+
+    fn load_clean_entries(&mut self) {
+        match self.repo.clean_candidates() {
+            Ok(entries) => {
+                self.clean_marked.retain(|path| entries.iter().any(|e| e.path == *path));
+                self.clean_entries = entries;
+                self.selected_clean = self.selected_clean.min(self.clean_entries.len().saturating_sub(1));
+            }
+            Err(e) => self.error_message = Some(format!("failed to list what git clean would remove: {:#}", e)),
+        }
+    }
+
+    // git clean -n of the marked entries (the selected one when none are), to say yes to
+    fn preview_clean(&mut self) {
+        let paths: Vec<String> = match self.clean_marked.is_empty() {
+            true => self.clean_entries.get(self.selected_clean).map(|e| e.path.clone()).into_iter().collect(),
+            false => self.clean_marked.iter().cloned().collect(),
+        };
+        if paths.is_empty() {
+            return;
+        }
+        match self.repo.clean_dry_run(&["-x"], &paths) {
+            Ok(would_remove) if would_remove.is_empty() => {
+                self.notify("nothing left to clean there.");
+                self.load_clean_entries();
+            }
+            Ok(would_remove) => self.clean_preview = Some(would_remove),
+            Err(e) => self.error_message = Some(format!("git clean -n failed: {:#}", e)),
+        }
+    }
+
+    fn run_clean(&mut self) -> Result<()> {
+        let Some(paths) = self.clean_preview.take() else {
+            return Ok(());
+        };
+        match self.repo.clean_paths(&paths) {
+            Ok(count) => self.notify(format!("removed {} untracked / ignored path(s).", count)),
+            Err(e) => self.error_message = Some(format!("git clean failed: {:#}", e)),
+        }
+        self.clean_marked.clear();
+        self.load_clean_entries();
+        self.refresh_data()
+    }
+
+    // the manifests are read again every time, members come and go
+    fn open_package_picker(&mut self) {
+        let Some(workdir) = self.repo.workdir() else {
+            self.error_message = Some("bare repository, there are no packages to pick from.".to_string());
+            return;
+        };
+        self.packages = packages::detect(workdir);
+        if self.packages.is_empty() {
+            self.error_message = Some("no packages here, that needs a Cargo.toml [workspace] or a package.json with workspaces.".to_string());
+            return;
+        }
+        self.selected_package = self
+            .active_package
+            .as_ref()
+            .and_then(|active| self.packages.iter().position(|p| p.path == active.path))
+            .map_or(0, |i| i + 1);
+        self.popup_return_mode = self.mode;
+        self.mode = AppMode::PackagePicker;
+    }
+
+    // status scoped to the package's directory, the log to the commits touching it. None is everything
+    fn apply_package(&mut self, package: Option<Package>) -> Result<()> {
+        if let Err(e) = self.repo.set_status_scope(package.as_ref().map(|p| p.path.as_str())) {
+            self.error_message = Some(format!("cannot limit to {}: {:#}", package.map_or(String::new(), |p| p.name), e));
+            return Ok(());
+        }
+        self.notifications.push(Level::Info, match &package {
+            Some(package) => format!("status and log limited to {} ({}/)", package.name, package.path),
+            None => "showing every package".to_string(),
+        });
+        self.active_package = package;
+        self.views.status.selected = 0;
+        self.views.log.selected = 0;
+        self.mode = self.popup_return_mode;
+        self.refresh_data()
+    }
+
+    fn apply_scope(&mut self, scope: Option<&str>) -> Result<()> {
+        if let Err(e) = self.repo.set_status_scope(scope) {
+            self.error_message = Some(format!("cannot scope status: {:#}", e));
+            return Ok(());
+        }
+        // a directory picked by hand isn't the package anymore
+        self.active_package = None;
+        self.notifications.push(Level::Info, match self.repo.status_scope() {
+            Some(scope) => format!("status limited to {}", scope),
+            None => "showing status for the whole repository".to_string(),
+        });
+        self.views.status.selected = 0;
+        self.mode = AppMode::Status;
+        self.refresh_data()?;
+        Ok(())
+    }
+
+    // nested entries come after every file in the status list
+    pub fn selected_nested_repo(&self) -> Option<&NestedRepo> {
+        let status = self.status.as_ref()?;
+        let files = status.staged.len() + status.unstaged.len() + status.untracked.len();
+        self.views.status.selected.checked_sub(files).and_then(|i| status.nested.get(i))
+    }
+
+    // flagged files come last, after the nested repos
+    pub fn selected_flagged_file(&self) -> Option<&FlaggedFile> {
+        let status = self.status.as_ref()?;
+        let before = status.staged.len() + status.unstaged.len() + status.untracked.len() + status.nested.len();
+        self.views.status.selected.checked_sub(before).and_then(|i| status.flagged.get(i))
+    }
+
+    // W / U on a tracked file or on an entry of the flagged section
+    fn toggle_index_flag(&mut self, flag: IndexFlag) -> Result<()> {
+        let (path, on) = match (self.selected_flagged_file(), self.get_selected_file_path()) {
+            (Some(file), _) => (file.path.clone(), !file.has(flag)),
+            (None, Some(path)) => {
+                let untracked = self.status.as_ref().is_some_and(|s| s.untracked.iter().any(|f| f.path == path));
+                if untracked {
+                    self.error_message = Some(format!("{} is untracked, only tracked files can be {}.", path, flag.label()));
+                    return Ok(());
+                }
+                (path, true)
+            }
+            (None, None) => return Ok(()),
+        };
+
+        match self.repo.set_index_flag(&path, flag, on) {
+            Ok(_) => {
+                let verb = if on { "set" } else { "cleared" };
+                self.notify(format!("{} {} on {}.", verb, flag.label(), path));
+                self.refresh_data()?;
+            }
+            Err(e) => self.error_message = Some(format!("failed to update {}: {:#}", path, e)),
+        }
+        Ok(())
+    }
+
+    // a second git-hydra on the nested repo, we pick up where we left off when it quits
+    fn open_nested_repo(&mut self, path: &str) -> Result<()> {
+        let Some(workdir) = self.repo.workdir() else {
+            return Ok(());
+        };
+        let mut cmd = Command::new(std::env::current_exe()?);
+        cmd.arg("--repo").arg(workdir.join(path));
+        self.run_external(cmd)?;
+        self.refresh_data()?;
+        Ok(())
+    }
+
+    // hand the terminal over to another program (editor, difftool...) and take it back afterwards
+    fn run_external(&mut self, cmd: Command) -> Result<()> {
+        self.hand_over_terminal(cmd, false)
+    }
+
+    // `pause` keeps the program's output up until enter, for ones that print something and exit
+    fn hand_over_terminal(&mut self, mut cmd: Command, pause: bool) -> Result<()> {
+        if let Some(workdir) = self.repo.workdir() {
+            cmd.current_dir(workdir);
+        }
+
+        disable_raw_mode()?;
+        execute!(io::stdout(), LeaveAlternateScreen, DisableMouseCapture)?;
+
+        let status = cmd.status();
+        if pause {
+            print!("\n[press enter to go back to git-hydra] ");
+            io::Write::flush(&mut io::stdout())?;
+            let _ = io::stdin().read_line(&mut String::new());
+        }
+
+        enable_raw_mode()?;
+        execute!(io::stdout(), EnterAlternateScreen, EnableMouseCapture)?;
+        self.needs_full_redraw = true;
+
+        match status {
+            Ok(s) if s.success() => {}
+            Ok(s) => self.error_message = Some(format!("{:?} exited with {}", cmd.get_program(), s)),
+            Err(e) => self.error_message = Some(format!("failed to run {:?}: {}", cmd.get_program(), e)),
+        }
+        Ok(())
+    }
+
+    fn open_in_editor(&mut self) -> Result<()> {
+        let Some(path) = self.get_selected_file_path() else {
+            self.error_message = Some("no file selected.".to_string());
+            return Ok(());
+        };
+
+        // jump to the first changed line if the diff has one
+        let line = self.repo.get_file_diff(&path).ok()
+            .and_then(|diff| first_changed_line(&diff));
+
+        let (mut cmd, program) = editor_command();
+        let is_vscode = program.ends_with("code") || program.ends_with("codium");
+        match line {
+            Some(line) if is_vscode => { cmd.arg("-g").arg(format!("{}:{}", path, line)); }
+            Some(line) => { cmd.arg(format!("+{}", line)).arg(&path); }
+            None => { cmd.arg(&path); }
+        }
+
+        self.run_external(cmd)?;
+        self.refresh_data()
+    }
+
+    fn open_difftool(&mut self) -> Result<()> {
+        let Some(path) = self.get_selected_file_path() else {
+            self.error_message = Some("no file selected.".to_string());
+            return Ok(());
+        };
+        let staged = self.status.as_ref()
+            .map(|s| self.views.status.selected < s.staged.len())
+            .unwrap_or(false);
+
+        let mut cmd = self.repo.git_command();
+        cmd.args(["difftool", "--no-prompt"]);
+        if staged {
+            cmd.arg("--cached");
+        }
+        cmd.arg("--").arg(&path);
+
+        self.run_external(cmd)?;
+        self.refresh_data()
+    }
+
+    // <repo dir>-<short id>, used for the default file name and the directory inside the archive
+    fn archive_prefix(&self, target: &str) -> String {
+        let repo_name = self.repo.workdir()
+            .and_then(|w| w.file_name())
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_else(|| "repo".to_string());
+        let short_id = if target == "HEAD" {
+            self.repo.get_commits(1).ok()
+                .and_then(|c| c.first().map(|c| self.repo.short_id(&c.id)))
+                .unwrap_or_else(|| "HEAD".to_string())
+        } else {
+            self.repo.short_id(target)
+        };
+        format!("{}-{}", repo_name, short_id)
+    }
+
+    fn export_archive(&mut self) {
+        let out_path = PathBuf::from(self.archive_path.trim());
+        let prefix = format!("{}/", self.archive_prefix(&self.archive_target));
+
+        match self.repo.archive_commit(&self.archive_target, &prefix, &out_path) {
+            Ok(count) => {
+                self.notify(format!("exported {} files to {}", count, out_path.display()));
+                self.mode = if self.archive_target == "HEAD" { AppMode::Status } else { AppMode::Log };
+                self.archive_path.clear();
+            }
+            Err(e) => {
+                self.error_message = Some(format!("export failed: {:#}", e));
+            }
+        }
+    }
+
+    fn run_bundle_action(&mut self) -> Result<()> {
+        let path = PathBuf::from(self.bundle_path.trim());
+        let result = match self.bundle_action {
+            BundleAction::Create => {
+                let refs: Vec<&str> = self.bundle_refs.split_whitespace().collect();
+                self.repo.create_bundle(&path, &refs)
+            }
+            BundleAction::Fetch => self.repo.fetch_bundle(&path),
+        };
+
+        match result {
+            Ok(message) => {
+                self.notify(message);
+                self.mode = AppMode::RemoteOperations;
+                self.refresh_data()?;
+            }
+            Err(e) => {
+                self.error_message = Some(format!("bundle failed: {:#}", e));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn selected_issue_refs(&self) -> Vec<IssueRef> {
+        self.commits
+            .get(self.views.log.selected)
+            .map(|c| issues::find_refs(&c.message, &self.issue_rules))
+            .unwrap_or_default()
+    }
+
+    // the commit view shows short ids, y copies the full one (both ends of a range), Y the parents'
+    fn copy_commit_ids(&mut self, parents: bool) {
+        let copied = match (&self.commit_range, self.commits.get(self.views.log.selected)) {
+            (Some((from, to)), _) if !parents => Some(("range", format!("{}..{}", from, to))),
+            (None, Some(commit)) if !parents => Some(("commit id", commit.id.clone())),
+            (None, Some(commit)) if !commit.parents.is_empty() => Some(("parent ids", commit.parents.join(" "))),
+            _ => None,
+        };
+        match copied {
+            Some((what, text)) => match clipboard::copy(&text) {
+                Ok(_) => self.notify(format!("copied {} to clipboard.", what)),
+                Err(e) => self.error_message = Some(format!("failed to copy: {:#}", e)),
+            },
+            None => self.error_message = Some("nothing to copy.".to_string()),
+        }
+    }
+
+    // y: copy whatever is selected in the current view, Y: copy the diff in status
+    fn copy_selection(&mut self, diff: bool) {
+        let selection = match self.mode {
+            AppMode::Status if diff => self.get_selected_file_path()
+                .and_then(|path| self.repo.get_file_diff(&path).ok())
+                .filter(|d| !d.is_empty())
+                .map(|d| ("diff", d)),
+            AppMode::Status => self.get_selected_file_path().map(|p| ("path", p)),
+            AppMode::Log => self.commits.get(self.views.log.selected).map(|c| ("commit hash", c.id.clone())),
+            AppMode::Branches => self.branches.get(self.views.branches.selected).map(|b| ("branch name", b.clone())),
+            AppMode::StashList => self.stashes.get(self.views.stashes.selected).map(|s| ("stash", s.to_string())),
+            AppMode::RemoteOperations => self.remotes.get(self.views.remotes.selected)
+                .and_then(|r| self.repo.get_remote_url(r).ok().flatten())
+                .map(|url| ("remote url", url)),
+            AppMode::MergeConflict => self.get_current_conflict_file().map(|f| ("path", f.path.clone())),
+            _ => None,
+        };
+
+        match selection {
+            Some((what, text)) => match clipboard::copy(&text) {
+                Ok(_) => self.notify(format!("copied {} to clipboard.", what)),
+                Err(e) => self.error_message = Some(format!("failed to copy: {:#}", e)),
+            },
+            None => self.error_message = Some("nothing to copy.".to_string()),
+        }
+    }
+
+    pub fn refresh_data(&mut self) -> Result<()> {
+        // a fetch elsewhere (auto-fetch's thread, another terminal) may have made one ambiguous
+        self.repo.forget_short_ids();
+        self.detached_head = self.repo.detached_head();
+        self.unborn_branch = self.repo.unborn_branch();
+        match self.repo.detect_merge_conflicts(){
+            Ok(Some(conflict)) => {
+                self.merge_conflict = Some(conflict);
+                if self.mode != AppMode::MergeConflict && self.mode != AppMode::MergeMessageDialog {
+                    self.mode = AppMode::MergeConflict;
+                    self.selected_conflict_file = 0;
+                    self.selected_conflict_hunk = 0;
+                    self.conflict_resolutions.clear();
+                }
+                return Ok(());
+            }
+            Ok(None) => {
+                if self.mode == AppMode::MergeConflict {
+                    self.merge_conflict = None;
+                    self.conflict_resolutions.clear();
+                    self.mode = AppMode::Status;
+                }
+            }
+            Err(e) => {
+                self.error_message = Some(format!("failed to detect merge conflicts: {:#}", e));
+            }
+        }
+        match self.mode {
+            AppMode::Status if self.repo.is_bare() => {
+                self.status = None;
+            }
+            AppMode::Status => {
+                let status = self.perf.time(Op::Status, "", || self.repo.status())?;
+                let total_files = status.entry_count();
+                if self.views.status.selected >= total_files {
+                    self.views.status.selected = total_files.saturating_sub(1);
+                }
+                self.status = Some(status);
+                self.line_counts.refresh(&self.repo);
+                self.eol_warnings = self.repo.eol_config_problems();
+                self.eol_churn.replace(None);
+                self.diff_attributes.replace(None);
+                self.base_changes = match self.repo.diff_base_changes() {
+                    Ok(files) => files,
+                    Err(e) => {
+                        self.error_message = Some(format!("cannot diff against {}: {:#}", self.repo.diff_base().unwrap_or(""), e));
+                        Vec::new()
+                    }
+                };
+            }
+            AppMode::Log => {
+                let started = Instant::now();
+                self.commits = match (&self.log_filter, &self.active_package) {
+                    (Some(needle), _) => self.repo.pickaxe_search(needle, self.log_limit)?,
+                    (None, Some(package)) => self.repo.path_commits(&package.path, self.log_limit)?,
+                    (None, None) if self.show_graph => self.graph_commits()?,
+                    (None, None) => self.repo.get_commits(self.log_limit)?,
+                };
+                let detail = match (&self.log_filter, &self.active_package, self.show_graph) {
+                    (Some(_), _, _) => "pickaxe",
+                    (None, Some(_), _) => "package",
+                    (None, None, true) => "graph",
+                    (None, None, false) => "",
+                };
+                self.perf.record(Op::Log, detail, started);
+                self.ref_map = self.repo.get_ref_map()?;
+                let (rules, errors) = self.repo.issue_rules();
+                self.issue_rules = rules;
+                if !errors.is_empty() {
+                    self.error_message = Some(errors.join("\n"));
+                }
+                self.absolute_dates = self.repo.config_bool("hydra.absoluteDates");
+                self.date_format = match self.repo.config_string("hydra.dateFormat") {
+                    // an unknown % would make chrono fail halfway through drawing
+                    Some(format) if chrono::format::StrftimeItems::new(&format).any(|item| item == chrono::format::Item::Error) => {
+                        self.error_message = Some(format!("hydra.dateFormat '{}' isn't a strftime format, e.g. %d.%m.%Y %H:%M", format));
+                        DEFAULT_DATE_FORMAT.to_string()
+                    }
+                    Some(format) => format,
+                    None => DEFAULT_DATE_FORMAT.to_string(),
+                };
+                if self.views.log.selected >= self.commits.len() {
+                    self.views.log.selected = self.commits.len().saturating_sub(1);
+                }
+                self.load_selected_signature();
+            }
+            AppMode::Branches => {
+                // the selection stays on the same branch when the list changes, and starts on HEAD's
+                let selected = self.branches.get(self.views.branches.selected).cloned();
+                self.current_branch = self.repo.get_current_branch()?;
+                self.branches = self.repo.get_branches()?;
+                self.views.branches.selected = selected.iter().chain([&self.current_branch])
+                    .find_map(|name| self.branches.iter().position(|b| b == name))
+                    .unwrap_or(0);
+                self.branch_upstreams = self.branches.iter()
+                    .filter_map(|b| self.repo.upstream(b).map(|u| (b.clone(), u)))
+                    .collect();
+                self.load_protection();
+            }
+            AppMode::StashList => {
+                self.stashes = self.repo.stash_list()?;
+                if self.views.stashes.selected >= self.stashes.len() {
+                    self.views.stashes.selected = self.stashes.len().saturating_sub(1);
+                }
+                self.load_selected_stash_files();
+
+            }
+            AppMode::RemoteOperations => {
+                self.remotes = self.repo.get_remotes()?;
+                self.current_branch = self.repo.get_current_branch()?;
+                self.remote_divergence = self.repo.remote_divergence(&self.current_branch)?;
+                if self.views.remotes.selected >= self.remotes.len() {
+                    self.views.remotes.selected = self.remotes.len().saturating_sub(1);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    // (hunk, None) for a hunk header, (hunk, Some(line)) for a line in it
+    pub fn line_rows(&self) -> Vec<(usize, Option<usize>)> {
+        let mut rows = Vec::new();
+        if let Some(file_diff) = &self.line_diff {
+            for (hunk_idx, hunk) in file_diff.hunks.iter().enumerate() {
+                rows.push((hunk_idx, None));
+                for line_idx in 0..hunk.lines.len() {
+                    rows.push((hunk_idx, Some(line_idx)));
+                }
+            }
+        }
+        rows
+    }
+
+    // on a header this flips the whole hunk
+    fn toggle_line_at_cursor(&mut self) {
+        let Some(&(hunk_idx, line)) = self.line_rows().get(self.line_cursor) else {
+            return;
+        };
+        let Some(hunk) = self.line_diff.as_ref().and_then(|d| d.hunks.get(hunk_idx)) else {
+            return;
+        };
+
+        let targets: Vec<(usize, usize)> = match line {
+            Some(line_idx) if hunk.lines[line_idx].is_change() => vec![(hunk_idx, line_idx)],
+            Some(_) => return,
+            None => hunk.lines.iter().enumerate()
+                .filter(|(_, l)| l.is_change())
+                .map(|(i, _)| (hunk_idx, i))
+                .collect(),
+        };
+
+        let all_selected = targets.iter().all(|t| self.selected_lines.contains(t));
+        for target in targets {
+            if all_selected {
+                self.selected_lines.remove(&target);
+            } else {
+                self.selected_lines.insert(target);
+            }
+        }
+
+        if line.is_some() && self.line_cursor + 1 < self.line_rows().len() {
+            self.line_cursor += 1;
+        }
+    }
+
+    fn apply_selected_lines(&mut self) -> Result<()> {
+        let Some(file_diff) = &self.line_diff else {
+            return Ok(());
+        };
+
+        if let Err(e) = self.repo.apply_line_selection(file_diff, &self.selected_lines, self.line_diff_staged) {
+            self.error_message = Some(format!("failed to {} lines: {:#}", if self.line_diff_staged { "unstage" } else { "stage" }, e));
+            return Ok(());
+        }
+
+        let path = file_diff.path.clone();
+        let count = self.selected_lines.len();
+        self.notify(format!("{} {} line(s)", if self.line_diff_staged { "unstaged" } else { "staged" }, count));
+        self.reload_line_diff(&path)
+    }
+
+    // the picked lines, or the whole hunk under the cursor when none are
+    fn confirm_discard(&mut self) {
+        let Some(file_diff) = &self.line_diff else {
+            return;
+        };
+        let lines = match self.selected_lines.is_empty() {
+            false => self.selected_lines.clone(),
+            true => {
+                let Some(&(hunk_idx, _)) = self.line_rows().get(self.line_cursor) else {
+                    return;
+                };
+                file_diff.hunks[hunk_idx].lines.iter().enumerate().filter(|(_, l)| l.is_change()).map(|(i, _)| (hunk_idx, i)).collect()
+            }
+        };
+        self.line_discard = Some(lines);
+    }
+
+    fn discard_lines(&mut self) -> Result<()> {
+        let (Some(lines), Some(file_diff)) = (self.line_discard.take(), &self.line_diff) else {
+            return Ok(());
+        };
+        if let Err(e) = self.repo.discard_line_selection(file_diff, &lines) {
+            self.error_message = Some(format!("failed to discard lines: {:#}", e));
+            return Ok(());
+        }
+        let path = file_diff.path.clone();
+        self.notify(format!("discarded {} line(s) of {} from the working tree", lines.len(), path));
+        self.reload_line_diff(&path)
+    }
+
+    // after staging or discarding: stay on the file while it still has changes on this side
+    fn reload_line_diff(&mut self, path: &str) -> Result<()> {
+        self.status = Some(self.repo.status()?);
+        self.line_counts.refresh(&self.repo);
+        self.selected_lines.clear();
+
+        match self.repo.get_file_hunks(path, self.line_diff_staged) {
+            Ok(file_diff) if !file_diff.hunks.is_empty() => {
+                self.line_cursor = self.line_cursor.min(file_diff.hunks.iter().map(|h| h.lines.len() + 1).sum::<usize>() - 1);
+                self.line_diff = Some(file_diff);
+            }
+            _ => {
+                self.line_diff = None;
+                self.mode = AppMode::Status;
+            }
+        }
+        Ok(())
+    }
+
+    fn save_attributes(&mut self) {
+        match self.repo.write_gitattributes(&self.attributes_lines) {
+            Ok(_) => {
+                self.diff_attributes.replace(None);
+                self.notify(".gitattributes updated".to_string());
+            }
+            Err(e) => self.error_message = Some(format!("failed to write .gitattributes: {:#}", e)),
+        }
+    }
+
+    fn load_selected_signature(&mut self) {
+        let Some(commit) = self.commits.get(self.views.log.selected) else {
+            return;
+        };
+        if self.signatures.contains_key(&commit.id) {
+            return;
+        }
+
+        let signature = self.repo.verify_commit_signature(&commit.id).unwrap_or_else(|e| CommitSignature {
+            status: SignatureStatus::Unverified,
+            signer: None,
+            detail: Some(e.to_string()),
+        });
+        self.signatures.insert(commit.id.clone(), signature);
+    }
+
+    fn render_branch_template(&mut self) {
+        self.branch_name = match self.branch_template {
+            Some(i) => branch_names::render(&self.branch_templates[i], &self.branch_fields),
+            None => String::new(),
+        };
+    }
+
+    // the branch a finish applies to: the current one, or the one selected in the list
+    pub fn flow_finish_branch(&self, kind: FlowKind) -> Option<String> {
+        let prefix = self.repo.flow_config().prefix(kind).to_string();
+        let current = self.repo.get_current_branch().ok().filter(|b| b.starts_with(&prefix));
+        current.or_else(|| self.branches.get(self.views.branches.selected).filter(|b| b.starts_with(&prefix)).cloned())
+    }
+
+    fn run_flow_action(&mut self) -> Result<()> {
+        let (kind, starting) = FLOW_ACTIONS[self.selected_flow_action];
+        let result = if starting {
+            if self.flow_name.trim().is_empty() {
+                self.error_message = Some(format!("name the {} first.", kind.label()));
+                return Ok(());
+            }
+            self.repo.flow_start(kind, &self.flow_name).map(|branch| format!("started {}.", branch))
+        } else {
+            match self.flow_finish_branch(kind) {
+                Some(branch) => self.repo.flow_finish(kind, &branch),
+                None => {
+                    self.error_message = Some(format!("check out or select a {} branch to finish.", kind.label()));
+                    return Ok(());
+                }
+            }
+        };
+
+        self.mode = AppMode::Branches;
+        match result {
+            Ok(message) => {
+                self.flow_name.clear();
+                self.notify(message);
+            }
+            Err(e) => self.error_message = Some(format!("gitflow: {:#}", e)),
+        }
+        // a conflicted merge switches to conflict mode here
+        self.refresh_data()
+    }
+
+    fn load_remote_listing(&mut self, remote: &str) {
+        if self.offline_blocks("listing remote branches") {
+            return;
+        }
+        match self.perf.time(Op::Network, remote, || self.repo.list_remote_branches(remote)) {
+            Ok(branches) => {
+                self.selected_listed_branch = 0;
+                self.listing_focused = true;
+                self.remote_listing = Some((remote.to_string(), branches));
+            }
+            Err(e) => self.error_message = Some(format!("cannot list {}: {:#}", remote, e)),
+        }
+    }
+
+    // keys for the ls-remote section of the branches view, false when it's not for us
+    fn handle_listing_key(&mut self, key: KeyCode) -> Result<bool> {
+        match key {
+            KeyCode::Tab => self.listing_focused = !self.listing_focused,
+            KeyCode::Esc => {
+                self.remote_listing = None;
+                self.listing_focused = false;
+            }
+            _ if !self.listing_focused => return Ok(false),
+            KeyCode::Up => self.selected_listed_branch = self.selected_listed_branch.saturating_sub(1),
+            KeyCode::Down => {
+                let count = self.remote_listing.as_ref().map(|(_, b)| b.len()).unwrap_or(0);
+                if self.selected_listed_branch + 1 < count {
+                    self.selected_listed_branch += 1;
+                }
+            }
+            KeyCode::Enter => {
+                let Some((remote, branches)) = &self.remote_listing else {
+                    return Ok(true);
+                };
+                let remote = remote.clone();
+                if let Some(branch) = branches.get(self.selected_listed_branch).map(|b| b.name.clone()) && !self.offline_blocks("fetching") {
+                    match self.perf.time(Op::Network, &remote, || self.repo.fetch_branch(&remote, &branch)) {
+                        Ok(message) => {
+                            self.notify(message);
+                            let selected = self.selected_listed_branch;
+                            self.load_remote_listing(&remote);
+                            self.selected_listed_branch = selected;
+                            self.refresh_data()?;
+                        }
+                        Err(e) => self.error_message = Some(format!("fetch failed: {:#}", e)),
+                    }
+                }
+            }
+            _ => return Ok(false),
+        }
+        Ok(true)
+    }
+
+    // what the typed refspecs would do, one entry per refspec
+    pub fn push_preview(&self) -> Vec<std::result::Result<String, String>> {
+        let remote = self.remotes.get(self.push_remote).map(|r| r.as_str()).unwrap_or("?");
+        self.push_refspecs
+            .split_whitespace()
+            .map(|spec| match self.repo.parse_push_refspec(spec) {
+                Ok(refspec) => {
+                    let protected = if self.protected_targets(std::slice::from_ref(&refspec)).is_empty() { "" } else { "   protected!" };
+                    Ok(format!("{}   ({}){}", refspec.describe(remote), refspec.spec(), protected))
+                }
+                Err(e) => Err(format!("{:#}", e)),
+            })
+            .collect()
+    }
+
+    // branches the one being edited could track, remote ones first
+    pub fn filtered_upstreams(&self) -> Vec<&String> {
+        let needle = self.upstream_input.to_lowercase();
+        let mut candidates: Vec<&String> = self.branches.iter()
+            .filter(|b| **b != self.upstream_branch && !b.ends_with("/HEAD"))
+            .filter(|b| b.to_lowercase().contains(&needle))
+            .collect();
+        candidates.sort_by_key(|b| self.repo.local_branch_exists(b));
+        candidates
+    }
+
+    pub fn filtered_restore_files(&self) -> Vec<&String> {
+        let needle = self.restore_input.to_lowercase();
+        self.restore_files.iter().filter(|f| f.to_lowercase().contains(&needle)).collect()
+    }
+
+    // the picked file, or whatever was typed when nothing in the commit matches it
+    pub fn restore_path(&self) -> Option<String> {
+        match self.filtered_restore_files().get(self.selected_restore_file) {
+            Some(path) => Some(path.to_string()),
+            None => Some(self.restore_input.trim().to_string()).filter(|p| !p.is_empty()),
+        }
+    }
+
+    fn load_restore_preview(&mut self) {
+        self.restore_scroll = 0;
+        self.restore_preview = match self.restore_path() {
+            Some(path) => match self.repo.restore_preview(&self.restore_commit, &path, self.restore_target) {
+                Ok(diff) if diff.trim().is_empty() => "no changes, the file is already like this.".to_string(),
+                Ok(diff) => diff,
+                Err(e) => format!("{:#}", e),
+            },
+            None => String::new(),
+        };
+    }
+
+    fn load_selected_stash_files(&mut self) {
+        let oid = self.stashes.get(self.views.stashes.selected).map(|s| s.oid);
+        self.stash_files = oid
+            .and_then(|oid| self.repo.stash_files(oid).ok())
+            .unwrap_or_default();
+        self.stash_preview = oid.and_then(|oid| self.repo.stash_preview(oid, &self.stash_files).ok());
+    }
+
+    pub fn conflict_label(&self) -> &'static str {
+        self.merge_conflict.as_ref()
+            .map(|mc| mc.operation.label())
+            .unwrap_or("merge")
+    }
+
+    pub fn get_current_conflict_file(&self) -> Option<&ConflictedFile> {
+        self.merge_conflict.as_ref()
+            .and_then(|mc | mc.files.get(self.selected_conflict_file))
+    }
+
+    pub fn get_current_conflict_hunk(&self) -> Option<&ConflictHunk> {
+        self.get_current_conflict_file()
+            .and_then(|file| file.conflicts.get(self.selected_conflict_hunk))
+    }
+
+    pub fn get_current_resolution(&self) -> Option<&MergeResolution> {
+        self.conflict_resolutions.get(&(self.selected_conflict_file, self.selected_conflict_hunk))
+    }
+
+    // git mergetool (merge.tool) on the selected file with the terminal handed over, then the
+    // file is read again: no markers left means it's resolved and staged, otherwise its hunks are
+    // whatever the tool left behind
+    fn run_mergetool(&mut self) -> Result<()> {
+        let (Some(merge_conflict), Some(file)) = (&self.merge_conflict, self.get_current_conflict_file()) else {
+            return Ok(());
+        };
+        let path = file.path.clone();
+        // resolutions go by position, which shifts when the file drops out of the list
+        let kept: Vec<(String, usize, MergeResolution)> = self.conflict_resolutions.iter()
+            .filter_map(|(&(file_idx, hunk_idx), resolution)| {
+                let other = merge_conflict.files.get(file_idx).filter(|f| f.path != path)?;
+                Some((other.path.clone(), hunk_idx, resolution.clone()))
+            })
+            .collect();
+
+        let mut cmd = self.repo.git_command();
+        cmd.args(["mergetool", "--no-prompt", "--", &path]);
+        self.run_external(cmd)?;
+        let resolved = self.repo.stage_if_resolved(&path)?;
+        self.refresh_data()?;
+
+        self.conflict_resolutions.clear();
+        if let Some(merge_conflict) = &self.merge_conflict {
+            for (other, hunk_idx, resolution) in kept {
+                if let Some(file_idx) = merge_conflict.files.iter().position(|f| f.path == other) {
+                    self.conflict_resolutions.insert((file_idx, hunk_idx), resolution);
+                }
+            }
+            self.selected_conflict_file = self.selected_conflict_file.min(merge_conflict.files.len().saturating_sub(1));
+            self.selected_conflict_hunk = 0;
+        }
+        if resolved {
+            self.notify(format!("{} resolved in the mergetool and staged.", path));
+        } else if let Some(left) = self.merge_conflict.as_ref().and_then(|mc| mc.files.iter().find(|f| f.path == path)) {
+            self.notify(format!("{} still has {} conflict(s) after the mergetool.", path, left.conflicts.len()));
+        }
+        Ok(())
+    }
+
+    // replaces whatever was picked before, for the selected file or every file
+    fn resolve_all(&mut self, resolution: MergeResolution, all_files: bool) {
+        let Some(merge_conflict) = &self.merge_conflict else {
+            return;
+        };
+        let side = match resolution {
+            MergeResolution::KeepOurs => "ours",
+            MergeResolution::OursFile => "our file",
+            MergeResolution::TheirsFile => "their file",
+            _ => "theirs",
+        };
+        let mut hunks = 0;
+        for (file_idx, file) in merge_conflict.files.iter().enumerate() {
+            if !all_files && file_idx != self.selected_conflict_file {
+                continue;
+            }
+            for hunk_idx in 0..file.conflicts.len() {
+                self.conflict_resolutions.insert((file_idx, hunk_idx), resolution.clone());
+                hunks += 1;
+            }
+        }
+        let scope = match merge_conflict.files.get(self.selected_conflict_file) {
+            Some(file) if !all_files => file.path.clone(),
+            _ => format!("{} file(s)", merge_conflict.files.len()),
+        };
+        self.notify(format!("{} for {} hunk(s) in {}.", side, hunks, scope));
+    }
+
+    pub fn set_current_resolution(&mut self, resolution: MergeResolution) {
+        // picking a hunk undoes taking the whole file, the other hunks go back to unresolved
+        let file_idx = self.selected_conflict_file;
+        if self.conflict_resolutions.get(&(file_idx, 0)).is_some_and(|r| r.is_whole_file()) {
+            self.conflict_resolutions.retain(|(f, _), _| *f != file_idx);
+        }
+        self.conflict_resolutions.insert((self.selected_conflict_file, self.selected_conflict_hunk), resolution);
+    }
+
+    pub fn can_complete_merge(&self) -> bool {
+        if let Some(merge_conflict) = &self.merge_conflict {
+            for (file_idx, file) in merge_conflict.files.iter().enumerate() {
+                for hunk_idx in 0 .. file.conflicts.len() {
+                    if !self.conflict_resolutions.contains_key(&(file_idx, hunk_idx)) {
+                        return false; 
+                    }
+                }
+            }
+            true
+        } else {
+            false
+        }
+    }
+
+    pub fn complete_merge(&mut self, commit_message: &str) -> Result<()> {
+        if let Some(merge_conflict) = &self.merge_conflict {
+            self.repo.resolve_conflicts(&self.conflict_resolutions, merge_conflict)?;
+            self.repo.continue_operation(merge_conflict.operation, commit_message)?;
+
+            if merge_conflict.operation == ConflictOperation::Stash {
+                match self.pending_stash_drop.take() {
+                    Some(stash_id) => {
+                        self.repo.stash_drop_by_id(stash_id)?;
+                        self.notify("stash conflicts resolved, stash dropped.".to_string());
+                    }
+                    None => {
+                        self.notify("stash conflicts resolved, the stash was kept.".to_string());
+                    }
+                }
+            }
+
+            self.merge_conflict = None;
+            self.conflict_resolutions.clear();
+            self.mode = AppMode::Status;
+            self.refresh_data()?;
+        }
+        Ok(())
+    }
+
+    pub fn abort_merge(&mut self) -> Result<()> {
+        let operation = self.merge_conflict.as_ref()
+            .map(|mc| mc.operation)
+            .unwrap_or(ConflictOperation::Merge);
+        self.repo.abort_operation(operation)?;
+        if operation == ConflictOperation::Stash {
+            // the stash was never dropped, nothing is lost
+            self.pending_stash_drop = None;
+        }
+        self.merge_conflict = None;
+        self.conflict_resolutions.clear();
+        self.mode = AppMode::Status;
+        self.refresh_data()?;
+        Ok(())
+    }
+}
+
+// first line of the new side of the first hunk ("@@ -a,b +c,d @@" -> c)
+// $VISUAL / $EDITOR with its arguments, and the program on its own
+fn editor_command() -> (Command, String) {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or("vi").to_string();
+    let mut cmd = Command::new(&program);
+    cmd.args(parts);
+    (cmd, program)
+}
+
+fn first_changed_line(diff: &str) -> Option<usize> {
+    diff.lines()
+        .find_map(|line| line.split_once("@@ -").map(|(_, rest)| rest))
+        .and_then(|rest| rest.split_whitespace().nth(1))
+        .and_then(|new| new.trim_start_matches('+').split(',').next())
+        .and_then(|n| n.parse().ok())
+}
+
+fn open_url(url: &str) -> Result<()> {
+    let mut cmd = if cfg!(target_os = "macos") {
+        Command::new("open")
+    } else if cfg!(target_os = "windows") {
+        let mut c = Command::new("cmd");
+        c.args(["/C", "start", ""]);
+        c
+    } else {
+        Command::new("xdg-open")
+    };
+
+    cmd.arg(url)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    Ok(())
+}
+
+// keys that check out, stage, stash or otherwise write to the working tree
+fn needs_worktree(mode: AppMode, key: KeyCode) -> bool {
+    matches!(
+        (mode, key),
+        (_, KeyCode::Char('s'))
+            | (AppMode::Status, KeyCode::Char('r'))
+            | (AppMode::Log, KeyCode::Char('C' | 'R' | 'r' | 'O'))
+            | (AppMode::Branches, KeyCode::Enter | KeyCode::Char('f' | 'O'))
+            | (AppMode::RemoteOperations, KeyCode::Char('u'))
+    )
+}
@@ -0,0 +1,240 @@
+use anyhow::{bail, Result};
+use std::io::Write;
+
+// minimal tar/zip writers for exporting a tree, no compression
+// (gzip the tar yourself if you need it smaller)
+
+pub struct ArchiveEntry {
+    pub path: String,
+    pub data: Vec<u8>,
+    pub executable: bool,
+    pub symlink: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ArchiveFormat {
+    Tar,
+    Zip,
+}
+
+impl ArchiveFormat {
+    pub fn from_path(path: &str) -> Self {
+        if path.to_lowercase().ends_with(".zip") {
+            ArchiveFormat::Zip
+        } else {
+            ArchiveFormat::Tar
+        }
+    }
+}
+
+pub fn write_archive<W: Write>(format: ArchiveFormat, entries: &[ArchiveEntry], mtime: i64, out: &mut W) -> Result<()> {
+    match format {
+        ArchiveFormat::Tar => write_tar(entries, mtime, out),
+        ArchiveFormat::Zip => write_zip(entries, mtime, out),
+    }
+}
+
+fn write_tar<W: Write>(entries: &[ArchiveEntry], mtime: i64, out: &mut W) -> Result<()> {
+    for entry in entries {
+        let (data, typeflag, linkname): (&[u8], u8, &[u8]) = if entry.symlink {
+            (&[], b'2', &entry.data)
+        } else {
+            (&entry.data, b'0', &[])
+        };
+
+        // ustar can split long names into prefix + name, anything longer gets a gnu longname record
+        let split = split_ustar_path(&entry.path);
+        if split.is_none() {
+            let mut long_name = entry.path.as_bytes().to_vec();
+            long_name.push(0);
+            out.write_all(&tar_header("././@LongLink", "", long_name.len() as u64, mtime, 0o644, b'L', &[])?)?;
+            write_tar_data(out, &long_name)?;
+        }
+        let (prefix, name) = split.unwrap_or(("", tail(&entry.path, 99)));
+
+        let mode = if entry.executable { 0o755 } else { 0o644 };
+        out.write_all(&tar_header(name, prefix, data.len() as u64, mtime, mode, typeflag, linkname)?)?;
+        write_tar_data(out, data)?;
+    }
+
+    // two empty blocks end the archive
+    out.write_all(&[0u8; 1024])?;
+    Ok(())
+}
+
+fn tail(s: &str, max_bytes: usize) -> &str {
+    let mut start = s.len().saturating_sub(max_bytes);
+    while !s.is_char_boundary(start) {
+        start += 1;
+    }
+    &s[start..]
+}
+
+fn split_ustar_path(path: &str) -> Option<(&str, &str)> {
+    if path.len() <= 100 {
+        return Some(("", path));
+    }
+    path.char_indices()
+        .filter(|(_, c)| *c == '/')
+        .map(|(i, _)| (&path[..i], &path[i + 1..]))
+        .find(|(prefix, name)| prefix.len() <= 155 && name.len() <= 100 && !name.is_empty())
+}
+
+fn tar_header(name: &str, prefix: &str, size: u64, mtime: i64, mode: u32, typeflag: u8, linkname: &[u8]) -> Result<[u8; 512]> {
+    let mut header = [0u8; 512];
+
+    if linkname.len() > 100 {
+        anyhow::bail!("symlink target too long for tar: {}", name);
+    }
+
+    header[..name.len()].copy_from_slice(name.as_bytes());
+    write_octal(&mut header[100..108], mode as u64);
+    write_octal(&mut header[108..116], 0);
+    write_octal(&mut header[116..124], 0);
+    write_octal(&mut header[124..136], size);
+    write_octal(&mut header[136..148], mtime.max(0) as u64);
+    header[148..156].copy_from_slice(b"        ");
+    header[156] = typeflag;
+    header[157..157 + linkname.len()].copy_from_slice(linkname);
+    header[257..263].copy_from_slice(b"ustar\0");
+    header[263..265].copy_from_slice(b"00");
+    header[345..345 + prefix.len()].copy_from_slice(prefix.as_bytes());
+
+    let checksum: u32 = header.iter().map(|b| *b as u32).sum();
+    write_octal(&mut header[148..155], checksum as u64);
+
+    Ok(header)
+}
+
+fn write_octal(field: &mut [u8], value: u64) {
+    let digits = field.len() - 1;
+    let s = format!("{:0width$o}", value, width = digits);
+    field[..digits].copy_from_slice(&s.as_bytes()[s.len() - digits..]);
+    field[digits] = 0;
+}
+
+fn write_tar_data<W: Write>(out: &mut W, data: &[u8]) -> Result<()> {
+    out.write_all(data)?;
+    let padding = (512 - data.len() % 512) % 512;
+    out.write_all(&vec![0u8; padding])?;
+    Ok(())
+}
+
+// the plain zip format counts entries in 16 bits and sizes and offsets in 32, past that it takes
+// zip64, which isn't written here. checked before anything is written, never truncated
+fn check_zip_limits(entries: &[ArchiveEntry]) -> Result<()> {
+    if entries.len() > u16::MAX as usize {
+        bail!("{} files is more than a zip without zip64 can hold ({})", entries.len(), u16::MAX);
+    }
+    let (mut data_end, mut central_len) = (0u64, 0u64);
+    for entry in entries {
+        if entry.path.len() > u16::MAX as usize {
+            bail!("a path of {} bytes is too long for a zip", entry.path.len());
+        }
+        if entry.data.len() > u32::MAX as usize {
+            bail!("{} is too large for a zip without zip64", entry.path);
+        }
+        data_end += 30 + entry.path.len() as u64 + entry.data.len() as u64;
+        central_len += 46 + entry.path.len() as u64;
+    }
+    if data_end + central_len > u32::MAX as u64 {
+        bail!("archive too large for a zip without zip64");
+    }
+    Ok(())
+}
+
+fn write_zip<W: Write>(entries: &[ArchiveEntry], mtime: i64, out: &mut W) -> Result<()> {
+    check_zip_limits(entries)?;
+    let (dos_time, dos_date) = dos_datetime(mtime);
+    let mut central = Vec::new();
+    let mut offset: u32 = 0;
+
+    for entry in entries {
+        let name = entry.path.as_bytes();
+        let crc = crc32(&entry.data);
+        let size = entry.data.len() as u32;
+
+        let mut local = Vec::with_capacity(30 + name.len());
+        local.extend_from_slice(&0x04034b50u32.to_le_bytes());
+        local.extend_from_slice(&10u16.to_le_bytes()); // version needed
+        local.extend_from_slice(&0x0800u16.to_le_bytes()); // utf-8 names
+        local.extend_from_slice(&0u16.to_le_bytes()); // stored
+        local.extend_from_slice(&dos_time.to_le_bytes());
+        local.extend_from_slice(&dos_date.to_le_bytes());
+        local.extend_from_slice(&crc.to_le_bytes());
+        local.extend_from_slice(&size.to_le_bytes());
+        local.extend_from_slice(&size.to_le_bytes());
+        local.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        local.extend_from_slice(&0u16.to_le_bytes());
+        local.extend_from_slice(name);
+        out.write_all(&local)?;
+        out.write_all(&entry.data)?;
+
+        // unix mode in the high bits of the external attributes
+        let unix_mode: u32 = if entry.symlink {
+            0o120777
+        } else if entry.executable {
+            0o100755
+        } else {
+            0o100644
+        };
+
+        central.extend_from_slice(&0x02014b50u32.to_le_bytes());
+        central.extend_from_slice(&(3u16 << 8 | 20).to_le_bytes()); // made by unix
+        central.extend_from_slice(&10u16.to_le_bytes());
+        central.extend_from_slice(&0x0800u16.to_le_bytes());
+        central.extend_from_slice(&0u16.to_le_bytes());
+        central.extend_from_slice(&dos_time.to_le_bytes());
+        central.extend_from_slice(&dos_date.to_le_bytes());
+        central.extend_from_slice(&crc.to_le_bytes());
+        central.extend_from_slice(&size.to_le_bytes());
+        central.extend_from_slice(&size.to_le_bytes());
+        central.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        central.extend_from_slice(&0u16.to_le_bytes()); // extra
+        central.extend_from_slice(&0u16.to_le_bytes()); // comment
+        central.extend_from_slice(&0u16.to_le_bytes()); // disk
+        central.extend_from_slice(&0u16.to_le_bytes()); // internal attrs
+        central.extend_from_slice(&(unix_mode << 16).to_le_bytes());
+        central.extend_from_slice(&offset.to_le_bytes());
+        central.extend_from_slice(name);
+
+        offset += local.len() as u32 + size;
+    }
+
+    out.write_all(&central)?;
+
+    let mut end = Vec::with_capacity(22);
+    end.extend_from_slice(&0x06054b50u32.to_le_bytes());
+    end.extend_from_slice(&0u16.to_le_bytes());
+    end.extend_from_slice(&0u16.to_le_bytes());
+    end.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+    end.extend_from_slice(&(entries.len() as u16).to_le_bytes());
+    end.extend_from_slice(&(central.len() as u32).to_le_bytes());
+    end.extend_from_slice(&offset.to_le_bytes());
+    end.extend_from_slice(&0u16.to_le_bytes());
+    out.write_all(&end)?;
+
+    Ok(())
+}
+
+fn dos_datetime(timestamp: i64) -> (u16, u16) {
+    use chrono::{Datelike, Timelike};
+
+    let dt = chrono::DateTime::from_timestamp(timestamp, 0).unwrap_or_default();
+    let year = (dt.year().clamp(1980, 2107) - 1980) as u16;
+    let time = (dt.hour() as u16) << 11 | (dt.minute() as u16) << 5 | (dt.second() as u16 / 2);
+    let date = year << 9 | (dt.month() as u16) << 5 | dt.day() as u16;
+    (time, date)
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = 0xFFFF_FFFFu32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
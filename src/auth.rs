@@ -0,0 +1,139 @@
+use crate::git::Repository;
+use std::process::Command;
+
+// the auth panel in the remote view (a): what ssh-agent holds, whether gpg-agent answers and
+// which key commits get signed with, for when a push or a signed commit fails and it isn't
+// obvious why. every check runs the real tool, so it says what git would run into
+
+#[derive(Debug, Clone)]
+pub struct AuthStatus {
+    // the agent's keys ("256 SHA256:... you@host (ED25519)"), or why it couldn't be asked
+    pub ssh_agent: Result<Vec<String>, String>,
+    pub gpg_agent: Result<String, String>,
+    pub signing: Signing,
+}
+
+#[derive(Debug, Clone)]
+pub struct Signing {
+    // gpg.format: openpgp, ssh or x509
+    pub format: String,
+    pub key: Option<String>,
+    pub commits: bool,
+    pub tags: bool,
+    // whether the key can actually sign, None when there is nothing to check
+    pub problem: Option<String>,
+}
+
+impl AuthStatus {
+    pub fn load(repo: &Repository) -> Self {
+        let ssh_agent = ssh_identities();
+        let gpg_agent = gpg_agent();
+        let format = repo.config_string("gpg.format").unwrap_or_else(|| "openpgp".to_string());
+        let key = repo.config_string("user.signingkey").filter(|k| !k.trim().is_empty());
+        let commits = repo.config_bool("commit.gpgsign");
+        let tags = repo.config_bool("tag.gpgsign");
+        // nothing set up to sign, nothing to complain about
+        let problem = match format.as_str() {
+            _ if key.is_none() && !commits && !tags => None,
+            "ssh" => ssh_key_problem(key.as_deref(), &ssh_agent),
+            "openpgp" => gpg_key_problem(repo, key.as_deref()),
+            _ => None,
+        };
+        let signing = Signing { format, key, commits, tags, problem };
+        Self { ssh_agent, gpg_agent, signing }
+    }
+}
+
+fn ssh_identities() -> Result<Vec<String>, String> {
+    // the windows agent listens on a named pipe, no socket variable there
+    if !cfg!(windows) && std::env::var_os("SSH_AUTH_SOCK").is_none_or(|s| s.is_empty()) {
+        return Err("SSH_AUTH_SOCK isn't set, there's no agent to ask".to_string());
+    }
+    let output = Command::new("ssh-add").arg("-l").output().map_err(|e| format!("can't run ssh-add: {}", e))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // 1 is a running agent without keys, 2 is no agent
+    match output.status.code() {
+        Some(0) => Ok(stdout.lines().map(|l| l.trim().to_string()).filter(|l| !l.is_empty()).collect()),
+        Some(1) => Ok(Vec::new()),
+        _ => Err(format!("can't reach the agent: {}", String::from_utf8_lossy(&output.stderr).trim())),
+    }
+}
+
+fn gpg_agent() -> Result<String, String> {
+    let output = Command::new("gpg-connect-agent")
+        .args(["--no-autostart", "GETINFO version", "/bye"])
+        .output()
+        .map_err(|e| format!("can't run gpg-connect-agent: {}", e))?;
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    // "D 2.2.40" then "OK", or "ERR ... No agent running"
+    if let Some(version) = stdout.lines().find_map(|l| l.strip_prefix("D ")) {
+        return Ok(format!("running, version {}", version.trim()));
+    }
+    let reason = stdout.lines().chain(String::from_utf8_lossy(&output.stderr).lines()).find(|l| !l.trim().is_empty()).map(|l| l.trim().to_string());
+    Err(reason.unwrap_or_else(|| "no agent running".to_string()))
+}
+
+// user.signingkey for ssh is a public key file or "key::<the key itself>", it signs only if the
+// agent has it or the private key sits next to the file
+fn ssh_key_problem(key: Option<&str>, agent: &Result<Vec<String>, String>) -> Option<String> {
+    let Some(key) = key else {
+        return Some("gpg.format is ssh but user.signingkey isn't set".to_string());
+    };
+    if let Some(literal) = key.strip_prefix("key::") {
+        return in_agent(literal, agent);
+    }
+    let path = match key.strip_prefix("~/") {
+        Some(rest) => crate::git::home_dir().unwrap_or_default().join(rest),
+        None => std::path::PathBuf::from(key),
+    };
+    if !path.is_file() {
+        return Some(format!("{} doesn't exist", path.display()));
+    }
+    let private = path.with_extension("");
+    if path.extension().is_some_and(|e| e == "pub") && private.is_file() {
+        return None;
+    }
+    std::fs::read_to_string(&path).ok().and_then(|public| in_agent(public.trim(), agent))
+}
+
+fn in_agent(public_key: &str, agent: &Result<Vec<String>, String>) -> Option<String> {
+    let fingerprint = fingerprint(public_key)?;
+    match agent {
+        Ok(keys) if keys.iter().any(|k| k.contains(&fingerprint)) => None,
+        Ok(_) => Some(format!("the agent doesn't hold the signing key ({}), ssh-add it", fingerprint)),
+        Err(_) => Some("the signing key is only usable through an agent, and there isn't one".to_string()),
+    }
+}
+
+// SHA256:... of a public key line, the way ssh-add -l prints it
+fn fingerprint(public_key: &str) -> Option<String> {
+    let mut child = Command::new("ssh-keygen")
+        .args(["-l", "-f", "-"])
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::null())
+        .spawn()
+        .ok()?;
+    use std::io::Write;
+    child.stdin.take()?.write_all(public_key.as_bytes()).ok()?;
+    let output = child.wait_with_output().ok()?;
+    String::from_utf8_lossy(&output.stdout).split_whitespace().nth(1).map(|f| f.to_string())
+}
+
+// gpg signs with the default key when user.signingkey isn't set, either way it needs a secret key
+fn gpg_key_problem(repo: &Repository, key: Option<&str>) -> Option<String> {
+    let program = repo.config_string("gpg.openpgp.program").or_else(|| repo.config_string("gpg.program")).unwrap_or_else(|| "gpg".to_string());
+    let mut cmd = Command::new(&program);
+    cmd.args(["--batch", "--list-secret-keys", "--with-colons"]);
+    if let Some(key) = key {
+        cmd.arg(key);
+    }
+    match cmd.output() {
+        Err(e) => Some(format!("can't run {}: {}", program, e)),
+        Ok(output) if String::from_utf8_lossy(&output.stdout).lines().any(|l| l.starts_with("sec:")) => None,
+        Ok(_) => Some(match key {
+            Some(key) => format!("{} has no secret key for {}", program, key),
+            None => format!("{} has no secret keys to sign with", program),
+        }),
+    }
+}
@@ -0,0 +1,84 @@
+use crate::events::{Message, Queue};
+use crate::git::Repository;
+use std::time::{Duration, Instant};
+
+// hydra.autoFetch = <minutes>: every remote is fetched on a thread that often, so ahead / behind
+// stay current without pressing anything. a remote that wants a password it can't get from the
+// agent or a credential helper just fails, it never opens the credential dialog on its own. what
+// went wrong is shown in the repo info pane next to when the last fetch was (FETCH_HEAD, so a
+// `git fetch` in another terminal counts too). a run reports back through the event queue with
+// Message::Fetched.
+
+pub struct AutoFetch {
+    interval: Option<Duration>,
+    next: Option<Instant>,
+    running: bool,
+    pub errors: Vec<String>,
+}
+
+impl AutoFetch {
+    pub fn new(interval: Option<Duration>) -> Self {
+        Self { interval, next: interval.map(|i| Instant::now() + i), running: false, errors: Vec::new() }
+    }
+
+    pub fn from_config(repo: &Repository) -> Self {
+        let minutes = repo.config_string("hydra.autoFetch").and_then(|m| m.trim().parse::<u64>().ok()).filter(|&m| m > 0);
+        Self::new(minutes.map(|m| Duration::from_secs(m * 60)))
+    }
+
+    pub fn interval(&self) -> Option<Duration> {
+        self.interval
+    }
+
+    pub fn running(&self) -> bool {
+        self.running
+    }
+
+    // starts a run when one is due (never offline), true when it did
+    pub fn tick(&mut self, repo: &Repository, offline: bool, queue: &Queue) -> bool {
+        if self.running {
+            return false;
+        }
+        let Some(interval) = self.interval.filter(|_| !offline) else {
+            return false;
+        };
+        if self.next.is_some_and(|next| Instant::now() < next) {
+            return false;
+        }
+        self.next = Some(Instant::now() + interval);
+        self.start(repo, queue)
+    }
+
+    // what the run's Message::Fetched brought back
+    pub fn finish(&mut self, errors: Vec<String>) {
+        self.running = false;
+        self.errors = errors;
+    }
+
+    fn start(&mut self, repo: &Repository, queue: &Queue) -> bool {
+        let remotes = repo.get_remotes().unwrap_or_default();
+        if remotes.is_empty() {
+            return false;
+        }
+        let git_dir = repo.git_dir().to_path_buf();
+        let work_tree = repo.workdir().map(|w| w.to_path_buf());
+        self.running = true;
+        queue.spawn(move || {
+            let mut errors = Vec::new();
+            // git2 handles can't cross threads, the thread opens the repo again
+            match Repository::open_git_dir(&git_dir, work_tree.as_deref()) {
+                Ok(mut repo) => {
+                    for remote in &remotes {
+                        if let Err(e) = repo.fetch_remote(remote) {
+                            log::warn!("auto-fetch of {} failed: {:#}", remote, e);
+                            errors.push(format!("{}: {:#}", remote, e));
+                        }
+                    }
+                }
+                Err(e) => errors.push(format!("{:#}", e)),
+            }
+            Message::Fetched(errors)
+        });
+        true
+    }
+}
@@ -0,0 +1,248 @@
+use ratatui::layout::Rect;
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::{Arc, Mutex};
+
+// gravatar pictures for the commit details pane, opt in with `git config hydra.avatars true`
+// (it sends an md5 of every author email to gravatar.com). fetched with curl into
+// $XDG_CACHE_HOME/git-hydra/avatars and drawn with the kitty graphics protocol, so only
+// kitty / wezterm / ghostty show them. everywhere else the initials chip is all you get.
+
+#[derive(Debug, Clone)]
+enum AvatarState {
+    Fetching,
+    Ready(PathBuf),
+    Missing,
+}
+
+pub struct Avatars {
+    enabled: bool,
+    // offline mode: what's cached still shows, nothing new gets downloaded
+    offline: bool,
+    states: Arc<Mutex<HashMap<String, AvatarState>>>,
+    // what's on screen right now, so we only send the image when it changes
+    shown: Option<(String, Rect)>,
+}
+
+impl Avatars {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled: enabled && terminal_supports_graphics() && cache_dir().is_some(),
+            offline: false,
+            states: Arc::new(Mutex::new(HashMap::new())),
+            shown: None,
+        }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn set_offline(&mut self, offline: bool) {
+        self.offline = offline;
+    }
+
+    // path to the picture if it's ready, starts the download the first time an email is asked for
+    fn get(&self, email: &str) -> Option<PathBuf> {
+        let key = email.trim().to_lowercase();
+        let mut states = self.states.lock().ok()?;
+        match states.get(&key) {
+            Some(AvatarState::Ready(path)) => return Some(path.clone()),
+            Some(_) => return None,
+            None => {}
+        }
+
+        let hash = md5_hex(key.as_bytes());
+        let path = cache_dir()?.join(format!("{}.png", hash));
+        if path.exists() {
+            states.insert(key, AvatarState::Ready(path.clone()));
+            return Some(path);
+        }
+        if self.offline {
+            return None;
+        }
+
+        states.insert(key.clone(), AvatarState::Fetching);
+        let states = Arc::clone(&self.states);
+        std::thread::spawn(move || {
+            let state = match fetch_gravatar(&hash, &path) {
+                true => AvatarState::Ready(path),
+                false => AvatarState::Missing,
+            };
+            if let Ok(mut states) = states.lock() {
+                states.insert(key, state);
+            }
+        });
+        None
+    }
+
+    // called after every frame with where the picture should go (None: nowhere)
+    pub fn render(&mut self, out: &mut impl Write, slot: Option<(String, Rect)>) -> std::io::Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+        let wanted = slot.and_then(|(email, area)| self.get(&email).map(|path| (email, area, path)));
+
+        let same = match (&wanted, &self.shown) {
+            (Some((email, area, _)), Some((shown_email, shown_area))) => email == shown_email && area == shown_area,
+            (None, None) => true,
+            _ => false,
+        };
+        if same {
+            return Ok(());
+        }
+
+        self.clear(out)?;
+        if let Some((email, area, path)) = wanted {
+            if let Ok(png) = fs::read(&path) {
+                write_kitty_image(out, &png, area)?;
+                self.shown = Some((email, area));
+            }
+        }
+        Ok(())
+    }
+
+    pub fn clear(&mut self, out: &mut impl Write) -> std::io::Result<()> {
+        if self.enabled {
+            // delete every image we placed
+            write!(out, "\x1b_Ga=d,d=A,q=2\x1b\\")?;
+            out.flush()?;
+        }
+        self.shown = None;
+        Ok(())
+    }
+
+    // forget what's on screen, the terminal was cleared under us
+    pub fn invalidate(&mut self) {
+        self.shown = None;
+    }
+}
+
+fn terminal_supports_graphics() -> bool {
+    let term = std::env::var("TERM").unwrap_or_default();
+    let program = std::env::var("TERM_PROGRAM").unwrap_or_default();
+    std::env::var_os("KITTY_WINDOW_ID").is_some()
+        || term.contains("kitty")
+        || term.contains("ghostty")
+        || program == "WezTerm"
+        || program == "ghostty"
+}
+
+fn cache_dir() -> Option<PathBuf> {
+    let base = match std::env::var_os("XDG_CACHE_HOME").filter(|d| !d.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None if cfg!(windows) => PathBuf::from(std::env::var_os("LOCALAPPDATA")?),
+        None => PathBuf::from(std::env::var_os("HOME")?).join(".cache"),
+    };
+    Some(base.join("git-hydra").join("avatars"))
+}
+
+// d=404 so authors without a gravatar don't all get the same placeholder
+fn fetch_gravatar(hash: &str, path: &PathBuf) -> bool {
+    let Some(dir) = path.parent() else {
+        return false;
+    };
+    if fs::create_dir_all(dir).is_err() {
+        return false;
+    }
+    let tmp = path.with_extension("part");
+    let url = format!("https://www.gravatar.com/avatar/{}?s=96&d=404", hash);
+    let ok = Command::new("curl")
+        .args(["-sfL", "--max-time", "10", "-o"])
+        .arg(&tmp)
+        .arg(&url)
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .is_ok_and(|s| s.success());
+
+    if ok && fs::rename(&tmp, path).is_ok() {
+        return true;
+    }
+    let _ = fs::remove_file(&tmp);
+    false
+}
+
+// png passed straight through (f=100), scaled by the terminal into the cell area.
+// z=-1 keeps it under the text so popups drawn on top still cover it
+fn write_kitty_image(out: &mut impl Write, png: &[u8], area: Rect) -> std::io::Result<()> {
+    let data = base64(png);
+    let chunks: Vec<&[u8]> = data.as_bytes().chunks(4096).collect();
+
+    // save the cursor, ratatui doesn't know we moved it
+    write!(out, "\x1b7\x1b[{};{}H", area.y + 1, area.x + 1)?;
+    for (i, chunk) in chunks.iter().enumerate() {
+        let more = if i + 1 < chunks.len() { 1 } else { 0 };
+        if i == 0 {
+            write!(out, "\x1b_Ga=T,f=100,q=2,C=1,z=-1,c={},r={},m={};", area.width, area.height, more)?;
+        } else {
+            write!(out, "\x1b_Gm={};", more)?;
+        }
+        out.write_all(chunk)?;
+        write!(out, "\x1b\\")?;
+    }
+    write!(out, "\x1b8")?;
+    out.flush()
+}
+
+fn base64(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut out = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let n = (b[0] as u32) << 16 | (b[1] as u32) << 8 | b[2] as u32;
+        for i in 0..4 {
+            if i <= chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                out.push('=');
+            }
+        }
+    }
+    out
+}
+
+// gravatar wants the md5 of the lowercased email, nothing else here needs a hash
+fn md5_hex(input: &[u8]) -> String {
+    const S: [u32; 64] = [
+        7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22,
+        5, 9, 14, 20, 5, 9, 14, 20, 5, 9, 14, 20, 5, 9, 14, 20,
+        4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23,
+        6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21,
+    ];
+    let k: Vec<u32> = (0..64).map(|i| ((i as f64 + 1.0).sin().abs() * 4294967296.0) as u32).collect();
+
+    let mut message = input.to_vec();
+    let bit_len = (input.len() as u64).wrapping_mul(8);
+    message.push(0x80);
+    while message.len() % 64 != 56 {
+        message.push(0);
+    }
+    message.extend_from_slice(&bit_len.to_le_bytes());
+
+    let mut state: [u32; 4] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+    for block in message.chunks(64) {
+        let m: Vec<u32> = block.chunks(4).map(|w| u32::from_le_bytes([w[0], w[1], w[2], w[3]])).collect();
+        let [mut a, mut b, mut c, mut d] = state;
+        for i in 0..64 {
+            let (f, g) = match i / 16 {
+                0 => ((b & c) | (!b & d), i),
+                1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+                2 => (b ^ c ^ d, (3 * i + 5) % 16),
+                _ => (c ^ (b | !d), (7 * i) % 16),
+            };
+            let rotated = a.wrapping_add(f).wrapping_add(k[i]).wrapping_add(m[g]).rotate_left(S[i]);
+            a = d;
+            d = c;
+            c = b;
+            b = b.wrapping_add(rotated);
+        }
+        state = [state[0].wrapping_add(a), state[1].wrapping_add(b), state[2].wrapping_add(c), state[3].wrapping_add(d)];
+    }
+
+    state.iter().flat_map(|s| s.to_le_bytes()).map(|b| format!("{:02x}", b)).collect()
+}
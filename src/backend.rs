@@ -0,0 +1,189 @@
+use crate::git::Repository;
+use anyhow::{Context, Result};
+use std::io::Write;
+use std::process::Stdio;
+
+// who does the work for the operations where libgit2 and git don't behave the same: libgit2 (the
+// default) runs in process and asks for passwords through hydra's own prompt, the git binary runs
+// the repo's hooks (pre-commit, commit-msg, pre-push, post-checkout...), its credential helpers and
+// whatever else is set up for real git, at the cost of starting a process.
+//   hydra.backend = git               every operation below through the git binary
+//   hydra.backend.push = libgit2      just this one, over hydra.backend
+// the operations are fetch, push, pull, commit and checkout. git::Repository keeps the same methods
+// whichever it is, they ask `choose` which one runs.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Operation {
+    Fetch,
+    Push,
+    Pull,
+    Commit,
+    Checkout,
+}
+
+impl Operation {
+    pub fn name(self) -> &'static str {
+        match self {
+            Operation::Fetch => "fetch",
+            Operation::Push => "push",
+            Operation::Pull => "pull",
+            Operation::Commit => "commit",
+            Operation::Checkout => "checkout",
+        }
+    }
+}
+
+pub trait Backend {
+    fn name(&self) -> &'static str;
+    // empty refspecs for the remote's configured ones
+    fn fetch(&self, repo: &mut Repository, remote: &str, refspecs: &[String]) -> Result<()>;
+    // refused updates are an error
+    fn push(&self, repo: &mut Repository, remote: &str, refspecs: &[String]) -> Result<()>;
+    fn pull(&self, repo: &mut Repository, remote: &str, branch: &str) -> Result<String>;
+    fn commit(&self, repo: &Repository, message: &str) -> Result<git2::Oid>;
+    fn checkout(&self, repo: &Repository, branch: &str) -> Result<()>;
+}
+
+pub struct Libgit2;
+
+impl Backend for Libgit2 {
+    fn name(&self) -> &'static str {
+        "libgit2"
+    }
+
+    fn fetch(&self, repo: &mut Repository, remote: &str, refspecs: &[String]) -> Result<()> {
+        repo.git2_fetch(remote, refspecs)
+    }
+
+    fn push(&self, repo: &mut Repository, remote: &str, refspecs: &[String]) -> Result<()> {
+        repo.git2_push(remote, refspecs)
+    }
+
+    fn pull(&self, repo: &mut Repository, remote: &str, branch: &str) -> Result<String> {
+        repo.git2_pull(remote, branch)
+    }
+
+    fn commit(&self, repo: &Repository, message: &str) -> Result<git2::Oid> {
+        repo.git2_commit(message)
+    }
+
+    fn checkout(&self, repo: &Repository, branch: &str) -> Result<()> {
+        repo.git2_checkout(branch)
+    }
+}
+
+pub struct Shell;
+
+impl Backend for Shell {
+    fn name(&self) -> &'static str {
+        "git"
+    }
+
+    fn fetch(&self, repo: &mut Repository, remote: &str, refspecs: &[String]) -> Result<()> {
+        let mut args = vec!["fetch", "--quiet", remote];
+        args.extend(refspecs.iter().map(String::as_str));
+        run(repo, &args, None).map(drop)
+    }
+
+    fn push(&self, repo: &mut Repository, remote: &str, refspecs: &[String]) -> Result<()> {
+        let mut args = vec!["push", "--quiet", remote];
+        args.extend(refspecs.iter().map(String::as_str));
+        run(repo, &args, None).map(drop)
+    }
+
+    fn pull(&self, repo: &mut Repository, remote: &str, branch: &str) -> Result<String> {
+        // pull.rebase, pull.ff and the rest are git's to apply
+        if let Err(e) = run(repo, &["pull", "--no-edit", remote, branch], None) {
+            // stopped on conflicts, the same error the libgit2 pull gives so the app opens them
+            if repo.index_conflicts().is_ok_and(|conflicts| !conflicts.is_empty()) {
+                return Err(e.context("merge conflicts require resolution!"));
+            }
+            return Err(e);
+        }
+        Ok(format!("pulled {} from {}", branch, remote))
+    }
+
+    fn commit(&self, repo: &Repository, message: &str) -> Result<git2::Oid> {
+        run(repo, &["commit", "--quiet", "--file=-"], Some(message))?;
+        let head = run(repo, &["rev-parse", "HEAD"], None)?;
+        Ok(git2::Oid::from_str(head.trim())?)
+    }
+
+    fn checkout(&self, repo: &Repository, branch: &str) -> Result<()> {
+        run(repo, &["switch", "--quiet", branch], None).map(drop).map_err(|e| {
+            // local changes in the way come back as libgit2's conflict, which the app offers to
+            // autostash
+            match repo.checkout_conflict(branch) {
+                Some(conflict) => anyhow::Error::new(conflict).context(format!("{:#}", e)),
+                None => e,
+            }
+        })
+    }
+}
+
+static LIBGIT2: Libgit2 = Libgit2;
+static SHELL: Shell = Shell;
+
+pub fn parse(name: &str) -> Option<&'static dyn Backend> {
+    match name.trim().to_lowercase().as_str() {
+        "libgit2" | "git2" => Some(&LIBGIT2),
+        "git" | "shell" => Some(&SHELL),
+        _ => None,
+    }
+}
+
+// hydra.backend.<operation>, then hydra.backend, then libgit2. a name it doesn't know is logged
+// and left out
+pub fn choose(repo: &Repository, operation: Operation) -> &'static dyn Backend {
+    let backend = [format!("hydra.backend.{}", operation.name()), "hydra.backend".to_string()]
+        .into_iter()
+        .find_map(|key| {
+            let name = repo.config_string(&key)?;
+            let backend = parse(&name);
+            if backend.is_none() {
+                log::warn!("{} = {}: not a backend, it's libgit2 or git", key, name);
+            }
+            backend
+        })
+        .unwrap_or(&LIBGIT2);
+    log::debug!("{} through {}", operation.name(), backend.name());
+    backend
+}
+
+// objects a partial clone left out, by id. always the git binary, libgit2 doesn't know promisor
+// remotes. the same fetch git does when it finds one missing, without touching any ref
+pub fn fetch_objects(repo: &Repository, remote: &str, ids: &[git2::Oid]) -> Result<()> {
+    // on stdin, a big diff can want more than fits on a command line
+    let ids: String = ids.iter().map(|id| format!("{}\n", id)).collect();
+    let args = [
+        "-c", "fetch.negotiationAlgorithm=noop", "fetch", "--quiet", "--no-tags", "--no-write-fetch-head",
+        "--recurse-submodules=no", "--filter=blob:none", "--stdin", remote,
+    ];
+    run(repo, &args, Some(&ids)).map(drop)
+}
+
+// the git binary without a terminal: no password prompt or editor, what it needs comes from the
+// credential helpers and `input` on stdin
+fn run(repo: &Repository, args: &[&str], input: Option<&str>) -> Result<String> {
+    log::debug!("running git {}", args.join(" "));
+    let mut child = repo
+        .git_command()
+        .args(args)
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .env("GIT_EDITOR", "true")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .context("failed to run git, is it installed?")?;
+    let mut stdin = child.stdin.take().context("no stdin for git")?;
+    stdin.write_all(input.unwrap_or("").as_bytes())?;
+    drop(stdin);
+    let output = child.wait_with_output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        log::warn!("git {} exited with {}: {}", args.join(" "), output.status, stderr.trim());
+        anyhow::bail!("git {} failed: {}", args.join(" "), stderr.trim());
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
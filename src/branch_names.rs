@@ -0,0 +1,39 @@
+// branch name templates for the create branch dialog, from `hydra.branchTemplate` (can be given
+// more than once), e.g. feature/{ticket}-{slug}. every {placeholder} is asked for in order,
+// {slug} is squashed to lowercase-with-dashes and anything else just loses its spaces.
+
+pub fn placeholders(template: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        let Some(close) = rest[open..].find('}') else {
+            break;
+        };
+        let name = &rest[open + 1..open + close];
+        if !name.is_empty() && !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+        rest = &rest[open + close + 1..];
+    }
+    names
+}
+
+pub fn render(template: &str, values: &[(String, String)]) -> String {
+    values.iter().fold(template.to_string(), |name, (placeholder, value)| {
+        let value = if placeholder == "slug" { slugify(value) } else { value.trim().replace(char::is_whitespace, "-") };
+        name.replace(&format!("{{{}}}", placeholder), &value)
+    })
+}
+
+// "Fix the Login page!" -> "fix-the-login-page"
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::new();
+    for c in text.chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.trim_end_matches('-').to_string()
+}
@@ -0,0 +1,161 @@
+use ratatui::buffer::Buffer;
+use ratatui::style::{Color, Modifier};
+
+// what the terminal can show, worked out from the environment the way most tools do it:
+// NO_COLOR (any value) means none, COLORTERM=truecolor / 24bit full color, a TERM with 256color
+// in it 256, TERM=dumb none, the linux console and the old vt ones only the 8 base colors (the
+// bright ones come out as bold), anything else 16. hydra.colors = none / 8 / 16 / 256 / truecolor
+// says it outright when the guess is wrong, over ssh or inside screen / tmux say.
+// once a frame is drawn every cell is brought down to that: colors the terminal doesn't have go
+// to the nearest it does, and without any the selection is drawn reversed so it still shows.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ColorLevel {
+    None,
+    Ansi8,
+    Ansi16,
+    Ansi256,
+    TrueColor,
+}
+
+impl ColorLevel {
+    pub fn parse(text: &str) -> Option<Self> {
+        match text.trim().to_lowercase().as_str() {
+            "none" | "off" | "0" | "false" => Some(Self::None),
+            "8" => Some(Self::Ansi8),
+            "16" => Some(Self::Ansi16),
+            "256" => Some(Self::Ansi256),
+            "truecolor" | "24bit" | "true" => Some(Self::TrueColor),
+            _ => None,
+        }
+    }
+}
+
+// `env` is std::env::var, passed in for the tests
+pub fn detect(setting: Option<&str>, env: impl Fn(&str) -> Option<String>) -> ColorLevel {
+    if let Some(level) = setting.and_then(ColorLevel::parse) {
+        return level;
+    }
+    if env("NO_COLOR").is_some() {
+        return ColorLevel::None;
+    }
+    if env("COLORTERM").is_some_and(|c| matches!(c.to_lowercase().as_str(), "truecolor" | "24bit")) {
+        return ColorLevel::TrueColor;
+    }
+    let term = env("TERM").unwrap_or_default().to_lowercase();
+    if term.contains("256color") {
+        return ColorLevel::Ansi256;
+    }
+    match term.as_str() {
+        "dumb" => ColorLevel::None,
+        "linux" | "vt100" | "vt220" | "ansi" | "cons25" => ColorLevel::Ansi8,
+        // windows terminals don't set TERM, and have all 16
+        _ => ColorLevel::Ansi16,
+    }
+}
+
+pub fn degrade(buffer: &mut Buffer, level: ColorLevel) {
+    if level == ColorLevel::TrueColor {
+        return;
+    }
+    for cell in buffer.content.iter_mut() {
+        match level {
+            ColorLevel::TrueColor => {}
+            ColorLevel::Ansi256 => {
+                cell.fg = to_256(cell.fg);
+                cell.bg = to_256(cell.bg);
+            }
+            ColorLevel::Ansi16 => {
+                cell.fg = to_16(cell.fg);
+                cell.bg = to_16(cell.bg);
+            }
+            ColorLevel::Ansi8 => {
+                let fg = to_16(cell.fg);
+                let (fg, bright) = to_8(fg);
+                if bright {
+                    cell.modifier |= Modifier::BOLD;
+                }
+                cell.fg = fg;
+                cell.bg = to_8(to_16(cell.bg)).0;
+            }
+            ColorLevel::None => {
+                // a background is how the lists show the selection
+                if cell.bg != Color::Reset {
+                    cell.modifier |= Modifier::REVERSED;
+                }
+                cell.fg = Color::Reset;
+                cell.bg = Color::Reset;
+            }
+        }
+    }
+}
+
+// the xterm defaults of the 16 base colors
+const BASE: [(u8, u8, u8); 16] = [
+    (0, 0, 0), (205, 0, 0), (0, 205, 0), (205, 205, 0), (0, 0, 238), (205, 0, 205), (0, 205, 205), (229, 229, 229),
+    (127, 127, 127), (255, 0, 0), (0, 255, 0), (255, 255, 0), (92, 92, 255), (255, 0, 255), (0, 255, 255), (255, 255, 255),
+];
+
+const NAMED: [Color; 16] = [
+    Color::Black, Color::Red, Color::Green, Color::Yellow, Color::Blue, Color::Magenta, Color::Cyan, Color::Gray,
+    Color::DarkGray, Color::LightRed, Color::LightGreen, Color::LightYellow, Color::LightBlue, Color::LightMagenta, Color::LightCyan, Color::White,
+];
+
+// what a color looks like in xterm, None for the terminal's own foreground / background
+pub fn rgb(color: Color) -> Option<(u8, u8, u8)> {
+    Some(match color {
+        Color::Reset => return None,
+        Color::Rgb(r, g, b) => (r, g, b),
+        Color::Indexed(i) if i < 16 => BASE[i as usize],
+        // the 6x6x6 cube, then 24 grays
+        Color::Indexed(i) if i < 232 => {
+            let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+            let i = i - 16;
+            (level(i / 36), level(i / 6 % 6), level(i % 6))
+        }
+        Color::Indexed(i) => {
+            let v = 8 + (i - 232) * 10;
+            (v, v, v)
+        }
+        named => BASE[NAMED.iter().position(|c| *c == named).unwrap_or(7)],
+    })
+}
+
+fn to_256(color: Color) -> Color {
+    let Color::Rgb(r, g, b) = color else {
+        return color;
+    };
+    // the nearest cube color, or gray when that's closer
+    let step = |v: u8| if v < 48 { 0 } else if v < 115 { 1 } else { (v - 35) / 40 };
+    let cube = 16 + 36 * step(r) + 6 * step(g) + step(b);
+    let average = (r as u16 + g as u16 + b as u16) / 3;
+    let gray = if average > 238 { 255 } else { 232 + (average.saturating_sub(3) / 10) as u8 };
+    let distance = |c: Color| rgb(c).map_or(u32::MAX, |other| distance((r, g, b), other));
+    if distance(Color::Indexed(gray)) < distance(Color::Indexed(cube)) { Color::Indexed(gray) } else { Color::Indexed(cube) }
+}
+
+fn to_16(color: Color) -> Color {
+    match color {
+        Color::Rgb(..) | Color::Indexed(_) => {
+            let Some(wanted) = rgb(color) else {
+                return color;
+            };
+            let nearest = (0..16).min_by_key(|&i| distance(wanted, BASE[i])).unwrap_or(7);
+            NAMED[nearest]
+        }
+        color => color,
+    }
+}
+
+// the base color and whether it was one of the bright ones
+fn to_8(color: Color) -> (Color, bool) {
+    match NAMED.iter().position(|c| *c == color) {
+        Some(i) if i >= 8 => (NAMED[i - 8], true),
+        _ => (color, false),
+    }
+}
+
+fn distance(a: (u8, u8, u8), b: (u8, u8, u8)) -> u32 {
+    let d = |x: u8, y: u8| (x as i32 - y as i32).pow(2) as u32;
+    d(a.0, b.0) + d(a.1, b.1) + d(a.2, b.2)
+}
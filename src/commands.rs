@@ -0,0 +1,154 @@
+use std::io::{BufRead, BufReader, Read};
+use std::path::PathBuf;
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+// user commands from config, listed in the palette as "run: <name>" (! opens it filtered to them):
+//   hydra.command       = "fmt: cargo fmt"           the tui steps aside while it runs
+//   hydra.panelCommand  = "test: cargo test {file}"  runs in the background, output in a panel
+// both run through the shell in the repo root. {file} is the selected file, {branch} the current
+// branch, {commit} the selected commit and {root} the repo root, quoted for the shell.
+
+const OUTPUT_LIMIT: usize = 5000;
+
+#[derive(Debug, Clone)]
+pub struct CustomCommand {
+    pub name: String,
+    pub label: String,
+    pub template: String,
+    pub panel: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct Vars {
+    pub file: Option<String>,
+    pub branch: Option<String>,
+    pub commit: Option<String>,
+    pub root: Option<PathBuf>,
+}
+
+pub fn parse(spec: &str, panel: bool) -> Result<CustomCommand, String> {
+    let key = if panel { "hydra.panelCommand" } else { "hydra.command" };
+    let (name, template) = spec
+        .split_once(':')
+        .ok_or_else(|| format!("{} '{}' should be '<name>: <command>'", key, spec))?;
+    let (name, template) = (name.trim(), template.trim());
+    if name.is_empty() || template.is_empty() {
+        return Err(format!("{} '{}' needs both a name and a command", key, spec));
+    }
+    Ok(CustomCommand { name: name.to_string(), label: format!("run: {}", name), template: template.to_string(), panel })
+}
+
+pub fn load(commands: &[String], panel_commands: &[String]) -> (Vec<CustomCommand>, Vec<String>) {
+    let mut loaded = Vec::new();
+    let mut errors = Vec::new();
+    let specs = commands.iter().map(|s| (s, false)).chain(panel_commands.iter().map(|s| (s, true)));
+    for (spec, panel) in specs {
+        match parse(spec, panel) {
+            Ok(command) => loaded.push(command),
+            Err(e) => errors.push(e),
+        }
+    }
+    (loaded, errors)
+}
+
+// the command line with every {variable} filled in, or which one there's nothing for
+pub fn render(template: &str, vars: &Vars) -> Result<String, String> {
+    let mut line = String::new();
+    let mut rest = template;
+    while let Some(open) = rest.find('{') {
+        let Some(close) = rest[open..].find('}') else {
+            break;
+        };
+        let name = &rest[open + 1..open + close];
+        let value = match name {
+            "file" => vars.file.clone().ok_or("{file} needs a selected file")?,
+            "branch" => vars.branch.clone().ok_or("{branch} needs a branch checked out")?,
+            "commit" => vars.commit.clone().ok_or("{commit} needs a selected commit, pick one in the log")?,
+            "root" => vars.root.as_ref().map(|r| r.display().to_string()).ok_or("{root} needs a working tree")?,
+            _ => return Err(format!("unknown variable {{{}}}, there's {{file}}, {{branch}}, {{commit}} and {{root}}", name)),
+        };
+        line.push_str(&rest[..open]);
+        line.push_str(&quote(&value));
+        rest = &rest[open + close + 1..];
+    }
+    line.push_str(rest);
+    Ok(line)
+}
+
+fn quote(value: &str) -> String {
+    if cfg!(windows) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        format!("'{}'", value.replace('\'', "'\\''"))
+    }
+}
+
+pub fn shell(line: &str) -> Command {
+    let mut cmd = if cfg!(windows) {
+        let mut c = Command::new("cmd");
+        c.arg("/C");
+        c
+    } else {
+        let mut c = Command::new("sh");
+        c.arg("-c");
+        c
+    };
+    cmd.arg(line);
+    cmd
+}
+
+// a panel command still going (or finished, with its status), output collected line by line
+pub struct RunningCommand {
+    pub name: String,
+    pub line: String,
+    pub output: Vec<String>,
+    pub status: Option<ExitStatus>,
+    child: Child,
+    lines: Receiver<String>,
+}
+
+impl RunningCommand {
+    pub fn start(name: &str, line: &str, mut cmd: Command) -> std::io::Result<Self> {
+        let mut child = cmd.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped()).spawn()?;
+        let (sender, lines) = mpsc::channel();
+        let stdout = child.stdout.take().map(|s| Box::new(s) as Box<dyn Read + Send>);
+        let stderr = child.stderr.take().map(|s| Box::new(s) as Box<dyn Read + Send>);
+        for stream in [stdout, stderr].into_iter().flatten() {
+            let sender = sender.clone();
+            thread::spawn(move || {
+                for line in BufReader::new(stream).lines().map_while(Result::ok) {
+                    if sender.send(line).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        Ok(Self { name: name.to_string(), line: line.to_string(), output: Vec::new(), status: None, child, lines })
+    }
+
+    // picks up new output, true once when the command finishes
+    pub fn poll(&mut self) -> bool {
+        self.output.extend(self.lines.try_iter());
+        if self.output.len() > OUTPUT_LIMIT {
+            self.output.drain(..self.output.len() - OUTPUT_LIMIT);
+        }
+        if self.status.is_some() {
+            return false;
+        }
+        match self.child.try_wait() {
+            Ok(Some(status)) => {
+                // whatever was printed right before it exited
+                self.output.extend(self.lines.try_iter());
+                self.status = Some(status);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn kill(&mut self) {
+        let _ = self.child.kill();
+    }
+}
@@ -0,0 +1,269 @@
+use clap::builder::ValueHint;
+
+// `git-hydra completions bash|zsh|fish`: a completion script made from the clap definition, so
+// new flags and subcommands show up without touching this. values come from what clap knows:
+// the choices of an enum, files for paths, and for an option whose value name is BRANCH or
+// REMOTE whatever `git-hydra complete branches|remotes` prints in the repo at hand
+
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum Shell {
+    Bash,
+    Zsh,
+    Fish,
+}
+
+// for the `complete` helper the scripts call
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum Candidates {
+    Branches,
+    Remotes,
+}
+
+// one per line, nothing when there's no repo here
+pub fn candidates(repo: &crate::git::Repository, what: Candidates) -> Vec<String> {
+    match what {
+        Candidates::Branches => repo.get_branches(),
+        Candidates::Remotes => repo.get_remotes(),
+    }
+    .unwrap_or_default()
+}
+
+// what an option or positional takes
+#[derive(Debug, Clone, PartialEq)]
+enum Values {
+    None,
+    Any,
+    Path,
+    Dir,
+    Choices(Vec<String>),
+    // the `complete` helper's argument
+    Dynamic(&'static str),
+}
+
+struct Opt {
+    long: Option<String>,
+    short: Option<char>,
+    values: Values,
+}
+
+struct Cmd {
+    name: String,
+    opts: Vec<Opt>,
+    positionals: Vec<Values>,
+    subcommands: Vec<Cmd>,
+}
+
+pub fn generate(shell: Shell, command: &mut clap::Command) -> String {
+    command.build();
+    let cmd = walk(command);
+    match shell {
+        Shell::Bash => bash(&cmd),
+        Shell::Zsh => zsh(&cmd),
+        Shell::Fish => fish(&cmd),
+    }
+}
+
+fn walk(command: &clap::Command) -> Cmd {
+    let mut opts = Vec::new();
+    let mut positionals = Vec::new();
+    for arg in command.get_arguments().filter(|a| !a.is_hide_set()) {
+        let values = values(arg);
+        if arg.is_positional() {
+            positionals.push(values);
+        } else {
+            opts.push(Opt { long: arg.get_long().map(|l| l.to_string()), short: arg.get_short(), values });
+        }
+    }
+    Cmd {
+        name: command.get_name().to_string(),
+        opts,
+        positionals,
+        subcommands: command.get_subcommands().filter(|c| !c.is_hide_set()).map(walk).collect(),
+    }
+}
+
+fn values(arg: &clap::Arg) -> Values {
+    if !arg.get_action().takes_values() {
+        return Values::None;
+    }
+    let choices: Vec<String> = arg.get_possible_values().iter().filter(|v| !v.is_hide_set()).map(|v| v.get_name().to_string()).collect();
+    if !choices.is_empty() {
+        return Values::Choices(choices);
+    }
+    match arg.get_value_names().and_then(|n| n.first()).map(|n| n.as_str()) {
+        Some("BRANCH") => return Values::Dynamic("branches"),
+        Some("REMOTE") => return Values::Dynamic("remotes"),
+        _ => {}
+    }
+    match arg.get_value_hint() {
+        ValueHint::AnyPath | ValueHint::FilePath => Values::Path,
+        ValueHint::DirPath => Values::Dir,
+        _ => Values::Any,
+    }
+}
+
+fn flags(opt: &Opt) -> Vec<String> {
+    opt.long.iter().map(|l| format!("--{}", l)).chain(opt.short.map(|s| format!("-{}", s))).collect()
+}
+
+fn words(cmd: &Cmd) -> String {
+    let mut words: Vec<String> = cmd.opts.iter().flat_map(flags).collect();
+    words.extend(cmd.subcommands.iter().map(|c| c.name.clone()));
+    if let Some(Values::Choices(choices)) = cmd.positionals.first() {
+        words.extend(choices.iter().cloned());
+    }
+    words.join(" ")
+}
+
+fn bash(cmd: &Cmd) -> String {
+    let function = format!("_{}", cmd.name.replace('-', "_"));
+    let subcommands: Vec<&str> = cmd.subcommands.iter().map(|c| c.name.as_str()).collect();
+    // top level options that take a value, so their value isn't taken for a subcommand
+    let skip: Vec<String> = cmd.opts.iter().filter(|o| o.values != Values::None).flat_map(flags).collect();
+
+    let mut out = format!("{}() {{\n", function);
+    out.push_str("    local cur prev cmd i\n");
+    out.push_str("    cur=\"${COMP_WORDS[COMP_CWORD]}\"\n");
+    out.push_str("    prev=\"${COMP_WORDS[COMP_CWORD-1]}\"\n");
+    out.push_str("    cmd=\"\"\n");
+    out.push_str("    for ((i = 1; i < COMP_CWORD; i++)); do\n");
+    out.push_str("        case \"${COMP_WORDS[i]}\" in\n");
+    if !skip.is_empty() {
+        out.push_str(&format!("            {}) ((i++)) ;;\n", skip.join("|")));
+    }
+    if !subcommands.is_empty() {
+        out.push_str(&format!("            {}) cmd=\"${{COMP_WORDS[i]}}\"; break ;;\n", subcommands.join("|")));
+    }
+    out.push_str("        esac\n");
+    out.push_str("    done\n");
+
+    out.push_str("    case \"$cmd:$prev\" in\n");
+    let scoped = std::iter::once(("", cmd)).chain(cmd.subcommands.iter().map(|c| (c.name.as_str(), c)));
+    for (name, sub) in scoped.clone() {
+        for opt in &sub.opts {
+            let reply = match &opt.values {
+                Values::None | Values::Any => continue,
+                Values::Path => "compgen -f -- \"$cur\"".to_string(),
+                Values::Dir => "compgen -d -- \"$cur\"".to_string(),
+                Values::Choices(choices) => format!("compgen -W \"{}\" -- \"$cur\"", choices.join(" ")),
+                Values::Dynamic(what) => format!("compgen -W \"$({} complete {} 2>/dev/null)\" -- \"$cur\"", cmd.name, what),
+            };
+            let patterns: Vec<String> = flags(opt).iter().map(|f| format!("{}:{}", name, f)).collect();
+            out.push_str(&format!("        {}) COMPREPLY=($({})); return ;;\n", patterns.join("|"), reply));
+        }
+    }
+    out.push_str("    esac\n");
+
+    out.push_str("    case \"$cmd\" in\n");
+    for (name, sub) in scoped {
+        out.push_str(&format!("        \"{}\") COMPREPLY=($(compgen -W \"{}\" -- \"$cur\")) ;;\n", name, words(sub)));
+    }
+    out.push_str("    esac\n");
+    out.push_str("}\n");
+    out.push_str(&format!("complete -F {} {}\n", function, cmd.name));
+    out
+}
+
+// the part after "description:" in an _arguments spec
+fn zsh_action(values: &Values, program: &str) -> String {
+    match values {
+        Values::None | Values::Any => " ".to_string(),
+        Values::Path => "_files".to_string(),
+        Values::Dir => "_files -/".to_string(),
+        Values::Choices(choices) => format!("({})", choices.join(" ")),
+        Values::Dynamic(what) => format!("{{compadd -- ${{(f)\"$({} complete {} 2>/dev/null)\"}}}}", program, what),
+    }
+}
+
+fn zsh_specs(cmd: &Cmd, program: &str) -> Vec<String> {
+    let mut specs = Vec::new();
+    for opt in &cmd.opts {
+        let (long_sep, short_sep, action) = match opt.values {
+            Values::None => ("", "", String::new()),
+            _ => ("=", "+", format!(": :{}", zsh_action(&opt.values, program))),
+        };
+        if let Some(long) = &opt.long {
+            specs.push(format!("'--{}{}[]{}'", long, long_sep, action));
+        }
+        if let Some(short) = opt.short {
+            specs.push(format!("'-{}{}[]{}'", short, short_sep, action));
+        }
+    }
+    for (i, values) in cmd.positionals.iter().enumerate() {
+        specs.push(format!("'{}: :{}'", i + 1, zsh_action(values, program)));
+    }
+    specs
+}
+
+fn zsh(cmd: &Cmd) -> String {
+    let function = format!("_{}", cmd.name.replace('-', "_"));
+    let mut out = format!("#compdef {}\n\n{}() {{\n", cmd.name, function);
+    out.push_str("    local state\n");
+    let mut specs = zsh_specs(cmd, &cmd.name);
+    if !cmd.subcommands.is_empty() {
+        specs.push("'1: :->command'".to_string());
+        specs.push("'*:: :->args'".to_string());
+    }
+    out.push_str(&format!("    _arguments -C \\\n        {} && return\n", specs.join(" \\\n        ")));
+    if !cmd.subcommands.is_empty() {
+        let names: Vec<&str> = cmd.subcommands.iter().map(|c| c.name.as_str()).collect();
+        out.push_str("    case $state in\n");
+        out.push_str(&format!("        command) _values 'command' {} ;;\n", names.join(" ")));
+        out.push_str("        args)\n");
+        out.push_str("            case $words[1] in\n");
+        for sub in &cmd.subcommands {
+            let specs = zsh_specs(sub, &cmd.name);
+            if specs.is_empty() {
+                continue;
+            }
+            out.push_str(&format!("                {}) _arguments {} ;;\n", sub.name, specs.join(" ")));
+        }
+        out.push_str("            esac\n");
+        out.push_str("            ;;\n");
+        out.push_str("    esac\n");
+    }
+    out.push_str("}\n\n");
+    out.push_str(&format!("{} \"$@\"\n", function));
+    out
+}
+
+// what follows the flags of a `complete` line: -r when it takes a value, -F for files, -a for
+// the candidates. positionals take no -r, there is no option to give it to
+fn fish_values(values: &Values, program: &str, option: bool) -> String {
+    let takes = if option { " -r" } else { "" };
+    match values {
+        Values::None => String::new(),
+        Values::Any => takes.to_string(),
+        Values::Path | Values::Dir => format!("{} -F", takes),
+        Values::Choices(choices) => format!("{} -a '{}'", takes, choices.join(" ")),
+        Values::Dynamic(what) => format!("{} -a '({} complete {} 2>/dev/null)'", takes, program, what),
+    }
+}
+
+fn fish(cmd: &Cmd) -> String {
+    let program = &cmd.name;
+    let mut out = format!("complete -c {} -f\n", program);
+    for sub in &cmd.subcommands {
+        out.push_str(&format!("complete -c {} -n __fish_use_subcommand -a {}\n", program, sub.name));
+    }
+    let scoped = std::iter::once(("__fish_use_subcommand".to_string(), cmd))
+        .chain(cmd.subcommands.iter().map(|c| (format!("'__fish_seen_subcommand_from {}'", c.name), c)));
+    for (condition, sub) in scoped {
+        for opt in &sub.opts {
+            let mut line = format!("complete -c {} -n {}", program, condition);
+            if let Some(short) = opt.short {
+                line.push_str(&format!(" -s {}", short));
+            }
+            if let Some(long) = &opt.long {
+                line.push_str(&format!(" -l {}", long));
+            }
+            line.push_str(&fish_values(&opt.values, program, true));
+            out.push_str(&line);
+            out.push('\n');
+        }
+        if let Some(values) = sub.positionals.first() && values != &Values::None {
+            out.push_str(&format!("complete -c {} -n {}{}\n", program, condition, fish_values(values, program, false)));
+        }
+    }
+    out
+}
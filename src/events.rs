@@ -0,0 +1,123 @@
+use crate::git::{RepoSnapshot, Repository};
+use anyhow::Result;
+use crossterm::event::{self, Event, KeyEvent};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::time::{Duration, Instant};
+
+// the run loop's one queue. keys and resizes from the terminal, the redraw timer, the repo watcher
+// and whatever runs on a thread (a fetch, ...) all come out of it as a Message, and App::update
+// is the only place they change the app. background work gets a Sender (or goes through
+// Queue::spawn) and sends its Message when it's done, so the ui never sits waiting for it.
+// the terminal isn't read on a thread of its own: while an editor or a hydra.command has the
+// screen, nothing may take its keys
+
+#[derive(Debug)]
+pub enum Message {
+    // from the terminal, or a --replay file
+    Key(KeyEvent),
+    // a resize, focus change or paste, nothing to do but draw again
+    Terminal,
+    // the redraw tick came round: background work that's polled, notifications timing out
+    Tick,
+    // an auto-fetch run is done, with what went wrong per remote
+    Fetched(Vec<String>),
+    // HEAD, a branch or the index moved, maybe outside hydra
+    RepoChanged,
+}
+
+// how often the terminal wait is cut short to look for a task's message
+const SLICE: Duration = Duration::from_millis(50);
+
+// how often the watcher looks at HEAD and the index. there's no file watching crate in here
+const WATCH_EVERY: Duration = Duration::from_millis(500);
+
+pub struct Queue {
+    sender: Sender<Message>,
+    receiver: Receiver<Message>,
+}
+
+impl Default for Queue {
+    fn default() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Self { sender, receiver }
+    }
+}
+
+impl Queue {
+    pub fn sender(&self) -> Sender<Message> {
+        self.sender.clone()
+    }
+
+    // runs `task` on a thread, what it returns comes out of the queue
+    pub fn spawn(&self, task: impl FnOnce() -> Message + Send + 'static) {
+        let sender = self.sender();
+        std::thread::spawn(move || {
+            let _ = sender.send(task());
+        });
+    }
+
+    // the next message a task sent, waiting up to `wait` for one
+    pub fn task(&self, wait: Duration) -> Option<Message> {
+        self.receiver.recv_timeout(wait).ok()
+    }
+
+    // whichever comes first, a task's message or the terminal's. None once `wait` is up
+    pub fn next(&self, wait: Duration) -> Result<Option<Message>> {
+        let until = Instant::now() + wait;
+        loop {
+            if let Some(message) = self.task(Duration::ZERO) {
+                return Ok(Some(message));
+            }
+            let left = until.saturating_duration_since(Instant::now());
+            if event::poll(left.min(SLICE))? {
+                return Ok(Some(match event::read()? {
+                    Event::Key(key) => Message::Key(key),
+                    _ => Message::Terminal,
+                }));
+            }
+            if Instant::now() >= until {
+                return Ok(None);
+            }
+        }
+    }
+}
+
+// sends RepoChanged whenever HEAD or the index looks different from last time, hydra's own
+// changes too (App::update tells them apart). stops when dropped
+pub struct Watcher {
+    stop: Arc<AtomicBool>,
+}
+
+impl Watcher {
+    pub fn start(repo: &Repository, sender: Sender<Message>) -> Self {
+        let stop = Arc::new(AtomicBool::new(false));
+        let git_dir = repo.git_dir().to_path_buf();
+        let work_tree: Option<PathBuf> = repo.workdir().map(|w| w.to_path_buf());
+        let stopped = Arc::clone(&stop);
+        std::thread::spawn(move || {
+            // git2 handles can't cross threads, the watcher opens the repo again
+            let Ok(repo) = Repository::open_git_dir(&git_dir, work_tree.as_deref()) else {
+                return;
+            };
+            let mut seen = repo.snapshot(&RepoSnapshot::default());
+            while !stopped.load(Ordering::Relaxed) {
+                std::thread::sleep(WATCH_EVERY);
+                let now = repo.snapshot(&seen);
+                if now != seen && sender.send(Message::RepoChanged).is_err() {
+                    return;
+                }
+                seen = now;
+            }
+        });
+        Self { stop }
+    }
+}
+
+impl Drop for Watcher {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+}
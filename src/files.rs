@@ -0,0 +1,231 @@
+use crate::app::ViewState;
+use crate::git::{Repository, TreeEntry, TreeEntryKind};
+use crate::highlight::{self, Kind};
+use anyhow::{Context, Result};
+use crossterm::event::KeyCode;
+use std::path::Path;
+
+// the files view (f in status or log): the tree of HEAD or the selected commit like a file
+// explorer, a directory at a time, with the selected file shown next to it as it was then. /
+// searches every file of the tree by name, x writes the selected one out of the repo.
+
+// past this much the preview only shows the start of the file
+const PREVIEW_LINES: usize = 5_000;
+
+pub enum Preview {
+    Empty,
+    Dir(usize),
+    Text(Vec<Vec<(Kind, String)>>),
+    Binary(usize),
+    // a partial clone that couldn't get the blob
+    NotFetched,
+    Submodule,
+}
+
+pub struct Search {
+    pub query: String,
+    pub matches: Vec<String>,
+    pub view: ViewState,
+}
+
+pub struct FileBrowser {
+    // the full id of the commit, and what it was opened as (HEAD or the id)
+    pub commit: String,
+    pub label: String,
+    // "" for the top, else the path ending in /
+    pub dir: String,
+    pub entries: Vec<TreeEntry>,
+    // selection in the directory, and how far the preview is scrolled
+    pub view: ViewState,
+    pub preview: Preview,
+    pub search: Option<Search>,
+    // the file name typed for x
+    pub export: Option<String>,
+    // every file of the tree, read the first time a search needs it
+    paths: Option<Vec<String>>,
+}
+
+impl FileBrowser {
+    pub fn open(repo: &Repository, rev: &str) -> Result<Self> {
+        let commit = repo.resolve_commit(rev)?;
+        let label = if rev == "HEAD" { "HEAD".to_string() } else { repo.short_id(&commit) };
+        let mut browser = Self {
+            commit,
+            label,
+            dir: String::new(),
+            entries: Vec::new(),
+            view: ViewState::default(),
+            preview: Preview::Empty,
+            search: None,
+            export: None,
+            paths: None,
+        };
+        browser.enter(repo, String::new(), None)?;
+        Ok(browser)
+    }
+
+    pub fn selected(&self) -> Option<&TreeEntry> {
+        self.entries.get(self.view.selected)
+    }
+
+    // shows `dir` with `select` (a name in it) selected, or the first entry
+    pub fn enter(&mut self, repo: &Repository, dir: String, select: Option<&str>) -> Result<()> {
+        self.entries = repo.tree_entries(&self.commit, &dir)?;
+        self.dir = dir;
+        self.view = ViewState::default();
+        if let Some(name) = select {
+            self.view.selected = self.entries.iter().position(|e| e.name == name).unwrap_or(0);
+        }
+        self.load_preview(repo);
+        Ok(())
+    }
+
+    // into the selected directory. true when it was one
+    pub fn open_selected(&mut self, repo: &Repository) -> Result<bool> {
+        match self.selected() {
+            Some(entry) if entry.kind == TreeEntryKind::Dir => {
+                let dir = entry.path.clone();
+                self.enter(repo, dir, None)?;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    // to the parent directory, with the one we were in selected
+    pub fn up(&mut self, repo: &Repository) -> Result<()> {
+        let Some(trimmed) = self.dir.strip_suffix('/') else {
+            return Ok(());
+        };
+        let (parent, name) = match trimmed.rsplit_once('/') {
+            Some((parent, name)) => (format!("{}/", parent), name.to_string()),
+            None => (String::new(), trimmed.to_string()),
+        };
+        self.enter(repo, parent, Some(&name))
+    }
+
+    pub fn select(&mut self, repo: &Repository, index: usize) {
+        if index < self.entries.len() && index != self.view.selected {
+            self.view.selected = index;
+            self.view.scroll = 0;
+            self.load_preview(repo);
+        }
+    }
+
+    pub fn load_preview(&mut self, repo: &Repository) {
+        self.preview = match self.selected() {
+            None => Preview::Empty,
+            Some(entry) => match entry.kind {
+                TreeEntryKind::Dir => match repo.tree_entries(&self.commit, &entry.path) {
+                    Ok(entries) => Preview::Dir(entries.len()),
+                    Err(_) => Preview::Empty,
+                },
+                TreeEntryKind::Submodule => Preview::Submodule,
+                _ => match repo.file_at(&self.commit, &entry.path) {
+                    Ok(Some(content)) => preview(&entry.path, &content),
+                    Ok(None) => Preview::NotFetched,
+                    Err(e) => Preview::Text(vec![vec![(Kind::Plain, format!("can't read it: {:#}", e))]]),
+                },
+            },
+        };
+    }
+
+    pub fn start_search(&mut self, repo: &Repository) -> Result<()> {
+        if self.paths.is_none() {
+            self.paths = Some(repo.tree_paths(&self.commit)?);
+        }
+        self.search = Some(Search { query: String::new(), matches: Vec::new(), view: ViewState::default() });
+        self.update_search();
+        Ok(())
+    }
+
+    // every file with the query in its path, case doesn't matter. the ones with it in the file
+    // name itself go first
+    pub fn update_search(&mut self) {
+        let (Some(search), Some(paths)) = (&mut self.search, &self.paths) else {
+            return;
+        };
+        let query = search.query.to_lowercase();
+        let mut matches: Vec<&String> = paths.iter().filter(|p| p.to_lowercase().contains(&query)).collect();
+        let in_name = |path: &str| path.rsplit('/').next().unwrap_or(path).to_lowercase().contains(&query);
+        matches.sort_by_key(|p| !in_name(p));
+        search.matches = matches.into_iter().cloned().collect();
+        search.view = ViewState::default();
+    }
+
+    // a key while the search is open: typing narrows it, enter goes to the match
+    pub fn search_key(&mut self, repo: &Repository, key: KeyCode) -> Result<()> {
+        let Some(search) = &mut self.search else {
+            return Ok(());
+        };
+        match key {
+            KeyCode::Esc => self.search = None,
+            KeyCode::Enter => self.go_to_match(repo)?,
+            KeyCode::Up => search.view.selected = search.view.selected.saturating_sub(1),
+            KeyCode::Down if search.view.selected + 1 < search.matches.len() => search.view.selected += 1,
+            KeyCode::Char(c) => {
+                search.query.push(c);
+                self.update_search();
+            }
+            KeyCode::Backspace => {
+                search.query.pop();
+                self.update_search();
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    // the picked search result, in its directory
+    pub fn go_to_match(&mut self, repo: &Repository) -> Result<()> {
+        let Some(path) = self.search.take().and_then(|s| s.matches.get(s.view.selected).cloned()) else {
+            return Ok(());
+        };
+        let (dir, name) = match path.rsplit_once('/') {
+            Some((dir, name)) => (format!("{}/", dir), name),
+            None => (String::new(), path.as_str()),
+        };
+        self.enter(repo, dir, Some(name))
+    }
+
+    // what x suggests: the file's name with the commit in it, so it doesn't land on the
+    // worktree's own copy
+    pub fn export_name(&self, repo: &Repository) -> Option<String> {
+        let entry = self.selected().filter(|e| !matches!(e.kind, TreeEntryKind::Dir | TreeEntryKind::Submodule))?;
+        let short = repo.short_id(&self.commit);
+        Some(match entry.name.rsplit_once('.') {
+            Some((stem, extension)) if !stem.is_empty() => format!("{}@{}.{}", stem, short, extension),
+            _ => format!("{}@{}", entry.name, short),
+        })
+    }
+
+    // writes the selected file to `out`, never over a file that's there already
+    pub fn export(&self, repo: &Repository, out: &Path) -> Result<usize> {
+        let entry = self.selected().context("nothing selected")?;
+        if out.exists() {
+            anyhow::bail!("{} already exists", out.display());
+        }
+        let content = repo
+            .file_at(&self.commit, &entry.path)?
+            .with_context(|| format!("{} isn't in this partial clone and couldn't be fetched", entry.path))?;
+        if let Some(parent) = out.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(out, &content).with_context(|| format!("cannot write {}", out.display()))?;
+        Ok(content.len())
+    }
+}
+
+fn preview(path: &str, content: &[u8]) -> Preview {
+    // like git, a NUL in the first 8000 bytes makes it binary
+    if content.iter().take(8000).any(|&b| b == 0) {
+        return Preview::Binary(content.len());
+    }
+    let text = String::from_utf8_lossy(content).replace('\t', "    ");
+    let shown: Vec<&str> = text.lines().take(PREVIEW_LINES + 1).collect();
+    let mut lines = highlight::highlight(path, &shown[..shown.len().min(PREVIEW_LINES)].join("\n"));
+    if shown.len() > PREVIEW_LINES {
+        lines.push(vec![(Kind::Comment, format!("… cut off after {} lines", PREVIEW_LINES))]);
+    }
+    Preview::Text(lines)
+}
@@ -1,974 +1,4672 @@
-use anyhow::{Context, Result};
-use chrono::{DateTime, Local};
-use git2::{Repository as Git2Repository, DiffOptions, StatusOptions, PushOptions, RemoteCallbacks, Cred, Progress};
-use std::io::{self, Write};
-use std::{any, fmt, vec};
-use std::fs;
-use std::path::Path;
-use std::collections::HashMap;
-
-#[derive(Debug, Clone)]
-pub struct Commit {
-    pub id: String,
-    pub message: String,
-    pub author: String,
-    pub timestamp: DateTime<Local>,
-    pub parents: Vec<String>,
-}
-
-impl fmt::Display for Commit {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result{
-        write!(
-            f,
-            "{} {} ({})",
-            &self.id[..8],
-            self.message.lines().next().unwrap_or(""),
-            self.author
-        )
-    }
-}
-
-#[derive(Debug, Clone)]
-pub struct FileStatus {
-    pub path: String,
-    pub status: String,
-}
-
-#[derive(Debug)]
-pub struct RepoStatus {
-    pub branch: String,
-    pub ahead: usize,
-    pub behind: usize,
-    pub staged: Vec<FileStatus>,
-    pub unstaged: Vec<FileStatus>,
-    pub untracked: Vec<FileStatus>,
-}
-
-#[derive(Debug, Clone)]
-pub struct ConflictHunk {
-    pub start_line: usize,
-    pub end_line: usize,
-    pub our_content: String,
-    pub their_content: String,
-    pub base_content: Option<String>,
-}
-
-#[derive(Debug, Clone)]
-pub struct ConflictedFile {
-    pub path: String,
-    pub conflicts: Vec<ConflictHunk>
-}
-
-#[derive(Debug, Clone)] 
-pub struct MergeConflict {
-    pub files: Vec<ConflictedFile>,
-    pub our_commit: String,
-    pub their_commit: String,
-}
-
-#[derive(Debug, Clone)]
-pub enum MergeResolution {
-    KeepOurs,
-    KeepTheirs,
-    KeepBoth,
-    Custom(String),
-}
-
-#[derive(Debug, Clone, Copy, PartialEq)] 
-pub enum ResetMode {
-    Soft,
-    Mixed,
-    Hard,
-}
-
-impl ConflictHunk {
-    pub fn resolve(&self, resolution: &MergeResolution) -> String {
-        match resolution {
-            MergeResolution::KeepOurs => self.our_content.clone(),
-            MergeResolution::KeepTheirs => self.their_content.clone(),
-            MergeResolution::KeepBoth => format!("{}\n{}", self.our_content, self.their_content),
-            MergeResolution::Custom(content) => content.clone(),
-        }
-    }
-}
-
-impl fmt::Display for ResetMode {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            ResetMode::Soft => write!(f, "soft"),
-            ResetMode::Mixed => write!(f, "mixed"),
-            ResetMode::Hard => write!(f, "hard"),
-        }
-    }
-}
-
-impl fmt::Display for RepoStatus {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        writeln!(f, "branch: {}", self.branch)?;
-        if self.ahead > 0 {
-            writeln!(f, "ahead by {} commits.", self.ahead)?;
-        }
-        if self.behind > 0 {
-            writeln!(f, "behind by {} commits.", self.behind)?;
-        }
-
-        if !self.staged.is_empty(){
-            writeln!(f, "\nstaged changes:")?;
-            for file in &self.staged {
-                writeln!(f, " {} {}", file.status, file.path)?;
-            }
-        }
-
-        if !self.unstaged.is_empty(){
-            writeln!(f, "\nunstaged changes:")?;
-            for file in &self.unstaged {
-                writeln!(f, " {} {}", file.status, file.path)?;
-            }
-        }
-
-        if !self.untracked.is_empty() {
-            writeln!(f, "\nuntracked files:")?;
-            for file in &self.untracked {
-                writeln!(f, " {} {}", file.status, file.path)?;
-            }
-        }
-
-        Ok(())
-    }
-}
-
-pub struct Repository {
-    repo: Git2Repository
-}
-
-impl Repository {
-    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let repo = Git2Repository::discover(path)
-            .context("failed to find Git repository. try again.")?;
-        Ok(Self { repo })
-    }
-
-    pub fn status(&self) -> Result<RepoStatus> {
-        let mut opts = StatusOptions::new();
-        opts.include_untracked(true);
-        opts.include_ignored(false);
-
-        let statuses = self.repo.statuses(Some(&mut opts))?;
-
-        let mut staged = Vec::new();
-        let mut unstaged = Vec::new();
-        let mut untracked = Vec::new();
-
-        for entry in statuses.iter() {
-            let path = entry.path().unwrap_or("").to_string();
-            let status = entry.status();
-
-            if status.is_index_new() || status.is_index_modified() || status.is_index_deleted() {
-                let status_char = if status.is_index_new() { "A" }
-                else if status.is_index_modified() { "M" }
-                else if status.is_index_deleted() { "D" }
-                else { "?" };
-
-                staged.push(FileStatus {
-                    path: path.clone(),
-                    status: status_char.to_string(),
-                });
-            }
-
-            if status.is_wt_new() {
-                untracked.push(FileStatus {
-                    path,
-                    status: "??".to_string(),
-                });
-            } else if status.is_wt_modified() || status.is_wt_deleted() {
-                let status_char = if status.is_wt_modified() { "M" } else { "D" };
-                unstaged.push(FileStatus {
-                    path,
-                    status: status_char.to_string(),
-                });
-            }
-        }
-
-        let head = self.repo.head()?;
-        let branch = if let Some(name) = head.shorthand() {
-            name.to_string()
-        } else {
-            "HEAD".to_string()
-        };
-
-        let (ahead, behind) = self.calculate_ahead_behind()?;
-
-        Ok(RepoStatus {
-            branch,
-            ahead,
-            behind,
-            staged,
-            unstaged,
-            untracked,
-        })
-    }
-
-    pub fn get_commits(&self, count: usize) -> Result<Vec<Commit>> {
-        let mut revwalk = self.repo.revwalk()?;
-        revwalk.push_head()?;
-        revwalk.set_sorting(git2::Sort::TIME)?;
-
-        let mut commits = Vec::new();
-
-        for (i, oid) in revwalk.enumerate() {
-            if i >= count { break; }
-
-            let oid = oid?;
-            let commit = self.repo.find_commit(oid)?;
-
-            let message = commit.message().unwrap_or("").to_string();
-            let author = commit.author();
-            let author_name = author.name().unwrap_or("unknown").to_string();
-
-            let timestamp = DateTime::from_timestamp(commit.time().seconds(), 0)
-                .unwrap_or_default()
-                .with_timezone(&Local);
-
-            let parents = commit.parents()
-                .map(|p| p.id().to_string())
-                .collect();
-            
-            commits.push(Commit {
-                id: oid.to_string(),
-                message,
-                author: author_name,
-                timestamp,
-                parents,
-            });
-
-        }
-        Ok(commits)
-    }
-
-    fn calculate_ahead_behind(&self) -> Result<(usize, usize)> {
-        let head = match self.repo.head(){
-            Ok(head) => head,
-            Err(_) => return Ok((0, 0)), // empty repo / head not found
-        };
-        
-        let local_oid = head.target().context("failed to get HEAD target.")?;
-
-        let branch_name = head.shorthand().unwrap_or("HEAD");
-        let upstream_name = format!("refs/remotes/origin/{}", branch_name);
-
-        let upstream_ref = match self.repo.find_reference(&upstream_name){
-            Ok(r) => r,
-            Err(_) => return Ok((0, 0))
-        };
-
-        let upstream_oid = upstream_ref.target().context("failed to get upstream target.")?;
-
-        let (ahead, behind) = self.repo.graph_ahead_behind(local_oid, upstream_oid)?;
-
-        Ok((ahead, behind))
-    }
-
-    pub fn reset_to_commit(&mut self, commit_id: &str, mode: ResetMode) -> Result<()> {
-        let oid = git2::Oid::from_str(commit_id)?;
-        let commit = self.repo.find_commit(oid)?;
-        let obj = commit.as_object();
-
-        let reset_type = match mode {
-            ResetMode::Soft => git2::ResetType::Soft,
-            ResetMode::Mixed => git2::ResetType::Mixed,
-            ResetMode::Hard => git2::ResetType::Hard,
-        };
-
-        if mode == ResetMode::Hard {
-            let mut checkout_opts = git2::build::CheckoutBuilder::new();
-            checkout_opts.force();
-            self.repo.reset(obj, reset_type, Some(&mut checkout_opts))?;
-        } else {
-            self.repo.reset(obj, reset_type, None)?;
-        };
-
-        Ok(())
-    }
-
-    pub fn reset_head(&mut self, mode: ResetMode) -> Result<()> {
-        let parent_id = {
-            let head = self.repo.head()?;
-            let commit = head.peel_to_commit()?;
-
-            if let Some(parent) = commit.parents().next() {
-                parent.id().to_string()
-            } else {
-                return Err(anyhow::anyhow!("cannot reset: no parent commit found"));
-            }
-        };
-
-        self.reset_to_commit(&parent_id, mode)
-    }
-
-    pub fn get_branches(&self) -> Result<Vec<String>> {
-        let branches = self.repo.branches(None)?;
-        let mut branch_names = Vec::new();
-
-        for branch in branches {
-            let (branch, _) = branch?;
-            if let Some(name) = branch.name()? {
-                branch_names.push(name.to_string());
-            }
-        }
-
-        Ok(branch_names)
-    }
-
-    pub fn stage_file(&self, path: &str) -> Result<()> {
-        let mut index = self.repo.index()?;
-        index.add_path(Path::new(path))?;
-        index.write()?;
-        Ok(())
-    }
-
-    pub fn unstage_file(&self, path: &str) -> Result<()> {
-        let mut index = self.repo.index()?;
-        let head = self.repo.head()?.peel_to_tree()?;
-
-        let entry = head.get_path(Path::new(path))?;
-        let mut index_entry = git2::IndexEntry{
-            ctime: git2::IndexTime::new(0, 0),
-            mtime: git2::IndexTime::new(0, 0),
-            dev: 0,
-            ino: 0,
-            mode: entry.filemode() as u32,
-            uid: 0,
-            gid: 0,
-            file_size: entry.to_object(&self.repo)?.as_blob().unwrap().size() as u32,
-            id: entry.id(),
-            flags: 0,
-            flags_extended: 0,
-            path: path.as_bytes().to_vec(),
-        };
-
-        index.add(&index_entry)?;
-        index.write()?;
-        Ok(())
-    }
-
-    pub fn commit(&self, message: &str) -> Result<git2::Oid>{
-        let signature = self.repo.signature()?;
-        let mut index = self.repo.index()?;
-        let tree_id = index.write_tree()?;
-        let tree = self.repo.find_tree(tree_id)?;
-
-        let parent_commit = match self.repo.head(){
-            Ok(head) => Some(head.peel_to_commit()?),
-            Err(_) => None,
-        };
-
-        let parents = if let Some(ref parent) = parent_commit {
-            vec![parent]
-        } else {
-            vec![]
-        };
-
-        let commit_id = self.repo.commit(
-            Some("HEAD"),
-            &signature,
-            &signature,
-            message,
-            &tree,
-            &parents,
-        )?;
-
-        Ok(commit_id)
-    }
-
-    pub fn create_branch(&self, name: &str) -> Result<()> {
-        let head = self.repo.head()?;
-        let commit = head.peel_to_commit()?;
-        self.repo.branch(name, &commit, false)?;
-
-        Ok(())
-    }
-
-    pub fn checkout_branch(&self, name: &str) -> Result<()>{
-        let ref_name = format!("refs/heads/{}", name);
-        let obj = self.repo.revparse_single(&ref_name)?;
-
-        self.repo.checkout_tree(&obj, None)?;
-        self.repo.set_head(&ref_name)?;
-        Ok(())
-    }
-
-    pub fn get_file_diff(&self, file_path: &str) -> Result<String> {
-        let mut diff_opts = DiffOptions::new();
-        diff_opts.pathspec(file_path);
-
-        let diff = self.repo.diff_index_to_workdir(None, Some(&mut diff_opts))?;
-
-        let mut diff_text = String::new();
-        diff.print(git2::DiffFormat::Patch, |_delta, _hunk, line| {
-            match line.origin(){
-                '+' => diff_text.push_str(&format!("+{}", std::str::from_utf8(line.content()).unwrap_or(""))),
-                '-' => diff_text.push_str(&format!("-{}", std::str::from_utf8(line.content()).unwrap_or(""))),
-                ' ' => diff_text.push_str(&format!(" {}", std::str::from_utf8(line.content()).unwrap_or(""))),
-                '=' => diff_text.push_str(&format!("={}", std::str::from_utf8(line.content()).unwrap_or(""))),
-                '>' => diff_text.push_str(&format!(">{}", std::str::from_utf8(line.content()).unwrap_or(""))),
-                '<' => diff_text.push_str(&format!("<{}", std::str::from_utf8(line.content()).unwrap_or(""))),
-                'F' => diff_text.push_str(&format!("F{}", std::str::from_utf8(line.content()).unwrap_or(""))),
-                'H' => diff_text.push_str(&format!("H{}", std::str::from_utf8(line.content()).unwrap_or(""))),
-                _ => diff_text.push_str(std::str::from_utf8(line.content()).unwrap_or("")),
-            }
-            true
-        })?;
-
-        if diff_text.is_empty(){
-            let head_tree = self.repo.head()?.peel_to_tree()?;
-            let diff = self.repo.diff_tree_to_index(Some(&head_tree), None, Some(&mut diff_opts))?;
-
-            diff.print(git2::DiffFormat::Patch, |_delta, _hunk, line| {
-                match line.origin(){
-                    '+' => diff_text.push_str(&format!("+{}", std::str::from_utf8(line.content()).unwrap_or(""))),
-                    '-' => diff_text.push_str(&format!("-{}", std::str::from_utf8(line.content()).unwrap_or(""))),
-                    ' ' => diff_text.push_str(&format!(" {}", std::str::from_utf8(line.content()).unwrap_or(""))),
-                    _ => diff_text.push_str(std::str::from_utf8(line.content()).unwrap_or("")),
-                }
-                true
-            })?;
-        }
-
-        Ok(diff_text)
-    }
-
-    pub fn stash_save(&mut self, message: Option<&str>) -> Result<git2::Oid> {
-        let signature = self.repo.signature()?;
-        let default_msg = "WIP on branch";
-        let stash_msg = message.unwrap_or(default_msg);
-        
-        let stash_id = self.repo.stash_save(&signature, stash_msg, Some(git2::StashFlags::DEFAULT))?;
-
-        Ok(stash_id)
-    }
-
-    pub fn stash_pop(&mut self) -> Result<()>{
-        let mut checkout_opts = git2::build::CheckoutBuilder::new();
-        checkout_opts.allow_conflicts(true);
-        checkout_opts.conflict_style_merge(true);
-
-        let mut stash_apply_opts = git2::StashApplyOptions::new();
-        stash_apply_opts.checkout_options(checkout_opts);
-
-        self.repo.stash_pop(0, Some(&mut stash_apply_opts))?;
-        Ok(())
-    }
-
-    pub fn stash_list(&mut self) -> Result<Vec<String>> {
-        let mut stashes = Vec::new();
-
-        self.repo.stash_foreach(|index, message, _oid| {
-            stashes.push(format!("stash@{{{}}}: {}", index, message));
-            true
-        })?;
-
-        Ok(stashes)
-    }
-
-    pub fn stash_drop(&mut self, index: usize) -> Result<()> {
-        self.repo.stash_drop(index)?;
-        Ok(())
-    }
-
-    // gaahhhhh
-    pub fn push_to_remote(&mut self, remote_name: &str, branch_name: &str) -> Result<String> {
-        let mut remote = self.repo.find_remote(remote_name)?;
-
-        let mut callbacks = RemoteCallbacks::new();
-
-        callbacks.credentials(|url, username_from_url, _allowed_types| {
-            if url.starts_with("git@") || url.contains("ssh://") {
-                if let Ok(cred) = Cred::ssh_key_from_agent(username_from_url.unwrap_or("git")) {
-                    return Ok(cred);
-                }
-
-                if let Ok(cred) = Cred::ssh_key(
-                    username_from_url.unwrap_or("git"),
-                    None,
-                    std::path::Path::new(&format!("{}/.ssh/id_rsa", std::env::var("HOME").unwrap_or_default())),
-                    None,
-                ) {
-                    return Ok(cred);
-                }
-            }
-
-            if let Ok(cred) = Cred::credential_helper(&self.repo.config()?, url, username_from_url) {
-                return Ok(cred);
-            }
-            if let Ok(cred) = Cred::default() {
-                return Ok(cred);
-            }
-
-            Cred::userpass_plaintext(username_from_url.unwrap_or(""), "")
-        });
-
-        callbacks.push_transfer_progress(|current, total, bytes| {
-            //print!("\rpushing... {}/{} objects ({} bytes)", current, total, bytes);
-            //io::stdout().flush().unwrap();
-            io::stdout().flush().unwrap();
-            true;
-        });
-
-        
-        let mut push_options = PushOptions::new();
-        push_options.remote_callbacks(callbacks);
-
-        let refspec = format!("refs/heads/{}:refs/heads/{}", branch_name, branch_name);
-        remote.push(&[&refspec], Some(&mut push_options))?;
-
-        //println!("\npush completed successfully!");
-        io::stdout().flush().unwrap(); 
-        Ok("push completed successfully!".to_string())
-    }
-
-    pub fn pull_from_remote(&mut self, remote_name: &str, branch_name: &str) -> Result<String> {
-        let mut remote = self.repo.find_remote(remote_name)?;
-
-        let mut callbacks = RemoteCallbacks::new();
-
-        callbacks.credentials(|url, username_from_url, _allowed_types| {
-            if url.starts_with("git@") || url.contains("ssh://") {
-                if let Ok(cred) = Cred::ssh_key_from_agent(username_from_url.unwrap_or("git")) {
-                    return Ok(cred);
-                }
-
-                if let Ok(cred) = Cred::ssh_key(
-                    username_from_url.unwrap_or("git"),
-                    None,
-                    std::path::Path::new(&format!("{}/.ssh/id_rsa", std::env::var("HOME").unwrap_or_default())),
-                    None,
-                ) {
-                    return Ok(cred);
-                }
-            }
-
-            if let Ok(cred) = Cred::credential_helper(&self.repo.config()?, url, username_from_url) {
-                return Ok(cred);
-            }
-            if let Ok(cred) = Cred::default() {
-                return Ok(cred);
-            }
-
-            Cred::userpass_plaintext(username_from_url.unwrap_or(""), "")
-        });
-
-        callbacks.transfer_progress(|stats| {
-            /*if stats.received_objects() == stats.total_objects(){
-                print!("resolving deltas {}/{} \r", stats.indexed_deltas(), stats.total_deltas());
-            } else if stats.total_objects() > 0 {
-                (100 * stats.received_objects() / stats.total_objects(),
-                stats.received_objects(),
-                stats.total_objects(),
-                stats.received_bytes());
-            }
-            io::stdout().flush().unwrap();
-            true*/
-            true
-        });
-
-        let mut fetch_options = git2::FetchOptions::new();
-        fetch_options.remote_callbacks(callbacks);
-
-        let refspec = format!("+refs/heads/{}:refs/remotes/{}/{}", branch_name, remote_name, branch_name);
-        remote.fetch(&[&refspec], Some(&mut fetch_options), None)?;
-
-        let remote_branch_name = format!("refs/remotes/{}/{}", remote_name, branch_name);
-        let remote_ref = self.repo.find_reference(&remote_branch_name)?;
-        let remote_oid = remote_ref.target().context("failed to get remote OID")?;
-        // let remote_commit = self.repo.find_commit(remote_oid)?;
-        let remote_annotated = self.repo.find_annotated_commit(remote_oid)?;
-        let annotated_commits = vec![&remote_annotated];
-        
-        
-        let analysis = self.repo.merge_analysis(&annotated_commits)?;
-        
-        if analysis.0.is_fast_forward() {
-            let head = self.repo.head()?;
-            if let Some(branch_ref_name) = head.name() {
-                let mut branch_ref = self.repo.find_reference(branch_ref_name)?;
-                branch_ref.set_target(remote_oid, "Fast-forward")?;
-            } else {
-                let mut head_ref = self.repo.find_reference("HEAD")?;
-                head_ref.set_target(remote_oid, "Fast-forward")?;
-            }
-            
-            // Checkout the updated files
-            self.repo.checkout_head(Some(
-                git2::build::CheckoutBuilder::default()
-                    .allow_conflicts(true)
-                    .conflict_style_merge(true)
-                    .force()
-            ))?;
-            //println!("\nfast-forward merge completed!");
-            return Ok("Fast-forward merge completed!".to_string());
-        } else if analysis.0.is_normal() {
-            // get on your big boy seats
-            let mut merge_opts = git2::MergeOptions::new();
-            merge_opts.file_favor(git2::FileFavor::Normal);
-
-            let mut checkout_opts = git2::build::CheckoutBuilder::new();
-            checkout_opts.allow_conflicts(true);
-            checkout_opts.conflict_style_merge(true);
-
-            self.repo.merge(&annotated_commits, Some(&mut merge_opts), Some(&mut checkout_opts))?;
-            let mut index = self.repo.index()?;
-
-            if index.has_conflicts() {
-                //println!("\nmerge conflicts detected! please resolve them.");
-                return Err(anyhow::anyhow!("merge conflicts require resolution!"));
-            } else {
-                let signature = self.repo.signature()?;
-                let tree_id = index.write_tree()?;
-                let tree = self.repo.find_tree(tree_id)?;
-
-                let head_commit = self.repo.head()?.peel_to_commit()?;
-                let merge_commit = self.repo.find_commit(remote_oid)?;
-                let parents = vec![&head_commit, &merge_commit];
-
-                self.repo.commit(
-                    Some("HEAD"),
-                    &signature,
-                    &signature,
-                    format!("merge branch {} of {}", branch_name, remote_name).as_str(),
-                    &tree,
-                    &parents,
-                )?;
-                return Ok(format!("Merge completed: {} from {}", branch_name, remote_name));
-            }
-        } else if analysis.0.is_up_to_date() {
-            return Ok("already up to date!".to_string());
-        } else {
-            return Err(anyhow::anyhow!("cannot merge - conflicting changes detected!"));
-        }
-        
-    }
-
-    pub fn get_remotes(&self) -> Result<Vec<String>>{
-        let remotes = self.repo.remotes()?;
-        Ok(remotes.iter()
-            .filter_map(|r| r.map(|s| s.to_string()))
-            .collect())
-    }
-
-    pub fn get_remote_url(&self, remote_name: &str) -> Result<Option<String>>{
-        let remote = self.repo.find_remote(remote_name)?;
-        Ok(remote.url().map(|s| s.to_string()))
-    }
-
-    pub fn get_current_branch(&self) -> Result<String>{
-        let head = self.repo.head()?;
-        if let Some(name) = head.shorthand(){
-            Ok(name.to_string())
-        } else {
-            Ok("HEAD".to_string())
-        }
-    }
-
-    pub fn detect_merge_conflicts(&self) -> Result<Option<MergeConflict>> {
-        let merge_head_path = self.repo.path().join("MERGE_HEAD");
-        if !merge_head_path.exists() {
-            return Ok(None);
-        }
-
-        let mut opts = StatusOptions::new();
-        opts.include_untracked(true);
-        let statuses = self.repo.statuses(Some(&mut opts))?;
-
-        let mut conflicted_files = Vec::new();
-
-        for entry in statuses.iter() {
-            let status = entry.status();
-            if status.is_conflicted() {
-                let path = entry.path().unwrap_or("").to_string();
-
-                if let Ok(conflicts) = self.parse_conflicted_file(&path) {
-                    if !conflicts.is_empty() {
-                        conflicted_files.push(ConflictedFile{
-                            path,
-                            conflicts
-                        });
-                    }
-                }
-            }
-        }
-
-        if conflicted_files.is_empty() {
-            return Ok(None);
-        }
-
-        let our_commit = self.repo.head()?.target()
-            .context("Failed to get HEAD")?
-            .to_string();
-
-        let their_commit = fs::read_to_string(merge_head_path)?
-            .trim()
-            .to_string();
-        
-        Ok(Some(MergeConflict { files: conflicted_files, our_commit, their_commit }))
-    }
-
-    fn parse_conflicted_file(&self, file_path: &str) -> Result<Vec<ConflictHunk>> {
-        let repo_workdir = self.repo.workdir()
-            .context("repository has no working directory.")?;
-        let full_path = repo_workdir.join(file_path);
-
-        let content = fs::read_to_string(&full_path)
-            .context("failed to read conflicted file.")?;
-
-        let mut conflicts = Vec::new();
-        let lines: Vec<&str> = content.lines().collect();
-        let mut i = 0;
-
-        while i < lines.len() {
-            if lines[i].starts_with("<<<<<<<") {
-                let start_line = i;
-                let mut our_content = String::new();
-                let mut their_content = String::new();
-                let mut base_content = None;
-
-                i += 1;
-
-                while i < lines.len() && !lines[i].starts_with("=======") && !lines[i].starts_with("|||||||") {
-                    if !our_content.is_empty() {
-                        our_content.push('\n');
-                    }
-                    our_content.push_str(lines[i]);
-                    i += 1;
-                }
-
-                if i < lines.len() && lines[i].starts_with("|||||||") {
-                    i += 1; // Skip the base marker
-                    let mut base = String::new();
-                    
-                    while i < lines.len() && !lines[i].starts_with("=======") {
-                        if !base.is_empty() {
-                            base.push('\n');
-                        }
-                        base.push_str(lines[i]);
-                        i += 1;
-                    }
-                    base_content = Some(base);
-                }
-
-                if i < lines.len() && lines[i].starts_with("======="){
-                    i += 1;
-                }
-
-                while i < lines.len() && !lines[i].starts_with(">>>>>>>") {
-                    if !their_content.is_empty() {
-                        their_content.push('\n');
-                    }
-                    their_content.push_str(lines[i]);
-                    i += 1;
-                }
-                
-                if i < lines.len() && lines[i].starts_with(">>>>>>>") {
-                    let end_line = i;
-                    i += 1;
-                    
-                    conflicts.push(ConflictHunk {
-                        start_line,
-                        end_line,
-                        our_content,
-                        their_content,
-                        base_content,
-                    });
-                }
-            } else {
-                i += 1;
-            }
-        }
-
-        Ok(conflicts)
-    }
-
-    pub fn resolve_conflicts(&mut self, conflict_resolutions: &std::collections::HashMap<(usize, usize), MergeResolution>, merge_conflict: &MergeConflict) -> Result<()> {
-        let repo_workdir = self.repo.workdir()
-            .context("repository has no working directory.")?;
-
-        for (file_idx, conflicted_file) in merge_conflict.files.iter().enumerate() {
-            let full_path = repo_workdir.join(&conflicted_file.path);
-            let original_content = fs::read_to_string(&full_path)?;
-            let lines: Vec<&str> = original_content.lines().collect();
-
-            let mut resolved_lines = Vec::new();
-            let mut i = 0;
-            let mut hunk_idx = 0;
-
-            while i < lines.len() {
-                if lines[i].starts_with("<<<<<<<"){
-                    if let Some(resolution) = conflict_resolutions.get(&(file_idx, hunk_idx)) {
-                        if hunk_idx < conflicted_file.conflicts.len(){
-                            let hunk = &conflicted_file.conflicts[hunk_idx];
-                            let resolved_content = hunk.resolve(resolution);
-
-                            for line in resolved_content.lines() {
-                                resolved_lines.push(line.to_string());
-                            }
-                        }
-                    } else {
-                        while i <= conflicted_file.conflicts[hunk_idx].end_line && i < lines.len() {
-                            resolved_lines.push(lines[i].to_string());
-                            i += 1;
-                        }
-                        i -= 1;
-                    }
-                    while i < lines.len() && !lines[i].starts_with(">>>>>>>") {
-                        i += 1;
-                    }
-                    hunk_idx += 1;
-                } else {
-                    resolved_lines.push(lines[i].to_string());
-                }
-                i += 1;
-            }
-
-            let resolved_content = resolved_lines.join("\n");
-            fs::write(&full_path, resolved_content)?;
-
-            self.stage_file(&conflicted_file.path)?;
-        }
-        Ok(())
-    }
-
-    pub fn complete_merge(&mut self, message: &str) -> Result<()> {
-        let signature = self.repo.signature()?;
-        let mut index = self.repo.index()?;
-        let tree_id = index.write_tree()?;
-        let tree = self.repo.find_tree(tree_id)?;
-
-        let head_commit = self.repo.head()?.peel_to_commit()?;
-        
-        let merge_head_path = self.repo.path().join("MERGE_HEAD");
-        let merge_head_oid = git2::Oid::from_str(&std::fs::read_to_string(merge_head_path)?.trim())?;
-        let merge_commit = self.repo.find_commit(merge_head_oid)?;
-
-        let parents = vec![&head_commit, &merge_commit];
-
-        self.repo.commit(
-            Some("HEAD"),
-            &signature,
-            &signature,
-            message,
-            &tree,
-            &parents,
-        )?;
-
-        let git_dir = self.repo.path();
-        let _ = std::fs::remove_file(git_dir.join("MERGE_HEAD"));
-        let _ = std::fs::remove_file(git_dir.join("MERGE_MSG"));
-        let _ = std::fs::remove_file(git_dir.join("MERGE_MODE"));
-
-        Ok(())
-
-    }
-
-    pub fn abort_merge(&mut self) -> Result<()> {
-        let head_commit = self.repo.head()?.peel_to_commit()?;
-        self.repo.reset(head_commit.as_object(), git2::ResetType::Hard, None)?;
-
-        let git_dir = self.repo.path();
-        let _ = std::fs::remove_file(git_dir.join("MERGE_HEAD"));
-        let _ = std::fs::remove_file(git_dir.join("MERGE_MSG"));
-        let _ = std::fs::remove_file(git_dir.join("MERGE_MODE"));
-        Ok(())
-    }
-
-    pub fn init_repo<P: AsRef<Path>>(path: P) -> Result<Self> {
-        let repo = Git2Repository::init(path.as_ref())
-            .with_context(|| format!("failed to init git repo at {}", path.as_ref().display()))?;
-
-        Ok(Self {repo})
-    }
-
-    pub fn add_remote(&mut self, name: &str, url: &str) -> Result<()> {
-        match self.repo.find_remote(name) {
-            Ok(_) => {
-                self.repo
-                    .remote_set_url(name, url)
-                    .with_context(|| format!("failed to set remote URL for {}", name))?;
-            }
-
-            Err(_) => {
-                self.repo
-                    .remote(name, url)
-                    .with_context(|| format!("failed to add remote {}", name))?;
-            }
-        }
-        Ok(())
-    }
-
-    pub fn has_commits(&self) -> bool {
-        self.repo.head().is_ok()
-    }
-
-    pub fn initial_commit_all(&mut self, message: &str) -> Result<()> {
-        use git2::{IndexAddOption, Signature};
-
-        let sig = self
-            .repo   
-            .signature()
-            .or_else(|_| Signature::now("You", "you@example.com"))
-            .context("failed to create git signature")?;
-        
-        let mut index = self.repo.index()?;
-        index.add_all(["*"].iter(), IndexAddOption::DEFAULT, None)?;
-        let oid = index.write_tree()?;
-        let tree = self.repo.find_tree(oid)?;
-
-        // parents if HEAD exists if not start an initial commit
-        let parent_commit = if let Ok(head_ref) = self.repo.head() {
-            head_ref.peel_to_commit().ok()
-        } else {
-            None
-        };
-
-        let parents: Vec<&git2::Commit> = match &parent_commit {
-            Some(commit) => vec![commit],
-            None => vec![]
-        };
-
-        let head_name = if self.repo.head().is_ok() {
-            "HEAD"
-        } else {
-            "refs/heads/main"
-        };
-        let _ = self
-            .repo
-            .commit(Some(head_name), &sig, &sig, message, &tree, &parents[..]);
-
-        if head_name != "HEAD" {
-            self.repo.set_head("refs/heads/main")?;
-        }
-
-        Ok(())
-    }
-
-    pub fn try_create_remote_with_gh(
-        &mut self,
-        remote: &str,
-        repo_name: Option<&str>,
-        private: bool,
-    ) -> Result<()> {
-        use std::process::Command;
-        let name = repo_name.unwrap_or("repo");
-        let visibility = if private { "--private" } else { "--public" };
-        // gh repo create <name> --source . --remote <remote> --push --confirm --private/--public
-        let status = Command::new("gh")
-            .args([
-                "repo", "create", name, "--source", ".", "--remote", remote, "--push", "--confirm",
-                visibility,
-            ])
-            .status();
-        match status {
-            Ok(s) if s.success() => Ok(()),
-            Ok(s) => anyhow::bail!("'gh repo create' failed with status {}", s),
-            Err(e) => anyhow::bail!("gh CLI not found or failed to run: {}", e),
-        }
-    }
-}
\ No newline at end of file
+use anyhow::{Context, Result};
+use chrono::{DateTime, FixedOffset};
+use git2::{Repository as Git2Repository, DiffOptions, StatusOptions, PushOptions, RemoteCallbacks, Cred};
+use std::io::{self, Write};
+use std::fmt;
+use crate::backend::{self, Operation};
+use crate::issues::IssueRule;
+use std::fs;
+use std::path::Path;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FlowKind {
+    Feature,
+    Release,
+    Hotfix,
+}
+
+impl FlowKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            FlowKind::Feature => "feature",
+            FlowKind::Release => "release",
+            FlowKind::Hotfix => "hotfix",
+        }
+    }
+}
+
+// same keys git-flow itself writes with `git flow init`, so both tools agree
+#[derive(Debug, Clone)]
+pub struct FlowConfig {
+    pub main: String,
+    pub develop: String,
+    pub feature_prefix: String,
+    pub release_prefix: String,
+    pub hotfix_prefix: String,
+    pub tag_prefix: String,
+}
+
+impl FlowConfig {
+    pub fn prefix(&self, kind: FlowKind) -> &str {
+        match kind {
+            FlowKind::Feature => &self.feature_prefix,
+            FlowKind::Release => &self.release_prefix,
+            FlowKind::Hotfix => &self.hotfix_prefix,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RestoreTarget {
+    Worktree,
+    Index,
+    Both,
+}
+
+impl RestoreTarget {
+    pub fn label(&self) -> &'static str {
+        match self {
+            RestoreTarget::Worktree => "working tree",
+            RestoreTarget::Index => "index",
+            RestoreTarget::Both => "index and working tree",
+        }
+    }
+
+    fn flags(&self) -> &'static [&'static str] {
+        match self {
+            RestoreTarget::Worktree => &["--worktree"],
+            RestoreTarget::Index => &["--staged"],
+            RestoreTarget::Both => &["--staged", "--worktree"],
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct StashEntry {
+    pub index: usize,
+    pub message: String,
+    // the branch it was stashed on, when the message says so
+    pub branch: Option<String>,
+    pub oid: git2::Oid,
+    pub time: DateTime<FixedOffset>,
+}
+
+impl fmt::Display for StashEntry {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "stash@{{{}}}: {}", self.index, self.message)
+    }
+}
+
+// a branch as the remote has it right now, next to what we last fetched of it
+#[derive(Debug, Clone)]
+pub struct ListedBranch {
+    pub name: String,
+    pub oid: git2::Oid,
+    pub fetched: Option<git2::Oid>,
+}
+
+impl ListedBranch {
+    pub fn state(&self) -> &'static str {
+        match self.fetched {
+            None => "not fetched",
+            Some(oid) if oid == self.oid => "fetched",
+            Some(_) => "changed since fetch",
+        }
+    }
+}
+
+// a file in the hooks dir. disabled hooks are the ones still called <name>.sample
+#[derive(Debug, Clone)]
+pub struct Hook {
+    pub name: String,
+    pub path: std::path::PathBuf,
+    pub enabled: bool,
+    pub executable: bool,
+}
+
+impl Hook {
+    pub fn state(&self) -> &'static str {
+        match (self.enabled, self.executable) {
+            (true, true) => "active",
+            (true, false) => "not executable",
+            (false, _) => "disabled",
+        }
+    }
+}
+
+// one push refspec with the shorthand expanded, src None deletes dst on the remote
+#[derive(Debug, Clone, PartialEq)]
+pub struct PushRefspec {
+    pub force: bool,
+    pub src: Option<String>,
+    pub dst: String,
+}
+
+impl PushRefspec {
+    pub fn spec(&self) -> String {
+        format!("{}{}:{}", if self.force { "+" } else { "" }, self.src.as_deref().unwrap_or(""), self.dst)
+    }
+
+    pub fn describe(&self, remote: &str) -> String {
+        let short = |name: &str| name.strip_prefix("refs/heads/").unwrap_or(name).to_string();
+        match &self.src {
+            None => format!("delete {} on {}", short(&self.dst), remote),
+            Some(src) => format!(
+                "{}{} -> {} on {}",
+                if self.force { "force " } else { "" },
+                short(src),
+                short(&self.dst),
+                remote
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SyncForkOptions {
+    // replay our own commits on the default branch on top of upstream, otherwise fast-forward only
+    pub rebase: bool,
+    // push the updated branch to origin afterwards, the app opens the push preview for it
+    pub push: bool,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StashOptions {
+    pub include_untracked: bool,
+    pub include_ignored: bool,
+    pub keep_index: bool,
+}
+
+impl StashOptions {
+    fn flags(&self) -> git2::StashFlags {
+        let mut flags = git2::StashFlags::DEFAULT;
+        if self.include_untracked {
+            flags |= git2::StashFlags::INCLUDE_UNTRACKED;
+        }
+        if self.include_ignored {
+            flags |= git2::StashFlags::INCLUDE_IGNORED;
+        }
+        if self.keep_index {
+            flags |= git2::StashFlags::KEEP_INDEX;
+        }
+        flags
+    }
+}
+
+// lines added / removed per path for the status list: staged is HEAD to the index, unstaged the
+// index to the worktree, where an untracked file is all added lines
+#[derive(Debug, Clone, Default)]
+pub struct LineCounts {
+    pub staged: std::collections::HashMap<String, (usize, usize)>,
+    pub unstaged: std::collections::HashMap<String, (usize, usize)>,
+}
+
+#[derive(Debug, Clone)]
+pub struct StashFileStat {
+    pub path: String,
+    // A/M/D/R, ? for untracked files stashed with -u
+    pub status: char,
+    pub additions: usize,
+    pub deletions: usize,
+}
+
+// what popping a stash would run into, worked out without touching anything
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct StashPreview {
+    // stashed files with local changes right now (or untracked ones already back in the
+    // worktree), the pop refuses to go over those
+    pub in_the_way: Vec<String>,
+    // changed by the stash and by the commits since it was made, a pop leaves conflict markers
+    pub conflicts: Vec<String>,
+}
+
+// what `p` would send, from the remote-tracking ref as of the last fetch
+#[derive(Debug, Clone)]
+pub struct PushPreview {
+    pub remote: String,
+    pub branch: String,
+    // None when the branch isn't on the remote yet
+    pub remote_tip: Option<String>,
+    // newest first, only the first PUSH_PREVIEW_LIMIT of `count`
+    pub commits: Vec<Commit>,
+    pub count: usize,
+    pub files: Vec<StashFileStat>,
+    // commits the remote has that the branch doesn't, the push gets rejected when there are any
+    pub behind: usize,
+}
+
+pub const PUSH_PREVIEW_LIMIT: usize = 500;
+
+// something `git clean` would take: untracked, or ignored (build output and the like)
+#[derive(Debug, Clone, PartialEq)]
+pub struct CleanEntry {
+    // relative to the worktree, directories end in /
+    pub path: String,
+    pub ignored: bool,
+    pub size: u64,
+}
+
+// one entry of a directory in the files view
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreeEntry {
+    pub name: String,
+    // from the top of the tree, directories end in /
+    pub path: String,
+    pub kind: TreeEntryKind,
+    // None for directories and submodules, and blobs a partial clone doesn't have
+    pub size: Option<usize>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TreeEntryKind {
+    Dir,
+    File,
+    Executable,
+    Link,
+    Submodule,
+}
+
+// a blob from the large objects tool, with where it first showed up if any commit has it
+#[derive(Debug, Clone)]
+pub struct LargeObject {
+    pub oid: git2::Oid,
+    pub size: usize,
+    pub path: Option<String>,
+    pub commit: Option<Commit>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ObjectTotals {
+    pub objects: usize,
+    pub blobs: usize,
+    // uncompressed, what the blobs add up to before packing
+    pub blob_bytes: u64,
+    // .git/objects as it is on disk, loose and packed
+    pub disk_bytes: u64,
+}
+
+// HEAD and the index as hydra last saw them, to notice another tool committing, switching
+// branches or staging behind its back
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RepoSnapshot {
+    // what HEAD points at (a branch, or HEAD itself when detached) and the commit it resolves to
+    head_ref: Option<String>,
+    head: Option<git2::Oid>,
+    index_modified: Option<std::time::SystemTime>,
+    // of the staged paths, ids and modes. git status rewriting the index's stat info changes its
+    // mtime but not this
+    index_entries: u64,
+}
+
+impl RepoSnapshot {
+    pub fn head(&self) -> Option<git2::Oid> {
+        self.head
+    }
+
+    // what happened between `self` and `now`, for telling the user
+    pub fn change(&self, now: &RepoSnapshot, repo: &Repository) -> Option<String> {
+        let short = |oid: Option<git2::Oid>| oid.map(|o| repo.short_id(o)).unwrap_or_else(|| "nothing".to_string());
+        let name = |r: &Option<String>| match r.as_deref() {
+            Some("HEAD") => "a detached HEAD".to_string(),
+            Some(r) => r.strip_prefix("refs/heads/").unwrap_or(r).to_string(),
+            None => String::new(),
+        };
+        if self.head_ref != now.head_ref {
+            Some(format!("HEAD switched from {} to {}", name(&self.head_ref), name(&now.head_ref)))
+        } else if self.head != now.head {
+            Some(format!("{} moved from {} to {}", name(&now.head_ref), short(self.head), short(now.head)))
+        } else if self.index_entries != now.index_entries {
+            Some("the index (what's staged) changed".to_string())
+        } else {
+            None
+        }
+    }
+}
+
+// something the health check found
+#[derive(Debug, Clone)]
+pub enum HealthIssue {
+    // a .lock file in the git dir, left behind by a git that crashed or is still running
+    Lock { path: std::path::PathBuf, modified: Option<std::time::SystemTime> },
+    // a ref that can't be read or points at an object that isn't there
+    BrokenRef { name: String, problem: String },
+    // a commit nothing points at any more, not even a reflog
+    Dangling(Commit),
+    // anything else git fsck complained about, as it printed it
+    Fsck(String),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct HealthReport {
+    pub issues: Vec<HealthIssue>,
+    // dangling blobs and trees aren't worth listing, gc takes care of them
+    pub dangling_objects: usize,
+}
+
+// the full-screen commit view: files with their +/- and the patch against the first parent
+// (or between two commits), cut off after COMMIT_DIFF_LIMIT lines
+#[derive(Debug, Clone, Default)]
+pub struct CommitDiff {
+    pub files: Vec<StashFileStat>,
+    pub lines: Vec<String>,
+    // where each file's "diff --git" header is in `lines`, for jumping file to file
+    pub file_starts: Vec<usize>,
+    pub truncated: bool,
+    // files of a partial clone whose content isn't here and couldn't be fetched
+    pub missing: Vec<String>,
+}
+
+const COMMIT_DIFF_LIMIT: usize = 20_000;
+
+#[derive(Debug, Clone)]
+pub struct Commit {
+    pub id: String,
+    pub message: String,
+    pub author: String,
+    pub author_email: String,
+    // in the committer's own timezone, the way `git log` shows it
+    pub timestamp: DateTime<FixedOffset>,
+    pub parents: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FileStatus {
+    pub path: String,
+    pub status: String,
+    // (old, new) when the mode changed too, 0o100644 -> 0o100755 or a file turning into a symlink
+    pub mode_change: Option<(u32, u32)>,
+}
+
+impl FileStatus {
+    // "100644 → 100755", for the status list
+    pub fn mode_label(&self) -> Option<String> {
+        self.mode_change.map(|(old, new)| format!("{:o} → {:o}", old, new))
+    }
+}
+
+// both sides known (not an add or delete) and different
+fn mode_change(delta: Option<git2::DiffDelta>) -> Option<(u32, u32)> {
+    let delta = delta?;
+    let (old, new) = (u32::from(delta.old_file().mode()), u32::from(delta.new_file().mode()));
+    (old != 0 && new != 0 && old != new).then_some((old, new))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum RefKind {
+    Head,
+    LocalBranch,
+    RemoteBranch,
+    Tag,
+}
+
+#[derive(Debug, Clone)]
+pub struct RefLabel {
+    pub name: String,
+    pub kind: RefKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NestedKind {
+    Submodule,
+    // a separate repo sitting untracked inside the working tree
+    Nested,
+}
+
+#[derive(Debug, Clone)]
+pub struct NestedRepo {
+    pub path: String,
+    pub kind: NestedKind,
+    // None when it can't be opened, e.g. a submodule that was never checked out
+    pub changes: Option<usize>,
+}
+
+#[derive(Debug)]
+pub struct RepoStatus {
+    pub branch: String,
+    // what ahead / behind are counted against, e.g. origin/main
+    pub upstream: Option<String>,
+    pub ahead: usize,
+    pub behind: usize,
+    pub staged: Vec<FileStatus>,
+    pub unstaged: Vec<FileStatus>,
+    pub untracked: Vec<FileStatus>,
+    pub nested: Vec<NestedRepo>,
+    // skip-worktree / assume-unchanged files, listed so they aren't forgotten
+    pub flagged: Vec<FlaggedFile>,
+}
+
+impl RepoStatus {
+    // rows in the status list: files, then nested repos, then flagged files
+    pub fn entry_count(&self) -> usize {
+        self.staged.len() + self.unstaged.len() + self.untracked.len() + self.nested.len() + self.flagged.len()
+    }
+}
+
+// a path with conflict stages in the index, and which sides (base, ours, theirs) have it
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexConflict {
+    pub path: String,
+    pub base: bool,
+    pub ours: bool,
+    pub theirs: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Upstream {
+    // origin/main, or a local branch name when tracking one
+    pub name: String,
+    pub ahead: usize,
+    pub behind: usize,
+    // configured, but the branch it points at doesn't exist (anymore)
+    pub gone: bool,
+}
+
+impl Upstream {
+    pub fn summary(&self) -> String {
+        match (self.gone, self.ahead, self.behind) {
+            (true, _, _) => "gone".to_string(),
+            (false, 0, 0) => "up to date".to_string(),
+            (false, ahead, 0) => format!("↑{}", ahead),
+            (false, 0, behind) => format!("↓{}", behind),
+            (false, ahead, behind) => format!("↑{} ↓{}", ahead, behind),
+        }
+    }
+}
+
+// a local branch in a stack: made on top of another local branch (its parent) instead of main.
+// the parent comes from branch.<name>.hydraParent when hydra created or restacked it, otherwise
+// it's the nearest local branch it contains
+#[derive(Debug, Clone, PartialEq)]
+pub struct StackedBranch {
+    pub name: String,
+    pub parent: Option<String>,
+    // commits of its own on top of the parent
+    pub ahead: usize,
+    // the parent moved (amended, rebased, got new commits) and this branch doesn't have that yet
+    pub needs_restack: bool,
+    // no hydraParent, the parent is a guess from history
+    pub guessed: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum IndexFlag {
+    SkipWorktree,
+    AssumeUnchanged,
+}
+
+impl IndexFlag {
+    pub fn label(&self) -> &'static str {
+        match self {
+            IndexFlag::SkipWorktree => "skip-worktree",
+            IndexFlag::AssumeUnchanged => "assume-unchanged",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FlaggedFile {
+    pub path: String,
+    pub skip_worktree: bool,
+    pub assume_unchanged: bool,
+}
+
+impl FlaggedFile {
+    pub fn has(&self, flag: IndexFlag) -> bool {
+        match flag {
+            IndexFlag::SkipWorktree => self.skip_worktree,
+            IndexFlag::AssumeUnchanged => self.assume_unchanged,
+        }
+    }
+
+    pub fn labels(&self) -> String {
+        [(self.skip_worktree, IndexFlag::SkipWorktree), (self.assume_unchanged, IndexFlag::AssumeUnchanged)]
+            .iter()
+            .filter(|(on, _)| *on)
+            .map(|(_, flag)| flag.label())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+#[derive(Debug, Clone)]
+#[allow(dead_code)]
+pub struct ConflictHunk {
+    pub start_line: usize,
+    pub end_line: usize,
+    pub our_content: String,
+    pub their_content: String,
+    pub base_content: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConflictKind {
+    Text,
+    Binary,
+    DeletedByUs,
+    DeletedByThem,
+    // a text conflict whose markers the parser can't make sense of (diff3 inside diff3, a
+    // non-utf8 file), it can only take one side's file from the index
+    Unparsed,
+}
+
+#[derive(Debug, Clone)]
+pub struct ConflictedFile {
+    pub path: String,
+    pub kind: ConflictKind,
+    pub conflicts: Vec<ConflictHunk>,
+    pub our_id: Option<git2::Oid>,
+    pub their_id: Option<git2::Oid>,
+}
+
+// the in-progress operation that left the conflicts behind
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConflictOperation {
+    Merge,
+    CherryPick,
+    Revert,
+    Rebase,
+    Stash,
+}
+
+#[derive(Debug, Clone)] 
+pub struct MergeConflict {
+    pub operation: ConflictOperation,
+    pub files: Vec<ConflictedFile>,
+    pub our_commit: String,
+    pub their_commit: String,
+}
+
+#[derive(Debug, Clone)]
+pub enum MergeResolution {
+    KeepOurs,
+    KeepTheirs,
+    KeepBoth,
+    Delete,
+    // the whole file exactly as the stage 2 / 3 index entry has it, like checkout --ours/--theirs.
+    // doesn't depend on the markers, so it still works when they can't be parsed
+    OursFile,
+    TheirsFile,
+    #[allow(dead_code)]
+    Custom(String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum StashPopResult {
+    Popped,
+    // applied with conflicts, the stash is kept until they're resolved
+    Conflicted(git2::Oid),
+}
+
+#[derive(Debug, Clone)]
+pub struct DiffLine {
+    pub origin: char,
+    // raw bytes incl. the trailing newline, patches are rebuilt from these
+    pub content: Vec<u8>,
+}
+
+#[derive(Debug, Clone)]
+pub struct DiffHunk {
+    pub header: String,
+    pub old_start: u32,
+    pub new_start: u32,
+    pub lines: Vec<DiffLine>,
+}
+
+// a slice of a file's diff, see get_file_diff_window
+#[derive(Debug, Clone)]
+pub struct DiffWindow {
+    pub start: usize,
+    pub lines: Vec<String>,
+    // None when the diff goes on past the window
+    pub total: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct FileDiff {
+    pub path: String,
+    // not in the index yet (untracked), so a forward patch has to create it
+    pub new_file: bool,
+    pub mode: u32,
+    pub hunks: Vec<DiffHunk>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SignatureStatus {
+    Unsigned,
+    Verified,
+    // good signature, but the key isn't trusted
+    Untrusted,
+    // couldn't check it: missing key, expired, revoked...
+    Unverified,
+    Bad,
+}
+
+#[derive(Debug, Clone)]
+pub struct CommitSignature {
+    pub status: SignatureStatus,
+    pub signer: Option<String>,
+    pub detail: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)] 
+pub enum ResetMode {
+    Soft,
+    Mixed,
+    Hard,
+}
+
+impl ConflictHunk {
+    pub fn resolve(&self, resolution: &MergeResolution) -> String {
+        match resolution {
+            MergeResolution::KeepOurs | MergeResolution::OursFile => self.our_content.clone(),
+            MergeResolution::KeepTheirs | MergeResolution::TheirsFile => self.their_content.clone(),
+            MergeResolution::KeepBoth => format!("{}\n{}", self.our_content, self.their_content),
+            MergeResolution::Delete => String::new(),
+            MergeResolution::Custom(content) => content.clone(),
+        }
+    }
+}
+
+impl ConflictOperation {
+    fn from_state(state: git2::RepositoryState) -> Option<Self> {
+        use git2::RepositoryState as S;
+        match state {
+            S::Merge => Some(ConflictOperation::Merge),
+            S::CherryPick | S::CherryPickSequence => Some(ConflictOperation::CherryPick),
+            S::Revert | S::RevertSequence => Some(ConflictOperation::Revert),
+            S::Rebase | S::RebaseInteractive | S::RebaseMerge => Some(ConflictOperation::Rebase),
+            _ => None,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ConflictOperation::Merge => "merge",
+            ConflictOperation::CherryPick => "cherry-pick",
+            ConflictOperation::Revert => "revert",
+            ConflictOperation::Rebase => "rebase",
+            ConflictOperation::Stash => "stash",
+        }
+    }
+
+    // during a rebase "ours" is the branch being rebased onto, not the user's own work
+    pub fn ours_label(&self) -> &'static str {
+        match self {
+            ConflictOperation::Rebase => "onto (upstream)",
+            ConflictOperation::Stash => "working tree",
+            _ => "current branch",
+        }
+    }
+
+    pub fn theirs_label(&self) -> &'static str {
+        match self {
+            ConflictOperation::Merge => "incoming branch",
+            ConflictOperation::CherryPick => "picked commit",
+            ConflictOperation::Revert => "reverted commit",
+            ConflictOperation::Rebase => "commit being replayed",
+            ConflictOperation::Stash => "stashed changes",
+        }
+    }
+
+    fn head_files(&self) -> &'static [&'static str] {
+        match self {
+            ConflictOperation::Merge => &["MERGE_HEAD"],
+            ConflictOperation::CherryPick => &["CHERRY_PICK_HEAD"],
+            ConflictOperation::Revert => &["REVERT_HEAD"],
+            ConflictOperation::Rebase => &["REBASE_HEAD", "rebase-merge/stopped-sha", "rebase-apply/original-commit"],
+            ConflictOperation::Stash => &["refs/stash"],
+        }
+    }
+}
+
+impl ConflictKind {
+    pub fn label(&self) -> &'static str {
+        match self {
+            ConflictKind::Text => "text",
+            ConflictKind::Binary => "binary",
+            ConflictKind::DeletedByUs => "deleted by us",
+            ConflictKind::DeletedByThem => "deleted by them",
+            ConflictKind::Unparsed => "markers unreadable",
+        }
+    }
+}
+
+impl MergeResolution {
+    pub fn is_whole_file(&self) -> bool {
+        matches!(self, MergeResolution::OursFile | MergeResolution::TheirsFile)
+    }
+}
+
+impl NestedRepo {
+    pub fn summary(&self) -> String {
+        let kind = match self.kind {
+            NestedKind::Submodule => "submodule",
+            NestedKind::Nested => "nested repo",
+        };
+        match self.changes {
+            None => format!("{}: not checked out", kind),
+            Some(0) => format!("{}: checked out commit differs", kind),
+            Some(1) => format!("{}: 1 change", kind),
+            Some(n) => format!("{}: {} changes", kind, n),
+        }
+    }
+}
+
+impl DiffLine {
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.content).trim_end_matches(['\n', '\r']).to_string()
+    }
+
+    pub fn is_change(&self) -> bool {
+        self.origin == '+' || self.origin == '-'
+    }
+}
+
+impl SignatureStatus {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SignatureStatus::Unsigned => "unsigned",
+            SignatureStatus::Verified => "verified",
+            SignatureStatus::Untrusted => "untrusted",
+            SignatureStatus::Unverified => "unverified",
+            SignatureStatus::Bad => "bad signature",
+        }
+    }
+}
+
+impl fmt::Display for ResetMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ResetMode::Soft => write!(f, "soft"),
+            ResetMode::Mixed => write!(f, "mixed"),
+            ResetMode::Hard => write!(f, "hard"),
+        }
+    }
+}
+
+impl fmt::Display for RepoStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "branch: {}", self.branch)?;
+        if let Some(upstream) = &self.upstream {
+            writeln!(f, "tracking: {}", upstream)?;
+        }
+        if self.ahead > 0 {
+            writeln!(f, "ahead by {} commits.", self.ahead)?;
+        }
+        if self.behind > 0 {
+            writeln!(f, "behind by {} commits.", self.behind)?;
+        }
+
+        if !self.staged.is_empty(){
+            writeln!(f, "\nstaged changes:")?;
+            for file in &self.staged {
+                writeln!(f, " {} {}", file.status, file.path)?;
+            }
+        }
+
+        if !self.unstaged.is_empty(){
+            writeln!(f, "\nunstaged changes:")?;
+            for file in &self.unstaged {
+                writeln!(f, " {} {}", file.status, file.path)?;
+            }
+        }
+
+        if !self.untracked.is_empty() {
+            writeln!(f, "\nuntracked files:")?;
+            for file in &self.untracked {
+                writeln!(f, " {} {}", file.status, file.path)?;
+            }
+        }
+
+        if !self.nested.is_empty() {
+            writeln!(f, "\nnested repositories:")?;
+            for repo in &self.nested {
+                writeln!(f, " {} ({})", repo.path, repo.summary())?;
+            }
+        }
+
+        if !self.flagged.is_empty() {
+            writeln!(f, "\nhidden from status ({}):", self.flagged.len())?;
+            for file in &self.flagged {
+                writeln!(f, "  {} ({})", file.path, file.labels())?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn to_commit(commit: &git2::Commit, mailmap: Option<&git2::Mailmap>) -> Commit {
+    let message = commit.message().unwrap_or("").to_string();
+    let author = mailmap.and_then(|m| commit.author_with_mailmap(m).ok()).unwrap_or_else(|| commit.author());
+    let author_name = author.name().unwrap_or("unknown").to_string();
+    let author_email = author.email().unwrap_or("").to_string();
+
+    let timestamp = commit_time(commit);
+
+    let parents = commit.parents()
+        .map(|p| p.id().to_string())
+        .collect();
+
+    Commit {
+        id: commit.id().to_string(),
+        message,
+        author: author_name,
+        author_email,
+        timestamp,
+        parents,
+    }
+}
+
+// a line in .mailmap's format: "Proper Name <commit@email>", "<proper@email> <commit@email>",
+// "Proper Name <proper@email> <commit@email>" or "Proper Name <proper@email> Commit Name <commit@email>"
+fn add_mailmap_line(mailmap: &mut git2::Mailmap, line: &str) -> Result<(), String> {
+    let mut pairs = Vec::new();
+    let mut rest = line.trim();
+    while let Some(open) = rest.find('<') {
+        let close = rest[open..].find('>').ok_or_else(|| format!("hydra.mailmap '{}': unclosed <", line))? + open;
+        let name = rest[..open].trim();
+        pairs.push(((!name.is_empty()).then_some(name), &rest[open + 1..close]));
+        rest = &rest[close + 1..];
+    }
+    let added = match pairs.as_slice() {
+        [(Some(name), email)] => mailmap.add_entry(Some(name), None, None, email),
+        [(real_name, real_email), (replace_name, replace_email)] => mailmap.add_entry(*real_name, Some(real_email), *replace_name, replace_email),
+        _ => return Err(format!("hydra.mailmap '{}' should look like a .mailmap line, e.g. Proper Name <proper@email> <old@email>", line)),
+    };
+    added.map_err(|e| format!("hydra.mailmap '{}': {}", line, e.message()))
+}
+
+pub fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+// whether any git process is running on this machine, in any repository (it can't be told which
+// one it's working on), or None when there's no way to tell. only used to decide whether a lock
+// file is stale, so any git counts
+pub fn git_running() -> Option<bool> {
+    if cfg!(windows) {
+        let output = std::process::Command::new("tasklist").args(["/FO", "CSV", "/NH"]).output().ok()?;
+        return Some(String::from_utf8_lossy(&output.stdout).lines().any(|l| l.to_lowercase().starts_with("\"git.exe\"")));
+    }
+    if let Ok(entries) = fs::read_dir("/proc") {
+        return Some(entries.filter_map(|e| e.ok()).any(|e| fs::read_to_string(e.path().join("comm")).is_ok_and(|comm| comm.trim() == "git")));
+    }
+    let output = std::process::Command::new("ps").args(["-A", "-o", "comm="]).output().ok()?;
+    Some(String::from_utf8_lossy(&output.stdout).lines().any(|l| Path::new(l.trim()).file_name().is_some_and(|n| n == "git")))
+}
+
+// every *.lock under `dir`, leaving out the object database
+// where ~/.ssh lives, %USERPROFILE% on windows where HOME usually isn't set (or is msys's)
+pub fn home_dir() -> Option<std::path::PathBuf> {
+    let var = |name| std::env::var_os(name).filter(|v| !v.is_empty()).map(std::path::PathBuf::from);
+    if cfg!(windows) {
+        var("USERPROFILE").or_else(|| var("HOME"))
+    } else {
+        var("HOME")
+    }
+}
+
+// pathspecs and index paths are always /-separated, windows users type and paste \ though. on
+// unix a backslash is just part of the name
+fn pathspec(path: &str) -> std::borrow::Cow<'_, str> {
+    if cfg!(windows) && path.contains('\\') {
+        std::borrow::Cow::Owned(path.replace('\\', "/"))
+    } else {
+        std::borrow::Cow::Borrowed(path)
+    }
+}
+
+fn find_locks(dir: &Path, locks: &mut Vec<HealthIssue>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.filter_map(|e| e.ok()) {
+        let path = entry.path();
+        match entry.file_type() {
+            Ok(t) if t.is_dir() && entry.file_name() != "objects" => find_locks(&path, locks),
+            Ok(t) if t.is_file() && path.extension().is_some_and(|e| e == "lock") => {
+                let modified = entry.metadata().and_then(|m| m.modified()).ok();
+                locks.push(HealthIssue::Lock { path, modified });
+            }
+            _ => {}
+        }
+    }
+}
+
+fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .filter_map(|e| e.ok())
+        .map(|e| match e.file_type() {
+            Ok(t) if t.is_dir() => dir_size(&e.path()),
+            _ => e.metadata().map(|m| m.len()).unwrap_or(0),
+        })
+        .sum()
+}
+
+fn commit_time(commit: &git2::Commit) -> DateTime<FixedOffset> {
+    let time = commit.time();
+    let offset = FixedOffset::east_opt(time.offset_minutes() * 60).unwrap_or(FixedOffset::east_opt(0).unwrap());
+    DateTime::from_timestamp(time.seconds(), 0).unwrap_or_default().with_timezone(&offset)
+}
+
+// non-overlapping matches, gives up once `limit` is reached
+fn count_occurrences(haystack: &[u8], needle: &[u8], limit: usize) -> usize {
+    if needle.is_empty() || haystack.len() < needle.len() {
+        return 0;
+    }
+
+    let first = needle[0];
+    let last_start = haystack.len() - needle.len();
+    let mut count = 0;
+    let mut i = 0;
+
+    while i <= last_start && count < limit {
+        match haystack[i..=last_start].iter().position(|b| *b == first) {
+            Some(offset) => {
+                i += offset;
+                if &haystack[i..i + needle.len()] == needle {
+                    count += 1;
+                    i += needle.len();
+                } else {
+                    i += 1;
+                }
+            }
+            None => break,
+        }
+    }
+    count
+}
+
+// builds a patch against the index out of the selected lines. unselected additions are dropped and
+// unselected removals become context. `reverse` flips a staged diff so it takes lines out of the index
+fn build_line_patch(file: &FileDiff, selected: &std::collections::HashSet<(usize, usize)>, reverse: bool) -> Option<Vec<u8>> {
+    let mut body = Vec::new();
+    let mut offset: i64 = 0;
+
+    for (hunk_idx, hunk) in file.hunks.iter().enumerate() {
+        let old_start = if reverse { hunk.new_start } else { hunk.old_start } as i64;
+        let mut lines: Vec<(u8, &[u8])> = Vec::new();
+        let mut old_count: i64 = 0;
+        let mut new_count: i64 = 0;
+        let mut changed = false;
+
+        for (line_idx, line) in hunk.lines.iter().enumerate() {
+            let origin = match (line.origin, reverse) {
+                ('+', true) => '-',
+                ('-', true) => '+',
+                (origin, _) => origin,
+            };
+            let picked = selected.contains(&(hunk_idx, line_idx));
+
+            match origin {
+                '+' if picked => {
+                    lines.push((b'+', &line.content));
+                    new_count += 1;
+                    changed = true;
+                }
+                '-' if picked => {
+                    lines.push((b'-', &line.content));
+                    old_count += 1;
+                    changed = true;
+                }
+                '+' => {}
+                _ => {
+                    lines.push((b' ', &line.content));
+                    old_count += 1;
+                    new_count += 1;
+                }
+            }
+        }
+
+        if !changed {
+            continue;
+        }
+
+        let mut new_start = old_start + offset;
+        if old_count == 0 {
+            new_start += 1;
+        } else if new_count == 0 {
+            new_start -= 1;
+        }
+        offset += new_count - old_count;
+
+        body.extend_from_slice(format!("@@ -{},{} +{},{} @@\n", old_start, old_count, new_start.max(0), new_count).as_bytes());
+        for (origin, content) in lines {
+            body.push(origin);
+            body.extend_from_slice(content);
+            if !content.ends_with(b"\n") {
+                body.extend_from_slice(b"\n\\ No newline at end of file\n");
+            }
+        }
+    }
+
+    if body.is_empty() {
+        return None;
+    }
+
+    let mut patch = format!("diff --git a/{0} b/{0}\n", file.path).into_bytes();
+    if file.new_file && !reverse {
+        patch.extend_from_slice(format!("new file mode {:o}\n--- /dev/null\n", file.mode).as_bytes());
+    } else {
+        patch.extend_from_slice(format!("--- a/{}\n", file.path).as_bytes());
+    }
+    patch.extend_from_slice(format!("+++ b/{}\n", file.path).as_bytes());
+    patch.extend_from_slice(&body);
+    Some(patch)
+}
+
+// the safe checkout refusing because local changes would be overwritten
+pub fn is_checkout_conflict(e: &anyhow::Error) -> bool {
+    e.downcast_ref::<git2::Error>().is_some_and(|e| e.code() == git2::ErrorCode::Conflict)
+}
+
+// "On main: msg" / "WIP on main: abc1234 subject" -> (Some("main"), "msg")
+fn parse_stash_message(raw: &str) -> (Option<String>, String) {
+    let rest = raw.strip_prefix("WIP on ").or_else(|| raw.strip_prefix("On "));
+    match rest.and_then(|r| r.split_once(": ")) {
+        Some((branch, message)) => (Some(branch.to_string()), message.to_string()),
+        None => (None, raw.to_string()),
+    }
+}
+
+fn collect_file_stats(diff: &git2::Diff, files: &mut Vec<StashFileStat>) -> Result<()> {
+    for i in 0..diff.deltas().len() {
+        if let Some(stat) = file_stat(diff, i, true)? {
+            files.push(stat);
+        }
+    }
+    Ok(())
+}
+
+// the `i`th file of the diff, without +/- when `count` is false (its content isn't here)
+fn file_stat(diff: &git2::Diff, i: usize, count: bool) -> Result<Option<StashFileStat>> {
+    let Some(delta) = diff.get_delta(i) else {
+        return Ok(None);
+    };
+    let path = delta.new_file().path().or(delta.old_file().path())
+        .map(|p| p.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let status = match delta.status() {
+        git2::Delta::Added => 'A',
+        git2::Delta::Deleted => 'D',
+        git2::Delta::Renamed => 'R',
+        _ => 'M',
+    };
+    let patch = if count { git2::Patch::from_diff(diff, i)? } else { None };
+    let (additions, deletions) = match patch {
+        Some(patch) => {
+            let (_, additions, deletions) = patch.line_stats()?;
+            (additions, deletions)
+        }
+        None => (0, 0),
+    };
+    Ok(Some(StashFileStat { path, status, additions, deletions }))
+}
+
+// git@host:user/repo.git, ssh://git@host/user/repo, https://host/user/repo.git -> https://host/user/repo
+fn remote_to_web_url(remote_url: &str) -> Option<String> {
+    let remote_url = remote_url.trim().trim_end_matches('/');
+    let remote_url = remote_url.strip_suffix(".git").unwrap_or(remote_url);
+
+    if let Some(rest) = remote_url.strip_prefix("git@") {
+        let (host, path) = rest.split_once(':')?;
+        return Some(format!("https://{}/{}", host, path));
+    }
+
+    let parsed = url::Url::parse(remote_url).ok()?;
+    let host = parsed.host_str()?;
+    match parsed.scheme() {
+        "http" | "https" | "ssh" | "git" => Some(format!("https://{}{}", host, parsed.path())),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ResolvedLine {
+    pub text: String,
+    pub from_resolution: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct ResolvedFile {
+    pub lines: Vec<ResolvedLine>,
+    // where each hunk ended up in the output, for scrolling a preview to it
+    pub hunk_starts: Vec<usize>,
+}
+
+// replaces every resolved hunk in `content` with its resolution, unresolved hunks keep their markers
+pub fn apply_resolutions(
+    content: &str,
+    conflicted_file: &ConflictedFile,
+    file_idx: usize,
+    conflict_resolutions: &std::collections::HashMap<(usize, usize), MergeResolution>,
+) -> ResolvedFile {
+    let lines: Vec<&str> = content.lines().collect();
+
+    let mut resolved_lines = Vec::new();
+    let mut hunk_starts = Vec::new();
+    let mut i = 0;
+    let mut hunk_idx = 0;
+
+    let plain = |text: &str| ResolvedLine { text: text.to_string(), from_resolution: false };
+
+    while i < lines.len() {
+        if lines[i].starts_with("<<<<<<<") && hunk_idx < conflicted_file.conflicts.len() {
+            hunk_starts.push(resolved_lines.len());
+            if let Some(resolution) = conflict_resolutions.get(&(file_idx, hunk_idx)) {
+                let hunk = &conflicted_file.conflicts[hunk_idx];
+                let resolved_content = hunk.resolve(resolution);
+
+                for line in resolved_content.lines() {
+                    resolved_lines.push(ResolvedLine { text: line.to_string(), from_resolution: true });
+                }
+            } else {
+                while i <= conflicted_file.conflicts[hunk_idx].end_line && i < lines.len() {
+                    resolved_lines.push(plain(lines[i]));
+                    i += 1;
+                }
+                i -= 1;
+            }
+            while i < lines.len() && !lines[i].starts_with(">>>>>>>") {
+                i += 1;
+            }
+            hunk_idx += 1;
+        } else {
+            resolved_lines.push(plain(lines[i]));
+        }
+        i += 1;
+    }
+
+    ResolvedFile { lines: resolved_lines, hunk_starts }
+}
+
+// how many characters of an id are shown when core.abbrev doesn't say (or says auto)
+const DEFAULT_ABBREV: usize = 8;
+
+pub struct Repository {
+    repo: Git2Repository,
+    // path prefix status is limited to, full status on a big monorepo can take seconds
+    status_scope: Option<String>,
+    // diff pane hides changes that are only CR/LF at line ends
+    ignore_eol: bool,
+    // a ref the diff pane compares files against instead of the index / HEAD, e.g. origin/main
+    diff_base: Option<String>,
+    // what `git credential fill` gave the network operation under way, approved or rejected
+    // once it's done so the helper remembers (or forgets) it
+    filled_credential: std::cell::RefCell<Option<String>>,
+    // typed into the credential prompt (url, username, token), for the rest of the session
+    prompted_credential: std::cell::RefCell<Option<(String, String, String)>>,
+    // a url nothing had a username/token for, the app picks it up and opens the prompt
+    credential_request: std::cell::RefCell<Option<String>>,
+    // full id -> what short_id gave for it, the log asks for the same ones every frame
+    short_ids: std::cell::RefCell<std::collections::HashMap<String, String>>,
+    // the app's offline mode, a partial clone doesn't go for missing objects then
+    offline: std::cell::Cell<bool>,
+}
+
+impl Repository {
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let repo = Git2Repository::discover(path)
+            .context("failed to find Git repository. try again.")?;
+        Ok(Self { repo, status_scope: None, ignore_eol: false, diff_base: None, filled_credential: Default::default(), prompted_credential: Default::default(), credential_request: Default::default(), short_ids: Default::default(), offline: Default::default() })
+    }
+
+    // like git --git-dir / --work-tree: no discovery, the work tree (if any) can live anywhere
+    pub fn open_git_dir(git_dir: &Path, work_tree: Option<&Path>) -> Result<Self> {
+        let repo = Git2Repository::open(git_dir)
+            .with_context(|| format!("{} is not a git directory", git_dir.display()))?;
+        let mut repo = Self { repo, status_scope: None, ignore_eol: false, diff_base: None, filled_credential: Default::default(), prompted_credential: Default::default(), credential_request: Default::default(), short_ids: Default::default(), offline: Default::default() };
+        if let Some(work_tree) = work_tree {
+            repo.set_work_tree(work_tree)?;
+        }
+        Ok(repo)
+    }
+
+    pub fn set_work_tree(&mut self, work_tree: &Path) -> Result<()> {
+        if !work_tree.is_dir() {
+            anyhow::bail!("work tree {} is not a directory", work_tree.display());
+        }
+        self.repo.set_workdir(work_tree, false)?;
+        Ok(())
+    }
+
+    pub fn is_bare(&self) -> bool {
+        self.repo.workdir().is_none()
+    }
+
+    // git pointed at this repo even when the git dir isn't <work tree>/.git
+    pub fn git_command(&self) -> std::process::Command {
+        let mut cmd = std::process::Command::new("git");
+        cmd.env("GIT_DIR", self.repo.path());
+        match self.repo.workdir() {
+            Some(workdir) => cmd.env("GIT_WORK_TREE", workdir).current_dir(workdir),
+            None => cmd.current_dir(self.repo.path()),
+        };
+        cmd
+    }
+
+    pub fn status(&self) -> Result<RepoStatus> {
+        if self.is_bare() {
+            anyhow::bail!("bare repository, there is no working tree to show the status of");
+        }
+        let mut opts = StatusOptions::new();
+        opts.include_untracked(true);
+        opts.include_ignored(false);
+        if let Some(scope) = &self.status_scope {
+            opts.pathspec(scope);
+        }
+
+        let statuses = self.repo.statuses(Some(&mut opts))?;
+
+        let mut staged = Vec::new();
+        let mut unstaged = Vec::new();
+        let mut untracked = Vec::new();
+        let mut nested = Vec::new();
+
+        let submodule_paths: Vec<String> = self.repo.submodules()
+            .map(|subs| subs.iter().map(|s| s.path().to_string_lossy().to_string()).collect())
+            .unwrap_or_default();
+
+        for entry in statuses.iter() {
+            let path = entry.path().unwrap_or("").to_string();
+            let status = entry.status();
+
+            // nested repos get one summary line instead of their files
+            let wt_changed = status.is_wt_new() || status.is_wt_modified() || status.is_wt_deleted();
+            let dir = path.trim_end_matches('/');
+            let nested_kind = if submodule_paths.iter().any(|p| p == dir) {
+                Some(NestedKind::Submodule)
+            } else if status.is_wt_new() && path.ends_with('/') && self.workdir().is_some_and(|w| w.join(dir).join(".git").exists()) {
+                Some(NestedKind::Nested)
+            } else {
+                None
+            };
+            if let Some(kind) = nested_kind {
+                if wt_changed {
+                    nested.push(NestedRepo {
+                        path: dir.to_string(),
+                        kind,
+                        changes: self.count_nested_changes(dir),
+                    });
+                }
+                if kind == NestedKind::Nested {
+                    continue;
+                }
+            }
+
+            if status.is_index_new() || status.is_index_modified() || status.is_index_deleted() || status.is_index_typechange() {
+                let status_char = if status.is_index_new() { "A" }
+                else if status.is_index_modified() { "M" }
+                else if status.is_index_deleted() { "D" }
+                else if status.is_index_typechange() { "T" }
+                else { "?" };
+
+                staged.push(FileStatus {
+                    path: path.clone(),
+                    status: status_char.to_string(),
+                    mode_change: mode_change(entry.head_to_index()),
+                });
+            }
+
+            if nested_kind.is_some() {
+                // submodule pointer changes in the index still show as staged above
+            } else if status.is_wt_new() {
+                untracked.push(FileStatus {
+                    path,
+                    status: "??".to_string(),
+                    mode_change: None,
+                });
+            } else if status.is_wt_modified() || status.is_wt_deleted() || status.is_wt_typechange() {
+                let status_char = if status.is_wt_modified() { "M" } else if status.is_wt_typechange() { "T" } else { "D" };
+                unstaged.push(FileStatus {
+                    path,
+                    status: status_char.to_string(),
+                    mode_change: mode_change(entry.index_to_workdir()),
+                });
+            }
+        }
+
+        let branch = self.get_current_branch()?;
+        let upstream = self.head_upstream();
+        let (ahead, behind) = upstream.as_ref().map(|u| (u.ahead, u.behind)).unwrap_or((0, 0));
+
+        Ok(RepoStatus {
+            branch,
+            upstream: upstream.map(|u| u.name),
+            ahead,
+            behind,
+            staged,
+            unstaged,
+            untracked,
+            nested,
+            flagged: self.flagged_files()?,
+        })
+    }
+
+    pub fn index_conflicts(&self) -> Result<Vec<IndexConflict>> {
+        let index = self.repo.index()?;
+        let mut conflicts = Vec::new();
+        for conflict in index.conflicts()? {
+            let conflict = conflict?;
+            let Some(entry) = conflict.our.as_ref().or(conflict.their.as_ref()).or(conflict.ancestor.as_ref()) else {
+                continue;
+            };
+            let path = String::from_utf8_lossy(&entry.path).to_string();
+            if self.status_scope.as_deref().is_some_and(|scope| !path.starts_with(scope)) {
+                continue;
+            }
+            conflicts.push(IndexConflict { path, base: conflict.ancestor.is_some(), ours: conflict.our.is_some(), theirs: conflict.their.is_some() });
+        }
+        Ok(conflicts)
+    }
+
+    fn flagged_files(&self) -> Result<Vec<FlaggedFile>> {
+        let index = self.repo.index()?;
+        let mut flagged = Vec::new();
+        for entry in index.iter() {
+            let skip_worktree = git2::IndexEntryExtendedFlag::from_bits_truncate(entry.flags_extended).is_skip_worktree();
+            let assume_unchanged = entry.flags & git2::IndexEntryFlag::VALID.bits() != 0;
+            if !skip_worktree && !assume_unchanged {
+                continue;
+            }
+            let path = String::from_utf8_lossy(&entry.path).to_string();
+            if self.status_scope.as_deref().is_some_and(|scope| !path.starts_with(scope)) {
+                continue;
+            }
+            flagged.push(FlaggedFile { path, skip_worktree, assume_unchanged });
+        }
+        Ok(flagged)
+    }
+
+    // git update-index --[no-]skip-worktree / --[no-]assume-unchanged
+    pub fn set_index_flag(&self, path: &str, flag: IndexFlag, on: bool) -> Result<()> {
+        let option = format!("--{}{}", if on { "" } else { "no-" }, flag.label());
+        self.run_git(&["update-index", option.as_str(), "--", path])?;
+        Ok(())
+    }
+
+    pub fn status_scope(&self) -> Option<&str> {
+        self.status_scope.as_deref()
+    }
+
+    pub fn set_status_scope(&mut self, scope: Option<&str>) -> Result<()> {
+        let scope = scope.map(pathspec);
+        let scope = scope.as_deref().map(|s| s.trim_matches('/')).filter(|s| !s.is_empty());
+        if let (Some(scope), Some(workdir)) = (scope, self.workdir()) {
+            if !workdir.join(scope).is_dir() {
+                anyhow::bail!("{} is not a directory in this repository", scope);
+            }
+        }
+        self.status_scope = scope.map(|s| format!("{}/", s));
+        Ok(())
+    }
+
+    pub fn ignore_eol(&self) -> bool {
+        self.ignore_eol
+    }
+
+    pub fn set_ignore_eol(&mut self, ignore: bool) {
+        self.ignore_eol = ignore;
+    }
+
+    pub fn diff_base(&self) -> Option<&str> {
+        self.diff_base.as_deref()
+    }
+
+    pub fn set_diff_base(&mut self, base: Option<&str>) -> Result<()> {
+        let base = base.map(|b| b.trim()).filter(|b| !b.is_empty());
+        if let Some(base) = base {
+            self.base_tree(base)?;
+        }
+        self.diff_base = base.map(|b| b.to_string());
+        Ok(())
+    }
+
+    fn base_tree(&self, base: &str) -> Result<git2::Tree<'_>> {
+        self.repo.revparse_single(base).and_then(|o| o.peel_to_tree()).with_context(|| format!("{} isn't a branch, tag or commit", base))
+    }
+
+    pub fn line_counts(&self) -> Result<LineCounts> {
+        let mut diff_opts = DiffOptions::new();
+        diff_opts.ignore_whitespace_eol(self.ignore_eol);
+        if let Some(scope) = &self.status_scope {
+            diff_opts.pathspec(scope);
+        }
+        let head_tree = self.repo.head().ok().and_then(|h| h.peel_to_tree().ok());
+        let staged = self.repo.diff_tree_to_index(head_tree.as_ref(), None, Some(&mut diff_opts))?;
+        diff_opts.include_untracked(true).show_untracked_content(true);
+        let unstaged = self.repo.diff_index_to_workdir(None, Some(&mut diff_opts))?;
+
+        let mut counts = LineCounts::default();
+        for (diff, map) in [(&staged, &mut counts.staged), (&unstaged, &mut counts.unstaged)] {
+            let mut files = Vec::new();
+            collect_file_stats(diff, &mut files)?;
+            map.extend(files.into_iter().map(|f| (f.path, (f.additions, f.deletions))));
+        }
+        Ok(counts)
+    }
+
+    // every tracked file the worktree (staged or not) differs in from the diff base, what the
+    // branch plus the local changes amount to. limited to the status scope like status is
+    pub fn diff_base_changes(&self) -> Result<Vec<StashFileStat>> {
+        let Some(base) = &self.diff_base else {
+            return Ok(Vec::new());
+        };
+        let tree = self.base_tree(base)?;
+        let mut diff_opts = DiffOptions::new();
+        if let Some(scope) = &self.status_scope {
+            diff_opts.pathspec(scope);
+        }
+        let mut diff = self.repo.diff_tree_to_workdir_with_index(Some(&tree), Some(&mut diff_opts))?;
+        diff.find_similar(None)?;
+        let mut files = Vec::new();
+        collect_file_stats(&diff, &mut files)?;
+        Ok(files)
+    }
+
+    // subdirectories of `dir` (relative to the workdir) for the scope picker
+    pub fn list_directories(&self, dir: &str) -> Result<Vec<String>> {
+        let workdir = self.workdir().context("bare repository has no working tree")?;
+        let mut dirs: Vec<String> = fs::read_dir(workdir.join(dir))?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.file_type().map(|t| t.is_dir()).unwrap_or(false))
+            .map(|entry| entry.file_name().to_string_lossy().to_string())
+            .filter(|name| name != ".git")
+            .map(|name| if dir.is_empty() { name } else { format!("{}/{}", dir, name) })
+            .collect();
+        dirs.sort();
+        Ok(dirs)
+    }
+
+    fn count_nested_changes(&self, path: &str) -> Option<usize> {
+        let nested_repo = Git2Repository::open(self.workdir()?.join(path)).ok()?;
+        let mut opts = StatusOptions::new();
+        opts.include_untracked(true);
+        opts.include_ignored(false);
+        nested_repo.statuses(Some(&mut opts)).ok().map(|s| s.len())
+    }
+
+    pub fn get_commits(&self, count: usize) -> Result<Vec<Commit>> {
+        self.get_commits_where(count, |_| true)
+    }
+
+    // the newest `count` of the ones `keep` lets through
+    pub fn get_commits_where(&self, count: usize, keep: impl Fn(&Commit) -> bool) -> Result<Vec<Commit>> {
+        if !self.has_commits() {
+            return Ok(Vec::new());
+        }
+        let mut revwalk = self.repo.revwalk()?;
+        revwalk.push_head()?;
+        revwalk.set_sorting(git2::Sort::TIME)?;
+
+        let mut commits = Vec::new();
+        let mailmap = self.mailmap();
+
+        for oid in revwalk {
+            if commits.len() >= count { break; }
+
+            let oid = oid?;
+            let commit = to_commit(&self.repo.find_commit(oid)?, mailmap.as_ref());
+            if keep(&commit) {
+                commits.push(commit);
+            }
+        }
+        Ok(commits)
+    }
+
+    // .mailmap (or mailmap.file / mailmap.blob) plus hydra.mailmap lines from config, for people
+    // who can't or won't commit a .mailmap. later entries win, so config overrides the file
+    pub fn mailmap(&self) -> Option<git2::Mailmap> {
+        let mut mailmap = self.repo.mailmap().ok()?;
+        for line in self.config_values("hydra.mailmap") {
+            if let Err(e) = add_mailmap_line(&mut mailmap, &line) {
+                log::warn!("{}", e);
+            }
+        }
+        Some(mailmap)
+    }
+
+    pub fn update_graph(&self, graph: &mut crate::graph::Graph) -> Result<bool> {
+        graph.update(&self.repo)
+    }
+
+    pub fn commits_by_id(&self, ids: &[git2::Oid]) -> Result<Vec<Commit>> {
+        let mailmap = self.mailmap();
+        ids.iter().map(|id| Ok(to_commit(&self.repo.find_commit(*id)?, mailmap.as_ref()))).collect()
+    }
+
+    // attributes that change how a file is diffed, merged or stored
+    pub fn file_attributes(&self, path: &str) -> Vec<String> {
+        let path = Path::new(path);
+        ["text", "eol", "diff", "merge", "filter", "working-tree-encoding"]
+            .iter()
+            .filter_map(|name| {
+                let value = self.repo.get_attr(path, name, git2::AttrCheckFlags::FILE_THEN_INDEX).ok()?;
+                match git2::AttrValue::from_string(value) {
+                    git2::AttrValue::True => Some(name.to_string()),
+                    git2::AttrValue::False => Some(format!("-{}", name)),
+                    git2::AttrValue::String(v) => Some(format!("{}={}", name, v)),
+                    git2::AttrValue::Bytes(v) => Some(format!("{}={}", name, String::from_utf8_lossy(v))),
+                    git2::AttrValue::Unspecified => None,
+                }
+            })
+            .collect()
+    }
+
+    fn gitattributes_path(&self) -> Result<std::path::PathBuf> {
+        let workdir = self.workdir().context("bare repository has no .gitattributes")?;
+        Ok(workdir.join(".gitattributes"))
+    }
+
+    pub fn read_gitattributes(&self) -> Result<Vec<String>> {
+        let path = self.gitattributes_path()?;
+        if !path.exists() {
+            return Ok(Vec::new());
+        }
+        Ok(fs::read_to_string(path)?.lines().map(|l| l.to_string()).collect())
+    }
+
+    pub fn write_gitattributes(&self, lines: &[String]) -> Result<()> {
+        let path = self.gitattributes_path()?;
+        let mut content = lines.join("\n");
+        if !content.is_empty() {
+            content.push('\n');
+        }
+        fs::write(path, content)?;
+        Ok(())
+    }
+
+    // gpg and ssh signatures both go through git's own verification so we respect gpg.* config
+    pub fn verify_commit_signature(&self, commit_id: &str) -> Result<CommitSignature> {
+        let oid = git2::Oid::from_str(commit_id)?;
+
+        // cheap check first, most commits aren't signed and don't need a git process
+        if self.repo.extract_signature(&oid, None).is_err() {
+            return Ok(CommitSignature { status: SignatureStatus::Unsigned, signer: None, detail: None });
+        }
+
+        let output = self.run_git(&["log", "-1", "--format=%G?%x00%GS%x00%GK", commit_id])?;
+        let mut fields = output.trim_end_matches('\n').split('\0');
+        let code = fields.next().unwrap_or("");
+        let signer = fields.next().filter(|s| !s.is_empty()).map(|s| s.to_string());
+        let key = fields.next().filter(|s| !s.is_empty()).map(|s| s.to_string());
+
+        let (status, detail) = match code {
+            "G" => (SignatureStatus::Verified, None),
+            "U" => (SignatureStatus::Untrusted, Some("key validity unknown".to_string())),
+            "B" => (SignatureStatus::Bad, None),
+            "X" => (SignatureStatus::Unverified, Some("signature expired".to_string())),
+            "Y" => (SignatureStatus::Unverified, Some("key expired".to_string())),
+            "R" => (SignatureStatus::Unverified, Some("key revoked".to_string())),
+            "E" => (SignatureStatus::Unverified, Some(match &key {
+                Some(key) => format!("missing key {}", key),
+                None => "cannot be checked".to_string(),
+            })),
+            _ => (SignatureStatus::Unverified, Some("no verifier configured".to_string())),
+        };
+
+        Ok(CommitSignature { status, signer, detail })
+    }
+
+    // commit id -> refs pointing at it, tags peeled to their commit
+    pub fn get_ref_map(&self) -> Result<std::collections::HashMap<String, Vec<RefLabel>>> {
+        let mut map: std::collections::HashMap<String, Vec<RefLabel>> = std::collections::HashMap::new();
+
+        if let Ok(head) = self.repo.head() {
+            if let Ok(commit) = head.peel_to_commit() {
+                map.entry(commit.id().to_string()).or_default().push(RefLabel {
+                    name: "HEAD".to_string(),
+                    kind: RefKind::Head,
+                });
+            }
+        }
+
+        for reference in self.repo.references()? {
+            let reference = reference?;
+            let kind = if reference.is_branch() {
+                RefKind::LocalBranch
+            } else if reference.is_remote() {
+                RefKind::RemoteBranch
+            } else if reference.is_tag() {
+                RefKind::Tag
+            } else {
+                continue;
+            };
+
+            let Some(name) = reference.shorthand() else {
+                continue;
+            };
+            // origin/HEAD just repeats the default branch
+            if kind == RefKind::RemoteBranch && name.ends_with("/HEAD") {
+                continue;
+            }
+            let Ok(commit) = reference.peel_to_commit() else {
+                continue;
+            };
+
+            map.entry(commit.id().to_string()).or_default().push(RefLabel {
+                name: name.to_string(),
+                kind,
+            });
+        }
+
+        for labels in map.values_mut() {
+            labels.sort_by_key(|l| match l.kind {
+                RefKind::Head => 0,
+                RefKind::LocalBranch => 1,
+                RefKind::RemoteBranch => 2,
+                RefKind::Tag => 3,
+            });
+        }
+        Ok(map)
+    }
+
+    pub fn resolve_commit(&self, rev: &str) -> Result<String> {
+        let commit = self.repo.revparse_single(rev.trim())
+            .with_context(|| format!("unknown revision '{}'", rev.trim()))?
+            .peel_to_commit()?;
+        Ok(commit.id().to_string())
+    }
+
+    // index of a commit in the same order get_commits walks, None if HEAD can't reach it
+    pub fn commit_position(&self, commit_id: &str) -> Result<Option<usize>> {
+        let target = git2::Oid::from_str(commit_id)?;
+        if !self.has_commits() {
+            return Ok(None);
+        }
+        let mut revwalk = self.repo.revwalk()?;
+        revwalk.push_head()?;
+        revwalk.set_sorting(git2::Sort::TIME)?;
+
+        for (i, oid) in revwalk.enumerate() {
+            if oid? == target {
+                return Ok(Some(i));
+            }
+        }
+        Ok(None)
+    }
+
+    // git log -S: commits where the number of occurrences of `needle` changes in some file
+    pub fn objects_disk_size(&self) -> u64 {
+        dir_size(&self.repo.path().join("objects"))
+    }
+
+    // the `limit` biggest blobs in the object database, biggest first, each with the path and
+    // commit that first added it. history is walked oldest first from every ref until they've
+    // all been found, which on a big repo takes a while
+    pub fn large_objects(&self, limit: usize) -> Result<(Vec<LargeObject>, ObjectTotals)> {
+        let odb = self.repo.odb()?;
+        let mut totals = ObjectTotals { disk_bytes: self.objects_disk_size(), ..Default::default() };
+        let mut blobs: Vec<(usize, git2::Oid)> = Vec::new();
+        odb.foreach(|oid| {
+            totals.objects += 1;
+            if let Ok((size, git2::ObjectType::Blob)) = odb.read_header(*oid) {
+                totals.blobs += 1;
+                totals.blob_bytes += size as u64;
+                blobs.push((size, *oid));
+            }
+            true
+        })?;
+        // packs and loose objects can both have the same one
+        blobs.sort_unstable_by(|a, b| b.cmp(a));
+        blobs.dedup_by_key(|(_, oid)| *oid);
+        blobs.truncate(limit);
+
+        let mut found: std::collections::HashMap<git2::Oid, (String, git2::Oid)> = std::collections::HashMap::new();
+        if self.has_commits() && !blobs.is_empty() {
+            let wanted: std::collections::HashSet<git2::Oid> = blobs.iter().map(|(_, oid)| *oid).collect();
+            let mut revwalk = self.repo.revwalk()?;
+            revwalk.push_glob("refs/*")?;
+            revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::REVERSE)?;
+            for oid in revwalk {
+                let commit = self.repo.find_commit(oid?)?;
+                let parent_tree = commit.parent(0).ok().map(|p| p.tree()).transpose()?;
+                let diff = self.repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), None)?;
+                for delta in diff.deltas() {
+                    let blob = delta.new_file().id();
+                    if wanted.contains(&blob) && !found.contains_key(&blob) {
+                        let path = delta.new_file().path().map(|p| p.to_string_lossy().to_string()).unwrap_or_default();
+                        found.insert(blob, (path, commit.id()));
+                    }
+                }
+                if found.len() == wanted.len() {
+                    break;
+                }
+            }
+        }
+
+        let mailmap = self.mailmap();
+        let objects = blobs
+            .into_iter()
+            .map(|(size, oid)| {
+                let (path, commit) = match found.get(&oid) {
+                    Some((path, commit)) => (Some(path.clone()), self.repo.find_commit(*commit).ok().map(|c| to_commit(&c, mailmap.as_ref()))),
+                    None => (None, None),
+                };
+                LargeObject { oid, size, path, commit }
+            })
+            .collect();
+        Ok((objects, totals))
+    }
+
+    // leftover lock files, refs that don't resolve and whatever `git fsck` finds. commits that are
+    // only dangling get listed so they can be turned back into branches
+    pub fn health_check(&self) -> Result<HealthReport> {
+        let mut report = HealthReport::default();
+        find_locks(self.repo.path(), &mut report.issues);
+
+        for reference in self.repo.references()? {
+            let problem = match &reference {
+                Err(e) => Some((String::new(), e.message().to_string())),
+                Ok(r) => {
+                    let name = r.name().unwrap_or("").to_string();
+                    match r.target() {
+                        Some(oid) if self.repo.find_object(oid, None).is_err() => Some((name, format!("points at {}, which isn't in the repository", oid))),
+                        Some(_) => None,
+                        None => r.resolve().err().map(|_| (name, format!("symbolic ref to {}, which doesn't exist", r.symbolic_target().unwrap_or("?")))),
+                    }
+                }
+            };
+            if let Some((name, problem)) = problem {
+                report.issues.push(HealthIssue::BrokenRef { name, problem });
+            }
+        }
+
+        let output = self.git_command()
+            .args(["fsck", "--no-progress", "--dangling"])
+            .output()
+            .context("failed to run git fsck, is git installed?")?;
+        let broken: Vec<String> = report.issues.iter().filter_map(|i| match i {
+            HealthIssue::BrokenRef { name, .. } if !name.is_empty() => Some(name.clone()),
+            _ => None,
+        }).collect();
+        let mailmap = self.mailmap();
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        for line in stdout.lines().chain(stderr.lines()).map(str::trim).filter(|l| !l.is_empty()) {
+            match line.strip_prefix("dangling ").and_then(|rest| rest.split_once(' ')) {
+                Some(("commit", id)) => match git2::Oid::from_str(id).and_then(|oid| self.repo.find_commit(oid)) {
+                    Ok(commit) => report.issues.push(HealthIssue::Dangling(to_commit(&commit, mailmap.as_ref()))),
+                    Err(_) => report.issues.push(HealthIssue::Fsck(line.to_string())),
+                },
+                Some(_) => report.dangling_objects += 1,
+                // the broken refs are listed already
+                None if broken.iter().any(|name| line.contains(&format!("{}:", name))) => {}
+                None => report.issues.push(HealthIssue::Fsck(line.to_string())),
+            }
+        }
+        if !output.status.success() && stderr.trim().is_empty() {
+            report.issues.push(HealthIssue::Fsck(format!("git fsck exited with {}", output.status)));
+        }
+        Ok(report)
+    }
+
+    // cheap unless the index file was touched since `previous`, then its entries are read again
+    pub fn snapshot(&self, previous: &RepoSnapshot) -> RepoSnapshot {
+        let head_ref = self.repo.find_reference("HEAD").ok().map(|r| match r.symbolic_target() {
+            Some(target) => target.to_string(),
+            None => "HEAD".to_string(),
+        });
+        let head = self.repo.refname_to_id("HEAD").ok();
+        let index_modified = fs::metadata(self.repo.path().join("index")).and_then(|m| m.modified()).ok();
+        let index_entries = if index_modified == previous.index_modified {
+            previous.index_entries
+        } else {
+            use std::hash::{Hash, Hasher};
+            let mut hasher = std::collections::hash_map::DefaultHasher::new();
+            if let Ok(mut index) = self.repo.index() && index.read(true).is_ok() {
+                for entry in index.iter() {
+                    (entry.path, entry.id, entry.mode, entry.flags).hash(&mut hasher);
+                }
+            }
+            hasher.finish()
+        };
+        RepoSnapshot { head_ref, head, index_modified, index_entries }
+    }
+
+    // when FETCH_HEAD was last written, by hydra or a git fetch anywhere else
+    pub fn last_fetched(&self) -> Option<DateTime<FixedOffset>> {
+        let modified = fs::metadata(self.repo.path().join("FETCH_HEAD")).and_then(|m| m.modified()).ok()?;
+        Some(DateTime::<chrono::Local>::from(modified).fixed_offset())
+    }
+
+    pub fn index_lock(&self) -> std::path::PathBuf {
+        self.repo.path().join("index.lock")
+    }
+
+    // only lock files inside the git dir, whatever the path says
+    pub fn remove_lock(&self, path: &Path) -> Result<()> {
+        if !path.starts_with(self.repo.path()) || path.extension().is_none_or(|e| e != "lock") {
+            anyhow::bail!("{} isn't a lock file of this repository", path.display());
+        }
+        fs::remove_file(path).with_context(|| format!("failed to remove {}", path.display()))
+    }
+
+    pub fn recover_commit(&self, commit_id: &str, branch: &str) -> Result<()> {
+        let commit = self.repo.find_commit(git2::Oid::from_str(commit_id)?)?;
+        self.repo.branch(branch, &commit, false)?;
+        Ok(())
+    }
+
+    // refs git2 can't even parse are deleted as files
+    pub fn delete_broken_ref(&self, name: &str) -> Result<()> {
+        match self.repo.find_reference(name) {
+            Ok(mut reference) => reference.delete()?,
+            Err(_) => fs::remove_file(self.repo.path().join(name)).with_context(|| format!("failed to delete {}", name))?,
+        }
+        Ok(())
+    }
+
+    // every commit on HEAD made on or after `since`, for the stats view. the walk is by commit
+    // time, so it stops at the first one that's older
+    pub fn contributions(&self, since: chrono::NaiveDate) -> Result<Vec<crate::stats::Contribution>> {
+        if !self.has_commits() {
+            return Ok(Vec::new());
+        }
+        let mut revwalk = self.repo.revwalk()?;
+        revwalk.push_head()?;
+        revwalk.set_sorting(git2::Sort::TIME)?;
+        let mailmap = self.mailmap();
+
+        let mut contributions = Vec::new();
+        for oid in revwalk {
+            let commit = self.repo.find_commit(oid?)?;
+            let day = commit_time(&commit).date_naive();
+            if day < since {
+                break;
+            }
+            let author = mailmap.as_ref().and_then(|m| commit.author_with_mailmap(m).ok()).unwrap_or_else(|| commit.author());
+            contributions.push(crate::stats::Contribution { day, author: author.name().unwrap_or("unknown").to_string() });
+        }
+        Ok(contributions)
+    }
+
+    pub fn pickaxe_search(&self, needle: &str, max_results: usize) -> Result<Vec<Commit>> {
+        if !self.has_commits() {
+            return Ok(Vec::new());
+        }
+        let mut revwalk = self.repo.revwalk()?;
+        revwalk.push_head()?;
+        revwalk.set_sorting(git2::Sort::TIME)?;
+
+        let mut commits = Vec::new();
+        let mailmap = self.mailmap();
+
+        for oid in revwalk {
+            let commit = self.repo.find_commit(oid?)?;
+            if self.commit_changes_occurrences(&commit, needle.as_bytes())? {
+                commits.push(to_commit(&commit, mailmap.as_ref()));
+                if commits.len() >= max_results {
+                    break;
+                }
+            }
+        }
+        Ok(commits)
+    }
+
+    // git log -- <dir>: commits whose diff against their first parent touches something under dir
+    pub fn path_commits(&self, dir: &str, max_results: usize) -> Result<Vec<Commit>> {
+        if !self.has_commits() {
+            return Ok(Vec::new());
+        }
+        let mut revwalk = self.repo.revwalk()?;
+        revwalk.push_head()?;
+        revwalk.set_sorting(git2::Sort::TIME)?;
+        let mut options = git2::DiffOptions::new();
+        options.pathspec(format!("{}/", dir.trim_end_matches('/')));
+
+        let mut commits = Vec::new();
+        let mailmap = self.mailmap();
+        for oid in revwalk {
+            let commit = self.repo.find_commit(oid?)?;
+            let parent_tree = match commit.parent(0) {
+                Ok(parent) => Some(parent.tree()?),
+                Err(_) => None,
+            };
+            let diff = self.repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), Some(&mut options))?;
+            if diff.deltas().len() > 0 {
+                commits.push(to_commit(&commit, mailmap.as_ref()));
+                if commits.len() >= max_results {
+                    break;
+                }
+            }
+        }
+        Ok(commits)
+    }
+
+    fn commit_changes_occurrences(&self, commit: &git2::Commit, needle: &[u8]) -> Result<bool> {
+        // like git, merges have no diff of their own to search
+        if commit.parent_count() > 1 {
+            return Ok(false);
+        }
+
+        let tree = commit.tree()?;
+        let parent_tree = match commit.parent_count() {
+            0 => None,
+            _ => Some(commit.parent(0)?.tree()?),
+        };
+        let diff = self.repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+
+        for delta in diff.deltas() {
+            let old_blob = self.find_blob_opt(delta.old_file().id());
+            let new_blob = self.find_blob_opt(delta.new_file().id());
+
+            if old_blob.as_ref().is_some_and(|b| b.is_binary()) || new_blob.as_ref().is_some_and(|b| b.is_binary()) {
+                continue;
+            }
+
+            let old_content = old_blob.as_ref().map(|b| b.content()).unwrap_or(&[]);
+            let new_content = new_blob.as_ref().map(|b| b.content()).unwrap_or(&[]);
+
+            let old_count = count_occurrences(old_content, needle, usize::MAX);
+            // only need to know if the new side differs, so stop one past the old count
+            if count_occurrences(new_content, needle, old_count + 1) != old_count {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn find_blob_opt(&self, id: git2::Oid) -> Option<git2::Blob<'_>> {
+        if id.is_zero() {
+            return None;
+        }
+        self.repo.find_blob(id).ok()
+    }
+
+    // the current branch's upstream. branches pushed from here have none configured (push
+    // doesn't set one), for those origin/<branch> is still the best guess
+    fn head_upstream(&self) -> Option<Upstream> {
+        let head = self.repo.head().ok()?; // empty repo / head not found
+        if !head.is_branch() {
+            return None;
+        }
+        let branch = head.shorthand()?;
+        if let Some(upstream) = self.upstream(branch) {
+            return Some(upstream);
+        }
+        let guess = format!("refs/remotes/origin/{}", branch);
+        self.repo.find_reference(&guess).ok()?;
+        Some(self.compare_refs(&format!("refs/heads/{}", branch), &guess))
+    }
+
+    // branch.<name>.remote / branch.<name>.merge of a local branch, None if not set
+    pub fn upstream(&self, branch: &str) -> Option<Upstream> {
+        let local_ref = format!("refs/heads/{}", branch);
+        let upstream_ref = self.repo.branch_upstream_name(&local_ref).ok()?;
+        Some(self.compare_refs(&local_ref, upstream_ref.as_str()?))
+    }
+
+    fn compare_refs(&self, local_ref: &str, upstream_ref: &str) -> Upstream {
+        let name = upstream_ref
+            .strip_prefix("refs/remotes/")
+            .or_else(|| upstream_ref.strip_prefix("refs/heads/"))
+            .unwrap_or(upstream_ref)
+            .to_string();
+        let ids = (self.repo.refname_to_id(local_ref), self.repo.refname_to_id(upstream_ref));
+        match ids {
+            (Ok(local), Ok(upstream)) => {
+                let (ahead, behind) = self.repo.graph_ahead_behind(local, upstream).unwrap_or((0, 0));
+                Upstream { name, ahead, behind, gone: false }
+            }
+            _ => Upstream { name, ahead: 0, behind: 0, gone: true },
+        }
+    }
+
+    // how `branch` compares with every remote: <remote>/<branch>, or the remote's default branch
+    // when it has no such branch (a fork's upstream usually only has main)
+    pub fn remote_divergence(&self, branch: &str) -> Result<Vec<(String, Option<Upstream>)>> {
+        let local_ref = format!("refs/heads/{}", branch);
+        let mut divergence = Vec::new();
+        for remote in self.get_remotes()? {
+            let same_name = format!("refs/remotes/{}/{}", remote, branch);
+            let target = if self.repo.find_reference(&same_name).is_ok() {
+                Some(same_name)
+            } else {
+                self.repo.find_reference(&format!("refs/remotes/{}/HEAD", remote)).ok()
+                    .and_then(|head| head.symbolic_target().map(|t| t.to_string()))
+            };
+            let upstream = target.map(|t| self.compare_refs(&local_ref, &t));
+            divergence.push((remote, upstream));
+        }
+        Ok(divergence)
+    }
+
+    // upstream: a remote branch (origin/main) or another local one, None to unset it
+    pub fn set_upstream(&self, branch: &str, upstream: Option<&str>) -> Result<()> {
+        let mut local = self.repo.find_branch(branch, git2::BranchType::Local)
+            .with_context(|| format!("no local branch '{}'", branch))?;
+        if upstream.is_none() && self.upstream(branch).is_none() {
+            return Ok(());
+        }
+        local.set_upstream(upstream)
+            .with_context(|| format!("cannot track '{}'", upstream.unwrap_or_default()))?;
+        Ok(())
+    }
+
+    pub fn reset_to_commit(&mut self, commit_id: &str, mode: ResetMode) -> Result<()> {
+        let oid = git2::Oid::from_str(commit_id)?;
+        let commit = self.repo.find_commit(oid)?;
+        let obj = commit.as_object();
+
+        let reset_type = match mode {
+            ResetMode::Soft => git2::ResetType::Soft,
+            ResetMode::Mixed => git2::ResetType::Mixed,
+            ResetMode::Hard => git2::ResetType::Hard,
+        };
+
+        if mode == ResetMode::Hard {
+            let mut checkout_opts = git2::build::CheckoutBuilder::new();
+            checkout_opts.force();
+            self.repo.reset(obj, reset_type, Some(&mut checkout_opts))?;
+        } else {
+            self.repo.reset(obj, reset_type, None)?;
+        };
+
+        Ok(())
+    }
+
+    pub fn reset_head(&mut self, mode: ResetMode) -> Result<()> {
+        let parent_id = {
+            let head = self.repo.head()?;
+            let commit = head.peel_to_commit()?;
+
+            if let Some(parent) = commit.parents().next() {
+                parent.id().to_string()
+            } else {
+                return Err(anyhow::anyhow!("cannot reset: no parent commit found"));
+            }
+        };
+
+        self.reset_to_commit(&parent_id, mode)
+    }
+
+    pub fn get_branches(&self) -> Result<Vec<String>> {
+        let branches = self.repo.branches(None)?;
+        let mut branch_names = Vec::new();
+
+        for branch in branches {
+            let (branch, _) = branch?;
+            if let Some(name) = branch.name()? {
+                branch_names.push(name.to_string());
+            }
+        }
+
+        Ok(branch_names)
+    }
+
+    pub fn stage_file(&self, path: &str) -> Result<()> {
+        let path = pathspec(path);
+        let mut index = self.repo.index()?;
+        index.add_path(Path::new(path.as_ref()))?;
+        index.write()?;
+        Ok(())
+    }
+
+    // a file whose mode and content both changed, staged one without the other. only works for
+    // the executable bit, a symlink or a file type change has no content to keep apart
+    pub fn stage_mode_only(&self, path: &str) -> Result<()> {
+        let (mut index, mut entry, _, new) = self.split_mode_change(path)?;
+        entry.mode = new;
+        index.add(&entry)?;
+        index.write()?;
+        Ok(())
+    }
+
+    pub fn stage_content_only(&self, path: &str) -> Result<()> {
+        let (mut index, _, old, _) = self.split_mode_change(path)?;
+        index.add_path(Path::new(pathspec(path).as_ref()))?;
+        let mut entry = index.get_path(Path::new(pathspec(path).as_ref()), 0).context("the file isn't in the index")?;
+        entry.mode = old;
+        index.add(&entry)?;
+        index.write()?;
+        Ok(())
+    }
+
+    // the index, the file's entry in it and the (old, new) mode of its unstaged mode change
+    fn split_mode_change(&self, path: &str) -> Result<(git2::Index, git2::IndexEntry, u32, u32)> {
+        let path = pathspec(path);
+        let mut diff_opts = DiffOptions::new();
+        diff_opts.pathspec(path.as_ref());
+        let diff = self.repo.diff_index_to_workdir(None, Some(&mut diff_opts))?;
+        let Some((old, new)) = mode_change(diff.get_delta(0)) else {
+            anyhow::bail!("{} has no unstaged mode change", path);
+        };
+        let blob_modes = [u32::from(git2::FileMode::Blob), u32::from(git2::FileMode::BlobExecutable)];
+        if !blob_modes.contains(&old) || !blob_modes.contains(&new) {
+            anyhow::bail!("{} changes type ({:o} → {:o}), that can only be staged as a whole", path, old, new);
+        }
+        let index = self.repo.index()?;
+        let entry = index.get_path(Path::new(path.as_ref()), 0).context("the file isn't in the index")?;
+        Ok((index, entry, old, new))
+    }
+
+    pub fn unstage_file(&self, path: &str) -> Result<()> {
+        let path = pathspec(path);
+        let path = path.as_ref();
+        let mut index = self.repo.index()?;
+        // nothing committed yet, unstaging just takes the file back out of the index
+        let Ok(head) = self.repo.head().and_then(|h| h.peel_to_tree()) else {
+            index.remove_path(Path::new(path))?;
+            index.write()?;
+            return Ok(());
+        };
+
+        let entry = head.get_path(Path::new(path))?;
+        let index_entry = git2::IndexEntry{
+            ctime: git2::IndexTime::new(0, 0),
+            mtime: git2::IndexTime::new(0, 0),
+            dev: 0,
+            ino: 0,
+            mode: entry.filemode() as u32,
+            uid: 0,
+            gid: 0,
+            file_size: entry.to_object(&self.repo)?.as_blob().unwrap().size() as u32,
+            id: entry.id(),
+            flags: 0,
+            flags_extended: 0,
+            path: path.as_bytes().to_vec(),
+        };
+
+        index.add(&index_entry)?;
+        index.write()?;
+        Ok(())
+    }
+
+    pub fn commit(&self, message: &str) -> Result<git2::Oid> {
+        backend::choose(self, Operation::Commit).commit(self, message)
+    }
+
+    pub fn git2_commit(&self, message: &str) -> Result<git2::Oid> {
+        let signature = self.repo.signature()?;
+        let mut index = self.repo.index()?;
+        let tree_id = index.write_tree()?;
+        let tree = self.repo.find_tree(tree_id)?;
+
+        let parent_commit = match self.repo.head(){
+            Ok(head) => Some(head.peel_to_commit()?),
+            Err(_) => None,
+        };
+
+        let parents = if let Some(ref parent) = parent_commit {
+            vec![parent]
+        } else {
+            vec![]
+        };
+
+        let commit_id = self.repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parents,
+        )?;
+
+        Ok(commit_id)
+    }
+
+    // why `name` can't be a new branch: git's ref format rules (check-ref-format), an existing
+    // branch, or the repo's own hydra.branchPattern
+    pub fn validate_branch_name(&self, name: &str) -> Option<String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Some("empty branch name".to_string());
+        }
+        if !git2::Branch::name_is_valid(name).unwrap_or(false) {
+            return Some(format!("'{}' is not a valid branch name", name));
+        }
+        if self.local_branch_exists(name) {
+            return Some(format!("branch '{}' already exists", name));
+        }
+        if let Some(pattern) = self.config_string("hydra.branchPattern") {
+            match crate::pattern::Pattern::new(&pattern) {
+                Ok(p) if !p.is_match(name) => return Some(format!("doesn't match hydra.branchPattern {}", pattern)),
+                Ok(_) => {}
+                Err(e) => return Some(format!("bad hydra.branchPattern: {:#}", e)),
+            }
+        }
+        None
+    }
+
+    pub fn create_branch(&self, name: &str) -> Result<()> {
+        if !self.has_commits() {
+            anyhow::bail!("no commits yet, make the first commit before creating branches");
+        }
+        let head = self.repo.head()?;
+        let commit = head.peel_to_commit()?;
+        self.repo.branch(name, &commit, false)?;
+
+        Ok(())
+    }
+
+    // checks out any commit with HEAD detached, the safe way: local changes in the way make it fail
+    pub fn checkout_detached(&self, rev: &str) -> Result<git2::Oid> {
+        let commit = self.repo.revparse_single(rev)?.peel_to_commit()?;
+        self.repo.checkout_tree(commit.as_object(), None)?;
+        self.repo.set_head_detached(commit.id())?;
+        Ok(commit.id())
+    }
+
+    // the commit HEAD points at when it's detached
+    pub fn detached_head(&self) -> Option<String> {
+        if !self.repo.head_detached().unwrap_or(false) {
+            return None;
+        }
+        self.repo.head().ok()?.target().map(|oid| oid.to_string())
+    }
+
+    // changes to tracked files, the kind a checkout or pull could overwrite
+    pub fn has_local_changes(&self) -> Result<bool> {
+        let mut opts = StatusOptions::new();
+        opts.include_untracked(false).include_ignored(false);
+        let statuses = self.repo.statuses(Some(&mut opts))?;
+        Ok(statuses.iter().any(|s| s.status() != git2::Status::CURRENT))
+    }
+
+    pub fn checkout_branch(&self, name: &str) -> Result<()> {
+        backend::choose(self, Operation::Checkout).checkout(self, name)
+    }
+
+    pub fn git2_checkout(&self, name: &str) -> Result<()> {
+        let ref_name = format!("refs/heads/{}", name);
+        let obj = self.repo.revparse_single(&ref_name)?;
+
+        self.repo.checkout_tree(&obj, None)?;
+        self.repo.set_head(&ref_name)?;
+        Ok(())
+    }
+
+    // the conflict a checkout of `name` would stop on: local changes, or untracked files, where the
+    // switch changes something. how a checkout through git that failed tells local changes in the
+    // way from anything else
+    pub fn checkout_conflict(&self, name: &str) -> Option<git2::Error> {
+        let target = self.repo.revparse_single(&format!("refs/heads/{}", name)).ok()?.peel_to_tree().ok()?;
+        let head = self.repo.head().ok().and_then(|h| h.peel_to_tree().ok());
+        let diff = self.repo.diff_tree_to_tree(head.as_ref(), Some(&target), None).ok()?;
+        let changed: std::collections::HashSet<std::path::PathBuf> = diff.deltas()
+            .flat_map(|d| [d.old_file().path(), d.new_file().path()])
+            .flatten()
+            .map(Path::to_path_buf)
+            .collect();
+        let mut options = git2::StatusOptions::new();
+        options.include_untracked(true).recurse_untracked_dirs(true);
+        let statuses = self.repo.statuses(Some(&mut options)).ok()?;
+        let in_the_way = statuses.iter()
+            .filter(|entry| entry.path().is_some_and(|path| changed.contains(Path::new(path))))
+            .count();
+        (in_the_way > 0).then(|| git2::Error::new(
+            git2::ErrorCode::Conflict,
+            git2::ErrorClass::Checkout,
+            format!("{} conflict(s) prevent checkout", in_the_way),
+        ))
+    }
+
+    pub fn get_file_diff(&self, file_path: &str) -> Result<String> {
+        let mut diff_text = String::new();
+        self.print_file_diff(file_path, &mut |chunk| {
+            diff_text.push_str(chunk);
+            true
+        })?;
+        Ok(diff_text)
+    }
+
+    // just the lines start..start + count of the diff, the rest is only walked far enough to know
+    // whether there's more, so a file with a few hundred thousand changed lines stays cheap
+    pub fn get_file_diff_window(&self, file_path: &str, start: usize, count: usize) -> Result<DiffWindow> {
+        let end = start + count;
+        let mut lines = Vec::new();
+        let mut current = String::new();
+        let mut line_no = 0;
+        let mut more = false;
+        self.print_file_diff(file_path, &mut |chunk| {
+            for (i, part) in chunk.split('\n').enumerate() {
+                if i > 0 {
+                    if line_no >= start && line_no < end {
+                        lines.push(std::mem::take(&mut current));
+                    }
+                    line_no += 1;
+                }
+                // the first bit of a line past the window is enough to tell there's more
+                if line_no >= end && !part.is_empty() {
+                    more = true;
+                    return false;
+                }
+                if line_no >= start && line_no < end {
+                    current.push_str(part);
+                }
+            }
+            true
+        })?;
+
+        let total = if more {
+            None
+        } else {
+            if !current.is_empty() {
+                lines.push(current);
+                line_no += 1;
+            }
+            Some(line_no)
+        };
+        Ok(DiffWindow { start, lines, total })
+    }
+
+    // hands the patch text to `sink` a line at a time (lines keep their newline), the staged
+    // changes when there's nothing unstaged. stops as soon as `sink` returns false
+    fn print_file_diff(&self, file_path: &str, sink: &mut dyn FnMut(&str) -> bool) -> Result<()> {
+        let mut diff_opts = DiffOptions::new();
+        diff_opts.pathspec(pathspec(file_path).as_ref()).ignore_whitespace_eol(self.ignore_eol);
+
+        // against the base, staged and unstaged together. a file that's the same there (or
+        // untracked) falls through to the usual diff
+        if let Some(base) = &self.diff_base {
+            let tree = self.base_tree(base)?;
+            let diff = self.repo.diff_tree_to_workdir_with_index(Some(&tree), Some(&mut diff_opts))?;
+            if print_patch(&diff, &['+', '-', ' ', '=', '>', '<'], sink)? {
+                return Ok(());
+            }
+        }
+
+        let diff = self.repo.diff_index_to_workdir(None, Some(&mut diff_opts))?;
+        if print_patch(&diff, &['+', '-', ' ', '=', '>', '<', 'F', 'H'], sink)? {
+            return Ok(());
+        }
+
+        let head_tree = self.repo.head().ok().and_then(|h| h.peel_to_tree().ok());
+        let diff = self.repo.diff_tree_to_index(head_tree.as_ref(), None, Some(&mut diff_opts))?;
+        print_patch(&diff, &['+', '-', ' '], sink)?;
+        Ok(())
+    }
+
+    // whether every changed line of the file's diff (the one the diff pane shows) is the same on
+    // both sides once the CR/LF at its end is dropped, i.e. an editor or autocrlf rewrote the line
+    // endings and nothing else
+    pub fn eol_only_change(&self, file_path: &str) -> Result<bool> {
+        let mut diff_opts = DiffOptions::new();
+        diff_opts.pathspec(pathspec(file_path).as_ref());
+
+        let diff = self.repo.diff_index_to_workdir(None, Some(&mut diff_opts))?;
+        let diff = if diff.deltas().len() > 0 {
+            diff
+        } else {
+            let head_tree = self.repo.head().ok().and_then(|h| h.peel_to_tree().ok());
+            self.repo.diff_tree_to_index(head_tree.as_ref(), None, Some(&mut diff_opts))?
+        };
+
+        let mut removed = Vec::new();
+        let mut added = Vec::new();
+        let mut too_big = false;
+        let result = diff.foreach(&mut |_, _| true, None, None, Some(&mut |_, _, line| {
+            let content = line.content();
+            let content = content.strip_suffix(b"\n").unwrap_or(content);
+            let content = content.strip_suffix(b"\r").unwrap_or(content).to_vec();
+            match line.origin() {
+                '-' => removed.push(content),
+                '+' => added.push(content),
+                _ => {}
+            }
+            too_big = removed.len() + added.len() > EOL_CHECK_LIMIT;
+            !too_big
+        }));
+        if too_big {
+            return Ok(false);
+        }
+        result?;
+        Ok(!removed.is_empty() && removed == added)
+    }
+
+    // core.autocrlf / core.eol / .gitattributes combinations that tend to turn into whole files
+    // showing up as changed, most of them on windows
+    pub fn eol_config_problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let autocrlf = self.config_string("core.autocrlf").map(|v| v.to_lowercase());
+        let eol = self.config_string("core.eol");
+        let attribute_rules = self.read_gitattributes().unwrap_or_default().iter().any(|line| {
+            let line = line.trim();
+            !line.starts_with('#')
+                && line.split_whitespace().skip(1).any(|attr| {
+                    let attr = attr.trim_start_matches(['-', '!']);
+                    attr == "text" || attr.starts_with("text=") || attr.starts_with("eol=")
+                })
+        });
+
+        match autocrlf.as_deref() {
+            None | Some("false") | Some("input") | Some("true") => {}
+            Some(other) => problems.push(format!("core.autocrlf = {} isn't true, false or input, git treats it as true", other)),
+        }
+        let converts = autocrlf.as_deref().is_some_and(|v| v != "false");
+        if autocrlf.as_deref() == Some("true") && !cfg!(windows) {
+            problems.push("core.autocrlf = true checks files out with CRLF, outside windows that's usually input or unset".to_string());
+        }
+        if cfg!(windows) && !converts && !attribute_rules {
+            problems.push("core.autocrlf is off and .gitattributes sets no text/eol, CRLF from windows editors gets committed as is".to_string());
+        }
+        if let Some(eol) = &eol
+            && autocrlf.as_deref() == Some("true")
+        {
+            problems.push(format!("core.eol = {} is ignored while core.autocrlf = true", eol));
+        }
+        if converts && attribute_rules {
+            problems.push("the text/eol rules in .gitattributes win over core.autocrlf for the files they match".to_string());
+        }
+        problems
+    }
+
+    // staged: HEAD -> index, otherwise index -> workdir (untracked files included)
+    pub fn get_file_hunks(&self, file_path: &str, staged: bool) -> Result<FileDiff> {
+        let mut diff_opts = DiffOptions::new();
+        diff_opts.pathspec(pathspec(file_path).as_ref()).disable_pathspec_match(true);
+
+        let diff = if staged {
+            let head_tree = self.repo.head().ok().and_then(|h| h.peel_to_tree().ok());
+            self.repo.diff_tree_to_index(head_tree.as_ref(), None, Some(&mut diff_opts))?
+        } else {
+            diff_opts.include_untracked(true).show_untracked_content(true).recurse_untracked_dirs(true);
+            self.repo.diff_index_to_workdir(None, Some(&mut diff_opts))?
+        };
+
+        let patch = git2::Patch::from_diff(&diff, 0)?
+            .context("no changes in this file")?;
+        let delta = patch.delta();
+        if delta.flags().is_binary() {
+            anyhow::bail!("binary file, stage it as a whole");
+        }
+
+        let mut hunks = Vec::new();
+        for hunk_idx in 0..patch.num_hunks() {
+            let (hunk, line_count) = patch.hunk(hunk_idx)?;
+            let mut lines = Vec::with_capacity(line_count);
+            for line_idx in 0..line_count {
+                let line = patch.line_in_hunk(hunk_idx, line_idx)?;
+                // the eof-newline markers are rebuilt from the content itself
+                if matches!(line.origin(), '+' | '-' | ' ') {
+                    lines.push(DiffLine { origin: line.origin(), content: line.content().to_vec() });
+                }
+            }
+            hunks.push(DiffHunk {
+                header: String::from_utf8_lossy(hunk.header()).trim_end().to_string(),
+                old_start: hunk.old_start(),
+                new_start: hunk.new_start(),
+                lines,
+            });
+        }
+
+        Ok(FileDiff {
+            path: file_path.to_string(),
+            new_file: !staged && delta.status() == git2::Delta::Untracked,
+            mode: u32::from(delta.new_file().mode()),
+            hunks,
+        })
+    }
+
+    // stages the selected (hunk, line) pairs of an unstaged diff, or unstages them from a staged one
+    pub fn apply_line_selection(&self, file: &FileDiff, selected: &std::collections::HashSet<(usize, usize)>, staged: bool) -> Result<()> {
+        let patch = build_line_patch(file, selected, staged)
+            .context("no changed lines selected")?;
+
+        let diff = git2::Diff::from_buffer(&patch)?;
+        self.repo.apply(&diff, git2::ApplyLocation::Index, None)?;
+        Ok(())
+    }
+
+    // throws the selected lines of an unstaged diff out of the worktree, the reverse of staging
+    // them. the index keeps whatever was staged
+    pub fn discard_line_selection(&self, file: &FileDiff, selected: &std::collections::HashSet<(usize, usize)>) -> Result<()> {
+        if file.new_file {
+            anyhow::bail!("{} is untracked, there's no older version to go back to", file.path);
+        }
+        let patch = build_line_patch(file, selected, true)
+            .context("no changed lines selected")?;
+
+        let diff = git2::Diff::from_buffer(&patch)?;
+        self.repo.apply(&diff, git2::ApplyLocation::WorkDir, None)?;
+        Ok(())
+    }
+
+    pub fn stash_save(&mut self, message: Option<&str>, options: StashOptions) -> Result<git2::Oid> {
+        let signature = self.repo.signature()?;
+        let default_msg = "WIP on branch";
+        let stash_msg = message.unwrap_or(default_msg);
+        
+        let stash_id = self.repo.stash_save(&signature, stash_msg, Some(options.flags()))?;
+
+        Ok(stash_id)
+    }
+
+    // applies the stash and only drops it when that went through without conflicts
+    pub fn stash_pop(&mut self, index: usize) -> Result<StashPopResult>{
+        let stash_id = self.stash_id(index)?
+            .with_context(|| format!("stash@{{{}}} does not exist", index))?;
+
+        let mut checkout_opts = git2::build::CheckoutBuilder::new();
+        checkout_opts.allow_conflicts(true);
+        checkout_opts.conflict_style_merge(true);
+
+        let mut stash_apply_opts = git2::StashApplyOptions::new();
+        stash_apply_opts.checkout_options(checkout_opts);
+
+        self.repo.stash_apply(index, Some(&mut stash_apply_opts))?;
+
+        if self.repo.index()?.has_conflicts() {
+            return Ok(StashPopResult::Conflicted(stash_id));
+        }
+
+        self.repo.stash_drop(index)?;
+        Ok(StashPopResult::Popped)
+    }
+
+    fn stash_id(&mut self, index: usize) -> Result<Option<git2::Oid>> {
+        let mut found = None;
+        self.repo.stash_foreach(|i, _message, oid| {
+            if i == index {
+                found = Some(*oid);
+                return false;
+            }
+            true
+        })?;
+        Ok(found)
+    }
+
+    // stash indices shift around, so a stash kept for later is dropped by its id
+    pub fn stash_drop_by_id(&mut self, stash_id: git2::Oid) -> Result<bool> {
+        let mut index = None;
+        self.repo.stash_foreach(|i, _message, oid| {
+            if *oid == stash_id {
+                index = Some(i);
+                return false;
+            }
+            true
+        })?;
+
+        match index {
+            Some(i) => {
+                self.repo.stash_drop(i)?;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    pub fn stash_list(&mut self) -> Result<Vec<StashEntry>> {
+        let mut raw = Vec::new();
+        self.repo.stash_foreach(|index, message, oid| {
+            raw.push((index, message.to_string(), *oid));
+            true
+        })?;
+
+        let stashes = raw
+            .into_iter()
+            .map(|(index, message, oid)| {
+                let (branch, message) = parse_stash_message(&message);
+                let time = self.repo.find_commit(oid)
+                    .map(|c| commit_time(&c))
+                    .unwrap_or_default();
+                StashEntry { index, message, branch, oid, time }
+            })
+            .collect();
+        Ok(stashes)
+    }
+
+    // what a stash touched: its worktree commit against the commit it was made on, plus
+    // the untracked files commit (third parent) when it was stashed with -u
+    pub fn stash_files(&self, oid: git2::Oid) -> Result<Vec<StashFileStat>> {
+        let stash = self.repo.find_commit(oid)?;
+        let base = stash.parent(0)?;
+
+        let mut files = Vec::new();
+        let diff = self.repo.diff_tree_to_tree(Some(&base.tree()?), Some(&stash.tree()?), None)?;
+        collect_file_stats(&diff, &mut files)?;
+
+        if let Ok(untracked) = stash.parent(2) {
+            let diff = self.repo.diff_tree_to_tree(None, Some(&untracked.tree()?), None)?;
+            let start = files.len();
+            collect_file_stats(&diff, &mut files)?;
+            for file in &mut files[start..] {
+                file.status = '?';
+            }
+        }
+        Ok(files)
+    }
+
+    // a dry-run merge of the stash (its base, HEAD, the stash) for the conflicts, and the status
+    // of its files for what's in the way. files is what stash_files found
+    pub fn stash_preview(&self, oid: git2::Oid, files: &[StashFileStat]) -> Result<StashPreview> {
+        let stash = self.repo.find_commit(oid)?;
+        let mut preview = StashPreview::default();
+
+        for file in files {
+            let in_the_way = match file.status {
+                '?' => self.workdir().is_some_and(|w| w.join(&file.path).exists()),
+                _ => self.repo.status_file(Path::new(&file.path)).is_ok_and(|s| !s.is_empty() && !s.is_ignored()),
+            };
+            if in_the_way {
+                preview.in_the_way.push(file.path.clone());
+            }
+        }
+
+        if let Ok(head) = self.repo.head().and_then(|h| h.peel_to_tree()) {
+            let base = stash.parent(0)?.tree()?;
+            let index = self.repo.merge_trees(&base, &head, &stash.tree()?, None)?;
+            for conflict in index.conflicts()? {
+                let conflict = conflict?;
+                let entry = conflict.our.or(conflict.their).or(conflict.ancestor);
+                if let Some(entry) = entry {
+                    preview.conflicts.push(String::from_utf8_lossy(&entry.path).to_string());
+                }
+            }
+        }
+        Ok(preview)
+    }
+
+    pub fn stash_drop(&mut self, index: usize) -> Result<()> {
+        self.repo.stash_drop(index)?;
+        Ok(())
+    }
+
+    // gaahhhhh
+    // ssh agent, then ~/.ssh/id_rsa, then git's credential helper (libgit2's take on it, then the
+    // git binary's, see credential_fill)
+    fn credential_callbacks(&self) -> RemoteCallbacks<'_> {
+        let mut callbacks = RemoteCallbacks::new();
+
+        // whatever the server prints (hooks, "remote: ..."), often the only hint why a push got refused
+        callbacks.sideband_progress(|data| {
+            for line in String::from_utf8_lossy(data).lines().filter(|l| !l.trim().is_empty()) {
+                log::debug!("remote: {}", line.trim_end());
+            }
+            true
+        });
+
+        callbacks.credentials(|url, username_from_url, allowed_types| {
+            log::debug!("credentials asked for {} (user {:?}, allowed {:?})", url, username_from_url, allowed_types);
+            if url.starts_with("git@") || url.contains("ssh://") {
+                if let Ok(cred) = Cred::ssh_key_from_agent(username_from_url.unwrap_or("git")) {
+                    return Ok(cred);
+                }
+
+                if let Ok(cred) = Cred::ssh_key(
+                    username_from_url.unwrap_or("git"),
+                    None,
+                    &home_dir().unwrap_or_default().join(".ssh").join("id_rsa"),
+                    None,
+                ) {
+                    return Ok(cred);
+                }
+            }
+
+            if let Ok(cred) = Cred::credential_helper(&self.repo.config()?, url, username_from_url) {
+                return Ok(cred);
+            }
+            if allowed_types.contains(git2::CredentialType::USER_PASS_PLAINTEXT) {
+                // asked again, what the helper or the prompt gave didn't work
+                if self.filled_credential.borrow().is_some() {
+                    self.credential_request.replace(Some(url.to_string()));
+                    return Err(git2::Error::new(git2::ErrorCode::Auth, git2::ErrorClass::Http, format!("{} rejected the username/token", url)));
+                }
+                if let Some((_, username, password)) = self.prompted_credential.borrow().as_ref().filter(|(prompted, _, _)| prompted == url) {
+                    self.filled_credential.replace(Some(format!("url={}\nusername={}\npassword={}\n", url, username, password)));
+                    return Cred::userpass_plaintext(username, password);
+                }
+                match self.credential_fill(url, username_from_url) {
+                    Ok((username, password, filled)) => {
+                        self.filled_credential.replace(Some(filled));
+                        return Cred::userpass_plaintext(&username, &password);
+                    }
+                    Err(e) => log::debug!("git credential fill: {:#}", e),
+                }
+                if !allowed_types.contains(git2::CredentialType::DEFAULT) {
+                    self.credential_request.replace(Some(url.to_string()));
+                    return Err(git2::Error::new(git2::ErrorCode::Auth, git2::ErrorClass::Http, format!("no username/token for {}", url)));
+                }
+            }
+            if let Ok(cred) = Cred::default() {
+                return Ok(cred);
+            }
+
+            Cred::userpass_plaintext(username_from_url.unwrap_or(""), "")
+        });
+
+        callbacks
+    }
+
+    // git credential fill for `url`. the git binary reads every gitconfig git does, so this finds
+    // the Windows Credential Manager git for windows sets up in its system config even when
+    // libgit2 doesn't. it never prompts on the terminal, the tui owns it
+    fn credential_fill(&self, url: &str, username: Option<&str>) -> Result<(String, String, String)> {
+        let mut input = format!("url={}\n", url);
+        if let Some(username) = username {
+            input.push_str(&format!("username={}\n", username));
+        }
+        input.push('\n');
+        let filled = self.run_git_input(&self.credential_args("fill"), &input)?;
+        let field = |name: &str| {
+            filled.lines().find_map(|line| line.strip_prefix(name)?.strip_prefix('=')).map(|v| v.to_string())
+        };
+        let password = field("password").context("the credential helper had no password")?;
+        let username = field("username").unwrap_or_default();
+        Ok((username, password, filled))
+    }
+
+    // git credential approve / reject, so the helper stores what worked and drops what didn't
+    fn settle_credential<T>(&self, result: std::result::Result<T, git2::Error>) -> std::result::Result<T, git2::Error> {
+        if let Some(filled) = self.filled_credential.take() {
+            let action = match &result {
+                Ok(_) => Some("approve"),
+                Err(e) if e.code() == git2::ErrorCode::Auth => Some("reject"),
+                Err(_) => None,
+            };
+            if let Some(action) = action
+                && let Err(e) = self.run_git_input(&self.credential_args(action), &format!("{}\n", filled.trim_end()))
+            {
+                log::warn!("git credential {}: {:#}", action, e);
+            }
+            if action == Some("reject") {
+                self.prompted_credential.take();
+            }
+        }
+        result
+    }
+
+    // the credential.helpers git is set up with, plus the keychain from credentialStore when
+    // it isn't one of them already, so what gets typed into the prompt is there next time
+    fn credential_args(&self, action: &str) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(store) = self.credential_store()
+            && self.credential_helper_installed(&store)
+        {
+            let configured = self.run_git(&["config", "--get-all", "credential.helper"]).unwrap_or_default();
+            if !configured.lines().any(|helper| helper.trim() == store) {
+                args.extend(["-c".to_string(), format!("credential.helper={}", store)]);
+            }
+        }
+        args.extend(["credential".to_string(), action.to_string()]);
+        args
+    }
+
+    // hydra.credentialStore: unset for the system's keychain, none to opt out, or the name of
+    // any other credential helper
+    pub fn credential_store(&self) -> Option<String> {
+        match self.config_string("hydra.credentialStore").as_deref().map(str::trim) {
+            Some("none") | Some("") => None,
+            Some(helper) => Some(helper.to_string()),
+            None if cfg!(target_os = "macos") => Some("osxkeychain".to_string()),
+            None if cfg!(windows) => Some("manager".to_string()),
+            None => Some("libsecret".to_string()),
+        }
+    }
+
+    // git-credential-<name> in git's exec path or on PATH, git only complains on stderr when
+    // it's missing. anything with a path or arguments is taken as it is
+    pub fn credential_helper_installed(&self, helper: &str) -> bool {
+        if helper.contains(['/', '\\', ' ']) || helper.starts_with('!') {
+            return true;
+        }
+        let program = format!("git-credential-{}{}", helper, std::env::consts::EXE_SUFFIX);
+        let exec_path = self.run_git(&["--exec-path"]).map(|p| std::path::PathBuf::from(p.trim())).ok();
+        let path = std::env::var_os("PATH").unwrap_or_default();
+        exec_path.into_iter().chain(std::env::split_paths(&path)).any(|dir| dir.join(&program).is_file())
+    }
+
+    pub fn take_credential_request(&self) -> Option<String> {
+        self.credential_request.take()
+    }
+
+    // used for `url` from now on, and handed to the helpers once it works
+    pub fn set_prompted_credential(&self, url: &str, username: &str, token: &str) {
+        self.prompted_credential.replace(Some((url.to_string(), username.to_string(), token.to_string())));
+    }
+
+    // a partial clone (--filter=blob:none and the like): the promisor remote that has what was
+    // left out, and the filter
+    pub fn partial_clone(&self) -> Option<(String, String)> {
+        let remote = self.config_string("extensions.partialClone").or_else(|| {
+            self.get_remotes().ok()?.into_iter().find(|r| self.config_bool(&format!("remote.{}.promisor", r)))
+        })?;
+        let filter = self.config_string(&format!("remote.{}.partialCloneFilter", remote)).unwrap_or_default();
+        Some((remote, filter))
+    }
+
+    pub fn set_offline(&self, offline: bool) {
+        self.offline.set(offline);
+    }
+
+    // the ones of `ids` a partial clone doesn't have yet, fetched in one go with the git binary
+    // (libgit2 can't ask a promisor remote for them). what's still missing after that, offline
+    // or with the remote gone, comes back so it can be shown as not fetched. nothing outside a
+    // partial clone, a missing object there is an error like any other
+    pub fn fetch_missing(&self, ids: impl IntoIterator<Item = git2::Oid>) -> Result<std::collections::HashSet<git2::Oid>> {
+        let Some((remote, _)) = self.partial_clone() else {
+            return Ok(Default::default());
+        };
+        let odb = self.repo.odb()?;
+        let mut missing: std::collections::HashSet<git2::Oid> = ids.into_iter().filter(|id| !odb.exists(*id)).collect();
+        if missing.is_empty() || self.offline.get() {
+            return Ok(missing);
+        }
+        log::info!("fetching {} missing object(s) from {}", missing.len(), remote);
+        let ids: Vec<git2::Oid> = missing.iter().copied().collect();
+        match backend::fetch_objects(self, &remote, &ids) {
+            Ok(()) => {
+                odb.refresh()?;
+                self.forget_short_ids();
+                missing.retain(|id| !odb.exists(*id));
+            }
+            Err(e) => log::warn!("fetching missing objects from {} failed: {:#}", remote, e),
+        }
+        Ok(missing)
+    }
+
+    pub fn fetch_remote(&mut self, remote_name: &str) -> Result<String> {
+        log::info!("fetch {} ({})", remote_name, self.get_remote_url(remote_name)?.unwrap_or_else(|| "no url".to_string()));
+        // empty refspecs -> the remote's configured fetch refspecs
+        backend::choose(self, Operation::Fetch).fetch(self, remote_name, &[])?;
+        self.forget_short_ids();
+        Ok(format!("fetched {}", remote_name))
+    }
+
+    pub fn git2_fetch(&mut self, remote_name: &str, refspecs: &[String]) -> Result<()> {
+        let mut remote = self.repo.find_remote(remote_name)?;
+        let mut fetch_options = git2::FetchOptions::new();
+        fetch_options.remote_callbacks(self.credential_callbacks());
+        self.settle_credential(remote.fetch(refspecs, Some(&mut fetch_options), None))?;
+        Ok(())
+    }
+
+    // git add -A
+    pub fn stage_all(&self) -> Result<()> {
+        let mut index = self.repo.index()?;
+        index.add_all(["*"].iter(), git2::IndexAddOption::DEFAULT, None)?;
+        index.update_all(["*"].iter(), None)?;
+        index.write()?;
+        Ok(())
+    }
+
+    // a branch that's new to the remote sends whatever none of its remote-tracking refs have
+    pub fn push_preview(&self, remote_name: &str, branch_name: &str) -> Result<PushPreview> {
+        let local = self.repo.refname_to_id(&format!("refs/heads/{}", branch_name))
+            .with_context(|| format!("{} has no commits to push", branch_name))?;
+        let remote_tip = self.repo.refname_to_id(&format!("refs/remotes/{}/{}", remote_name, branch_name)).ok();
+
+        let mut revwalk = self.repo.revwalk()?;
+        revwalk.push(local)?;
+        match remote_tip {
+            Some(tip) => revwalk.hide(tip)?,
+            None => revwalk.hide_glob(&format!("refs/remotes/{}/*", remote_name))?,
+        }
+        revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
+        let mailmap = self.mailmap();
+        let mut commits = Vec::new();
+        let mut count = 0;
+        let mut oldest = None;
+        for oid in revwalk {
+            let oid = oid?;
+            count += 1;
+            oldest = Some(oid);
+            if commits.len() < PUSH_PREVIEW_LIMIT {
+                commits.push(to_commit(&self.repo.find_commit(oid)?, mailmap.as_ref()));
+            }
+        }
+
+        // the diffstat is from where the pushed commits start
+        let base = match remote_tip {
+            Some(tip) => Some(self.repo.merge_base(local, tip)?),
+            None => oldest.and_then(|oid| self.repo.find_commit(oid).ok()?.parent_id(0).ok()),
+        };
+        let base_tree = base.map(|oid| self.repo.find_commit(oid)?.tree()).transpose()?;
+        let diff = self.repo.diff_tree_to_tree(base_tree.as_ref(), Some(&self.repo.find_commit(local)?.tree()?), None)?;
+        let mut files = Vec::new();
+        if count > 0 {
+            collect_file_stats(&diff, &mut files)?;
+        }
+        let behind = match remote_tip {
+            Some(tip) => self.repo.graph_ahead_behind(local, tip)?.1,
+            None => 0,
+        };
+
+        Ok(PushPreview {
+            remote: remote_name.to_string(),
+            branch: branch_name.to_string(),
+            remote_tip: remote_tip.map(|oid| oid.to_string()),
+            commits,
+            count,
+            files,
+            behind,
+        })
+    }
+
+    // the commits a refspec push sends that the remote doesn't have yet, as far as its
+    // remote-tracking branches tell. deletes send nothing
+    pub fn outgoing_commits(&self, remote_name: &str, refspecs: &[PushRefspec]) -> Result<Vec<Commit>> {
+        let mut revwalk = self.repo.revwalk()?;
+        for src in refspecs.iter().filter_map(|r| r.src.as_deref()) {
+            if src.contains('*') {
+                revwalk.push_glob(src)?;
+            } else if let Ok(commit) = self.repo.revparse_single(src).and_then(|o| o.peel_to_commit()) {
+                // a tag of a tree or blob has no commits to send
+                revwalk.push(commit.id())?;
+            }
+        }
+        revwalk.hide_glob(&format!("refs/remotes/{}/*", remote_name))?;
+        revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
+        let mailmap = self.mailmap();
+        revwalk
+            .map(|oid| Ok(to_commit(&self.repo.find_commit(oid?)?, mailmap.as_ref())))
+            .collect()
+    }
+
+    // the lines each commit adds, checked against the secret rules. stops at FINDING_LIMIT
+    pub fn scan_for_secrets(&self, commits: &[Commit], rules: &[crate::secrets::Rule]) -> Result<Vec<crate::secrets::Finding>> {
+        let mut findings = Vec::new();
+        for commit in commits {
+            let commit = self.repo.find_commit(git2::Oid::from_str(&commit.id)?)?;
+            let parent_tree = commit.parent(0).ok().map(|p| p.tree()).transpose()?;
+            let diff = self.repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), None)?;
+            let result = diff.print(git2::DiffFormat::Patch, |delta, _, line| {
+                if line.origin() != '+' {
+                    return true;
+                }
+                let text = String::from_utf8_lossy(line.content());
+                if let Some((rule, text)) = crate::secrets::check(rules, text.trim_end()) {
+                    findings.push(crate::secrets::Finding {
+                        commit: commit.id().to_string(),
+                        path: delta.new_file().path().map(|p| p.to_string_lossy().to_string()).unwrap_or_default(),
+                        line: line.new_lineno().unwrap_or(0),
+                        rule,
+                        text,
+                    });
+                }
+                findings.len() < crate::secrets::FINDING_LIMIT
+            });
+            // stopping early shows up as an error
+            if findings.len() >= crate::secrets::FINDING_LIMIT {
+                break;
+            }
+            result?;
+        }
+        Ok(findings)
+    }
+
+    pub fn push_to_remote(&mut self, remote_name: &str, branch_name: &str) -> Result<String> {
+        let refspec = format!("refs/heads/{}:refs/heads/{}", branch_name, branch_name);
+        log::info!("push {} to {} ({})", refspec, remote_name, self.get_remote_url(remote_name)?.unwrap_or_else(|| "no url".to_string()));
+        backend::choose(self, Operation::Push).push(self, remote_name, &[refspec])?;
+        Ok("push completed successfully!".to_string())
+    }
+
+    // git ls-remote --heads: asks the remote without fetching anything
+    pub fn list_remote_branches(&self, remote_name: &str) -> Result<Vec<ListedBranch>> {
+        let mut remote = self.repo.find_remote(remote_name)?;
+        let connection = self
+            .settle_credential(remote.connect_auth(git2::Direction::Fetch, Some(self.credential_callbacks()), None))
+            .with_context(|| format!("cannot connect to {}", remote_name))?;
+
+        let mut branches: Vec<ListedBranch> = connection
+            .list()?
+            .iter()
+            .filter_map(|head| {
+                let name = head.name().strip_prefix("refs/heads/")?.to_string();
+                let fetched = self.repo.refname_to_id(&format!("refs/remotes/{}/{}", remote_name, name)).ok();
+                Some(ListedBranch { name, oid: head.oid(), fetched })
+            })
+            .collect();
+        branches.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(branches)
+    }
+
+    // fetch one branch into <remote>/<branch>, leaving everything else alone
+    pub fn fetch_branch(&mut self, remote_name: &str, branch: &str) -> Result<String> {
+        let refspec = format!("+refs/heads/{}:refs/remotes/{}/{}", branch, remote_name, branch);
+        backend::choose(self, Operation::Fetch).fetch(self, remote_name, &[refspec])?;
+        self.forget_short_ids();
+        Ok(format!("fetched {}/{}", remote_name, branch))
+    }
+
+    // git push shorthand: main, +main, main:other, :gone, v1.0, refs/tags/*, HEAD:refs/heads/x
+    pub fn parse_push_refspec(&self, text: &str) -> Result<PushRefspec> {
+        let (force, spec) = match text.strip_prefix('+') {
+            Some(rest) => (true, rest),
+            None => (false, text),
+        };
+        let (src, dst) = spec.split_once(':').unwrap_or((spec, spec));
+        if dst.is_empty() {
+            anyhow::bail!("'{}' has no destination", text);
+        }
+
+        let src = match src {
+            "" => None,
+            _ if src.starts_with("refs/") => Some(src.to_string()),
+            _ if self.local_branch_exists(src) => Some(format!("refs/heads/{}", src)),
+            _ if self.repo.refname_to_id(&format!("refs/tags/{}", src)).is_ok() => Some(format!("refs/tags/{}", src)),
+            _ if self.repo.revparse_single(src).is_ok() => Some(src.to_string()),
+            _ => anyhow::bail!("'{}' is not a branch, tag or commit here", src),
+        };
+
+        let dst = if dst.starts_with("refs/") {
+            dst.to_string()
+        } else if src.as_deref().is_some_and(|s| s.starts_with("refs/tags/")) {
+            format!("refs/tags/{}", dst)
+        } else {
+            format!("refs/heads/{}", dst)
+        };
+        if src.as_deref().is_some_and(|s| !s.starts_with("refs/")) && dst == format!("refs/heads/{}", spec) {
+            anyhow::bail!("pushing '{}' needs a destination, e.g. {}:refs/heads/<branch>", spec, spec);
+        }
+        Ok(PushRefspec { force, src, dst })
+    }
+
+    // every refspec in one push, refused updates (non fast-forward, protected...) make it fail
+    pub fn push_refspecs(&mut self, remote_name: &str, refspecs: &[PushRefspec]) -> Result<String> {
+        let specs: Vec<String> = refspecs.iter().map(|r| r.spec()).collect();
+        log::info!("push {} to {} ({})", specs.join(" "), remote_name, self.get_remote_url(remote_name)?.unwrap_or_else(|| "no url".to_string()));
+        backend::choose(self, Operation::Push).push(self, remote_name, &specs)?;
+        Ok(format!("pushed {} ref(s) to {}", specs.len(), remote_name))
+    }
+
+    pub fn git2_push(&mut self, remote_name: &str, specs: &[String]) -> Result<()> {
+        let mut remote = self.repo.find_remote(remote_name)?;
+        let rejected = std::cell::RefCell::new(Vec::new());
+        let mut callbacks = self.credential_callbacks();
+        callbacks.push_update_reference(|refname, status| {
+            if let Some(message) = status {
+                log::warn!("{} refused {}: {}", remote_name, refname, message);
+                rejected.borrow_mut().push(format!("{}: {}", refname, message));
+            }
+            Ok(())
+        });
+        let mut push_options = PushOptions::new();
+        push_options.remote_callbacks(callbacks);
+        self.settle_credential(remote.push(specs, Some(&mut push_options)))?;
+        drop(push_options);
+
+        let rejected = rejected.into_inner();
+        if !rejected.is_empty() {
+            anyhow::bail!("{} refused {}", remote_name, rejected.join(", "));
+        }
+        Ok(())
+    }
+
+    pub fn pull_from_remote(&mut self, remote_name: &str, branch_name: &str) -> Result<String> {
+        log::info!("pull {} from {} ({})", branch_name, remote_name, self.get_remote_url(remote_name)?.unwrap_or_else(|| "no url".to_string()));
+        let pulled = backend::choose(self, Operation::Pull).pull(self, remote_name, branch_name)?;
+        self.forget_short_ids();
+        Ok(pulled)
+    }
+
+    pub fn git2_pull(&mut self, remote_name: &str, branch_name: &str) -> Result<String> {
+        let mut remote = self.repo.find_remote(remote_name)?;
+
+        let mut callbacks = self.credential_callbacks();
+
+        callbacks.transfer_progress(|_stats| {
+            /*if stats.received_objects() == stats.total_objects(){
+                print!("resolving deltas {}/{} \r", stats.indexed_deltas(), stats.total_deltas());
+            } else if stats.total_objects() > 0 {
+                (100 * stats.received_objects() / stats.total_objects(),
+                stats.received_objects(),
+                stats.total_objects(),
+                stats.received_bytes());
+            }
+            io::stdout().flush().unwrap();
+            true*/
+            true
+        });
+
+        let mut fetch_options = git2::FetchOptions::new();
+        fetch_options.remote_callbacks(callbacks);
+
+        let refspec = format!("+refs/heads/{}:refs/remotes/{}/{}", branch_name, remote_name, branch_name);
+        self.settle_credential(remote.fetch(&[&refspec], Some(&mut fetch_options), None))?;
+
+        let remote_branch_name = format!("refs/remotes/{}/{}", remote_name, branch_name);
+        let remote_ref = self.repo.find_reference(&remote_branch_name)?;
+        let remote_oid = remote_ref.target().context("failed to get remote OID")?;
+        // let remote_commit = self.repo.find_commit(remote_oid)?;
+        let remote_annotated = self.repo.find_annotated_commit(remote_oid)?;
+        let annotated_commits = vec![&remote_annotated];
+        
+        
+        let analysis = self.repo.merge_analysis(&annotated_commits)?;
+        log::debug!("pull merge analysis for {}: {:?}", remote_oid, analysis.0);
+        
+        if analysis.0.is_fast_forward() {
+            let head = self.repo.head()?;
+            if let Some(branch_ref_name) = head.name() {
+                let mut branch_ref = self.repo.find_reference(branch_ref_name)?;
+                branch_ref.set_target(remote_oid, "Fast-forward")?;
+            } else {
+                let mut head_ref = self.repo.find_reference("HEAD")?;
+                head_ref.set_target(remote_oid, "Fast-forward")?;
+            }
+            
+            // Checkout the updated files
+            self.repo.checkout_head(Some(
+                git2::build::CheckoutBuilder::default()
+                    .allow_conflicts(true)
+                    .conflict_style_merge(true)
+                    .force()
+            ))?;
+            //println!("\nfast-forward merge completed!");
+            return Ok("Fast-forward merge completed!".to_string());
+        } else if analysis.0.is_normal() {
+            // get on your big boy seats
+            let mut merge_opts = git2::MergeOptions::new();
+            merge_opts.file_favor(git2::FileFavor::Normal);
+
+            let mut checkout_opts = git2::build::CheckoutBuilder::new();
+            checkout_opts.allow_conflicts(true);
+            checkout_opts.conflict_style_merge(true);
+
+            self.repo.merge(&annotated_commits, Some(&mut merge_opts), Some(&mut checkout_opts))?;
+            let mut index = self.repo.index()?;
+
+            if index.has_conflicts() {
+                let paths: Vec<String> = index.conflicts()?
+                    .filter_map(|c| c.ok()?.our.map(|e| String::from_utf8_lossy(&e.path).to_string()))
+                    .collect();
+                log::warn!("pull of {} from {} stopped on conflicts in {}", branch_name, remote_name, paths.join(", "));
+                //println!("\nmerge conflicts detected! please resolve them.");
+                return Err(anyhow::anyhow!("merge conflicts require resolution!"));
+            } else {
+                let signature = self.repo.signature()?;
+                let tree_id = index.write_tree()?;
+                let tree = self.repo.find_tree(tree_id)?;
+
+                let head_commit = self.repo.head()?.peel_to_commit()?;
+                let merge_commit = self.repo.find_commit(remote_oid)?;
+                let parents = vec![&head_commit, &merge_commit];
+
+                self.repo.commit(
+                    Some("HEAD"),
+                    &signature,
+                    &signature,
+                    format!("merge branch {} of {}", branch_name, remote_name).as_str(),
+                    &tree,
+                    &parents,
+                )?;
+                return Ok(format!("Merge completed: {} from {}", branch_name, remote_name));
+            }
+        } else if analysis.0.is_up_to_date() {
+            return Ok("already up to date!".to_string());
+        } else {
+            return Err(anyhow::anyhow!("cannot merge - conflicting changes detected!"));
+        }
+        
+    }
+
+    pub fn get_remotes(&self) -> Result<Vec<String>>{
+        let remotes = self.repo.remotes()?;
+        Ok(remotes.iter()
+            .filter_map(|r| r.map(|s| s.to_string()))
+            .collect())
+    }
+
+    pub fn get_remote_url(&self, remote_name: &str) -> Result<Option<String>>{
+        let remote = self.repo.find_remote(remote_name)?;
+        Ok(remote.url().map(|s| s.to_string()))
+    }
+
+    // git archive, more or less: writes the tree of `rev` into a tar or zip under `prefix/`
+    pub fn archive_commit(&self, rev: &str, prefix: &str, out_path: &Path) -> Result<usize> {
+        use crate::archive::{write_archive, ArchiveEntry, ArchiveFormat};
+
+        let commit = self.repo.revparse_single(rev)?.peel_to_commit()?;
+        let tree = commit.tree()?;
+
+        // a partial clone gets what it doesn't have in one go first
+        if self.partial_clone().is_some() {
+            let mut ids = Vec::new();
+            tree.walk(git2::TreeWalkMode::PreOrder, |_, entry| {
+                if entry.kind() == Some(git2::ObjectType::Blob) {
+                    ids.push(entry.id());
+                }
+                git2::TreeWalkResult::Ok
+            })?;
+            let missing = self.fetch_missing(ids)?;
+            if !missing.is_empty() {
+                anyhow::bail!("{} file(s) aren't in this partial clone and couldn't be fetched from its promisor remote", missing.len());
+            }
+        }
+
+        let mut entries = Vec::new();
+        let mut walk_error = None;
+        tree.walk(git2::TreeWalkMode::PreOrder, |root, entry| {
+            if entry.kind() != Some(git2::ObjectType::Blob) {
+                return git2::TreeWalkResult::Ok;
+            }
+            let name = entry.name().unwrap_or("");
+            match self.repo.find_blob(entry.id()) {
+                Ok(blob) => entries.push(ArchiveEntry {
+                    path: format!("{}{}{}", prefix, root, name),
+                    data: blob.content().to_vec(),
+                    executable: entry.filemode() == 0o100755,
+                    symlink: entry.filemode() == 0o120000,
+                }),
+                Err(e) => {
+                    walk_error = Some(e);
+                    return git2::TreeWalkResult::Abort;
+                }
+            }
+            git2::TreeWalkResult::Ok
+        })?;
+        if let Some(e) = walk_error {
+            return Err(e.into());
+        }
+
+        let format = ArchiveFormat::from_path(&out_path.to_string_lossy());
+        let mut file = io::BufWriter::new(fs::File::create(out_path)
+            .with_context(|| format!("failed to create {}", out_path.display()))?);
+        // no half-written archive left behind
+        if let Err(e) = write_archive(format, &entries, commit.time().seconds(), &mut file).and_then(|_| Ok(file.flush()?)) {
+            drop(file);
+            let _ = fs::remove_file(out_path);
+            return Err(e);
+        }
+
+        Ok(entries.len())
+    }
+
+    pub fn git_dir(&self) -> &Path {
+        self.repo.path()
+    }
+
+    pub fn workdir(&self) -> Option<&Path> {
+        self.repo.workdir()
+    }
+
+    // core.hooksPath (relative to the top of the working tree, like git does) or .git/hooks
+    pub fn hooks_dir(&self) -> (std::path::PathBuf, bool) {
+        let configured = self.repo.config().ok().and_then(|c| c.get_path("core.hooksPath").ok());
+        match configured {
+            Some(dir) if dir.is_absolute() => (dir, true),
+            Some(dir) => (self.repo.workdir().unwrap_or(self.repo.path()).join(dir), true),
+            None => (self.repo.path().join("hooks"), false),
+        }
+    }
+
+    pub fn list_hooks(&self) -> Result<Vec<Hook>> {
+        let (dir, _) = self.hooks_dir();
+        let mut hooks: Vec<Hook> = Vec::new();
+        let entries = match fs::read_dir(&dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(hooks),
+            Err(e) => return Err(e).with_context(|| format!("cannot read {}", dir.display())),
+        };
+        for entry in entries {
+            let entry = entry?;
+            if !entry.file_type()?.is_file() {
+                continue;
+            }
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let (name, enabled) = match file_name.strip_suffix(".sample") {
+                Some(name) => (name.to_string(), false),
+                None => (file_name, true),
+            };
+            // an enabled hook hides its sample
+            if let Some(existing) = hooks.iter_mut().find(|h| h.name == name) {
+                if enabled {
+                    *existing = Hook { name, enabled, executable: is_executable(&entry.path()), path: entry.path() };
+                }
+                continue;
+            }
+            hooks.push(Hook { name, enabled, executable: is_executable(&entry.path()), path: entry.path() });
+        }
+        hooks.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(hooks)
+    }
+
+    // enabling drops the .sample and makes it executable, disabling puts the .sample back
+    pub fn set_hook_enabled(&self, hook: &Hook, enabled: bool) -> Result<()> {
+        let (dir, _) = self.hooks_dir();
+        let active = dir.join(&hook.name);
+        let sample = dir.join(format!("{}.sample", hook.name));
+        if enabled {
+            if !hook.enabled {
+                if active.exists() {
+                    anyhow::bail!("{} is already there next to the sample", active.display());
+                }
+                fs::rename(&sample, &active)?;
+            }
+            set_executable(&active)?;
+        } else if hook.enabled {
+            if sample.exists() {
+                anyhow::bail!("{} already exists, remove it to disable {} this way", sample.display(), hook.name);
+            }
+            fs::rename(&active, &sample)?;
+        }
+        Ok(())
+    }
+
+    // runs the hook the way git would (from the top of the working tree) with made up arguments
+    // where it expects some, output and exit status for the user to look at
+    pub fn run_hook(&self, hook: &Hook) -> Result<(std::process::ExitStatus, String)> {
+        let mut cmd = std::process::Command::new(&hook.path);
+        if let Some(workdir) = self.repo.workdir() {
+            cmd.current_dir(workdir);
+        }
+        cmd.env("GIT_DIR", self.repo.path());
+
+        let message_file = self.repo.path().join("HYDRA_HOOK_TEST_MSG");
+        match hook.name.as_str() {
+            "commit-msg" | "prepare-commit-msg" | "applypatch-msg" => {
+                fs::write(&message_file, "hook test message\n")?;
+                cmd.arg(&message_file);
+            }
+            "pre-push" => {
+                let url = self.repo.find_remote("origin").ok().and_then(|r| r.url().map(|u| u.to_string())).unwrap_or_default();
+                cmd.args(["origin", &url]);
+            }
+            _ => {}
+        }
+
+        let output = cmd.stdin(std::process::Stdio::null()).output();
+        let _ = fs::remove_file(&message_file);
+        let output = output.with_context(|| format!("cannot run {}", hook.path.display()))?;
+
+        let mut text = String::from_utf8_lossy(&output.stdout).to_string();
+        text.push_str(&String::from_utf8_lossy(&output.stderr));
+        Ok((output.status, text))
+    }
+
+    // browser url for the repo (or a commit on it), derived from origin or the first remote
+    pub fn get_web_url(&self, commit_id: Option<&str>) -> Result<String> {
+        let remotes = self.get_remotes()?;
+        let remote_name = if remotes.iter().any(|r| r == "origin") {
+            "origin".to_string()
+        } else {
+            remotes.first().cloned().context("no remotes configured.")?
+        };
+
+        let url = self.get_remote_url(&remote_name)?
+            .context("remote has no url.")?;
+        let base = remote_to_web_url(&url)
+            .with_context(|| format!("cannot derive a web url from '{}'", url))?;
+
+        Ok(match commit_id {
+            Some(id) if base.contains("gitlab") => format!("{}/-/commit/{}", base, id),
+            Some(id) if base.contains("bitbucket") => format!("{}/commits/{}", base, id),
+            Some(id) => format!("{}/commit/{}", base, id),
+            None => base,
+        })
+    }
+
+    // the id cut to core.abbrev characters (8 unless it says otherwise, "no" for all 40), longer
+    // when that many would match another object too, like git does
+    pub fn short_id(&self, id: impl fmt::Display) -> String {
+        let id = id.to_string();
+        if let Some(short) = self.short_ids.borrow().get(&id) {
+            return short.clone();
+        }
+        let mut len = self.abbrev_len().min(id.len());
+        if let (Ok(oid), Ok(odb)) = (git2::Oid::from_str(&id), self.repo.odb()) {
+            while len < id.len() && odb.exists_prefix(oid, len).is_err_and(|e| e.code() == git2::ErrorCode::Ambiguous) {
+                len += 1;
+            }
+        }
+        let short: String = id.chars().take(len).collect();
+        self.short_ids.borrow_mut().insert(id, short.clone());
+        short
+    }
+
+    // new objects can make a prefix that was unique ambiguous, after a fetch the ids are worked
+    // out again
+    pub fn forget_short_ids(&self) {
+        self.short_ids.borrow_mut().clear();
+    }
+
+    fn abbrev_len(&self) -> usize {
+        match self.config_string("core.abbrev").as_deref().map(str::trim) {
+            Some("no") | Some("false") => 40,
+            Some(len) => len.parse::<usize>().map_or(DEFAULT_ABBREV, |len| len.clamp(4, 40)),
+            None => DEFAULT_ABBREV,
+        }
+    }
+
+    pub fn config_bool(&self, name: &str) -> bool {
+        self.repo.config().ok().and_then(|c| c.get_bool(name).ok()).unwrap_or(false)
+    }
+
+    pub fn set_config_bool(&self, name: &str, value: bool) -> Result<()> {
+        self.repo.config()?.open_level(git2::ConfigLevel::Local)?.set_bool(name, value)?;
+        Ok(())
+    }
+
+    pub fn config_string(&self, name: &str) -> Option<String> {
+        self.repo.config().ok()?.get_string(name).ok()
+    }
+
+    // every value of a multi-valued key, in config order
+    pub fn config_values(&self, name: &str) -> Vec<String> {
+        let Ok(config) = self.repo.config() else {
+            return Vec::new();
+        };
+        let mut values = Vec::new();
+        if let Ok(mut entries) = config.multivar(name, None) {
+            while let Some(Ok(entry)) = entries.next() {
+                if let Some(value) = entry.value() {
+                    values.push(value.to_string());
+                }
+            }
+        }
+        values
+    }
+
+    // issue link rules from hydra.issueLink / hydra.issueUrl, plus any that didn't parse
+    pub fn issue_rules(&self) -> (Vec<IssueRule>, Vec<String>) {
+        let web_url = self.get_web_url(None).ok();
+        crate::issues::load_rules(
+            &self.config_values("hydra.issueLink"),
+            self.config_string("hydra.issueUrl").as_deref(),
+            web_url.as_deref(),
+        )
+    }
+
+    pub fn get_current_branch(&self) -> Result<String>{
+        let head = match self.repo.head() {
+            Ok(head) => head,
+            // empty repo: HEAD still names the branch the first commit will create
+            Err(e) if e.code() == git2::ErrorCode::UnbornBranch => return Ok(self.unborn_branch().unwrap_or_else(|| "HEAD".to_string())),
+            Err(e) => return Err(e.into()),
+        };
+        if let Some(name) = head.shorthand(){
+            Ok(name.to_string())
+        } else {
+            Ok("HEAD".to_string())
+        }
+    }
+
+    // the branch HEAD points at before it has any commits
+    pub fn unborn_branch(&self) -> Option<String> {
+        if self.has_commits() {
+            return None;
+        }
+        let head = self.repo.find_reference("HEAD").ok()?;
+        let target = head.symbolic_target()?;
+        Some(target.strip_prefix("refs/heads/").unwrap_or(target).to_string())
+    }
+
+    pub fn detect_merge_conflicts(&self) -> Result<Option<MergeConflict>> {
+        let index = self.repo.index()?;
+
+        // a conflicted index with no operation in progress is what a stash apply leaves behind
+        let operation = match ConflictOperation::from_state(self.repo.state()) {
+            Some(operation) => operation,
+            None if index.has_conflicts() => ConflictOperation::Stash,
+            None => return Ok(None),
+        };
+        let mut conflicted_files = Vec::new();
+
+        // the index knows about every kind of conflict, the markers in the file only about text ones
+        for conflict in index.conflicts()? {
+            let conflict = conflict?;
+            let Some(entry) = conflict.our.as_ref()
+                .or(conflict.their.as_ref())
+                .or(conflict.ancestor.as_ref()) else {
+                continue;
+            };
+            let path = String::from_utf8_lossy(&entry.path).to_string();
+            let our_id = conflict.our.as_ref().map(|e| e.id);
+            let their_id = conflict.their.as_ref().map(|e| e.id);
+
+            let mut kind = match (our_id, their_id) {
+                (None, _) => ConflictKind::DeletedByUs,
+                (_, None) => ConflictKind::DeletedByThem,
+                (Some(ours), Some(theirs)) if self.is_binary_blob(ours) || self.is_binary_blob(theirs) => ConflictKind::Binary,
+                _ => ConflictKind::Text,
+            };
+
+            let parsed = if kind == ConflictKind::Text {
+                match self.parse_conflicted_file(&path) {
+                    Ok(conflicts) if !conflicts.is_empty() => Some(conflicts),
+                    // no markers left, it's been fixed by hand and only needs staging
+                    Ok(_) if !self.has_conflict_markers(&path) => continue,
+                    _ => {
+                        kind = ConflictKind::Unparsed;
+                        None
+                    }
+                }
+            } else {
+                None
+            };
+
+            let conflicts = if let Some(conflicts) = parsed {
+                conflicts
+            } else {
+                // one pseudo-hunk for the whole file so it can be resolved like any other
+                vec![ConflictHunk {
+                    start_line: 0,
+                    end_line: 0,
+                    our_content: self.describe_conflict_side(our_id, "deleted in our branch"),
+                    their_content: self.describe_conflict_side(their_id, "deleted in their branch"),
+                    base_content: None,
+                }]
+            };
+
+            conflicted_files.push(ConflictedFile {
+                path,
+                kind,
+                conflicts,
+                our_id,
+                their_id,
+            });
+        }
+
+        if conflicted_files.is_empty() {
+            return Ok(None);
+        }
+
+        let our_commit = self.repo.head()?.target()
+            .context("Failed to get HEAD")?
+            .to_string();
+
+        let their_commit = operation.head_files()
+            .iter()
+            .find_map(|name| fs::read_to_string(self.repo.path().join(name)).ok())
+            .map(|id| id.trim().to_string())
+            .filter(|id| id.len() >= 8)
+            .unwrap_or_else(|| our_commit.clone());
+        
+        Ok(Some(MergeConflict { operation, files: conflicted_files, our_commit, their_commit }))
+    }
+
+    // whether any line still starts a marker, read as bytes so a non-utf8 file counts too
+    fn has_conflict_markers(&self, path: &str) -> bool {
+        let Some(workdir) = self.repo.workdir() else {
+            return false;
+        };
+        match fs::read(workdir.join(path)) {
+            Ok(content) => content.split(|&b| b == b'\n').any(|l| l.starts_with(b"<<<<<<<") || l.starts_with(b">>>>>>>")),
+            Err(_) => false,
+        }
+    }
+
+    fn is_binary_blob(&self, id: git2::Oid) -> bool {
+        self.repo.find_blob(id).map(|b| b.is_binary()).unwrap_or(false)
+    }
+
+    fn describe_conflict_side(&self, id: Option<git2::Oid>, deleted: &str) -> String {
+        match id.and_then(|id| self.repo.find_blob(id).ok()) {
+            Some(blob) if blob.is_binary() => format!("binary file, {} bytes ({})", blob.size(), self.short_id(blob.id())),
+            Some(blob) => String::from_utf8_lossy(blob.content()).to_string(),
+            None => deleted.to_string(),
+        }
+    }
+
+    // what the conflicted file would look like with the current resolutions applied
+    pub fn preview_resolved_file(
+        &self,
+        conflicted_file: &ConflictedFile,
+        file_idx: usize,
+        conflict_resolutions: &std::collections::HashMap<(usize, usize), MergeResolution>,
+    ) -> Result<ResolvedFile> {
+        let whole_file = conflict_resolutions.get(&(file_idx, 0)).is_some_and(|r| r.is_whole_file());
+        if conflicted_file.kind != ConflictKind::Text || whole_file {
+            let hunk = &conflicted_file.conflicts[0];
+            let text = match conflict_resolutions.get(&(file_idx, 0)) {
+                Some(MergeResolution::Delete) => "(file will be deleted)".to_string(),
+                Some(MergeResolution::OursFile) => self.describe_conflict_side(conflicted_file.our_id, "deleted in our branch"),
+                Some(MergeResolution::TheirsFile) => self.describe_conflict_side(conflicted_file.their_id, "deleted in their branch"),
+                Some(resolution) => hunk.resolve(resolution),
+                None => format!("unresolved {} conflict", conflicted_file.kind.label()),
+            };
+            let from_resolution = conflict_resolutions.contains_key(&(file_idx, 0));
+            return Ok(ResolvedFile {
+                lines: text.lines()
+                    .map(|l| ResolvedLine { text: l.to_string(), from_resolution })
+                    .collect(),
+                hunk_starts: vec![0],
+            });
+        }
+
+        let repo_workdir = self.repo.workdir()
+            .context("repository has no working directory.")?;
+        let content = fs::read_to_string(repo_workdir.join(&conflicted_file.path))
+            .context("failed to read conflicted file.")?;
+
+        Ok(apply_resolutions(&content, conflicted_file, file_idx, conflict_resolutions))
+    }
+
+    // non-text conflicts (and text ones taking a whole file) are resolved by writing one side's
+    // blob from the index, or by dropping the file
+    fn resolve_whole_file(&self, file: &ConflictedFile, resolution: &MergeResolution) -> Result<()> {
+        let repo_workdir = self.repo.workdir()
+            .context("repository has no working directory.")?;
+        let full_path = repo_workdir.join(&file.path);
+
+        let keep = match resolution {
+            MergeResolution::KeepOurs | MergeResolution::OursFile => file.our_id,
+            MergeResolution::KeepTheirs | MergeResolution::TheirsFile => file.their_id,
+            MergeResolution::Delete => None,
+            _ => anyhow::bail!("{} can only take ours, theirs, or be deleted", file.path),
+        };
+
+        let mut index = self.repo.index()?;
+        match keep {
+            Some(id) => {
+                let blob = self.repo.find_blob(id)?;
+                if let Some(parent) = full_path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(&full_path, blob.content())?;
+                index.add_path(Path::new(&file.path))?;
+            }
+            None => {
+                if full_path.exists() {
+                    fs::remove_file(&full_path)?;
+                }
+                index.remove_path(Path::new(&file.path))?;
+            }
+        }
+        index.write()?;
+        Ok(())
+    }
+
+    // after a mergetool: a text conflict with every marker gone is resolved, so it's staged like
+    // `git add` would. true when the file is no longer conflicted, staged here or by the tool
+    pub fn stage_if_resolved(&self, path: &str) -> Result<bool> {
+        let index = self.repo.index()?;
+        let conflicted = index.conflicts()?.filter_map(|c| c.ok()).any(|c| {
+            [c.our, c.their, c.ancestor].into_iter().flatten().any(|e| e.path == path.as_bytes())
+        });
+        if !conflicted {
+            return Ok(true);
+        }
+        let exists = self.workdir().is_some_and(|w| w.join(path).is_file());
+        if !exists || !self.parse_conflicted_file(path)?.is_empty() {
+            return Ok(false);
+        }
+        self.stage_file(path)?;
+        Ok(true)
+    }
+
+    fn parse_conflicted_file(&self, file_path: &str) -> Result<Vec<ConflictHunk>> {
+        let repo_workdir = self.repo.workdir()
+            .context("repository has no working directory.")?;
+        let full_path = repo_workdir.join(file_path);
+
+        let content = fs::read_to_string(&full_path)
+            .context("failed to read conflicted file.")?;
+
+        let mut conflicts = Vec::new();
+        let lines: Vec<&str> = content.lines().collect();
+        let mut i = 0;
+
+        while i < lines.len() {
+            if lines[i].starts_with("<<<<<<<") {
+                let start_line = i;
+                let mut our_content = String::new();
+                let mut their_content = String::new();
+                let mut base_content = None;
+
+                i += 1;
+
+                while i < lines.len() && !lines[i].starts_with("=======") && !lines[i].starts_with("|||||||") {
+                    if !our_content.is_empty() {
+                        our_content.push('\n');
+                    }
+                    our_content.push_str(lines[i]);
+                    i += 1;
+                }
+
+                if i < lines.len() && lines[i].starts_with("|||||||") {
+                    i += 1; // Skip the base marker
+                    let mut base = String::new();
+                    
+                    while i < lines.len() && !lines[i].starts_with("=======") {
+                        if !base.is_empty() {
+                            base.push('\n');
+                        }
+                        base.push_str(lines[i]);
+                        i += 1;
+                    }
+                    base_content = Some(base);
+                }
+
+                if i < lines.len() && lines[i].starts_with("======="){
+                    i += 1;
+                }
+
+                while i < lines.len() && !lines[i].starts_with(">>>>>>>") {
+                    if !their_content.is_empty() {
+                        their_content.push('\n');
+                    }
+                    their_content.push_str(lines[i]);
+                    i += 1;
+                }
+                
+                if i < lines.len() && lines[i].starts_with(">>>>>>>") {
+                    let end_line = i;
+                    i += 1;
+                    
+                    conflicts.push(ConflictHunk {
+                        start_line,
+                        end_line,
+                        our_content,
+                        their_content,
+                        base_content,
+                    });
+                }
+            } else {
+                i += 1;
+            }
+        }
+
+        Ok(conflicts)
+    }
+
+    pub fn resolve_conflicts(&mut self, conflict_resolutions: &std::collections::HashMap<(usize, usize), MergeResolution>, merge_conflict: &MergeConflict) -> Result<()> {
+        let repo_workdir = self.repo.workdir()
+            .context("repository has no working directory.")?;
+
+        for (file_idx, conflicted_file) in merge_conflict.files.iter().enumerate() {
+            let whole_file = conflict_resolutions.get(&(file_idx, 0)).is_some_and(|r| r.is_whole_file());
+            if conflicted_file.kind != ConflictKind::Text || whole_file {
+                if let Some(resolution) = conflict_resolutions.get(&(file_idx, 0)) {
+                    self.resolve_whole_file(conflicted_file, resolution)?;
+                }
+                continue;
+            }
+
+            let full_path = repo_workdir.join(&conflicted_file.path);
+            let original_content = fs::read_to_string(&full_path)?;
+
+            let resolved = apply_resolutions(&original_content, conflicted_file, file_idx, conflict_resolutions);
+            let mut resolved_content = resolved.lines
+                .into_iter()
+                .map(|line| line.text)
+                .collect::<Vec<_>>()
+                .join("\n");
+            if original_content.ends_with('\n') {
+                resolved_content.push('\n');
+            }
+            fs::write(&full_path, resolved_content)?;
+
+            self.stage_file(&conflicted_file.path)?;
+        }
+        Ok(())
+    }
+
+    pub fn complete_merge(&mut self, message: &str) -> Result<()> {
+        let signature = self.repo.signature()?;
+        let mut index = self.repo.index()?;
+        let tree_id = index.write_tree()?;
+        let tree = self.repo.find_tree(tree_id)?;
+
+        let head_commit = self.repo.head()?.peel_to_commit()?;
+        
+        let merge_head_path = self.repo.path().join("MERGE_HEAD");
+        let merge_head_oid = git2::Oid::from_str(std::fs::read_to_string(merge_head_path)?.trim())?;
+        let merge_commit = self.repo.find_commit(merge_head_oid)?;
+        log::info!("committing merge of {} into {}", merge_head_oid, head_commit.id());
+
+        let parents = vec![&head_commit, &merge_commit];
+
+        self.repo.commit(
+            Some("HEAD"),
+            &signature,
+            &signature,
+            message,
+            &tree,
+            &parents,
+        )?;
+
+        self.repo.cleanup_state()?;
+        Ok(())
+    }
+
+    // finish whatever operation stopped on conflicts, once they're resolved and staged
+    pub fn continue_operation(&mut self, operation: ConflictOperation, message: &str) -> Result<()> {
+        match operation {
+            ConflictOperation::Merge => self.complete_merge(message),
+            ConflictOperation::CherryPick | ConflictOperation::Revert => {
+                // libgit2 has no sequencer, git commits with whatever is in MERGE_MSG
+                fs::write(self.repo.path().join("MERGE_MSG"), message)?;
+                self.run_git(&[operation.label(), "--continue"])?;
+                Ok(())
+            }
+            ConflictOperation::Rebase => {
+                self.run_git(&["rebase", "--continue"])?;
+                Ok(())
+            }
+            // nothing to commit, resolving and staging the files was the whole job
+            ConflictOperation::Stash => Ok(()),
+        }
+    }
+
+    pub fn abort_operation(&mut self, operation: ConflictOperation) -> Result<()> {
+        match operation {
+            ConflictOperation::Merge => self.abort_merge(),
+            // undo the conflicted paths but leave unrelated local changes alone, the stash is still there
+            ConflictOperation::Stash => {
+                self.run_git(&["reset", "--merge"])?;
+                Ok(())
+            }
+            _ => {
+                self.run_git(&[operation.label(), "--abort"])?;
+                Ok(())
+            }
+        }
+    }
+
+    // libgit2 can't read or write bundles, so these go through the git binary
+    pub fn create_bundle(&self, bundle_path: &Path, refs: &[&str]) -> Result<String> {
+        let bundle_path = std::path::absolute(bundle_path)?;
+        let mut args = vec!["bundle", "create"];
+        let path_str = bundle_path.to_string_lossy().to_string();
+        args.push(&path_str);
+        if refs.is_empty() {
+            args.push("--all");
+        } else {
+            args.extend_from_slice(refs);
+        }
+        self.run_git(&args)?;
+        Ok(format!("bundle written to {}", bundle_path.display()))
+    }
+
+    // fetched branches land in refs/remotes/bundle/* so nothing local gets overwritten. tags come
+    // in under their own name, except one that's here already pointing somewhere else: that one
+    // is left alone and named in the result
+    pub fn fetch_bundle(&self, bundle_path: &Path) -> Result<String> {
+        let bundle_path = std::path::absolute(bundle_path)?;
+        let path_str = bundle_path.to_string_lossy().to_string();
+
+        self.run_git(&["bundle", "verify", &path_str])?;
+        let mut refspecs = vec!["+refs/heads/*:refs/remotes/bundle/*".to_string()];
+        let mut kept = Vec::new();
+        for line in self.run_git(&["bundle", "list-heads", &path_str])?.lines() {
+            let Some((id, name)) = line.split_once(' ') else {
+                continue;
+            };
+            let Some(tag) = name.strip_prefix("refs/tags/").filter(|t| !t.ends_with("^{}")) else {
+                continue;
+            };
+            match self.repo.refname_to_id(name) {
+                Ok(local) if local.to_string() != id => kept.push(tag.to_string()),
+                // never forced, git refuses to move a tag on its own too
+                _ => refspecs.push(format!("{0}:{0}", name)),
+            }
+        }
+        let mut args = vec!["fetch", "--no-tags", path_str.as_str()];
+        args.extend(refspecs.iter().map(String::as_str));
+        self.run_git(&args)?;
+        if kept.is_empty() {
+            Ok("fetched bundle into bundle/* branches".to_string())
+        } else {
+            Ok(format!("fetched bundle into bundle/* branches, kept the local {} (the bundle's differ)", kept.join(", ")))
+        }
+    }
+
+    // runs the git binary in the repo for things libgit2 can't do, never opens an editor
+    // run_git with `input` on stdin, for the plumbing that reads its arguments from there
+    fn run_git_input(&self, args: &[String], input: &str) -> Result<String> {
+        log::debug!("running git {}", args.join(" "));
+        let mut child = self.git_command()
+            .args(args)
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .stdin(std::process::Stdio::piped())
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .spawn()
+            .context("failed to run git, is it installed?")?;
+        child.stdin.take().context("no stdin for git")?.write_all(input.as_bytes())?;
+        let output = child.wait_with_output()?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            anyhow::bail!("git {} failed: {}", args.join(" "), stderr.trim());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    fn run_git(&self, args: &[&str]) -> Result<String> {
+        log::debug!("running git {}", args.join(" "));
+        let output = self.git_command()
+            .args(args)
+            .env("GIT_EDITOR", "true")
+            .output()
+            .context("failed to run git, is it installed?")?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            log::warn!("git {} exited with {}: {}", args.join(" "), output.status, stderr.trim());
+            anyhow::bail!("git {} failed: {}", args.join(" "), stderr.trim());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    pub fn flow_config(&self) -> FlowConfig {
+        let main = self.config_string("gitflow.branch.master").unwrap_or_else(|| {
+            let has_main = self.repo.find_branch("main", git2::BranchType::Local).is_ok();
+            if has_main { "main".to_string() } else { "master".to_string() }
+        });
+        let get = |key: &str, default: &str| self.config_string(key).unwrap_or_else(|| default.to_string());
+        FlowConfig {
+            main,
+            develop: get("gitflow.branch.develop", "develop"),
+            feature_prefix: get("gitflow.prefix.feature", "feature/"),
+            release_prefix: get("gitflow.prefix.release", "release/"),
+            hotfix_prefix: get("gitflow.prefix.hotfix", "hotfix/"),
+            tag_prefix: get("gitflow.prefix.versiontag", ""),
+        }
+    }
+
+    pub fn local_branch_exists(&self, name: &str) -> bool {
+        self.repo.find_branch(name, git2::BranchType::Local).is_ok()
+    }
+
+    // features and releases start on develop (made from main if it isn't there yet), hotfixes on main
+    pub fn flow_start(&self, kind: FlowKind, name: &str) -> Result<String> {
+        let config = self.flow_config();
+        let branch = format!("{}{}", config.prefix(kind), name.trim());
+        let base = match kind {
+            FlowKind::Hotfix => &config.main,
+            _ => &config.develop,
+        };
+
+        if !self.local_branch_exists(&config.main) {
+            anyhow::bail!("main branch '{}' does not exist, set gitflow.branch.master", config.main);
+        }
+        if !self.local_branch_exists(base) {
+            self.run_git(&["branch", base, &config.main])?;
+        }
+        self.run_git(&["checkout", "-b", &branch, base])?;
+        Ok(branch)
+    }
+
+    // merges back with --no-ff: features into develop, releases and hotfixes into main (tagged)
+    // and then develop. a conflicting merge stops here, finishing again after committing the
+    // resolution picks up where it left off since the done merges are no-ops by then
+    pub fn flow_finish(&self, kind: FlowKind, branch: &str) -> Result<String> {
+        let config = self.flow_config();
+        let name = branch
+            .strip_prefix(config.prefix(kind))
+            .with_context(|| format!("{} is not a {} branch (prefix '{}')", branch, kind.label(), config.prefix(kind)))?;
+
+        if self.has_local_changes()? {
+            anyhow::bail!("commit or stash your changes before finishing {}", branch);
+        }
+
+        let targets: Vec<&str> = match kind {
+            FlowKind::Feature => vec![&config.develop],
+            _ => vec![&config.main, &config.develop],
+        };
+
+        let mut tag = None;
+        for target in targets {
+            if !self.local_branch_exists(target) {
+                anyhow::bail!("branch '{}' does not exist", target);
+            }
+            self.run_git(&["checkout", target])?;
+            let message = format!("Merge branch '{}' into {}", branch, target);
+            self.run_git(&["merge", "--no-ff", "-m", &message, branch]).with_context(|| {
+                format!("merging {} into {} stopped, resolve and commit, then finish again", branch, target)
+            })?;
+
+            if kind != FlowKind::Feature && target == config.main {
+                let tag_name = format!("{}{}", config.tag_prefix, name);
+                if self.repo.refname_to_id(&format!("refs/tags/{}", tag_name)).is_err() {
+                    let tag_message = format!("{} {}", kind.label(), name);
+                    self.run_git(&["tag", "-a", &tag_name, "-m", &tag_message])?;
+                }
+                tag = Some(tag_name);
+            }
+        }
+
+        self.run_git(&["branch", "-d", branch])?;
+
+        Ok(match tag {
+            Some(tag) => format!("finished {}, tagged {}, back on {}.", branch, tag, config.develop),
+            None => format!("finished {}, merged into {}.", branch, config.develop),
+        })
+    }
+
+    // the remote a fork was made from, hydra.upstreamRemote or "upstream"
+    pub fn fork_parent_remote(&self) -> String {
+        self.config_string("hydra.upstreamRemote").unwrap_or_else(|| "upstream".to_string())
+    }
+
+    // the branch <remote>/HEAD points at, or main / master when the remote never said
+    fn remote_default_branch(&self, remote: &str) -> Option<String> {
+        let prefix = format!("refs/remotes/{}/", remote);
+        if let Ok(head) = self.repo.find_reference(&format!("{}HEAD", prefix)) {
+            if let Some(name) = head.symbolic_target().and_then(|t| t.strip_prefix(&prefix)) {
+                return Some(name.to_string());
+            }
+        }
+        ["main", "master"].iter()
+            .find(|b| self.repo.find_reference(&format!("{}{}", prefix, b)).is_ok())
+            .map(|b| b.to_string())
+    }
+
+    // what `git rebase --onto <new> <old> <tip>` would replay: everything on tip that old doesn't
+    // have, newest first
+    pub fn rebase_onto_commits(&self, old_base: &str, tip: &str) -> Result<Vec<Commit>> {
+        let old = git2::Oid::from_str(&self.resolve_commit(old_base)?)?;
+        let tip = git2::Oid::from_str(&self.resolve_commit(tip)?)?;
+        let mut revwalk = self.repo.revwalk()?;
+        revwalk.set_sorting(git2::Sort::TOPOLOGICAL)?;
+        revwalk.push(tip)?;
+        revwalk.hide(old)?;
+        let mailmap = self.mailmap();
+        revwalk.take(1000).map(|oid| Ok(to_commit(&self.repo.find_commit(oid?)?, mailmap.as_ref()))).collect()
+    }
+
+    // "a..b" is what b has and a doesn't, a lone rev its whole history. newest first
+    pub fn commits_in_range(&self, range: &str) -> Result<Vec<Commit>> {
+        let (from, to) = match range.trim().split_once("..") {
+            Some((from, to)) => (Some(from), if to.is_empty() { "HEAD" } else { to }),
+            None => (None, range.trim()),
+        };
+        let mut revwalk = self.repo.revwalk()?;
+        revwalk.set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)?;
+        revwalk.push(git2::Oid::from_str(&self.resolve_commit(to)?)?)?;
+        if let Some(from) = from {
+            revwalk.hide(git2::Oid::from_str(&self.resolve_commit(from)?)?)?;
+        }
+        let mailmap = self.mailmap();
+        revwalk.map(|oid| Ok(to_commit(&self.repo.find_commit(oid?)?, mailmap.as_ref()))).collect()
+    }
+
+    // the newest tag HEAD can reach, like `git describe --tags --abbrev=0`
+    pub fn latest_tag(&self) -> Option<String> {
+        let mut options = git2::DescribeOptions::new();
+        options.describe_tags();
+        let describe = self.repo.describe(&options).ok()?;
+        describe.format(Some(git2::DescribeFormatOptions::new().abbreviated_size(0))).ok()
+    }
+
+    // tags on HEAD or before it, lightweight and annotated
+    pub fn tag_names(&self) -> Result<Vec<String>> {
+        Ok(self.repo.tag_names(None)?.iter().flatten().map(|t| t.to_string()).collect())
+    }
+
+    pub fn reachable_tags(&self) -> Result<Vec<String>> {
+        let head = self.repo.head()?.peel_to_commit()?.id();
+        let mut tags = Vec::new();
+        for name in self.repo.tag_names(None)?.iter().flatten() {
+            let Ok(target) = self.repo.revparse_single(&format!("refs/tags/{}", name)).and_then(|o| o.peel_to_commit()) else {
+                continue;
+            };
+            if target.id() == head || self.repo.graph_descendant_of(head, target.id())? {
+                tags.push(name.to_string());
+            }
+        }
+        Ok(tags)
+    }
+
+    // git tag -a on HEAD, through git so tag.gpgSign is honoured. verbatim keeps markdown headings
+    pub fn create_annotated_tag(&self, name: &str, message: &str) -> Result<()> {
+        if self.repo.refname_to_id(&format!("refs/tags/{}", name)).is_ok() {
+            anyhow::bail!("tag {} already exists", name);
+        }
+        log::info!("tag -a {}", name);
+        let args: Vec<String> = ["tag", "-a", "--cleanup=verbatim", "-F", "-", name].iter().map(|a| a.to_string()).collect();
+        self.run_git_input(&args, message)?;
+        Ok(())
+    }
+
+    // git checks out tip first, so a branch there is what ends up moved (anything else detaches)
+    pub fn rebase_onto(&mut self, new_base: &str, old_base: &str, tip: &str) -> Result<String> {
+        self.resolve_commit(new_base)?;
+        let count = self.rebase_onto_commits(old_base, tip)?.len();
+        if count == 0 {
+            anyhow::bail!("{} has nothing after {}, there's no commit to move", tip, old_base);
+        }
+        log::info!("rebase --onto {} {} {} ({} commits)", new_base, old_base, tip, count);
+        self.run_git(&["rebase", "--onto", new_base, old_base, tip])?;
+        Ok(format!("moved {} commit(s) of {} onto {}", count, tip, new_base))
+    }
+
+    // remembers that branch sits on parent as it is now, for the stack view and restack
+    pub fn record_stack_parent(&self, branch: &str, parent: &str) -> Result<()> {
+        let tip = self.resolve_commit(parent)?;
+        let mut config = self.repo.config()?.open_level(git2::ConfigLevel::Local)?;
+        config.set_str(&format!("branch.{}.hydraParent", branch), parent)?;
+        config.set_str(&format!("branch.{}.hydraBase", branch), &tip)?;
+        Ok(())
+    }
+
+    // every local branch with the one it's stacked on, parents before their children
+    pub fn branch_stack(&self) -> Result<Vec<StackedBranch>> {
+        let mut tips = Vec::new();
+        for branch in self.repo.branches(Some(git2::BranchType::Local))? {
+            let (branch, _) = branch?;
+            if let (Some(name), Some(tip)) = (branch.name()?, branch.get().target()) {
+                tips.push((name.to_string(), tip));
+            }
+        }
+        let tip_of = |name: &str| tips.iter().find(|(n, _)| n == name).map(|(_, tip)| *tip);
+        let contains = |tip: git2::Oid, other: git2::Oid| tip == other || self.repo.graph_descendant_of(tip, other).unwrap_or(false);
+
+        let mut stack = Vec::new();
+        for (name, tip) in &tips {
+            let recorded = self.config_string(&format!("branch.{}.hydraParent", name)).filter(|p| p != name && tip_of(p).is_some());
+            let guessed = recorded.is_none();
+            let parent = recorded.or_else(|| {
+                // the branches it was made on top of, and of those the one furthest along
+                let below: Vec<&(String, git2::Oid)> = tips.iter().filter(|(n, t)| n != name && t != tip && contains(*tip, *t)).collect();
+                below
+                    .iter()
+                    .find(|(_, t)| below.iter().all(|(_, other)| contains(*t, *other)))
+                    .map(|(n, _)| n.clone())
+            });
+            let (ahead, needs_restack) = match parent.as_deref().and_then(tip_of) {
+                Some(parent_tip) => (self.repo.graph_ahead_behind(*tip, parent_tip)?.0, !contains(*tip, parent_tip)),
+                None => (0, false),
+            };
+            stack.push(StackedBranch { name: name.clone(), parent, ahead, needs_restack, guessed });
+        }
+
+        // depth first from the branches that aren't stacked on anything, so a recorded cycle
+        // just drops out instead of looping
+        let mut ordered: Vec<StackedBranch> = Vec::new();
+        let mut pending: Vec<StackedBranch> = stack.iter().filter(|b| b.parent.is_none()).rev().cloned().collect();
+        while let Some(branch) = pending.pop() {
+            if ordered.iter().any(|b| b.name == branch.name) {
+                continue;
+            }
+            pending.extend(stack.iter().filter(|b| b.parent.as_ref() == Some(&branch.name)).rev().cloned());
+            ordered.push(branch);
+        }
+        Ok(ordered)
+    }
+
+    // rebases every branch whose parent moved onto the parent's new tip, bottom of the stack first
+    // so the ones above follow, then goes back to the branch that was checked out. what moved
+    pub fn restack(&mut self) -> Result<Vec<String>> {
+        let stack = self.branch_stack()?;
+        let original = match self.detached_head() {
+            Some(commit) => commit,
+            None => self.get_current_branch()?,
+        };
+        // where each branch started from, before anything below it moves
+        let mut bases = Vec::new();
+        for branch in &stack {
+            let Some(parent) = &branch.parent else {
+                continue;
+            };
+            let tip = git2::Oid::from_str(&self.resolve_commit(&branch.name)?)?;
+            let recorded = self
+                .config_string(&format!("branch.{}.hydraBase", branch.name))
+                .and_then(|base| git2::Oid::from_str(&base).ok())
+                .filter(|base| *base == tip || self.repo.graph_descendant_of(tip, *base).unwrap_or(false));
+            let base = match recorded {
+                Some(base) => base,
+                None => self.repo.merge_base(tip, git2::Oid::from_str(&self.resolve_commit(parent)?)?)?,
+            };
+            bases.push((branch.name.clone(), parent.clone(), base));
+        }
+
+        let main = self.flow_config().main;
+        let mut moved = Vec::new();
+        for (branch, parent, base) in bases {
+            let tip = git2::Oid::from_str(&self.resolve_commit(&branch)?)?;
+            let parent_tip = git2::Oid::from_str(&self.resolve_commit(&parent)?)?;
+            if tip != parent_tip && !self.repo.graph_descendant_of(tip, parent_tip)? {
+                log::info!("restack: {} onto {} (was on {})", branch, parent, base);
+                self.run_git(&["rebase", "--onto", &parent, &base.to_string(), &branch])?;
+                moved.push(format!("{} onto {}", branch, parent));
+            }
+            // branches straight off main aren't stacks, main just moving on shouldn't flag them
+            if parent != main {
+                self.record_stack_parent(&branch, &parent)?;
+            }
+        }
+        if !moved.is_empty() {
+            self.run_git(&["checkout", &original])?;
+        }
+        Ok(moved)
+    }
+
+    // fetch the repo this one was forked from and bring its default branch over here. the
+    // branch, and what happened, one entry per step. pushing it to origin is up to the caller,
+    // through the push preview
+    pub fn sync_fork(&mut self, options: SyncForkOptions) -> Result<(String, Vec<String>)> {
+        let upstream = self.fork_parent_remote();
+        if !self.get_remotes()?.contains(&upstream) {
+            anyhow::bail!("no '{}' remote, add the repository you forked from with: git remote add {} <url>", upstream, upstream);
+        }
+
+        let mut steps = Vec::new();
+        steps.push(self.fetch_remote(&upstream).with_context(|| format!("fetching {} failed", upstream))?);
+
+        let branch = self.remote_default_branch(&upstream)
+            .with_context(|| format!("can't tell the default branch of {}, set it with: git remote set-head {} -a", upstream, upstream))?;
+        let upstream_branch = format!("{}/{}", upstream, branch);
+        let upstream_oid = self.repo.refname_to_id(&format!("refs/remotes/{}", upstream_branch))?;
+        let local_ref = format!("refs/heads/{}", branch);
+        let on_branch = self.get_current_branch()? == branch && self.detached_head().is_none();
+
+        match self.repo.refname_to_id(&local_ref) {
+            Err(_) => {
+                let commit = self.repo.find_commit(upstream_oid)?;
+                self.repo.branch(&branch, &commit, false)?;
+                steps.push(format!("created {} from {}", branch, upstream_branch));
+            }
+            Ok(local_oid) => {
+                let (ahead, behind) = self.repo.graph_ahead_behind(local_oid, upstream_oid)?;
+                match (ahead, behind) {
+                    (_, 0) => steps.push(format!("{} already has everything from {}", branch, upstream_branch)),
+                    (0, _) if on_branch => {
+                        if self.has_local_changes()? {
+                            anyhow::bail!("commit or stash your changes before syncing {}", branch);
+                        }
+                        self.run_git(&["merge", "--ff-only", &upstream_branch])?;
+                        steps.push(format!("fast-forwarded {} by {} commit(s)", branch, behind));
+                    }
+                    (0, _) => {
+                        self.repo.find_reference(&local_ref)?.set_target(upstream_oid, "sync fork: fast-forward")?;
+                        steps.push(format!("fast-forwarded {} by {} commit(s)", branch, behind));
+                    }
+                    _ if !options.rebase => anyhow::bail!(
+                        "{} has {} commit(s) {} doesn't, turn on rebase to replay them on top",
+                        branch, ahead, upstream_branch
+                    ),
+                    _ => {
+                        if self.has_local_changes()? {
+                            anyhow::bail!("commit or stash your changes before rebasing {}", branch);
+                        }
+                        // rebase checks the branch out, go back to where we were afterwards
+                        let back_to = self.detached_head().unwrap_or(self.get_current_branch()?);
+                        self.run_git(&["rebase", &upstream_branch, &branch])
+                            .with_context(|| format!("rebasing {} stopped, resolve and git rebase --continue", branch))?;
+                        if !on_branch {
+                            self.run_git(&["checkout", &back_to])?;
+                        }
+                        steps.push(format!("rebased {} commit(s) of {} onto {}", ahead, branch, upstream_branch));
+                    }
+                }
+            }
+        }
+
+        Ok((branch, steps))
+    }
+
+    // paths a commit changed against its first parent, everything for a root commit
+    // what's in `dir` ("" for the top, else ending in /) at `rev`, directories first
+    pub fn tree_entries(&self, rev: &str, dir: &str) -> Result<Vec<TreeEntry>> {
+        let tree = self.repo.revparse_single(rev)?.peel_to_tree()?;
+        let tree = match dir.trim_end_matches('/') {
+            "" => tree,
+            dir => tree.get_path(Path::new(dir))?.to_object(&self.repo)?.peel_to_tree().with_context(|| format!("{} is not a directory at {}", dir, rev))?,
+        };
+        let odb = self.repo.odb()?;
+        let mut entries: Vec<TreeEntry> = tree
+            .iter()
+            .map(|entry| {
+                let name = String::from_utf8_lossy(entry.name_bytes()).to_string();
+                let kind = match entry.filemode() {
+                    0o040000 => TreeEntryKind::Dir,
+                    0o100755 => TreeEntryKind::Executable,
+                    0o120000 => TreeEntryKind::Link,
+                    0o160000 => TreeEntryKind::Submodule,
+                    _ => TreeEntryKind::File,
+                };
+                let size = match kind {
+                    TreeEntryKind::Dir | TreeEntryKind::Submodule => None,
+                    _ => odb.read_header(entry.id()).ok().map(|(size, _)| size),
+                };
+                let path = format!("{}{}{}", dir, name, if kind == TreeEntryKind::Dir { "/" } else { "" });
+                TreeEntry { name, path, kind, size }
+            })
+            .collect();
+        entries.sort_by(|a, b| (b.kind == TreeEntryKind::Dir).cmp(&(a.kind == TreeEntryKind::Dir)).then_with(|| a.name.cmp(&b.name)));
+        Ok(entries)
+    }
+
+    // every file in the tree of `rev`, by path, submodules left out
+    pub fn tree_paths(&self, rev: &str) -> Result<Vec<String>> {
+        let tree = self.repo.revparse_single(rev)?.peel_to_tree()?;
+        let mut paths = Vec::new();
+        tree.walk(git2::TreeWalkMode::PreOrder, |root, entry| {
+            if entry.kind() == Some(git2::ObjectType::Blob) {
+                paths.push(format!("{}{}", root, String::from_utf8_lossy(entry.name_bytes())));
+            }
+            git2::TreeWalkResult::Ok
+        })?;
+        Ok(paths)
+    }
+
+    // the file as it was at `rev`, fetched first in a partial clone. None when it's not here
+    // and couldn't be
+    pub fn file_at(&self, rev: &str, path: &str) -> Result<Option<Vec<u8>>> {
+        let tree = self.repo.revparse_single(rev)?.peel_to_tree()?;
+        let entry = tree.get_path(Path::new(path)).with_context(|| format!("no {} at {}", path, rev))?;
+        if !self.fetch_missing([entry.id()])?.is_empty() {
+            return Ok(None);
+        }
+        let blob = self.repo.find_blob(entry.id()).with_context(|| format!("{} is not a file", path))?;
+        Ok(Some(blob.content().to_vec()))
+    }
+
+    pub fn commit_files(&self, rev: &str) -> Result<Vec<String>> {
+        let commit = self.repo.revparse_single(rev)?.peel_to_commit()?;
+        let parent_tree = match commit.parent(0) {
+            Ok(parent) => Some(parent.tree()?),
+            Err(_) => None,
+        };
+        let diff = self.repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), None)?;
+        Ok(diff.deltas()
+            .filter_map(|d| d.new_file().path().or(d.old_file().path()).map(|p| p.to_string_lossy().to_string()))
+            .collect())
+    }
+
+    pub fn commit_diff(&self, rev: &str) -> Result<CommitDiff> {
+        let commit = self.repo.revparse_single(rev)?.peel_to_commit()?;
+        let parent_tree = match commit.parent(0) {
+            Ok(parent) => Some(parent.tree()?),
+            Err(_) => None,
+        };
+        let diff = self.repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&commit.tree()?), None)?;
+        tree_diff(&diff, &self.fetch_missing(diff_blobs(&diff))?)
+    }
+
+    // `git diff from..to`: everything between the two trees, whatever lies in between
+    pub fn range_diff(&self, from: &str, to: &str) -> Result<CommitDiff> {
+        let from = self.repo.revparse_single(from)?.peel_to_tree()?;
+        let to = self.repo.revparse_single(to)?.peel_to_tree()?;
+        let diff = self.repo.diff_tree_to_tree(Some(&from), Some(&to), None)?;
+        tree_diff(&diff, &self.fetch_missing(diff_blobs(&diff))?)
+    }
+
+    // git clean -ndx, ignored ones first like -X would list them. untracked directories are one
+    // entry each, nested repos aren't touched (that would take -ff)
+    pub fn clean_candidates(&self) -> Result<Vec<CleanEntry>> {
+        let workdir = self.workdir().context("bare repository, there is nothing to clean")?.to_path_buf();
+        let ignored: std::collections::HashSet<String> = self.clean_dry_run(&["-X"], &[])?.into_iter().collect();
+        let mut entries: Vec<CleanEntry> = self
+            .clean_dry_run(&["-x"], &[])?
+            .into_iter()
+            .map(|path| {
+                let full = workdir.join(&path);
+                let size = match fs::symlink_metadata(&full) {
+                    Ok(meta) if meta.is_dir() => dir_size(&full),
+                    Ok(meta) => meta.len(),
+                    Err(_) => 0,
+                };
+                CleanEntry { ignored: ignored.contains(&path), path, size }
+            })
+            .collect();
+        entries.sort_by(|a, b| b.ignored.cmp(&a.ignored).then_with(|| a.path.cmp(&b.path)));
+        Ok(entries)
+    }
+
+    // the "Would remove" list, for the given paths or everything. the paths are names, never
+    // globs or pathspec magic: a file called * mustn't stand for everything
+    pub fn clean_dry_run(&self, flags: &[&str], paths: &[String]) -> Result<Vec<String>> {
+        let mut args = vec!["--literal-pathspecs", "-c", "core.quotePath=false", "clean", "-n", "-d"];
+        args.extend(flags);
+        if !paths.is_empty() {
+            args.push("--");
+            args.extend(paths.iter().map(|p| p.as_str()));
+        }
+        let output = self.run_git(&args)?;
+        Ok(output.lines().filter_map(|l| l.strip_prefix("Would remove ")).map(|l| l.to_string()).collect())
+    }
+
+    // git clean -fdx on just these, ignored or not, taken literally like in clean_dry_run.
+    // returns what git says it removed
+    pub fn clean_paths(&self, paths: &[String]) -> Result<usize> {
+        if paths.is_empty() {
+            return Ok(0);
+        }
+        log::info!("clean {} path(s)", paths.len());
+        let mut args = vec!["--literal-pathspecs", "-c", "core.quotePath=false", "clean", "-f", "-d", "-x", "--"];
+        args.extend(paths.iter().map(|p| p.as_str()));
+        let output = self.run_git(&args)?;
+        Ok(output.lines().filter(|l| l.starts_with("Removing ")).count())
+    }
+
+    // what `restore_file` would change, as a diff from the current content to `rev`'s
+    pub fn restore_preview(&self, rev: &str, path: &str, target: RestoreTarget) -> Result<String> {
+        let mut args = vec!["--literal-pathspecs", "diff", "--no-color", "-R"];
+        if target == RestoreTarget::Index {
+            args.push("--cached");
+        }
+        args.extend([rev, "--", path]);
+        self.run_git(&args)
+    }
+
+    // git restore --source=<rev>, a path that didn't exist at rev gets removed
+    pub fn restore_file(&self, rev: &str, path: &str, target: RestoreTarget) -> Result<()> {
+        let source = format!("--source={}", rev);
+        let mut args = vec!["--literal-pathspecs", "restore", source.as_str()];
+        args.extend(target.flags());
+        args.extend(["--", path]);
+        self.run_git(&args)?;
+        Ok(())
+    }
+
+    // the message git prepared in MERGE_MSG, if any
+    pub fn get_merge_message(&self) -> Option<String> {
+        self.repo.message().ok()
+            .map(|m| m.trim_end().to_string())
+            .filter(|m| !m.is_empty())
+    }
+
+    pub fn abort_merge(&mut self) -> Result<()> {
+        let head_commit = self.repo.head()?.peel_to_commit()?;
+        log::info!("aborting merge, resetting to {}", head_commit.id());
+        self.repo.reset(head_commit.as_object(), git2::ResetType::Hard, None)?;
+
+        self.repo.cleanup_state()?;
+        Ok(())
+    }
+
+    pub fn init_repo<P: AsRef<Path>>(path: P) -> Result<Self> {
+        let repo = Git2Repository::init(path.as_ref())
+            .with_context(|| format!("failed to init git repo at {}", path.as_ref().display()))?;
+
+        Ok(Self { repo, status_scope: None, ignore_eol: false, diff_base: None, filled_credential: Default::default(), prompted_credential: Default::default(), credential_request: Default::default(), short_ids: Default::default(), offline: Default::default() })
+    }
+
+    pub fn add_remote(&mut self, name: &str, url: &str) -> Result<()> {
+        match self.repo.find_remote(name) {
+            Ok(_) => {
+                self.repo
+                    .remote_set_url(name, url)
+                    .with_context(|| format!("failed to set remote URL for {}", name))?;
+            }
+
+            Err(_) => {
+                self.repo
+                    .remote(name, url)
+                    .with_context(|| format!("failed to add remote {}", name))?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn has_commits(&self) -> bool {
+        self.repo.head().is_ok()
+    }
+
+    pub fn initial_commit_all(&mut self, message: &str) -> Result<()> {
+        use git2::{IndexAddOption, Signature};
+
+        let sig = self
+            .repo   
+            .signature()
+            .or_else(|_| Signature::now("You", "you@example.com"))
+            .context("failed to create git signature")?;
+        
+        let mut index = self.repo.index()?;
+        index.add_all(["*"].iter(), IndexAddOption::DEFAULT, None)?;
+        let oid = index.write_tree()?;
+        let tree = self.repo.find_tree(oid)?;
+
+        // parents if HEAD exists if not start an initial commit
+        let parent_commit = if let Ok(head_ref) = self.repo.head() {
+            head_ref.peel_to_commit().ok()
+        } else {
+            None
+        };
+
+        let parents: Vec<&git2::Commit> = match &parent_commit {
+            Some(commit) => vec![commit],
+            None => vec![]
+        };
+
+        let head_name = if self.repo.head().is_ok() {
+            "HEAD"
+        } else {
+            "refs/heads/main"
+        };
+        let _ = self
+            .repo
+            .commit(Some(head_name), &sig, &sig, message, &tree, &parents[..]);
+
+        if head_name != "HEAD" {
+            self.repo.set_head("refs/heads/main")?;
+        }
+
+        Ok(())
+    }
+
+    pub fn try_create_remote_with_gh(
+        &mut self,
+        remote: &str,
+        repo_name: Option<&str>,
+        private: bool,
+    ) -> Result<()> {
+        use std::process::Command;
+        let name = repo_name.unwrap_or("repo");
+        let visibility = if private { "--private" } else { "--public" };
+        // gh repo create <name> --source . --remote <remote> --push --confirm --private/--public
+        let status = Command::new("gh")
+            .args([
+                "repo", "create", name, "--source", ".", "--remote", remote, "--push", "--confirm",
+                visibility,
+            ])
+            .status();
+        match status {
+            Ok(s) if s.success() => Ok(()),
+            Ok(s) => anyhow::bail!("'gh repo create' failed with status {}", s),
+            Err(e) => anyhow::bail!("gh CLI not found or failed to run: {}", e),
+        }
+    }
+}
+
+// prints a patch with the origin of the `prefixed` kinds of lines in front of them, true when
+// anything was printed
+// changed lines eol_only_change looks at before giving up, a rewrite that big isn't worth
+// comparing every frame
+const EOL_CHECK_LIMIT: usize = 20_000;
+
+// the blobs on both sides of each file, submodules (commits) and added / deleted sides aside
+fn diff_blobs(diff: &git2::Diff) -> Vec<git2::Oid> {
+    diff.deltas()
+        .flat_map(|d| [d.old_file(), d.new_file()])
+        .filter(|f| !f.id().is_zero() && f.mode() != git2::FileMode::Commit)
+        .map(|f| f.id())
+        .collect()
+}
+
+// `missing` are blobs a partial clone couldn't get, their files are named with no patch
+fn tree_diff(diff: &git2::Diff, missing: &std::collections::HashSet<git2::Oid>) -> Result<CommitDiff> {
+    let mut result = CommitDiff::default();
+    let mut lines = Vec::new();
+    let mut current = String::new();
+    let mut sink = |chunk: &str| {
+        for (i, part) in chunk.split('\n').enumerate() {
+            if i > 0 {
+                lines.push(std::mem::take(&mut current));
+            }
+            current.push_str(part);
+        }
+        lines.len() < COMMIT_DIFF_LIMIT
+    };
+    if missing.is_empty() {
+        collect_file_stats(diff, &mut result.files)?;
+        print_patch(diff, &['+', '-', ' '], &mut sink)?;
+    } else {
+        // file by file, so the ones that are here still get their patch
+        for (i, delta) in diff.deltas().enumerate() {
+            let here = !missing.contains(&delta.old_file().id()) && !missing.contains(&delta.new_file().id());
+            let Some(stat) = file_stat(diff, i, here)? else {
+                continue;
+            };
+            if !here {
+                let old = delta.old_file().path().map_or(stat.path.clone(), |p| p.to_string_lossy().to_string());
+                sink(&format!("diff --git a/{} b/{}\n", old, stat.path));
+                sink("(not fetched, it's only on the promisor remote of this partial clone)\n");
+                result.missing.push(stat.path.clone());
+            } else if let Some(mut patch) = git2::Patch::from_diff(diff, i)? {
+                let mut stopped = false;
+                let printed = patch.print(&mut |_, _, line| {
+                    stopped = !sink(&patch_text(&line, &['+', '-', ' ']));
+                    !stopped
+                });
+                if !stopped {
+                    printed?;
+                }
+            }
+            result.files.push(stat);
+        }
+    }
+    if !current.is_empty() {
+        lines.push(current);
+    }
+    result.lines = lines;
+    if result.lines.len() >= COMMIT_DIFF_LIMIT {
+        result.lines.truncate(COMMIT_DIFF_LIMIT);
+        result.truncated = true;
+    }
+    result.file_starts = result.lines.iter().enumerate().filter(|(_, l)| l.starts_with("diff --git ")).map(|(i, _)| i).collect();
+    Ok(result)
+}
+
+fn print_patch(diff: &git2::Diff, prefixed: &[char], sink: &mut dyn FnMut(&str) -> bool) -> Result<bool> {
+    let mut printed = false;
+    let mut stopped = false;
+    let result = diff.print(git2::DiffFormat::Patch, |_delta, _hunk, line| {
+        let text = patch_text(&line, prefixed);
+        printed |= !text.is_empty();
+        stopped = !sink(&text);
+        !stopped
+    });
+    // libgit2 reports the callback bailing out as an error
+    if !stopped {
+        result?;
+    }
+    Ok(printed)
+}
+
+// a line of a patch like git prints it, the origin in front for the `prefixed` kinds
+fn patch_text(line: &git2::DiffLine, prefixed: &[char]) -> String {
+    let content = std::str::from_utf8(line.content()).unwrap_or("");
+    if prefixed.contains(&line.origin()) {
+        format!("{}{}", line.origin(), content)
+    } else {
+        content.to_string()
+    }
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).map(|m| m.permissions().mode() & 0o111 != 0).unwrap_or(false)
+}
+
+// git for windows runs hooks through its own sh, there's no executable bit to check
+#[cfg(not(unix))]
+fn is_executable(_path: &Path) -> bool {
+    true
+}
+
+#[cfg(unix)]
+fn set_executable(path: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+    let mut permissions = fs::metadata(path)?.permissions();
+    permissions.set_mode(permissions.mode() | 0o111);
+    fs::set_permissions(path, permissions)?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn set_executable(_path: &Path) -> Result<()> {
+    Ok(())
+}
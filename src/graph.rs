@@ -0,0 +1,226 @@
+use anyhow::Result;
+use git2::{Oid, Sort};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+// lanes for the log graph. rows are laid out oldest commit first, so new commits only ever add
+// rows on top and never move the ones under them: the layout is kept in a file per repo under
+// the state dir and on a refresh only the commits since the cached HEAD get walked. when HEAD
+// moved anywhere else (reset, another branch) it's built again from scratch.
+
+// more lanes than this and the rest of the line gets a …
+const MAX_LANES: usize = 24;
+
+#[derive(Debug, Clone)]
+pub struct GraphRow {
+    pub oid: Oid,
+    pub lane: u16,
+    // lane of the first parent when this commit couldn't continue it (another child already did)
+    pub parent_lane: Option<u16>,
+    // lanes with a line going on up to newer commits, the ones coming from below are the
+    // previous row's
+    pub above: Vec<u16>,
+}
+
+#[derive(Debug, Default)]
+pub struct Graph {
+    head: Option<Oid>,
+    // oldest first
+    rows: Vec<GraphRow>,
+    // the commit at the top of each lane, still waiting for a child
+    lanes: Vec<Option<Oid>>,
+    index: HashMap<Oid, usize>,
+}
+
+impl Graph {
+    pub fn load(path: &Path) -> Self {
+        fs::read_to_string(path).ok().and_then(|content| parse(&content)).unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut out = String::new();
+        out.push_str(&format!("head {}\n", self.head.map(|o| o.to_string()).unwrap_or_default()));
+        let lanes: Vec<String> = self.lanes.iter().map(|l| l.map(|o| o.to_string()).unwrap_or_else(|| "-".to_string())).collect();
+        out.push_str(&format!("lanes {}\n", lanes.join(",")));
+        for row in &self.rows {
+            let above: Vec<String> = row.above.iter().map(|l| l.to_string()).collect();
+            let parent_lane = row.parent_lane.map(|l| l.to_string()).unwrap_or_else(|| "-".to_string());
+            out.push_str(&format!("{} {} {} {}\n", row.oid, row.lane, parent_lane, above.join(",")));
+        }
+        fs::write(path, out)?;
+        Ok(())
+    }
+
+    // bring the layout up to HEAD, true when anything changed
+    pub fn update(&mut self, repo: &git2::Repository) -> Result<bool> {
+        let head = repo.head().ok().and_then(|h| h.target());
+        if head == self.head {
+            return Ok(false);
+        }
+        let Some(head) = head else {
+            *self = Self::default();
+            return Ok(true);
+        };
+
+        let mut revwalk = repo.revwalk()?;
+        revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME | Sort::REVERSE)?;
+        revwalk.push(head)?;
+        match self.head {
+            Some(old) if repo.graph_descendant_of(head, old).unwrap_or(false) => revwalk.hide(old)?,
+            _ => *self = Self::default(),
+        }
+
+        for oid in revwalk {
+            let commit = repo.find_commit(oid?)?;
+            let parents: Vec<Oid> = commit.parent_ids().collect();
+            self.add(commit.id(), &parents);
+        }
+        self.head = Some(head);
+        Ok(true)
+    }
+
+    fn add(&mut self, oid: Oid, parents: &[Oid]) {
+        let first_parent = parents.first().and_then(|p| self.lanes.iter().position(|l| *l == Some(*p)));
+        let free = self.lanes.iter().position(|l| l.is_none());
+        let (lane, parent_lane) = match first_parent {
+            // a free lane further left, move over so the graph doesn't stay indented after a merge
+            Some(lane) if free.is_some_and(|f| f < lane) => {
+                self.lanes[lane] = None;
+                (free.unwrap_or(lane), None)
+            }
+            Some(lane) => (lane, None),
+            None => {
+                let lane = match free {
+                    Some(free) => free,
+                    None => {
+                        self.lanes.push(None);
+                        self.lanes.len() - 1
+                    }
+                };
+                let parent_lane = parents.first().and_then(|p| self.index.get(p)).map(|&i| self.rows[i].lane);
+                (lane, parent_lane)
+            }
+        };
+
+        // the other parents' lines end here, merged into this commit
+        for parent in parents.iter().skip(1) {
+            if let Some(merged) = self.lanes.iter().position(|l| *l == Some(*parent)) {
+                if merged != lane {
+                    self.lanes[merged] = None;
+                }
+            }
+        }
+        self.lanes[lane] = Some(oid);
+        while self.lanes.last() == Some(&None) {
+            self.lanes.pop();
+        }
+
+        let above = self.lanes.iter().enumerate().filter(|(_, l)| l.is_some()).map(|(i, _)| i as u16).collect();
+        self.index.insert(oid, self.rows.len());
+        self.rows.push(GraphRow { oid, lane: lane as u16, parent_lane, above });
+    }
+
+    // newest first, like the log
+    pub fn top(&self, count: usize) -> Vec<Oid> {
+        self.rows.iter().rev().take(count).map(|r| r.oid).collect()
+    }
+
+    pub fn position(&self, oid: Oid) -> Option<usize> {
+        self.index.get(&oid).map(|&i| self.rows.len() - 1 - i)
+    }
+
+    // the graph part of a log line, `position` counted from the top
+    pub fn cells(&self, position: usize) -> String {
+        let Some(i) = self.rows.len().checked_sub(position + 1) else {
+            return String::new();
+        };
+        let row = &self.rows[i];
+        let below: &[u16] = if i == 0 { &[] } else { &self.rows[i - 1].above };
+        let lane = row.lane as usize;
+        let width = below.iter().chain(&row.above).map(|&l| l as usize + 1).max().unwrap_or(0).max(lane + 1);
+
+        let mut cells = vec![' '; width];
+        // lanes this row draws a connection to, from the commit
+        let mut links = Vec::new();
+        for (l, cell) in cells.iter_mut().enumerate() {
+            let from_below = below.contains(&(l as u16));
+            let to_above = row.above.contains(&(l as u16));
+            *cell = if l == lane {
+                '●'
+            } else if from_below && to_above {
+                if row.parent_lane == Some(l as u16) {
+                    links.push(l);
+                    if l < lane { '├' } else { '┤' }
+                } else {
+                    '│'
+                }
+            } else if from_below {
+                links.push(l);
+                if l < lane { '╭' } else { '╮' }
+            } else {
+                ' '
+            };
+        }
+
+        let mut line = String::new();
+        for (l, cell) in cells.iter().enumerate().take(MAX_LANES) {
+            let joined = |a: usize, b: usize| links.iter().any(|&t| t.min(lane) <= a && b <= t.max(lane));
+            // a line crossing this lane on its way to one further out
+            let through = l > 0 && joined(l - 1, l) && joined(l, l + 1);
+            let cell = match *cell {
+                ' ' if joined(l, l) => '─',
+                '╭' | '╮' if through => '┬',
+                '│' | '├' | '┤' if through => '┼',
+                cell => cell,
+            };
+            line.push(cell);
+            line.push(if joined(l, l + 1) { '─' } else { ' ' });
+        }
+        if width > MAX_LANES {
+            line.push('…');
+        }
+        line
+    }
+
+    // the lanes going on below the commit at `position`, for the lines printed under it
+    pub fn gap(&self, position: usize, width: usize) -> String {
+        let below: &[u16] = match self.rows.len().checked_sub(position + 1) {
+            Some(i) if i > 0 => &self.rows[i - 1].above,
+            _ => &[],
+        };
+        let mut line: String = (0..MAX_LANES).map(|l| if below.contains(&(l as u16)) { "│ " } else { "  " }).collect();
+        line.truncate(line.char_indices().nth(width).map_or(line.len(), |(at, _)| at));
+        format!("{:width$}", line, width = width)
+    }
+}
+
+fn parse(content: &str) -> Option<Graph> {
+    let mut lines = content.lines();
+    let head = lines.next()?.strip_prefix("head ")?;
+    let head = if head.is_empty() { None } else { Some(Oid::from_str(head).ok()?) };
+    let lanes = lines.next()?.strip_prefix("lanes ")?;
+    let lanes = lanes
+        .split(',')
+        .filter(|l| !l.is_empty())
+        .map(|l| if l == "-" { Some(None) } else { Oid::from_str(l).ok().map(Some) })
+        .collect::<Option<Vec<_>>>()?;
+
+    let mut graph = Graph { head, lanes, ..Graph::default() };
+    for line in lines {
+        let mut parts = line.split(' ');
+        let oid = Oid::from_str(parts.next()?).ok()?;
+        let lane = parts.next()?.parse().ok()?;
+        let parent_lane = match parts.next()? {
+            "-" => None,
+            l => Some(l.parse().ok()?),
+        };
+        let above = parts.next().unwrap_or("").split(',').filter(|l| !l.is_empty()).map(|l| l.parse().ok()).collect::<Option<Vec<_>>>()?;
+        graph.index.insert(oid, graph.rows.len());
+        graph.rows.push(GraphRow { oid, lane, parent_lane, above });
+    }
+    Some(graph)
+}
@@ -0,0 +1,71 @@
+// the manual: a markdown page per view, embedded in the binary. F1 opens the one for the current
+// view in the help browser, `git-hydra help <topic>` prints it and `git-hydra help --man` turns
+// all of them into a man page
+
+pub struct Page {
+    pub topic: &'static str,
+    pub text: &'static str,
+}
+
+pub const PAGES: &[Page] = &[
+    Page { topic: "status", text: include_str!("help/status.md") },
+    Page { topic: "log", text: include_str!("help/log.md") },
+    Page { topic: "branches", text: include_str!("help/branches.md") },
+    Page { topic: "stashes", text: include_str!("help/stashes.md") },
+    Page { topic: "remote", text: include_str!("help/remote.md") },
+    Page { topic: "files", text: include_str!("help/files.md") },
+    Page { topic: "conflicts", text: include_str!("help/conflicts.md") },
+    Page { topic: "palette", text: include_str!("help/palette.md") },
+    Page { topic: "config", text: include_str!("help/config.md") },
+];
+
+pub fn topic_parser() -> clap::builder::PossibleValuesParser {
+    clap::builder::PossibleValuesParser::new(PAGES.iter().map(|p| p.topic))
+}
+
+pub fn find(topic: &str) -> Option<usize> {
+    PAGES.iter().position(|p| p.topic == topic)
+}
+
+// every line with the query in it, as (page, line), case doesn't matter
+pub fn search(query: &str) -> Vec<(usize, usize)> {
+    let query = query.to_lowercase();
+    if query.is_empty() {
+        return Vec::new();
+    }
+    PAGES
+        .iter()
+        .enumerate()
+        .flat_map(|(page, p)| p.text.lines().enumerate().filter(|(_, l)| l.to_lowercase().contains(&query)).map(move |(line, _)| (page, line)))
+        .collect()
+}
+
+// every page in roff, for `man -l`
+pub fn man() -> String {
+    let mut out = format!(".TH GIT-HYDRA 1 \"\" \"git-hydra {}\"\n", env!("CARGO_PKG_VERSION"));
+    out.push_str(".SH NAME\ngit-hydra \\- a tui for git\n");
+    for page in PAGES {
+        for line in page.text.lines() {
+            let line = line.replace('`', "");
+            if let Some(heading) = line.strip_prefix("# ") {
+                out.push_str(&format!(".SH {}\n", roff_escape(heading).to_uppercase()));
+            } else if let Some(heading) = line.strip_prefix("## ") {
+                out.push_str(&format!(".SS {}\n", roff_escape(heading)));
+            } else if let Some(item) = line.strip_prefix("- ") {
+                out.push_str(&format!(".IP \\(bu 2\n{}\n", roff_escape(item)));
+            } else if line.is_empty() {
+                out.push_str(".PP\n");
+            } else {
+                out.push_str(&roff_escape(&line));
+                out.push('\n');
+            }
+        }
+    }
+    out
+}
+
+// a line starting with . or ' would be read as a request
+fn roff_escape(text: &str) -> String {
+    let text = text.replace('\\', "\\e").replace('-', "\\-");
+    if text.starts_with('.') || text.starts_with('\'') { format!("\\&{}", text) } else { text }
+}
@@ -0,0 +1,171 @@
+// just enough syntax highlighting for the files view's preview. there's no grammar behind it:
+// keywords, strings, numbers and comments of the language the file extension says, a word at a
+// time. a file it has no language for comes out plain.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Kind {
+    Plain,
+    Keyword,
+    String,
+    Number,
+    Comment,
+}
+
+struct Language {
+    extensions: &'static [&'static str],
+    keywords: &'static [&'static str],
+    line_comment: &'static [&'static str],
+    block_comment: Option<(&'static str, &'static str)>,
+    quotes: &'static [char],
+}
+
+const C_LIKE_KEYWORDS: &[&str] = &[
+    "auto", "break", "case", "catch", "char", "class", "const", "continue", "default", "delete", "do", "double", "else",
+    "enum", "export", "extends", "extern", "false", "final", "float", "for", "func", "function", "go", "goto", "if",
+    "implements", "import", "include", "int", "interface", "let", "long", "namespace", "new", "null", "nullptr",
+    "package", "private", "protected", "public", "return", "short", "signed", "sizeof", "static", "struct", "switch",
+    "template", "this", "throw", "true", "try", "typedef", "typeof", "union", "unsigned", "using", "var", "virtual",
+    "void", "volatile", "while", "async", "await", "yield", "type", "defer", "range", "map", "chan", "select",
+];
+
+const LANGUAGES: &[Language] = &[
+    Language {
+        extensions: &["rs"],
+        keywords: &[
+            "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern", "false", "fn",
+            "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref", "return", "self",
+            "Self", "static", "struct", "super", "trait", "true", "type", "unsafe", "use", "where", "while",
+        ],
+        line_comment: &["//"],
+        block_comment: Some(("/*", "*/")),
+        quotes: &['"'],
+    },
+    Language {
+        extensions: &["c", "h", "cc", "cpp", "hpp", "cxx", "java", "js", "jsx", "mjs", "ts", "tsx", "go", "cs", "kt", "swift", "scala", "dart"],
+        keywords: C_LIKE_KEYWORDS,
+        line_comment: &["//"],
+        block_comment: Some(("/*", "*/")),
+        quotes: &['"', '\'', '`'],
+    },
+    Language {
+        extensions: &["py", "pyi"],
+        keywords: &[
+            "and", "as", "assert", "async", "await", "break", "class", "continue", "def", "del", "elif", "else", "except",
+            "False", "finally", "for", "from", "global", "if", "import", "in", "is", "lambda", "None", "nonlocal", "not",
+            "or", "pass", "raise", "return", "True", "try", "while", "with", "yield",
+        ],
+        line_comment: &["#"],
+        block_comment: None,
+        quotes: &['"', '\''],
+    },
+    Language {
+        extensions: &["sh", "bash", "zsh"],
+        keywords: &[
+            "case", "do", "done", "elif", "else", "esac", "export", "fi", "for", "function", "if", "in", "local", "return",
+            "then", "until", "while",
+        ],
+        line_comment: &["#"],
+        block_comment: None,
+        quotes: &['"', '\''],
+    },
+    Language {
+        extensions: &["toml", "yaml", "yml", "ini", "cfg", "conf"],
+        keywords: &["true", "false", "null"],
+        line_comment: &["#", ";"],
+        block_comment: None,
+        quotes: &['"', '\''],
+    },
+];
+
+fn language(path: &str) -> Option<&'static Language> {
+    let name = path.rsplit('/').next().unwrap_or(path);
+    let extension = name.rsplit_once('.').map(|(_, e)| e.to_lowercase())?;
+    LANGUAGES.iter().find(|l| l.extensions.contains(&extension.as_str()))
+}
+
+// every line of `text` as the runs of one kind it's made of, a block comment carries over from
+// line to line
+pub fn highlight(path: &str, text: &str) -> Vec<Vec<(Kind, String)>> {
+    let language = language(path);
+    let mut in_block = false;
+    text.lines()
+        .map(|line| match language {
+            Some(language) => highlight_line(language, line, &mut in_block),
+            None => vec![(Kind::Plain, line.to_string())],
+        })
+        .collect()
+}
+
+fn highlight_line(language: &Language, line: &str, in_block: &mut bool) -> Vec<(Kind, String)> {
+    let mut runs: Vec<(Kind, String)> = Vec::new();
+    let mut push = |kind: Kind, text: &str| match runs.last_mut() {
+        Some((last, run)) if *last == kind => run.push_str(text),
+        _ => runs.push((kind, text.to_string())),
+    };
+    let mut rest = line;
+    while !rest.is_empty() {
+        if *in_block {
+            let (_, end) = language.block_comment.unwrap_or(("", ""));
+            match rest.find(end) {
+                Some(at) => {
+                    push(Kind::Comment, &rest[..at + end.len()]);
+                    rest = &rest[at + end.len()..];
+                    *in_block = false;
+                }
+                None => {
+                    push(Kind::Comment, rest);
+                    rest = "";
+                }
+            }
+            continue;
+        }
+        if let Some((start, _)) = language.block_comment.filter(|(start, _)| rest.starts_with(start)) {
+            push(Kind::Comment, start);
+            rest = &rest[start.len()..];
+            *in_block = true;
+            continue;
+        }
+        if language.line_comment.iter().any(|c| rest.starts_with(c)) {
+            push(Kind::Comment, rest);
+            break;
+        }
+        let first = rest.chars().next().unwrap_or(' ');
+        let len = if language.quotes.contains(&first) {
+            let end = string_end(rest, first);
+            push(Kind::String, &rest[..end]);
+            end
+        } else if first.is_alphanumeric() || first == '_' {
+            let end = rest.find(|c: char| !(c.is_alphanumeric() || c == '_' || (first.is_ascii_digit() && c == '.'))).unwrap_or(rest.len());
+            let word = &rest[..end];
+            let kind = if first.is_ascii_digit() {
+                Kind::Number
+            } else if language.keywords.contains(&word) {
+                Kind::Keyword
+            } else {
+                Kind::Plain
+            };
+            push(kind, word);
+            end
+        } else {
+            push(Kind::Plain, &rest[..first.len_utf8()]);
+            first.len_utf8()
+        };
+        rest = &rest[len..];
+    }
+    runs
+}
+
+// past the closing quote, or the end of the line when there's none. a backslash skips what's
+// after it
+fn string_end(text: &str, quote: char) -> usize {
+    let mut escaped = false;
+    for (i, c) in text.char_indices().skip(1) {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            c if c == quote => return i + c.len_utf8(),
+            _ => {}
+        }
+    }
+    text.len()
+}
@@ -0,0 +1,67 @@
+// up/down recall for the single line dialogs (commit message, branch name, stash message)
+
+#[derive(Debug, Default)]
+pub struct InputHistory {
+    // things entered this session, newest first
+    session: Vec<String>,
+    // session entries plus whatever the repo suggests, rebuilt each time the dialog opens
+    entries: Vec<String>,
+    position: Option<usize>,
+    // what was typed before browsing, restored when going back down past the newest entry
+    draft: String,
+}
+
+impl InputHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn remember(&mut self, entry: &str) {
+        let entry = entry.trim();
+        if entry.is_empty() {
+            return;
+        }
+        self.session.retain(|e| e != entry);
+        self.session.insert(0, entry.to_string());
+    }
+
+    pub fn begin(&mut self, suggestions: Vec<String>) {
+        self.entries = self.session.clone();
+        for suggestion in suggestions {
+            let suggestion = suggestion.trim().to_string();
+            if !suggestion.is_empty() && !self.entries.contains(&suggestion) {
+                self.entries.push(suggestion);
+            }
+        }
+        self.position = None;
+        self.draft.clear();
+    }
+
+    // Up: one entry further back
+    pub fn older(&mut self, current: &str) -> Option<String> {
+        let next = match self.position {
+            None => {
+                self.draft = current.to_string();
+                0
+            }
+            Some(i) => i + 1,
+        };
+        let entry = self.entries.get(next)?.clone();
+        self.position = Some(next);
+        Some(entry)
+    }
+
+    // Down: back towards the draft
+    pub fn newer(&mut self) -> Option<String> {
+        match self.position? {
+            0 => {
+                self.position = None;
+                Some(self.draft.clone())
+            }
+            i => {
+                self.position = Some(i - 1);
+                self.entries.get(i - 1).cloned()
+            }
+        }
+    }
+}
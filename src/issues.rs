@@ -0,0 +1,89 @@
+use crate::pattern::Pattern;
+use anyhow::{Context, Result};
+
+// issue references in commit messages.
+//   hydra.issueUrl    url for #123 refs, default <remote web url>/issues/{id}
+//   hydra.issueLink   extra "<pattern> <url>" rules, can be given more than once, e.g.
+//                     ([A-Z][A-Z0-9]+-\d+) https://jira.example.com/browse/{id}
+// {id} is the first capture group (or the whole match), {web} the remote's web url.
+
+#[derive(Debug, Clone)]
+pub struct IssueRule {
+    pattern: Pattern,
+    template: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IssueRef {
+    // byte range of the reference in the searched text
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+    pub url: String,
+}
+
+impl IssueRule {
+    pub fn new(pattern: &str, template: &str) -> Result<Self> {
+        let pattern = Pattern::new(pattern).with_context(|| format!("bad issue pattern '{}'", pattern))?;
+        Ok(Self { pattern, template: template.to_string() })
+    }
+
+    // "<pattern> <url>", split on the last space since urls don't have any
+    pub fn parse(spec: &str) -> Result<Self> {
+        let (pattern, template) = spec
+            .trim()
+            .rsplit_once(char::is_whitespace)
+            .with_context(|| format!("hydra.issueLink '{}' should be '<pattern> <url>'", spec))?;
+        Self::new(pattern.trim(), template)
+    }
+}
+
+// rules from the repo config, the built-in #123 one goes last so custom ones win on overlap
+pub fn load_rules(issue_links: &[String], issue_url: Option<&str>, web_url: Option<&str>) -> (Vec<IssueRule>, Vec<String>) {
+    let mut rules = Vec::new();
+    let mut errors = Vec::new();
+
+    for spec in issue_links {
+        match IssueRule::parse(spec) {
+            Ok(rule) => rules.push(rule),
+            Err(e) => errors.push(format!("{:#}", e)),
+        }
+    }
+
+    let default_url = web_url.map(|web| {
+        if web.contains("gitlab") { "{web}/-/issues/{id}" } else { "{web}/issues/{id}" }
+    });
+    if let Some(template) = issue_url.or(default_url) {
+        rules.push(IssueRule::new(r"#(\d+)\b", template).expect("built-in issue pattern"));
+    }
+
+    for rule in &mut rules {
+        if let Some(web) = web_url {
+            rule.template = rule.template.replace("{web}", web);
+        }
+    }
+    (rules, errors)
+}
+
+// every reference in `text`, in order, without overlaps
+pub fn find_refs(text: &str, rules: &[IssueRule]) -> Vec<IssueRef> {
+    let mut refs: Vec<IssueRef> = Vec::new();
+
+    for rule in rules {
+        for m in rule.pattern.find_iter(text) {
+            if m.start == m.end || refs.iter().any(|r| m.start < r.end && r.start < m.end) {
+                continue;
+            }
+            let id = m.group(text, 1).unwrap_or(&text[m.start..m.end]);
+            refs.push(IssueRef {
+                start: m.start,
+                end: m.end,
+                text: text[m.start..m.end].to_string(),
+                url: rule.template.replace("{id}", id),
+            });
+        }
+    }
+
+    refs.sort_by_key(|r| r.start);
+    refs
+}
@@ -0,0 +1,148 @@
+use crate::action::Action;
+use crate::app::AppMode;
+use crate::git::Repository;
+use crate::palette;
+use crate::replay;
+use crossterm::event::KeyCode;
+
+// what a key does in the main views, as an Action for App::dispatch. the defaults are the keys
+// the palette lists; hydra.bind (can be given more than once) adds or replaces one:
+//   hydra.bind = "log J = jump to HEAD"
+//   hydra.bind = "<f6> = fetch selected remote"
+// a view (status, log, branches, stashes, remote) in front limits it to that view, without one
+// it's bound in all of them. keys are written like in a --record file: the char, or <enter>,
+// <space>, <f6>... the command is a palette name, case doesn't matter. keys nothing is bound to
+// go to the view's own handling, so arrows, enter and the dialogs are never in here.
+
+const VIEWS: &[(&str, AppMode)] = &[
+    ("status", AppMode::Status),
+    ("log", AppMode::Log),
+    ("branches", AppMode::Branches),
+    ("stashes", AppMode::StashList),
+    ("remote", AppMode::RemoteOperations),
+];
+
+// every view, the stash list did nothing with most other keys so it only gets these
+const EVERYWHERE: &[(char, Action)] = &[
+    ('1', Action::GoStatus),
+    ('2', Action::GoLog),
+    ('3', Action::GoBranches),
+    ('4', Action::GoStashes),
+    ('5', Action::GoRemote),
+    ('q', Action::Quit),
+];
+
+// every view but the stash list
+const MOST_VIEWS: &[(KeyCode, Action)] = &[
+    (KeyCode::Char('N'), Action::Notifications),
+    (KeyCode::Char('E'), Action::ErrorLog),
+    (KeyCode::Char('K'), Action::Hooks),
+    (KeyCode::Char('%'), Action::Stats),
+    (KeyCode::Char('o'), Action::OpenOnWeb),
+    (KeyCode::F(5), Action::Refresh),
+];
+
+const IN_VIEW: &[(AppMode, char, Action)] = &[
+    (AppMode::Status, 'c', Action::Commit),
+    (AppMode::Status, 's', Action::Stash),
+    (AppMode::Status, 'w', Action::ToggleIgnoreEol),
+    (AppMode::Status, 'F', Action::ScopeStatus),
+    (AppMode::Status, 'D', Action::DiffBase),
+    (AppMode::Status, 'A', Action::EditAttributes),
+    (AppMode::Status, 'v', Action::StageLines),
+    (AppMode::Status, 'X', Action::StageModeOnly),
+    (AppMode::Status, 'T', Action::StageContentOnly),
+    (AppMode::Status, 'x', Action::ExportArchive),
+    (AppMode::Status, 'f', Action::BrowseFiles),
+    (AppMode::Status, 'Z', Action::Packages),
+    (AppMode::Log, 'G', Action::ToggleGraph),
+    (AppMode::Log, 'S', Action::SearchChanges),
+    (AppMode::Log, 'g', Action::JumpToCommit),
+    (AppMode::Log, 'H', Action::JumpToHead),
+    (AppMode::Log, 'R', Action::RestoreFile),
+    (AppMode::Log, 'C', Action::CheckoutCommit),
+    (AppMode::Log, ' ', Action::MarkCommit),
+    (AppMode::Log, 'i', Action::OpenIssue),
+    (AppMode::Log, 'I', Action::CopyIssueLinks),
+    (AppMode::Log, 'O', Action::RebaseOnto),
+    (AppMode::Log, 'x', Action::ExportArchive),
+    (AppMode::Log, 'f', Action::BrowseFiles),
+    (AppMode::Log, 'Z', Action::Packages),
+    (AppMode::Branches, 'n', Action::NewBranch),
+    (AppMode::Branches, 'f', Action::Gitflow),
+    (AppMode::Branches, 't', Action::SetUpstream),
+    (AppMode::Branches, 'L', Action::ListRemoteBranches),
+    (AppMode::Branches, 'P', Action::PushBranches),
+    (AppMode::Branches, 'T', Action::Stacks),
+    (AppMode::RemoteOperations, 'p', Action::Push),
+    (AppMode::RemoteOperations, 'u', Action::Pull),
+    (AppMode::RemoteOperations, 'b', Action::CreateBundle),
+    (AppMode::RemoteOperations, 'B', Action::FetchBundle),
+    (AppMode::RemoteOperations, 'a', Action::AuthStatus),
+    (AppMode::RemoteOperations, 'F', Action::SyncFork),
+];
+
+pub struct Keymap {
+    // later ones win, so hydra.bind goes after the defaults
+    bindings: Vec<(AppMode, KeyCode, Action)>,
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        let mut bindings = Vec::new();
+        for &(_, mode) in VIEWS {
+            bindings.extend(EVERYWHERE.iter().map(|&(c, action)| (mode, KeyCode::Char(c), action)));
+            if mode != AppMode::StashList {
+                bindings.extend(MOST_VIEWS.iter().map(|&(key, action)| (mode, key, action)));
+            }
+        }
+        bindings.extend(IN_VIEW.iter().map(|&(mode, c, action)| (mode, KeyCode::Char(c), action)));
+        Self { bindings }
+    }
+}
+
+impl Keymap {
+    // the defaults with the repo's hydra.bind on top, and what was wrong with the ones that didn't parse
+    pub fn from_config(repo: &Repository) -> (Self, Vec<String>) {
+        let mut keymap = Self::default();
+        let mut errors = Vec::new();
+        for spec in repo.config_values("hydra.bind") {
+            match parse(&spec) {
+                Ok(bindings) => keymap.bindings.extend(bindings),
+                Err(e) => errors.push(e),
+            }
+        }
+        (keymap, errors)
+    }
+
+    pub fn action(&self, mode: AppMode, key: KeyCode) -> Option<Action> {
+        self.bindings.iter().rev().find(|(m, k, _)| *m == mode && *k == key).map(|(_, _, action)| *action)
+    }
+}
+
+// "[<view>] <key> = <command>", one binding per view it's for
+pub fn parse(spec: &str) -> Result<Vec<(AppMode, KeyCode, Action)>, String> {
+    let (keys, command) = spec
+        .split_once('=')
+        .ok_or_else(|| format!("hydra.bind '{}' should be '[<view>] <key> = <command>'", spec))?;
+    let command = command.trim();
+    let action = palette::COMMANDS
+        .iter()
+        .find(|cmd| cmd.name.eq_ignore_ascii_case(command))
+        .map(|cmd| cmd.action)
+        .ok_or_else(|| format!("hydra.bind '{}': no command called '{}'", spec, command))?;
+
+    let words: Vec<&str> = keys.split_whitespace().collect();
+    let (modes, key) = match words.as_slice() {
+        [key] => (VIEWS.iter().map(|&(_, mode)| mode).collect(), *key),
+        [view, key] => match VIEWS.iter().find(|(name, _)| name.eq_ignore_ascii_case(view)) {
+            Some(&(_, mode)) => (vec![mode], *key),
+            None => return Err(format!("hydra.bind '{}': no view called '{}', it's one of status, log, branches, stashes, remote", spec, view)),
+        },
+        _ => return Err(format!("hydra.bind '{}' should be '[<view>] <key> = <command>'", spec)),
+    };
+    let key = replay::parse_key(key)
+        .filter(|k| k.modifiers.is_empty())
+        .ok_or_else(|| format!("hydra.bind '{}': '{}' is not a key (ctrl / alt ones can't be bound)", spec, key))?;
+    Ok(modes.into_iter().map(|mode| (mode, key.code, action)).collect())
+}
@@ -0,0 +1,50 @@
+use crate::git::{LineCounts, Repository};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+// the +N -M next to each file in the status list. counting patches every changed file, which on
+// a big change takes longer than a frame, so it runs on a thread after each status refresh and
+// the list shows whatever the last finished run found until the next one is in
+
+#[derive(Default)]
+pub struct LineCountCache {
+    counts: Arc<Mutex<LineCounts>>,
+    // one per refresh, a run that finishes after a newer one started is dropped
+    generation: Arc<AtomicU64>,
+}
+
+impl LineCountCache {
+    pub fn refresh(&self, repo: &Repository) {
+        let generation = self.generation.fetch_add(1, Ordering::SeqCst) + 1;
+        let git_dir = repo.git_dir().to_path_buf();
+        let work_tree = repo.workdir().map(|w| w.to_path_buf());
+        let scope = repo.status_scope().map(|s| s.to_string());
+        let ignore_eol = repo.ignore_eol();
+        let counts = Arc::clone(&self.counts);
+        let latest = Arc::clone(&self.generation);
+        std::thread::spawn(move || {
+            // git2 handles can't cross threads, the thread opens the repo again
+            let result = Repository::open_git_dir(&git_dir, work_tree.as_deref()).and_then(|mut repo| {
+                repo.set_status_scope(scope.as_deref())?;
+                repo.set_ignore_eol(ignore_eol);
+                repo.line_counts()
+            });
+            match result {
+                Ok(found) if latest.load(Ordering::SeqCst) == generation => {
+                    if let Ok(mut counts) = counts.lock() {
+                        *counts = found;
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => log::warn!("counting changed lines failed: {:#}", e),
+            }
+        });
+    }
+
+    // (added, removed), None until it's been counted
+    pub fn get(&self, path: &str, staged: bool) -> Option<(usize, usize)> {
+        let counts = self.counts.lock().ok()?;
+        let map = if staged { &counts.staged } else { &counts.unstaged };
+        map.get(path).copied()
+    }
+}
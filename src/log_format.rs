@@ -0,0 +1,190 @@
+use crate::git::{Commit, Repository};
+use crate::graph::Graph;
+use anyhow::{Result, bail};
+use chrono::{DateTime, Duration, FixedOffset, Local, NaiveDate, TimeZone};
+
+// what `git-hydra log` prints: the commits the log view lists, like `git log` shows them by
+// default, one line each with --oneline, or through a --format of placeholders:
+//   %H hash, %h short hash (core.abbrev long), %s subject, %b body, %an / %ae author name / email, %ad date,
+//   %n a newline, %% a %
+// --graph walks them in graph order with the lanes of the log view in front. --author (a part
+// of the name or email, any case) and --since leave commits out before --count counts them.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Layout {
+    Medium,
+    Oneline,
+    Format(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct Options {
+    pub layout: Layout,
+    pub graph: bool,
+    pub author: Option<String>,
+    pub since: Option<DateTime<FixedOffset>>,
+}
+
+// the same date git prints, "Mon May 6 14:03:12 2024 +0200"
+const DATE: &str = "%a %b %-d %H:%M:%S %Y %z";
+
+// commits are turned into Commits this many at a time while walking the graph
+const BATCH: usize = 256;
+
+pub fn render(repo: &Repository, count: usize, options: &Options) -> Result<String> {
+    let keep = |commit: &Commit| matches(commit, options);
+    let mut out = String::new();
+    if !options.graph {
+        for commit in repo.get_commits_where(count, keep)? {
+            push_lines(&mut out, &lines(repo, &commit, &options.layout), "", "");
+        }
+        return Ok(out);
+    }
+
+    let mut graph = Graph::default();
+    repo.update_graph(&mut graph)?;
+    let order = graph.top(usize::MAX);
+    let mut shown = 0;
+    'walk: for (batch, ids) in order.chunks(BATCH).enumerate() {
+        for (i, commit) in repo.commits_by_id(ids)?.into_iter().enumerate() {
+            if shown == count {
+                break 'walk;
+            }
+            if !keep(&commit) {
+                continue;
+            }
+            let position = batch * BATCH + i;
+            let cells = graph.cells(position);
+            let gap = graph.gap(position, cells.chars().count());
+            push_lines(&mut out, &lines(repo, &commit, &options.layout), &cells, &gap);
+            shown += 1;
+        }
+    }
+    Ok(out)
+}
+
+pub fn matches(commit: &Commit, options: &Options) -> bool {
+    if let Some(author) = &options.author {
+        let author = author.to_lowercase();
+        if !commit.author.to_lowercase().contains(&author) && !commit.author_email.to_lowercase().contains(&author) {
+            return false;
+        }
+    }
+    options.since.is_none_or(|since| commit.timestamp >= since)
+}
+
+// the first line after the commit's lanes, the rest after the ones going on below it
+fn push_lines(out: &mut String, lines: &[String], cells: &str, gap: &str) {
+    for (i, line) in lines.iter().enumerate() {
+        let prefix = if i == 0 { cells } else { gap };
+        out.push_str(format!("{}{}", prefix, line).trim_end());
+        out.push('\n');
+    }
+}
+
+pub fn lines(repo: &Repository, commit: &Commit, layout: &Layout) -> Vec<String> {
+    match layout {
+        Layout::Oneline => vec![format!("{} {}", repo.short_id(&commit.id), subject(commit))],
+        Layout::Format(spec) => format(repo, commit, spec).split('\n').map(|l| l.to_string()).collect(),
+        Layout::Medium => {
+            let mut lines = vec![format!("commit {}", commit.id)];
+            if commit.parents.len() > 1 {
+                let parents: Vec<String> = commit.parents.iter().map(|p| repo.short_id(p)).collect();
+                lines.push(format!("Merge: {}", parents.join(" ")));
+            }
+            lines.push(format!("Author: {} <{}>", commit.author, commit.author_email));
+            lines.push(format!("Date:   {}", commit.timestamp.format(DATE)));
+            lines.push(String::new());
+            lines.extend(commit.message.trim_end().lines().map(|l| format!("    {}", l)));
+            lines.push(String::new());
+            lines
+        }
+    }
+}
+
+// placeholders it doesn't know stay as they are
+pub fn format(repo: &Repository, commit: &Commit, spec: &str) -> String {
+    let mut out = String::new();
+    let mut chars = spec.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            out.push(c);
+            continue;
+        }
+        let rest: String = chars.clone().take(2).collect();
+        let (text, len) = match rest.as_str() {
+            "an" => (commit.author.clone(), 2),
+            "ae" => (commit.author_email.clone(), 2),
+            "ad" => (commit.timestamp.format(DATE).to_string(), 2),
+            _ => match rest.chars().next() {
+                Some('H') => (commit.id.clone(), 1),
+                Some('h') => (repo.short_id(&commit.id), 1),
+                Some('s') => (subject(commit).to_string(), 1),
+                Some('b') => (body(commit).to_string(), 1),
+                Some('n') => ("\n".to_string(), 1),
+                Some('%') => ("%".to_string(), 1),
+                _ => ("%".to_string(), 0),
+            },
+        };
+        out.push_str(&text);
+        for _ in 0..len {
+            chars.next();
+        }
+    }
+    out
+}
+
+fn subject(commit: &Commit) -> &str {
+    commit.message.lines().next().unwrap_or("").trim()
+}
+
+fn body(commit: &Commit) -> &str {
+    commit.message.split_once('\n').map_or("", |(_, body)| body.trim())
+}
+
+// a date ("2024-05-06"), a date and time ("2024-05-06 14:00", rfc 3339 too), or how long ago:
+// "2 weeks ago", "3.days.ago", "1 month", "yesterday"
+pub fn parse_since(text: &str, now: DateTime<Local>) -> Result<DateTime<FixedOffset>> {
+    let text = text.trim();
+    if let Ok(at) = DateTime::parse_from_rfc3339(text) {
+        return Ok(at);
+    }
+    for pattern in ["%Y-%m-%d %H:%M:%S", "%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M"] {
+        if let Ok(at) = chrono::NaiveDateTime::parse_from_str(text, pattern)
+            && let Some(at) = Local.from_local_datetime(&at).earliest()
+        {
+            return Ok(at.fixed_offset());
+        }
+    }
+    if let Ok(day) = NaiveDate::parse_from_str(text, "%Y-%m-%d")
+        && let Some(at) = day.and_hms_opt(0, 0, 0).and_then(|at| Local.from_local_datetime(&at).earliest())
+    {
+        return Ok(at.fixed_offset());
+    }
+
+    let words: Vec<String> = text.to_lowercase().replace('.', " ").split_whitespace().map(|w| w.to_string()).collect();
+    let words: Vec<&str> = words.iter().map(|w| w.as_str()).filter(|w| *w != "ago").collect();
+    let ago = match words.as_slice() {
+        ["yesterday"] => Some(Duration::days(1)),
+        [n, unit] => n.parse::<i64>().ok().and_then(|n| unit_length(unit).map(|d| d * n as i32)),
+        _ => None,
+    };
+    match ago {
+        Some(ago) => Ok((now - ago).fixed_offset()),
+        None => bail!("can't tell when '{}' is, try 2024-05-06 or \"2 weeks ago\"", text),
+    }
+}
+
+fn unit_length(unit: &str) -> Option<Duration> {
+    let unit = unit.strip_suffix('s').unwrap_or(unit);
+    Some(match unit {
+        "second" | "sec" => Duration::seconds(1),
+        "minute" | "min" => Duration::minutes(1),
+        "hour" => Duration::hours(1),
+        "day" => Duration::days(1),
+        "week" => Duration::weeks(1),
+        "month" => Duration::days(30),
+        "year" => Duration::days(365),
+        _ => return None,
+    })
+}
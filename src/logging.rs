@@ -0,0 +1,111 @@
+use chrono::Local;
+use log::{LevelFilter, Log, Metadata, Record};
+use std::collections::VecDeque;
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+
+use crate::session;
+
+// everything goes through the `log` macros into <state dir>/log (log.1 and log.2 are the older
+// ones) and into a buffer of recent lines for the log viewer. info and up by default, debug with
+// --verbose, which is what to ask for when a push or merge fails in a way the popup doesn't explain
+
+const MAX_FILE_SIZE: u64 = 1024 * 1024;
+const OLD_FILES: usize = 2;
+const RECENT_LIMIT: usize = 1000;
+
+static RECENT: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+static PATH: OnceLock<PathBuf> = OnceLock::new();
+
+struct FileLogger {
+    level: LevelFilter,
+    // the open log and how much is in it
+    file: Mutex<Option<(File, u64)>>,
+}
+
+impl Log for FileLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+        // continuation lines indented so every entry still starts with a timestamp
+        let message = record.args().to_string().replace('\n', "\n    ");
+        let line = format!("{} {:<5} {} {}", Local::now().format("%Y-%m-%dT%H:%M:%S%.3f"), record.level(), record.target(), message);
+
+        if let Ok(mut file) = self.file.lock() {
+            if let Some((handle, size)) = file.as_mut() {
+                if writeln!(handle, "{}", line).is_ok() {
+                    *size += line.len() as u64 + 1;
+                }
+                if *size > MAX_FILE_SIZE {
+                    *file = PATH.get().and_then(|path| rotate(path).ok());
+                }
+            }
+        }
+        if let Ok(mut recent) = RECENT.lock() {
+            recent.push_back(line);
+            while recent.len() > RECENT_LIMIT {
+                recent.pop_front();
+            }
+        }
+    }
+
+    fn flush(&self) {
+        if let Ok(mut file) = self.file.lock() {
+            if let Some((handle, _)) = file.as_mut() {
+                let _ = handle.flush();
+            }
+        }
+    }
+}
+
+// log -> log.1 -> log.2, dropping the oldest, and a fresh log
+fn rotate(path: &Path) -> std::io::Result<(File, u64)> {
+    let numbered = |n: usize| PathBuf::from(format!("{}.{}", path.display(), n));
+    for n in (1..OLD_FILES).rev() {
+        let _ = fs::rename(numbered(n), numbered(n + 1));
+    }
+    let _ = fs::rename(path, numbered(1));
+    open(path)
+}
+
+fn open(path: &Path) -> std::io::Result<(File, u64)> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    let size = file.metadata()?.len();
+    Ok((file, size))
+}
+
+// once at startup. without a usable state dir the log viewer still gets everything
+pub fn init(verbose: bool) {
+    let file = session::state_dir().and_then(|dir| {
+        fs::create_dir_all(&dir).ok()?;
+        let path = dir.join("log");
+        let file = match open(&path).ok()? {
+            (_, size) if size > MAX_FILE_SIZE => rotate(&path).ok()?,
+            file => file,
+        };
+        let _ = PATH.set(path);
+        Some(file)
+    });
+
+    let level = if verbose { LevelFilter::Debug } else { LevelFilter::Info };
+    let logger = FileLogger { level, file: Mutex::new(file) };
+    if log::set_logger(Box::leak(Box::new(logger))).is_ok() {
+        log::set_max_level(level);
+    }
+}
+
+pub fn path() -> Option<&'static Path> {
+    PATH.get().map(|p| p.as_path())
+}
+
+// oldest first
+pub fn recent() -> Vec<String> {
+    RECENT.lock().map(|recent| recent.iter().cloned().collect()).unwrap_or_default()
+}
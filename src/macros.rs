@@ -0,0 +1,57 @@
+use crate::action::Action;
+use crate::palette;
+
+// named lists of palette commands from hydra.macro (can be given more than once), e.g.
+//   hydra.macro = "sync: fetch selected remote; pull current branch"
+// they show up in the palette as "macro: sync" and run one command after the other, stopping at
+// the first that fails. the recorded kind (Q to start and stop, @ to replay) lives in app.rs,
+// it's just the keys.
+
+#[derive(Debug, Clone)]
+pub struct Macro {
+    pub name: String,
+    // what the palette shows
+    pub label: String,
+    pub steps: Vec<(String, Action)>,
+}
+
+// "<name>: <command>; <command>...", commands by their palette name, case doesn't matter
+pub fn parse(spec: &str) -> Result<Macro, String> {
+    let (name, steps) = spec
+        .split_once(':')
+        .ok_or_else(|| format!("hydra.macro '{}' should be '<name>: <command>; <command>...'", spec))?;
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(format!("hydra.macro '{}' has no name", spec));
+    }
+
+    let steps = steps
+        .split(';')
+        .map(str::trim)
+        .filter(|step| !step.is_empty())
+        .map(|step| {
+            palette::COMMANDS
+                .iter()
+                .find(|cmd| cmd.name.eq_ignore_ascii_case(step))
+                .map(|cmd| (cmd.name.to_string(), cmd.action))
+                .ok_or_else(|| format!("macro '{}': no command called '{}'", name, step))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    if steps.is_empty() {
+        return Err(format!("macro '{}' has no commands", name));
+    }
+    Ok(Macro { name: name.to_string(), label: format!("macro: {}", name), steps })
+}
+
+// every macro that parsed, plus what was wrong with the rest
+pub fn load(specs: &[String]) -> (Vec<Macro>, Vec<String>) {
+    let mut macros = Vec::new();
+    let mut errors = Vec::new();
+    for spec in specs {
+        match parse(spec) {
+            Ok(m) => macros.push(m),
+            Err(e) => errors.push(e),
+        }
+    }
+    (macros, errors)
+}
@@ -1,161 +1,434 @@
-use anyhow::Result;
-use clap::{Parser, Subcommand};
-use std::path::PathBuf;
-use std::{thread, time::Duration};
-use crossterm::{
-    execute,
-    terminal::{Clear, ClearType},
-};
-use std::io::{self};
-
-mod app;
-mod git;
-mod ui;
-
-use app::App;
-fn prompt_yes_no(prompt: &str) -> bool {
-    use std::io::{self, Write};
-    print!("{} ", prompt);
-    let _ = io::stdout().flush();
-    let mut s = String::new();
-    if io::stdin().read_line(&mut s).is_ok() {
-        matches!(s.trim().to_lowercase().as_str(), "y" | "yes")
-    } else {
-        false
-    }
-}
-
-fn prompt_string(prompt: &str) -> Option<String> {
-    use std::io::{self, Write};
-    print!("{} ", prompt);
-    let _ = io::stdout().flush();
-    let mut s = String::new();
-    if io::stdin().read_line(&mut s).is_ok() {
-        let v = s.trim().to_string();
-        if v.is_empty() {
-            None
-        } else {
-            Some(v)
-        }
-    } else {
-        None
-    }
-}
-
-fn bootstrap_repo_if_missing(path: &std::path::Path) -> anyhow::Result<()> {
-    match git::Repository::open(path) {
-        Ok(_) => return Ok(()),
-        Err(e) => {
-            eprintln!("No Git repository found at '{}': {}", path.display(), e);
-
-            if !prompt_yes_no("Initialize a new git repository here? [y/N]") {
-                anyhow::bail!("A Git repository is required. Aborting.");
-            }
-            let mut repo = git::Repository::init_repo(path)?;
-
-            if prompt_yes_no("Create an initial commit adding all files? [Y/n]") {
-                repo.initial_commit_all("Initial commit")?;
-            }
-
-            println!("Remote setup options:");
-            println!("  [e] Add existing remote URL");
-            println!("  [g] Create a new remote with GitHub CLI (gh)");
-            println!("  [s] Skip");
-            if let Some(choice) = prompt_string("Choose (e/g/s):") {
-                match choice.as_str() {
-                    "e" | "E" => {
-                        if let Some(url) = prompt_string(
-                            "Enter remote URL (e.g., https://github.com/user/repo.git):",
-                        ) {
-                            repo.add_remote("origin", &url)?;
-                            println!("Remote 'origin' added.");
-                        }
-                    }
-                    "g" | "G" => {
-                        let name = prompt_string("Enter new repository name (default: current folder):");
-                        let private = prompt_yes_no("Private repo? [y/N]");
-                        repo.try_create_remote_with_gh("origin", name.as_deref(), private)?;
-                        println!("Created remote with gh and set as origin.");
-                    }
-                    _ => {
-                        println!("Skipping remote setup.");
-                    }
-                }
-            }
-            Ok(())
-        }
-    }
-}
-
-
-#[derive(Parser)]
-#[command(name = "git-hydra")]
-#[command(about = "a tui git interface inspired by GitKraken.")]
-
-struct Cli{
-    // repo path (defaults to the current dir.)
-    #[arg(short, long)]
-    repo: Option<PathBuf>,
-
-    #[command(subcommand)]
-    command: Option<Commands>,
-}
-
-#[derive(Subcommand)]
-enum Commands {
-    Ui,
-    Status,
-    Log {
-        #[arg(short, long, default_value = "10")]
-        count: usize,
-    }
-}
-
-#[tokio::main]
-async fn main() -> Result<()> {
-    execute!(io::stdout(), Clear(ClearType::All))?;
-    print!(r"
-                
-                $$\   $$\           $$\                       $$\                    
-                \__|  $$ |          $$ |                      $$ |                   
-        $$$$$$\  $$\ $$$$$$\         $$$$$$$\  $$\   $$\  $$$$$$$ | $$$$$$\  $$$$$$\  
-        $$  __$$\ $$ |\_$$  _|$$$$$$\ $$  __$$\ $$ |  $$ |$$  __$$ |$$  __$$\ \____$$\ 
-        $$ /  $$ |$$ |  $$ |  \______|$$ |  $$ |$$ |  $$ |$$ /  $$ |$$ |  \__|$$$$$$$ |
-        $$ |  $$ |$$ |  $$ |$$\       $$ |  $$ |$$ |  $$ |$$ |  $$ |$$ |     $$  __$$ |
-        \$$$$$$$ |$$ |  \$$$$  |      $$ |  $$ |\$$$$$$$ |\$$$$$$$ |$$ |     \$$$$$$$ |
-        \____$$ |\__|   \____/       \__|  \__| \____$$ | \_______|\__|      \_______|
-        $$\   $$ |                              $$\   $$ |                             
-        \$$$$$$  |                              \$$$$$$  |                             
-        \______/                                \______/                              
-
-            ");
-
-    thread::sleep(Duration::from_millis(2000)); 
-    execute!(io::stdout(), Clear(ClearType::All))?;
-    
-    let cli = Cli::parse();
-
-    let repo_path = cli.repo.unwrap_or_else(|| PathBuf::from("."));
-
-    match cli.command {
-        Some(Commands::Ui) | None => {
-            bootstrap_repo_if_missing(&repo_path)?;
-            let mut app = App::new(repo_path)?;
-            app.run().await?;
-        }
-        Some(Commands::Status) => {
-            let repo = git::Repository::open(&repo_path)?;
-            let status = repo.status()?;
-            println!("{}", status);
-        }
-        Some(Commands::Log { count}) => {
-            let repo = git ::Repository::open(&repo_path)?;
-            let commits = repo.get_commits(count)?;
-            for commit in commits {
-                println!("{:?}", commit);
-            }
-        }
-    }
-
-    Ok(())
+use anyhow::Result;
+use clap::{Parser, Subcommand};
+use std::path::PathBuf;
+use std::{thread, time::Duration};
+use crossterm::{
+    execute,
+    terminal::{Clear, ClearType},
+};
+use std::io::{self, Write};
+
+mod action;
+mod announce;
+mod app;
+mod archive;
+mod auth;
+mod auto_fetch;
+mod avatars;
+mod backend;
+mod branch_names;
+mod clipboard;
+mod colors;
+mod commands;
+mod completions;
+mod events;
+mod files;
+mod git;
+mod graph;
+mod help;
+mod highlight;
+mod input_history;
+mod issues;
+mod keymap;
+mod line_counts;
+mod log_format;
+mod logging;
+mod macros;
+mod maintenance;
+mod notifications;
+mod packages;
+mod palette;
+mod pattern;
+mod perf;
+mod protection;
+mod release;
+mod release_notes;
+mod replay;
+mod secrets;
+mod session;
+mod snapshot;
+mod stats;
+mod status_format;
+#[cfg(test)]
+mod tests;
+mod ui;
+mod watch;
+
+use app::App;
+fn prompt_yes_no(prompt: &str) -> bool {
+    use std::io::{self, Write};
+    print!("{} ", prompt);
+    let _ = io::stdout().flush();
+    let mut s = String::new();
+    if io::stdin().read_line(&mut s).is_ok() {
+        matches!(s.trim().to_lowercase().as_str(), "y" | "yes")
+    } else {
+        false
+    }
+}
+
+fn prompt_string(prompt: &str) -> Option<String> {
+    use std::io::{self, Write};
+    print!("{} ", prompt);
+    let _ = io::stdout().flush();
+    let mut s = String::new();
+    if io::stdin().read_line(&mut s).is_ok() {
+        let v = s.trim().to_string();
+        if v.is_empty() {
+            None
+        } else {
+            Some(v)
+        }
+    } else {
+        None
+    }
+}
+
+fn bootstrap_repo_if_missing(path: &std::path::Path) -> anyhow::Result<()> {
+    match git::Repository::open(path) {
+        Ok(_) => return Ok(()),
+        Err(e) => {
+            eprintln!("No Git repository found at '{}': {}", path.display(), e);
+
+            if !prompt_yes_no("Initialize a new git repository here? [y/N]") {
+                anyhow::bail!("A Git repository is required. Aborting.");
+            }
+            let mut repo = git::Repository::init_repo(path)?;
+
+            if prompt_yes_no("Create an initial commit adding all files? [Y/n]") {
+                repo.initial_commit_all("Initial commit")?;
+            }
+
+            println!("Remote setup options:");
+            println!("  [e] Add existing remote URL");
+            println!("  [g] Create a new remote with GitHub CLI (gh)");
+            println!("  [s] Skip");
+            if let Some(choice) = prompt_string("Choose (e/g/s):") {
+                match choice.as_str() {
+                    "e" | "E" => {
+                        if let Some(url) = prompt_string(
+                            "Enter remote URL (e.g., https://github.com/user/repo.git):",
+                        ) {
+                            repo.add_remote("origin", &url)?;
+                            println!("Remote 'origin' added.");
+                        }
+                    }
+                    "g" | "G" => {
+                        let name = prompt_string("Enter new repository name (default: current folder):");
+                        let private = prompt_yes_no("Private repo? [y/N]");
+                        repo.try_create_remote_with_gh("origin", name.as_deref(), private)?;
+                        println!("Created remote with gh and set as origin.");
+                    }
+                    _ => {
+                        println!("Skipping remote setup.");
+                    }
+                }
+            }
+            Ok(())
+        }
+    }
+}
+
+
+fn open_repo(cli: &Cli, repo_path: &std::path::Path) -> anyhow::Result<git::Repository> {
+    if let Some(git_dir) = &cli.git_dir {
+        return git::Repository::open_git_dir(git_dir, cli.work_tree.as_deref());
+    }
+    let mut repo = git::Repository::open(repo_path)?;
+    if let Some(work_tree) = &cli.work_tree {
+        repo.set_work_tree(work_tree)?;
+    }
+    Ok(repo)
+}
+
+#[derive(Parser)]
+#[command(name = "git-hydra", disable_help_subcommand = true)]
+#[command(about = "a tui git interface inspired by GitKraken.")]
+struct Cli{
+    // repo path (defaults to the current dir.)
+    #[arg(short, long)]
+    repo: Option<PathBuf>,
+
+    // only compute status under this path, for big monorepos
+    #[arg(long)]
+    scope: Option<String>,
+
+    // open this .git directory directly, for bare repos or a git dir kept apart from its files
+    #[arg(long)]
+    git_dir: Option<PathBuf>,
+
+    // the files that go with --git-dir (or instead of the discovered ones)
+    #[arg(long)]
+    work_tree: Option<PathBuf>,
+
+    // debug detail in the log file (remote messages, git commands run, credentials tried)
+    #[arg(short, long)]
+    verbose: bool,
+
+    // write every key pressed to this file, with its timing, for --replay
+    #[arg(long)]
+    record: Option<PathBuf>,
+
+    // press the keys of a --record file again, at the pace they were recorded
+    #[arg(long)]
+    replay: Option<PathBuf>,
+
+    // say what changes on screen, for screen readers: "title" for the terminal title, or a file
+    #[arg(long)]
+    announce: Option<String>,
+
+    // start in offline mode: nothing goes over the network (also hydra.offline)
+    #[arg(long)]
+    offline: bool,
+
+    #[command(subcommand)]
+    command: Option<Commands>,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    Ui,
+    Status {
+        // XY and the path for each changed file, like `git status --short`
+        #[arg(short, long)]
+        short: bool,
+        // the same, after a `## branch...upstream [ahead, behind]` line, and stable for scripts
+        #[arg(long, conflicts_with = "short")]
+        porcelain: bool,
+        // entries end with NUL instead of a newline, paths unquoted. --porcelain without --short
+        #[arg(short = 'z')]
+        nul: bool,
+    },
+    Log {
+        #[arg(short, long, default_value = "10")]
+        count: usize,
+        // hash and subject, a line each
+        #[arg(long, conflicts_with = "format")]
+        oneline: bool,
+        // placeholders like git's: %h %H %s %b %an %ae %ad %n
+        #[arg(long)]
+        format: Option<String>,
+        // in graph order with the lanes in front
+        #[arg(long)]
+        graph: bool,
+        // part of the author's name or email
+        #[arg(long)]
+        author: Option<String>,
+        // "2024-05-06", "2 weeks ago"
+        #[arg(long)]
+        since: Option<String>,
+    },
+    // draw one view without a terminal, to --out or stdout
+    Snapshot {
+        #[arg(long, value_enum, default_value = "status")]
+        view: snapshot::View,
+        // .svg writes an svg unless --format says otherwise
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+        #[arg(long, value_enum)]
+        format: Option<snapshot::Format>,
+        #[arg(long, default_value = "120")]
+        width: u16,
+        #[arg(long, default_value = "40")]
+        height: u16,
+    },
+    // a completion script for the shell, e.g. `git-hydra completions bash > /etc/bash_completion.d/git-hydra`
+    Completions {
+        shell: completions::Shell,
+    },
+    // the manual page for a view (status, log, branches, ...), the list of them without one
+    Help {
+        #[arg(value_parser = help::topic_parser())]
+        topic: Option<String>,
+        // all of it as a man page: `git-hydra help --man > git-hydra.1`
+        #[arg(long)]
+        man: bool,
+    },
+    // a few lines of live status (branch, ahead / behind, changed files, last commit) for a pane
+    Watch {
+        // seconds between full status checks, HEAD and the index are looked at more often
+        #[arg(short, long, default_value = "2")]
+        interval: u64,
+    },
+    // just staging and committing: status and the commit dialog, quits after the commit
+    Commit {
+        // what the commit dialog starts with
+        #[arg(short, long)]
+        message: Option<String>,
+    },
+    // markdown release notes for a range ("v1.2.0..HEAD", the last tag..HEAD without one)
+    ReleaseNotes {
+        range: Option<String>,
+        #[arg(short, long)]
+        out: Option<PathBuf>,
+    },
+    // branch or remote names for the completion scripts
+    #[command(hide = true)]
+    Complete {
+        what: completions::Candidates,
+    },
+}
+
+#[tokio::main]
+async fn main() -> Result<()> {
+    let cli = Cli::parse();
+    // these go to stdout, no splash in front of them
+    if !matches!(cli.command, Some(Commands::Snapshot { .. } | Commands::Completions { .. } | Commands::Complete { .. } | Commands::Help { .. } | Commands::ReleaseNotes { .. } | Commands::Commit { .. } | Commands::Log { .. } | Commands::Watch { .. }))
+        && !matches!(cli.command, Some(Commands::Status { short, porcelain, nul }) if short || porcelain || nul)
+    {
+        execute!(io::stdout(), Clear(ClearType::All))?;
+        print!(r"
+                
+                $$\   $$\           $$\                       $$\                    
+                \__|  $$ |          $$ |                      $$ |                   
+        $$$$$$\  $$\ $$$$$$\         $$$$$$$\  $$\   $$\  $$$$$$$ | $$$$$$\  $$$$$$\  
+        $$  __$$\ $$ |\_$$  _|$$$$$$\ $$  __$$\ $$ |  $$ |$$  __$$ |$$  __$$\ \____$$\ 
+        $$ /  $$ |$$ |  $$ |  \______|$$ |  $$ |$$ |  $$ |$$ /  $$ |$$ |  \__|$$$$$$$ |
+        $$ |  $$ |$$ |  $$ |$$\       $$ |  $$ |$$ |  $$ |$$ |  $$ |$$ |     $$  __$$ |
+        \$$$$$$$ |$$ |  \$$$$  |      $$ |  $$ |\$$$$$$$ |\$$$$$$$ |$$ |     \$$$$$$$ |
+        \____$$ |\__|   \____/       \__|  \__| \____$$ | \_______|\__|      \_______|
+        $$\   $$ |                              $$\   $$ |                             
+        \$$$$$$  |                              \$$$$$$  |                             
+        \______/                                \______/                              
+
+            ");
+
+        thread::sleep(Duration::from_millis(2000));
+        execute!(io::stdout(), Clear(ClearType::All))?;
+    }
+    logging::init(cli.verbose);
+
+    let repo_path = cli.repo.clone().unwrap_or_else(|| PathBuf::from("."));
+    log::info!("git-hydra {} starting in {}", env!("CARGO_PKG_VERSION"), repo_path.display());
+
+    match cli.command {
+        Some(Commands::Ui) | None => {
+            if cli.git_dir.is_none() {
+                bootstrap_repo_if_missing(&repo_path)?;
+            }
+            let mut app = App::new(open_repo(&cli, &repo_path)?)?;
+            if let Some(scope) = &cli.scope {
+                app.repo.set_status_scope(Some(scope))?;
+            }
+            if let Some(path) = &cli.replay {
+                app.replay = Some(replay::Replay::load(path)?);
+            }
+            if let Some(path) = &cli.record {
+                let size = crossterm::terminal::size().unwrap_or((80, 24));
+                app.recorder = Some(replay::Recorder::create(path, size)?);
+            }
+            if let Some(target) = &cli.announce {
+                app.announcer = Some(announce::Announcer::open(target)?);
+            }
+            if cli.offline {
+                app.set_offline(true);
+            }
+            app.run().await?;
+        }
+        Some(Commands::Watch { interval }) => {
+            let mut repo = open_repo(&cli, &repo_path)?;
+            repo.set_status_scope(cli.scope.as_deref())?;
+            watch::run(&repo, Duration::from_secs(interval.max(1)))?;
+        }
+        Some(Commands::Commit { ref message }) => {
+            let mut app = App::new(open_repo(&cli, &repo_path)?)?;
+            if let Some(scope) = &cli.scope {
+                app.repo.set_status_scope(Some(scope))?;
+            }
+            app.start_quick_commit(message.as_deref());
+            app.run().await?;
+            match &app.committed {
+                Some(commit) => println!("{}", commit),
+                None => anyhow::bail!("nothing committed"),
+            }
+        }
+        Some(Commands::Status { short, porcelain, nul }) => {
+            let mut repo = open_repo(&cli, &repo_path)?;
+            repo.set_status_scope(cli.scope.as_deref())?;
+            let status = repo.status()?;
+            let format = match (short, porcelain || nul) {
+                (true, _) => status_format::Format::Short,
+                (_, true) => status_format::Format::Porcelain,
+                _ => {
+                    println!("{}", status);
+                    return Ok(());
+                }
+            };
+            let conflicts = repo.index_conflicts()?;
+            print!("{}", status_format::render(&status, &conflicts, format, nul));
+            // 1 when dirty, for prompts and scripts
+            if status_format::is_dirty(&status, &conflicts) {
+                io::stdout().flush()?;
+                std::process::exit(1);
+            }
+        }
+        Some(Commands::Log { count, oneline, ref format, graph, ref author, ref since }) => {
+            let repo = open_repo(&cli, &repo_path)?;
+            if let Some(branch) = repo.unborn_branch() {
+                println!("no commits on {} yet", branch);
+            }
+            let layout = match format {
+                Some(spec) => log_format::Layout::Format(spec.clone()),
+                None if oneline => log_format::Layout::Oneline,
+                None => log_format::Layout::Medium,
+            };
+            let since = since.as_deref().map(|s| log_format::parse_since(s, chrono::Local::now())).transpose()?;
+            let options = log_format::Options { layout, graph, author: author.clone(), since };
+            print!("{}", log_format::render(&repo, count, &options)?);
+        }
+        Some(Commands::Snapshot { view, ref out, format, width, height }) => {
+            let mut app = App::new(open_repo(&cli, &repo_path)?)?;
+            if let Some(scope) = &cli.scope {
+                app.repo.set_status_scope(Some(scope))?;
+            }
+            // the view as it is after the recorded keys, without waiting between them
+            let keys = match &cli.replay {
+                Some(path) => replay::Replay::load(path)?.into_keys(),
+                None => Vec::new(),
+            };
+            let buffer = snapshot::capture(&mut app, view, &keys, width, height)?;
+            let svg = format.map(|f| f == snapshot::Format::Svg)
+                .unwrap_or_else(|| out.as_ref().is_some_and(|p| p.extension().is_some_and(|e| e == "svg")));
+            let rendered = if svg { snapshot::svg(&buffer) } else { snapshot::text(&buffer) };
+            match out {
+                Some(path) => std::fs::write(path, rendered)?,
+                None => print!("{}", rendered),
+            }
+        }
+        Some(Commands::Completions { shell }) => {
+            use clap::CommandFactory;
+            print!("{}", completions::generate(shell, &mut Cli::command()));
+        }
+        Some(Commands::Help { topic, man }) => match (topic.as_deref().and_then(help::find), man) {
+            (_, true) => print!("{}", help::man()),
+            (Some(page), false) => print!("{}", help::PAGES[page].text),
+            (None, false) => {
+                println!("git-hydra help <topic>, or F1 in the app. topics:");
+                for page in help::PAGES {
+                    println!("  {}", page.topic);
+                }
+            }
+        },
+        Some(Commands::ReleaseNotes { ref range, ref out }) => {
+            let repo = open_repo(&cli, &repo_path)?;
+            let range = range.clone().unwrap_or_else(|| release_notes::default_range(&repo));
+            let (notes, errors) = release_notes::generate(&repo, &range)?;
+            for error in errors {
+                eprintln!("{}", error);
+            }
+            match out {
+                Some(path) => std::fs::write(path, notes)?,
+                None => print!("{}", notes),
+            }
+        }
+        Some(Commands::Complete { what }) => {
+            if let Ok(repo) = open_repo(&cli, &repo_path) {
+                for name in completions::candidates(&repo, what) {
+                    println!("{}", name);
+                }
+            }
+        }
+    }
+
+    Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,36 @@
+// the maintenance menu: git's housekeeping commands, run with the git binary in the background
+// (their output goes to the command panel) and .git/objects measured before and after
+
+pub struct Task {
+    pub name: &'static str,
+    pub args: &'static [&'static str],
+    pub description: &'static str,
+}
+
+pub const TASKS: &[Task] = &[
+    Task {
+        name: "gc",
+        args: &["gc"],
+        description: "repacks everything, expires old reflog entries and prunes objects unreachable for two weeks",
+    },
+    Task {
+        name: "repack",
+        args: &["repack", "-a", "-d"],
+        description: "puts every object into a single pack and drops the packs and loose objects it replaces",
+    },
+    Task {
+        name: "prune",
+        args: &["prune", "--expire=2.weeks.ago", "--verbose"],
+        description: "deletes loose objects no ref or reflog can reach that are older than two weeks, like gc does",
+    },
+    Task {
+        name: "expire reflogs",
+        args: &["reflog", "expire", "--all"],
+        description: "drops reflog entries older than gc.reflogExpire (90 days, 30 if unreachable), so prune can take what they held on to",
+    },
+    Task {
+        name: "maintenance",
+        args: &["maintenance", "run", "--task=commit-graph", "--task=loose-objects", "--task=incremental-repack"],
+        description: "the lighter `git maintenance` tasks: writes the commit-graph, packs loose objects and merges small packs",
+    },
+];
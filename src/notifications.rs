@@ -0,0 +1,83 @@
+use chrono::{DateTime, Local};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+// toasts in the footer, one at a time, plus a history of everything for the session
+
+const TOAST_DURATION: Duration = Duration::from_secs(4);
+// when more are waiting each one gets less screen time
+const QUEUED_TOAST_DURATION: Duration = Duration::from_millis(1500);
+const HISTORY_LIMIT: usize = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Level {
+    Info,
+    Success,
+    Error,
+}
+
+#[derive(Debug, Clone)]
+pub struct Notification {
+    pub level: Level,
+    pub message: String,
+    pub at: DateTime<Local>,
+}
+
+pub struct Notifications {
+    queue: VecDeque<Notification>,
+    current: Option<(Notification, Instant)>,
+    history: VecDeque<Notification>,
+}
+
+impl Notifications {
+    pub fn new() -> Self {
+        Self {
+            queue: VecDeque::new(),
+            current: None,
+            history: VecDeque::new(),
+        }
+    }
+
+    pub fn push(&mut self, level: Level, message: impl Into<String>) {
+        let notification = Notification {
+            level,
+            message: message.into(),
+            at: Local::now(),
+        };
+
+        self.history.push_front(notification.clone());
+        self.history.truncate(HISTORY_LIMIT);
+
+        // errors already get the popup, they only go to the history
+        if level != Level::Error {
+            self.queue.push_back(notification);
+            self.tick();
+        }
+    }
+
+    // called every loop iteration, moves on to the next toast when the current one is done
+    pub fn tick(&mut self) {
+        let limit = if self.queue.is_empty() { TOAST_DURATION } else { QUEUED_TOAST_DURATION };
+        let expired = self.current.as_ref().is_none_or(|(_, shown)| shown.elapsed() >= limit);
+
+        if expired {
+            self.current = self.queue.pop_front().map(|n| (n, Instant::now()));
+        }
+    }
+
+    pub fn current(&self) -> Option<&Notification> {
+        self.current.as_ref().map(|(n, _)| n)
+    }
+
+    pub fn queued(&self) -> usize {
+        self.queue.len()
+    }
+
+    pub fn dismiss(&mut self) {
+        self.current = self.queue.pop_front().map(|n| (n, Instant::now()));
+    }
+
+    pub fn history(&self) -> &VecDeque<Notification> {
+        &self.history
+    }
+}
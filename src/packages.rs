@@ -0,0 +1,177 @@
+use std::path::Path;
+
+// the packages of a monorepo, from the workspace manifests at the top of the worktree: the
+// members of a Cargo.toml [workspace] and the "workspaces" of a package.json (an array, or yarn's
+// { "packages": [...] }). members can use * for one directory level, like both tools allow.
+// picking one (Z) scopes status to its directory and the log to commits that touch it.
+// there's no toml or json parser in here, it reads just enough of both to find those lists.
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Package {
+    pub name: String,
+    // relative to the worktree, with / and without a trailing one
+    pub path: String,
+    // "cargo" or "npm"
+    pub kind: &'static str,
+}
+
+pub fn detect(workdir: &Path) -> Vec<Package> {
+    let mut packages = Vec::new();
+    if let Ok(manifest) = std::fs::read_to_string(workdir.join("Cargo.toml")) {
+        let members = table(&manifest, "workspace").map(|t| string_array(t, "members")).unwrap_or_default();
+        let excluded = table(&manifest, "workspace").map(|t| string_array(t, "exclude")).unwrap_or_default();
+        for path in expand(workdir, &members, "Cargo.toml") {
+            if excluded.contains(&path) {
+                continue;
+            }
+            let name = std::fs::read_to_string(workdir.join(&path).join("Cargo.toml"))
+                .ok()
+                .and_then(|m| table(&m, "package").and_then(|t| toml_string(t, "name")));
+            packages.push(Package { name: name.unwrap_or_else(|| dir_name(&path)), path, kind: "cargo" });
+        }
+    }
+    if let Ok(manifest) = std::fs::read_to_string(workdir.join("package.json")) {
+        for path in expand(workdir, &json_workspaces(&manifest), "package.json") {
+            let name = std::fs::read_to_string(workdir.join(&path).join("package.json")).ok().and_then(|m| json_string(&m, "name"));
+            packages.push(Package { name: name.unwrap_or_else(|| dir_name(&path)), path, kind: "npm" });
+        }
+    }
+    packages.sort_by(|a, b| a.path.cmp(&b.path));
+    packages.dedup_by(|a, b| a.path == b.path);
+    packages
+}
+
+fn dir_name(path: &str) -> String {
+    path.rsplit('/').next().unwrap_or(path).to_string()
+}
+
+// the member patterns as directories that have the manifest
+fn expand(workdir: &Path, patterns: &[String], manifest: &str) -> Vec<String> {
+    let mut found = Vec::new();
+    for pattern in patterns {
+        let pattern = pattern.trim().trim_start_matches("./").trim_end_matches('/');
+        let mut dirs = vec![String::new()];
+        for segment in pattern.split('/').filter(|s| !s.is_empty()) {
+            dirs = dirs
+                .into_iter()
+                .flat_map(|dir| {
+                    let join = |name: &str| if dir.is_empty() { name.to_string() } else { format!("{}/{}", dir, name) };
+                    if !segment.contains('*') {
+                        return vec![join(segment)];
+                    }
+                    let Ok(entries) = std::fs::read_dir(workdir.join(&dir)) else {
+                        return Vec::new();
+                    };
+                    let mut names: Vec<String> = entries
+                        .flatten()
+                        .filter(|e| e.path().is_dir())
+                        .filter_map(|e| e.file_name().to_str().map(|n| n.to_string()))
+                        .filter(|n| !n.starts_with('.') && segment_match(segment, n))
+                        .collect();
+                    names.sort();
+                    names.iter().map(|n| join(n)).collect()
+                })
+                .collect();
+        }
+        found.extend(dirs.into_iter().filter(|d| !d.is_empty() && workdir.join(d).join(manifest).is_file()));
+    }
+    found
+}
+
+// one * in a path segment, "crate-*" or "*"
+fn segment_match(pattern: &str, name: &str) -> bool {
+    match pattern.split_once('*') {
+        Some((prefix, suffix)) => name.len() >= prefix.len() + suffix.len() && name.starts_with(prefix) && name.ends_with(suffix),
+        None => pattern == name,
+    }
+}
+
+// the lines of a [name] table, up to the next table header
+fn table<'a>(toml: &'a str, name: &str) -> Option<&'a str> {
+    let header = format!("[{}]", name);
+    let mut start = None;
+    let mut offset = 0;
+    for line in toml.split_inclusive('\n') {
+        let trimmed = line.trim();
+        match start {
+            None if trimmed == header => start = Some(offset + line.len()),
+            Some(start) if trimmed.starts_with('[') && !trimmed.contains('=') => return Some(&toml[start..offset]),
+            _ => {}
+        }
+        offset += line.len();
+    }
+    start.map(|start| &toml[start..])
+}
+
+// key = ["a", "b"], over as many lines as it takes
+fn string_array(table: &str, key: &str) -> Vec<String> {
+    let mut offset = 0;
+    for line in table.split_inclusive('\n') {
+        if line.trim_start().strip_prefix(key).is_some_and(|rest| rest.trim_start().starts_with('=')) {
+            let rest = &table[offset..];
+            let Some(open) = rest.find('[') else {
+                break;
+            };
+            let close = rest[open..].find(']').map_or(rest.len(), |at| at + open);
+            return quoted(&rest[open + 1..close]);
+        }
+        offset += line.len();
+    }
+    Vec::new()
+}
+
+fn toml_string(table: &str, key: &str) -> Option<String> {
+    table.lines().find_map(|line| {
+        let rest = line.trim_start().strip_prefix(key)?.trim_start().strip_prefix('=')?;
+        quoted(rest).into_iter().next()
+    })
+}
+
+// every "..." (or '...') in order, comments after # left out
+fn quoted(text: &str) -> Vec<String> {
+    let mut values = Vec::new();
+    for line in text.lines() {
+        let mut rest = line;
+        while let Some(open) = rest.find(['"', '\'']) {
+            if rest[..open].contains('#') {
+                break;
+            }
+            let quote = rest.as_bytes()[open] as char;
+            let Some(len) = rest[open + 1..].find(quote) else {
+                break;
+            };
+            values.push(rest[open + 1..open + 1 + len].to_string());
+            rest = &rest[open + len + 2..];
+        }
+    }
+    values
+}
+
+// "workspaces": [...] or "workspaces": { "packages": [...] }
+fn json_workspaces(json: &str) -> Vec<String> {
+    let Some(at) = json.find("\"workspaces\"") else {
+        return Vec::new();
+    };
+    let rest = json[at + "\"workspaces\"".len()..].trim_start().trim_start_matches(':').trim_start();
+    let rest = match rest.strip_prefix('{') {
+        Some(object) => match object.find("\"packages\"") {
+            Some(at) => &object[at + "\"packages\"".len()..],
+            None => return Vec::new(),
+        },
+        None => rest,
+    };
+    let (Some(open), Some(close)) = (rest.find('['), rest.find(']')) else {
+        return Vec::new();
+    };
+    if close < open {
+        return Vec::new();
+    }
+    quoted(&rest[open + 1..close])
+}
+
+// the first "key": "value", which for a package.json's name is the top-level one
+fn json_string(json: &str, key: &str) -> Option<String> {
+    let at = json.find(&format!("\"{}\"", key))?;
+    let rest = json[at + key.len() + 2..].trim_start().strip_prefix(':')?.trim_start().strip_prefix('"')?;
+    Some(rest[..rest.find('"')?].to_string())
+}
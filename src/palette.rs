@@ -0,0 +1,112 @@
+use crate::action::Action;
+
+// the `:` / ctrl-p command palette: every action by name, fuzzy matched
+
+pub struct PaletteCommand<'a> {
+    pub action: Action,
+    pub name: &'a str,
+    // the direct key, if there is one
+    pub key: &'a str,
+}
+
+pub const COMMANDS: &[PaletteCommand<'static>] = &[
+    PaletteCommand { action: Action::GoStatus, name: "go to status", key: "1" },
+    PaletteCommand { action: Action::GoLog, name: "go to log", key: "2" },
+    PaletteCommand { action: Action::GoBranches, name: "go to branches", key: "3" },
+    PaletteCommand { action: Action::GoStashes, name: "go to stashes", key: "4" },
+    PaletteCommand { action: Action::GoRemote, name: "go to remote", key: "5" },
+    PaletteCommand { action: Action::StageAll, name: "stage all changes", key: "" },
+    PaletteCommand { action: Action::Commit, name: "commit staged changes", key: "c" },
+    PaletteCommand { action: Action::Stash, name: "stash changes", key: "s" },
+    PaletteCommand { action: Action::NewBranch, name: "new branch", key: "n" },
+    PaletteCommand { action: Action::Gitflow, name: "gitflow: start / finish feature, release, hotfix", key: "f" },
+    PaletteCommand { action: Action::SetUpstream, name: "set upstream (tracking branch) of branch", key: "t" },
+    PaletteCommand { action: Action::Fetch, name: "fetch selected remote", key: "" },
+    PaletteCommand { action: Action::ListRemoteBranches, name: "list remote branches without fetching (ls-remote)", key: "L" },
+    PaletteCommand { action: Action::Push, name: "push current branch", key: "p" },
+    PaletteCommand { action: Action::PushBranches, name: "push marked branches / refspecs", key: "P" },
+    PaletteCommand { action: Action::Pull, name: "pull current branch", key: "u" },
+    PaletteCommand { action: Action::SyncFork, name: "sync fork with upstream", key: "F" },
+    PaletteCommand { action: Action::AuthStatus, name: "auth status: ssh-agent, gpg-agent, signing key", key: "a" },
+    PaletteCommand { action: Action::CreateBundle, name: "create bundle", key: "b" },
+    PaletteCommand { action: Action::FetchBundle, name: "fetch from bundle", key: "B" },
+    PaletteCommand { action: Action::ToggleGraph, name: "toggle commit graph", key: "G" },
+    PaletteCommand { action: Action::ToggleIgnoreEol, name: "toggle ignoring line ending (CRLF) changes in diffs", key: "w" },
+    PaletteCommand { action: Action::TogglePerfHud, name: "toggle performance hud", key: "F12" },
+    PaletteCommand { action: Action::ToggleOffline, name: "toggle offline mode: no fetch, push, pull or downloads", key: "" },
+    PaletteCommand { action: Action::SearchChanges, name: "search commits by content (pickaxe)", key: "S" },
+    PaletteCommand { action: Action::JumpToCommit, name: "jump to commit / branch / tag", key: "g" },
+    PaletteCommand { action: Action::JumpToHead, name: "jump to HEAD", key: "H" },
+    PaletteCommand { action: Action::RestoreFile, name: "restore a file from selected commit", key: "R" },
+    PaletteCommand { action: Action::MarkCommit, name: "mark commit to diff against another (git diff A..B)", key: "space" },
+    PaletteCommand { action: Action::CheckoutCommit, name: "checkout selected commit (detached HEAD)", key: "C" },
+    PaletteCommand { action: Action::RebaseOnto, name: "rebase --onto: move commits to another base", key: "O" },
+    PaletteCommand { action: Action::Stacks, name: "branch stacks / restack", key: "T" },
+    PaletteCommand { action: Action::OpenIssue, name: "open issue referenced by commit", key: "i" },
+    PaletteCommand { action: Action::CopyIssueLinks, name: "copy issue links of commit", key: "I" },
+    PaletteCommand { action: Action::ScopeStatus, name: "scope status to a directory", key: "F" },
+    PaletteCommand { action: Action::DiffBase, name: "diff against a ref: compare files with origin/main or a tag", key: "D" },
+    PaletteCommand { action: Action::Packages, name: "packages: limit status and log to one workspace package", key: "Z" },
+    PaletteCommand { action: Action::EditAttributes, name: "edit .gitattributes", key: "A" },
+    PaletteCommand { action: Action::Hooks, name: "manage git hooks", key: "K" },
+    PaletteCommand { action: Action::Stats, name: "stats: contribution calendar and authors", key: "%" },
+    PaletteCommand { action: Action::LargeObjects, name: "find large objects (what makes the repo big)", key: "" },
+    PaletteCommand { action: Action::Health, name: "health check: fsck, broken refs, leftover lock files", key: "" },
+    PaletteCommand { action: Action::ReleaseNotes, name: "release notes: changelog of a range by commit type", key: "" },
+    PaletteCommand { action: Action::NewRelease, name: "new release: tag the next semver version with its notes", key: "" },
+    PaletteCommand { action: Action::Maintenance, name: "maintenance: gc, repack, prune, expire reflogs", key: "" },
+    PaletteCommand { action: Action::Clean, name: "clean: delete untracked and ignored files (git clean)", key: "" },
+    PaletteCommand { action: Action::StageLines, name: "stage lines of selected file", key: "v" },
+    PaletteCommand { action: Action::StageModeOnly, name: "stage only the mode change (chmod +x) of selected file", key: "X" },
+    PaletteCommand { action: Action::StageContentOnly, name: "stage only the content of selected file, not its mode change", key: "T" },
+    PaletteCommand { action: Action::ExportArchive, name: "export archive", key: "x" },
+    PaletteCommand { action: Action::BrowseFiles, name: "browse files of HEAD / selected commit", key: "f" },
+    PaletteCommand { action: Action::OpenOnWeb, name: "open on web", key: "o" },
+    PaletteCommand { action: Action::SwitchRepo, name: "switch repository", key: "" },
+    PaletteCommand { action: Action::Notifications, name: "notification history", key: "N" },
+    PaletteCommand { action: Action::ErrorLog, name: "error log", key: "E" },
+    PaletteCommand { action: Action::LogViewer, name: "debug log", key: "E tab" },
+    PaletteCommand { action: Action::RecordMacro, name: "start / stop recording a macro", key: "Q" },
+    PaletteCommand { action: Action::ReplayMacro, name: "replay recorded macro", key: "@" },
+    PaletteCommand { action: Action::CommandOutput, name: "show output of last command", key: "" },
+    PaletteCommand { action: Action::Help, name: "help: keys and docs for each view", key: "F1" },
+    PaletteCommand { action: Action::Refresh, name: "refresh", key: "F5" },
+    PaletteCommand { action: Action::Quit, name: "quit", key: "q" },
+];
+
+// best match first, everything when the query is empty. `extra` are the ones from config
+pub fn filter<'a>(query: &str, extra: &'a [PaletteCommand<'a>]) -> Vec<&'a PaletteCommand<'a>> {
+    let mut scored: Vec<(i32, &PaletteCommand)> = COMMANDS
+        .iter()
+        .chain(extra)
+        .filter_map(|cmd| fuzzy_score(query, cmd.name).map(|score| (score, cmd)))
+        .collect();
+    scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    scored.into_iter().map(|(_, cmd)| cmd).collect()
+}
+
+// every query char has to appear in order. runs of consecutive chars and word starts score higher
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i32> {
+    let candidate: Vec<char> = candidate.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut pos = 0;
+    let mut last_match: Option<usize> = None;
+
+    for qc in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let found = (pos..candidate.len()).find(|&i| candidate[i] == qc)?;
+
+        score += 1;
+        if last_match.is_some_and(|last| last + 1 == found) {
+            score += 5;
+        }
+        if found == 0 || !candidate[found - 1].is_alphanumeric() {
+            score += 3;
+        }
+        // earlier matches win ties
+        score -= (found - pos).min(5) as i32;
+
+        last_match = Some(found);
+        pos = found + 1;
+    }
+    Some(score)
+}
@@ -0,0 +1,486 @@
+use anyhow::{bail, Result};
+
+// a small backtracking regex for the places that take user patterns (issue links...).
+// supports literals, . [a-z] [^...] \d \w \s \D \W \S \b ^ $, groups ( ) (?: ), |,
+// * + ? {n} {n,} {n,m} (lazy with a trailing ?) and a leading (?i) for case-insensitive.
+// no lookaround or backreferences, patterns here are short.
+
+#[derive(Debug, Clone)]
+enum Node {
+    Char(char),
+    Any,
+    Class { ranges: Vec<(char, char)>, negated: bool },
+    Start,
+    End,
+    WordBoundary,
+    Group(Box<Node>, Option<usize>),
+    Concat(Vec<Node>),
+    Alt(Vec<Node>),
+    Repeat { node: Box<Node>, min: usize, max: Option<usize>, greedy: bool },
+}
+
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    node: Node,
+    groups: usize,
+    case_insensitive: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Match {
+    // byte offsets into the searched text
+    pub start: usize,
+    pub end: usize,
+    // capture groups, 1-based like everywhere else; groups[0] is group 1
+    pub groups: Vec<Option<(usize, usize)>>,
+}
+
+impl Match {
+    pub fn group<'t>(&self, text: &'t str, index: usize) -> Option<&'t str> {
+        if index == 0 {
+            return Some(&text[self.start..self.end]);
+        }
+        self.groups.get(index - 1).copied().flatten().map(|(s, e)| &text[s..e])
+    }
+}
+
+const DIGIT: &[(char, char)] = &[('0', '9')];
+const WORD: &[(char, char)] = &[('a', 'z'), ('A', 'Z'), ('0', '9'), ('_', '_')];
+const SPACE: &[(char, char)] = &[(' ', ' '), ('\t', '\t'), ('\n', '\n'), ('\r', '\r'), ('\x0b', '\x0c')];
+
+struct Parser {
+    chars: Vec<char>,
+    pos: usize,
+    groups: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.pos).copied()
+    }
+
+    fn eat(&mut self, c: char) -> bool {
+        if self.peek() == Some(c) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn parse_alt(&mut self) -> Result<Node> {
+        let mut branches = vec![self.parse_concat()?];
+        while self.eat('|') {
+            branches.push(self.parse_concat()?);
+        }
+        Ok(if branches.len() == 1 { branches.pop().unwrap() } else { Node::Alt(branches) })
+    }
+
+    fn parse_concat(&mut self) -> Result<Node> {
+        let mut items = Vec::new();
+        while let Some(c) = self.peek() {
+            if c == '|' || c == ')' {
+                break;
+            }
+            let atom = self.parse_atom()?;
+            items.push(self.parse_quantifier(atom)?);
+        }
+        Ok(Node::Concat(items))
+    }
+
+    fn parse_quantifier(&mut self, atom: Node) -> Result<Node> {
+        let (min, max) = match self.peek() {
+            Some('*') => { self.pos += 1; (0, None) }
+            Some('+') => { self.pos += 1; (1, None) }
+            Some('?') => { self.pos += 1; (0, Some(1)) }
+            Some('{') => match self.parse_braces() {
+                Some(bounds) => bounds,
+                // not a valid {n,m}, it's a literal brace
+                None => return Ok(atom),
+            },
+            _ => return Ok(atom),
+        };
+        if matches!(atom, Node::Start | Node::End | Node::WordBoundary) {
+            bail!("nothing to repeat at position {}", self.pos);
+        }
+        let greedy = !self.eat('?');
+        Ok(Node::Repeat { node: Box::new(atom), min, max, greedy })
+    }
+
+    fn parse_braces(&mut self) -> Option<(usize, Option<usize>)> {
+        let rest: String = self.chars[self.pos..].iter().collect();
+        let close = rest.find('}')?;
+        let inner = &rest[1..close];
+        let bounds = match inner.split_once(',') {
+            None => {
+                let n = inner.parse().ok()?;
+                (n, Some(n))
+            }
+            Some((min, "")) => (min.parse().ok()?, None),
+            Some((min, max)) => (min.parse().ok()?, Some(max.parse().ok()?)),
+        };
+        self.pos += rest[..=close].chars().count();
+        Some(bounds)
+    }
+
+    fn parse_atom(&mut self) -> Result<Node> {
+        let c = self.peek().unwrap();
+        self.pos += 1;
+        Ok(match c {
+            '.' => Node::Any,
+            '^' => Node::Start,
+            '$' => Node::End,
+            '[' => self.parse_class()?,
+            '(' => {
+                let capture = if self.eat('?') {
+                    if !self.eat(':') {
+                        bail!("unsupported group syntax at position {}", self.pos);
+                    }
+                    None
+                } else {
+                    self.groups += 1;
+                    Some(self.groups - 1)
+                };
+                let inner = self.parse_alt()?;
+                if !self.eat(')') {
+                    bail!("unclosed group");
+                }
+                Node::Group(Box::new(inner), capture)
+            }
+            ')' => bail!("unmatched ) at position {}", self.pos),
+            '*' | '+' | '?' => bail!("nothing to repeat at position {}", self.pos),
+            '\\' => self.parse_escape(false)?,
+            c => Node::Char(c),
+        })
+    }
+
+    fn parse_escape(&mut self, in_class: bool) -> Result<Node> {
+        let Some(c) = self.peek() else {
+            bail!("pattern ends with a backslash");
+        };
+        self.pos += 1;
+        let class = |ranges: &[(char, char)], negated| Node::Class { ranges: ranges.to_vec(), negated };
+        Ok(match c {
+            'd' => class(DIGIT, false),
+            'D' => class(DIGIT, true),
+            'w' => class(WORD, false),
+            'W' => class(WORD, true),
+            's' => class(SPACE, false),
+            'S' => class(SPACE, true),
+            'b' if !in_class => Node::WordBoundary,
+            'n' => Node::Char('\n'),
+            't' => Node::Char('\t'),
+            'r' => Node::Char('\r'),
+            c if c.is_alphanumeric() => bail!("unknown escape \\{}", c),
+            c => Node::Char(c),
+        })
+    }
+
+    fn parse_class(&mut self) -> Result<Node> {
+        let negated = self.eat('^');
+        let mut ranges = Vec::new();
+        let mut first = true;
+
+        loop {
+            let Some(c) = self.peek() else {
+                bail!("unclosed character class");
+            };
+            if c == ']' && !first {
+                self.pos += 1;
+                break;
+            }
+            first = false;
+            self.pos += 1;
+
+            let low = if c == '\\' {
+                match self.parse_escape(true)? {
+                    Node::Char(c) => c,
+                    Node::Class { ranges: inner, negated: false } => {
+                        ranges.extend(inner);
+                        continue;
+                    }
+                    _ => bail!("negated escapes aren't supported inside [...]"),
+                }
+            } else {
+                c
+            };
+
+            // a-z, but a trailing - is literal
+            if self.peek() == Some('-') && self.chars.get(self.pos + 1).is_some_and(|c| *c != ']') {
+                self.pos += 1;
+                let high = self.peek().unwrap();
+                self.pos += 1;
+                let high = if high == '\\' {
+                    match self.parse_escape(true)? {
+                        Node::Char(c) => c,
+                        _ => bail!("bad range in character class"),
+                    }
+                } else {
+                    high
+                };
+                if high < low {
+                    bail!("bad range {}-{} in character class", low, high);
+                }
+                ranges.push((low, high));
+            } else {
+                ranges.push((low, low));
+            }
+        }
+        Ok(Node::Class { ranges, negated })
+    }
+}
+
+struct Matcher<'a> {
+    text: &'a [char],
+    case_insensitive: bool,
+    // guards against patterns like (a*)* crawling on long input, shared by every match of a search
+    steps: usize,
+    // how deep the continuations go, a repeated group nests a few frames per iteration
+    depth: usize,
+    gave_up: bool,
+}
+
+const STEP_LIMIT: usize = 1_000_000;
+const DEPTH_LIMIT: usize = 1_000;
+
+type Captures = Vec<Option<(usize, usize)>>;
+
+impl Matcher<'_> {
+    fn chars_eq(&self, a: char, b: char) -> bool {
+        a == b || (self.case_insensitive && a.to_lowercase().eq(b.to_lowercase()))
+    }
+
+    fn class_matches(&self, ranges: &[(char, char)], negated: bool, c: char) -> bool {
+        let test = |c: char| ranges.iter().any(|(lo, hi)| *lo <= c && c <= *hi);
+        let hit = test(c) || (self.case_insensitive && (c.to_lowercase().any(test) || c.to_uppercase().any(test)));
+        hit != negated
+    }
+
+    fn is_word(&self, pos: usize) -> bool {
+        self.text.get(pos).is_some_and(|c| c.is_alphanumeric() || *c == '_')
+    }
+
+    // whether a node that takes exactly one char takes the one at `pos`
+    fn single(&self, node: &Node, pos: usize) -> bool {
+        let Some(t) = self.text.get(pos) else {
+            return false;
+        };
+        match node {
+            Node::Char(c) => self.chars_eq(*c, *t),
+            Node::Any => *t != '\n',
+            Node::Class { ranges, negated } => self.class_matches(ranges, *negated, *t),
+            _ => false,
+        }
+    }
+
+    // continuation passing: `k` is whatever has to match after `node`
+    fn m(&mut self, node: &Node, pos: usize, caps: &mut Captures, k: &mut dyn FnMut(&mut Self, usize, &mut Captures) -> bool) -> bool {
+        self.steps += 1;
+        if self.gave_up || self.steps > STEP_LIMIT || self.depth > DEPTH_LIMIT {
+            self.gave_up = true;
+            return false;
+        }
+        self.depth += 1;
+        let found = self.m_node(node, pos, caps, k);
+        self.depth -= 1;
+        found
+    }
+
+    fn m_node(&mut self, node: &Node, pos: usize, caps: &mut Captures, k: &mut dyn FnMut(&mut Self, usize, &mut Captures) -> bool) -> bool {
+        match node {
+            Node::Char(_) | Node::Any | Node::Class { .. } => self.single(node, pos) && k(self, pos + 1, caps),
+            Node::Start => pos == 0 && k(self, pos, caps),
+            Node::End => pos == self.text.len() && k(self, pos, caps),
+            Node::WordBoundary => {
+                let boundary = pos > 0 && self.is_word(pos - 1);
+                (boundary != self.is_word(pos)) && k(self, pos, caps)
+            }
+            Node::Group(inner, capture) => {
+                let capture = *capture;
+                self.m(inner, pos, caps, &mut |this, end, caps| {
+                    let Some(idx) = capture else {
+                        return k(this, end, caps);
+                    };
+                    let saved = caps[idx];
+                    caps[idx] = Some((pos, end));
+                    if k(this, end, caps) {
+                        return true;
+                    }
+                    caps[idx] = saved;
+                    false
+                })
+            }
+            Node::Concat(items) => self.m_seq(items, pos, caps, k),
+            Node::Alt(branches) => branches.iter().any(|b| self.m(b, pos, caps, k)),
+            // .* [a-z]{32} \s+ and the like: how far it reaches in a loop, then the rest from each
+            // place it could stop, instead of a few frames of recursion per char
+            Node::Repeat { node, min, max, greedy } if node.is_single() => {
+                let mut reach = pos;
+                while max.is_none_or(|max| reach - pos < max) && self.single(node, reach) {
+                    reach += 1;
+                }
+                self.steps += reach - pos;
+                if reach - pos < *min {
+                    return false;
+                }
+                let mut stops = pos + min..=reach;
+                if *greedy {
+                    stops.rev().any(|end| k(self, end, caps))
+                } else {
+                    stops.any(|end| k(self, end, caps))
+                }
+            }
+            Node::Repeat { node, min, max, greedy } => self.m_repeat(node, *min, *max, *greedy, 0, pos, caps, k),
+        }
+    }
+
+    fn m_seq(&mut self, items: &[Node], pos: usize, caps: &mut Captures, k: &mut dyn FnMut(&mut Self, usize, &mut Captures) -> bool) -> bool {
+        match items.split_first() {
+            None => k(self, pos, caps),
+            Some((first, rest)) => self.m(first, pos, caps, &mut |this, next, caps| this.m_seq(rest, next, caps, k)),
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn m_repeat(
+        &mut self,
+        node: &Node,
+        min: usize,
+        max: Option<usize>,
+        greedy: bool,
+        count: usize,
+        pos: usize,
+        caps: &mut Captures,
+        k: &mut dyn FnMut(&mut Self, usize, &mut Captures) -> bool,
+    ) -> bool {
+        let can_stop = count >= min;
+        let can_go = max.is_none_or(|max| count < max);
+
+        if greedy {
+            if can_go && self.m_repeat_once(node, min, max, greedy, count, pos, caps, k) {
+                return true;
+            }
+            can_stop && k(self, pos, caps)
+        } else {
+            if can_stop && k(self, pos, caps) {
+                return true;
+            }
+            can_go && self.m_repeat_once(node, min, max, greedy, count, pos, caps, k)
+        }
+    }
+
+    // one more iteration of a repeat, then the rest of it
+    #[allow(clippy::too_many_arguments)]
+    fn m_repeat_once(
+        &mut self,
+        node: &Node,
+        min: usize,
+        max: Option<usize>,
+        greedy: bool,
+        count: usize,
+        pos: usize,
+        caps: &mut Captures,
+        k: &mut dyn FnMut(&mut Self, usize, &mut Captures) -> bool,
+    ) -> bool {
+        self.m(node, pos, caps, &mut |this, next, caps| {
+            // an iteration that matched nothing would loop forever
+            next != pos && this.m_repeat(node, min, max, greedy, count + 1, next, caps, k)
+        })
+    }
+}
+
+impl Node {
+    fn is_single(&self) -> bool {
+        matches!(self, Node::Char(_) | Node::Any | Node::Class { .. })
+    }
+}
+
+// the text as chars, and each char's byte offset with one extra entry for the end
+fn index(text: &str) -> (Vec<char>, Vec<usize>) {
+    let chars = text.chars().collect();
+    let mut offsets: Vec<usize> = text.char_indices().map(|(i, _)| i).collect();
+    offsets.push(text.len());
+    (chars, offsets)
+}
+
+// char positions back to byte offsets
+fn to_match(offsets: &[usize], start: usize, end: usize, caps: &Captures) -> Match {
+    Match {
+        start: offsets[start],
+        end: offsets[end],
+        groups: caps.iter().map(|c| c.map(|(s, e)| (offsets[s], offsets[e]))).collect(),
+    }
+}
+
+impl Pattern {
+    pub fn new(pattern: &str) -> Result<Self> {
+        let (pattern, case_insensitive) = match pattern.strip_prefix("(?i)") {
+            Some(rest) => (rest, true),
+            None => (pattern, false),
+        };
+
+        let mut parser = Parser { chars: pattern.chars().collect(), pos: 0, groups: 0 };
+        let node = parser.parse_alt()?;
+        if parser.pos < parser.chars.len() {
+            bail!("unmatched ) at position {}", parser.pos);
+        }
+        Ok(Self { node, groups: parser.groups, case_insensitive })
+    }
+
+    pub fn is_match(&self, text: &str) -> bool {
+        let (chars, _) = index(text);
+        self.find_from(&mut self.matcher(&chars), 0).is_some()
+    }
+
+    // non-overlapping matches, left to right
+    pub fn find_iter(&self, text: &str) -> Vec<Match> {
+        let (chars, offsets) = index(text);
+        let mut matcher = self.matcher(&chars);
+        let mut matches = Vec::new();
+        let mut from = 0;
+        while from <= chars.len() {
+            let Some((start, end, caps)) = self.find_from(&mut matcher, from) else {
+                break;
+            };
+            // an empty match steps over one char
+            from = if end > start { end } else { end + 1 };
+            matches.push(to_match(&offsets, start, end, &caps));
+        }
+        matches
+    }
+
+    // the first match, or an error when the pattern gave up on the text (too many steps, too
+    // deep) instead of knowing there's none
+    pub fn find(&self, text: &str) -> Result<Option<Match>> {
+        let (chars, offsets) = index(text);
+        let mut matcher = self.matcher(&chars);
+        match self.find_from(&mut matcher, 0) {
+            Some((start, end, caps)) => Ok(Some(to_match(&offsets, start, end, &caps))),
+            None if matcher.gave_up => bail!("gave up on {} chars, the pattern backtracks too much", chars.len()),
+            None => Ok(None),
+        }
+    }
+
+    fn matcher<'a>(&self, chars: &'a [char]) -> Matcher<'a> {
+        Matcher { text: chars, case_insensitive: self.case_insensitive, steps: 0, depth: 0, gave_up: false }
+    }
+
+    // the first match starting at char `from` or later, in chars
+    fn find_from(&self, matcher: &mut Matcher, from: usize) -> Option<(usize, usize, Captures)> {
+        for start in from..=matcher.text.len() {
+            let mut caps: Captures = vec![None; self.groups];
+            let mut end = None;
+            let found = matcher.m(&self.node, start, &mut caps, &mut |_, pos, _| {
+                end = Some(pos);
+                true
+            });
+            if found {
+                return Some((start, end.unwrap(), caps));
+            }
+            if matcher.gave_up {
+                return None;
+            }
+        }
+        None
+    }
+}
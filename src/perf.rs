@@ -0,0 +1,67 @@
+use std::cell::{Cell, RefCell};
+use std::time::{Duration, Instant};
+
+// timings for the debug hud (F12): how long the last status / diff / log / network operation took
+// and what a pass of the event loop costs, for when something is slow on someone's big repo.
+// recorded through &self since the diff is only worked out while drawing
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Op {
+    Status,
+    Diff,
+    Log,
+    Network,
+}
+
+impl Op {
+    pub const ALL: [Op; 4] = [Op::Status, Op::Diff, Op::Log, Op::Network];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Op::Status => "status",
+            Op::Diff => "diff",
+            Op::Log => "log",
+            Op::Network => "network",
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Perf {
+    pub visible: bool,
+    // what it was on (a path, a remote) and how long it took, by Op
+    last: RefCell<[Option<(String, Duration)>; 4]>,
+    draw: Cell<Duration>,
+    input: Cell<Duration>,
+}
+
+impl Perf {
+    pub fn time<T>(&self, op: Op, detail: &str, f: impl FnOnce() -> T) -> T {
+        let started = Instant::now();
+        let result = f();
+        self.record(op, detail, started);
+        result
+    }
+
+    pub fn record(&self, op: Op, detail: &str, started: Instant) {
+        self.last.borrow_mut()[op as usize] = Some((detail.to_string(), started.elapsed()));
+    }
+
+    pub fn last(&self, op: Op) -> Option<(String, Duration)> {
+        self.last.borrow()[op as usize].clone()
+    }
+
+    // one pass of the event loop, not counting the time spent waiting for a key
+    pub fn frame(&self, draw: Duration, input: Duration) {
+        self.draw.set(draw);
+        self.input.set(input);
+    }
+
+    pub fn frame_times(&self) -> (Duration, Duration) {
+        (self.draw.get(), self.input.get())
+    }
+}
+
+pub fn millis(duration: Duration) -> String {
+    format!("{:.1}ms", duration.as_secs_f64() * 1000.0)
+}
@@ -0,0 +1,42 @@
+// protected branches, from hydra.protectedBranch (can be given more than once, * matches
+// anything, / included):
+//   hydra.protectedBranch = main
+//   hydra.protectedBranch = release/*
+// a push to one of them has to be confirmed, or with hydra.protectedPush = block hydra won't do it
+// at all. they're marked in the branches view either way.
+
+#[derive(Debug, Clone, Default)]
+pub struct Protection {
+    pub patterns: Vec<String>,
+    pub block: bool,
+}
+
+impl Protection {
+    pub fn load(patterns: Vec<String>, mode: Option<String>) -> (Self, Option<String>) {
+        let (block, error) = match mode.as_deref() {
+            None | Some("confirm") => (false, None),
+            Some("block") => (true, None),
+            Some(other) => (false, Some(format!("hydra.protectedPush '{}' should be confirm or block, confirming for now", other))),
+        };
+        let patterns = patterns.into_iter().map(|p| p.trim().to_string()).filter(|p| !p.is_empty()).collect();
+        (Self { patterns, block }, error)
+    }
+
+    // branch names as they are locally or on the remote, without refs/heads/ or origin/
+    pub fn is_protected(&self, branch: &str) -> bool {
+        self.patterns.iter().any(|pattern| glob_match(pattern, branch))
+    }
+}
+
+fn glob_match(pattern: &str, name: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == name,
+        Some((prefix, rest)) => {
+            let Some(name) = name.strip_prefix(prefix) else {
+                return false;
+            };
+            // the star takes 0..=all of what's left, shortest first
+            name.char_indices().map(|(i, _)| i).chain([name.len()]).any(|i| glob_match(rest, &name[i..]))
+        }
+    }
+}
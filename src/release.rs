@@ -0,0 +1,126 @@
+use crate::git::{Commit, Repository};
+use crate::release_notes::{self, Group};
+use anyhow::Result;
+use std::fmt;
+
+// the new release flow (palette): the highest semver tag HEAD can reach, the next version the
+// conventional commits since then ask for (a breaking change is major, a feat minor, anything
+// else patch; before 1.0.0 breaking changes only bump the minor), and an annotated tag of it with
+// the release notes as its message. tags look like 1.2.3 or v1.2.3, pre-releases are skipped
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Version {
+    pub major: u64,
+    pub minor: u64,
+    pub patch: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Bump {
+    Patch,
+    Minor,
+    Major,
+}
+
+pub const BUMPS: [Bump; 3] = [Bump::Patch, Bump::Minor, Bump::Major];
+
+impl Bump {
+    pub fn label(self) -> &'static str {
+        match self {
+            Bump::Patch => "patch",
+            Bump::Minor => "minor",
+            Bump::Major => "major",
+        }
+    }
+}
+
+impl Version {
+    // "v1.2.3" -> (v, 1.2.3)
+    pub fn parse(tag: &str) -> Option<(&str, Version)> {
+        let digits = tag.find(|c: char| c.is_ascii_digit())?;
+        let (prefix, version) = tag.split_at(digits);
+        if !matches!(prefix, "" | "v" | "V") {
+            return None;
+        }
+        let mut parts = version.split('.').map(|p| p.parse::<u64>().ok());
+        let (Some(Some(major)), Some(Some(minor)), Some(Some(patch)), None) = (parts.next(), parts.next(), parts.next(), parts.next()) else {
+            return None;
+        };
+        Some((prefix, Version { major, minor, patch }))
+    }
+
+    pub fn bump(self, bump: Bump) -> Version {
+        match bump {
+            Bump::Patch => Version { patch: self.patch + 1, ..self },
+            Bump::Minor => Version { minor: self.minor + 1, patch: 0, ..self },
+            Bump::Major => Version { major: self.major + 1, minor: 0, patch: 0 },
+        }
+    }
+}
+
+impl fmt::Display for Version {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}.{}", self.major, self.minor, self.patch)
+    }
+}
+
+pub fn suggest(current: Version, commits: &[Commit]) -> Bump {
+    let mut bump = Bump::Patch;
+    for commit in commits {
+        let subject = commit.message.lines().next().unwrap_or("");
+        let Some(parsed) = release_notes::parse(subject) else {
+            continue;
+        };
+        if parsed.breaking || release_notes::breaking_note(&commit.message).is_some() {
+            return if current.major == 0 { Bump::Minor } else { Bump::Major };
+        }
+        if parsed.kind.eq_ignore_ascii_case("feat") {
+            bump = Bump::Minor;
+        }
+    }
+    bump
+}
+
+#[derive(Debug, Clone)]
+pub struct Plan {
+    // the tag it starts from, None for the first release
+    pub latest: Option<String>,
+    pub prefix: String,
+    pub current: Version,
+    pub suggested: Bump,
+    pub bump: Bump,
+    pub commits: Vec<Commit>,
+    groups: Vec<Group>,
+}
+
+impl Plan {
+    // and what's wrong with hydra.notesGroup
+    pub fn load(repo: &Repository) -> Result<(Self, Vec<String>)> {
+        let latest = repo
+            .reachable_tags()?
+            .into_iter()
+            .filter_map(|tag| Version::parse(&tag).map(|(prefix, version)| (version, prefix.to_string(), tag.clone())))
+            .max_by_key(|(version, _, _)| *version);
+        let (current, prefix, latest) = match latest {
+            Some((version, prefix, tag)) => (version, prefix, Some(tag)),
+            None => (Version { major: 0, minor: 0, patch: 0 }, "v".to_string(), None),
+        };
+        let range = match &latest {
+            Some(tag) => format!("{}..HEAD", tag),
+            None => "HEAD".to_string(),
+        };
+        let commits = repo.commits_in_range(&range)?;
+        let (groups, errors) = release_notes::load(&repo.config_values("hydra.notesGroup"));
+        let suggested = suggest(current, &commits);
+        Ok((Self { latest, prefix, current, suggested, bump: suggested, commits, groups }, errors))
+    }
+
+    pub fn tag(&self, bump: Bump) -> String {
+        format!("{}{}", self.prefix, self.current.bump(bump))
+    }
+
+    // the tag's message
+    pub fn notes(&self, repo: &Repository) -> String {
+        release_notes::render(repo, &self.tag(self.bump), &self.commits, &self.groups)
+    }
+}
@@ -0,0 +1,148 @@
+use crate::git::{Commit, Repository};
+use anyhow::Result;
+
+// release notes for the commits between two refs (the last tag..HEAD by default), their subjects
+// grouped by conventional commit type: "feat(parser)!: drop the old syntax". the groups come from
+// hydra.notesGroup (can be given more than once) as "<types>: <heading>", e.g.
+//   hydra.notesGroup = "feat: new"
+//   hydra.notesGroup = "fix, perf: fixed"
+//   hydra.notesGroup = "*: everything else"
+// which replace the built-in ones once any is set. * takes whatever no group names, without it
+// those end up under "other changes". merge commits are left out.
+
+const BUILT_IN: &[(&str, &str)] = &[
+    ("feat", "features"),
+    ("fix", "bug fixes"),
+    ("perf", "performance"),
+    ("refactor", "refactoring"),
+    ("docs", "documentation"),
+    ("revert", "reverts"),
+    ("build, ci, chore, style, test", "maintenance"),
+];
+
+const OTHER: &str = "other changes";
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Group {
+    // lowercase, "*" for the rest
+    pub types: Vec<String>,
+    pub heading: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Conventional<'a> {
+    pub kind: &'a str,
+    pub scope: Option<&'a str>,
+    pub breaking: bool,
+    pub description: &'a str,
+}
+
+// the last tag up to HEAD, all of HEAD before the first tag
+pub fn default_range(repo: &Repository) -> String {
+    match repo.latest_tag() {
+        Some(tag) => format!("{}..HEAD", tag),
+        None => "HEAD".to_string(),
+    }
+}
+
+// the notes for a range with the repo's hydra.notesGroup, and what's wrong with those
+pub fn generate(repo: &Repository, range: &str) -> Result<(String, Vec<String>)> {
+    let (groups, errors) = load(&repo.config_values("hydra.notesGroup"));
+    let commits = repo.commits_in_range(range)?;
+    Ok((render(repo, range.trim(), &commits, &groups), errors))
+}
+
+pub fn load(specs: &[String]) -> (Vec<Group>, Vec<String>) {
+    let mut groups = Vec::new();
+    let mut errors = Vec::new();
+    for spec in specs {
+        match spec.split_once(':').map(|(types, heading)| (split_types(types), heading.trim())) {
+            Some((types, heading)) if !types.is_empty() && !heading.is_empty() => groups.push(Group { types, heading: heading.to_string() }),
+            _ => errors.push(format!("hydra.notesGroup '{}' should be '<types>: <heading>'", spec)),
+        }
+    }
+    if groups.is_empty() {
+        groups = BUILT_IN.iter().map(|(types, heading)| Group { types: split_types(types), heading: heading.to_string() }).collect();
+    }
+    (groups, errors)
+}
+
+fn split_types(types: &str) -> Vec<String> {
+    types.split(',').map(|t| t.trim().to_lowercase()).filter(|t| !t.is_empty()).collect()
+}
+
+// "type(scope)!: description", None for a subject that isn't one
+pub fn parse(subject: &str) -> Option<Conventional<'_>> {
+    let (prefix, description) = subject.split_once(':')?;
+    let (prefix, breaking) = match prefix.strip_suffix('!') {
+        Some(prefix) => (prefix, true),
+        None => (prefix, false),
+    };
+    let (kind, scope) = match prefix.split_once('(') {
+        Some((kind, scope)) => (kind, Some(scope.strip_suffix(')')?.trim())),
+        None => (prefix, None),
+    };
+    if kind.is_empty() || !kind.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+        return None;
+    }
+    let description = description.trim();
+    if description.is_empty() {
+        return None;
+    }
+    Some(Conventional { kind, scope: scope.filter(|s| !s.is_empty()), breaking, description })
+}
+
+// what a BREAKING CHANGE: footer in the body says, if there is one
+pub fn breaking_note(message: &str) -> Option<&str> {
+    message.lines().skip(1).find_map(|line| line.strip_prefix("BREAKING CHANGE:").or_else(|| line.strip_prefix("BREAKING-CHANGE:"))).map(|note| note.trim())
+}
+
+// the markdown: a heading (the range, or the tag for a release), breaking changes first, then the
+// groups in their order. commits come newest first and stay that way inside a group
+pub fn render(repo: &Repository, title: &str, commits: &[Commit], groups: &[Group]) -> String {
+    let mut sections: Vec<(&str, Vec<String>)> = groups.iter().map(|g| (g.heading.as_str(), Vec::new())).collect();
+    let mut other = Vec::new();
+    let mut breaking = Vec::new();
+    let catch_all = groups.iter().position(|g| g.types.iter().any(|t| t == "*"));
+
+    for commit in commits.iter().filter(|c| c.parents.len() < 2) {
+        let subject = commit.message.lines().next().unwrap_or("").trim();
+        let id = repo.short_id(&commit.id);
+        let Some(parsed) = parse(subject) else {
+            match catch_all {
+                Some(i) => sections[i].1.push(format!("- {} ({})", subject, id)),
+                None => other.push(format!("- {} ({})", subject, id)),
+            }
+            continue;
+        };
+        let entry = |text: &str| match parsed.scope {
+            Some(scope) => format!("- **{}:** {} ({})", scope, text, id),
+            None => format!("- {} ({})", text, id),
+        };
+        let note = breaking_note(&commit.message);
+        if parsed.breaking || note.is_some() {
+            breaking.push(entry(note.filter(|n| !n.is_empty()).unwrap_or(parsed.description)));
+        }
+        let kind = parsed.kind.to_lowercase();
+        match groups.iter().position(|g| g.types.contains(&kind)).or(catch_all) {
+            Some(i) => sections[i].1.push(entry(parsed.description)),
+            None => other.push(entry(parsed.description)),
+        }
+    }
+
+    let mut out = format!("## {}\n", title);
+    let all = std::iter::once(("⚠ breaking changes", breaking)).chain(sections).chain(std::iter::once((OTHER, other)));
+    let mut empty = true;
+    for (heading, entries) in all.filter(|(_, entries)| !entries.is_empty()) {
+        empty = false;
+        out.push_str(&format!("\n### {}\n\n", heading));
+        for entry in entries {
+            out.push_str(&entry);
+            out.push('\n');
+        }
+    }
+    if empty {
+        out.push_str("\nno changes.\n");
+    }
+    out
+}
@@ -0,0 +1,152 @@
+use anyhow::{Context, Result};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+// --record <file> writes every key press with the time since the one before, --replay <file>
+// presses them again at the same pace, so a reported ui bug can be walked through exactly. one
+// key per line:
+//   # git-hydra recording, 120x40
+//   0 2
+//   350 j
+//   1200 <enter>
+// keys are written the way the test driver takes them: the char itself, or <enter>, <c-p> and
+// the like. what's typed into the token field of the credential dialog is recorded as *
+
+pub struct Recorder {
+    file: File,
+    last: Instant,
+}
+
+impl Recorder {
+    pub fn create(path: &Path, size: (u16, u16)) -> Result<Self> {
+        let mut file = File::create(path).with_context(|| format!("can't create {}", path.display()))?;
+        writeln!(file, "# git-hydra recording, {}x{}", size.0, size.1)?;
+        Ok(Self { file, last: Instant::now() })
+    }
+
+    // written straight away, so a crash still leaves the keys that led to it
+    pub fn record(&mut self, key: KeyEvent, secret: bool) {
+        let key = match key.code {
+            KeyCode::Char(_) if secret => KeyEvent::new(KeyCode::Char('*'), KeyModifiers::NONE),
+            _ => key,
+        };
+        let elapsed = self.last.elapsed().as_millis();
+        self.last = Instant::now();
+        if let Err(e) = writeln!(self.file, "{} {}", elapsed, key_name(&key)).and_then(|_| self.file.flush()) {
+            log::warn!("can't write the recording: {}", e);
+        }
+    }
+}
+
+pub struct Replay {
+    keys: VecDeque<(Duration, KeyEvent)>,
+    last: Instant,
+}
+
+impl Replay {
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path).with_context(|| format!("can't read {}", path.display()))?;
+        let mut keys = VecDeque::new();
+        for (n, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let parsed = line.split_once(' ').and_then(|(ms, key)| Some((ms.parse::<u64>().ok()?, parse_key(key)?)));
+            let Some((ms, key)) = parsed else {
+                anyhow::bail!("{} line {}: '{}' should be '<milliseconds> <key>'", path.display(), n + 1, line);
+            };
+            keys.push_back((Duration::from_millis(ms), key));
+        }
+        Ok(Self { keys, last: Instant::now() })
+    }
+
+    // the next key once its time has come
+    pub fn due(&mut self) -> Option<KeyEvent> {
+        let (delay, _) = self.keys.front()?;
+        if self.last.elapsed() < *delay {
+            return None;
+        }
+        self.last = Instant::now();
+        self.keys.pop_front().map(|(_, key)| key)
+    }
+
+    pub fn wait(&self) -> Option<Duration> {
+        self.keys.front().map(|(delay, _)| delay.saturating_sub(self.last.elapsed()))
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    // all at once, for the snapshot command
+    pub fn into_keys(self) -> Vec<KeyEvent> {
+        self.keys.into_iter().map(|(_, key)| key).collect()
+    }
+}
+
+pub fn key_name(key: &KeyEvent) -> String {
+    let name = match key.code {
+        KeyCode::Char(' ') => "space".to_string(),
+        KeyCode::Char('<') => "lt".to_string(),
+        KeyCode::Char(c) if key.modifiers.contains(KeyModifiers::CONTROL) => format!("c-{}", c),
+        KeyCode::Char(c) => return c.to_string(),
+        KeyCode::Enter => "enter".to_string(),
+        KeyCode::Esc => "esc".to_string(),
+        KeyCode::Tab => "tab".to_string(),
+        KeyCode::BackTab => "backtab".to_string(),
+        KeyCode::Backspace => "bs".to_string(),
+        KeyCode::Delete => "del".to_string(),
+        KeyCode::Up => "up".to_string(),
+        KeyCode::Down => "down".to_string(),
+        KeyCode::Left => "left".to_string(),
+        KeyCode::Right => "right".to_string(),
+        KeyCode::Home => "home".to_string(),
+        KeyCode::End => "end".to_string(),
+        KeyCode::PageUp => "pgup".to_string(),
+        KeyCode::PageDown => "pgdn".to_string(),
+        KeyCode::F(n) => format!("f{}", n),
+        other => format!("{:?}", other).to_lowercase(),
+    };
+    format!("<{}>", name)
+}
+
+// one key as key_name writes it
+pub fn parse_key(text: &str) -> Option<KeyEvent> {
+    let plain = |code| Some(KeyEvent::new(code, KeyModifiers::NONE));
+    let Some(name) = text.strip_prefix('<').and_then(|t| t.strip_suffix('>')) else {
+        let mut chars = text.chars();
+        return match (chars.next(), chars.next()) {
+            (Some(c), None) => plain(KeyCode::Char(c)),
+            _ => None,
+        };
+    };
+    match name {
+        "space" => plain(KeyCode::Char(' ')),
+        "lt" => plain(KeyCode::Char('<')),
+        "enter" => plain(KeyCode::Enter),
+        "esc" => plain(KeyCode::Esc),
+        "tab" => plain(KeyCode::Tab),
+        "backtab" => plain(KeyCode::BackTab),
+        "bs" => plain(KeyCode::Backspace),
+        "del" => plain(KeyCode::Delete),
+        "up" => plain(KeyCode::Up),
+        "down" => plain(KeyCode::Down),
+        "left" => plain(KeyCode::Left),
+        "right" => plain(KeyCode::Right),
+        "home" => plain(KeyCode::Home),
+        "end" => plain(KeyCode::End),
+        "pgup" => plain(KeyCode::PageUp),
+        "pgdn" => plain(KeyCode::PageDown),
+        _ => {
+            if let Some(c) = name.strip_prefix("c-").and_then(|c| c.chars().next()) {
+                return Some(KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL));
+            }
+            name.strip_prefix('f').and_then(|n| n.parse().ok()).and_then(|n| plain(KeyCode::F(n)))
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use crate::pattern::Pattern;
+
+// the secret scan before `p` pushes, on with hydra.secretScan = true. every added line of the
+// outgoing commits is matched against the rules below and any hydra.secretPattern (can be given
+// more than once) as "<name>: <pattern>", e.g.
+//   hydra.secretPattern = "internal token: itk_[a-z0-9]{32}"
+// a match blocks the push until it's overridden in the preview, and so does a line a rule gave
+// up on: a secret it couldn't rule out doesn't go through quietly.
+
+const BUILT_IN: &[(&str, &str)] = &[
+    ("aws access key", r"\b(AKIA|ASIA)[0-9A-Z]{16}\b"),
+    ("private key", r"-----BEGIN ([A-Z]+ )?PRIVATE KEY-----"),
+    ("github token", r"\bgh[pousr]_[A-Za-z0-9]{36}\b"),
+    ("slack token", r"\bxox[abprs]-[A-Za-z0-9-]{10,}"),
+    ("generic secret", r#"(?i)(api[_-]?key|secret|token|passw(or)?d)\s*[:=]\s*['"][^'"\s]{8,}['"]"#),
+];
+
+// a longer line (minified, generated, a long .env value) is scanned this much at a time, the
+// backtracking matcher would crawl on the whole of it. the pieces overlap so a secret on the
+// edge of one is whole in the next
+const LINE_LIMIT: usize = 1000;
+const OVERLAP: usize = 200;
+pub const FINDING_LIMIT: usize = 200;
+
+#[derive(Debug, Clone)]
+pub struct Rule {
+    pub name: String,
+    pattern: Pattern,
+}
+
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub commit: String,
+    pub path: String,
+    pub line: u32,
+    pub rule: String,
+    // the line with most of the match starred out, so the report doesn't show the secret again
+    pub text: String,
+}
+
+pub fn load(specs: &[String]) -> (Vec<Rule>, Vec<String>) {
+    let mut rules: Vec<Rule> = BUILT_IN
+        .iter()
+        .map(|(name, pattern)| Rule { name: name.to_string(), pattern: Pattern::new(pattern).expect("built-in secret pattern") })
+        .collect();
+    let mut errors = Vec::new();
+    for spec in specs {
+        let Some((name, pattern)) = spec.split_once(':') else {
+            errors.push(format!("hydra.secretPattern '{}' should be '<name>: <pattern>'", spec));
+            continue;
+        };
+        match Pattern::new(pattern.trim()) {
+            Ok(pattern) => rules.push(Rule { name: name.trim().to_string(), pattern }),
+            Err(e) => errors.push(format!("hydra.secretPattern '{}': {:#}", spec, e)),
+        }
+    }
+    (rules, errors)
+}
+
+// the first rule the line trips, with the line (the piece of it for a long one) masked. when
+// nothing matched but a rule gave up, that's what comes back
+pub fn check(rules: &[Rule], line: &str) -> Option<(String, String)> {
+    let mut gave_up = None;
+    for piece in pieces(line) {
+        for rule in rules {
+            match rule.pattern.find(piece) {
+                Ok(Some(m)) => return Some((rule.name.clone(), mask(piece, m.start, m.end))),
+                Ok(None) => {}
+                Err(e) => {
+                    log::warn!("secret rule {} on a line of {} bytes: {:#}", rule.name, line.len(), e);
+                    gave_up.get_or_insert_with(|| (format!("{} (couldn't scan, check it by hand)", rule.name), format!("{} bytes not scanned", piece.len())));
+                }
+            }
+        }
+    }
+    gave_up
+}
+
+fn pieces(line: &str) -> Vec<&str> {
+    let boundary = |mut i: usize| {
+        while !line.is_char_boundary(i) {
+            i -= 1;
+        }
+        i
+    };
+    let mut pieces = Vec::new();
+    let mut start = 0;
+    loop {
+        let end = boundary((start + LINE_LIMIT).min(line.len()));
+        pieces.push(&line[start..end]);
+        if end == line.len() {
+            return pieces;
+        }
+        start = boundary(end - OVERLAP);
+    }
+}
+
+// keeps the first 4 chars of the match so it can still be found
+fn mask(line: &str, start: usize, end: usize) -> String {
+    let matched: String = line[start..end].chars().enumerate().map(|(i, c)| if i < 4 { c } else { '*' }).collect();
+    format!("{}{}{}", &line[..start], matched, &line[end..]).trim().to_string()
+}
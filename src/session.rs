@@ -0,0 +1,104 @@
+use anyhow::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+// ui state remembered per repo between runs, as plain key=value lines under
+// $XDG_STATE_HOME/git-hydra (~/.local/state/git-hydra)
+
+#[derive(Debug, Clone, Default)]
+pub struct SessionState {
+    pub mode: Option<String>,
+    pub selected_file: usize,
+    pub selected_commit: usize,
+    pub selected_remote: usize,
+    pub selected_stash: usize,
+    pub diff_scroll: usize,
+    pub log_limit: usize,
+    pub log_filter: Option<String>,
+    pub status_scope: Option<String>,
+    pub show_conflict_preview: bool,
+    pub show_graph: bool,
+}
+
+pub fn state_dir() -> Option<PathBuf> {
+    if let Some(dir) = std::env::var_os("XDG_STATE_HOME").filter(|d| !d.is_empty()) {
+        return Some(PathBuf::from(dir).join("git-hydra"));
+    }
+    if cfg!(windows) {
+        if let Some(dir) = std::env::var_os("LOCALAPPDATA") {
+            return Some(PathBuf::from(dir).join("git-hydra"));
+        }
+    }
+    std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/state/git-hydra"))
+}
+
+// one file per repo (and kind), named after its path so it's easy to find and delete by hand
+pub fn repo_file(repo_path: &Path, extension: &str) -> Option<PathBuf> {
+    let name: String = repo_path
+        .to_string_lossy()
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '.' { c } else { '_' })
+        .collect();
+    Some(state_dir()?.join(format!("{}.{}", name.trim_matches('_'), extension)))
+}
+
+fn state_file(repo_path: &Path) -> Option<PathBuf> {
+    repo_file(repo_path, "state")
+}
+
+pub fn load(repo_path: &Path) -> SessionState {
+    let mut state = SessionState::default();
+    let Some(content) = state_file(repo_path).and_then(|p| fs::read_to_string(p).ok()) else {
+        return state;
+    };
+
+    for line in content.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let value = value.to_string();
+        match key {
+            "mode" => state.mode = Some(value),
+            "selected_file" => state.selected_file = value.parse().unwrap_or(0),
+            "selected_commit" => state.selected_commit = value.parse().unwrap_or(0),
+            "selected_remote" => state.selected_remote = value.parse().unwrap_or(0),
+            "selected_stash" => state.selected_stash = value.parse().unwrap_or(0),
+            "diff_scroll" => state.diff_scroll = value.parse().unwrap_or(0),
+            "log_limit" => state.log_limit = value.parse().unwrap_or(0),
+            "log_filter" => state.log_filter = Some(value).filter(|v| !v.is_empty()),
+            "status_scope" => state.status_scope = Some(value).filter(|v| !v.is_empty()),
+            "show_conflict_preview" => state.show_conflict_preview = value == "true",
+            "show_graph" => state.show_graph = value == "true",
+            _ => {}
+        }
+    }
+    state
+}
+
+pub fn save(repo_path: &Path, state: &SessionState) -> Result<()> {
+    let path = state_file(repo_path).ok_or_else(|| anyhow::anyhow!("no state directory (HOME not set)"))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+
+    // values are single line, newlines in a search string would break the format
+    let clean = |v: &Option<String>| v.as_deref().unwrap_or("").replace('\n', " ");
+
+    let content = [
+        format!("mode={}", state.mode.as_deref().unwrap_or("")),
+        format!("selected_file={}", state.selected_file),
+        format!("selected_commit={}", state.selected_commit),
+        format!("selected_remote={}", state.selected_remote),
+        format!("selected_stash={}", state.selected_stash),
+        format!("diff_scroll={}", state.diff_scroll),
+        format!("log_limit={}", state.log_limit),
+        format!("log_filter={}", clean(&state.log_filter)),
+        format!("status_scope={}", clean(&state.status_scope)),
+        format!("show_conflict_preview={}", state.show_conflict_preview),
+        format!("show_graph={}", state.show_graph),
+    ]
+    .join("\n");
+
+    fs::write(path, content + "\n")?;
+    Ok(())
+}
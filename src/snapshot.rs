@@ -0,0 +1,144 @@
+use crate::app::App;
+use anyhow::Result;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{
+    Terminal,
+    backend::TestBackend,
+    buffer::Buffer,
+    style::{Color, Modifier},
+};
+
+// `git-hydra snapshot`: one view drawn off screen, as plain text or an svg, for docs, bug reports
+// and comparing against a saved copy. the same TestBackend the tests use
+
+const CELL_WIDTH: usize = 9;
+const CELL_HEIGHT: usize = 18;
+const FOREGROUND: &str = "#d0d0d0";
+const BACKGROUND: &str = "#1e1e1e";
+
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum View {
+    Status,
+    Log,
+    Branches,
+    Stashes,
+    Remote,
+}
+
+impl View {
+    // the tab key that opens it
+    fn key(&self) -> char {
+        match self {
+            View::Status => '1',
+            View::Log => '2',
+            View::Branches => '3',
+            View::Stashes => '4',
+            View::Remote => '5',
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, clap::ValueEnum)]
+pub enum Format {
+    Text,
+    Svg,
+}
+
+// the view the way it looks right after opening it with its number key, and pressing `keys`
+pub fn capture(app: &mut App, view: View, keys: &[KeyEvent], width: u16, height: u16) -> Result<Buffer> {
+    app.refresh_data()?;
+    app.handle_input(KeyEvent::new(KeyCode::Char(view.key()), KeyModifiers::NONE));
+    for key in keys {
+        app.handle_input(*key);
+    }
+    if keys.is_empty() && let Some(error) = &app.error_message {
+        anyhow::bail!("{}", error);
+    }
+    Ok(render(app, width, height))
+}
+
+pub fn render(app: &App, width: u16, height: u16) -> Buffer {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).expect("the test backend doesn't fail");
+    terminal.draw(|f| crate::ui::draw(f, app)).expect("the test backend doesn't fail");
+    terminal.backend().buffer().clone()
+}
+
+// one line per row, trailing blanks dropped
+pub fn text(buffer: &Buffer) -> String {
+    let mut out = String::new();
+    for y in 0..buffer.area.height {
+        let row: String = (0..buffer.area.width).map(|x| buffer[(x, y)].symbol()).collect();
+        out.push_str(row.trim_end());
+        out.push('\n');
+    }
+    out
+}
+
+// a <text> per run of cells that look the same, on top of a <rect> for each background
+pub fn svg(buffer: &Buffer) -> String {
+    let (width, height) = (buffer.area.width as usize, buffer.area.height as usize);
+    let mut out = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" font-family=\"monospace\" font-size=\"15\">\n<rect width=\"100%\" height=\"100%\" fill=\"{}\"/>\n",
+        width * CELL_WIDTH,
+        height * CELL_HEIGHT,
+        BACKGROUND
+    );
+    for y in 0..height {
+        let mut x = 0;
+        while x < width {
+            let cell = &buffer[(x as u16, y as u16)];
+            let (fg, bg, modifier) = colors(cell);
+            let mut run = String::new();
+            let start = x;
+            while x < width {
+                let next = &buffer[(x as u16, y as u16)];
+                if colors(next) != (fg.clone(), bg.clone(), modifier) {
+                    break;
+                }
+                run.push_str(next.symbol());
+                x += 1;
+            }
+            let (px, py) = (start * CELL_WIDTH, y * CELL_HEIGHT);
+            if bg != BACKGROUND {
+                out.push_str(&format!("<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>\n", px, py, (x - start) * CELL_WIDTH, CELL_HEIGHT, bg));
+            }
+            if run.trim().is_empty() {
+                continue;
+            }
+            let mut attrs = format!("x=\"{}\" y=\"{}\" fill=\"{}\"", px, py + CELL_HEIGHT - 5, fg);
+            if modifier.contains(Modifier::BOLD) {
+                attrs.push_str(" font-weight=\"bold\"");
+            }
+            if modifier.contains(Modifier::ITALIC) {
+                attrs.push_str(" font-style=\"italic\"");
+            }
+            if modifier.contains(Modifier::UNDERLINED) {
+                attrs.push_str(" text-decoration=\"underline\"");
+            }
+            // every cell is exactly one column wide
+            attrs.push_str(&format!(" textLength=\"{}\" xml:space=\"preserve\"", (x - start) * CELL_WIDTH));
+            out.push_str(&format!("<text {}>{}</text>\n", attrs, escape(&run)));
+        }
+    }
+    out.push_str("</svg>\n");
+    out
+}
+
+fn colors(cell: &ratatui::buffer::Cell) -> (String, String, Modifier) {
+    let mut fg = hex(cell.fg).unwrap_or_else(|| FOREGROUND.to_string());
+    let mut bg = hex(cell.bg).unwrap_or_else(|| BACKGROUND.to_string());
+    if cell.modifier.contains(Modifier::REVERSED) {
+        std::mem::swap(&mut fg, &mut bg);
+    }
+    (fg, bg, cell.modifier & (Modifier::BOLD | Modifier::ITALIC | Modifier::UNDERLINED))
+}
+
+// None for the terminal's own color
+fn hex(color: Color) -> Option<String> {
+    let (r, g, b) = crate::colors::rgb(color)?;
+    Some(format!("#{:02x}{:02x}{:02x}", r, g, b))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
@@ -0,0 +1,54 @@
+use chrono::{Datelike, Duration, NaiveDate};
+
+// the stats view (%): who committed when, as a github-style calendar with a column per week.
+// how far back it goes is hydra.statsWeeks, or +/- in the view
+
+pub const DEFAULT_WEEKS: usize = 52;
+pub const MAX_WEEKS: usize = 520;
+
+// a commit, by the day it was made on (in its own timezone) and who made it (after the mailmap)
+#[derive(Debug, Clone)]
+pub struct Contribution {
+    pub day: NaiveDate,
+    pub author: String,
+}
+
+// the sunday the calendar starts on, so the last column is the week `today` is in
+pub fn first_day(today: NaiveDate, weeks: usize) -> NaiveDate {
+    today - Duration::days(today.weekday().num_days_from_sunday() as i64) - Duration::weeks(weeks.saturating_sub(1) as i64)
+}
+
+// commits per day, a column per week, sunday first
+pub fn heatmap(contributions: &[Contribution], author: Option<&str>, today: NaiveDate, weeks: usize) -> Vec<[usize; 7]> {
+    let first = first_day(today, weeks);
+    let mut grid = vec![[0; 7]; weeks];
+    for contribution in contributions.iter().filter(|c| author.is_none_or(|a| a == c.author)) {
+        let offset = (contribution.day - first).num_days();
+        if offset >= 0 && (offset as usize) < weeks * 7 {
+            grid[offset as usize / 7][offset as usize % 7] += 1;
+        }
+    }
+    grid
+}
+
+// everyone who committed in the range, busiest first
+pub fn authors(contributions: &[Contribution]) -> Vec<(String, usize)> {
+    let mut counts: Vec<(String, usize)> = Vec::new();
+    for contribution in contributions {
+        match counts.iter_mut().find(|(name, _)| *name == contribution.author) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((contribution.author.clone(), 1)),
+        }
+    }
+    counts.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    counts
+}
+
+// 0 for nothing, then 1-4 in quarters of the busiest day, like github's shades
+pub fn level(count: usize, max: usize) -> usize {
+    match count {
+        0 => 0,
+        _ if max == 0 => 0,
+        _ => (count * 4).div_ceil(max).clamp(1, 4),
+    }
+}
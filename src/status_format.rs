@@ -0,0 +1,106 @@
+use crate::git::{IndexConflict, RepoStatus};
+use std::collections::BTreeMap;
+
+// `git-hydra status --short` / `--porcelain` for scripts and shell prompts, git's own v1 lines:
+// XY and the path, X for the index and Y for the worktree, "??" for untracked files and the
+// two-letter codes (UU, AA, DU, ...) for a conflict. --porcelain starts with the branch line,
+//   ## main...origin/main [ahead 1, behind 2]
+// and stays the same from one version to the next. paths with a quote, backslash or control
+// character in them (or a space) are quoted the way git does, -z ends every entry with a NUL instead and
+// leaves them alone. both exit with 1 when anything changed, 0 on a clean tree.
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Format {
+    Short,
+    Porcelain,
+}
+
+pub fn render(status: &RepoStatus, conflicts: &[IndexConflict], format: Format, nul: bool) -> String {
+    let mut out = String::new();
+    let end = if nul { '\0' } else { '\n' };
+    if format == Format::Porcelain {
+        out.push_str(&branch_line(status));
+        out.push(end);
+    }
+    for (code, path) in entries(status, conflicts) {
+        let path = if nul { path } else { quote(&path) };
+        out.push_str(&format!("{} {}", code, path));
+        out.push(end);
+    }
+    out
+}
+
+pub fn is_dirty(status: &RepoStatus, conflicts: &[IndexConflict]) -> bool {
+    !status.staged.is_empty() || !status.unstaged.is_empty() || !status.untracked.is_empty() || !conflicts.is_empty()
+}
+
+pub fn branch_line(status: &RepoStatus) -> String {
+    let mut line = format!("## {}", status.branch);
+    if let Some(upstream) = &status.upstream {
+        line.push_str(&format!("...{}", upstream));
+        let counts: Vec<String> = [("ahead", status.ahead), ("behind", status.behind)]
+            .iter()
+            .filter(|(_, n)| *n > 0)
+            .map(|(what, n)| format!("{} {}", what, n))
+            .collect();
+        if !counts.is_empty() {
+            line.push_str(&format!(" [{}]", counts.join(", ")));
+        }
+    }
+    line
+}
+
+// sorted by path like git's, untracked ones last
+fn entries(status: &RepoStatus, conflicts: &[IndexConflict]) -> Vec<(String, String)> {
+    let mut tracked: BTreeMap<&str, (char, char)> = BTreeMap::new();
+    for file in &status.staged {
+        tracked.entry(&file.path).or_insert((' ', ' ')).0 = code(&file.status);
+    }
+    for file in &status.unstaged {
+        tracked.entry(&file.path).or_insert((' ', ' ')).1 = code(&file.status);
+    }
+    for conflict in conflicts {
+        tracked.insert(&conflict.path, conflict_code(conflict));
+    }
+    let mut entries: Vec<(String, String)> = tracked.into_iter().map(|(path, (x, y))| (format!("{}{}", x, y), path.to_string())).collect();
+    let mut untracked: Vec<&str> = status.untracked.iter().map(|f| f.path.as_str()).collect();
+    untracked.sort();
+    entries.extend(untracked.into_iter().map(|path| ("??".to_string(), path.to_string())));
+    entries
+}
+
+fn code(status: &str) -> char {
+    status.chars().next().unwrap_or(' ')
+}
+
+fn conflict_code(conflict: &IndexConflict) -> (char, char) {
+    match (conflict.base, conflict.ours, conflict.theirs) {
+        (true, false, false) => ('D', 'D'),
+        (false, true, false) => ('A', 'U'),
+        (true, false, true) => ('D', 'U'),
+        (true, true, false) => ('U', 'D'),
+        (false, false, true) => ('U', 'A'),
+        (false, true, true) => ('A', 'A'),
+        _ => ('U', 'U'),
+    }
+}
+
+// "a \"b\"\\c" for the path a "b"\c, anything without those or a space as it is
+pub fn quote(path: &str) -> String {
+    if !path.chars().any(|c| c == ' ' || c == '"' || c == '\\' || c.is_control()) {
+        return path.to_string();
+    }
+    let mut quoted = String::from("\"");
+    for c in path.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\{:03o}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
@@ -0,0 +1,45 @@
+use super::driver::Driver;
+use super::fixture::Fixture;
+use crate::announce::{self, Announcer};
+
+#[test]
+fn only_changes_are_announced() {
+    let fixture = Fixture::with_commit();
+    fixture.write("a.txt", "new\n");
+    let file = fixture.path.join(".git/announce");
+    let mut driver = Driver::new(fixture.app());
+    driver.app.announcer = Some(Announcer::open(file.to_str().unwrap()).unwrap());
+
+    driver.app.announce();
+    driver.app.announce();
+    driver.keys("2");
+    driver.app.announce();
+    let said = std::fs::read_to_string(&file).unwrap();
+    let lines: Vec<&str> = said.lines().collect();
+    assert_eq!(lines[0], "status view");
+    assert_eq!(lines[1], "untracked file a.txt");
+    assert!(lines[2].starts_with("HEAD at ") && lines[2].ends_with(" first commit"), "{}", said);
+    assert_eq!(lines[3], "log view");
+    assert!(lines[4].starts_with("commit ") && lines[4].ends_with("by hydra test: first commit"), "{}", said);
+    assert_eq!(lines.len(), 5, "{}", said);
+}
+
+#[test]
+fn notifications_are_announced_once() {
+    let fixture = Fixture::with_commit();
+    let file = fixture.path.join(".git/announce");
+    let mut app = fixture.app();
+    app.announcer = Some(Announcer::open(file.to_str().unwrap()).unwrap());
+    app.announce();
+    app.notify("pushed main");
+    app.announce();
+    app.announce();
+    let said = std::fs::read_to_string(&file).unwrap();
+    assert_eq!(said.lines().filter(|l| *l == "pushed main").count(), 1, "{}", said);
+}
+
+#[test]
+fn mode_names_are_words() {
+    assert_eq!(announce::mode_name(&crate::app::AppMode::CommitDialog), "commit dialog");
+    assert_eq!(announce::mode_name(&crate::git::MergeResolution::KeepOurs), "keep ours");
+}
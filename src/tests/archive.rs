@@ -0,0 +1,24 @@
+use crate::archive::{write_archive, ArchiveEntry, ArchiveFormat};
+
+fn entry(path: String) -> ArchiveEntry {
+    ArchiveEntry { path, data: b"x".to_vec(), executable: false, symlink: false }
+}
+
+#[test]
+fn zips_past_the_plain_format_limits_are_refused() {
+    let mut out = Vec::new();
+    let many: Vec<ArchiveEntry> = (0..=u16::MAX as usize).map(|i| entry(format!("f{}", i))).collect();
+    let err = write_archive(ArchiveFormat::Zip, &many, 0, &mut out).unwrap_err();
+    assert!(format!("{:#}", err).contains("65536 files"), "{:#}", err);
+    assert!(out.is_empty(), "nothing is written before the check");
+
+    let long = [entry("d/".repeat(40_000))];
+    assert!(write_archive(ArchiveFormat::Zip, &long, 0, &mut out).is_err());
+    // the tar writer has no such limits
+    assert!(write_archive(ArchiveFormat::Tar, &long, 0, &mut out).is_ok());
+
+    let mut out = Vec::new();
+    write_archive(ArchiveFormat::Zip, &many[..u16::MAX as usize], 0, &mut out).unwrap();
+    let end = &out[out.len() - 22..];
+    assert_eq!(u16::from_le_bytes([end[10], end[11]]), u16::MAX);
+}
@@ -0,0 +1,55 @@
+use super::driver::Driver;
+use super::fixture::Fixture;
+use crate::auto_fetch::AutoFetch;
+use crate::events::{Message, Queue};
+use std::time::Duration;
+
+// what the run sends back when it's done
+fn fetched(queue: &Queue) -> Vec<String> {
+    match queue.task(Duration::from_secs(10)) {
+        Some(Message::Fetched(errors)) => errors,
+        other => panic!("auto-fetch never finished: {:?}", other),
+    }
+}
+
+#[test]
+fn fetches_every_remote_in_the_background() {
+    let upstream = Fixture::with_commit();
+    let fixture = Fixture::new();
+    fixture.git(&["remote", "add", "origin", &upstream.path.display().to_string()]);
+    fixture.git(&["fetch", "-q", "origin"]);
+    fixture.git(&["checkout", "-q", "-b", "main", "--track", "origin/main"]);
+    upstream.write("a", "a\n");
+    upstream.commit("new upstream");
+    let repo = fixture.repo();
+
+    let queue = Queue::default();
+    let mut auto_fetch = AutoFetch::new(Some(Duration::ZERO));
+    assert!(auto_fetch.tick(&repo, false, &queue));
+    assert!(!auto_fetch.tick(&repo, false, &queue), "one run at a time");
+    auto_fetch.finish(fetched(&queue));
+    assert!(!auto_fetch.running());
+    assert!(auto_fetch.errors.is_empty(), "{:?}", auto_fetch.errors);
+    assert_eq!(repo.status().unwrap().behind, 1);
+    assert!(repo.last_fetched().is_some());
+}
+
+#[test]
+fn off_without_the_setting_and_failures_show_up() {
+    let fixture = Fixture::with_commit();
+    fixture.git(&["remote", "add", "origin", &fixture.path.join("nowhere").display().to_string()]);
+    assert_eq!(AutoFetch::from_config(&fixture.repo()).interval(), None);
+
+    fixture.git(&["config", "hydra.autoFetch", "5"]);
+    let mut driver = Driver::new(fixture.app());
+    assert_eq!(driver.app.auto_fetch.interval(), Some(Duration::from_secs(300)));
+    driver.assert_screen_contains("fetched: not yet (auto every 5m)");
+
+    driver.app.auto_fetch = AutoFetch::new(Some(Duration::ZERO));
+    assert!(!driver.app.auto_fetch.tick(&fixture.repo(), true, &driver.app.queue), "nothing while offline");
+    driver.app.update(Message::Tick).unwrap();
+    let errors = fetched(&driver.app.queue);
+    driver.app.update(Message::Fetched(errors)).unwrap();
+    assert_eq!(driver.app.auto_fetch.errors.len(), 1);
+    driver.assert_screen_contains("fetch failed, origin:");
+}
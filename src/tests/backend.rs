@@ -0,0 +1,100 @@
+use super::fixture::Fixture;
+use crate::backend::{self, Operation};
+
+#[test]
+fn per_operation_setting_wins_over_the_default() {
+    let fixture = Fixture::with_commit();
+    let repo = fixture.repo();
+    assert_eq!(backend::choose(&repo, Operation::Push).name(), "libgit2");
+    fixture.git(&["config", "hydra.backend", "git"]);
+    fixture.git(&["config", "hydra.backend.push", "libgit2"]);
+    assert_eq!(backend::choose(&repo, Operation::Fetch).name(), "git");
+    assert_eq!(backend::choose(&repo, Operation::Push).name(), "libgit2");
+    // one it doesn't know falls through to hydra.backend
+    fixture.git(&["config", "hydra.backend.commit", "svn"]);
+    assert_eq!(backend::choose(&repo, Operation::Commit).name(), "git");
+}
+
+#[cfg(unix)]
+#[test]
+fn commits_through_git_run_the_hooks() {
+    use std::os::unix::fs::PermissionsExt;
+    let fixture = Fixture::with_commit();
+    fixture.write(".git/hooks/commit-msg", "#!/bin/sh\ngrep -q 'ticket-' \"$1\" || { echo 'no ticket' >&2; exit 1; }\n");
+    std::fs::set_permissions(fixture.path.join(".git/hooks/commit-msg"), std::fs::Permissions::from_mode(0o755)).unwrap();
+    let repo = fixture.repo();
+
+    fixture.write("b.txt", "b\n");
+    repo.stage_file("b.txt").unwrap();
+    repo.commit("libgit2 knows nothing of hooks").unwrap();
+
+    fixture.git(&["config", "hydra.backend.commit", "git"]);
+    fixture.write("c.txt", "c\n");
+    repo.stage_file("c.txt").unwrap();
+    let error = repo.commit("no reference").unwrap_err();
+    assert!(format!("{:#}", error).contains("no ticket"), "{:#}", error);
+    let id = repo.commit("ticket-12 add c").unwrap();
+    assert_eq!(fixture.git(&["rev-parse", "HEAD"]), id.to_string());
+    assert_eq!(fixture.git(&["log", "-1", "--format=%s"]), "ticket-12 add c");
+}
+
+#[test]
+fn fetch_push_and_checkout_through_git() {
+    let upstream = Fixture::with_commit();
+    upstream.git(&["config", "receive.denyCurrentBranch", "ignore"]);
+    let fixture = Fixture::new();
+    fixture.git(&["remote", "add", "origin", &upstream.path.display().to_string()]);
+    fixture.git(&["config", "hydra.backend", "git"]);
+    let mut repo = fixture.repo();
+
+    repo.fetch_remote("origin").unwrap();
+    fixture.git(&["checkout", "-q", "-b", "main", "--track", "origin/main"]);
+    fixture.git(&["branch", "topic"]);
+    repo.checkout_branch("topic").unwrap();
+    assert_eq!(fixture.git(&["branch", "--show-current"]), "topic");
+
+    fixture.write("b.txt", "b\n");
+    fixture.commit("on topic");
+    repo.push_to_remote("origin", "topic").unwrap();
+    assert_eq!(upstream.git(&["rev-parse", "topic"]), fixture.git(&["rev-parse", "topic"]));
+    assert!(repo.checkout_branch("nope").is_err());
+}
+
+#[test]
+fn checkout_blocked_by_local_changes_through_git_is_a_conflict() {
+    let fixture = Fixture::with_commit();
+    fixture.git(&["config", "hydra.backend", "git"]);
+    fixture.git(&["checkout", "-q", "-b", "other"]);
+    fixture.write("README", "on other\n");
+    fixture.commit("change README on other");
+    fixture.git(&["checkout", "-q", "main"]);
+    fixture.write("README", "local change\n");
+    let repo = fixture.repo();
+
+    let error = repo.checkout_branch("other").unwrap_err();
+    assert!(crate::git::is_checkout_conflict(&error), "{:#}", error);
+    assert_eq!(fixture.git(&["branch", "--show-current"]), "main");
+    // anything else git refuses stays a plain error
+    assert!(!crate::git::is_checkout_conflict(&repo.checkout_branch("nope").unwrap_err()));
+}
+
+#[test]
+fn conflicting_pull_through_git_says_so() {
+    let upstream = Fixture::with_commit();
+    upstream.git(&["config", "receive.denyCurrentBranch", "ignore"]);
+    let fixture = Fixture::new();
+    fixture.git(&["remote", "add", "origin", &upstream.path.display().to_string()]);
+    fixture.git(&["config", "hydra.backend", "git"]);
+    fixture.git(&["config", "pull.rebase", "false"]);
+    fixture.git(&["fetch", "-q", "origin"]);
+    fixture.git(&["checkout", "-q", "-b", "main", "--track", "origin/main"]);
+    fixture.write("README", "ours\n");
+    fixture.commit("ours");
+    upstream.write("README", "theirs\n");
+    upstream.commit("theirs");
+    let mut repo = fixture.repo();
+
+    let error = repo.pull_from_remote("origin", "main").unwrap_err();
+    assert!(error.to_string().contains("merge conflicts require resolution"), "{:#}", error);
+    assert!(!repo.index_conflicts().unwrap().is_empty());
+}
@@ -0,0 +1,22 @@
+use super::fixture::Fixture;
+
+#[test]
+fn fetching_a_bundle_never_moves_a_local_tag() {
+    let upstream = Fixture::with_commit();
+    upstream.git(&["tag", "v1.0"]);
+    upstream.git(&["tag", "v2.0"]);
+    let bundle = upstream.path.join("all.bundle");
+    upstream.git(&["bundle", "create", bundle.to_str().unwrap(), "--all"]);
+
+    let fixture = Fixture::with_commit();
+    fixture.write("other", "other\n");
+    fixture.commit("ours");
+    fixture.git(&["tag", "v1.0"]);
+    let ours = fixture.git(&["rev-parse", "v1.0"]);
+
+    let message = fixture.repo().fetch_bundle(&bundle).unwrap();
+    assert!(message.contains("kept the local v1.0"), "{}", message);
+    assert_eq!(fixture.git(&["rev-parse", "v1.0"]), ours);
+    assert_eq!(fixture.git(&["rev-parse", "v2.0"]), upstream.git(&["rev-parse", "v2.0"]));
+    assert_eq!(fixture.git(&["rev-parse", "bundle/main"]), upstream.git(&["rev-parse", "main"]));
+}
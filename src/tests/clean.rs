@@ -0,0 +1,58 @@
+use super::driver::Driver;
+use super::fixture::Fixture;
+use crate::app::AppMode;
+
+fn fixture() -> Fixture {
+    let fixture = Fixture::with_commit();
+    fixture.write(".gitignore", "build/\n*.log\n");
+    fixture.commit("ignore build output");
+    fixture.write("build/out.bin", "0123456789");
+    fixture.write("debug.log", "noise\n");
+    fixture.write("notes.txt", "keep me\n");
+    fixture
+}
+
+#[test]
+fn candidates_list_ignored_files_first() {
+    let fixture = fixture();
+    let entries = fixture.repo().clean_candidates().unwrap();
+    let found: Vec<(&str, bool)> = entries.iter().map(|e| (e.path.as_str(), e.ignored)).collect();
+    assert_eq!(found, [("build/", true), ("debug.log", true), ("notes.txt", false)]);
+    assert_eq!(entries[0].size, 10);
+}
+
+#[test]
+fn marking_the_ignored_ones_deletes_only_those() {
+    let fixture = fixture();
+    let mut driver = Driver::new(fixture.app());
+
+    driver.keys("<c-p>").type_text("clean").keys("<enter>");
+    assert_eq!(driver.app.mode, AppMode::Clean);
+    driver.keys("i <enter>");
+    driver.assert_screen_contains("git clean would remove 2 path(s)");
+    assert!(fixture.path.join("build/out.bin").exists(), "the dry run deletes nothing");
+
+    driver.keys("y");
+    assert!(!fixture.path.join("build").exists());
+    assert!(!fixture.path.join("debug.log").exists());
+    assert_eq!(fixture.read("notes.txt"), b"keep me\n");
+    let left: Vec<&str> = driver.app.clean_entries.iter().map(|e| e.path.as_str()).collect();
+    assert_eq!(left, ["notes.txt"]);
+}
+
+#[test]
+fn paths_that_look_like_globs_are_just_names() {
+    let fixture = fixture();
+    fixture.write("*", "a file named star\n");
+    fixture.write("[ab].txt", "brackets\n");
+    fixture.write("a.txt", "matched by [ab].txt as a glob\n");
+    let repo = fixture.repo();
+
+    let names = ["*".to_string(), "[ab].txt".to_string()];
+    assert_eq!(repo.clean_dry_run(&["-x"], &names).unwrap(), ["*", "[ab].txt"]);
+    assert_eq!(repo.clean_paths(&names).unwrap(), 2);
+    assert!(!fixture.path.join("*").exists() && !fixture.path.join("[ab].txt").exists());
+    for kept in ["a.txt", "notes.txt", "debug.log", "build/out.bin"] {
+        assert!(fixture.path.join(kept).exists(), "{} was deleted", kept);
+    }
+}
@@ -0,0 +1,57 @@
+use crate::colors::{self, ColorLevel};
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::{Color, Modifier, Style};
+
+fn env<'a>(vars: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a {
+    move |name| vars.iter().find(|(n, _)| *n == name).map(|(_, v)| v.to_string())
+}
+
+#[test]
+fn level_comes_from_the_environment_or_the_setting() {
+    assert_eq!(colors::detect(None, env(&[("TERM", "xterm-256color"), ("COLORTERM", "truecolor")])), ColorLevel::TrueColor);
+    assert_eq!(colors::detect(None, env(&[("TERM", "screen-256color")])), ColorLevel::Ansi256);
+    assert_eq!(colors::detect(None, env(&[("TERM", "xterm")])), ColorLevel::Ansi16);
+    assert_eq!(colors::detect(None, env(&[("TERM", "linux")])), ColorLevel::Ansi8);
+    assert_eq!(colors::detect(None, env(&[("TERM", "dumb")])), ColorLevel::None);
+    assert_eq!(colors::detect(None, env(&[("TERM", "xterm-256color"), ("NO_COLOR", "")])), ColorLevel::None);
+    assert_eq!(colors::detect(Some("256"), env(&[("TERM", "linux")])), ColorLevel::Ansi256);
+    // a setting it doesn't know leaves it to the guess
+    assert_eq!(colors::detect(Some("lots"), env(&[("TERM", "linux")])), ColorLevel::Ansi8);
+}
+
+fn buffer() -> Buffer {
+    let mut buffer = Buffer::empty(Rect::new(0, 0, 3, 1));
+    buffer.set_string(0, 0, "a", Style::default().fg(Color::Rgb(250, 10, 10)));
+    buffer.set_string(1, 0, "b", Style::default().fg(Color::LightGreen));
+    buffer.set_string(2, 0, "c", Style::default().fg(Color::White).bg(Color::Blue));
+    buffer
+}
+
+#[test]
+fn frames_are_brought_down_to_what_the_terminal_has() {
+    let mut full = buffer();
+    colors::degrade(&mut full, ColorLevel::TrueColor);
+    assert_eq!(full, buffer());
+
+    let mut indexed = buffer();
+    colors::degrade(&mut indexed, ColorLevel::Ansi256);
+    assert_eq!(indexed[(0, 0)].fg, Color::Indexed(196));
+
+    let mut sixteen = buffer();
+    colors::degrade(&mut sixteen, ColorLevel::Ansi16);
+    assert_eq!(sixteen[(0, 0)].fg, Color::LightRed);
+    assert_eq!(sixteen[(1, 0)].fg, Color::LightGreen);
+
+    // bright colors turn bold instead
+    let mut eight = buffer();
+    colors::degrade(&mut eight, ColorLevel::Ansi8);
+    assert_eq!((eight[(1, 0)].fg, eight[(1, 0)].modifier), (Color::Green, Modifier::BOLD));
+    assert_eq!((eight[(2, 0)].fg, eight[(2, 0)].bg), (Color::Gray, Color::Blue));
+
+    let mut none = buffer();
+    colors::degrade(&mut none, ColorLevel::None);
+    assert!(none.content.iter().all(|c| c.fg == Color::Reset && c.bg == Color::Reset));
+    assert!(none[(2, 0)].modifier.contains(Modifier::REVERSED));
+    assert!(!none[(0, 0)].modifier.contains(Modifier::REVERSED));
+}
@@ -0,0 +1,47 @@
+use super::fixture::Fixture;
+use crate::completions::{self, Candidates, Shell};
+use clap::{Arg, Command};
+
+fn command() -> Command {
+    Command::new("git-hydra")
+        .arg(Arg::new("repo").long("repo").short('r').value_parser(clap::value_parser!(std::path::PathBuf)))
+        .subcommand(
+            Command::new("diff")
+                .arg(Arg::new("against").long("against").value_name("BRANCH"))
+                .arg(Arg::new("format").long("format").value_parser(["text", "svg"])),
+        )
+        .subcommand(Command::new("secret").hide(true))
+}
+
+#[test]
+fn bash_completes_subcommands_choices_and_branches() {
+    let script = completions::generate(Shell::Bash, &mut command());
+    assert!(script.contains("diff|help) cmd="), "{}", script);
+    assert!(script.contains(":--repo|:-r) COMPREPLY=($(compgen -f"));
+    assert!(script.contains("diff:--format) COMPREPLY=($(compgen -W \"text svg\""));
+    assert!(script.contains("diff:--against) COMPREPLY=($(compgen -W \"$(git-hydra complete branches 2>/dev/null)\""));
+    assert!(!script.contains("secret"));
+    assert!(script.ends_with("complete -F _git_hydra git-hydra\n"));
+}
+
+#[test]
+fn zsh_and_fish_know_the_same_options() {
+    let zsh = completions::generate(Shell::Zsh, &mut command());
+    assert!(zsh.starts_with("#compdef git-hydra\n"));
+    assert!(zsh.contains("diff) _arguments '--against=[]: :{compadd -- ${(f)\"$(git-hydra complete branches 2>/dev/null)\"}}' '--format=[]: :(text svg)'"), "{}", zsh);
+
+    let fish = completions::generate(Shell::Fish, &mut command());
+    assert!(fish.contains("complete -c git-hydra -n __fish_use_subcommand -a diff\n"));
+    assert!(fish.contains("complete -c git-hydra -n '__fish_seen_subcommand_from diff' -l format -r -a 'text svg'\n"), "{}", fish);
+    assert!(fish.contains("complete -c git-hydra -n __fish_use_subcommand -s r -l repo -r -F\n"));
+}
+
+#[test]
+fn candidates_come_from_the_repo() {
+    let fixture = Fixture::with_commit();
+    fixture.git(&["branch", "feature"]);
+    fixture.git(&["remote", "add", "upstream", "https://example.com/r.git"]);
+    let repo = fixture.repo();
+    assert_eq!(completions::candidates(&repo, Candidates::Branches), ["feature", "main"]);
+    assert_eq!(completions::candidates(&repo, Candidates::Remotes), ["upstream"]);
+}
@@ -0,0 +1,61 @@
+use crate::app::App;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::{Terminal, backend::TestBackend};
+
+const WIDTH: u16 = 120;
+const HEIGHT: u16 = 40;
+
+// drives an App with scripted keys and renders it like the run loop does, on a TestBackend
+pub struct Driver {
+    pub app: App,
+    terminal: Terminal<TestBackend>,
+}
+
+impl Driver {
+    pub fn new(app: App) -> Self {
+        let terminal = Terminal::new(TestBackend::new(WIDTH, HEIGHT)).expect("test terminal");
+        Self { app, terminal }
+    }
+
+    // space separated keys: plain chars ("j", "2", or "jjk" for several) and the named ones of
+    // replay::parse_key, <enter> <esc> <space> <c-p> and so on
+    pub fn keys(&mut self, script: &str) -> &mut Self {
+        for key in parse_keys(script) {
+            self.app.handle_input(key);
+            // a frame between keys, some state only settles when it's drawn
+            self.screen();
+        }
+        self
+    }
+
+    // each char as its own key press, for text fields
+    pub fn type_text(&mut self, text: &str) -> &mut Self {
+        for c in text.chars() {
+            self.app.handle_input(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+        }
+        self
+    }
+
+    // the rendered screen as text, see snapshot::text
+    pub fn screen(&mut self) -> String {
+        self.terminal.draw(|f| crate::ui::draw(f, &self.app)).expect("draw");
+        crate::snapshot::text(self.terminal.backend().buffer())
+    }
+
+    pub fn assert_screen_contains(&mut self, text: &str) {
+        let screen = self.screen();
+        assert!(screen.contains(text), "{:?} isn't on the screen:\n{}", text, screen);
+    }
+}
+
+pub fn parse_keys(script: &str) -> Vec<KeyEvent> {
+    let mut keys = Vec::new();
+    for token in script.split_whitespace() {
+        if token.starts_with('<') && token.len() > 1 {
+            keys.push(crate::replay::parse_key(token).unwrap_or_else(|| panic!("unknown key {}", token)));
+        } else {
+            keys.extend(token.chars().map(|c| KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)));
+        }
+    }
+    keys
+}
@@ -0,0 +1,44 @@
+use super::driver::Driver;
+use super::fixture::Fixture;
+use crate::events::{Message, Queue, Watcher};
+use std::time::Duration;
+
+#[test]
+fn spawned_work_comes_out_of_the_queue() {
+    let queue = Queue::default();
+    queue.spawn(|| Message::Fetched(vec!["origin: nope".to_string()]));
+    match queue.task(Duration::from_secs(5)) {
+        Some(Message::Fetched(errors)) => assert_eq!(errors, ["origin: nope"]),
+        other => panic!("{:?}", other),
+    }
+    assert!(queue.task(Duration::ZERO).is_none());
+}
+
+#[test]
+fn commits_from_outside_are_picked_up_and_hydras_own_are_not() {
+    let fixture = Fixture::with_commit();
+    let mut driver = Driver::new(fixture.app());
+    driver.keys("2");
+    assert!(!driver.app.update(Message::RepoChanged).unwrap(), "nothing moved yet");
+
+    fixture.write("b.txt", "b\n");
+    fixture.commit("from another terminal");
+    assert!(driver.app.update(Message::RepoChanged).unwrap());
+    assert_eq!(driver.app.commits[0].message.trim(), "from another terminal");
+    driver.assert_screen_contains("outside git-hydra, refreshed");
+
+    // the same change only counts once
+    assert!(!driver.app.update(Message::RepoChanged).unwrap());
+}
+
+#[test]
+fn the_watcher_sees_the_index_move() {
+    let fixture = Fixture::with_commit();
+    let queue = Queue::default();
+    let _watcher = Watcher::start(&fixture.repo(), queue.sender());
+    // give it time to take its first look
+    std::thread::sleep(Duration::from_millis(100));
+    fixture.write("b.txt", "b\n");
+    fixture.git(&["add", "b.txt"]);
+    assert!(matches!(queue.task(Duration::from_secs(5)), Some(Message::RepoChanged)));
+}
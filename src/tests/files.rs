@@ -0,0 +1,71 @@
+use super::driver::Driver;
+use super::fixture::Fixture;
+use crate::app::AppMode;
+use crate::files::Preview;
+use crate::highlight::{highlight, Kind};
+
+// README at the top, src/main.rs changed after it was added
+fn fixture() -> Fixture {
+    let fixture = Fixture::with_commit();
+    fixture.write("src/main.rs", "fn main() {}\n");
+    fixture.commit("add main");
+    fixture.write("src/main.rs", "fn main() { run() }\n");
+    fixture.commit("run");
+    fixture
+}
+
+#[test]
+fn browses_the_tree_of_an_old_commit() {
+    let fixture = fixture();
+    let mut driver = Driver::new(fixture.app());
+
+    driver.keys("2 <down> f");
+    assert_eq!(driver.app.mode, AppMode::Files);
+    let names: Vec<_> = driver.app.files.as_ref().unwrap().entries.iter().map(|e| e.name.clone()).collect();
+    assert_eq!(names, ["src", "README"]);
+    driver.assert_screen_contains("src/");
+
+    driver.keys("<enter>");
+    assert_eq!(driver.app.files.as_ref().unwrap().dir, "src/");
+    driver.assert_screen_contains("fn main() {}");
+
+    driver.keys("<left>");
+    let files = driver.app.files.as_ref().unwrap();
+    assert_eq!((files.dir.as_str(), files.view.selected), ("", 0));
+    assert!(matches!(files.preview, Preview::Dir(1)));
+
+    driver.keys("<esc>");
+    assert_eq!(driver.app.mode, AppMode::Log);
+}
+
+#[test]
+fn search_goes_to_the_file_and_export_writes_it_as_it_was() {
+    let fixture = fixture();
+    let mut driver = Driver::new(fixture.app());
+
+    driver.keys("2 <down> f /").type_text("MAIN");
+    assert_eq!(driver.app.files.as_ref().unwrap().search.as_ref().unwrap().matches, ["src/main.rs"]);
+    driver.keys("<enter>");
+    let files = driver.app.files.as_ref().unwrap();
+    assert!(files.search.is_none());
+    assert_eq!(files.selected().unwrap().path, "src/main.rs");
+
+    let short = fixture.repo().short_id(&files.commit);
+    assert_eq!(files.export_name(&fixture.repo()), Some(format!("main@{}.rs", short)));
+    assert!(files.export(&fixture.repo(), &fixture.path.join("src/main.rs")).is_err());
+    assert_eq!(fixture.read("src/main.rs"), b"fn main() { run() }\n");
+    assert_eq!(files.export(&fixture.repo(), &fixture.path.join("old.rs")).unwrap(), 13);
+    assert_eq!(fixture.read("old.rs"), b"fn main() {}\n");
+}
+
+#[test]
+fn highlights_by_extension() {
+    let lines = highlight("src/a.rs", "fn x() { \"}\" } // done\n/* one\ntwo */ 42");
+    assert_eq!(lines[0][0], (Kind::Keyword, "fn".to_string()));
+    assert!(lines[0].contains(&(Kind::String, "\"}\"".to_string())));
+    assert_eq!(lines[0].last().unwrap(), &(Kind::Comment, "// done".to_string()));
+    assert_eq!(lines[1], [(Kind::Comment, "/* one".to_string())]);
+    assert_eq!(lines[2][0], (Kind::Comment, "two */".to_string()));
+    assert_eq!(lines[2].last().unwrap(), &(Kind::Number, "42".to_string()));
+    assert_eq!(highlight("notes.txt", "fn x"), [[(Kind::Plain, "fn x".to_string())]]);
+}
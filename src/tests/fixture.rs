@@ -0,0 +1,121 @@
+use crate::app::App;
+use crate::git::Repository;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static NEXT: AtomicUsize = AtomicUsize::new(0);
+
+// a repo in the temp dir, removed again when it's dropped. set up through the git binary, so it's
+// in the state real git leaves behind and not whatever hydra's own code would make of it
+pub struct Fixture {
+    pub path: PathBuf,
+    // commits made through `commit` are a minute apart, so the log's time order is fixed
+    clock: std::cell::Cell<i64>,
+}
+
+impl Fixture {
+    pub fn new() -> Self {
+        let path = std::env::temp_dir().join(format!("git-hydra-test-{}-{}", std::process::id(), NEXT.fetch_add(1, Ordering::SeqCst)));
+        let _ = std::fs::remove_dir_all(&path);
+        std::fs::create_dir_all(&path).expect("create fixture dir");
+        let fixture = Self { path, clock: std::cell::Cell::new(1_700_000_000) };
+        fixture.git(&["init", "-q", "-b", "main"]);
+        // nothing from the machine's config that would change what the tests see
+        fixture.git(&["config", "user.name", "hydra test"]);
+        fixture.git(&["config", "user.email", "test@example.com"]);
+        fixture.git(&["config", "commit.gpgsign", "false"]);
+        fixture.git(&["config", "merge.conflictStyle", "merge"]);
+        fixture.git(&["config", "core.autocrlf", "false"]);
+        fixture
+    }
+
+    // one commit of README
+    pub fn with_commit() -> Self {
+        let fixture = Self::new();
+        fixture.write("README", "hello\n");
+        fixture.commit("first commit");
+        fixture
+    }
+
+    // main and other both changed line 2 of f, stopped in the merge of other
+    pub fn with_conflict() -> Self {
+        let fixture = Self::new();
+        fixture.write("f", "a\nb\nc\n");
+        fixture.commit("base");
+        fixture.git(&["checkout", "-q", "-b", "other"]);
+        fixture.write("f", "a\ntheirs\nc\n");
+        fixture.commit("theirs");
+        fixture.git(&["checkout", "-q", "main"]);
+        fixture.write("f", "a\nours\nc\n");
+        fixture.commit("ours");
+        assert!(!fixture.try_git(&["merge", "-q", "other"]), "the merge should have conflicted");
+        fixture
+    }
+
+    pub fn write(&self, path: &str, content: impl AsRef<[u8]>) {
+        let full = self.path.join(path);
+        if let Some(parent) = full.parent() {
+            std::fs::create_dir_all(parent).expect("create parent dir");
+        }
+        std::fs::write(full, content).expect("write fixture file");
+    }
+
+    pub fn read(&self, path: &str) -> Vec<u8> {
+        std::fs::read(self.path.join(path)).expect("read fixture file")
+    }
+
+    // everything in the worktree, returns the new commit's id
+    pub fn commit(&self, message: &str) -> String {
+        self.git(&["add", "-A"]);
+        let date = format!("@{} +0000", self.clock.get());
+        self.clock.set(self.clock.get() + 60);
+        let output = self
+            .command(&["commit", "-q", "--allow-empty", "-m", message])
+            .env("GIT_AUTHOR_DATE", &date)
+            .env("GIT_COMMITTER_DATE", &date)
+            .output()
+            .expect("run git");
+        assert!(output.status.success(), "git commit failed: {}", String::from_utf8_lossy(&output.stderr));
+        self.git(&["rev-parse", "HEAD"])
+    }
+
+    // stdout, trimmed. panics when git fails
+    pub fn git(&self, args: &[&str]) -> String {
+        let output = self.command(args).output().expect("run git");
+        assert!(output.status.success(), "git {} failed: {}", args.join(" "), String::from_utf8_lossy(&output.stderr));
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    pub fn try_git(&self, args: &[&str]) -> bool {
+        self.command(args).output().is_ok_and(|o| o.status.success())
+    }
+
+    fn command(&self, args: &[&str]) -> Command {
+        let mut cmd = Command::new("git");
+        cmd.args(args)
+            .current_dir(&self.path)
+            .env("GIT_CONFIG_NOSYSTEM", "1")
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .env("GIT_EDITOR", "true");
+        cmd
+    }
+
+    pub fn repo(&self) -> Repository {
+        Repository::open(&self.path).expect("open fixture repo")
+    }
+
+    // an App on the repo with its data loaded, like right after startup
+    pub fn app(&self) -> App {
+        let mut app = App::new(self.repo()).expect("create app");
+        app.refresh_data().expect("refresh app");
+        app
+    }
+
+}
+
+impl Drop for Fixture {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
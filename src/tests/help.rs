@@ -0,0 +1,49 @@
+use super::driver::Driver;
+use super::fixture::Fixture;
+use crate::app::AppMode;
+use crate::help;
+
+#[test]
+fn every_page_is_titled_with_its_topic() {
+    for page in help::PAGES {
+        assert_eq!(page.text.lines().next(), Some(format!("# {}", page.topic).as_str()));
+    }
+}
+
+#[test]
+fn search_finds_lines_on_every_page() {
+    let matches = help::search("MERGETOOL");
+    assert!(!matches.is_empty());
+    let (page, line) = matches[0];
+    assert_eq!(help::PAGES[page].topic, "conflicts");
+    assert!(help::PAGES[page].text.lines().nth(line).unwrap().contains("mergetool"));
+    assert!(help::search("").is_empty());
+}
+
+#[test]
+fn man_page_has_a_section_per_page() {
+    let man = help::man();
+    assert!(man.starts_with(".TH GIT-HYDRA 1"));
+    for page in help::PAGES {
+        assert!(man.contains(&format!(".SH {}\n", page.topic.to_uppercase())), "{}", page.topic);
+    }
+    assert!(man.contains(".IP \\(bu 2\n"));
+    assert!(!man.lines().any(|l| l.starts_with("- ")));
+}
+
+#[test]
+fn f1_opens_the_page_of_the_view_and_searches() {
+    let fixture = Fixture::with_commit();
+    let mut driver = Driver::new(fixture.app());
+    driver.keys("2 <f1>");
+    assert_eq!(driver.app.mode, AppMode::Help);
+    assert_eq!(help::PAGES[driver.app.help_page].topic, "log");
+    driver.assert_screen_contains("commit history of HEAD");
+
+    driver.keys("/").type_text("gitflow").keys("<enter>");
+    assert_eq!(help::PAGES[driver.app.help_page].topic, "branches");
+    driver.assert_screen_contains("• f gitflow");
+
+    driver.keys("<esc>");
+    assert_eq!(driver.app.mode, AppMode::Log);
+}
@@ -0,0 +1,51 @@
+use super::driver::Driver;
+use super::fixture::Fixture;
+use crate::action::Action;
+use crate::app::AppMode;
+use crate::keymap::{self, Keymap};
+use crossterm::event::KeyCode;
+
+#[test]
+fn default_keys_stand_for_actions_in_their_view() {
+    let keymap = Keymap::default();
+    assert_eq!(keymap.action(AppMode::Status, KeyCode::Char('c')), Some(Action::Commit));
+    assert_eq!(keymap.action(AppMode::Branches, KeyCode::Char('T')), Some(Action::Stacks));
+    assert_eq!(keymap.action(AppMode::Status, KeyCode::Char('T')), Some(Action::StageContentOnly));
+    assert_eq!(keymap.action(AppMode::StashList, KeyCode::Char('5')), Some(Action::GoRemote));
+    // arrows and dialogs are the view's own
+    assert_eq!(keymap.action(AppMode::Log, KeyCode::Down), None);
+    assert_eq!(keymap.action(AppMode::CommitDialog, KeyCode::Char('q')), None);
+}
+
+#[test]
+fn bindings_parse_with_and_without_a_view() {
+    assert_eq!(keymap::parse("log J = Jump to HEAD"), Ok(vec![(AppMode::Log, KeyCode::Char('J'), Action::JumpToHead)]));
+    let everywhere = keymap::parse("<f6> = fetch selected remote").unwrap();
+    assert_eq!(everywhere.len(), 5);
+    assert!(everywhere.iter().all(|(_, key, action)| *key == KeyCode::F(6) && *action == Action::Fetch));
+
+    assert!(keymap::parse("log J").is_err());
+    assert!(keymap::parse("log J = fly").unwrap_err().contains("no command called 'fly'"));
+    assert!(keymap::parse("graph J = quit").unwrap_err().contains("no view called 'graph'"));
+    assert!(keymap::parse("log <c-j> = quit").is_err());
+}
+
+#[test]
+fn hydra_bind_replaces_a_key_and_actions_run_without_a_terminal() {
+    let fixture = Fixture::with_commit();
+    fixture.git(&["config", "--add", "hydra.bind", "status J = go to log"]);
+    fixture.git(&["config", "--add", "hydra.bind", "log q = go to branches"]);
+    let mut app = fixture.app();
+    assert_eq!(app.error_message, None);
+
+    app.dispatch(Action::GoRemote).unwrap();
+    assert_eq!(app.mode, AppMode::RemoteOperations);
+
+    app.mode = AppMode::Status;
+    let mut driver = Driver::new(app);
+    driver.keys("J");
+    assert_eq!(driver.app.mode, AppMode::Log);
+    driver.keys("q");
+    assert_eq!(driver.app.mode, AppMode::Branches);
+    assert!(!driver.app.should_quit);
+}
@@ -0,0 +1,77 @@
+use super::fixture::Fixture;
+use crate::log_format::{self, Layout, Options};
+use chrono::{Local, TimeZone};
+
+fn options(layout: Layout) -> Options {
+    Options { layout, graph: false, author: None, since: None }
+}
+
+#[test]
+fn format_placeholders_fill_in() {
+    let fixture = Fixture::with_commit();
+    fixture.write("a", "a\n");
+    let id = fixture.commit("add a\n\nwith a body");
+    let repo = fixture.repo();
+    let commit = repo.get_commits(1).unwrap().remove(0);
+
+    assert_eq!(
+        log_format::format(&repo, &commit, "%h|%H|%s|%b|%an <%ae>|%ad|%n|100%%|%x"),
+        format!("{}|{}|add a|with a body|hydra test <test@example.com>|Tue Nov 14 22:14:20 2023 +0000|\n|100%|%x", &id[..8], id)
+    );
+    assert_eq!(log_format::lines(&repo, &commit, &Layout::Oneline), [format!("{} add a", &id[..8])]);
+}
+
+#[test]
+fn author_and_since_leave_commits_out_before_counting() {
+    let fixture = Fixture::with_commit();
+    fixture.git(&["config", "user.name", "someone else"]);
+    fixture.write("a", "a\n");
+    fixture.commit("theirs");
+    fixture.git(&["config", "user.name", "hydra test"]);
+    fixture.write("b", "b\n");
+    fixture.commit("mine");
+    let repo = fixture.repo();
+
+    let mine = Options { author: Some("HYDRA".to_string()), ..options(Layout::Format("%s".to_string())) };
+    assert_eq!(log_format::render(&repo, 10, &mine).unwrap(), "mine\nfirst commit\n");
+    assert_eq!(log_format::render(&repo, 1, &mine).unwrap(), "mine\n");
+
+    // the second commit is a minute after the first
+    let since = Some(chrono::DateTime::from_timestamp(1_700_000_060, 0).unwrap().fixed_offset());
+    let recent = Options { since, ..options(Layout::Format("%s".to_string())) };
+    assert_eq!(log_format::render(&repo, 10, &recent).unwrap(), "mine\ntheirs\n");
+}
+
+#[test]
+fn graph_puts_the_lanes_in_front() {
+    let fixture = Fixture::with_commit();
+    fixture.git(&["checkout", "-q", "-b", "topic"]);
+    fixture.write("a", "a\n");
+    fixture.commit("on topic");
+    fixture.git(&["checkout", "-q", "main"]);
+    fixture.write("b", "b\n");
+    fixture.commit("on main");
+    fixture.git(&["merge", "-q", "--no-ff", "-m", "merge topic", "topic"]);
+
+    let graph = Options { graph: true, ..options(Layout::Format("%s%nsecond line".to_string())) };
+    let out = log_format::render(&fixture.repo(), 10, &graph).unwrap();
+    let lines: Vec<&str> = out.lines().collect();
+    assert_eq!(lines.len(), 8);
+    assert!(lines[0].contains('●') && lines[0].ends_with("merge topic"), "{}", out);
+    // both sides of the merge go on under it, nothing under the first commit
+    assert!(lines[1].starts_with("│ │ second line"), "{}", out);
+    assert!(lines[6].ends_with("● first commit") && lines[7].trim() == "second line", "{}", out);
+}
+
+#[test]
+fn since_takes_dates_and_how_long_ago() {
+    let now = Local.with_ymd_and_hms(2024, 5, 20, 12, 0, 0).unwrap();
+    let ago = |text: &str| now.fixed_offset() - log_format::parse_since(text, now).unwrap();
+    assert_eq!(ago("2 weeks ago"), chrono::Duration::weeks(2));
+    assert_eq!(ago("3.days.ago"), chrono::Duration::days(3));
+    assert_eq!(ago("1 hour"), chrono::Duration::hours(1));
+    assert_eq!(ago("yesterday"), chrono::Duration::days(1));
+    assert_eq!(ago("2024-05-20"), chrono::Duration::hours(12));
+    assert!(log_format::parse_since("2024-05-06T10:00:00+02:00", now).is_ok());
+    assert!(log_format::parse_since("soon", now).is_err());
+}
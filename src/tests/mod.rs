@@ -0,0 +1,29 @@
+// the test harness: throwaway repos built with the git binary (fixture), and a driver that feeds
+// key presses to an App and renders it to a TestBackend, the way the run loop would
+mod announce;
+mod app_flow;
+mod archive;
+mod auto_fetch;
+mod backend;
+mod bundle;
+mod clean;
+mod colors;
+mod completions;
+mod driver;
+mod events;
+mod files;
+mod fixture;
+mod help;
+mod keymap;
+mod log_format;
+mod packages;
+mod partial_clone;
+mod pattern;
+mod release;
+mod release_notes;
+mod replay;
+mod repository;
+mod secrets;
+mod snapshot;
+mod status_format;
+mod watch;
@@ -0,0 +1,73 @@
+use super::driver::Driver;
+use super::fixture::Fixture;
+use crate::app::AppMode;
+use crate::packages::{self, Package};
+
+#[test]
+fn cargo_workspace_members_with_globs() {
+    let fixture = Fixture::new();
+    fixture.write(
+        "Cargo.toml",
+        "[workspace]\nmembers = [\n    \"crates/*\", # every crate\n    \"tools/gen\",\n]\nexclude = [\"crates/old\"]\n\n[workspace.package]\nversion = \"0.1.0\"\n",
+    );
+    fixture.write("crates/core/Cargo.toml", "[package]\nname = \"hydra-core\"\nversion.workspace = true\n");
+    fixture.write("crates/cli/Cargo.toml", "[package]\nname = \"hydra-cli\"\n");
+    fixture.write("crates/old/Cargo.toml", "[package]\nname = \"old\"\n");
+    fixture.write("crates/notes/README", "not a crate\n");
+    fixture.write("tools/gen/Cargo.toml", "[package]\nname = 'gen'\n");
+
+    let found: Vec<(String, String)> = packages::detect(&fixture.path).into_iter().map(|p| (p.name, p.path)).collect();
+    let expected = [("hydra-cli", "crates/cli"), ("hydra-core", "crates/core"), ("gen", "tools/gen")];
+    assert_eq!(found, expected.map(|(n, p)| (n.to_string(), p.to_string())));
+}
+
+#[test]
+fn package_json_workspaces() {
+    let fixture = Fixture::new();
+    fixture.write("package.json", r#"{ "name": "root", "private": true, "workspaces": { "packages": ["packages/*"] } }"#);
+    fixture.write("packages/web/package.json", r#"{ "name": "@acme/web", "dependencies": { "name": "x" } }"#);
+    fixture.write("packages/api/package.json", "{}");
+
+    let found = packages::detect(&fixture.path);
+    assert_eq!(
+        found,
+        [
+            Package { name: "api".to_string(), path: "packages/api".to_string(), kind: "npm" },
+            Package { name: "@acme/web".to_string(), path: "packages/web".to_string(), kind: "npm" },
+        ]
+    );
+    assert!(packages::detect(&Fixture::with_commit().path).is_empty());
+}
+
+#[test]
+fn picking_a_package_limits_status_and_log() {
+    let fixture = Fixture::new();
+    fixture.write("package.json", r#"{ "workspaces": ["packages/*"] }"#);
+    fixture.write("packages/a/package.json", r#"{ "name": "a" }"#);
+    fixture.write("packages/b/package.json", r#"{ "name": "b" }"#);
+    fixture.commit("set up the workspace");
+    fixture.write("packages/a/index.js", "a\n");
+    fixture.commit("work on a");
+    fixture.write("packages/b/index.js", "b\n");
+    fixture.commit("work on b");
+    fixture.write("packages/a/index.js", "changed\n");
+    fixture.write("packages/b/index.js", "changed\n");
+    let mut driver = Driver::new(fixture.app());
+
+    driver.keys("Z");
+    assert_eq!(driver.app.mode, AppMode::PackagePicker);
+    driver.keys("<down> <enter>");
+    assert_eq!(driver.app.mode, AppMode::Status);
+    driver.assert_screen_contains("[package: a]");
+    driver.assert_screen_contains("packages/a/index.js");
+    assert!(!driver.screen().contains("packages/b/index.js"));
+
+    driver.keys("2");
+    let subjects: Vec<&str> = driver.app.commits.iter().map(|c| c.message.trim()).collect();
+    assert_eq!(subjects, ["work on a", "set up the workspace"]);
+
+    // back to everything
+    driver.keys("Z <up> <enter>");
+    assert_eq!(driver.app.active_package, None);
+    assert_eq!(driver.app.commits.len(), 3);
+}
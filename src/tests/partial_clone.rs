@@ -0,0 +1,57 @@
+use super::fixture::Fixture;
+
+// a clone of `upstream` with --filter=blob:none, set up the way `git clone` does it
+fn partial_clone(upstream: &Fixture) -> Fixture {
+    upstream.git(&["config", "uploadpack.allowFilter", "true"]);
+    upstream.git(&["config", "uploadpack.allowAnySHA1InWant", "true"]);
+    let fixture = Fixture::new();
+    fixture.git(&["remote", "add", "origin", &format!("file://{}", upstream.path.display())]);
+    fixture.git(&["config", "remote.origin.promisor", "true"]);
+    fixture.git(&["config", "remote.origin.partialCloneFilter", "blob:none"]);
+    fixture.git(&["fetch", "-q", "--filter=blob:none", "origin"]);
+    fixture
+}
+
+fn upstream() -> Fixture {
+    let upstream = Fixture::with_commit();
+    upstream.write("README", "hello again\n");
+    upstream.commit("second");
+    upstream
+}
+
+#[test]
+fn diffs_fetch_the_blobs_they_need() {
+    let upstream = upstream();
+    let fixture = partial_clone(&upstream);
+    let repo = fixture.repo();
+    assert_eq!(repo.partial_clone(), Some(("origin".to_string(), "blob:none".to_string())));
+    assert!(fixture.git(&["rev-list", "--objects", "--missing=print", "origin/main"]).contains('?'));
+
+    let diff = repo.commit_diff("origin/main").unwrap();
+    assert!(diff.missing.is_empty());
+    assert!(diff.lines.contains(&"-hello".to_string()) && diff.lines.contains(&"+hello again".to_string()), "{:?}", diff.lines);
+    assert_eq!((diff.files[0].additions, diff.files[0].deletions), (1, 1));
+    assert!(Fixture::with_commit().repo().partial_clone().is_none());
+}
+
+#[test]
+fn blobs_it_cannot_get_are_shown_as_not_fetched() {
+    let upstream = upstream();
+    upstream.write("other", "other\n");
+    upstream.commit("third");
+    let fixture = partial_clone(&upstream);
+    let repo = fixture.repo();
+
+    repo.set_offline(true);
+    let diff = repo.commit_diff("origin/main~1").unwrap();
+    assert_eq!(diff.missing, ["README"]);
+    assert_eq!(diff.lines[0], "diff --git a/README b/README");
+    assert!(diff.lines[1].contains("not fetched"), "{:?}", diff.lines);
+    assert!(repo.archive_commit("origin/main", "x/", &fixture.path.join("x.tar")).is_err());
+
+    // the remote gone: still a diff, of what is here
+    repo.set_offline(false);
+    fixture.git(&["remote", "set-url", "origin", &format!("file://{}", fixture.path.join("nowhere").display())]);
+    let diff = repo.range_diff("origin/main~2", "origin/main").unwrap();
+    assert_eq!(diff.missing.len(), 2, "{:?}", diff.lines);
+}
@@ -0,0 +1,27 @@
+use crate::pattern::Pattern;
+
+#[test]
+fn repeats_keep_their_meaning() {
+    let pattern = Pattern::new(r"(\w+)-(\d{2,3}?)").unwrap();
+    let text = "see ABC-1234 and x-12";
+    let found = pattern.find_iter(text);
+    assert_eq!(found.len(), 2);
+    assert_eq!((found[0].group(text, 1), found[0].group(text, 2)), (Some("ABC"), Some("12")));
+    assert_eq!(found[1].group(text, 0), Some("x-12"));
+    assert!(Pattern::new("(?i)^fix(es)?: .*done$").unwrap().is_match("FIXES: it's done"));
+    assert!(!Pattern::new("a{3}").unwrap().is_match("aa"));
+}
+
+#[test]
+fn long_text_does_not_blow_the_stack() {
+    let text = format!("{}b", "a".repeat(200_000));
+    let found = Pattern::new("a.*b").unwrap().find_iter(&text);
+    assert_eq!((found.len(), found[0].end), (1, text.len()));
+
+    let text = "#12 ".repeat(20_000);
+    assert_eq!(Pattern::new(r"#(\d+)").unwrap().find_iter(&text).len(), 20_000);
+
+    // a repeated group still nests, past the depth limit it stops instead of crashing
+    let text = "ab".repeat(100_000);
+    assert!(Pattern::new("(ab)+c").unwrap().find_iter(&text).is_empty());
+}
@@ -0,0 +1,66 @@
+use super::driver::Driver;
+use super::fixture::Fixture;
+use crate::app::AppMode;
+use crate::release::{self, Bump, Version};
+
+#[test]
+fn versions_parse_and_bump() {
+    let (prefix, version) = Version::parse("v1.2.3").unwrap();
+    assert_eq!((prefix, version), ("v", Version { major: 1, minor: 2, patch: 3 }));
+    assert_eq!(Version::parse("1.2.3").map(|(p, _)| p), Some(""));
+    assert_eq!(Version::parse("v1.2.3-rc1"), None);
+    assert_eq!(Version::parse("release-1.2.3"), None);
+    assert_eq!(Version::parse("v1.2"), None);
+
+    assert_eq!(version.bump(Bump::Patch).to_string(), "1.2.4");
+    assert_eq!(version.bump(Bump::Minor).to_string(), "1.3.0");
+    assert_eq!(version.bump(Bump::Major).to_string(), "2.0.0");
+}
+
+#[test]
+fn commits_suggest_the_bump() {
+    let fixture = Fixture::with_commit();
+    let repo = fixture.repo();
+    let commits = |range: &str| repo.commits_in_range(range).unwrap();
+    let one = Version { major: 1, minor: 0, patch: 0 };
+    let zero = Version { major: 0, minor: 3, patch: 0 };
+
+    fixture.write("a", "a\n");
+    fixture.commit("fix: a");
+    assert_eq!(release::suggest(one, &commits("HEAD~1..HEAD")), Bump::Patch);
+    fixture.write("b", "b\n");
+    fixture.commit("feat: b");
+    assert_eq!(release::suggest(one, &commits("HEAD~2..HEAD")), Bump::Minor);
+    fixture.write("c", "c\n");
+    fixture.commit("refactor!: c");
+    assert_eq!(release::suggest(one, &commits("HEAD~3..HEAD")), Bump::Major);
+    // nothing's stable before 1.0.0
+    assert_eq!(release::suggest(zero, &commits("HEAD~3..HEAD")), Bump::Minor);
+}
+
+#[test]
+fn new_release_tags_and_pushes() {
+    let fixture = Fixture::with_commit();
+    let remote = Fixture::new();
+    // libgit2 only pushes to bare repos on disk
+    remote.git(&["config", "core.bare", "true"]);
+    fixture.git(&["remote", "add", "origin", &remote.path.display().to_string()]);
+    fixture.git(&["tag", "v0.1.0"]);
+    fixture.write("a", "a\n");
+    fixture.commit("feat: add a");
+    let mut driver = Driver::new(fixture.app());
+
+    driver.keys("<c-p>").type_text("new release").keys("<enter>");
+    assert_eq!(driver.app.mode, AppMode::NewRelease);
+    driver.assert_screen_contains("1 commit(s) since v0.1.0");
+    driver.assert_screen_contains("minor v0.2.0*");
+
+    // major, then back to minor
+    driver.keys("<right> <left> p <enter>");
+    assert_eq!(driver.app.error_message, None);
+    assert_eq!(driver.app.mode, AppMode::Status);
+    assert_eq!(fixture.git(&["cat-file", "-t", "v0.2.0"]), "tag");
+    let message = fixture.git(&["tag", "-l", "--format=%(contents)", "v0.2.0"]);
+    assert!(message.starts_with("## v0.2.0\n\n### features\n\n- add a"), "{}", message);
+    assert_eq!(remote.git(&["tag", "-l"]), "v0.2.0");
+}
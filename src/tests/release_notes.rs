@@ -0,0 +1,57 @@
+use super::fixture::Fixture;
+use crate::release_notes::{self, Conventional};
+
+#[test]
+fn conventional_subjects_parse() {
+    assert_eq!(
+        release_notes::parse("feat(parser)!: drop the old syntax"),
+        Some(Conventional { kind: "feat", scope: Some("parser"), breaking: true, description: "drop the old syntax" })
+    );
+    assert_eq!(release_notes::parse("fix: off by one").map(|c| (c.kind, c.scope)), Some(("fix", None)));
+    assert_eq!(release_notes::parse("update the readme"), None);
+    assert_eq!(release_notes::parse("see http://example.com"), None);
+    assert_eq!(release_notes::parse("feat(broken: no closing paren"), None);
+}
+
+#[test]
+fn notes_group_the_range_since_the_last_tag() {
+    let fixture = Fixture::with_commit();
+    fixture.git(&["tag", "v1.0.0"]);
+    fixture.write("a", "a\n");
+    fixture.commit("feat(ui): a new view");
+    fixture.write("b", "b\n");
+    fixture.commit("fix: crash on start\n\nBREAKING CHANGE: the config moved");
+    fixture.write("c", "c\n");
+    fixture.commit("tidy up");
+    let repo = fixture.repo();
+
+    let range = release_notes::default_range(&repo);
+    assert_eq!(range, "v1.0.0..HEAD");
+    let (notes, errors) = release_notes::generate(&repo, &range).unwrap();
+    assert!(errors.is_empty());
+    let lines: Vec<&str> = notes.lines().filter(|l| !l.is_empty()).map(|l| l.rsplit_once(" (").map_or(l, |(text, _)| text)).collect();
+    assert_eq!(
+        lines,
+        [
+            "## v1.0.0..HEAD",
+            "### ⚠ breaking changes",
+            "- the config moved",
+            "### features",
+            "- **ui:** a new view",
+            "### bug fixes",
+            "- crash on start",
+            "### other changes",
+            "- tidy up",
+        ]
+    );
+
+    // configured groups replace the built-in ones, * takes the rest
+    fixture.git(&["config", "--add", "hydra.notesGroup", "feat, fix: changes"]);
+    fixture.git(&["config", "--add", "hydra.notesGroup", "*: misc"]);
+    fixture.git(&["config", "--add", "hydra.notesGroup", "no heading"]);
+    let (notes, errors) = release_notes::generate(&repo, &range).unwrap();
+    assert_eq!(errors.len(), 1);
+    assert!(notes.contains("### changes\n\n- crash on start"));
+    assert!(notes.contains("### misc\n\n- tidy up"));
+    assert!(!notes.contains("other changes"));
+}
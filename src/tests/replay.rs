@@ -0,0 +1,65 @@
+use super::driver::Driver;
+use super::fixture::Fixture;
+use crate::app::AppMode;
+use crate::replay::{self, Replay};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+
+#[test]
+fn key_names_round_trip() {
+    let keys = [
+        KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE),
+        KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE),
+        KeyEvent::new(KeyCode::Char('<'), KeyModifiers::NONE),
+        KeyEvent::new(KeyCode::Char('p'), KeyModifiers::CONTROL),
+        KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE),
+        KeyEvent::new(KeyCode::F(12), KeyModifiers::NONE),
+    ];
+    for key in keys {
+        let name = replay::key_name(&key);
+        assert_eq!(replay::parse_key(&name), Some(key), "{}", name);
+    }
+    assert_eq!(replay::parse_key("jk"), None);
+    assert_eq!(replay::parse_key("<nope>"), None);
+}
+
+#[test]
+fn recording_replays_into_the_same_state() {
+    let fixture = Fixture::with_commit();
+    let file = fixture.path.join(".git/keys");
+    let mut driver = Driver::new(fixture.app());
+    driver.app.recorder = Some(replay::Recorder::create(&file, (120, 40)).unwrap());
+    driver.keys("2 <down> 3");
+    driver.app.recorder = None;
+
+    let recorded = std::fs::read_to_string(&file).unwrap();
+    assert!(recorded.starts_with("# git-hydra recording, 120x40\n"), "{}", recorded);
+    let keys = Replay::load(&file).unwrap().into_keys();
+    assert_eq!(keys, super::driver::parse_keys("2 <down> 3"));
+
+    let mut replayed = Driver::new(fixture.app());
+    for key in keys {
+        replayed.app.handle_input(key);
+    }
+    assert_eq!(replayed.app.mode, AppMode::Branches);
+}
+
+#[test]
+fn bad_lines_are_reported_with_their_number() {
+    let fixture = Fixture::new();
+    let file = fixture.path.join(".git/keys");
+    std::fs::write(&file, "# comment\n0 j\n\nsoon <enter>\n").unwrap();
+    let error = Replay::load(&file).err().expect("a parse error");
+    assert!(format!("{:#}", error).contains("line 4"), "{:#}", error);
+}
+
+#[test]
+fn due_waits_for_the_recorded_delay() {
+    let fixture = Fixture::new();
+    let file = fixture.path.join(".git/keys");
+    std::fs::write(&file, "0 j\n60000 k\n").unwrap();
+    let mut replay = Replay::load(&file).unwrap();
+    assert_eq!(replay.due().map(|k| k.code), Some(KeyCode::Char('j')));
+    assert_eq!(replay.due(), None);
+    assert!(replay.wait().is_some_and(|w| w.as_secs() > 50));
+    assert!(!replay.is_done());
+}
@@ -0,0 +1,230 @@
+use super::fixture::Fixture;
+use crate::git::{ConflictKind, MergeResolution, StashOptions, RestoreTarget, StashPopResult};
+use std::collections::HashMap;
+
+#[test]
+fn status_splits_staged_unstaged_and_untracked() {
+    let fixture = Fixture::with_commit();
+    fixture.write("README", "changed\n");
+    fixture.write("staged", "new\n");
+    fixture.write("untracked", "new\n");
+    let repo = fixture.repo();
+    repo.stage_file("staged").unwrap();
+
+    let status = repo.status().unwrap();
+    assert_eq!(status.branch, "main");
+    let paths = |files: &[crate::git::FileStatus]| files.iter().map(|f| f.path.clone()).collect::<Vec<_>>();
+    assert_eq!(paths(&status.staged), ["staged"]);
+    assert_eq!(paths(&status.unstaged), ["README"]);
+    assert_eq!(paths(&status.untracked), ["untracked"]);
+}
+
+#[test]
+fn commit_goes_on_top_of_the_log() {
+    let fixture = Fixture::with_commit();
+    fixture.write("README", "second\n");
+    let repo = fixture.repo();
+    repo.stage_file("README").unwrap();
+    let id = repo.commit("second commit").unwrap();
+
+    let commits = repo.get_commits(10).unwrap();
+    assert_eq!(commits.len(), 2);
+    assert_eq!(commits[0].id, id.to_string());
+    assert_eq!(commits[0].message.trim(), "second commit");
+    assert_eq!(fixture.git(&["rev-parse", "HEAD"]), id.to_string());
+}
+
+#[test]
+fn create_and_checkout_branch() {
+    let fixture = Fixture::with_commit();
+    let repo = fixture.repo();
+    repo.create_branch("feature").unwrap();
+    repo.checkout_branch("feature").unwrap();
+
+    assert!(repo.get_branches().unwrap().iter().any(|b| b == "feature"));
+    assert_eq!(fixture.git(&["symbolic-ref", "--short", "HEAD"]), "feature");
+}
+
+#[test]
+fn stash_save_and_pop_round_trip() {
+    let fixture = Fixture::with_commit();
+    fixture.write("README", "work in progress\n");
+    let mut repo = fixture.repo();
+    repo.stash_save(Some("wip"), StashOptions::default()).unwrap();
+
+    assert_eq!(fixture.read("README"), b"hello\n");
+    let stashes = repo.stash_list().unwrap();
+    assert_eq!(stashes.len(), 1);
+    assert!(stashes[0].message.contains("wip"));
+
+    assert_eq!(repo.stash_pop(0).unwrap(), StashPopResult::Popped);
+    assert_eq!(fixture.read("README"), b"work in progress\n");
+    assert!(repo.stash_list().unwrap().is_empty());
+}
+
+#[test]
+fn merge_conflict_is_detected_with_its_hunk() {
+    let fixture = Fixture::with_conflict();
+    let conflict = fixture.repo().detect_merge_conflicts().unwrap().expect("a conflict");
+
+    assert_eq!(conflict.files.len(), 1);
+    let file = &conflict.files[0];
+    assert_eq!((file.path.as_str(), file.kind), ("f", ConflictKind::Text));
+    assert_eq!(file.conflicts.len(), 1);
+    assert_eq!(file.conflicts[0].our_content.trim(), "ours");
+    assert_eq!(file.conflicts[0].their_content.trim(), "theirs");
+}
+
+#[test]
+fn hunk_resolution_rewrites_the_markers() {
+    let fixture = Fixture::with_conflict();
+    let mut repo = fixture.repo();
+    let conflict = repo.detect_merge_conflicts().unwrap().unwrap();
+    let resolutions = HashMap::from([((0, 0), MergeResolution::KeepBoth)]);
+    repo.resolve_conflicts(&resolutions, &conflict).unwrap();
+
+    assert_eq!(fixture.read("f"), b"a\nours\ntheirs\nc\n");
+    assert!(repo.detect_merge_conflicts().unwrap().is_none());
+}
+
+// markers in a file that isn't utf8 can't be parsed, the stage blob is written as it is
+#[test]
+fn whole_file_resolution_writes_the_stage_blob() {
+    let fixture = Fixture::new();
+    fixture.write("g", b"x\n");
+    fixture.commit("base");
+    fixture.git(&["checkout", "-q", "-b", "other"]);
+    fixture.write("g", b"x\xff theirs\n");
+    fixture.commit("theirs");
+    fixture.git(&["checkout", "-q", "main"]);
+    fixture.write("g", b"x\xfe ours\n");
+    fixture.commit("ours");
+    assert!(!fixture.try_git(&["merge", "-q", "other"]));
+
+    let mut repo = fixture.repo();
+    let conflict = repo.detect_merge_conflicts().unwrap().unwrap();
+    assert_eq!(conflict.files[0].kind, ConflictKind::Unparsed);
+    let resolutions = HashMap::from([((0, 0), MergeResolution::TheirsFile)]);
+    repo.resolve_conflicts(&resolutions, &conflict).unwrap();
+
+    assert_eq!(fixture.read("g"), b"x\xff theirs\n");
+    assert_eq!(fixture.git(&["diff", "--cached", "--name-only", "other", "--", "g"]), "");
+}
+
+#[test]
+fn range_diff_spans_several_commits() {
+    let fixture = Fixture::with_commit();
+    let first = fixture.git(&["rev-parse", "HEAD"]);
+    fixture.write("a", "a\n");
+    fixture.commit("add a");
+    fixture.write("README", "changed\n");
+    fixture.write("b", "b\n");
+    let last = fixture.commit("change readme, add b");
+
+    let diff = fixture.repo().range_diff(&first, &last).unwrap();
+    let files: Vec<_> = diff.files.iter().map(|f| (f.path.as_str(), f.status)).collect();
+    assert_eq!(files, [("README", 'M'), ("a", 'A'), ("b", 'A')]);
+    assert_eq!(diff.file_starts.len(), 3);
+    assert!(diff.lines[diff.file_starts[1]].starts_with("diff --git a/a b/a"));
+}
+
+#[test]
+fn line_counts_of_staged_unstaged_and_untracked_files() {
+    let fixture = Fixture::with_commit();
+    fixture.write("README", "hello\nstaged\n");
+    fixture.git(&["add", "README"]);
+    fixture.write("README", "changed\nstaged\nunstaged\n");
+    fixture.write("new", "a\nb\nc\n");
+
+    let counts = fixture.repo().line_counts().unwrap();
+    assert_eq!(counts.staged.get("README"), Some(&(1, 0)));
+    assert_eq!(counts.unstaged.get("README"), Some(&(2, 1)));
+    assert_eq!(counts.unstaged.get("new"), Some(&(3, 0)));
+}
+
+#[cfg(unix)]
+#[test]
+fn content_staged_without_the_mode_change() {
+    use std::os::unix::fs::PermissionsExt;
+    let fixture = Fixture::with_commit();
+    fixture.write("README", "hello\nmore\n");
+    std::fs::set_permissions(fixture.path.join("README"), std::fs::Permissions::from_mode(0o755)).unwrap();
+
+    let repo = fixture.repo();
+    assert_eq!(repo.status().unwrap().unstaged[0].mode_label().as_deref(), Some("100644 → 100755"));
+    repo.stage_content_only("README").unwrap();
+    assert!(fixture.git(&["diff", "--cached"]).contains("+more"));
+    assert!(!fixture.git(&["diff", "--cached", "--summary"]).contains("mode change"));
+    assert_eq!(fixture.git(&["diff", "--summary"]), "mode change 100644 => 100755 README");
+    assert_eq!(fixture.git(&["diff"]).lines().filter(|l| l.starts_with('+') || l.starts_with('-')).count(), 0);
+}
+
+#[test]
+fn stash_preview_finds_conflicts_and_local_changes() {
+    let fixture = Fixture::with_commit();
+    fixture.write("other", "x\n");
+    fixture.commit("add other");
+    fixture.write("README", "stashed\n");
+    fixture.write("other", "stashed too\n");
+    fixture.git(&["stash", "-q"]);
+    fixture.write("README", "committed\n");
+    fixture.commit("change readme");
+    fixture.write("other", "local\n");
+
+    let mut repo = fixture.repo();
+    let stash = repo.stash_list().unwrap()[0].oid;
+    let files = repo.stash_files(stash).unwrap();
+    let preview = repo.stash_preview(stash, &files).unwrap();
+    assert_eq!(preview.conflicts, ["README"]);
+    assert_eq!(preview.in_the_way, ["other"]);
+
+    fixture.git(&["checkout", "-q", "--", "other"]);
+    fixture.git(&["reset", "-q", "--hard", "HEAD~1"]);
+    assert_eq!(repo.stash_preview(stash, &files).unwrap(), Default::default());
+}
+
+#[test]
+fn short_ids_follow_core_abbrev_and_grow_past_a_collision() {
+    let fixture = Fixture::with_commit();
+    let head = fixture.git(&["rev-parse", "HEAD"]);
+    assert_eq!(fixture.repo().short_id(&head), head[..8]);
+    fixture.git(&["config", "core.abbrev", "12"]);
+    assert_eq!(fixture.repo().short_id(&head), head[..12]);
+
+    // two blobs whose ids start with the same 4 characters
+    fixture.git(&["config", "core.abbrev", "4"]);
+    let id = |n: u32| git2::Oid::hash_object(git2::ObjectType::Blob, n.to_string().as_bytes()).unwrap().to_string();
+    let mut seen = HashMap::new();
+    let (a, b) = (0..).find_map(|n| seen.insert(id(n)[..4].to_string(), n).map(|other| (other, n))).unwrap();
+    let repo = fixture.repo();
+    for n in [a, b] {
+        if n == b {
+            assert_eq!(repo.short_id(id(a)), id(a)[..4]);
+        }
+        fixture.write(&format!("blob{}", n), n.to_string());
+        fixture.git(&["hash-object", "-w", &format!("blob{}", n)]);
+    }
+    // remembered until a fetch (or a refresh) says objects came in
+    assert_eq!(repo.short_id(id(a)), id(a)[..4]);
+    repo.forget_short_ids();
+    let short = repo.short_id(id(a));
+    assert!(short.len() > 4 && id(a).starts_with(&short) && !id(b).starts_with(&short), "{}", short);
+}
+
+#[test]
+fn restore_takes_the_path_literally() {
+    let fixture = Fixture::new();
+    fixture.write("[ab].txt", "one\n");
+    fixture.write("a.txt", "one\n");
+    fixture.commit("two files");
+    fixture.write("[ab].txt", "two\n");
+    fixture.write("a.txt", "two\n");
+    let repo = fixture.repo();
+
+    // as a glob [ab].txt would be a.txt too
+    let preview = repo.restore_preview("HEAD", "[ab].txt", RestoreTarget::Worktree).unwrap();
+    assert!(preview.contains("[ab].txt") && !preview.contains("a/a.txt"), "{}", preview);
+    repo.restore_file("HEAD", "[ab].txt", RestoreTarget::Worktree).unwrap();
+    assert_eq!(fixture.read("[ab].txt"), b"one\n");
+    assert_eq!(fixture.read("a.txt"), b"two\n");
+}
@@ -0,0 +1,33 @@
+use super::fixture::Fixture;
+use crate::snapshot::{self, View};
+use ratatui::{buffer::Buffer, layout::Rect, style::{Color, Style}};
+
+#[test]
+fn text_has_a_line_per_row_without_trailing_blanks() {
+    let mut buffer = Buffer::empty(Rect::new(0, 0, 6, 2));
+    buffer.set_string(0, 0, "ab", Style::default());
+    buffer.set_string(2, 1, "c", Style::default());
+    assert_eq!(snapshot::text(&buffer), "ab\n  c\n");
+}
+
+#[test]
+fn svg_escapes_and_colors_runs() {
+    let mut buffer = Buffer::empty(Rect::new(0, 0, 8, 1));
+    buffer.set_string(0, 0, "<a&b>", Style::default().fg(Color::Red).bg(Color::Rgb(1, 2, 3)));
+    let svg = snapshot::svg(&buffer);
+    assert!(svg.starts_with("<svg "));
+    assert!(svg.contains(">&lt;a&amp;b&gt;</text>"), "{}", svg);
+    assert!(svg.contains("fill=\"#cd0000\""));
+    assert!(svg.contains("<rect x=\"0\" y=\"0\" width=\"45\" height=\"18\" fill=\"#010203\"/>"));
+}
+
+#[test]
+fn capture_opens_the_view() {
+    let fixture = Fixture::with_commit();
+    let mut app = fixture.app();
+    let buffer = snapshot::capture(&mut app, View::Log, &[], 100, 20).unwrap();
+    assert_eq!(buffer.area, Rect::new(0, 0, 100, 20));
+    let text = snapshot::text(&buffer);
+    assert!(text.contains("commit history"), "{}", text);
+    assert!(text.contains("first commit"));
+}
@@ -0,0 +1,46 @@
+use super::fixture::Fixture;
+use crate::status_format::{self, Format};
+
+#[test]
+fn porcelain_lines_match_git() {
+    let fixture = Fixture::with_commit();
+    fixture.write("staged", "s\n");
+    fixture.git(&["add", "staged"]);
+    fixture.write("staged", "s and more\n");
+    fixture.write("README", "changed\n");
+    fixture.write("new file", "n\n");
+    let repo = fixture.repo();
+    let status = repo.status().unwrap();
+    let conflicts = repo.index_conflicts().unwrap();
+
+    let ours = status_format::render(&status, &conflicts, Format::Short, false);
+    // git() trims the leading space of " M"
+    assert_eq!(ours.trim(), fixture.git(&["status", "--porcelain"]));
+    assert!(status_format::is_dirty(&status, &conflicts));
+    assert_eq!(status_format::render(&status, &conflicts, Format::Porcelain, true), "## main\0 M README\0AM staged\0?? new file\0");
+}
+
+#[test]
+fn porcelain_shows_conflicts_and_the_upstream() {
+    let fixture = Fixture::with_conflict();
+    let repo = fixture.repo();
+    let status = repo.status().unwrap();
+    let conflicts = repo.index_conflicts().unwrap();
+    assert_eq!(status_format::render(&status, &conflicts, Format::Porcelain, false), "## main\nUU f\n");
+
+    fixture.git(&["merge", "--abort"]);
+    fixture.git(&["checkout", "-q", "-b", "topic", "--track", "main"]);
+    fixture.write("g", "g\n");
+    fixture.commit("on topic");
+    let status = fixture.repo().status().unwrap();
+    assert_eq!(status_format::render(&status, &[], Format::Porcelain, false), "## topic...main [ahead 1]\n");
+    assert!(!status_format::is_dirty(&status, &[]));
+}
+
+#[test]
+fn odd_paths_get_quoted() {
+    assert_eq!(status_format::quote("plain.txt"), "plain.txt");
+    assert_eq!(status_format::quote("new file"), "\"new file\"");
+    assert_eq!(status_format::quote("a \"b\"\\c"), "\"a \\\"b\\\"\\\\c\"");
+    assert_eq!(status_format::quote("tab\there"), "\"tab\\there\"");
+}
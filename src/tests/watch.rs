@@ -0,0 +1,21 @@
+use super::fixture::Fixture;
+use crate::watch;
+
+#[test]
+fn summary_has_branch_counts_and_last_commit() {
+    let fixture = Fixture::with_commit();
+    fixture.git(&["checkout", "-q", "-b", "topic", "--track", "main"]);
+    fixture.write("a", "a\n");
+    fixture.commit("on topic");
+    let repo = fixture.repo();
+    let lines = watch::summary(&repo);
+    assert_eq!(lines[0], "topic → main ↑1");
+    assert_eq!(lines[1], "clean");
+    assert!(lines[2].contains(" on topic ("), "{:?}", lines);
+
+    fixture.write("a", "changed\n");
+    fixture.write("b", "b\n");
+    fixture.git(&["add", "b"]);
+    fixture.write("c", "c\n");
+    assert_eq!(watch::summary(&repo)[1], "1 staged, 1 unstaged, 1 untracked");
+}
@@ -1,1092 +1,4143 @@
-use std::{thread::current, vec};
-
-use git2::DiffHunk;
-use ratatui::{
-    backend::Backend,
-    layout::{self, Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
-    text::{Line, Span, Text},
-    widgets::{
-        Block, Borders, Clear, List, ListItem, ListState, Paragraph, Tabs, Wrap
-    },
-    Frame,
-};
-
-use crate::app::{App, AppMode};
-
-
-pub fn draw(f: &mut Frame, app: &App) {
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Length(3), Constraint::Min(0), Constraint::Length(3)])
-        .split(f.area());
-
-    draw_header(f, chunks[0], app);
-    
-    match app.mode {
-        AppMode::Status => draw_status_view(f, chunks[1], app),
-        AppMode::Log => draw_log_view(f, chunks[1], app),
-        AppMode::Branches => draw_branches_view(f, chunks[1], app),
-        AppMode::StashList => draw_stash_view(f, chunks[1], app),
-
-        AppMode::CommitDialog => {
-            draw_status_view(f, chunks[1], app);
-            draw_commit_dialog(f, f.area(), app);
-        }
-        AppMode::CreateBranchDialog => {
-            draw_branches_view(f, chunks[1], app);
-            draw_create_branch_dialog(f, f.area(), app);
-        }
-        AppMode::StashDialog => {
-            draw_status_view(f, chunks[1], app);
-            draw_stash_dialog(f, f.area(), app);
-        }
-        AppMode::RemoteOperations => draw_remote_view(f, chunks[1], app),
-        AppMode::MergeConflict => draw_merge_conflict_view(f, chunks[1], app),
-        AppMode::ResetDialog => {
-            match app.reset_target_commit {
-                Some(_) => draw_log_view(f, chunks[1], app),
-                None => draw_status_view(f, chunks[1], app),
-            }
-            draw_reset_dialog(f, f.area(), app);
-        }
-    }
-
-    if let Some(error) = &app.error_message {
-        draw_error_popup(f, f.area(), error);
-    }
-    draw_footer(f, chunks[2], app);
-}
-
-fn draw_header(f: &mut Frame, area: Rect, app: &App){
-    let titles = vec!["status (1)", "log (2)", "branches (3)", "stashes (4)", "remote (5)", "MERGE (m)"];
-    let selected = match app.mode {
-        AppMode::Status | AppMode::CommitDialog | AppMode::StashDialog | AppMode::ResetDialog => 0,
-        AppMode::Log => 1,
-        AppMode::Branches | AppMode::CreateBranchDialog => 2,
-        AppMode::StashList => 3,
-        AppMode::RemoteOperations => 4,
-        AppMode::MergeConflict => 5,
-    };
-
-    let tabs = Tabs::new(titles)
-        .block(Block::default().borders(Borders::ALL).title("git-hydra"))
-        .select(selected)
-        .style(Style::default().fg(Color::White))
-        .highlight_style(
-            Style::default()
-                .fg(if app.merge_conflict.is_some() { Color::Red } else { Color::Cyan })
-                .add_modifier(Modifier::BOLD),
-        );
-    
-    f.render_widget(tabs, area);
-}
-
-fn draw_reset_dialog(f: &mut Frame, area: Rect, app: &App) {
-    let popup_area = centered_rect(70, 60, area);
-
-    f.render_widget(Clear, popup_area);
-
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length(3),
-            Constraint::Length(4),
-            Constraint::Min(8),
-            Constraint::Length(3),
-        ])
-        .split(popup_area);
-
-    let title = Paragraph::new("Git Reset")
-        .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(Color::Red)))
-        .style(Style::default().fg(Color::White).add_modifier(Modifier::BOLD));
-    f.render_widget(title, chunks[0]);
-
-    draw_reset_target_info(f, chunks[1], app);
-    draw_reset_mode_selection(f, chunks[2], app);
-
-    let help_text = if app.get_current_reset_mode() == crate::git::ResetMode::Hard {
-        "WARNING: hard reset will permanently delete changes! \nenter: execute reset | esc: cancel"
-    } else {
-        "↑/↓: select mode | enter: execute reset | esc: cancel"
-    };
-
-    let help_style = if app.get_current_reset_mode() == crate::git::ResetMode::Hard {
-        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
-    } else {
-        Style::default().fg(Color::Gray)
-    };
-
-    let help = Paragraph::new(help_text)
-        .block(Block::default().borders(Borders::ALL))
-        .style(help_style)
-        .wrap(Wrap { trim: true });
-
-    f.render_widget(help, chunks[3]);
-}
-
-fn draw_reset_target_info(f: &mut Frame, area: Rect, app: &App) {
-    let mut text = Vec::new();
-
-    if let Some(target_commit) = &app.reset_target_commit {
-        if let Some(commit) = app.commits.iter().find(|c| c.id == *target_commit) {
-            text.push(Line::from(vec![
-                Span::styled("reset to commit: ", Style::default().fg(Color::Gray)),
-                Span::styled(&commit.id[..8], Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
-            ]));
-            text.push(Line::from(vec![
-                Span::styled("message: ", Style::default().fg(Color::Gray)),
-                Span::styled(
-                    commit.message.lines().next().unwrap_or(""),
-                    Style::default().fg(Color::White)
-                ),
-            ]));
-        } else {
-            text.push(Line::from(vec![
-                Span::styled("reset to commit: ", Style::default().fg(Color::Gray)),
-                Span::styled(&target_commit[..8], Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))                
-            ]));
-        }
-    } else {
-        text.push(Line::from(vec![
-            Span::styled("reset to: ", Style::default().fg(Color::Gray)),
-            Span::styled("HEAD~1", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
-            Span::styled(" (undo last commit)", Style::default().fg(Color::Gray)),
-        ]));
-    }
-
-    let info = Paragraph::new(text)
-        .block(Block::default().borders(Borders::ALL).title("target"))
-        .wrap(Wrap { trim: true });
-
-    f.render_widget(info, area);
-}
-
-fn draw_reset_mode_selection(f: &mut Frame, area: Rect, app: &App) {
-    let modes = crate::app::App::get_reset_modes();
-    let mut items = Vec::new();
-
-    for (i, mode_name) in modes.iter().enumerate() {
-        let is_selected = i == app.selected_reset_mode;
-
-        let (color, description) = match i {
-            0 => (Color::Green, "keep changes staged (safe)"),
-            1 => (Color::Yellow, "keep changes unstaged (safe)"),
-            2 => (Color::Red, "DISCARD ALL CHANGES (destructive)"),
-            _ => (Color::White, ""),
-        };
-
-        let style = if is_selected {
-            Style::default().bg(Color::DarkGray).fg(Color::White).add_modifier(Modifier::BOLD)
-        } else {
-            Style::default().fg(color)
-        };
-
-        let selection_indicator = if is_selected { "► " } else { " " };
-
-        items.push(ListItem::new(Line::from(vec![
-            Span::styled(selection_indicator, Style::default().fg(Color::Cyan)),
-            Span::styled(format!("{:<6}", mode_name), style),
-            Span::styled(format!(" - {}", description), Style::default().fg(Color::Gray)),
-        ])));
-    }
-
-    let list = List::new(items)
-        .block(Block::default().borders(Borders::ALL).title("Reset Mode"))
-        .style(Style::default().fg(Color::White));
-
-    f.render_widget(list, area);
-}
-
-fn draw_footer(f: &mut Frame, area: Rect, app: &App) {
-    let help_text = if app.mode == AppMode::MergeConflict {
-        match f.area().width > 100 {
-            true => "↑/↓: navigate hunks | ←/→: navigate files | o: keep ours | t: keep theirs | b: keep both | c: complete merge | a: abort | F5: refresh | q: quit",
-            false => "↑/↓: hunks | ←/→: files | o: ours | t: theirs | b: both | c: complete | a: abort | q: quit",
-        }
-    } else {
-        match f.area().width > 120 {
-            true => "↑/↓: navigate | 1/2/3/4: switch tabs | enter: action | s: stash | n: new branch | c: commit | pgup/down: scroll diff view | F5: refresh | r: reset to commit | q: quit",
-            false => "↑/↓: nav | 1/2/3/4: tabs | enter: action | s: stash | n: branch | c: commit | pgup/down: scroll diff | r: reset | q: quit",
-        }
-    };
-    
-    let help = Paragraph::new(help_text)
-        .block(Block::default().borders(Borders::ALL))
-        .style(Style::default().fg(Color::Gray))
-        .wrap(Wrap { trim: true });
-
-    f.render_widget(help, area);
-}
-
-fn draw_status_view(f: &mut Frame, area: Rect, app: &App) {
-    if let Some(status) = &app.status{
-        let chunks = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(40), Constraint::Percentage(30), Constraint::Percentage(30)])
-            .split(area);
-
-        draw_file_changes(f, chunks[0], app, status);
-
-        draw_repo_info(f, chunks[1], status);
-
-        draw_file_diff(f, chunks[2], app);
-    } else {
-        let loading = Paragraph::new("loading repository status...")
-            .block(Block::default().borders(Borders::ALL).title("status."))
-            .style(Style::default().fg(Color::Cyan));
-
-        f.render_widget(loading, area);
-    }
-}
-
-
-fn draw_file_changes(f: &mut Frame, area: Rect, app: &App, status: &crate::git::RepoStatus){
-    let mut items = Vec::new();
-    let mut current_index = 0;
-
-    if !status.staged.is_empty(){
-        items.push(ListItem::new(Line::from(vec![
-            Span::styled("── staged changes ──", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD))
-        ])));
-
-        for file in &status.staged {
-            let style = if current_index == app.selected_file {
-                Style::default().bg(Color::DarkGray).fg(Color::White)
-            } else {
-                Style::default().fg(Color::Green)
-            };
-
-            items.push(ListItem::new(Line::from(vec![
-                Span::styled(format!(" {} ", file.status), Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
-                Span::styled(&file.path, style),
-            ])));
-            current_index += 1;
-        }
-    }
-
-    if !status.unstaged.is_empty(){
-        items.push(ListItem::new(Line::from(vec![
-            Span::styled("── unstaged changes ──", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))
-        ])));
-
-        for file in &status.unstaged {
-            let style = if current_index == app.selected_file {
-                Style::default().bg(Color::DarkGray).fg(Color::White)
-            } else {
-                Style::default().fg(Color::Red)
-            };
-
-            items.push(ListItem::new(Line::from(vec![
-                Span::styled(format!(" {} ", file.status), Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
-                Span::styled(&file.path, style),
-            ])));
-            current_index += 1;
-        }
-    }
-
-    if !status.untracked.is_empty(){
-        items.push(ListItem::new(Line::from(vec![
-            Span::styled("── untracked files ──", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
-        ])));
-
-        for file in &status.untracked {
-            let style = if current_index == app.selected_file {
-                Style::default().bg(Color::DarkGray).fg(Color::White)
-            } else {
-                Style::default().fg(Color::Cyan)
-            };
-
-            items.push(ListItem::new(Line::from(vec![
-                Span::styled(" ?? ", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
-                Span::styled(&file.path, style),
-            ])));
-            current_index += 1;
-        }
-    }
-
-    if items.is_empty(){
-        items.push(ListItem::new(Line::from(vec![
-            Span::styled("✔ working tree clean.", Style::default().fg(Color::Green))
-        ])));
-    }
-
-    let list = List::new(items)
-        .block(Block::default().borders(Borders::ALL).title("changes"))
-        .style(Style::default().fg(Color::White));
-
-    f.render_widget(list, area);
-}
-
-fn draw_repo_info(f: &mut Frame, area: Rect, status: &crate::git::RepoStatus){
-    let mut text = Vec::new();
-
-    text.push(Line::from(vec![
-        Span::styled("branch: ", Style::default().fg(Color::Gray)),
-        Span::styled(&status.branch, Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
-    ]));
-    
-    if status.ahead > 0 {
-        text.push(Line::from(vec![
-            Span::styled("↑ ahead: ", Style::default().fg(Color::Green)),
-            Span::styled(status.ahead.to_string(), Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
-            Span::styled(" commits", Style::default().fg(Color::Gray))
-        ]));
-    }
-
-    if status.behind > 0 {
-        text.push(Line::from(vec![
-            Span::styled("↓ behind: ", Style::default().fg(Color::Red)),
-            Span::styled(status.behind.to_string(), Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
-            Span::styled(" commits", Style::default().fg(Color::Gray)),
-        ]));
-    }
-    
-    if status.ahead == 0 && status.behind == 0{
-        text.push(Line::from(vec![
-            Span::styled("✓ up to date.", Style::default().fg(Color::Green)),
-        ]));
-    }
-    
-    text.push(Line::from(""));
-
-    let total_changes = status.staged.len() + status.unstaged.len() + status.untracked.len();
-    if total_changes > 0{
-        text.push(Line::from(vec![
-            Span::styled("files changed: ", Style::default().fg(Color::Gray)),
-            Span::styled(total_changes.to_string(), Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
-        ]));
-        
-        if !status.staged.is_empty() {
-            text.push(Line::from(vec![
-                Span::styled("  staged: ", Style::default().fg(Color::Green)),
-                Span::styled(status.staged.len().to_string(), Style::default().fg(Color::Green)),
-            ]));
-        }
-        
-        if !status.unstaged.is_empty() {
-            text.push(Line::from(vec![
-                Span::styled("  unstaged: ", Style::default().fg(Color::Red)),
-                Span::styled(status.unstaged.len().to_string(), Style::default().fg(Color::Red)),
-            ]));
-        }
-        
-        if !status.untracked.is_empty() {
-            text.push(Line::from(vec![
-                Span::styled("  untracked: ", Style::default().fg(Color::Cyan)),
-                Span::styled(status.untracked.len().to_string(), Style::default().fg(Color::Cyan)),
-            ]));
-        }
-    }
-
-    let paragraph = Paragraph::new(text)
-        .block(Block::default().borders(Borders::ALL).title("repository info."))
-        .wrap(Wrap { trim: true });
-    
-    f.render_widget(paragraph, area);
-
-}
-
-fn draw_file_diff(f: &mut Frame, area: Rect, app: &App){
-    if let Some(file_path) = app.get_selected_file_path() {
-        match app.repo.get_file_diff(&file_path){
-            Ok(diff_text) => {
-                if diff_text.is_empty(){
-                    let no_diff = Paragraph::new("no changes to display.")
-                        .block(Block::default().borders(Borders::ALL).title("diff."))
-                        .style(Style::default().fg(Color::Gray));
-
-                    f.render_widget(no_diff, area);
-                } else {
-                    let lines: Vec<Line> = diff_text
-                        .lines()
-                        .map(|line| {
-                            if line.starts_with('+') && !line.starts_with("+++") {
-                                Line::from(Span::styled(line, Style::default().fg(Color::Green)))
-                            } else if line.starts_with('-') && !line.starts_with("---") {
-                                Line::from(Span::styled(line, Style::default().fg(Color::Red)))
-                            } else if line.starts_with("@@") {
-                                Line::from(Span::styled(line, Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)))
-                            } else if line.starts_with("+++") || line.starts_with("---") {
-                                Line::from(Span::styled(line, Style::default().fg(Color::Yellow)))
-                            } else {
-                                Line::from(Span::styled(line, Style::default().fg(Color::White)))
-                            }
-                        })
-                        .collect();
-                        
-                    let content_height = lines.len() as u16;
-                    let viewport_height = area.height.saturating_sub(2);
-                    let max_scroll = content_height.saturating_sub(viewport_height);
-
-                    let actual_scroll = app.diff_scroll.min(max_scroll);
-
-                    let title = if content_height > viewport_height {
-                        format!("diff: {} ({}%)", file_path,
-                            (actual_scroll * 100 / max_scroll.max(1)))
-                    } else {
-                        format!("diff: {}", file_path)
-                    };
-                        
-                    let diff_paragraph = Paragraph::new(lines)
-                        .block(Block::default().borders(Borders::ALL).title(title))
-                        .wrap(Wrap { trim: false })
-                        .scroll((actual_scroll, 0));
-                    
-                    f.render_widget(diff_paragraph, area);
-                }
-            }
-            Err(_) => {
-                let error_diff = Paragraph::new("error loading diff.")
-                    .block(Block::default().borders(Borders::ALL).title("diff"))
-                    .style(Style::default().fg(Color::Red));
-                f.render_widget(error_diff, area);
-            }
-        }
-        
-    } else {
-        let no_file = Paragraph::new("select a file to view diff.")
-            .block(Block::default().borders(Borders::ALL).title("diff"))
-            .style(Style::default().fg(Color::Gray));
-        f.render_widget(no_file, area);
-    }
-}
-
-fn draw_log_view(f: &mut Frame, area: Rect, app: &App){
-    let chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(70), Constraint::Percentage(30)])
-        .split(area);
-    
-    draw_commit_list(f, chunks[0], app);
-    
-    draw_commit_details(f, chunks[1], app);
-}
-
-fn draw_commit_list(f: &mut Frame, area: Rect, app: &App){
-    let items: Vec<ListItem> = app
-        .commits
-        .iter()
-        .enumerate()
-        .map(|(i, commit)| {
-            let style = if i == app.selected_commit {
-                Style::default().bg(Color::DarkGray).fg(Color::White)
-            } else {
-                Style::default().fg(Color::White)
-            };
-            
-            let short_id = &commit.id[..8];
-            let first_line = commit.message.lines().next().unwrap_or("");
-            let time_str = commit.timestamp.format("%m/%d %H:%M").to_string();
-            
-            ListItem::new(Line::from(vec![
-                Span::styled(format!("{} ", short_id), Style::default().fg(Color::Cyan)),
-                Span::styled(format!("{} ", first_line), style),
-                Span::styled(format!("({})", time_str), Style::default().fg(Color::Gray)),
-            ]))
-        })
-        .collect();
-    
-    let list = List::new(items)
-        .block(Block::default().borders(Borders::ALL).title("commit history."))
-        .style(Style::default().fg(Color::White));
-    
-    f.render_widget(list, area);   
-}
-
-fn draw_commit_details(f: &mut Frame, area: Rect, app: &App){
-    if let Some(commit) = app.commits.get(app.selected_commit) {
-        let mut text = Vec::new();
-        
-        text.push(Line::from(vec![
-            Span::styled("commit: ", Style::default().fg(Color::Gray)),
-            Span::styled(&commit.id[..8], Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
-        ]));
-        
-        text.push(Line::from(vec![
-            Span::styled("author: ", Style::default().fg(Color::Gray)),
-            Span::styled(&commit.author, Style::default().fg(Color::White)),
-        ]));
-        
-        text.push(Line::from(vec![
-            Span::styled("date: ", Style::default().fg(Color::Gray)),
-            Span::styled(commit.timestamp.format("%Y-%m-%d %H:%M:%S").to_string(), Style::default().fg(Color::White)),
-        ]));
-        
-        text.push(Line::from(""));
-        
-        for line in commit.message.lines() {
-            text.push(Line::from(line.to_string()));
-        }
-        
-        if !commit.parents.is_empty(){
-            text.push(Line::from(""));
-            text.push(Line::from(vec![
-                Span::styled("Parents: ", Style::default().fg(Color::Gray)),
-            ]));
-            for parent in &commit.parents {
-                text.push(Line::from(vec![
-                    Span::styled("  ", Style::default()),
-                    Span::styled(&parent[..8], Style::default().fg(Color::Cyan)),
-                ]));
-            }
-        }
-        
-        let paragraph = Paragraph::new(text)
-            .block(Block::default().borders(Borders::ALL).title("commit details."))
-            .wrap(Wrap { trim: true });
-        
-        f.render_widget(paragraph, area);
-    } else {
-        let empty = Paragraph::new("no commits found.")
-            .block(Block::default().borders(Borders::ALL).title("commit details."))
-            .style(Style::default().fg(Color::Gray));
-        f.render_widget(empty, area);
-    }
-}
-
-
-fn draw_branches_view(f: &mut Frame, area: Rect, app: &App) {
-    let items: Vec<ListItem> = app
-        .branches
-        .iter()
-        .enumerate()
-        .map(|(i, branch)| {
-            let is_selected = i == app.selected_file;
-            let base_style = if branch.starts_with("origin/") {
-                Style::default().fg(Color::Cyan)
-            } else {
-                Style::default().fg(Color::Green)
-            };
-
-            let style = if is_selected {
-                base_style.bg(Color::DarkGray)
-            } else {
-                base_style
-            };
-
-            let name = if branch.starts_with("origin/") {
-                format!("remote: {}", branch)
-            } else {
-                format!("local: {}", branch)
-            };
-
-            ListItem::new(Line::from(vec![
-                Span::styled(name, style),
-            ]))
-        })
-        .collect();
-
-    let list = List::new(items)
-        .block(Block::default().borders(Borders::ALL).title("branches (enter to checkout)"))
-        .style(Style::default().fg(Color::White));
-
-    f.render_widget(list, area);
-}
-
-fn draw_commit_dialog(f: &mut Frame, area: Rect, app: &App) {
-    let popup_area = centered_rect(60, 20, area);
-
-    f.render_widget(Clear, popup_area);
-
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Length(3), Constraint::Min(3), Constraint::Length(3)])
-        .split(popup_area);
-
-
-    let title = Paragraph::new("commit message")
-        .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(Color::Cyan)))
-        .style(Style::default().fg(Color::White));
-    f.render_widget(title, chunks[0]);
-
-    let message = Paragraph::new(app.commit_message.as_str())
-        .block(Block::default().borders(Borders::ALL))
-        .style(Style::default().fg(Color::White).bg(Color::Black))
-        .wrap(Wrap {trim: false});
-
-    f.render_widget(message, chunks[1]);
-    // test
-    let help = Paragraph::new("enter: commit | esc: cancel")
-        .block(Block::default().borders(Borders::ALL))
-        .style(Style::default().fg(Color::Gray));
-
-    f.render_widget(help, chunks[2]);
-
-    f.set_cursor( // i am so cba to use the new one i am sorry
-        chunks[1].x + app.commit_message.len() as u16 + 1,
-        chunks[1].y + 1, // i do not know how make this wrap yet
-    );
-
-}
-
-fn draw_error_popup(f: &mut Frame, area: Rect, error: &str) {
-    let popup_area = centered_rect(50, 10, area);
-
-    f.render_widget(Clear, popup_area);
-
-    let error_widget = Paragraph::new(error)
-        .block(
-            Block::default()
-                .borders(Borders::ALL)
-                .title("error")
-                .border_style(Style::default().fg(Color::Red))
-        )
-        .style(Style::default().fg(Color::Red))
-        .wrap(Wrap { trim: true });
-
-    f.render_widget(error_widget, popup_area);
-}
-
-fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
-    let popup_layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Percentage(100 - percent_y / 2),
-            Constraint::Percentage(percent_y),
-            Constraint::Percentage(100 - percent_y / 2),
-        ])
-        .split(r);
-
-    Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([
-            Constraint::Percentage(100 - percent_x / 2),
-            Constraint::Percentage(percent_x),
-            Constraint::Percentage(100 - percent_x / 2),
-        ])
-        .split(popup_layout[1])[1]
-}
-
-fn draw_stash_view(f: &mut Frame, area: Rect, app: &App) {
-    let items: Vec<ListItem> = app
-        .stashes
-        .iter()
-        .enumerate()
-        .map(|(i, stash)| {
-            let style = if i == app.selected_stash {
-                Style::default().bg(Color::DarkGray).fg(Color::White)
-            } else {
-                Style::default().fg(Color::White)
-            };
-
-            ListItem::new(Line::from(vec![
-                Span::styled(stash, style),
-            ]))        
-        })  
-        .collect();
-
-    let list = List::new(items)
-        .block(Block::default().borders(Borders::ALL).title("stashes (enter: pop | del: drop | esc: back)"))
-        .style(Style::default().fg(Color::White));
-
-    f.render_widget(list, area);
-}
-
-fn draw_create_branch_dialog(f: &mut Frame, area: Rect, app: &App) {
-    let popup_area = centered_rect(50, 15, area);
-
-    f.render_widget(Clear, popup_area);
-
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Length(3), Constraint::Length(3), Constraint::Length(3)])
-        .split(popup_area);
-
-    let title = Paragraph::new("create new branch")
-        .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(Color::Green)))
-        .style(Style::default().fg(Color::White));
-
-    f.render_widget(title, chunks[0]);
-
-    let input = Paragraph::new(app.branch_name.as_str())
-        .block(Block::default().borders(Borders::ALL).title("branch name"))
-        .style(Style::default().fg(Color::White).bg(Color::Black));
-
-    f.render_widget(input, chunks[1]);
-
-    let help = Paragraph::new("enter: create | esc: cancel")
-        .block(Block::default().borders(Borders::ALL))
-        .style(Style::default().fg(Color::Gray));
-
-    f.render_widget(help, chunks[2]);
-
-    f.set_cursor(
-        chunks[1].x + app.branch_name.len() as u16 + 1,
-        chunks[1].y + 1,
-    );
-}
-
-fn draw_stash_dialog(f: &mut Frame, area: Rect, app: &App) {
-    let popup_area = centered_rect(60, 20, area);
-    
-    f.render_widget(Clear, popup_area);
-    
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Length(3), Constraint::Min(3), Constraint::Length(3)])
-        .split(popup_area);
-    
-    let title = Paragraph::new("stash changes")
-        .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(Color::Cyan)))
-        .style(Style::default().fg(Color::White));
-        
-    f.render_widget(title, chunks[0]);
-    
-    let message = Paragraph::new(app.stash_message.as_str())
-        .block(Block::default().borders(Borders::ALL).title("stash message (optional)"))
-        .style(Style::default().fg(Color::White).bg(Color::Black))
-        .wrap(Wrap { trim: false });
-
-    f.render_widget(message, chunks[1]);
-    
-    let help = Paragraph::new("enter: stash | esc: cancel")
-        .block(Block::default().borders(Borders::ALL))
-        .style(Style::default().fg(Color::Gray));
-        
-    f.render_widget(help, chunks[2]);
-    
-    f.set_cursor(
-        chunks[1].x + app.stash_message.len() as u16 + 1,
-        chunks[1].y + 1,
-    );
-}
-
-fn draw_remote_view(f: &mut Frame, area: Rect, app: &App){
-    let chunks = Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-        .split(area);
-    
-    draw_remote_list(f, chunks[0], app);
-    
-    draw_remote_operations(f, chunks[1], app);
-}
-
-fn draw_remote_list(f: &mut Frame, area: Rect, app: &App){
-    let items: Vec<ListItem> = app
-        .remotes
-        .iter()
-        .enumerate()
-        .map(|(i, remote)| {
-            let style = if i == app.selected_remote {
-                Style::default().bg(Color::DarkGray).fg(Color::White)
-            } else {
-                Style::default().fg(Color::White)
-            };
-            
-            ListItem::new(Line::from(vec![
-                Span::styled(format!("remote: {}", remote), style),
-            ]))
-        })
-        .collect();
-    
-    let list = List::new(items)
-        .block(Block::default().borders(Borders::ALL).title("remotes"))
-        .style(Style::default().fg(Color::White));
-    
-    f.render_widget(list, area);
-}
-
-fn draw_remote_operations(f: &mut Frame, area: Rect, app: &App) {
-    let mut text = Vec::new();
-    
-    text.push(Line::from(vec![
-        Span::styled("current Branch: ", Style::default().fg(Color::Gray)),
-        Span::styled(&app.current_branch, Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
-    ]));
-    
-    text.push(Line::from(""));
-    
-    if !app.remotes.is_empty() && app.selected_remote < app.remotes.len(){
-        let selected_remote = &app.remotes[app.selected_remote];
-        text.push(Line::from(vec![
-            Span::styled("selected remote: ", Style::default().fg(Color::Gray)),
-            Span::styled(selected_remote, Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
-        ]));
-        
-        if let Ok(Some(url)) = app.repo.get_remote_url(selected_remote) {
-            text.push(Line::from(vec![
-                Span::styled("URL: ", Style::default().fg(Color::Gray)),
-                Span::styled(url, Style::default().fg(Color::Blue)),
-            ]));
-        }
-        
-        text.push(Line::from(""));
-
-        if let Some(push_msg) = &app.push_progress {
-            text.push(Line::from(vec![
-                Span::styled(push_msg, Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
-            ]));
-        } else if let Some(pull_msg) = &app.pull_progress {
-            text.push(Line::from(vec![
-                Span::styled(pull_msg, Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD)),
-            ]));
-        } else if app.is_pushing {
-            text.push(Line::from(vec![
-                Span::styled("Pushing...", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
-            ]));
-        } else if app.is_pulling {
-            text.push(Line::from(vec![
-                Span::styled("Pulling...", Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD)),
-            ]));
-        } else {
-            text.push(Line::from(vec![
-                Span::styled("operations:", Style::default().fg(Color::Gray)),
-            ]));
-            text.push(Line::from(vec![
-                Span::styled("  p - Push to remote", Style::default().fg(Color::Green)),
-            ]));
-            text.push(Line::from(vec![
-                Span::styled("  u - Pull from remote", Style::default().fg(Color::Blue)),
-            ]));
-        }
-    } else {
-        text.push(Line::from(vec![
-            Span::styled("No remotes configured", Style::default().fg(Color::Red)),
-        ]));
-        text.push(Line::from(vec![
-            Span::styled("use 'git remote add origin <url>' to add a remote", Style::default().fg(Color::Gray)),
-        ]));
-    }
-    
-    let paragraph = Paragraph::new(text)
-        .block(Block::default().borders(Borders::ALL).title("remote operations."))
-        .wrap(Wrap { trim: true });
-    
-    f.render_widget(paragraph, area);
-}
-
-fn draw_merge_conflict_view(f: &mut Frame, area: Rect, app: &App) {
-    if let Some(merge_conflict) = &app.merge_conflict {
-        let chunks = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([Constraint::Percentage(30), Constraint::Percentage(70)])
-            .split(area);
-
-        draw_conflict_file_list(f, chunks[0], app, merge_conflict);
-        draw_conflict_resolution_panel(f, chunks[1], app);
-    } else {
-        let no_conflicts = Paragraph::new("no merge conflicts detected. \n\n this mode is only available during a merge with conflicts.")
-            .block(Block::default().borders(Borders::ALL).title("merge conflict"))
-            .style(Style::default().fg(Color::Gray))
-            .wrap(Wrap { trim: true });
-
-        f.render_widget(no_conflicts, area);
-    }
-}
-
-fn draw_conflict_file_list(f: &mut Frame, area: Rect, app: &App, merge_conflict: &crate::git::MergeConflict) {
-    let mut items = Vec::new();
-
-    items.push(ListItem::new(Line::from(vec![
-        Span::styled("── MERGE CONFLICT ──", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))
-    ])));
-
-    items.push(ListItem::new(Line::from(vec![
-        Span::styled("our commit: ", Style::default().fg(Color::Gray)),
-        Span::styled(&merge_conflict.our_commit[..8], Style::default().fg(Color::Green).add_modifier(Modifier::BOLD))
-    ])));
-
-    items.push(ListItem::new(Line::from(vec![
-        Span::styled("their commit: ", Style::default().fg(Color::Gray)),
-        Span::styled(&merge_conflict.their_commit[..8], Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))
-    ])));
-
-    items.push(ListItem::new(Line::from("")));
-
-    for (file_idx, file) in merge_conflict.files.iter().enumerate() {
-        let is_selected_file = file_idx == app.selected_file;
-
-        let resolved_count = file.conflicts.iter().enumerate()
-            .filter(|(hunk_idx, _)| app.conflict_resolutions.contains_key(&(file_idx, *hunk_idx)))
-            .count();
-        
-        let total_conflicts = file.conflicts.len();
-        let all_resolved = resolved_count == total_conflicts;
-
-        let file_style = if is_selected_file {
-            Style::default().bg(Color::DarkGray).fg(Color::White)
-        } else if all_resolved {
-            Style::default().fg(Color::Green)
-        } else {
-            Style::default().fg(Color::Red)
-        };
-
-        let status_icon = if all_resolved {
-            "✓"
-        } else {
-            "✗"
-        };
-        let progress = format!("({}/{})", resolved_count, total_conflicts);
-
-        items.push(ListItem::new(Line::from(vec![
-            Span::styled(format!("{} ", status_icon),
-                if all_resolved { Style::default().fg(Color::Green) } else { Style::default().fg(Color::Red) }),
-            Span::styled(&file.path, file_style),
-            Span::styled(format!(" {}", progress), Style::default().fg(Color::Gray)),
-
-        ])));
-        
-        // im gonna rip my hair out ui design is a pain in my butt
-
-        if is_selected_file {
-            for (hunk_idx, _hunk) in file.conflicts.iter().enumerate() {
-                let is_selected_hunk = hunk_idx == app.selected_conflict_hunk;
-                let is_resolved = app.conflict_resolutions.contains_key(&(file_idx, hunk_idx));
-
-                let hunk_style = if is_selected_hunk {
-                    Style::default().bg(Color::DarkGray).fg(Color::White)
-                } else if is_resolved {
-                    Style::default().fg(Color::Green)
-                } else {
-                    Style::default().fg(Color::Red)
-                };
-
-                let resolution_text = if let Some(resolution) = app.conflict_resolutions.get(&(file_idx, hunk_idx)) {
-                    match resolution {
-                        crate::git::MergeResolution::KeepOurs => "[OURS]",
-                        crate::git::MergeResolution::KeepTheirs => "[THEIRS]",
-                        crate::git::MergeResolution::KeepBoth => "[BOTH]",
-                        crate::git::MergeResolution::Custom(_) => "[CUSTOM]",
-                    }
-                } else {
-                    "[UNRESOLVED]"
-                };
-
-                items.push(ListItem::new(Line::from(vec![
-                    Span::styled("  └─ ", Style::default()),
-                    Span::styled(format!("Hunk {}{}", hunk_idx + 1, resolution_text), hunk_style),
-                ])));
-            }
-        }
-    }
-
-    let list = List::new(items)
-        .block(Block::default().borders(Borders::ALL).title("conflicted files"))
-        .style(Style::default().fg(Color::White));
-
-    f.render_widget(list, area);
-}
-
-// someone please hop on this project and do the UI
-fn draw_conflict_resolution_panel(f: &mut Frame, area: Rect, app: &App) {
-    if let Some(current_hunk) = app.get_current_conflict_hunk(){
-        let chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([
-                Constraint::Length(3),
-                Constraint::Percentage(40),
-                Constraint::Percentage(40),
-                Constraint::Min(5),
-            ])
-            .split(area);
-        
-        draw_conflict_header(f, chunks[0], app);
-        draw_conflict_ours_section(f, chunks[1], current_hunk);
-        draw_conflict_theirs_section(f, chunks[2], current_hunk);
-        draw_conflict_resolution_section(f, chunks[3], app, current_hunk);
-    } else {
-        let no_hunk = Paragraph::new("select a conflict to view details")
-            .block(Block::default().borders(Borders::ALL).title("conflict resolution"))
-            .style(Style::default().fg(Color::Gray))
-            .wrap(Wrap { trim: true });
-
-        f.render_widget(no_hunk, area);
-    }
-}
-
-fn draw_conflict_header(f: &mut Frame, area: Rect, app: &App) {
-    if let Some(file) = app.get_current_conflict_file() {
-        let current_hunk = app.selected_conflict_hunk + 1;
-        let total_hunks = file.conflicts.len();
-        
-        let header_text = format!("{} - conflict {} of {}", file.path, current_hunk, total_hunks);
-        
-        let header = Paragraph::new(header_text)
-            .block(Block::default().borders(Borders::ALL))
-            .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
-            .wrap(Wrap { trim: true });
-
-        f.render_widget(header, area);
-    }
-}
-
-fn draw_conflict_ours_section(f: &mut Frame, area: Rect, hunk: &crate::git::ConflictHunk) {
-    let lines: Vec<Line> = hunk.our_content
-        .lines()
-        .map(|line| Line::from(Span::styled(line, Style::default().fg(Color::Green))))
-        .collect();
-
-    let our_section = Paragraph::new(lines)
-        .block(Block::default().borders(Borders::ALL).title("OURS (current branch) - press 'o' to keep"))
-        .style(Style::default().fg(Color::White))
-        .wrap(Wrap { trim: false });
-
-    f.render_widget(our_section, area);
-}
-
-fn draw_conflict_theirs_section(f: &mut Frame, area: Rect, hunk: &crate::git::ConflictHunk) {
-    let lines: Vec<Line> = hunk.their_content
-        .lines()
-        .map(|line| Line::from(Span::styled(line, Style::default().fg(Color::Blue))))
-        .collect();
-
-    let their_section = Paragraph::new(lines)
-        .block(Block::default().borders(Borders::ALL).title("THEIRS (incoming branch) - press 't' to keep"))
-        .style(Style::default().fg(Color::White))
-        .wrap(Wrap { trim: false });
-
-    f.render_widget(their_section, area);
-}
-
-fn draw_conflict_resolution_section(f: &mut Frame, area: Rect, app: &App, hunk: &crate::git::ConflictHunk) {
-    let mut text = Vec::new();
-    
-    if let Some(resolution) = app.get_current_resolution() {
-        text.push(Line::from(vec![
-            Span::styled("current resolution: ", Style::default().fg(Color::Gray)),
-            match resolution {
-                crate::git::MergeResolution::KeepOurs => 
-                    Span::styled("KEEP OURS", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
-                crate::git::MergeResolution::KeepTheirs => 
-                    Span::styled("KEEP THEIRS", Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD)),
-                crate::git::MergeResolution::KeepBoth => 
-                    Span::styled("KEEP BOTH", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
-                crate::git::MergeResolution::Custom(_) => 
-                    Span::styled("CUSTOM", Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD)),
-            }
-        ]));
-        
-        text.push(Line::from(""));
-        text.push(Line::from(vec![
-            Span::styled("Preview:", Style::default().fg(Color::Gray)),
-        ]));
-        
-        let preview = hunk.resolve(resolution);
-        for line in preview.lines().take(5) { // Show first 5 lines of preview
-            text.push(Line::from(Span::styled(line.to_string(), Style::default().fg(Color::White))));
-        }
-        
-        if preview.lines().count() > 5 {
-            text.push(Line::from(Span::styled("... (truncated)", Style::default().fg(Color::Gray))));
-        }
-    } else {
-        text.push(Line::from(vec![
-            Span::styled("UNRESOLVED", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
-        ]));
-        text.push(Line::from(""));
-        text.push(Line::from("Choose resolution:"));
-        text.push(Line::from("• 'o' - keep our version (green)"));
-        text.push(Line::from("• 't' - keep their version (blue)"));  
-        text.push(Line::from("• 'b' - keep both versions"));
-        text.push(Line::from("• 'e' - edit custom resolution"));
-    }
-
-    let resolution_panel = Paragraph::new(text)
-        .block(Block::default().borders(Borders::ALL).title("resolution"))
-        .style(Style::default().fg(Color::White))
-        .wrap(Wrap { trim: true });
-
-    f.render_widget(resolution_panel, area);
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Margin, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{
+        Block, Borders, Clear, List, ListItem, ListState, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, Tabs, Wrap
+    },
+    Frame,
+};
+use chrono::{DateTime, Datelike, FixedOffset, NaiveDate, Utc};
+use std::cell::Cell;
+
+use crate::app::{App, AppMode, NotesInput};
+use crate::files::{FileBrowser, Preview};
+use crate::git::{human_size, Commit, HealthIssue, RefKind, SignatureStatus, StashFileStat, TreeEntryKind};
+use crate::highlight::Kind;
+use crate::issues::{find_refs, IssueRule};
+use crate::logging;
+use crate::maintenance;
+use crate::notifications::Level;
+use crate::perf::{millis, Op};
+use crate::stats;
+
+
+pub fn draw(f: &mut Frame, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(0), Constraint::Length(3)])
+        .split(f.area());
+
+    draw_header(f, chunks[0], app);
+    
+    match app.mode {
+        AppMode::Status => draw_status_view(f, chunks[1], app),
+        AppMode::Log => draw_log_view(f, chunks[1], app),
+        AppMode::Branches => draw_branches_view(f, chunks[1], app),
+        AppMode::StashList => draw_stash_view(f, chunks[1], app),
+
+        AppMode::CommitDialog => {
+            draw_status_view(f, chunks[1], app);
+            draw_commit_dialog(f, f.area(), app);
+        }
+        AppMode::CreateBranchDialog => {
+            draw_branches_view(f, chunks[1], app);
+            draw_create_branch_dialog(f, f.area(), app);
+        }
+        AppMode::StashDialog => {
+            draw_status_view(f, chunks[1], app);
+            draw_stash_dialog(f, f.area(), app);
+        }
+        AppMode::RemoteOperations => draw_remote_view(f, chunks[1], app),
+        AppMode::PushPreview => draw_push_preview(f, chunks[1], app),
+        AppMode::BundleDialog => {
+            draw_remote_view(f, chunks[1], app);
+            draw_bundle_dialog(f, f.area(), app);
+        }
+        AppMode::SyncForkDialog => {
+            draw_remote_view(f, chunks[1], app);
+            draw_sync_fork_dialog(f, f.area(), app);
+        }
+        AppMode::MergeConflict => draw_merge_conflict_view(f, chunks[1], app),
+        AppMode::MergeMessageDialog => {
+            draw_merge_conflict_view(f, chunks[1], app);
+            draw_merge_message_dialog(f, f.area(), app);
+        }
+        AppMode::LineStaging => draw_line_staging_view(f, chunks[1], app),
+        AppMode::Hooks => draw_hooks_view(f, chunks[1], app),
+        AppMode::CommitView => draw_commit_view(f, chunks[1], app),
+        AppMode::Files => draw_files_view(f, chunks[1], app),
+        AppMode::Stats => draw_stats_view(f, chunks[1], app),
+        AppMode::LargeObjects => draw_large_objects_view(f, chunks[1], app),
+        AppMode::Maintenance => draw_maintenance_view(f, chunks[1], app),
+        AppMode::Clean => draw_clean_view(f, chunks[1], app),
+        AppMode::Health => draw_health_view(f, chunks[1], app),
+        AppMode::Help => draw_help_view(f, chunks[1], app),
+        AppMode::ReleaseNotes => draw_release_notes_view(f, chunks[1], app),
+        AppMode::NewRelease => draw_new_release_view(f, chunks[1], app),
+        // the palette floats over whatever it was opened from
+        AppMode::CommandPalette | AppMode::NotificationHistory | AppMode::ErrorLog | AppMode::LogViewer | AppMode::CommandOutput | AppMode::AutostashDialog | AppMode::CredentialDialog | AppMode::RebaseOntoDialog | AppMode::StackView | AppMode::PackagePicker => match app.overlay_return_mode() {
+            AppMode::Log => draw_log_view(f, chunks[1], app),
+            AppMode::Branches => draw_branches_view(f, chunks[1], app),
+            AppMode::StashList => draw_stash_view(f, chunks[1], app),
+            AppMode::RemoteOperations => draw_remote_view(f, chunks[1], app),
+            AppMode::MergeConflict => draw_merge_conflict_view(f, chunks[1], app),
+            _ => draw_status_view(f, chunks[1], app),
+        },
+        AppMode::SwitchRepoDialog => {
+            draw_status_view(f, chunks[1], app);
+            draw_switch_repo_dialog(f, f.area(), app);
+        }
+        AppMode::JumpDialog => {
+            draw_log_view(f, chunks[1], app);
+            draw_jump_dialog(f, f.area(), app);
+        }
+        AppMode::RestoreDialog => {
+            draw_log_view(f, chunks[1], app);
+            draw_restore_dialog(f, f.area(), app);
+        }
+        AppMode::GitflowDialog => {
+            draw_branches_view(f, chunks[1], app);
+            draw_gitflow_dialog(f, f.area(), app);
+        }
+        AppMode::UpstreamDialog => {
+            draw_branches_view(f, chunks[1], app);
+            draw_upstream_dialog(f, f.area(), app);
+        }
+        AppMode::PushDialog => {
+            draw_branches_view(f, chunks[1], app);
+            draw_push_dialog(f, f.area(), app);
+        }
+        AppMode::ScopePicker => {
+            draw_status_view(f, chunks[1], app);
+            draw_scope_picker(f, f.area(), app);
+        }
+        AppMode::DiffBasePicker => {
+            draw_status_view(f, chunks[1], app);
+            draw_diff_base_picker(f, f.area(), app);
+        }
+        AppMode::AttributesDialog => {
+            draw_status_view(f, chunks[1], app);
+            draw_attributes_dialog(f, f.area(), app);
+        }
+        AppMode::PickaxeDialog => {
+            draw_log_view(f, chunks[1], app);
+            draw_pickaxe_dialog(f, f.area(), app);
+        }
+        AppMode::ArchiveDialog => {
+            if app.archive_target == "HEAD" {
+                draw_status_view(f, chunks[1], app);
+            } else {
+                draw_log_view(f, chunks[1], app);
+            }
+            draw_archive_dialog(f, f.area(), app);
+        }
+        AppMode::ResetDialog => {
+            match app.reset_target_commit {
+                Some(_) => draw_log_view(f, chunks[1], app),
+                None => draw_status_view(f, chunks[1], app),
+            }
+            draw_reset_dialog(f, f.area(), app);
+        }
+    }
+
+    if app.mode == AppMode::CommandPalette {
+        draw_command_palette(f, f.area(), app);
+    }
+    if app.mode == AppMode::NotificationHistory {
+        draw_notification_history(f, f.area(), app);
+    }
+
+    if app.mode == AppMode::ErrorLog {
+        draw_error_log(f, f.area(), app);
+    }
+
+    if app.mode == AppMode::LogViewer {
+        draw_log_viewer(f, f.area(), app);
+    }
+
+    if app.mode == AppMode::CommandOutput {
+        draw_command_output(f, f.area(), app);
+    }
+
+    if app.mode == AppMode::CredentialDialog {
+        draw_credential_dialog(f, f.area(), app);
+    }
+    if app.mode == AppMode::AutostashDialog {
+        draw_autostash_dialog(f, f.area(), app);
+    }
+
+    if app.mode == AppMode::RebaseOntoDialog {
+        draw_rebase_onto_dialog(f, f.area(), app);
+    }
+
+    if app.mode == AppMode::StackView {
+        draw_stack_view(f, f.area(), app);
+    }
+
+    if app.mode == AppMode::PackagePicker {
+        draw_package_picker(f, f.area(), app);
+    }
+
+    if app.perf.visible {
+        draw_perf_hud(f, chunks[1], app);
+    }
+
+    if let Some(error) = &app.error_message {
+        draw_error_popup(f, f.area(), error, app.error_scroll, app.index_locked);
+    }
+    draw_footer(f, chunks[2], app);
+    crate::colors::degrade(f.buffer_mut(), app.colors);
+}
+
+// top right corner of the main area, over whatever view is open
+fn draw_perf_hud(f: &mut Frame, area: Rect, app: &App) {
+    let (draw, input) = app.perf.frame_times();
+    let mut lines = vec![Line::from(vec![
+        Span::styled("frame    ", Style::default().fg(Color::Gray)),
+        Span::styled(format!("draw {} input {}", millis(draw), millis(input)), Style::default().fg(Color::White)),
+    ])];
+    for op in Op::ALL {
+        let value = match app.perf.last(op) {
+            Some((detail, took)) if detail.is_empty() => millis(took),
+            Some((detail, took)) => format!("{} ({})", millis(took), detail),
+            None => "-".to_string(),
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!("{:<9}", op.label()), Style::default().fg(Color::Gray)),
+            Span::styled(value, Style::default().fg(Color::White)),
+        ]));
+    }
+
+    let width = lines.iter().map(|l| l.width() as u16).max().unwrap_or(0).saturating_add(2).min(area.width);
+    let height = (lines.len() as u16 + 2).min(area.height);
+    let hud_area = Rect { x: area.x + area.width - width, y: area.y, width, height };
+    f.render_widget(Clear, hud_area);
+    let hud = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title("perf. F12").border_style(Style::default().fg(Color::Magenta)));
+    f.render_widget(hud, hud_area);
+}
+
+fn draw_header(f: &mut Frame, area: Rect, app: &App){
+    let conflict_tab = format!("{} (m)", app.conflict_label().to_uppercase());
+    let titles = vec!["status (1)", "log (2)", "branches (3)", "stashes (4)", "remote (5)", conflict_tab.as_str()];
+    let selected = match app.overlay_return_mode() {
+        AppMode::Status | AppMode::CommitDialog | AppMode::StashDialog | AppMode::ResetDialog | AppMode::AttributesDialog | AppMode::LineStaging | AppMode::ScopePicker | AppMode::DiffBasePicker | AppMode::SwitchRepoDialog | AppMode::CommandPalette | AppMode::NotificationHistory | AppMode::ErrorLog | AppMode::LogViewer | AppMode::CommandOutput | AppMode::Hooks | AppMode::Stats | AppMode::LargeObjects | AppMode::Maintenance | AppMode::Health | AppMode::Help | AppMode::ReleaseNotes | AppMode::NewRelease | AppMode::PackagePicker | AppMode::Clean | AppMode::AutostashDialog | AppMode::CredentialDialog | AppMode::RebaseOntoDialog | AppMode::StackView | AppMode::Files => 0,
+        AppMode::ArchiveDialog if app.archive_target == "HEAD" => 0,
+        AppMode::Log | AppMode::CommitView | AppMode::ArchiveDialog | AppMode::PickaxeDialog | AppMode::JumpDialog | AppMode::RestoreDialog => 1,
+        AppMode::Branches | AppMode::CreateBranchDialog | AppMode::GitflowDialog | AppMode::UpstreamDialog | AppMode::PushDialog => 2,
+        AppMode::StashList => 3,
+        AppMode::RemoteOperations | AppMode::BundleDialog | AppMode::SyncForkDialog | AppMode::PushPreview => 4,
+        AppMode::MergeConflict | AppMode::MergeMessageDialog => 5,
+    };
+
+    let name = if app.quick_commit.is_some() { "git-hydra commit" } else { "git-hydra" };
+    let title = match (&app.active_package, app.repo.status_scope()) {
+        (Some(package), _) => format!("{} [package: {}]", name, package.name),
+        (None, Some(scope)) => format!("{} [scope: {}]", name, scope),
+        (None, None) => name.to_string(),
+    };
+    let mut title = vec![Span::raw(title)];
+    if let Some(commit) = &app.detached_head {
+        title.push(Span::raw(" "));
+        title.push(Span::styled(
+            format!(" HEAD detached at {} (n in log: create branch here) ", app.repo.short_id(commit)),
+            Style::default().fg(Color::White).bg(Color::Red).add_modifier(Modifier::BOLD),
+        ));
+    }
+    if let Some(running) = app.running_command.as_ref().filter(|r| r.status.is_none()) {
+        title.push(Span::raw(" "));
+        title.push(Span::styled(
+            format!(" running {} ", running.name),
+            Style::default().fg(Color::Black).bg(Color::Yellow),
+        ));
+    }
+    if app.offline {
+        title.push(Span::raw(" "));
+        title.push(Span::styled(" offline ", Style::default().fg(Color::Black).bg(Color::Gray).add_modifier(Modifier::BOLD)));
+    }
+    if let Some(keys) = &app.recording {
+        title.push(Span::raw(" "));
+        title.push(Span::styled(
+            format!(" ● recording macro, {} key(s) (Q: stop) ", keys.len()),
+            Style::default().fg(Color::White).bg(Color::Magenta).add_modifier(Modifier::BOLD),
+        ));
+    }
+
+    let tabs = Tabs::new(titles)
+        .block(Block::default().borders(Borders::ALL).title(Line::from(title)))
+        .select(selected)
+        .style(Style::default().fg(Color::White))
+        .highlight_style(
+            Style::default()
+                .fg(if app.merge_conflict.is_some() { Color::Red } else { Color::Cyan })
+                .add_modifier(Modifier::BOLD),
+        );
+    
+    f.render_widget(tabs, area);
+}
+
+fn draw_reset_dialog(f: &mut Frame, area: Rect, app: &App) {
+    let popup_area = centered_rect(70, 60, area);
+
+    f.render_widget(Clear, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Length(4),
+            Constraint::Min(8),
+            Constraint::Length(3),
+        ])
+        .split(popup_area);
+
+    let title = Paragraph::new("Git Reset")
+        .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(Color::Red)))
+        .style(Style::default().fg(Color::White).add_modifier(Modifier::BOLD));
+    f.render_widget(title, chunks[0]);
+
+    draw_reset_target_info(f, chunks[1], app);
+    draw_reset_mode_selection(f, chunks[2], app);
+
+    let help_text = if app.get_current_reset_mode() == crate::git::ResetMode::Hard {
+        "WARNING: hard reset will permanently delete changes! \nenter: execute reset | esc: cancel"
+    } else {
+        "↑/↓: select mode | enter: execute reset | esc: cancel"
+    };
+
+    let help_style = if app.get_current_reset_mode() == crate::git::ResetMode::Hard {
+        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+    } else {
+        Style::default().fg(Color::Gray)
+    };
+
+    let help = Paragraph::new(help_text)
+        .block(Block::default().borders(Borders::ALL))
+        .style(help_style)
+        .wrap(Wrap { trim: true });
+
+    f.render_widget(help, chunks[3]);
+}
+
+fn draw_reset_target_info(f: &mut Frame, area: Rect, app: &App) {
+    let mut text = Vec::new();
+
+    if let Some(target_commit) = &app.reset_target_commit {
+        if let Some(commit) = app.commits.iter().find(|c| c.id == *target_commit) {
+            text.push(Line::from(vec![
+                Span::styled("reset to commit: ", Style::default().fg(Color::Gray)),
+                Span::styled(app.repo.short_id(&commit.id), Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+            ]));
+            text.push(Line::from(vec![
+                Span::styled("message: ", Style::default().fg(Color::Gray)),
+                Span::styled(
+                    commit.message.lines().next().unwrap_or(""),
+                    Style::default().fg(Color::White)
+                ),
+            ]));
+        } else {
+            text.push(Line::from(vec![
+                Span::styled("reset to commit: ", Style::default().fg(Color::Gray)),
+                Span::styled(app.repo.short_id(target_commit), Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))                
+            ]));
+        }
+    } else {
+        text.push(Line::from(vec![
+            Span::styled("reset to: ", Style::default().fg(Color::Gray)),
+            Span::styled("HEAD~1", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+            Span::styled(" (undo last commit)", Style::default().fg(Color::Gray)),
+        ]));
+    }
+
+    let info = Paragraph::new(text)
+        .block(Block::default().borders(Borders::ALL).title("target"))
+        .wrap(Wrap { trim: true });
+
+    f.render_widget(info, area);
+}
+
+fn draw_reset_mode_selection(f: &mut Frame, area: Rect, app: &App) {
+    let modes = crate::app::App::get_reset_modes();
+    let mut items = Vec::new();
+
+    for (i, mode_name) in modes.iter().enumerate() {
+        let is_selected = i == app.selected_reset_mode;
+
+        let (color, description) = match i {
+            0 => (Color::Green, "keep changes staged (safe)"),
+            1 => (Color::Yellow, "keep changes unstaged (safe)"),
+            2 => (Color::Red, "DISCARD ALL CHANGES (destructive)"),
+            _ => (Color::White, ""),
+        };
+
+        let style = if is_selected {
+            Style::default().bg(Color::DarkGray).fg(Color::White).add_modifier(Modifier::BOLD)
+        } else {
+            Style::default().fg(color)
+        };
+
+        let selection_indicator = if is_selected { "► " } else { " " };
+
+        items.push(ListItem::new(Line::from(vec![
+            Span::styled(selection_indicator, Style::default().fg(Color::Cyan)),
+            Span::styled(format!("{:<6}", mode_name), style),
+            Span::styled(format!(" - {}", description), Style::default().fg(Color::Gray)),
+        ])));
+    }
+
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title("Reset Mode"))
+        .style(Style::default().fg(Color::White));
+
+    f.render_widget(list, area);
+}
+
+fn draw_footer(f: &mut Frame, area: Rect, app: &App) {
+    let help_text = if app.mode == AppMode::LineStaging && app.line_discard.is_some() {
+        "y/enter: discard them | n/esc: keep them"
+    } else if app.mode == AppMode::LineStaging {
+        if app.line_diff_staged {
+            "↑/↓: move | space: pick line (on a header: whole hunk) | enter: unstage picked lines | esc: back"
+        } else {
+            "↑/↓: move | space: pick line (on a header: whole hunk) | enter: stage picked lines | d: discard picked lines / the hunk | esc: back"
+        }
+    } else if app.mode == AppMode::Status && app.quick_commit.is_some() {
+        "↑/↓: select | enter/space: stage / unstage | v: stage lines | pgup/pgdn: scroll diff | c: commit and quit | esc/q: quit without committing"
+    } else if app.mode == AppMode::PushPreview {
+        "enter/y: push | esc/n: cancel | ↑/↓ pgup/pgdn: scroll"
+    } else if app.mode == AppMode::Help && app.help_searching {
+        "type to search every page | enter: go to the first match | esc: cancel"
+    } else if app.mode == AppMode::Help {
+        "←/→ tab: page | ↑/↓ pgup/pgdn: scroll | /: search | n/N: next / previous match | esc: back"
+    } else if app.mode == AppMode::ReleaseNotes && app.notes_input.is_some() {
+        "enter: done | esc: cancel"
+    } else if app.mode == AppMode::ReleaseNotes {
+        "r: range | w: write to a file | y: copy | ↑/↓ pgup/pgdn: scroll | F5: regenerate | esc: back"
+    } else if app.mode == AppMode::NewRelease {
+        "←/→: patch / minor / major | p: push the tag too | tab: remote | ↑/↓ pgup/pgdn: scroll | enter: tag | esc: cancel"
+    } else if app.mode == AppMode::Health {
+        "↑/↓: select | enter: fix the selected problem | F5: check again | esc: back"
+    } else if app.mode == AppMode::Clean && app.clean_preview.is_some() {
+        "y/enter: delete them for good | n/esc: back to the list"
+    } else if app.mode == AppMode::Clean {
+        "↑/↓: select | space: mark | a: mark all / none | i: mark the ignored ones | enter: dry run of the marked | F5: reload | esc: back"
+    } else if app.mode == AppMode::Maintenance {
+        "↑/↓: select | enter: run in the background | esc: back"
+    } else if app.mode == AppMode::LargeObjects {
+        "↑/↓: select | enter: show the commit that added it | F5: scan again | esc: back"
+    } else if app.mode == AppMode::Stats {
+        "←/→: pick author | +/-: four weeks more / less | F5: reload | esc: back"
+    } else if app.mode == AppMode::CommitView {
+        match app.commit_range {
+            Some(_) => "↑/↓/pgup/pgdn: scroll | home/end: top / bottom | n/p: next / previous file | y: copy range | esc/enter: back to the log",
+            None => "↑/↓/pgup/pgdn: scroll | home/end: top / bottom | n/p: next / previous file | ←/→: newer / older commit | y/Y: copy id / parents | esc/enter: back to the log",
+        }
+    } else if app.mode == AppMode::Files {
+        match app.files.as_ref() {
+            Some(files) if files.export.is_some() => "enter: write the file | esc: cancel",
+            Some(files) if files.search.is_some() => "type to search every file | ↑/↓: select | enter: go to it | esc: cancel",
+            _ => "↑/↓: select | enter/→: open directory | ←/backspace: up | pgup/pgdn: scroll the file | /: search by name | x: export the file | y: copy path | esc: back",
+        }
+    } else if app.mode == AppMode::Hooks {
+        "↑/↓: select | space: enable / disable | e: edit in $EDITOR | r: test run | F5: reload | esc: back"
+    } else if app.mode == AppMode::MergeConflict {
+        match f.area().width > 100 {
+            true => "↑/↓: navigate hunks | ←/→: navigate files | o: keep ours | t: keep theirs | b: keep both | O/T: ours/theirs for the file | </>: ours/theirs for all files | [/]: our/their file from the index | d: delete file | p: preview | M: mergetool | c: complete merge | a: abort | F1: help | F5: refresh | q: quit",
+            false => "↑/↓: hunks | ←/→: files | o: ours | t: theirs | O/T: whole file | </>: all files | [/]: index file | b: both | p: preview | c: complete | a: abort | q: quit",
+        }
+    } else {
+        match f.area().width > 120 {
+            true => "↑/↓: navigate | 1/2/3/4: switch tabs | :/ctrl-p: commands | F1: help | N: notifications | E: error log | enter: action | s: stash | n: new branch | L: ls-remote (branches) | c: commit | pgup/down: scroll diff view | y/Y: copy selection/diff | e: edit | d: difftool | o: open on web | v: stage lines | w: ignore line endings | W/U: skip-worktree/assume-unchanged | X/T: stage only the mode / content change | F: scope status | D: diff against a ref | Z: packages | x: export archive | S: search changes | space: mark commit, enter on another diffs the two | g/H: jump to rev/HEAD | C: checkout commit (detached) | R: restore file from commit | i/I: open/copy issue links | A: .gitattributes | F5: refresh | r: reset to commit | q: quit",
+            false => "↑/↓: nav | 1/2/3/4: tabs | : commands | F1: help | enter: action | s: stash | n: branch | c: commit | pgup/down: scroll diff | y: copy | r: reset | q: quit",
+        }
+    };
+    
+    let help = if let Some(toast) = app.notifications.current() {
+        let color = match toast.level {
+            Level::Info => Color::Cyan,
+            Level::Success => Color::Green,
+            Level::Error => Color::Red,
+        };
+        let mut spans = vec![Span::styled(toast.message.as_str(), Style::default().fg(color))];
+        if app.notifications.queued() > 0 {
+            spans.push(Span::styled(format!("  (+{} more, N: history)", app.notifications.queued()), Style::default().fg(Color::Gray)));
+        }
+        Paragraph::new(Line::from(spans))
+            .block(Block::default().borders(Borders::ALL))
+    } else {
+        Paragraph::new(help_text)
+            .block(Block::default().borders(Borders::ALL))
+            .style(Style::default().fg(Color::Gray))
+            .wrap(Wrap { trim: true })
+    };
+
+    f.render_widget(help, area);
+}
+
+fn draw_status_view(f: &mut Frame, area: Rect, app: &App) {
+    if let Some(status) = &app.status{
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(40), Constraint::Percentage(30), Constraint::Percentage(30)])
+            .split(area);
+
+        draw_file_changes(f, chunks[0], app, status);
+
+        let base = app.repo.diff_base().map(|base| (base, app.base_changes.as_slice()));
+        draw_repo_info(f, chunks[1], status, &app.eol_warnings, base, &fetch_lines(app));
+
+        draw_file_diff(f, chunks[2], app);
+    } else if app.repo.is_bare() {
+        let bare = Paragraph::new(vec![
+            Line::from(format!("bare repository at {}.", app.repo.git_dir().display())),
+            Line::from(""),
+            Line::from("there are no files to stage or diff here. log, branches and remotes (2, 3, 5) still work,"),
+            Line::from("or open it with --git-dir <dir> --work-tree <dir> to pair it with a checkout."),
+        ])
+        .block(Block::default().borders(Borders::ALL).title("status."))
+        .style(Style::default().fg(Color::Gray))
+        .wrap(Wrap { trim: false });
+
+        f.render_widget(bare, area);
+    } else {
+        let loading = Paragraph::new("loading repository status...")
+            .block(Block::default().borders(Borders::ALL).title("status."))
+            .style(Style::default().fg(Color::Cyan));
+
+        f.render_widget(loading, area);
+    }
+}
+
+
+fn draw_file_changes(f: &mut Frame, area: Rect, app: &App, status: &crate::git::RepoStatus){
+    let mut items = Vec::new();
+    let mut current_index = 0;
+    // the section headers are rows too
+    let mut selected_row = None;
+
+    if !status.staged.is_empty(){
+        items.push(ListItem::new(Line::from(vec![
+            Span::styled("── staged changes ──", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD))
+        ])));
+
+        for file in &status.staged {
+            if current_index == app.views.status.selected {
+                selected_row = Some(items.len());
+            }
+            let style = if current_index == app.views.status.selected {
+                Style::default().bg(Color::DarkGray).fg(Color::White)
+            } else {
+                Style::default().fg(Color::Green)
+            };
+
+            let mut spans = vec![
+                Span::styled(format!(" {} ", file.status), Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+                Span::styled(&file.path, style),
+            ];
+            spans.extend(mode_change_span(file));
+            spans.extend(line_count_spans(app, &file.path, true));
+            items.push(ListItem::new(Line::from(spans)));
+            current_index += 1;
+        }
+    }
+
+    if !status.unstaged.is_empty(){
+        items.push(ListItem::new(Line::from(vec![
+            Span::styled("── unstaged changes ──", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))
+        ])));
+
+        for file in &status.unstaged {
+            if current_index == app.views.status.selected {
+                selected_row = Some(items.len());
+            }
+            let style = if current_index == app.views.status.selected {
+                Style::default().bg(Color::DarkGray).fg(Color::White)
+            } else {
+                Style::default().fg(Color::Red)
+            };
+
+            let mut spans = vec![
+                Span::styled(format!(" {} ", file.status), Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+                Span::styled(&file.path, style),
+            ];
+            spans.extend(mode_change_span(file));
+            spans.extend(line_count_spans(app, &file.path, false));
+            items.push(ListItem::new(Line::from(spans)));
+            current_index += 1;
+        }
+    }
+
+    if !status.untracked.is_empty(){
+        items.push(ListItem::new(Line::from(vec![
+            Span::styled("── untracked files ──", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+        ])));
+
+        for file in &status.untracked {
+            if current_index == app.views.status.selected {
+                selected_row = Some(items.len());
+            }
+            let style = if current_index == app.views.status.selected {
+                Style::default().bg(Color::DarkGray).fg(Color::White)
+            } else {
+                Style::default().fg(Color::Cyan)
+            };
+
+            let mut spans = vec![
+                Span::styled(" ?? ", Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+                Span::styled(&file.path, style),
+            ];
+            spans.extend(mode_change_span(file));
+            spans.extend(line_count_spans(app, &file.path, false));
+            items.push(ListItem::new(Line::from(spans)));
+            current_index += 1;
+        }
+    }
+
+    if !status.nested.is_empty(){
+        items.push(ListItem::new(Line::from(vec![
+            Span::styled("── nested repositories ──", Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD))
+        ])));
+
+        for repo in &status.nested {
+            if current_index == app.views.status.selected {
+                selected_row = Some(items.len());
+            }
+            let style = if current_index == app.views.status.selected {
+                Style::default().bg(Color::DarkGray).fg(Color::White)
+            } else {
+                Style::default().fg(Color::Magenta)
+            };
+
+            items.push(ListItem::new(Line::from(vec![
+                Span::styled(" ▸ ", Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD)),
+                Span::styled(&repo.path, style),
+                Span::styled(format!(" ({})", repo.summary()), Style::default().fg(Color::Gray)),
+            ])));
+            current_index += 1;
+        }
+    }
+
+    if !status.flagged.is_empty(){
+        items.push(ListItem::new(Line::from(vec![
+            Span::styled("── hidden from status (W/U to clear) ──", Style::default().fg(Color::DarkGray).add_modifier(Modifier::BOLD))
+        ])));
+
+        for file in &status.flagged {
+            if current_index == app.views.status.selected {
+                selected_row = Some(items.len());
+            }
+            let style = if current_index == app.views.status.selected {
+                Style::default().bg(Color::DarkGray).fg(Color::White)
+            } else {
+                Style::default().fg(Color::Gray)
+            };
+
+            let marker = if file.skip_worktree { " S " } else { " h " };
+            items.push(ListItem::new(Line::from(vec![
+                Span::styled(marker, Style::default().fg(Color::DarkGray).add_modifier(Modifier::BOLD)),
+                Span::styled(&file.path, style),
+                Span::styled(format!(" ({})", file.labels()), Style::default().fg(Color::DarkGray)),
+            ])));
+            current_index += 1;
+        }
+    }
+
+    if items.is_empty(){
+        items.push(ListItem::new(Line::from(vec![
+            Span::styled("✔ working tree clean.", Style::default().fg(Color::Green))
+        ])));
+    }
+
+    let len = items.len();
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title("changes"))
+        .style(Style::default().fg(Color::White));
+
+    render_list(f, area, list, len, selected_row, &app.views.status.offset);
+}
+
+fn mode_change_span<'a>(file: &crate::git::FileStatus) -> Option<Span<'a>> {
+    file.mode_label().map(|label| Span::styled(format!(" [{}]", label), Style::default().fg(Color::Magenta)))
+}
+
+// " +12 -3" after a file, nothing until the background count has it (or for binary files)
+fn line_count_spans<'a>(app: &App, path: &str, staged: bool) -> Vec<Span<'a>> {
+    match app.line_counts.get(path, staged) {
+        Some((added, removed)) if added + removed > 0 => vec![
+            Span::styled(format!(" +{}", added), Style::default().fg(Color::Green)),
+            Span::styled(format!(" -{}", removed), Style::default().fg(Color::Red)),
+        ],
+        _ => Vec::new(),
+    }
+}
+
+// keeps the selection in view, scrolling only as far as it has to from where the list was last
+// frame (a fresh ListState would pin the selection to the bottom edge), with a scrollbar once
+// it doesn't fit
+fn render_list(f: &mut Frame, area: Rect, list: List, len: usize, selected: Option<usize>, offset: &Cell<usize>) {
+    let mut state = ListState::default().with_offset(offset.get().min(len.saturating_sub(1))).with_selected(selected);
+    f.render_stateful_widget(list, area, &mut state);
+    offset.set(state.offset());
+    draw_scrollbar(f, area, len, state.offset());
+}
+
+// on the right border of a bordered widget showing `len` rows from `position`
+fn draw_scrollbar(f: &mut Frame, area: Rect, len: usize, position: usize) {
+    let height = area.height.saturating_sub(2) as usize;
+    if len <= height {
+        return;
+    }
+    let mut state = ScrollbarState::new(len - height).position(position.min(len - height));
+    let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight).begin_symbol(None).end_symbol(None);
+    f.render_stateful_widget(scrollbar, area.inner(Margin { vertical: 1, horizontal: 0 }), &mut state);
+}
+
+// a partial clone's promisor remote, when the remotes were last fetched and what auto-fetch ran
+// into. nothing for a repo without remotes that was never fetched
+fn fetch_lines(app: &App) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    if let Some((remote, filter)) = app.repo.partial_clone() {
+        let filter = if filter.is_empty() { String::new() } else { format!(" ({})", filter) };
+        lines.push(Line::from(vec![
+            Span::styled("partial clone: ", Style::default().fg(Color::Gray)),
+            Span::styled(format!("the rest is on {}{}", remote, filter), Style::default().fg(Color::White)),
+        ]));
+    }
+    let last = app.repo.last_fetched();
+    let every = match app.auto_fetch.interval() {
+        Some(_) if app.offline => " (auto-fetch paused, offline)".to_string(),
+        Some(interval) => format!(" (auto every {}m)", interval.as_secs() / 60),
+        None => String::new(),
+    };
+    if last.is_none() && every.is_empty() {
+        return lines;
+    }
+    lines.push(Line::from(vec![
+        Span::styled("fetched: ", Style::default().fg(Color::Gray)),
+        Span::styled(last.map(|at| relative_date(at, Utc::now())).unwrap_or_else(|| "not yet".to_string()), Style::default().fg(Color::White)),
+        Span::styled(every, Style::default().fg(Color::Gray)),
+    ]));
+    for error in &app.auto_fetch.errors {
+        lines.push(Line::from(Span::styled(format!("  fetch failed, {}", error), Style::default().fg(Color::Red))));
+    }
+    lines
+}
+
+fn draw_repo_info(f: &mut Frame, area: Rect, status: &crate::git::RepoStatus, eol_warnings: &[String], base: Option<(&str, &[StashFileStat])>, fetch: &[Line<'static>]){
+    let mut text = Vec::new();
+
+    text.push(Line::from(vec![
+        Span::styled("branch: ", Style::default().fg(Color::Gray)),
+        Span::styled(&status.branch, Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+    ]));
+    if let Some(upstream) = &status.upstream {
+        text.push(Line::from(vec![
+            Span::styled("tracking: ", Style::default().fg(Color::Gray)),
+            Span::styled(upstream.as_str(), Style::default().fg(Color::White)),
+        ]));
+    }
+    
+    if status.ahead > 0 {
+        text.push(Line::from(vec![
+            Span::styled("↑ ahead: ", Style::default().fg(Color::Green)),
+            Span::styled(status.ahead.to_string(), Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+            Span::styled(" commits", Style::default().fg(Color::Gray))
+        ]));
+    }
+
+    if status.behind > 0 {
+        text.push(Line::from(vec![
+            Span::styled("↓ behind: ", Style::default().fg(Color::Red)),
+            Span::styled(status.behind.to_string(), Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+            Span::styled(" commits", Style::default().fg(Color::Gray)),
+        ]));
+    }
+    
+    if status.ahead == 0 && status.behind == 0{
+        text.push(Line::from(vec![
+            Span::styled("✓ up to date.", Style::default().fg(Color::Green)),
+        ]));
+    }
+    text.extend(fetch.iter().cloned());
+    
+    text.push(Line::from(""));
+
+    let total_changes = status.staged.len() + status.unstaged.len() + status.untracked.len();
+    if total_changes > 0{
+        text.push(Line::from(vec![
+            Span::styled("files changed: ", Style::default().fg(Color::Gray)),
+            Span::styled(total_changes.to_string(), Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+        ]));
+        
+        if !status.staged.is_empty() {
+            text.push(Line::from(vec![
+                Span::styled("  staged: ", Style::default().fg(Color::Green)),
+                Span::styled(status.staged.len().to_string(), Style::default().fg(Color::Green)),
+            ]));
+        }
+        
+        if !status.unstaged.is_empty() {
+            text.push(Line::from(vec![
+                Span::styled("  unstaged: ", Style::default().fg(Color::Red)),
+                Span::styled(status.unstaged.len().to_string(), Style::default().fg(Color::Red)),
+            ]));
+        }
+        
+        if !status.untracked.is_empty() {
+            text.push(Line::from(vec![
+                Span::styled("  untracked: ", Style::default().fg(Color::Cyan)),
+                Span::styled(status.untracked.len().to_string(), Style::default().fg(Color::Cyan)),
+            ]));
+        }
+    }
+
+    // everything the branch and the local changes add up to, compared with the diff base
+    if let Some((base, files)) = base {
+        text.push(Line::from(""));
+        text.push(Line::from(vec![
+            Span::styled("against ", Style::default().fg(Color::Gray)),
+            Span::styled(base, Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD)),
+            Span::styled(format!(": {} file(s)", files.len()), Style::default().fg(Color::Gray)),
+        ]));
+        for file in files {
+            text.push(Line::from(vec![
+                Span::styled(format!("  {} ", file.status), Style::default().fg(Color::Magenta)),
+                Span::styled(file.path.as_str(), Style::default().fg(Color::White)),
+                Span::styled(format!(" +{}", file.additions), Style::default().fg(Color::Green)),
+                Span::styled(format!(" -{}", file.deletions), Style::default().fg(Color::Red)),
+            ]));
+        }
+    }
+
+    if !eol_warnings.is_empty() {
+        text.push(Line::from(""));
+        text.push(Line::from(Span::styled("line endings:", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD))));
+        for warning in eol_warnings {
+            text.push(Line::from(Span::styled(format!("  {}", warning), Style::default().fg(Color::Yellow))));
+        }
+    }
+
+    let paragraph = Paragraph::new(text)
+        .block(Block::default().borders(Borders::ALL).title("repository info."))
+        .wrap(Wrap { trim: true });
+    
+    f.render_widget(paragraph, area);
+
+}
+
+// lines fetched past the bottom of the diff pane, wrapped lines eat into the pane so it needs
+// some to spare
+const DIFF_MARGIN: usize = 100;
+
+fn draw_file_diff(f: &mut Frame, area: Rect, app: &App){
+    if let Some(file_path) = app.get_selected_file_path() {
+        let viewport_height = area.height.saturating_sub(2) as usize;
+        let window = app.perf.time(Op::Diff, &file_path, || {
+            app.repo.get_file_diff_window(&file_path, app.views.status.scroll, viewport_height + DIFF_MARGIN).and_then(|window| {
+                // scrolled past the end, show the last page instead
+                match window.total {
+                    Some(total) if window.start > total.saturating_sub(viewport_height) => {
+                        app.repo.get_file_diff_window(&file_path, total.saturating_sub(viewport_height), viewport_height + DIFF_MARGIN)
+                    }
+                    _ => Ok(window),
+                }
+            })
+        });
+        match window {
+            Ok(window) => {
+                if window.lines.is_empty() && window.total == Some(0) {
+                    let message = if app.repo.ignore_eol() && app.eol_only_change(&file_path) {
+                        "only line endings changed, hidden (w shows them)."
+                    } else {
+                        "no changes to display."
+                    };
+                    let no_diff = Paragraph::new(message)
+                        .block(Block::default().borders(Borders::ALL).title("diff."))
+                        .style(Style::default().fg(Color::Gray));
+
+                    f.render_widget(no_diff, area);
+                } else {
+                    let attributes = app.file_attributes(&file_path);
+                    let mut lines: Vec<Line> = Vec::new();
+                    if !attributes.is_empty() {
+                        lines.push(Line::from(vec![
+                            Span::styled("attributes: ", Style::default().fg(Color::Gray)),
+                            Span::styled(attributes.join(" "), Style::default().fg(Color::Magenta)),
+                        ]));
+                    }
+                    lines.extend(window.lines.iter().map(|line| styled_diff_line(line)));
+                    if window.total.is_none() {
+                        lines.push(Line::from(Span::styled("… more below, pgdn to load", Style::default().fg(Color::Gray))));
+                    }
+
+                    let mut title = match window.total {
+                        Some(total) if total > viewport_height => format!("diff: {} ({}%)", file_path,
+                            window.start * 100 / (total - viewport_height)),
+                        Some(_) => format!("diff: {}", file_path),
+                        None => format!("diff: {} (from line {})", file_path, window.start + 1),
+                    };
+                    if let Some(base) = app.repo.diff_base() {
+                        title.push_str(&format!(" [against {}]", base));
+                    }
+                    if app.repo.ignore_eol() {
+                        title.push_str(" [ignoring line endings]");
+                    } else if app.eol_only_change(&file_path) {
+                        title.push_str(" [line endings only, w hides]");
+                    }
+
+                    let diff_paragraph = Paragraph::new(lines)
+                        .block(Block::default().borders(Borders::ALL).title(title))
+                        .wrap(Wrap { trim: false });
+                    
+                    f.render_widget(diff_paragraph, area);
+                    // past the loaded window the length is a guess, at least one more page
+                    let len = window.total.unwrap_or(window.start + window.lines.len() + viewport_height);
+                    draw_scrollbar(f, area, len, window.start);
+                }
+            }
+            Err(_) => {
+                let error_diff = Paragraph::new("error loading diff.")
+                    .block(Block::default().borders(Borders::ALL).title("diff"))
+                    .style(Style::default().fg(Color::Red));
+                f.render_widget(error_diff, area);
+            }
+        }
+        
+    } else {
+        let no_file = Paragraph::new("select a file to view diff.")
+            .block(Block::default().borders(Borders::ALL).title("diff"))
+            .style(Style::default().fg(Color::Gray));
+        f.render_widget(no_file, area);
+    }
+}
+
+fn draw_line_staging_view(f: &mut Frame, area: Rect, app: &App) {
+    let (Some(status), Some(file_diff)) = (&app.status, &app.line_diff) else {
+        return;
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(35), Constraint::Percentage(65)])
+        .split(area);
+
+    draw_file_changes(f, chunks[0], app, status);
+
+    let rows = app.line_rows();
+    let lines: Vec<Line> = rows
+        .iter()
+        .enumerate()
+        .map(|(row, &(hunk_idx, line))| {
+            let hunk = &file_diff.hunks[hunk_idx];
+            let mut line = match line {
+                None => Line::from(Span::styled(
+                    hunk.header.clone(),
+                    Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+                )),
+                Some(line_idx) => {
+                    let diff_line = &hunk.lines[line_idx];
+                    let picked = app.selected_lines.contains(&(hunk_idx, line_idx));
+                    let marker = match (diff_line.is_change(), picked) {
+                        (true, true) => "[x] ",
+                        (true, false) => "[ ] ",
+                        _ => "    ",
+                    };
+                    let color = match diff_line.origin {
+                        '+' => Color::Green,
+                        '-' => Color::Red,
+                        _ => Color::White,
+                    };
+                    Line::from(vec![
+                        Span::styled(marker, Style::default().fg(Color::Yellow)),
+                        Span::styled(format!("{}{}", diff_line.origin, diff_line.text()), Style::default().fg(color)),
+                    ])
+                }
+            };
+            if row == app.line_cursor {
+                line = line.style(Style::default().bg(Color::DarkGray));
+            }
+            line
+        })
+        .collect();
+
+    let viewport_height = chunks[1].height.saturating_sub(2) as usize;
+    let scroll = app.line_cursor.saturating_sub(viewport_height / 2) as u16;
+
+    let title = format!(
+        "{} lines: {} ({} picked)",
+        if app.line_diff_staged { "unstage" } else { "stage" },
+        file_diff.path,
+        app.selected_lines.len(),
+    );
+
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(title).border_style(Style::default().fg(Color::Yellow)))
+        .scroll((scroll, 0));
+
+    f.render_widget(paragraph, chunks[1]);
+
+    if let Some(discard) = &app.line_discard {
+        draw_discard_confirmation(f, area, &file_diff.path, discard.len());
+    }
+}
+
+fn draw_discard_confirmation(f: &mut Frame, area: Rect, path: &str, count: usize) {
+    let popup_area = centered_rect(50, 20, area);
+    f.render_widget(Clear, popup_area);
+    let text = vec![
+        Line::from(format!("discard {} changed line(s) of {} from the working tree?", count, path)),
+        Line::from(""),
+        Line::from(Span::styled("they aren't stashed or staged anywhere, there's no undo.", Style::default().fg(Color::Red))),
+        Line::from(""),
+        Line::from(Span::styled("y: discard | n: keep", Style::default().fg(Color::Gray))),
+    ];
+    let paragraph = Paragraph::new(text)
+        .block(Block::default().borders(Borders::ALL).title("discard").border_style(Style::default().fg(Color::Red)))
+        .wrap(Wrap { trim: true });
+    f.render_widget(paragraph, popup_area);
+}
+
+fn draw_log_view(f: &mut Frame, area: Rect, app: &App){
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(70), Constraint::Percentage(30)])
+        .split(area);
+    
+    draw_commit_list(f, chunks[0], app);
+    
+    draw_commit_details(f, chunks[1], app);
+}
+
+fn draw_commit_list(f: &mut Frame, area: Rect, app: &App){
+    let items: Vec<ListItem> = app
+        .commits
+        .iter()
+        .enumerate()
+        .map(|(i, commit)| {
+            let style = if i == app.views.log.selected {
+                Style::default().bg(Color::DarkGray).fg(Color::White)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            
+            let short_id = app.repo.short_id(&commit.id);
+            let first_line = commit.message.lines().next().unwrap_or("");
+            let time_str = list_date(app, commit.timestamp);
+            
+            let mut spans = Vec::new();
+            if let (Some(graph), true, None, None) = (&app.graph, app.show_graph, &app.log_filter, &app.active_package) {
+                spans.push(Span::styled(graph.cells(i), Style::default().fg(Color::Magenta)));
+            }
+            if app.log_mark.as_ref() == Some(&commit.id) {
+                spans.push(Span::styled("▶ ", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)));
+            }
+            spans.push(Span::styled(format!("{} ", short_id), Style::default().fg(Color::Cyan)));
+            if let Some(labels) = app.ref_map.get(&commit.id) {
+                for label in labels {
+                    let color = match label.kind {
+                        RefKind::Head => Color::Cyan,
+                        RefKind::LocalBranch => Color::Green,
+                        RefKind::RemoteBranch => Color::Red,
+                        RefKind::Tag => Color::Yellow,
+                    };
+                    spans.push(Span::styled(
+                        format!("[{}]", label.name),
+                        Style::default().fg(Color::Black).bg(color).add_modifier(Modifier::BOLD),
+                    ));
+                    spans.push(Span::raw(" "));
+                }
+            }
+            spans.push(Span::styled(
+                format!("{:<2}", initials(&commit.author)),
+                Style::default().fg(Color::Black).bg(author_color(commit)).add_modifier(Modifier::BOLD),
+            ));
+            spans.push(Span::raw(" "));
+            spans.push(Span::styled(format!("{} ", first_line), style));
+            spans.push(Span::styled(format!("({})", time_str), Style::default().fg(Color::Gray)));
+
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+    
+    let title = match (&app.log_filter, &app.active_package) {
+        (Some(needle), _) => format!("commits changing \"{}\" (esc: clear).", needle),
+        (None, Some(package)) => format!("commits touching {}/ (Z: other package).", package.path),
+        (None, None) if app.show_graph => "commit graph (G: flat list).".to_string(),
+        (None, None) => "commit history.".to_string(),
+    };
+
+    if let (Some(branch), None) = (&app.unborn_branch, &app.log_filter) {
+        let empty = Paragraph::new(vec![
+            Line::from(format!("no commits on {} yet.", branch)),
+            Line::from(""),
+            Line::from("stage files in the status tab (1, then enter on a file) and press c for the first commit."),
+        ])
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .style(Style::default().fg(Color::Gray))
+        .wrap(Wrap { trim: false });
+        f.render_widget(empty, area);
+        return;
+    }
+
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .style(Style::default().fg(Color::White));
+
+    render_list(f, area, list, app.commits.len(), Some(app.views.log.selected), &app.views.log.offset);
+}
+
+fn draw_commit_details(f: &mut Frame, area: Rect, app: &App){
+    if let Some(commit) = app.commits.get(app.views.log.selected) {
+        let mut text = Vec::new();
+        
+        text.push(Line::from(vec![
+            Span::styled("commit: ", Style::default().fg(Color::Gray)),
+            Span::styled(app.repo.short_id(&commit.id), Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+        ]));
+        
+        text.push(Line::from(vec![
+            Span::styled("author: ", Style::default().fg(Color::Gray)),
+            Span::styled(&commit.author, Style::default().fg(author_color(commit))),
+        ]));
+        
+        text.push(Line::from(vec![
+            Span::styled("date: ", Style::default().fg(Color::Gray)),
+            Span::styled(full_date(app, commit.timestamp), Style::default().fg(Color::White)),
+        ]));
+
+        if let Some(signature) = app.signatures.get(&commit.id) {
+            if signature.status != SignatureStatus::Unsigned {
+                let (badge, color) = match signature.status {
+                    SignatureStatus::Verified => ("✓", Color::Green),
+                    SignatureStatus::Untrusted => ("?", Color::Yellow),
+                    SignatureStatus::Bad => ("✗", Color::Red),
+                    _ => ("!", Color::Yellow),
+                };
+                let mut spans = vec![
+                    Span::styled("signed: ", Style::default().fg(Color::Gray)),
+                    Span::styled(format!("{} {}", badge, signature.status.label()), Style::default().fg(color).add_modifier(Modifier::BOLD)),
+                ];
+                if let Some(detail) = &signature.detail {
+                    spans.push(Span::styled(format!(" ({})", detail), Style::default().fg(Color::Gray)));
+                }
+                text.push(Line::from(spans));
+                if let Some(signer) = &signature.signer {
+                    text.push(Line::from(vec![
+                        Span::styled("signer: ", Style::default().fg(Color::Gray)),
+                        Span::styled(signer.as_str(), Style::default().fg(color)),
+                    ]));
+                }
+            }
+        }
+        
+        text.push(Line::from(""));
+        
+        for line in commit.message.lines() {
+            text.push(Line::from(issue_spans(line, &app.issue_rules)));
+        }
+
+        let issue_refs = app.selected_issue_refs();
+        if !issue_refs.is_empty() {
+            text.push(Line::from(""));
+            text.push(Line::from(vec![
+                Span::styled("issues: ", Style::default().fg(Color::Gray)),
+                Span::styled("(i open first, I copy all)", Style::default().fg(Color::DarkGray)),
+            ]));
+            for issue in &issue_refs {
+                text.push(Line::from(vec![
+                    Span::styled(format!("  {} ", issue.text), Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD)),
+                    Span::styled(issue.url.clone(), Style::default().fg(Color::Blue).add_modifier(Modifier::UNDERLINED)),
+                ]));
+            }
+        }
+        
+        if !commit.parents.is_empty(){
+            text.push(Line::from(""));
+            text.push(Line::from(vec![
+                Span::styled("Parents: ", Style::default().fg(Color::Gray)),
+            ]));
+            for parent in &commit.parents {
+                text.push(Line::from(vec![
+                    Span::styled("  ", Style::default()),
+                    Span::styled(app.repo.short_id(parent), Style::default().fg(Color::Cyan)),
+                ]));
+            }
+        }
+        
+        // wrapped lines aren't counted, so this can stop a bit short of a huge message's last line
+        let scroll = app.views.log.scroll.min(text.len().saturating_sub(1)).min(u16::MAX as usize) as u16;
+        let title = if scroll > 0 { "commit details. (pgup/pgdn: scroll, enter: full view)" } else { "commit details. (enter: full view)" };
+        let paragraph = Paragraph::new(text)
+            .block(Block::default().borders(Borders::ALL).title(title))
+            .wrap(Wrap { trim: true })
+            .scroll((scroll, 0));
+        
+        f.render_widget(paragraph, area);
+
+        // top right corner inside the border, only when nothing is drawn over the log
+        if app.avatars.enabled() && app.mode == AppMode::Log && app.error_message.is_none() && area.width >= 30 && area.height >= 6 {
+            let slot = Rect::new(area.right() - 8, area.y + 1, 6, 3);
+            app.avatar_slot.set(Some((commit.author_email.clone(), slot)));
+        }
+    } else {
+        let text = if app.unborn_branch.is_some() { "empty repository." } else { "no commits found." };
+        let empty = Paragraph::new(text)
+            .block(Block::default().borders(Borders::ALL).title("commit details."))
+            .style(Style::default().fg(Color::Gray));
+        f.render_widget(empty, area);
+    }
+}
+
+fn draw_large_objects_view(f: &mut Frame, area: Rect, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(4), Constraint::Min(3)])
+        .split(area);
+
+    let totals = &app.object_totals;
+    let listed: u64 = app.large_objects.iter().map(|o| o.size as u64).sum();
+    let summary = Paragraph::new(vec![
+        Line::from(format!(
+            "{} objects, {} of them blobs adding up to {} before compression. .git/objects takes {} on disk.",
+            totals.objects,
+            totals.blobs,
+            human_size(totals.blob_bytes),
+            human_size(totals.disk_bytes)
+        )),
+        Line::from(Span::styled(
+            format!("the {} biggest blobs below are {} of that.", app.large_objects.len(), human_size(listed)),
+            Style::default().fg(Color::Gray),
+        )),
+    ])
+    .block(Block::default().borders(Borders::ALL).title("repository size").border_style(Style::default().fg(Color::Cyan)));
+    f.render_widget(summary, chunks[0]);
+
+    let items: Vec<ListItem> = app
+        .large_objects
+        .iter()
+        .enumerate()
+        .map(|(i, object)| {
+            let style = if i == app.selected_large_object {
+                Style::default().fg(Color::Black).bg(Color::Cyan)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            let mut spans = vec![
+                Span::styled(format!("{:>10} ", human_size(object.size as u64)), Style::default().fg(Color::Yellow)),
+                Span::styled(object.path.clone().unwrap_or_else(|| format!("{} (not in any commit)", app.repo.short_id(object.oid))), style),
+            ];
+            if let Some(commit) = &object.commit {
+                spans.push(Span::styled(format!("  {} ", app.repo.short_id(&commit.id)), Style::default().fg(Color::Cyan)));
+                spans.push(Span::styled(
+                    format!("{} ({})", commit.message.lines().next().unwrap_or(""), list_date(app, commit.timestamp)),
+                    Style::default().fg(Color::Gray),
+                ));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+    let items = if items.is_empty() { vec![ListItem::new(Span::styled("no blobs in this repository.", Style::default().fg(Color::Gray)))] } else { items };
+    let mut state = ListState::default();
+    state.select(Some(app.selected_large_object));
+    let list = List::new(items).block(Block::default().borders(Borders::ALL).title("largest blobs, and the commit that added each"));
+    f.render_stateful_widget(list, chunks[1], &mut state);
+}
+
+fn draw_maintenance_view(f: &mut Frame, area: Rect, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(maintenance::TASKS.len() as u16 + 2), Constraint::Min(3)])
+        .split(area);
+
+    let items: Vec<ListItem> = maintenance::TASKS
+        .iter()
+        .map(|task| {
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{:<16}", task.name), Style::default().fg(Color::White)),
+                Span::styled(format!("git {}", task.args.join(" ")), Style::default().fg(Color::Gray)),
+            ]))
+        })
+        .collect();
+    let title = format!("maintenance | .git/objects is {} on disk", human_size(app.maintenance_size));
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(title).border_style(Style::default().fg(Color::Cyan)))
+        .highlight_style(Style::default().bg(Color::DarkGray));
+    let mut state = ListState::default();
+    state.select(Some(app.selected_maintenance_task));
+    f.render_stateful_widget(list, chunks[0], &mut state);
+
+    let description = Paragraph::new(maintenance::TASKS[app.selected_maintenance_task].description)
+        .block(Block::default().borders(Borders::ALL))
+        .style(Style::default().fg(Color::White))
+        .wrap(Wrap { trim: true });
+    f.render_widget(description, chunks[1]);
+}
+
+fn draw_clean_view(f: &mut Frame, area: Rect, app: &App) {
+    let preview_height = app.clean_preview.as_ref().map_or(3, |paths| (paths.len() as u16 + 3).min(area.height / 2));
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(preview_height)])
+        .split(area);
+
+    let items: Vec<ListItem> = match app.clean_entries.is_empty() {
+        true => vec![ListItem::new("nothing untracked or ignored, the worktree is clean.").style(Style::default().fg(Color::Gray))],
+        false => app
+            .clean_entries
+            .iter()
+            .map(|entry| {
+                let mark = if app.clean_marked.contains(&entry.path) { "[x] " } else { "[ ] " };
+                let (kind, color) = if entry.ignored { ("ignored   ", Color::Yellow) } else { ("untracked ", Color::Cyan) };
+                ListItem::new(Line::from(vec![
+                    Span::styled(mark, Style::default().fg(Color::Green)),
+                    Span::styled(kind, Style::default().fg(color)),
+                    Span::styled(format!("{:>10}  ", human_size(entry.size)), Style::default().fg(Color::Gray)),
+                    Span::styled(entry.path.as_str(), Style::default().fg(Color::White)),
+                ]))
+            })
+            .collect(),
+    };
+    let ignored = app.clean_entries.iter().filter(|e| e.ignored).count();
+    let marked: u64 = app.clean_entries.iter().filter(|e| app.clean_marked.contains(&e.path)).map(|e| e.size).sum();
+    let title = format!(
+        "clean | {} untracked, {} ignored | {} marked, {}",
+        app.clean_entries.len() - ignored,
+        ignored,
+        app.clean_marked.len(),
+        human_size(marked)
+    );
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(title).border_style(Style::default().fg(Color::Cyan)))
+        .highlight_style(Style::default().bg(Color::DarkGray));
+    let mut state = ListState::default();
+    state.select((!app.clean_entries.is_empty()).then_some(app.selected_clean));
+    f.render_stateful_widget(list, chunks[0], &mut state);
+
+    let bottom = match &app.clean_preview {
+        Some(paths) => Paragraph::new(paths.iter().map(|p| Line::from(Span::styled(format!("  {}", p), Style::default().fg(Color::White)))).collect::<Vec<_>>())
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!("git clean would remove {} path(s), there's no undo", paths.len()))
+                    .border_style(Style::default().fg(Color::Red)),
+            ),
+        None => Paragraph::new("nothing is deleted without a dry run first: enter shows what git clean -n says, y then deletes it.")
+            .block(Block::default().borders(Borders::ALL))
+            .style(Style::default().fg(Color::Gray))
+            .wrap(Wrap { trim: true }),
+    };
+    f.render_widget(bottom, chunks[1]);
+}
+
+fn draw_help_view(f: &mut Frame, area: Rect, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(14), Constraint::Min(20)])
+        .split(area);
+
+    let topics: Vec<ListItem> = crate::help::PAGES.iter().map(|p| ListItem::new(p.topic)).collect();
+    let list = List::new(topics)
+        .block(Block::default().borders(Borders::ALL).title("help"))
+        .highlight_style(Style::default().bg(Color::DarkGray).fg(Color::White));
+    let mut state = ListState::default();
+    state.select(Some(app.help_page));
+    f.render_stateful_widget(list, chunks[0], &mut state);
+
+    let query = app.help_query.to_lowercase();
+    let page = &crate::help::PAGES[app.help_page];
+    let lines: Vec<Line> = page.text.lines().map(|line| help_line(line, &query)).collect();
+    let title = match app.help_searching {
+        true => format!("/{}", app.help_query),
+        false => page.topic.to_string(),
+    };
+    let text = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(title).border_style(Style::default().fg(Color::Cyan)))
+        .wrap(Wrap { trim: false })
+        .scroll((app.help_scroll, 0));
+    f.render_widget(text, chunks[1]);
+}
+
+// just enough markdown: headings, bullets and `code`, lines with the search in them stand out
+fn help_line<'a>(line: &'a str, query: &str) -> Line<'a> {
+    let base = if !query.is_empty() && line.to_lowercase().contains(query) {
+        Style::default().bg(Color::DarkGray)
+    } else {
+        Style::default()
+    };
+    if let Some(heading) = line.strip_prefix("# ") {
+        return Line::from(Span::styled(heading, base.fg(Color::Cyan).add_modifier(Modifier::BOLD)));
+    }
+    if let Some(heading) = line.strip_prefix("## ") {
+        return Line::from(Span::styled(heading, base.fg(Color::Yellow).add_modifier(Modifier::BOLD)));
+    }
+    let (mut spans, rest) = match line.strip_prefix("- ") {
+        Some(rest) => (vec![Span::styled("• ", base.fg(Color::Gray))], rest),
+        None => (Vec::new(), line),
+    };
+    // odd pieces are inside backticks
+    for (i, piece) in rest.split('`').enumerate() {
+        let style = if i % 2 == 1 { base.fg(Color::Yellow) } else { base.fg(Color::White) };
+        spans.push(Span::styled(piece, style));
+    }
+    Line::from(spans)
+}
+
+fn draw_release_notes_view(f: &mut Frame, area: Rect, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(3)])
+        .split(area);
+
+    let (label, text, editing) = match app.notes_input {
+        Some(NotesInput::Range) => ("range: ", format!("{}_", app.notes_buffer), true),
+        Some(NotesInput::File) => ("write to: ", format!("{}_", app.notes_buffer), true),
+        None => ("range: ", app.notes_range.clone(), false),
+    };
+    let input = Paragraph::new(Line::from(vec![
+        Span::styled(label, Style::default().fg(Color::Gray)),
+        Span::styled(text, Style::default().fg(if editing { Color::Yellow } else { Color::White })),
+    ]))
+    .block(Block::default().borders(Borders::ALL).title("release notes").border_style(Style::default().fg(Color::Cyan)));
+    f.render_widget(input, chunks[0]);
+
+    // one # less so the range and the groups come out as the help's headings
+    let lines: Vec<Line> = app.notes.lines().map(|line| help_line(line.strip_prefix('#').unwrap_or(line), "")).collect();
+    let preview = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title("preview (markdown)"))
+        .wrap(Wrap { trim: false })
+        .scroll((app.notes_scroll, 0));
+    f.render_widget(preview, chunks[1]);
+}
+
+fn draw_new_release_view(f: &mut Frame, area: Rect, app: &App) {
+    let Some(plan) = &app.release_plan else {
+        return;
+    };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(5), Constraint::Min(3)])
+        .split(area);
+
+    let since = match &plan.latest {
+        Some(tag) => format!("{} commit(s) since {}", plan.commits.len(), tag),
+        None => format!("{} commit(s), no release tagged yet", plan.commits.len()),
+    };
+    let mut bumps = vec![Span::styled("next:   ", Style::default().fg(Color::Gray))];
+    for bump in crate::release::BUMPS {
+        let style = match bump == plan.bump {
+            true => Style::default().fg(Color::Black).bg(Color::Cyan).add_modifier(Modifier::BOLD),
+            false => Style::default().fg(Color::White),
+        };
+        // the one the commits ask for
+        let suggested = if bump == plan.suggested { "*" } else { "" };
+        bumps.push(Span::styled(format!(" {} {}{} ", bump.label(), plan.tag(bump), suggested), style));
+        bumps.push(Span::raw(" "));
+    }
+    let push = match (app.release_push, app.remotes.get(app.release_remote)) {
+        (true, Some(remote)) => Span::styled(format!("yes, to {}", remote), Style::default().fg(Color::Yellow)),
+        (true, None) => Span::styled("no remote to push to", Style::default().fg(Color::Red)),
+        (false, _) => Span::styled("no, only the local tag", Style::default().fg(Color::White)),
+    };
+    let summary = Paragraph::new(vec![
+        Line::from(vec![Span::styled("since:  ", Style::default().fg(Color::Gray)), Span::raw(since)]),
+        Line::from(bumps),
+        Line::from(vec![Span::styled("push:   ", Style::default().fg(Color::Gray)), push]),
+    ])
+    .block(Block::default().borders(Borders::ALL).title("new release (* is what the commits suggest)").border_style(Style::default().fg(Color::Cyan)));
+    f.render_widget(summary, chunks[0]);
+
+    let notes = plan.notes(&app.repo);
+    let lines: Vec<Line> = notes.lines().map(|line| help_line(line.strip_prefix('#').unwrap_or(line), "")).collect();
+    let preview = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title("tag message"))
+        .wrap(Wrap { trim: false })
+        .scroll((app.release_scroll, 0));
+    f.render_widget(preview, chunks[1]);
+}
+
+fn draw_health_view(f: &mut Frame, area: Rect, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(3), Constraint::Length(4)])
+        .split(area);
+
+    let report = &app.health;
+    let (summary, color) = match report.issues.len() {
+        0 => ("no problems found.".to_string(), Color::Green),
+        n => (format!("{} thing(s) to look at.", n), Color::Yellow),
+    };
+    let mut spans = vec![Span::styled(summary, Style::default().fg(color).add_modifier(Modifier::BOLD))];
+    if report.dangling_objects > 0 {
+        spans.push(Span::styled(
+            format!(" {} dangling blobs and trees too, gc prunes those.", report.dangling_objects),
+            Style::default().fg(Color::Gray),
+        ));
+    }
+    let summary = Paragraph::new(Line::from(spans))
+        .block(Block::default().borders(Borders::ALL).title("health check").border_style(Style::default().fg(Color::Cyan)));
+    f.render_widget(summary, chunks[0]);
+
+    let items: Vec<ListItem> = report
+        .issues
+        .iter()
+        .map(|issue| {
+            let (tag, color, text) = match issue {
+                HealthIssue::Lock { path, modified } => {
+                    let path = path.strip_prefix(app.repo.git_dir()).unwrap_or(path).display();
+                    let text = match modified {
+                        Some(at) => format!("{} (last touched {})", path, relative_date(DateTime::<Utc>::from(*at).fixed_offset(), Utc::now())),
+                        None => path.to_string(),
+                    };
+                    ("lock", Color::Yellow, text)
+                }
+                HealthIssue::BrokenRef { name, problem } if name.is_empty() => ("broken ref", Color::Red, problem.clone()),
+                HealthIssue::BrokenRef { name, problem } => ("broken ref", Color::Red, format!("{}: {}", name, problem)),
+                HealthIssue::Dangling(commit) => (
+                    "dangling",
+                    Color::Cyan,
+                    format!("{} {} ({})", app.repo.short_id(&commit.id), commit.message.lines().next().unwrap_or(""), list_date(app, commit.timestamp)),
+                ),
+                HealthIssue::Fsck(line) => ("fsck", Color::Red, line.clone()),
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{:<11}", tag), Style::default().fg(color)),
+                Span::styled(text, Style::default().fg(Color::White)),
+            ]))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL))
+        .highlight_style(Style::default().bg(Color::DarkGray));
+    let mut state = ListState::default();
+    state.select((!report.issues.is_empty()).then_some(app.selected_health_issue));
+    f.render_stateful_widget(list, chunks[1], &mut state);
+
+    let fix = match report.issues.get(app.selected_health_issue) {
+        Some(HealthIssue::Lock { .. }) => "enter deletes it, unless a git process is running. it's how git commands keep each other out, so only stale ones should go.",
+        Some(HealthIssue::Dangling(_)) => "enter creates a branch recovered-<id> at it, so it's reachable again and gc won't take it.",
+        Some(HealthIssue::BrokenRef { name, .. }) if !name.is_empty() => "enter deletes the ref. what it pointed at is gone already.",
+        Some(_) => "nothing hydra can do about this one, `git fsck --full` has the details.",
+        None => "",
+    };
+    let fix = Paragraph::new(fix)
+        .block(Block::default().borders(Borders::ALL).title("fix"))
+        .style(Style::default().fg(Color::Gray))
+        .wrap(Wrap { trim: true });
+    f.render_widget(fix, chunks[2]);
+}
+
+fn draw_files_view(f: &mut Frame, area: Rect, app: &App) {
+    let Some(files) = &app.files else {
+        return;
+    };
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(35), Constraint::Percentage(65)])
+        .split(area);
+
+    let items: Vec<ListItem> = files
+        .entries
+        .iter()
+        .map(|entry| {
+            let (name, color) = match entry.kind {
+                TreeEntryKind::Dir => (format!("{}/", entry.name), Color::Cyan),
+                TreeEntryKind::Executable => (format!("{}*", entry.name), Color::Green),
+                TreeEntryKind::Link => (format!("{}@", entry.name), Color::Magenta),
+                TreeEntryKind::Submodule => (format!("{} (submodule)", entry.name), Color::Yellow),
+                TreeEntryKind::File => (entry.name.clone(), Color::White),
+            };
+            let size = entry.size.map(|s| human_size(s as u64)).unwrap_or_default();
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{:>9} ", size), Style::default().fg(Color::Gray)),
+                Span::styled(name, Style::default().fg(color)),
+            ]))
+        })
+        .collect();
+    let len = items.len();
+    let items = if items.is_empty() { vec![ListItem::new(Span::styled("empty tree.", Style::default().fg(Color::Gray)))] } else { items };
+    let title = format!("files of {}: /{}", files.label, files.dir);
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(title).border_style(Style::default().fg(Color::Cyan)))
+        .highlight_style(Style::default().fg(Color::Black).bg(Color::Cyan));
+    render_list(f, chunks[0], list, len, (len > 0).then_some(files.view.selected), &files.view.offset);
+
+    draw_file_preview(f, chunks[1], files);
+
+    if let Some(search) = &files.search {
+        let popup_area = centered_rect(70, 60, area);
+        f.render_widget(Clear, popup_area);
+        let parts = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(3), Constraint::Min(3)])
+            .split(popup_area);
+        let input = Paragraph::new(search.query.as_str())
+            .block(Block::default().borders(Borders::ALL).title(format!("find a file in {}", files.label)).border_style(Style::default().fg(Color::Cyan)))
+            .style(Style::default().fg(Color::White).bg(Color::Black));
+        f.render_widget(input, parts[0]);
+        f.set_cursor_position((parts[0].x + search.query.chars().count() as u16 + 1, parts[0].y + 1));
+        let items: Vec<ListItem> = search.matches.iter().map(|path| ListItem::new(path.as_str())).collect();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(format!("{} files", search.matches.len())))
+            .highlight_style(Style::default().fg(Color::Black).bg(Color::Cyan));
+        let selected = (!search.matches.is_empty()).then_some(search.view.selected);
+        render_list(f, parts[1], list, search.matches.len(), selected, &search.view.offset);
+    }
+
+    if let Some(name) = &files.export {
+        let popup_area = centered_rect(60, 15, area);
+        f.render_widget(Clear, popup_area);
+        let path = files.selected().map(|e| e.path.as_str()).unwrap_or("");
+        let input = Paragraph::new(name.as_str())
+            .block(Block::default().borders(Borders::ALL).title(format!("write {} as of {} to", path, files.label)).border_style(Style::default().fg(Color::Cyan)))
+            .style(Style::default().fg(Color::White).bg(Color::Black));
+        let input_area = Rect { height: 3.min(popup_area.height), ..popup_area };
+        f.render_widget(input, input_area);
+        f.set_cursor_position((input_area.x + name.chars().count() as u16 + 1, input_area.y + 1));
+    }
+}
+
+fn draw_file_preview(f: &mut Frame, area: Rect, files: &FileBrowser) {
+    let title = files.selected().map(|e| e.path.clone()).unwrap_or_default();
+    let block = Block::default().borders(Borders::ALL).title(title);
+    let note = |text: String| Paragraph::new(Span::styled(text, Style::default().fg(Color::Gray))).block(block.clone()).wrap(Wrap { trim: false });
+    let paragraph = match &files.preview {
+        Preview::Empty => note(String::new()),
+        Preview::Dir(count) => note(format!("directory, {} entries. enter opens it.", count)),
+        Preview::Binary(size) => note(format!("binary file, {}. x writes it out.", human_size(*size as u64))),
+        Preview::NotFetched => note("not fetched, it's only on the promisor remote of this partial clone.".to_string()),
+        Preview::Submodule => note("a submodule, its files are in its own repository.".to_string()),
+        Preview::Text(lines) => {
+            let height = area.height.saturating_sub(2) as usize;
+            let scroll = files.view.scroll.min(lines.len().saturating_sub(height));
+            let number_width = lines.len().to_string().len();
+            let shown: Vec<Line> = lines
+                .iter()
+                .enumerate()
+                .skip(scroll)
+                .take(height)
+                .map(|(i, runs)| {
+                    let mut spans = vec![Span::styled(format!("{:>width$} ", i + 1, width = number_width), Style::default().fg(Color::DarkGray))];
+                    spans.extend(runs.iter().map(|(kind, text)| {
+                        let style = match kind {
+                            Kind::Plain => Style::default().fg(Color::White),
+                            Kind::Keyword => Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD),
+                            Kind::String => Style::default().fg(Color::Green),
+                            Kind::Number => Style::default().fg(Color::Yellow),
+                            Kind::Comment => Style::default().fg(Color::DarkGray),
+                        };
+                        Span::styled(text.as_str(), style)
+                    }));
+                    Line::from(spans)
+                })
+                .collect();
+            let paragraph = Paragraph::new(shown).block(block);
+            f.render_widget(paragraph, area);
+            draw_scrollbar(f, area, lines.len(), scroll);
+            return;
+        }
+    };
+    f.render_widget(paragraph, area);
+}
+
+fn draw_stats_view(f: &mut Frame, area: Rect, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(12), Constraint::Min(3)])
+        .split(area);
+
+    let authors = stats::authors(&app.contributions);
+    let author = authors.get(app.stats_author.wrapping_sub(1)).map(|(name, _)| name.as_str());
+    let today = chrono::Local::now().date_naive();
+    let grid = stats::heatmap(&app.contributions, author, today, app.stats_weeks);
+    let max = grid.iter().flatten().copied().max().unwrap_or(0);
+    let total: usize = grid.iter().flatten().sum();
+
+    // two columns a week after the weekday labels, the most recent weeks when it doesn't all fit
+    let fits = (chunks[0].width.saturating_sub(2 + 4) / 2) as usize;
+    let shown = app.stats_weeks.min(fits);
+    let first = stats::first_day(today, app.stats_weeks);
+    let weeks: Vec<(NaiveDate, &[usize; 7])> = grid
+        .iter()
+        .enumerate()
+        .skip(app.stats_weeks - shown)
+        .map(|(i, counts)| (first + chrono::Duration::weeks(i as i64), counts))
+        .collect();
+
+    // month names over the week they start in, when there's room before the next one
+    let mut months = vec![' '; 4 + 2 * weeks.len()];
+    let mut free_from = 0;
+    for (i, (start, _)) in weeks.iter().enumerate() {
+        let starts_month = i == 0 || weeks[i - 1].0.month() != start.month();
+        let at = 4 + 2 * i;
+        if starts_month && at >= free_from && at + 3 <= months.len() {
+            for (j, c) in start.format("%b").to_string().chars().enumerate() {
+                months[at + j] = c;
+            }
+            free_from = at + 4;
+        }
+    }
+    let months: String = months.into_iter().collect();
+    let mut lines = vec![Line::from(Span::styled(months, Style::default().fg(Color::Gray)))];
+    for (day, label) in ["", "Mon", "", "Wed", "", "Fri", ""].iter().enumerate() {
+        let mut spans = vec![Span::styled(format!("{:<4}", label), Style::default().fg(Color::Gray))];
+        for (start, counts) in &weeks {
+            if *start + chrono::Duration::days(day as i64) > today {
+                spans.push(Span::raw("  "));
+                continue;
+            }
+            let (cell, color) = match stats::level(counts[day], max) {
+                0 => ("· ", Color::DarkGray),
+                1 => ("░░", Color::Green),
+                2 => ("▒▒", Color::Green),
+                3 => ("▓▓", Color::Green),
+                _ => ("██", Color::Green),
+            };
+            spans.push(Span::styled(cell, Style::default().fg(color)));
+        }
+        lines.push(Line::from(spans));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(vec![
+        Span::styled(format!("{} commit(s) ", total), Style::default().fg(Color::White).add_modifier(Modifier::BOLD)),
+        Span::styled(format!("in the last {} weeks, busiest day {}", app.stats_weeks, max), Style::default().fg(Color::Gray)),
+    ]));
+
+    let title = format!("contributions: {} (←/→ to change)", author.unwrap_or("everyone"));
+    let heatmap = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title).border_style(Style::default().fg(Color::Cyan)));
+    f.render_widget(heatmap, chunks[0]);
+
+    let widest = authors.first().map_or(1, |(_, count)| *count).max(1);
+    let bar_room = chunks[1].width.saturating_sub(40) as usize;
+    let items: Vec<ListItem> = authors
+        .iter()
+        .enumerate()
+        .map(|(i, (name, count))| {
+            let style = if i + 1 == app.stats_author {
+                Style::default().fg(Color::Black).bg(Color::Cyan)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{:<28}", name), style),
+                Span::styled(format!("{:>6} ", count), Style::default().fg(Color::Gray)),
+                Span::styled("■".repeat((count * bar_room).div_ceil(widest)), Style::default().fg(key_color(name))),
+            ]))
+        })
+        .collect();
+    let list = List::new(items).block(Block::default().borders(Borders::ALL).title("authors"));
+    f.render_widget(list, chunks[1]);
+}
+
+fn draw_commit_view(f: &mut Frame, area: Rect, app: &App) {
+    let Some(diff) = &app.commit_view else {
+        return;
+    };
+    let mut head = match &app.commit_range {
+        Some((from, to)) => range_head(app, from, to),
+        None => match app.commits.get(app.views.log.selected) {
+            Some(commit) => commit_head(app, commit),
+            None => return,
+        },
+    };
+
+    let (mut additions, mut deletions) = (0, 0);
+    for file in &diff.files {
+        additions += file.additions;
+        deletions += file.deletions;
+        let mut line = vec![
+            Span::styled(format!(" {} ", file.status), Style::default().fg(Color::Yellow)),
+            Span::raw(format!("{} ", file.path)),
+        ];
+        if diff.missing.contains(&file.path) {
+            line.push(Span::styled("not fetched", Style::default().fg(Color::DarkGray)));
+        } else {
+            line.extend([
+                Span::styled(format!("+{}", file.additions), Style::default().fg(Color::Green)),
+                Span::raw(" "),
+                Span::styled(format!("-{}", file.deletions), Style::default().fg(Color::Red)),
+            ]);
+        }
+        head.push(Line::from(line));
+    }
+    head.push(Line::from(Span::styled(
+        format!(" {} file(s) changed, +{} -{}", diff.files.len(), additions, deletions),
+        Style::default().fg(Color::Gray),
+    )));
+    head.push(Line::from(""));
+
+    // header and message up top, then the patch, of which only what's on screen gets styled
+    app.commit_view_head.set(head.len());
+    let height = area.height.saturating_sub(2) as usize;
+    let total = head.len() + diff.lines.len() + diff.truncated as usize;
+    let scroll = app.commit_view_scroll.min(total.saturating_sub(height));
+    let mut lines: Vec<Line> = head.into_iter().skip(scroll).take(height).collect();
+    let patch_start = scroll.saturating_sub(total - diff.lines.len() - diff.truncated as usize);
+    lines.extend(diff.lines.iter().skip(patch_start).take(height - lines.len()).map(|line| styled_diff_line(line)));
+    if diff.truncated && lines.len() < height {
+        lines.push(Line::from(Span::styled("… diff cut off here, it's too long to show in full", Style::default().fg(Color::Yellow))));
+    }
+
+    let name = match &app.commit_range {
+        Some((from, to)) => format!("{}..{}", app.repo.short_id(from), app.repo.short_id(to)),
+        None => app.commits.get(app.views.log.selected).map_or(String::new(), |c| format!("commit {}", app.repo.short_id(&c.id))),
+    };
+    let title = format!("{} ({}/{})", name, (scroll + height).min(total), total);
+    let view = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title(title).border_style(Style::default().fg(Color::Cyan)));
+    f.render_widget(view, area);
+}
+
+// who, when and the message above the file list
+fn commit_head<'a>(app: &App, commit: &'a Commit) -> Vec<Line<'a>> {
+    let mut head = vec![
+        Line::from(vec![
+            Span::styled("commit ", Style::default().fg(Color::Gray)),
+            Span::styled(commit.id.clone(), Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+        ]),
+        Line::from(vec![
+            Span::styled("author ", Style::default().fg(Color::Gray)),
+            Span::styled(format!("{} <{}>", commit.author, commit.author_email), Style::default().fg(author_color(commit))),
+        ]),
+        Line::from(vec![
+            Span::styled("date   ", Style::default().fg(Color::Gray)),
+            Span::raw(full_date(app, commit.timestamp)),
+        ]),
+    ];
+    if !commit.parents.is_empty() {
+        let parents: Vec<String> = commit.parents.iter().map(|p| app.repo.short_id(p)).collect();
+        head.push(Line::from(vec![
+            Span::styled("parents ", Style::default().fg(Color::Gray)),
+            Span::styled(parents.join(" "), Style::default().fg(Color::Cyan)),
+        ]));
+    }
+    head.push(Line::from(""));
+    for line in commit.message.lines() {
+        head.push(Line::from(issue_spans(&format!("    {}", line), &app.issue_rules)));
+    }
+    head.push(Line::from(""));
+    head
+}
+
+// the two ends and the commits the range holds, newest first like the log
+fn range_head<'a>(app: &App, from: &str, to: &str) -> Vec<Line<'a>> {
+    let subject = |id: &str| app.commits.iter().find(|c| c.id == id).map_or(String::new(), |c| c.message.lines().next().unwrap_or("").to_string());
+    let mut head = vec![
+        Line::from(vec![
+            Span::styled("from ", Style::default().fg(Color::Gray)),
+            Span::styled(app.repo.short_id(from), Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+            Span::raw(format!(" {}", subject(from))),
+        ]),
+        Line::from(vec![
+            Span::styled("to   ", Style::default().fg(Color::Gray)),
+            Span::styled(app.repo.short_id(to), Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+            Span::raw(format!(" {}", subject(to))),
+        ]),
+        Line::from(""),
+    ];
+    // only what's between them in the loaded log, a range across branches shows just the ends
+    let start = app.commits.iter().position(|c| c.id == to);
+    let end = app.commits.iter().position(|c| c.id == from);
+    if let (Some(start), Some(end)) = (start, end) {
+        for commit in &app.commits[start..end] {
+            head.push(Line::from(vec![
+                Span::styled(format!("    {} ", app.repo.short_id(&commit.id)), Style::default().fg(Color::Cyan)),
+                Span::raw(commit.message.lines().next().unwrap_or("").to_string()),
+            ]));
+        }
+        head.push(Line::from(""));
+    }
+    head
+}
+
+// "3 hours ago" in the lists, unless hydra.absoluteDates asks for hydra.dateFormat there too
+fn list_date(app: &App, at: DateTime<FixedOffset>) -> String {
+    if app.absolute_dates {
+        at.format(&app.date_format).to_string()
+    } else {
+        relative_date(at, Utc::now())
+    }
+}
+
+// details always get the date itself, in the timezone it was made in
+fn full_date(app: &App, at: DateTime<FixedOffset>) -> String {
+    format!("{} ({})", at.format(&app.date_format), relative_date(at, Utc::now()))
+}
+
+pub fn relative_date(at: DateTime<FixedOffset>, now: DateTime<Utc>) -> String {
+    let seconds = now.signed_duration_since(at).num_seconds();
+    // clocks disagree, or the commit was made with a date in the future
+    if seconds < 0 {
+        return at.format("%Y-%m-%d").to_string();
+    }
+    let (n, unit) = match seconds {
+        0..60 => return "just now".to_string(),
+        60..3600 => (seconds / 60, "minute"),
+        3600..86400 => (seconds / 3600, "hour"),
+        86400..1209600 => (seconds / 86400, "day"),
+        1209600..5184000 => (seconds / 604800, "week"),
+        5184000..31536000 => (seconds / 2592000, "month"),
+        _ => (seconds / 31536000, "year"),
+    };
+    format!("{} {}{} ago", n, unit, if n == 1 { "" } else { "s" })
+}
+
+// same author, same color, keyed on email so name spellings agree
+fn author_color(commit: &crate::git::Commit) -> Color {
+    key_color(if commit.author_email.is_empty() { &commit.author } else { &commit.author_email })
+}
+
+fn key_color(key: &str) -> Color {
+    const COLORS: [Color; 12] = [
+        Color::Red, Color::Green, Color::Yellow, Color::Blue, Color::Magenta, Color::Cyan,
+        Color::LightRed, Color::LightGreen, Color::LightYellow, Color::LightBlue, Color::LightMagenta, Color::LightCyan,
+    ];
+    // fnv-1a, stable between runs unlike the std hasher
+    let hash = key.to_lowercase().bytes().fold(0xcbf29ce484222325u64, |h, b| (h ^ b as u64).wrapping_mul(0x100000001b3));
+    COLORS[(hash % COLORS.len() as u64) as usize]
+}
+
+// "Ada Lovelace" -> AL, "ada" -> AD
+fn initials(name: &str) -> String {
+    let words: Vec<&str> = name.split_whitespace().filter(|w| w.chars().next().is_some_and(|c| c.is_alphanumeric())).collect();
+    let letters: String = match words.as_slice() {
+        [] => "?".to_string(),
+        [one] => one.chars().take(2).collect(),
+        [first, .., last] => first.chars().take(1).chain(last.chars().take(1)).collect(),
+    };
+    letters.to_uppercase()
+}
+
+// a message line with its issue references picked out
+fn issue_spans(line: &str, rules: &[IssueRule]) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut pos = 0;
+    for issue in find_refs(line, rules) {
+        spans.push(Span::raw(line[pos..issue.start].to_string()));
+        spans.push(Span::styled(issue.text, Style::default().fg(Color::Blue).add_modifier(Modifier::UNDERLINED)));
+        pos = issue.end;
+    }
+    spans.push(Span::raw(line[pos..].to_string()));
+    spans
+}
+
+fn draw_branches_view(f: &mut Frame, area: Rect, app: &App) {
+    let items: Vec<ListItem> = app
+        .branches
+        .iter()
+        .enumerate()
+        .map(|(i, branch)| {
+            let is_selected = i == app.views.branches.selected;
+            let is_head = app.detached_head.is_none() && *branch == app.current_branch;
+            let base_style = if is_head {
+                Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+            } else if branch.starts_with("origin/") {
+                Style::default().fg(Color::Cyan)
+            } else {
+                Style::default().fg(Color::Green)
+            };
+
+            let style = if is_selected {
+                base_style.bg(Color::DarkGray)
+            } else {
+                base_style
+            };
+
+            let name = if branch.starts_with("origin/") {
+                format!("remote: {}", branch)
+            } else {
+                format!("local: {}", branch)
+            };
+
+            let marker = if app.marked_branches.contains(branch) { "● " } else { "  " };
+            let head = if is_head { "* " } else { "  " };
+            let mut spans = vec![
+                Span::styled(marker, Style::default().fg(Color::Yellow)),
+                Span::styled(head, Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+                Span::styled(name, style),
+            ];
+            // remote ones are protected by the name they have there
+            let plain = match app.repo.local_branch_exists(branch) {
+                true => branch.as_str(),
+                false => branch.split_once('/').map_or(branch.as_str(), |(_, name)| name),
+            };
+            if app.protection.is_protected(plain) {
+                spans.push(Span::styled("  protected", Style::default().fg(Color::Magenta)));
+            }
+            if let Some(upstream) = app.branch_upstreams.get(branch) {
+                let color = if upstream.gone { Color::Red } else { Color::Gray };
+                spans.push(Span::styled(format!("  → {} ", upstream.name), Style::default().fg(Color::Gray)));
+                spans.push(Span::styled(format!("[{}]", upstream.summary()), Style::default().fg(color)));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+
+    let title = "branches (enter: checkout | n: new | t: upstream | space: mark | P: push | f: gitflow | T: stacks)";
+    if let (Some(branch), true) = (&app.unborn_branch, app.branches.is_empty()) {
+        let empty = Paragraph::new(vec![
+            Line::from("no branches yet."),
+            Line::from(""),
+            Line::from(format!("the first commit creates {}, branches can be made from there.", branch)),
+        ])
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .style(Style::default().fg(Color::Gray))
+        .wrap(Wrap { trim: false });
+        f.render_widget(empty, area);
+        return;
+    }
+
+    let area = match &app.remote_listing {
+        Some((remote, listed)) => {
+            let rows = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .split(area);
+            draw_remote_listing(f, rows[1], app, remote, listed);
+            rows[0]
+        }
+        None => area,
+    };
+
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .style(Style::default().fg(Color::White));
+
+    render_list(f, area, list, app.branches.len(), Some(app.views.branches.selected), &app.views.branches.offset);
+}
+
+fn draw_remote_listing(f: &mut Frame, area: Rect, app: &App, remote: &str, listed: &[crate::git::ListedBranch]) {
+    let items: Vec<ListItem> = listed
+        .iter()
+        .enumerate()
+        .map(|(i, branch)| {
+            let style = if app.listing_focused && i == app.selected_listed_branch {
+                Style::default().bg(Color::DarkGray).fg(Color::White)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            let color = match branch.fetched {
+                None => Color::Yellow,
+                Some(oid) if oid == branch.oid => Color::Green,
+                Some(_) => Color::Cyan,
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{} ", app.repo.short_id(branch.oid)), Style::default().fg(Color::Gray)),
+                Span::styled(branch.name.as_str(), style),
+                Span::styled(format!("  {}", branch.state()), Style::default().fg(color)),
+            ]))
+        })
+        .collect();
+
+    let border = if app.listing_focused { Color::Cyan } else { Color::White };
+    let title = format!("on {} now, {} branches (enter: fetch just this | tab: switch list | L: next remote | esc: close)", remote, listed.len());
+    let list = List::new(items).block(Block::default().borders(Borders::ALL).title(title).border_style(Style::default().fg(border)));
+    let mut state = ListState::default();
+    state.select(app.listing_focused.then_some(app.selected_listed_branch));
+    f.render_stateful_widget(list, area, &mut state);
+}
+
+fn draw_commit_dialog(f: &mut Frame, area: Rect, app: &App) {
+    let popup_area = centered_rect(60, 20, area);
+
+    f.render_widget(Clear, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(3), Constraint::Length(3)])
+        .split(popup_area);
+
+
+    let title = Paragraph::new("commit message")
+        .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(Color::Cyan)))
+        .style(Style::default().fg(Color::White));
+    f.render_widget(title, chunks[0]);
+
+    let message = Paragraph::new(app.commit_message.as_str())
+        .block(Block::default().borders(Borders::ALL))
+        .style(Style::default().fg(Color::White).bg(Color::Black))
+        .wrap(Wrap {trim: false});
+
+    f.render_widget(message, chunks[1]);
+    // test
+    let help = Paragraph::new("enter: commit | ↑/↓: previous messages | esc: cancel")
+        .block(Block::default().borders(Borders::ALL))
+        .style(Style::default().fg(Color::Gray));
+
+    f.render_widget(help, chunks[2]);
+
+    f.set_cursor_position(( // i am so cba to use the new one i am sorry
+        chunks[1].x + app.commit_message.len() as u16 + 1,
+        chunks[1].y + 1, // i do not know how make this wrap yet
+    ));
+
+}
+
+fn draw_merge_message_dialog(f: &mut Frame, area: Rect, app: &App) {
+    let popup_area = centered_rect(70, 50, area);
+
+    f.render_widget(Clear, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(3), Constraint::Length(3)])
+        .split(popup_area);
+
+    let title = Paragraph::new(format!("{} commit message", app.conflict_label()))
+        .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(Color::Red)))
+        .style(Style::default().fg(Color::White));
+    f.render_widget(title, chunks[0]);
+
+    // lines starting with '#' are stripped when committing, same as git
+    let lines: Vec<Line> = app.merge_message
+        .lines()
+        .map(|line| {
+            if line.starts_with('#') {
+                Line::from(Span::styled(line, Style::default().fg(Color::DarkGray)))
+            } else {
+                Line::from(Span::styled(line, Style::default().fg(Color::White)))
+            }
+        })
+        .collect();
+
+    let message = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title("from MERGE_MSG"))
+        .style(Style::default().bg(Color::Black))
+        .wrap(Wrap { trim: false });
+    f.render_widget(message, chunks[1]);
+
+    let help = Paragraph::new(format!("ctrl-s/alt-enter: complete {} | enter: new line | esc: back to conflicts | '#' lines are ignored", app.conflict_label()))
+        .block(Block::default().borders(Borders::ALL))
+        .style(Style::default().fg(Color::Gray));
+    f.render_widget(help, chunks[2]);
+}
+
+fn draw_error_popup(f: &mut Frame, area: Rect, error: &str, scroll: u16, index_locked: bool) {
+    // grow with the message instead of cutting it off, up to most of the screen
+    let width = (area.width * 6 / 10).max(20);
+    let wrapped_lines = error.lines()
+        .map(|l| (l.len() as u16 / width.saturating_sub(2).max(1)) + 1)
+        .sum::<u16>();
+    let height = (wrapped_lines + 2).clamp(5, area.height * 8 / 10);
+    let popup_area = Rect {
+        x: area.x + (area.width.saturating_sub(width)) / 2,
+        y: area.y + (area.height.saturating_sub(height)) / 2,
+        width,
+        height,
+    };
+
+    f.render_widget(Clear, popup_area);
+
+    let title = if index_locked {
+        "error | esc: dismiss | L: remove stale lock | ↑/↓: scroll | E: error log"
+    } else {
+        "error | esc: dismiss | ↑/↓: scroll | E: error log"
+    };
+    let error_widget = Paragraph::new(error)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(Style::default().fg(Color::Red))
+        )
+        .style(Style::default().fg(Color::Red))
+        .wrap(Wrap { trim: true })
+        .scroll((scroll, 0));
+
+    f.render_widget(error_widget, popup_area);
+}
+
+fn draw_hooks_view(f: &mut Frame, area: Rect, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(35), Constraint::Percentage(65)])
+        .split(area);
+
+    let (dir, configured) = app.repo.hooks_dir();
+    let title = if configured {
+        format!("hooks in {} (core.hooksPath)", dir.display())
+    } else {
+        format!("hooks in {}", dir.display())
+    };
+    let items: Vec<ListItem> = if app.hooks.is_empty() {
+        vec![ListItem::new("no hooks here.").style(Style::default().fg(Color::Gray))]
+    } else {
+        app.hooks
+            .iter()
+            .map(|hook| {
+                let color = match hook.state() {
+                    "active" => Color::Green,
+                    "not executable" => Color::Yellow,
+                    _ => Color::Gray,
+                };
+                ListItem::new(Line::from(vec![
+                    Span::styled(format!("{:<24}", hook.name), Style::default().fg(Color::White)),
+                    Span::styled(hook.state(), Style::default().fg(color)),
+                ]))
+            })
+            .collect()
+    };
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .highlight_style(Style::default().bg(Color::DarkGray));
+    let mut state = ListState::default();
+    state.select(Some(app.selected_hook));
+    f.render_stateful_widget(list, chunks[0], &mut state);
+
+    // the last test run, otherwise the script itself
+    let (title, text) = match (&app.hook_output, app.hooks.get(app.selected_hook)) {
+        (Some(output), _) => ("test run".to_string(), output.clone()),
+        (None, Some(hook)) => (hook.path.display().to_string(), std::fs::read_to_string(&hook.path).unwrap_or_else(|e| format!("cannot read it: {}", e))),
+        (None, None) => ("hook".to_string(), String::new()),
+    };
+    let preview = Paragraph::new(text)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .style(Style::default().fg(Color::White));
+    f.render_widget(preview, chunks[1]);
+}
+
+// output of a hydra.panelCommand, following the end unless scrolled up
+fn draw_command_output(f: &mut Frame, area: Rect, app: &App) {
+    let Some(running) = &app.running_command else {
+        return;
+    };
+    let popup_area = centered_rect(85, 80, area);
+    f.render_widget(Clear, popup_area);
+
+    let height = popup_area.height.saturating_sub(2) as usize;
+    let output = &running.output;
+    let end = output.len().saturating_sub(app.command_output_scroll.min(output.len().saturating_sub(height)));
+    let lines: Vec<Line> = output[end.saturating_sub(height)..end].iter().map(|l| Line::from(l.as_str())).collect();
+
+    let (state, color) = match running.status {
+        None => ("running".to_string(), Color::Yellow),
+        Some(status) if status.success() => ("done".to_string(), Color::Green),
+        Some(status) => (format!("failed, {}", status), Color::Red),
+    };
+    let title = format!("{} ({}) | $ {} | ↑/↓: scroll | k: stop | esc: hide", running.name, state, running.line);
+    let panel = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(title).border_style(Style::default().fg(color)))
+        .style(Style::default().fg(Color::White));
+    f.render_widget(panel, popup_area);
+}
+
+// the tail of the log file for this session, newest at the bottom
+fn draw_log_viewer(f: &mut Frame, area: Rect, app: &App) {
+    let popup_area = centered_rect(90, 80, area);
+    f.render_widget(Clear, popup_area);
+
+    let recent = logging::recent();
+    let height = popup_area.height.saturating_sub(2) as usize;
+    let end = recent.len().saturating_sub(app.log_viewer_scroll.min(recent.len().saturating_sub(height)));
+    let lines: Vec<Line> = recent[end.saturating_sub(height)..end]
+        .iter()
+        .map(|line| {
+            let color = if line.contains(" ERROR ") {
+                Color::Red
+            } else if line.contains(" WARN ") {
+                Color::Yellow
+            } else if line.contains(" DEBUG ") {
+                Color::Gray
+            } else {
+                Color::White
+            };
+            Line::from(Span::styled(line.as_str(), Style::default().fg(color)))
+        })
+        .collect();
+
+    let title = match logging::path() {
+        Some(path) => format!("log ({}) | ↑/↓: scroll | y: copy | tab: errors | esc: close", path.display()),
+        None => "log (not written to a file) | ↑/↓: scroll | y: copy | tab: errors | esc: close".to_string(),
+    };
+    let viewer = Paragraph::new(if lines.is_empty() { vec![Line::from(Span::styled("nothing logged yet.", Style::default().fg(Color::Gray)))] } else { lines })
+        .block(Block::default().borders(Borders::ALL).title(title).border_style(Style::default().fg(Color::Cyan)));
+    f.render_widget(viewer, popup_area);
+}
+
+fn draw_error_log(f: &mut Frame, area: Rect, app: &App) {
+    let popup_area = centered_rect(80, 70, area);
+
+    f.render_widget(Clear, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+        .split(popup_area);
+
+    let items: Vec<ListItem> = if app.error_log.is_empty() {
+        vec![ListItem::new("no errors this session.").style(Style::default().fg(Color::Gray))]
+    } else {
+        app.error_log
+            .iter()
+            .enumerate()
+            .map(|(i, (at, error))| {
+                let style = if i == app.selected_error {
+                    Style::default().bg(Color::DarkGray).fg(Color::White)
+                } else {
+                    Style::default().fg(Color::Red)
+                };
+                ListItem::new(Line::from(vec![
+                    Span::styled(at.format("%H:%M:%S ").to_string(), Style::default().fg(Color::Gray)),
+                    Span::styled(error.lines().next().unwrap_or(""), style),
+                ]))
+            })
+            .collect()
+    };
+
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title("error log | ↑/↓: select | y: copy | tab: debug log | esc: close").border_style(Style::default().fg(Color::Red)));
+
+    let mut state = ListState::default();
+    state.select(Some(app.selected_error));
+    f.render_stateful_widget(list, chunks[0], &mut state);
+
+    let detail = app.error_log.get(app.selected_error).map(|(_, e)| e.as_str()).unwrap_or("");
+    let details = Paragraph::new(detail)
+        .block(Block::default().borders(Borders::ALL).title("details | pgup/pgdn: scroll"))
+        .style(Style::default().fg(Color::White))
+        .wrap(Wrap { trim: false })
+        .scroll((app.error_scroll, 0));
+
+    f.render_widget(details, chunks[1]);
+}
+
+fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage(100 - percent_y / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage(100 - percent_y / 2),
+        ])
+        .split(r);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage(100 - percent_x / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage(100 - percent_x / 2),
+        ])
+        .split(popup_layout[1])[1]
+}
+
+fn draw_stash_view(f: &mut Frame, area: Rect, app: &App) {
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(65), Constraint::Percentage(35)])
+        .split(area);
+
+    let branch_width = app.stashes.iter()
+        .map(|s| s.branch.as_deref().unwrap_or("").chars().count())
+        .max()
+        .unwrap_or(0)
+        .min(24);
+
+    let items: Vec<ListItem> = app
+        .stashes
+        .iter()
+        .enumerate()
+        .map(|(i, stash)| {
+            let style = if i == app.views.stashes.selected {
+                Style::default().bg(Color::DarkGray).fg(Color::White)
+            } else {
+                Style::default().fg(Color::White)
+            };
+
+            let branch: String = stash.branch.as_deref().unwrap_or("").chars().take(branch_width).collect();
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("stash@{{{}}} ", stash.index), Style::default().fg(Color::Cyan)),
+                Span::styled(format!("{:<width$} ", branch, width = branch_width), Style::default().fg(Color::Green)),
+                Span::styled(format!("{} ", list_date(app, stash.time)), Style::default().fg(Color::Gray)),
+                Span::styled(stash.message.as_str(), style),
+            ]))        
+        })  
+        .collect();
+
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title("stashes (enter: pop | del: drop | esc: back)"))
+        .style(Style::default().fg(Color::White));
+
+    render_list(f, chunks[0], list, app.stashes.len(), Some(app.views.stashes.selected), &app.views.stashes.offset);
+
+    draw_stash_files(f, chunks[1], app);
+}
+
+fn draw_stash_files(f: &mut Frame, area: Rect, app: &App) {
+    let additions: usize = app.stash_files.iter().map(|s| s.additions).sum();
+    let deletions: usize = app.stash_files.iter().map(|s| s.deletions).sum();
+
+    let items: Vec<ListItem> = app
+        .stash_files
+        .iter()
+        .map(|file| {
+            let color = match file.status {
+                'A' => Color::Green,
+                'D' => Color::Red,
+                '?' => Color::Yellow,
+                _ => Color::White,
+            };
+            let mut spans = vec![
+                Span::styled(format!("{} ", file.status), Style::default().fg(color).add_modifier(Modifier::BOLD)),
+                Span::styled(format!("{} ", file.path), Style::default().fg(color)),
+                Span::styled(format!("+{}", file.additions), Style::default().fg(Color::Green)),
+                Span::styled(format!(" -{}", file.deletions), Style::default().fg(Color::Red)),
+            ];
+            if let Some(preview) = &app.stash_preview {
+                if preview.in_the_way.contains(&file.path) {
+                    spans.push(Span::styled(" in the way", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)));
+                }
+                if preview.conflicts.contains(&file.path) {
+                    spans.push(Span::styled(" conflicts", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)));
+                }
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+
+    let summary = match &app.stash_preview {
+        _ if app.stash_files.is_empty() => None,
+        None => Some(vec![Line::from(Span::styled("couldn't work out what a pop would do.", Style::default().fg(Color::Gray)))]),
+        Some(preview) => Some(stash_pop_summary(preview)),
+    };
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(summary.as_ref().map_or(0, |s| s.len() as u16 + 2))])
+        .split(area);
+
+    let title = match app.stash_files.len() {
+        0 => "files.".to_string(),
+        n => format!("{} file(s), +{} -{}", n, additions, deletions),
+    };
+    let list = List::new(items).block(Block::default().borders(Borders::ALL).title(title));
+    f.render_widget(list, chunks[0]);
+
+    if let Some(summary) = summary {
+        let paragraph = Paragraph::new(summary)
+            .block(Block::default().borders(Borders::ALL).title("if you pop it"))
+            .wrap(Wrap { trim: true });
+        f.render_widget(paragraph, chunks[1]);
+    }
+}
+
+fn stash_pop_summary(preview: &crate::git::StashPreview) -> Vec<Line<'static>> {
+    if preview.in_the_way.is_empty() && preview.conflicts.is_empty() {
+        return vec![Line::from(Span::styled("✓ applies cleanly.", Style::default().fg(Color::Green)))];
+    }
+    let mut lines = Vec::new();
+    if !preview.in_the_way.is_empty() {
+        lines.push(Line::from(Span::styled(
+            format!("{} file(s) have local changes, commit or stash them first.", preview.in_the_way.len()),
+            Style::default().fg(Color::Yellow),
+        )));
+    }
+    if !preview.conflicts.is_empty() {
+        lines.push(Line::from(Span::styled(
+            format!("{} file(s) also changed since the stash, expect conflicts.", preview.conflicts.len()),
+            Style::default().fg(Color::Red),
+        )));
+    }
+    lines
+}
+
+fn draw_create_branch_dialog(f: &mut Frame, area: Rect, app: &App) {
+    let field_rows = app.branch_fields.len() as u16;
+    let popup_area = centered_rect(50, 30, area);
+
+    f.render_widget(Clear, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Length(if field_rows > 0 { field_rows + 2 } else { 0 }),
+            Constraint::Length(3),
+            Constraint::Length(1),
+            Constraint::Length(3),
+        ])
+        .split(popup_area);
+
+    let title = match app.branch_template {
+        Some(i) => format!("create new branch from {}", app.branch_templates[i]),
+        None => "create new branch".to_string(),
+    };
+    let title = Paragraph::new(title)
+        .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(Color::Green)))
+        .style(Style::default().fg(Color::White));
+
+    f.render_widget(title, chunks[0]);
+
+    if field_rows > 0 {
+        let lines: Vec<Line> = app.branch_fields
+            .iter()
+            .enumerate()
+            .map(|(i, (placeholder, value))| {
+                let style = if i == app.branch_field {
+                    Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(Color::White)
+                };
+                Line::from(vec![
+                    Span::styled(format!("{}: ", placeholder), Style::default().fg(Color::Gray)),
+                    Span::styled(value.as_str(), style),
+                ])
+            })
+            .collect();
+        let fields = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title("fill in"));
+        f.render_widget(fields, chunks[1]);
+    }
+
+    let input = Paragraph::new(app.branch_name.as_str())
+        .block(Block::default().borders(Borders::ALL).title("branch name"))
+        .style(Style::default().fg(Color::White).bg(Color::Black));
+
+    f.render_widget(input, chunks[2]);
+
+    let validation = match app.repo.validate_branch_name(&app.branch_name) {
+        _ if app.branch_name.trim().is_empty() => Span::raw(""),
+        Some(problem) => Span::styled(format!(" ✗ {}", problem), Style::default().fg(Color::Red)),
+        None => Span::styled(" ✓ ok", Style::default().fg(Color::Green)),
+    };
+    f.render_widget(Paragraph::new(Line::from(validation)), chunks[3]);
+
+    let help = match (app.branch_template.is_some(), app.branch_templates.is_empty()) {
+        (true, _) => "enter: next field / create | ↑/↓: field | tab: next template | esc: cancel",
+        (false, false) => "enter: create | tab: use a template | ↑/↓: previous names | esc: cancel",
+        (false, true) => "enter: create | ↑/↓: previous names | esc: cancel",
+    };
+    let help = Paragraph::new(help)
+        .block(Block::default().borders(Borders::ALL))
+        .style(Style::default().fg(Color::Gray));
+
+    f.render_widget(help, chunks[4]);
+
+    match app.branch_fields.get(app.branch_field) {
+        Some((placeholder, value)) if app.branch_template.is_some() => f.set_cursor_position((
+            chunks[1].x + (placeholder.len() + 2 + value.len()) as u16 + 1,
+            chunks[1].y + 1 + app.branch_field as u16,
+        )),
+        _ => f.set_cursor_position((
+            chunks[2].x + app.branch_name.len() as u16 + 1,
+            chunks[2].y + 1,
+        )),
+    }
+}
+
+fn draw_archive_dialog(f: &mut Frame, area: Rect, app: &App) {
+    let popup_area = centered_rect(60, 15, area);
+
+    f.render_widget(Clear, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Length(3), Constraint::Length(3)])
+        .split(popup_area);
+
+    let target = if app.archive_target == "HEAD" {
+        "HEAD".to_string()
+    } else {
+        app.repo.short_id(&app.archive_target)
+    };
+    let title = Paragraph::new(format!("export tree of {}", target))
+        .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(Color::Cyan)))
+        .style(Style::default().fg(Color::White));
+
+    f.render_widget(title, chunks[0]);
+
+    let input = Paragraph::new(app.archive_path.as_str())
+        .block(Block::default().borders(Borders::ALL).title("output file (.tar or .zip)"))
+        .style(Style::default().fg(Color::White).bg(Color::Black));
+
+    f.render_widget(input, chunks[1]);
+
+    let help = Paragraph::new("enter: export | esc: cancel")
+        .block(Block::default().borders(Borders::ALL))
+        .style(Style::default().fg(Color::Gray));
+
+    f.render_widget(help, chunks[2]);
+
+    f.set_cursor_position((
+        chunks[1].x + app.archive_path.len() as u16 + 1,
+        chunks[1].y + 1,
+    ));
+}
+
+fn draw_scope_picker(f: &mut Frame, area: Rect, app: &App) {
+    let popup_area = centered_rect(50, 60, area);
+
+    f.render_widget(Clear, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(3)])
+        .split(popup_area);
+
+    let items: Vec<ListItem> = if app.scope_picker_entries.is_empty() {
+        vec![ListItem::new("(no subdirectories)").style(Style::default().fg(Color::Gray))]
+    } else {
+        app.scope_picker_entries
+            .iter()
+            .enumerate()
+            .map(|(i, dir)| {
+                let style = if i == app.selected_scope_entry {
+                    Style::default().bg(Color::DarkGray).fg(Color::White)
+                } else {
+                    Style::default().fg(Color::Cyan)
+                };
+                ListItem::new(format!("{}/", dir)).style(style)
+            })
+            .collect()
+    };
+
+    let title = format!("scope status to... /{}", app.scope_picker_dir);
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(title).border_style(Style::default().fg(Color::Cyan)));
+
+    f.render_widget(list, chunks[0]);
+
+    let help = Paragraph::new("enter: scope to selected | →/←: open/parent | .: scope here (root clears) | esc: cancel")
+        .block(Block::default().borders(Borders::ALL))
+        .style(Style::default().fg(Color::Gray))
+        .wrap(Wrap { trim: true });
+
+    f.render_widget(help, chunks[1]);
+}
+
+fn draw_diff_base_picker(f: &mut Frame, area: Rect, app: &App) {
+    let popup_area = centered_rect(60, 60, area);
+    f.render_widget(Clear, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(3), Constraint::Length(3)])
+        .split(popup_area);
+
+    let title = match app.repo.diff_base() {
+        Some(base) => format!("diff against (now {})", base),
+        None => "diff against (now the index / HEAD)".to_string(),
+    };
+    let input = Paragraph::new(app.diff_base_input.as_str())
+        .block(Block::default().borders(Borders::ALL).title(title).border_style(Style::default().fg(Color::Cyan)))
+        .style(Style::default().fg(Color::White).bg(Color::Black));
+    f.render_widget(input, chunks[0]);
+
+    let candidates = app.filtered_diff_bases();
+    let items: Vec<ListItem> = match candidates.is_empty() {
+        true => vec![ListItem::new("no ref matches, enter takes it as typed (a sha, HEAD~3)").style(Style::default().fg(Color::Gray))],
+        false => candidates
+            .iter()
+            .map(|(name, kind)| {
+                ListItem::new(Line::from(vec![
+                    Span::styled(name.as_str(), Style::default().fg(Color::White)),
+                    Span::styled(format!("  {}", kind), Style::default().fg(Color::Gray)),
+                ]))
+            })
+            .collect(),
+    };
+    let mut state = ListState::default();
+    state.select((!candidates.is_empty()).then_some(app.selected_diff_base));
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title("refs"))
+        .highlight_style(Style::default().bg(Color::DarkGray));
+    f.render_stateful_widget(list, chunks[1], &mut state);
+
+    let help = Paragraph::new("enter: diff against it | del: back to the index / HEAD | ↑/↓: select | esc: cancel")
+        .block(Block::default().borders(Borders::ALL))
+        .style(Style::default().fg(Color::Gray));
+    f.render_widget(help, chunks[2]);
+
+    f.set_cursor_position((chunks[0].x + app.diff_base_input.len() as u16 + 1, chunks[0].y + 1));
+}
+
+fn draw_package_picker(f: &mut Frame, area: Rect, app: &App) {
+    let popup_area = centered_rect(60, 60, area);
+    f.render_widget(Clear, popup_area);
+
+    let active = app.active_package.as_ref().map(|p| p.path.as_str());
+    let mut items = vec![ListItem::new(Line::from(vec![
+        Span::styled(if active.is_none() { "● " } else { "  " }, Style::default().fg(Color::Green)),
+        Span::styled("all packages", Style::default().fg(Color::White)),
+    ]))];
+    let width = app.packages.iter().map(|p| p.name.chars().count()).max().unwrap_or(0);
+    for package in &app.packages {
+        items.push(ListItem::new(Line::from(vec![
+            Span::styled(if active == Some(package.path.as_str()) { "● " } else { "  " }, Style::default().fg(Color::Green)),
+            Span::styled(format!("{:<width$} ", package.name, width = width), Style::default().fg(Color::Cyan)),
+            Span::styled(format!("{}/ ", package.path), Style::default().fg(Color::White)),
+            Span::styled(format!("({})", package.kind), Style::default().fg(Color::Gray)),
+        ])));
+    }
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title("packages (enter: limit status and log to it | esc: cancel)")
+                .border_style(Style::default().fg(Color::Cyan)),
+        )
+        .highlight_style(Style::default().bg(Color::DarkGray));
+    let mut state = ListState::default();
+    state.select(Some(app.selected_package));
+    f.render_stateful_widget(list, popup_area, &mut state);
+}
+
+fn draw_attributes_dialog(f: &mut Frame, area: Rect, app: &App) {
+    let popup_area = centered_rect(70, 60, area);
+
+    f.render_widget(Clear, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(3), Constraint::Length(3)])
+        .split(popup_area);
+
+    let items: Vec<ListItem> = app.attributes_lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| {
+            let style = if i == app.selected_attribute {
+                Style::default().bg(Color::DarkGray).fg(Color::White)
+            } else if line.trim_start().starts_with('#') {
+                Style::default().fg(Color::Gray)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            ListItem::new(line.as_str()).style(style)
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(".gitattributes").border_style(Style::default().fg(Color::Magenta)));
+
+    f.render_widget(list, chunks[0]);
+
+    let input = Paragraph::new(app.attributes_input.as_str())
+        .block(Block::default().borders(Borders::ALL).title("new pattern (e.g. *.png -diff, *.psd filter=lfs diff=lfs merge=lfs -text)"))
+        .style(Style::default().fg(Color::White).bg(Color::Black));
+
+    f.render_widget(input, chunks[1]);
+
+    let help = Paragraph::new("enter: add line | ↑/↓: select | del: remove selected | esc: close")
+        .block(Block::default().borders(Borders::ALL))
+        .style(Style::default().fg(Color::Gray));
+
+    f.render_widget(help, chunks[2]);
+
+    f.set_cursor_position((
+        chunks[1].x + app.attributes_input.len() as u16 + 1,
+        chunks[1].y + 1,
+    ));
+}
+
+fn draw_command_palette(f: &mut Frame, area: Rect, app: &App) {
+    let popup_area = centered_rect(60, 60, area);
+
+    f.render_widget(Clear, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(3)])
+        .split(popup_area);
+
+    let input = Paragraph::new(format!(":{}", app.palette_query))
+        .block(Block::default().borders(Borders::ALL).title("command palette").border_style(Style::default().fg(Color::Cyan)))
+        .style(Style::default().fg(Color::White).bg(Color::Black));
+
+    f.render_widget(input, chunks[0]);
+
+    let extras = app.palette_extras();
+    let matches = crate::palette::filter(&app.palette_query, &extras);
+    let items: Vec<ListItem> = matches
+        .iter()
+        .enumerate()
+        .map(|(i, cmd)| {
+            let style = if i == app.selected_palette_item {
+                Style::default().bg(Color::DarkGray).fg(Color::White)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            let mut spans = vec![Span::styled(cmd.name, style)];
+            if !cmd.key.is_empty() {
+                spans.push(Span::styled(format!("  {}", cmd.key), Style::default().fg(Color::Gray)));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title("enter: run | ↑/↓: select | esc: close"));
+
+    let mut state = ListState::default();
+    state.select(Some(app.selected_palette_item));
+    f.render_stateful_widget(list, chunks[1], &mut state);
+
+    f.set_cursor_position((
+        chunks[0].x + app.palette_query.len() as u16 + 2,
+        chunks[0].y + 1,
+    ));
+}
+
+fn draw_notification_history(f: &mut Frame, area: Rect, app: &App) {
+    let popup_area = centered_rect(70, 60, area);
+
+    f.render_widget(Clear, popup_area);
+
+    let items: Vec<ListItem> = if app.notifications.history().is_empty() {
+        vec![ListItem::new("nothing yet.").style(Style::default().fg(Color::Gray))]
+    } else {
+        app.notifications.history()
+            .iter()
+            .enumerate()
+            .map(|(i, n)| {
+                let color = match n.level {
+                    Level::Info => Color::Cyan,
+                    Level::Success => Color::Green,
+                    Level::Error => Color::Red,
+                };
+                let mut line = Line::from(vec![
+                    Span::styled(n.at.format("%H:%M:%S ").to_string(), Style::default().fg(Color::Gray)),
+                    Span::styled(n.message.as_str(), Style::default().fg(color)),
+                ]);
+                if i == app.notification_scroll {
+                    line = line.style(Style::default().bg(Color::DarkGray));
+                }
+                ListItem::new(line)
+            })
+            .collect()
+    };
+
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title("notifications (newest first) | ↑/↓: scroll | esc: close").border_style(Style::default().fg(Color::Cyan)));
+
+    let mut state = ListState::default();
+    state.select(Some(app.notification_scroll));
+    f.render_stateful_widget(list, popup_area, &mut state);
+}
+
+fn draw_switch_repo_dialog(f: &mut Frame, area: Rect, app: &App) {
+    let popup_area = centered_rect(60, 15, area);
+
+    f.render_widget(Clear, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Length(3), Constraint::Length(3)])
+        .split(popup_area);
+
+    let title = Paragraph::new("switch repository")
+        .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(Color::Cyan)))
+        .style(Style::default().fg(Color::White));
+
+    f.render_widget(title, chunks[0]);
+
+    let input = Paragraph::new(app.switch_repo_path.as_str())
+        .block(Block::default().borders(Borders::ALL).title("path"))
+        .style(Style::default().fg(Color::White).bg(Color::Black));
+
+    f.render_widget(input, chunks[1]);
+
+    let help = Paragraph::new("enter: open | esc: cancel")
+        .block(Block::default().borders(Borders::ALL))
+        .style(Style::default().fg(Color::Gray));
+
+    f.render_widget(help, chunks[2]);
+
+    f.set_cursor_position((
+        chunks[1].x + app.switch_repo_path.len() as u16 + 1,
+        chunks[1].y + 1,
+    ));
+}
+
+fn draw_jump_dialog(f: &mut Frame, area: Rect, app: &App) {
+    let popup_area = centered_rect(60, 15, area);
+
+    f.render_widget(Clear, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Length(3), Constraint::Length(3)])
+        .split(popup_area);
+
+    let title = Paragraph::new("jump to commit")
+        .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(Color::Cyan)))
+        .style(Style::default().fg(Color::White));
+
+    f.render_widget(title, chunks[0]);
+
+    let input = Paragraph::new(app.jump_input.as_str())
+        .block(Block::default().borders(Borders::ALL).title("hash, branch, tag or rev (e.g. main~3)"))
+        .style(Style::default().fg(Color::White).bg(Color::Black));
+
+    f.render_widget(input, chunks[1]);
+
+    let help = Paragraph::new("enter: jump | esc: cancel")
+        .block(Block::default().borders(Borders::ALL))
+        .style(Style::default().fg(Color::Gray));
+
+    f.render_widget(help, chunks[2]);
+
+    f.set_cursor_position((
+        chunks[1].x + app.jump_input.len() as u16 + 1,
+        chunks[1].y + 1,
+    ));
+}
+
+fn draw_pickaxe_dialog(f: &mut Frame, area: Rect, app: &App) {
+    let popup_area = centered_rect(60, 15, area);
+
+    f.render_widget(Clear, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Length(3), Constraint::Length(3)])
+        .split(popup_area);
+
+    let title = Paragraph::new("find commits that add or remove a string")
+        .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(Color::Cyan)))
+        .style(Style::default().fg(Color::White));
+
+    f.render_widget(title, chunks[0]);
+
+    let input = Paragraph::new(app.pickaxe_query.as_str())
+        .block(Block::default().borders(Borders::ALL).title("string"))
+        .style(Style::default().fg(Color::White).bg(Color::Black));
+
+    f.render_widget(input, chunks[1]);
+
+    let help = Paragraph::new("enter: search (empty clears) | esc: cancel")
+        .block(Block::default().borders(Borders::ALL))
+        .style(Style::default().fg(Color::Gray));
+
+    f.render_widget(help, chunks[2]);
+
+    f.set_cursor_position((
+        chunks[1].x + app.pickaxe_query.len() as u16 + 1,
+        chunks[1].y + 1,
+    ));
+}
+
+fn draw_bundle_dialog(f: &mut Frame, area: Rect, app: &App) {
+    let creating = app.bundle_action == crate::app::BundleAction::Create;
+    let popup_area = centered_rect(60, if creating { 20 } else { 15 }, area);
+
+    f.render_widget(Clear, popup_area);
+
+    let mut constraints = vec![Constraint::Length(3), Constraint::Length(3)];
+    if creating {
+        constraints.push(Constraint::Length(3));
+    }
+    constraints.push(Constraint::Length(3));
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(constraints)
+        .split(popup_area);
+
+    let title = Paragraph::new(if creating { "create bundle" } else { "fetch from bundle" })
+        .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(Color::Magenta)))
+        .style(Style::default().fg(Color::White));
+    f.render_widget(title, chunks[0]);
+
+    let active = Style::default().fg(Color::Magenta);
+    let inactive = Style::default();
+
+    let path = Paragraph::new(app.bundle_path.as_str())
+        .block(Block::default().borders(Borders::ALL).title("bundle file")
+            .border_style(if app.bundle_editing_refs { inactive } else { active }))
+        .style(Style::default().fg(Color::White).bg(Color::Black));
+    f.render_widget(path, chunks[1]);
+
+    if creating {
+        let refs = Paragraph::new(app.bundle_refs.as_str())
+            .block(Block::default().borders(Borders::ALL).title("refs (space separated, empty for --all)")
+                .border_style(if app.bundle_editing_refs { active } else { inactive }))
+            .style(Style::default().fg(Color::White).bg(Color::Black));
+        f.render_widget(refs, chunks[2]);
+    }
+
+    let help = if creating { "enter: create | tab: switch field | esc: cancel" } else { "enter: fetch | esc: cancel" };
+    let help = Paragraph::new(help)
+        .block(Block::default().borders(Borders::ALL))
+        .style(Style::default().fg(Color::Gray));
+    f.render_widget(help, chunks[chunks.len() - 1]);
+
+    let (input_area, len) = if app.bundle_editing_refs {
+        (chunks[2], app.bundle_refs.len())
+    } else {
+        (chunks[1], app.bundle_path.len())
+    };
+    f.set_cursor_position((
+        input_area.x + len as u16 + 1,
+        input_area.y + 1,
+    ));
+}
+
+fn styled_diff_line(line: &str) -> Line<'_> {
+    if line.starts_with('+') && !line.starts_with("+++") {
+        Line::from(Span::styled(line, Style::default().fg(Color::Green)))
+    } else if line.starts_with('-') && !line.starts_with("---") {
+        Line::from(Span::styled(line, Style::default().fg(Color::Red)))
+    } else if line.starts_with("@@") {
+        Line::from(Span::styled(line, Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)))
+    } else if line.starts_with("+++") || line.starts_with("---") {
+        Line::from(Span::styled(line, Style::default().fg(Color::Yellow)))
+    } else if line.starts_with("old mode ") || line.starts_with("new mode ") {
+        Line::from(Span::styled(line, Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD)))
+    } else {
+        Line::from(Span::styled(line, Style::default().fg(Color::White)))
+    }
+}
+
+fn draw_restore_dialog(f: &mut Frame, area: Rect, app: &App) {
+    let popup_area = centered_rect(85, 80, area);
+    f.render_widget(Clear, popup_area);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Length(3), Constraint::Min(5), Constraint::Length(3)])
+        .split(popup_area);
+
+    let title = Paragraph::new(format!(
+        "restore a file as of {} into the {}",
+        app.repo.short_id(&app.restore_commit),
+        app.restore_target.label()
+    ))
+    .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(Color::Cyan)))
+    .style(Style::default().fg(Color::White));
+    f.render_widget(title, rows[0]);
+
+    let input = Paragraph::new(app.restore_input.as_str())
+        .block(Block::default().borders(Borders::ALL).title("filter, or any other path"))
+        .style(Style::default().fg(Color::White).bg(Color::Black));
+    f.render_widget(input, rows[1]);
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(35), Constraint::Percentage(65)])
+        .split(rows[2]);
+
+    let files = app.filtered_restore_files();
+    let items: Vec<ListItem> = files
+        .iter()
+        .enumerate()
+        .map(|(i, path)| {
+            let style = if i == app.selected_restore_file {
+                Style::default().bg(Color::DarkGray).fg(Color::White)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            ListItem::new(Line::from(Span::styled(path.as_str(), style)))
+        })
+        .collect();
+    let list_title = if files.is_empty() && !app.restore_input.trim().is_empty() {
+        "changed in commit (none match, using the typed path)"
+    } else {
+        "changed in commit"
+    };
+    let mut state = ListState::default();
+    state.select(Some(app.selected_restore_file));
+    f.render_stateful_widget(List::new(items).block(Block::default().borders(Borders::ALL).title(list_title)), columns[0], &mut state);
+
+    let preview_title = match app.restore_path() {
+        Some(path) => format!("what restoring {} changes", path),
+        None => "preview".to_string(),
+    };
+    let preview = Paragraph::new(app.restore_preview.lines().map(styled_diff_line).collect::<Vec<_>>())
+        .block(Block::default().borders(Borders::ALL).title(preview_title))
+        .scroll((app.restore_scroll.min(u16::MAX as usize) as u16, 0));
+    f.render_widget(preview, columns[1]);
+
+    let help = Paragraph::new("enter: restore | ↑/↓: pick file | tab: working tree / index / both | pgup/pgdn: scroll preview | esc: cancel")
+        .block(Block::default().borders(Borders::ALL))
+        .style(Style::default().fg(Color::Gray));
+    f.render_widget(help, rows[3]);
+
+    f.set_cursor_position((rows[1].x + app.restore_input.len() as u16 + 1, rows[1].y + 1));
+}
+
+fn draw_push_dialog(f: &mut Frame, area: Rect, app: &App) {
+    let popup_area = centered_rect(70, 50, area);
+    f.render_widget(Clear, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Length(3), Constraint::Min(3), Constraint::Length(3)])
+        .split(popup_area);
+
+    let remote = app.remotes.get(app.push_remote).map(|r| r.as_str()).unwrap_or("no remotes configured");
+    let title = Paragraph::new(format!("push to {}", remote))
+        .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(Color::Cyan)))
+        .style(Style::default().fg(Color::White));
+    f.render_widget(title, chunks[0]);
+
+    let input = Paragraph::new(app.push_refspecs.as_str())
+        .block(Block::default().borders(Borders::ALL).title("refspecs, space separated (main  +main  main:other  :old  v1.0  refs/tags/*)"))
+        .style(Style::default().fg(Color::White).bg(Color::Black));
+    f.render_widget(input, chunks[1]);
+
+    let mut preview: Vec<Line> = app
+        .push_preview()
+        .into_iter()
+        .map(|line| match line {
+            Ok(text) => {
+                let color = if text.starts_with("delete") || text.starts_with("force") { Color::Red } else { Color::Green };
+                Line::from(Span::styled(text, Style::default().fg(color)))
+            }
+            Err(e) => Line::from(Span::styled(format!("✗ {}", e), Style::default().fg(Color::Red))),
+        })
+        .collect();
+    if !app.secret_findings.is_empty() {
+        preview.push(Line::from(""));
+        preview.extend(secret_finding_lines(app, "ctrl-o"));
+    }
+    let preview = Paragraph::new(preview)
+        .block(Block::default().borders(Borders::ALL).title("will run"))
+        .wrap(Wrap { trim: false });
+    f.render_widget(preview, chunks[2]);
+
+    let help = if !app.secret_findings.is_empty() && !app.secrets_overridden {
+        Paragraph::new("ctrl-o: push anyway, it has possible secrets | esc: cancel").style(Style::default().fg(Color::Red))
+    } else if app.push_confirmed {
+        Paragraph::new("this pushes to protected branches, enter again to push anyway | esc: cancel").style(Style::default().fg(Color::Magenta))
+    } else {
+        Paragraph::new("enter: push | tab: next remote | esc: cancel").style(Style::default().fg(Color::Gray))
+    };
+    f.render_widget(help.block(Block::default().borders(Borders::ALL)), chunks[3]);
+
+    f.set_cursor_position((chunks[1].x + app.push_refspecs.len() as u16 + 1, chunks[1].y + 1));
+}
+
+// the secret scan's findings and whether they block the push, `toggle` is the key that overrides
+fn secret_finding_lines<'a>(app: &'a App, toggle: &str) -> Vec<Line<'a>> {
+    let (heading, color) = if app.secrets_overridden {
+        (format!("{} possible secret(s), pushing anyway ({}: block again)", app.secret_findings.len(), toggle), Color::Yellow)
+    } else {
+        (format!("{} possible secret(s), the push is blocked ({}: push anyway)", app.secret_findings.len(), toggle), Color::Red)
+    };
+    let mut lines = vec![Line::from(Span::styled(heading, Style::default().fg(color).add_modifier(Modifier::BOLD)))];
+    for finding in &app.secret_findings {
+        lines.push(Line::from(vec![
+            Span::styled(format!("  {} ", app.repo.short_id(&finding.commit)), Style::default().fg(Color::Cyan)),
+            Span::styled(format!("{}:{} ", finding.path, finding.line), Style::default().fg(Color::White)),
+            Span::styled(format!("{}  ", finding.rule), Style::default().fg(color)),
+            Span::styled(finding.text.clone(), Style::default().fg(Color::Gray)),
+        ]));
+    }
+    lines
+}
+
+fn draw_sync_fork_dialog(f: &mut Frame, area: Rect, app: &App) {
+    let popup_area = centered_rect(60, 40, area);
+    f.render_widget(Clear, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(5), Constraint::Length(4), Constraint::Length(3)])
+        .split(popup_area);
+
+    let upstream = app.repo.fork_parent_remote();
+    let has_upstream = app.remotes.contains(&upstream);
+    let mut plan = vec![
+        Line::from(format!("1. fetch {}", upstream)),
+        Line::from(format!(
+            "2. {} the default branch onto {}/<default>",
+            if app.sync_fork_options.rebase { "fast-forward, or rebase" } else { "fast-forward" },
+            upstream
+        )),
+    ];
+    if app.sync_fork_options.push {
+        plan.push(Line::from("3. push it to origin, shown in the push preview first"));
+    }
+    if !has_upstream {
+        plan.push(Line::from(""));
+        plan.push(Line::from(Span::styled(
+            format!("no '{}' remote yet: git remote add {} <url> (or set hydra.upstreamRemote)", upstream, upstream),
+            Style::default().fg(Color::Red),
+        )));
+    }
+    let plan = Paragraph::new(plan)
+        .block(Block::default().borders(Borders::ALL).title("sync fork").border_style(Style::default().fg(Color::Cyan)))
+        .style(Style::default().fg(Color::White))
+        .wrap(Wrap { trim: false });
+    f.render_widget(plan, chunks[0]);
+
+    let checkbox = |on: bool| if on { "x" } else { " " };
+    let options = Paragraph::new(vec![
+        Line::from(format!("[{}] r: rebase when the branch has commits of its own", checkbox(app.sync_fork_options.rebase))),
+        Line::from(format!("[{}] p: push to origin afterwards", checkbox(app.sync_fork_options.push))),
+    ])
+    .block(Block::default().borders(Borders::ALL).title("options"));
+    f.render_widget(options, chunks[1]);
+
+    let help = Paragraph::new("enter: sync | r / p: toggle | esc: cancel")
+        .block(Block::default().borders(Borders::ALL))
+        .style(Style::default().fg(Color::Gray));
+    f.render_widget(help, chunks[2]);
+}
+
+fn draw_upstream_dialog(f: &mut Frame, area: Rect, app: &App) {
+    let popup_area = centered_rect(60, 60, area);
+    f.render_widget(Clear, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Length(3), Constraint::Min(3), Constraint::Length(3)])
+        .split(popup_area);
+
+    let current = match app.branch_upstreams.get(&app.upstream_branch) {
+        Some(upstream) => format!("tracks {} [{}]", upstream.name, upstream.summary()),
+        None => "no upstream".to_string(),
+    };
+    let title = Paragraph::new(format!("upstream of {}, {}", app.upstream_branch, current))
+        .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(Color::Cyan)))
+        .style(Style::default().fg(Color::White));
+    f.render_widget(title, chunks[0]);
+
+    let input = Paragraph::new(app.upstream_input.as_str())
+        .block(Block::default().borders(Borders::ALL).title("filter"))
+        .style(Style::default().fg(Color::White).bg(Color::Black));
+    f.render_widget(input, chunks[1]);
+
+    let items: Vec<ListItem> = app
+        .filtered_upstreams()
+        .iter()
+        .enumerate()
+        .map(|(i, name)| {
+            let style = if i == app.selected_upstream {
+                Style::default().bg(Color::DarkGray).fg(Color::White)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            ListItem::new(Line::from(Span::styled(name.as_str(), style)))
+        })
+        .collect();
+    let mut state = ListState::default();
+    state.select(Some(app.selected_upstream));
+    f.render_stateful_widget(List::new(items).block(Block::default().borders(Borders::ALL).title("branches")), chunks[2], &mut state);
+
+    let help = Paragraph::new("enter: track selected | del: unset upstream | ↑/↓: select | esc: cancel")
+        .block(Block::default().borders(Borders::ALL))
+        .style(Style::default().fg(Color::Gray));
+    f.render_widget(help, chunks[3]);
+
+    f.set_cursor_position((chunks[1].x + app.upstream_input.len() as u16 + 1, chunks[1].y + 1));
+}
+
+fn draw_gitflow_dialog(f: &mut Frame, area: Rect, app: &App) {
+    let popup_area = centered_rect(60, 50, area);
+    f.render_widget(Clear, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(8), Constraint::Length(3), Constraint::Length(3)])
+        .split(popup_area);
+
+    let config = app.repo.flow_config();
+    let items: Vec<ListItem> = crate::app::FLOW_ACTIONS
+        .iter()
+        .enumerate()
+        .map(|(i, (kind, starting))| {
+            let style = if i == app.selected_flow_action {
+                Style::default().bg(Color::DarkGray).fg(Color::White)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            let (verb, detail) = if *starting {
+                let base = if *kind == crate::git::FlowKind::Hotfix { &config.main } else { &config.develop };
+                ("start", format!("{}<name> from {}", config.prefix(*kind), base))
+            } else {
+                let branch = app.flow_finish_branch(*kind).unwrap_or_else(|| format!("{}...", config.prefix(*kind)));
+                ("finish", branch)
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{} {}", verb, kind.label()), style),
+                Span::styled(format!("  {}", detail), Style::default().fg(Color::Gray)),
+            ]))
+        })
+        .collect();
+
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title("gitflow").border_style(Style::default().fg(Color::Cyan)));
+    let mut state = ListState::default();
+    state.select(Some(app.selected_flow_action));
+    f.render_stateful_widget(list, chunks[0], &mut state);
+
+    let starting = crate::app::FLOW_ACTIONS[app.selected_flow_action].1;
+    let input = Paragraph::new(if starting { app.flow_name.as_str() } else { "" })
+        .block(Block::default().borders(Borders::ALL).title(if starting { "name / version" } else { "name (not needed to finish)" }))
+        .style(Style::default().fg(Color::White).bg(Color::Black));
+    f.render_widget(input, chunks[1]);
+
+    let help = Paragraph::new("↑/↓: action | enter: run | esc: cancel (prefixes: gitflow.prefix.*)")
+        .block(Block::default().borders(Borders::ALL))
+        .style(Style::default().fg(Color::Gray));
+    f.render_widget(help, chunks[2]);
+
+    if starting {
+        f.set_cursor_position((chunks[1].x + app.flow_name.len() as u16 + 1, chunks[1].y + 1));
+    }
+}
+
+fn draw_autostash_dialog(f: &mut Frame, area: Rect, app: &App) {
+    let popup_area = centered_rect(60, 25, area);
+    f.render_widget(Clear, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(4), Constraint::Length(3)])
+        .split(popup_area);
+
+    let operation = app.autostash_operation.as_ref().map(|op| op.describe()).unwrap_or_default();
+    let text = vec![
+        Line::from(Span::styled(
+            format!("local changes are in the way of {}.", operation),
+            Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+        )),
+        Line::from(""),
+        Line::from("stash them, run it, and re-apply them afterwards?"),
+    ];
+    let body = Paragraph::new(text)
+        .block(Block::default().borders(Borders::ALL).title("autostash").border_style(Style::default().fg(Color::Yellow)))
+        .wrap(Wrap { trim: true });
+    f.render_widget(body, chunks[0]);
+
+    let help = Paragraph::new("enter/y: autostash | a: always (sets hydra.autoStash) | esc/n: cancel")
+        .block(Block::default().borders(Borders::ALL))
+        .style(Style::default().fg(Color::Gray));
+    f.render_widget(help, chunks[1]);
+}
+
+fn draw_credential_dialog(f: &mut Frame, area: Rect, app: &App) {
+    // four fields high whatever the terminal, centered_rect's share of a short one is too little
+    let width = area.width.min(90);
+    let height = area.height.min(12);
+    let popup_area = Rect::new(area.x + (area.width - width) / 2, area.y + (area.height - height) / 2, width, height);
+    f.render_widget(Clear, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Length(3), Constraint::Length(3), Constraint::Length(3)])
+        .split(popup_area);
+
+    let title = Paragraph::new(format!("{} needs a username and token (or password)", app.credential_url))
+        .block(Block::default().borders(Borders::ALL).title("credentials").border_style(Style::default().fg(Color::Magenta)))
+        .style(Style::default().fg(Color::White));
+    f.render_widget(title, chunks[0]);
+
+    let active = Style::default().fg(Color::Magenta);
+    let inactive = Style::default();
+
+    let username = Paragraph::new(app.credential_username.as_str())
+        .block(Block::default().borders(Borders::ALL).title("username")
+            .border_style(if app.credential_editing_token { inactive } else { active }))
+        .style(Style::default().fg(Color::White).bg(Color::Black));
+    f.render_widget(username, chunks[1]);
+
+    let masked = "*".repeat(app.credential_token.chars().count());
+    let token = Paragraph::new(masked.as_str())
+        .block(Block::default().borders(Borders::ALL).title("token")
+            .border_style(if app.credential_editing_token { active } else { inactive }))
+        .style(Style::default().fg(Color::White).bg(Color::Black));
+    f.render_widget(token, chunks[2]);
+
+    let help = Paragraph::new(format!("enter: use | tab: switch field | esc: cancel | {}", app.credential_store_note))
+        .block(Block::default().borders(Borders::ALL))
+        .style(Style::default().fg(Color::Gray));
+    f.render_widget(help, chunks[3]);
+
+    let (input_area, len) = if app.credential_editing_token {
+        (chunks[2], masked.len())
+    } else {
+        (chunks[1], app.credential_username.len())
+    };
+    f.set_cursor_position((
+        input_area.x + len as u16 + 1,
+        input_area.y + 1,
+    ));
+}
+
+fn draw_rebase_onto_dialog(f: &mut Frame, area: Rect, app: &App) {
+    let popup_area = centered_rect(90, 90, area);
+    f.render_widget(Clear, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Length(3),
+            Constraint::Min(5),
+            Constraint::Length(8),
+            Constraint::Length(3),
+        ])
+        .split(popup_area);
+
+    let labels = ["new base", "old base (exclusive, its commits stay)", "tip (branch to move)"];
+    for (i, label) in labels.iter().enumerate() {
+        let border = if app.onto_field == i { Color::Yellow } else { Color::White };
+        let field = Paragraph::new(app.onto_fields[i].as_str())
+            .block(Block::default().borders(Borders::ALL).title(*label).border_style(Style::default().fg(border)))
+            .style(Style::default().fg(Color::White).bg(Color::Black));
+        f.render_widget(field, chunks[i]);
+    }
+    let focused = chunks[app.onto_field];
+    f.set_cursor_position((focused.x + 1 + app.onto_fields[app.onto_field].chars().count() as u16, focused.y + 1));
+
+    let items: Vec<ListItem> = app
+        .filtered_onto_candidates()
+        .iter()
+        .enumerate()
+        .map(|(i, (name, detail))| {
+            let style = if i == app.selected_onto_candidate {
+                Style::default().fg(Color::Black).bg(Color::Cyan)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(format!("{:<24} ", name), style),
+                Span::styled(detail.clone(), Style::default().fg(Color::Gray)),
+            ]))
+        })
+        .collect();
+    let mut state = ListState::default();
+    state.select(Some(app.selected_onto_candidate));
+    let list = List::new(items).block(Block::default().borders(Borders::ALL).title(format!("pick {}", labels[app.onto_field])));
+    f.render_stateful_widget(list, chunks[3], &mut state);
+
+    // what would move, worked out from the old base and tip as they're typed
+    let [new_base, old_base, tip] = app.onto_fields.clone().map(|f| f.trim().to_string());
+    let mut preview = vec![Line::from(Span::styled(
+        format!("git rebase --onto {} {} {}", or_blank(&new_base), or_blank(&old_base), or_blank(&tip)),
+        Style::default().fg(Color::Cyan),
+    ))];
+    if !old_base.is_empty() && !tip.is_empty() {
+        match app.repo.rebase_onto_commits(&old_base, &tip) {
+            Ok(commits) if commits.is_empty() => preview.push(Line::from(Span::styled("nothing to move", Style::default().fg(Color::Yellow)))),
+            Ok(commits) => {
+                preview.push(Line::from(format!("replays {} commit(s):", commits.len())));
+                for commit in commits.iter().take(4) {
+                    let summary = commit.message.lines().next().unwrap_or("");
+                    preview.push(Line::from(vec![
+                        Span::styled(format!("  {} ", app.repo.short_id(&commit.id)), Style::default().fg(Color::Yellow)),
+                        Span::raw(summary.to_string()),
+                    ]));
+                }
+                if commits.len() > 4 {
+                    preview.push(Line::from(Span::styled(format!("  ... and {} more", commits.len() - 4), Style::default().fg(Color::Gray))));
+                }
+            }
+            Err(e) => preview.push(Line::from(Span::styled(format!("{:#}", e), Style::default().fg(Color::Red)))),
+        }
+    }
+    let preview = Paragraph::new(preview)
+        .block(Block::default().borders(Borders::ALL).title("rebase --onto").border_style(Style::default().fg(Color::Cyan)))
+        .wrap(Wrap { trim: false });
+    f.render_widget(preview, chunks[4]);
+
+    let help = Paragraph::new("tab: next field | ↑/↓: pick | enter: fill / run | esc: cancel")
+        .block(Block::default().borders(Borders::ALL))
+        .style(Style::default().fg(Color::Gray));
+    f.render_widget(help, chunks[5]);
+}
+
+fn draw_stack_view(f: &mut Frame, area: Rect, app: &App) {
+    let popup_area = centered_rect(90, 80, area);
+    f.render_widget(Clear, popup_area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(3), Constraint::Length(3)])
+        .split(popup_area);
+
+    // parents come before their children, so each one's depth is already known
+    let mut depths: Vec<(&str, usize)> = Vec::new();
+    let items: Vec<ListItem> = app
+        .stack
+        .iter()
+        .enumerate()
+        .map(|(i, branch)| {
+            let depth = branch
+                .parent
+                .as_deref()
+                .and_then(|parent| depths.iter().find(|(name, _)| *name == parent))
+                .map_or(0, |(_, depth)| depth + 1);
+            depths.push((&branch.name, depth));
+
+            let style = if i == app.selected_stacked {
+                Style::default().fg(Color::Black).bg(Color::Cyan)
+            } else if branch.name == app.current_branch {
+                Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            let connector = if depth == 0 { String::new() } else { format!("{}└─ ", "   ".repeat(depth - 1)) };
+            let mut spans = vec![Span::styled(connector, Style::default().fg(Color::DarkGray)), Span::styled(branch.name.clone(), style)];
+            if branch.parent.is_some() {
+                spans.push(Span::styled(format!("  +{}", branch.ahead), Style::default().fg(Color::Gray)));
+            }
+            if branch.needs_restack {
+                spans.push(Span::styled("  needs restack", Style::default().fg(Color::Yellow)));
+            }
+            if branch.parent.is_some() && branch.guessed {
+                spans.push(Span::styled("  (guessed from history)", Style::default().fg(Color::DarkGray)));
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+
+    let mut state = ListState::default();
+    state.select(Some(app.selected_stacked));
+    let list = List::new(items).block(
+        Block::default()
+            .borders(Borders::ALL)
+            .title("branch stacks")
+            .border_style(Style::default().fg(Color::Cyan)),
+    );
+    f.render_stateful_widget(list, chunks[0], &mut state);
+
+    let help = Paragraph::new("↑/↓: select | enter: checkout | r: restack branches whose parent moved | esc: back")
+        .block(Block::default().borders(Borders::ALL))
+        .style(Style::default().fg(Color::Gray));
+    f.render_widget(help, chunks[1]);
+}
+
+fn or_blank(value: &str) -> &str {
+    if value.is_empty() { "_" } else { value }
+}
+
+fn draw_stash_dialog(f: &mut Frame, area: Rect, app: &App) {
+    let popup_area = centered_rect(60, 30, area);
+    
+    f.render_widget(Clear, popup_area);
+    
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Length(3), Constraint::Min(3), Constraint::Length(5), Constraint::Length(3)])
+        .split(popup_area);
+    
+    let title = Paragraph::new("stash changes")
+        .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(Color::Cyan)))
+        .style(Style::default().fg(Color::White));
+        
+    f.render_widget(title, chunks[0]);
+    
+    let message_border = if app.stash_focus == 0 { Color::Yellow } else { Color::White };
+    let message = Paragraph::new(app.stash_message.as_str())
+        .block(Block::default().borders(Borders::ALL).title("stash message (optional)").border_style(Style::default().fg(message_border)))
+        .style(Style::default().fg(Color::White).bg(Color::Black))
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(message, chunks[1]);
+
+    let options = app.stash_options;
+    let toggles = [
+        ("include untracked files", options.include_untracked),
+        ("include ignored files", options.include_ignored),
+        ("keep staged changes in the index", options.keep_index),
+    ];
+    let lines: Vec<Line> = toggles
+        .iter()
+        .enumerate()
+        .map(|(i, (label, on))| {
+            let style = if app.stash_focus == i + 1 {
+                Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            Line::from(Span::styled(format!("[{}] {}", if *on { "x" } else { " " }, label), style))
+        })
+        .collect();
+    let toggles = Paragraph::new(lines).block(Block::default().borders(Borders::ALL).title("options"));
+    f.render_widget(toggles, chunks[2]);
+    
+    let help = Paragraph::new("enter: stash | tab: next field | space: toggle | ↑/↓: previous messages | esc: cancel")
+        .block(Block::default().borders(Borders::ALL))
+        .style(Style::default().fg(Color::Gray));
+        
+    f.render_widget(help, chunks[3]);
+    
+    if app.stash_focus == 0 {
+        f.set_cursor_position((
+            chunks[1].x + app.stash_message.len() as u16 + 1,
+            chunks[1].y + 1,
+        ));
+    }
+}
+
+fn draw_remote_view(f: &mut Frame, area: Rect, app: &App){
+    let chunks = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+        .split(area);
+    
+    draw_remote_list(f, chunks[0], app);
+    
+    draw_remote_operations(f, chunks[1], app);
+}
+
+// the commits and diffstat `p` is about to send, one scrolling page
+fn draw_push_preview(f: &mut Frame, area: Rect, app: &App) {
+    let Some(preview) = &app.pending_push else {
+        return;
+    };
+    let tracking = format!("{}/{}", preview.remote, preview.branch);
+    let mut lines = vec![match &preview.remote_tip {
+        Some(tip) => Line::from(vec![
+            Span::styled(format!("{} → {}", preview.branch, tracking), Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+            Span::styled(format!("  ({} was at {} when last fetched)", tracking, app.repo.short_id(tip)), Style::default().fg(Color::Gray)),
+        ]),
+        None => Line::from(vec![
+            Span::styled(format!("{} → {}", preview.branch, tracking), Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+            Span::styled(format!("  (new on {}, the push creates it)", preview.remote), Style::default().fg(Color::Gray)),
+        ]),
+    }];
+    if app.protection.is_protected(&preview.branch) {
+        lines.push(Line::from(Span::styled(
+            format!("{} is a protected branch (hydra.protectedBranch), make sure this is meant to go there.", preview.branch),
+            Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD),
+        )));
+    }
+    if preview.behind > 0 {
+        lines.push(Line::from(Span::styled(
+            format!("{} has {} commit(s) {} doesn't, the remote will reject this push. pull first.", tracking, preview.behind, preview.branch),
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        )));
+    }
+    lines.push(Line::from(""));
+
+    if !app.secret_findings.is_empty() {
+        lines.extend(secret_finding_lines(app, "o"));
+        if preview.count > preview.commits.len() {
+            lines.push(Line::from(Span::styled(
+                format!("  only the newest {} commits were scanned", preview.commits.len()),
+                Style::default().fg(Color::Gray),
+            )));
+        }
+        lines.push(Line::from(""));
+    }
+
+    lines.push(Line::from(Span::styled(format!("{} commit(s) to send", preview.count), Style::default().fg(Color::White).add_modifier(Modifier::BOLD))));
+    for commit in &preview.commits {
+        lines.push(Line::from(vec![
+            Span::styled(format!("  {} ", app.repo.short_id(&commit.id)), Style::default().fg(Color::Cyan)),
+            Span::raw(commit.message.lines().next().unwrap_or("").to_string()),
+            Span::styled(format!("  {}, {}", commit.author, list_date(app, commit.timestamp)), Style::default().fg(Color::Gray)),
+        ]));
+    }
+    if preview.count > preview.commits.len() {
+        lines.push(Line::from(Span::styled(format!("  … and {} more", preview.count - preview.commits.len()), Style::default().fg(Color::Gray))));
+    }
+    lines.push(Line::from(""));
+
+    let (mut additions, mut deletions) = (0, 0);
+    for file in &preview.files {
+        additions += file.additions;
+        deletions += file.deletions;
+        lines.push(Line::from(vec![
+            Span::styled(format!(" {} ", file.status), Style::default().fg(Color::Yellow)),
+            Span::raw(format!("{} ", file.path)),
+            Span::styled(format!("+{}", file.additions), Style::default().fg(Color::Green)),
+            Span::raw(" "),
+            Span::styled(format!("-{}", file.deletions), Style::default().fg(Color::Red)),
+        ]));
+    }
+    lines.push(Line::from(Span::styled(
+        format!(" {} file(s) changed, +{} -{}", preview.files.len(), additions, deletions),
+        Style::default().fg(Color::Gray),
+    )));
+
+    let height = area.height.saturating_sub(2) as usize;
+    let scroll = app.push_preview_scroll.min(lines.len().saturating_sub(height));
+    let blocked = !app.secret_findings.is_empty() && !app.secrets_overridden;
+    let color = if preview.behind > 0 || blocked { Color::Red } else { Color::Green };
+    let page = Paragraph::new(lines.into_iter().skip(scroll).take(height).collect::<Vec<_>>())
+        .block(Block::default().borders(Borders::ALL).title(format!("push to {}?", preview.remote)).border_style(Style::default().fg(color)));
+    f.render_widget(page, area);
+}
+
+fn draw_remote_list(f: &mut Frame, area: Rect, app: &App){
+    let items: Vec<ListItem> = app
+        .remotes
+        .iter()
+        .enumerate()
+        .map(|(i, remote)| {
+            let style = if i == app.views.remotes.selected {
+                Style::default().bg(Color::DarkGray).fg(Color::White)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            
+            let mut spans = vec![Span::styled(format!("remote: {}", remote), style)];
+            match app.remote_divergence.get(i).and_then(|(_, upstream)| upstream.as_ref()) {
+                Some(upstream) => {
+                    let color = match (upstream.ahead, upstream.behind) {
+                        (0, 0) => Color::Green,
+                        (_, 0) => Color::Yellow,
+                        _ => Color::Red,
+                    };
+                    spans.push(Span::styled(format!("  {} ", upstream.name), Style::default().fg(Color::Gray)));
+                    spans.push(Span::styled(format!("[{}]", upstream.summary()), Style::default().fg(color)));
+                }
+                None => spans.push(Span::styled(format!("  no {} here", app.current_branch), Style::default().fg(Color::DarkGray))),
+            }
+            ListItem::new(Line::from(spans))
+        })
+        .collect();
+    
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title(format!("remotes ({} against each)", app.current_branch)))
+        .style(Style::default().fg(Color::White));
+    
+    f.render_widget(list, area);
+}
+
+fn draw_remote_operations(f: &mut Frame, area: Rect, app: &App) {
+    let mut text = Vec::new();
+    
+    text.push(Line::from(vec![
+        Span::styled("current Branch: ", Style::default().fg(Color::Gray)),
+        Span::styled(&app.current_branch, Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+    ]));
+    
+    text.push(Line::from(""));
+    
+    if !app.remotes.is_empty() && app.views.remotes.selected < app.remotes.len(){
+        let selected_remote = &app.remotes[app.views.remotes.selected];
+        text.push(Line::from(vec![
+            Span::styled("selected remote: ", Style::default().fg(Color::Gray)),
+            Span::styled(selected_remote, Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD)),
+        ]));
+        
+        if let Ok(Some(url)) = app.repo.get_remote_url(selected_remote) {
+            text.push(Line::from(vec![
+                Span::styled("URL: ", Style::default().fg(Color::Gray)),
+                Span::styled(url, Style::default().fg(Color::Blue)),
+            ]));
+        }
+        if let Some((_, Some(upstream))) = app.remote_divergence.get(app.views.remotes.selected) {
+            text.push(Line::from(vec![
+                Span::styled("compared with: ", Style::default().fg(Color::Gray)),
+                Span::styled(upstream.name.as_str(), Style::default().fg(Color::White)),
+                Span::styled(format!(" ({} ahead, {} behind)", upstream.ahead, upstream.behind), Style::default().fg(Color::Gray)),
+            ]));
+        }
+        
+        text.push(Line::from(""));
+
+        if app.is_pushing {
+            text.push(Line::from(vec![
+                Span::styled("Pushing...", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+            ]));
+        } else if app.is_pulling {
+            text.push(Line::from(vec![
+                Span::styled("Pulling...", Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD)),
+            ]));
+        } else {
+            text.push(Line::from(vec![
+                Span::styled("operations:", Style::default().fg(Color::Gray)),
+            ]));
+            text.push(Line::from(vec![
+                Span::styled("  p - Push to remote (shows what it sends first)", Style::default().fg(Color::Green)),
+            ]));
+            text.push(Line::from(vec![
+                Span::styled("  u - Pull from remote", Style::default().fg(Color::Blue)),
+            ]));
+            text.push(Line::from(vec![
+                Span::styled("  F - Sync fork with upstream", Style::default().fg(Color::Cyan)),
+            ]));
+            text.push(Line::from(vec![
+                Span::styled("  b - Create bundle (offline transfer)", Style::default().fg(Color::Magenta)),
+            ]));
+            text.push(Line::from(vec![
+                Span::styled("  B - Fetch from bundle", Style::default().fg(Color::Magenta)),
+            ]));
+            text.push(Line::from(vec![
+                Span::styled("  a - Auth status (ssh-agent, gpg-agent, signing key)", Style::default().fg(Color::Yellow)),
+            ]));
+        }
+        if let Some(auth) = &app.auth_status {
+            text.push(Line::from(""));
+            auth_lines(auth, &mut text);
+        }
+    } else {
+        text.push(Line::from(vec![
+            Span::styled("No remotes configured", Style::default().fg(Color::Red)),
+        ]));
+        text.push(Line::from(vec![
+            Span::styled("use 'git remote add origin <url>' to add a remote", Style::default().fg(Color::Gray)),
+        ]));
+    }
+    
+    let paragraph = Paragraph::new(text)
+        .block(Block::default().borders(Borders::ALL).title("remote operations."))
+        .wrap(Wrap { trim: true });
+    
+    f.render_widget(paragraph, area);
+}
+
+fn auth_lines<'a>(auth: &'a crate::auth::AuthStatus, text: &mut Vec<Line<'a>>) {
+    let good = Style::default().fg(Color::Green);
+    let bad = Style::default().fg(Color::Red);
+    let label = Style::default().fg(Color::Gray);
+    text.push(Line::from(Span::styled("auth:", label)));
+
+    match &auth.ssh_agent {
+        Ok(keys) if keys.is_empty() => text.push(Line::from(vec![
+            Span::styled("  ssh-agent: ", label),
+            Span::styled("running, but holds no keys (ssh-add)", Style::default().fg(Color::Yellow)),
+        ])),
+        Ok(keys) => {
+            text.push(Line::from(vec![Span::styled("  ssh-agent: ", label), Span::styled(format!("{} key(s)", keys.len()), good)]));
+            for key in keys {
+                text.push(Line::from(Span::styled(format!("    {}", key), Style::default().fg(Color::White))));
+            }
+        }
+        Err(reason) => text.push(Line::from(vec![Span::styled("  ssh-agent: ", label), Span::styled(reason.as_str(), bad)])),
+    }
+
+    text.push(Line::from(match &auth.gpg_agent {
+        Ok(info) => vec![Span::styled("  gpg-agent: ", label), Span::styled(info.as_str(), good)],
+        Err(reason) => vec![Span::styled("  gpg-agent: ", label), Span::styled(reason.as_str(), bad)],
+    }));
+
+    let signing = &auth.signing;
+    let signs = match (signing.commits, signing.tags) {
+        (true, true) => "commits and tags",
+        (true, false) => "commits",
+        (false, true) => "tags",
+        (false, false) => "nothing by default",
+    };
+    // a key:: literal is the whole public key, its type and comment say enough
+    let key = match signing.key.as_deref() {
+        Some(key) if key.starts_with("key::") => {
+            let words: Vec<&str> = key.split_whitespace().collect();
+            format!("{} … {}", words[0], words.last().filter(|_| words.len() > 2).unwrap_or(&""))
+        }
+        Some(key) => key.to_string(),
+        None => "default key".to_string(),
+    };
+    text.push(Line::from(vec![
+        Span::styled("  signing: ", label),
+        Span::styled(format!("{} ({}), signs {}", key.trim_end(), signing.format, signs), Style::default().fg(Color::White)),
+    ]));
+    if let Some(problem) = &signing.problem {
+        text.push(Line::from(Span::styled(format!("    {}", problem), bad)));
+    }
+}
+
+fn draw_merge_conflict_view(f: &mut Frame, area: Rect, app: &App) {
+    if let Some(merge_conflict) = &app.merge_conflict {
+        let chunks = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(30), Constraint::Percentage(70)])
+            .split(area);
+
+        draw_conflict_file_list(f, chunks[0], app, merge_conflict);
+
+        if app.show_conflict_preview {
+            let panels = Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+                .split(chunks[1]);
+
+            draw_conflict_resolution_panel(f, panels[0], app);
+            draw_conflict_preview(f, panels[1], app);
+        } else {
+            draw_conflict_resolution_panel(f, chunks[1], app);
+        }
+    } else {
+        let no_conflicts = Paragraph::new("no conflicts detected. \n\n this mode is only available during a merge, cherry-pick, revert or rebase with conflicts.")
+            .block(Block::default().borders(Borders::ALL).title("merge conflict"))
+            .style(Style::default().fg(Color::Gray))
+            .wrap(Wrap { trim: true });
+
+        f.render_widget(no_conflicts, area);
+    }
+}
+
+fn draw_conflict_file_list(f: &mut Frame, area: Rect, app: &App, merge_conflict: &crate::git::MergeConflict) {
+    let mut items = Vec::new();
+
+    items.push(ListItem::new(Line::from(vec![
+        Span::styled(format!("── {} CONFLICT ──", merge_conflict.operation.label().to_uppercase()), Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))
+    ])));
+
+    items.push(ListItem::new(Line::from(vec![
+        Span::styled("our commit: ", Style::default().fg(Color::Gray)),
+        Span::styled(app.repo.short_id(&merge_conflict.our_commit), Style::default().fg(Color::Green).add_modifier(Modifier::BOLD))
+    ])));
+
+    items.push(ListItem::new(Line::from(vec![
+        Span::styled("their commit: ", Style::default().fg(Color::Gray)),
+        Span::styled(app.repo.short_id(&merge_conflict.their_commit), Style::default().fg(Color::Red).add_modifier(Modifier::BOLD))
+    ])));
+
+    items.push(ListItem::new(Line::from("")));
+
+    for (file_idx, file) in merge_conflict.files.iter().enumerate() {
+        let is_selected_file = file_idx == app.selected_conflict_file;
+
+        let resolved_count = file.conflicts.iter().enumerate()
+            .filter(|(hunk_idx, _)| app.conflict_resolutions.contains_key(&(file_idx, *hunk_idx)))
+            .count();
+        
+        let total_conflicts = file.conflicts.len();
+        let all_resolved = resolved_count == total_conflicts;
+
+        let file_style = if is_selected_file {
+            Style::default().bg(Color::DarkGray).fg(Color::White)
+        } else if all_resolved {
+            Style::default().fg(Color::Green)
+        } else {
+            Style::default().fg(Color::Red)
+        };
+
+        let status_icon = if all_resolved {
+            "✓"
+        } else {
+            "✗"
+        };
+        let progress = format!("({}/{})", resolved_count, total_conflicts);
+
+        let mut spans = vec![
+            Span::styled(format!("{} ", status_icon),
+                if all_resolved { Style::default().fg(Color::Green) } else { Style::default().fg(Color::Red) }),
+            Span::styled(&file.path, file_style),
+            Span::styled(format!(" {}", progress), Style::default().fg(Color::Gray)),
+        ];
+        if file.kind != crate::git::ConflictKind::Text {
+            spans.push(Span::styled(format!(" [{}]", file.kind.label()), Style::default().fg(Color::Magenta)));
+        }
+        items.push(ListItem::new(Line::from(spans)));
+        
+        // im gonna rip my hair out ui design is a pain in my butt
+
+        if is_selected_file {
+            for (hunk_idx, _hunk) in file.conflicts.iter().enumerate() {
+                let is_selected_hunk = hunk_idx == app.selected_conflict_hunk;
+                let is_resolved = app.conflict_resolutions.contains_key(&(file_idx, hunk_idx));
+
+                let hunk_style = if is_selected_hunk {
+                    Style::default().bg(Color::DarkGray).fg(Color::White)
+                } else if is_resolved {
+                    Style::default().fg(Color::Green)
+                } else {
+                    Style::default().fg(Color::Red)
+                };
+
+                let resolution_text = if let Some(resolution) = app.conflict_resolutions.get(&(file_idx, hunk_idx)) {
+                    match resolution {
+                        crate::git::MergeResolution::KeepOurs => "[OURS]",
+                        crate::git::MergeResolution::KeepTheirs => "[THEIRS]",
+                        crate::git::MergeResolution::KeepBoth => "[BOTH]",
+                        crate::git::MergeResolution::Delete => "[DELETE]",
+                        crate::git::MergeResolution::OursFile => "[OUR FILE]",
+                        crate::git::MergeResolution::TheirsFile => "[THEIR FILE]",
+                        crate::git::MergeResolution::Custom(_) => "[CUSTOM]",
+                    }
+                } else {
+                    "[UNRESOLVED]"
+                };
+
+                items.push(ListItem::new(Line::from(vec![
+                    Span::styled("  └─ ", Style::default()),
+                    Span::styled(format!("Hunk {}{}", hunk_idx + 1, resolution_text), hunk_style),
+                ])));
+            }
+        }
+    }
+
+    let list = List::new(items)
+        .block(Block::default().borders(Borders::ALL).title("conflicted files"))
+        .style(Style::default().fg(Color::White));
+
+    f.render_widget(list, area);
+}
+
+// someone please hop on this project and do the UI
+fn draw_conflict_resolution_panel(f: &mut Frame, area: Rect, app: &App) {
+    if let Some(current_hunk) = app.get_current_conflict_hunk(){
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(3),
+                Constraint::Percentage(40),
+                Constraint::Percentage(40),
+                Constraint::Min(5),
+            ])
+            .split(area);
+        
+        draw_conflict_header(f, chunks[0], app);
+        let operation = app.merge_conflict.as_ref()
+            .map(|mc| mc.operation)
+            .unwrap_or(crate::git::ConflictOperation::Merge);
+        draw_conflict_ours_section(f, chunks[1], current_hunk, operation);
+        draw_conflict_theirs_section(f, chunks[2], current_hunk, operation);
+        draw_conflict_resolution_section(f, chunks[3], app, current_hunk);
+    } else {
+        let no_hunk = Paragraph::new("select a conflict to view details")
+            .block(Block::default().borders(Borders::ALL).title("conflict resolution"))
+            .style(Style::default().fg(Color::Gray))
+            .wrap(Wrap { trim: true });
+
+        f.render_widget(no_hunk, area);
+    }
+}
+
+fn draw_conflict_preview(f: &mut Frame, area: Rect, app: &App) {
+    let Some(file) = app.get_current_conflict_file() else {
+        return;
+    };
+
+    let preview = match app.repo.preview_resolved_file(file, app.selected_conflict_file, &app.conflict_resolutions) {
+        Ok(preview) => preview,
+        Err(e) => {
+            let error = Paragraph::new(format!("cannot preview: {}", e))
+                .block(Block::default().borders(Borders::ALL).title("preview"))
+                .style(Style::default().fg(Color::Red))
+                .wrap(Wrap { trim: true });
+            f.render_widget(error, area);
+            return;
+        }
+    };
+
+    let lines: Vec<Line> = preview.lines
+        .iter()
+        .map(|line| {
+            let style = if line.from_resolution {
+                Style::default().fg(Color::Green)
+            } else if line.text.starts_with("<<<<<<<") || line.text.starts_with("=======")
+                || line.text.starts_with(">>>>>>>") || line.text.starts_with("|||||||") {
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+            } else {
+                Style::default().fg(Color::White)
+            };
+            Line::from(Span::styled(line.text.as_str(), style))
+        })
+        .collect();
+
+    // keep the selected hunk in view, pgup/pgdn moves from there
+    let hunk_start = preview.hunk_starts
+        .get(app.selected_conflict_hunk)
+        .copied()
+        .unwrap_or(0)
+        .saturating_sub(3) as u16;
+    let max_scroll = (lines.len() as u16).saturating_sub(area.height.saturating_sub(2));
+    let scroll = hunk_start.saturating_add(app.conflict_preview_scroll).min(max_scroll);
+
+    let title = if app.can_complete_merge() {
+        "preview (all resolved)"
+    } else {
+        "preview (unresolved hunks keep markers)"
+    };
+
+    let paragraph = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .scroll((scroll, 0));
+
+    f.render_widget(paragraph, area);
+}
+
+fn draw_conflict_header(f: &mut Frame, area: Rect, app: &App) {
+    if let Some(file) = app.get_current_conflict_file() {
+        let current_hunk = app.selected_conflict_hunk + 1;
+        let total_hunks = file.conflicts.len();
+        
+        let header_text = format!("{} - conflict {} of {}", file.path, current_hunk, total_hunks);
+        
+        let header = Paragraph::new(header_text)
+            .block(Block::default().borders(Borders::ALL))
+            .style(Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))
+            .wrap(Wrap { trim: true });
+
+        f.render_widget(header, area);
+    }
+}
+
+fn draw_conflict_ours_section(f: &mut Frame, area: Rect, hunk: &crate::git::ConflictHunk, operation: crate::git::ConflictOperation) {
+    let lines: Vec<Line> = hunk.our_content
+        .lines()
+        .map(|line| Line::from(Span::styled(line, Style::default().fg(Color::Green))))
+        .collect();
+
+    let our_section = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(format!("OURS ({}) - press 'o' to keep", operation.ours_label())))
+        .style(Style::default().fg(Color::White))
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(our_section, area);
+}
+
+fn draw_conflict_theirs_section(f: &mut Frame, area: Rect, hunk: &crate::git::ConflictHunk, operation: crate::git::ConflictOperation) {
+    let lines: Vec<Line> = hunk.their_content
+        .lines()
+        .map(|line| Line::from(Span::styled(line, Style::default().fg(Color::Blue))))
+        .collect();
+
+    let their_section = Paragraph::new(lines)
+        .block(Block::default().borders(Borders::ALL).title(format!("THEIRS ({}) - press 't' to keep", operation.theirs_label())))
+        .style(Style::default().fg(Color::White))
+        .wrap(Wrap { trim: false });
+
+    f.render_widget(their_section, area);
+}
+
+fn draw_conflict_resolution_section(f: &mut Frame, area: Rect, app: &App, hunk: &crate::git::ConflictHunk) {
+    let mut text = Vec::new();
+    
+    if let Some(resolution) = app.get_current_resolution() {
+        text.push(Line::from(vec![
+            Span::styled("current resolution: ", Style::default().fg(Color::Gray)),
+            match resolution {
+                crate::git::MergeResolution::KeepOurs => 
+                    Span::styled("KEEP OURS", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+                crate::git::MergeResolution::KeepTheirs => 
+                    Span::styled("KEEP THEIRS", Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD)),
+                crate::git::MergeResolution::KeepBoth => 
+                    Span::styled("KEEP BOTH", Style::default().fg(Color::Yellow).add_modifier(Modifier::BOLD)),
+                crate::git::MergeResolution::Delete =>
+                    Span::styled("DELETE FILE", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+                crate::git::MergeResolution::OursFile =>
+                    Span::styled("OUR FILE (index stage 2)", Style::default().fg(Color::Green).add_modifier(Modifier::BOLD)),
+                crate::git::MergeResolution::TheirsFile =>
+                    Span::styled("THEIR FILE (index stage 3)", Style::default().fg(Color::Blue).add_modifier(Modifier::BOLD)),
+                crate::git::MergeResolution::Custom(_) => 
+                    Span::styled("CUSTOM", Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD)),
+            }
+        ]));
+        
+        text.push(Line::from(""));
+        text.push(Line::from(vec![
+            Span::styled("Preview:", Style::default().fg(Color::Gray)),
+        ]));
+        
+        let preview = hunk.resolve(resolution);
+        for line in preview.lines().take(5) { // Show first 5 lines of preview
+            text.push(Line::from(Span::styled(line.to_string(), Style::default().fg(Color::White))));
+        }
+        
+        if preview.lines().count() > 5 {
+            text.push(Line::from(Span::styled("... (truncated)", Style::default().fg(Color::Gray))));
+        }
+    } else {
+        text.push(Line::from(vec![
+            Span::styled("UNRESOLVED", Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)),
+        ]));
+        text.push(Line::from(""));
+        text.push(Line::from("Choose resolution:"));
+        text.push(Line::from("• 'o' - keep our version (green)"));
+        text.push(Line::from("• 't' - keep their version (blue)"));  
+        if app.get_current_conflict_file().is_some_and(|f| f.kind != crate::git::ConflictKind::Text) {
+            text.push(Line::from("• 'd' - delete the file"));
+        } else {
+            text.push(Line::from("• 'b' - keep both versions"));
+            text.push(Line::from("• 'e' - edit custom resolution"));
+        }
+        text.push(Line::from("• '[' / ']' - our / their whole file from the index"));
+    }
+
+    let resolution_panel = Paragraph::new(text)
+        .block(Block::default().borders(Borders::ALL).title("resolution"))
+        .style(Style::default().fg(Color::White))
+        .wrap(Wrap { trim: true });
+
+    f.render_widget(resolution_panel, area);
 }
\ No newline at end of file
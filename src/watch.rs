@@ -0,0 +1,99 @@
+use crate::git::{RepoSnapshot, RepoStatus, Repository};
+use anyhow::Result;
+use chrono::Utc;
+use crossterm::{cursor::MoveTo, execute, terminal::{Clear, ClearType}};
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+// `git-hydra watch`: a few lines of status for a small tmux pane next to the editor, no keys,
+// ctrl-c to stop. there's no file watching crate in here, so it polls: HEAD and the index every
+// quarter second (a commit, checkout or `git add` shows up right away), the worktree with a full
+// status every --interval. the screen is only drawn again when what it says changed.
+
+const TICK: Duration = Duration::from_millis(250);
+
+pub fn run(repo: &Repository, interval: Duration) -> Result<()> {
+    let mut stdout = io::stdout();
+    let mut snapshot = RepoSnapshot::default();
+    let mut checked: Option<Instant> = None;
+    let mut lines = Vec::new();
+    let mut shown: Option<(Vec<String>, (u16, u16))> = None;
+    loop {
+        let now = repo.snapshot(&snapshot);
+        if now != snapshot || checked.is_none_or(|at| at.elapsed() >= interval) {
+            snapshot = now;
+            checked = Some(Instant::now());
+            lines = summary(repo);
+        }
+        // a resized pane is drawn again too
+        let size = crossterm::terminal::size().unwrap_or((80, 24));
+        if shown.as_ref().is_none_or(|(was, at)| *was != lines || *at != size) {
+            execute!(stdout, Clear(ClearType::All), MoveTo(0, 0))?;
+            for line in lines.iter().take(size.1 as usize) {
+                writeln!(stdout, "{}", fit(line, size.0 as usize))?;
+            }
+            stdout.flush()?;
+            shown = Some((lines.clone(), size));
+        }
+        std::thread::sleep(TICK);
+    }
+}
+
+// what the pane shows, an error in place of it when the repo can't be read right now (a rebase
+// halfway through rewriting the index, say)
+pub fn summary(repo: &Repository) -> Vec<String> {
+    match repo.status() {
+        Ok(status) => {
+            let conflicts = repo.index_conflicts().map(|c| c.len()).unwrap_or(0);
+            let mut lines = vec![branch_line(&status), counts_line(&status, conflicts)];
+            match repo.get_commits(1).ok().and_then(|c| c.into_iter().next()) {
+                Some(commit) => lines.push(format!(
+                    "{} {} ({})",
+                    repo.short_id(&commit.id),
+                    commit.message.lines().next().unwrap_or(""),
+                    crate::ui::relative_date(commit.timestamp, Utc::now())
+                )),
+                None => lines.push("no commits yet".to_string()),
+            }
+            lines
+        }
+        Err(e) => vec![format!("can't read the status: {:#}", e)],
+    }
+}
+
+fn branch_line(status: &RepoStatus) -> String {
+    let mut line = status.branch.clone();
+    if let Some(upstream) = &status.upstream {
+        line.push_str(&format!(" → {}", upstream));
+        if status.ahead > 0 {
+            line.push_str(&format!(" ↑{}", status.ahead));
+        }
+        if status.behind > 0 {
+            line.push_str(&format!(" ↓{}", status.behind));
+        }
+        if status.ahead == 0 && status.behind == 0 {
+            line.push_str(" up to date");
+        }
+    }
+    line
+}
+
+fn counts_line(status: &RepoStatus, conflicts: usize) -> String {
+    let mut parts = Vec::new();
+    for (n, what) in [(conflicts, "conflicted"), (status.staged.len(), "staged"), (status.unstaged.len(), "unstaged"), (status.untracked.len(), "untracked")] {
+        if n > 0 {
+            parts.push(format!("{} {}", n, what));
+        }
+    }
+    if parts.is_empty() { "clean".to_string() } else { parts.join(", ") }
+}
+
+// cut to the pane's width, a … where it was cut
+fn fit(line: &str, width: usize) -> String {
+    if line.chars().count() <= width || width == 0 {
+        return line.to_string();
+    }
+    let mut cut: String = line.chars().take(width - 1).collect();
+    cut.push('…');
+    cut
+}